@@ -1,10 +1,10 @@
 //! A minimal construct action dispatch test, for debugging.
 
-use bevy::{diagnostic::LogDiagnosticsPlugin, prelude::*};
+use bevy::{diagnostic::LogDiagnosticsPlugin, prelude::*, reflect::TypeRegistry};
 use loot_and_roam::{common::construct::install::install_part_on_construct, prelude::*};
 
 // Spawn a construct with 3 debug parts
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, registry: Res<AppTypeRegistry>) {
     let construct = commands.spawn(Name::new("TestConstruct")).id();
 
     // Add slots
@@ -29,17 +29,18 @@ fn setup(mut commands: Commands) {
         install_part_on_construct(&mut commands, part3, construct);
     }
 
-    send_debug_action(construct, &mut commands);
+    send_debug_action(construct, &mut commands, &registry.read());
 }
 
 // Trigger action - will only b sent to parts with "debug" tag
-fn send_debug_action(construct: Entity, commands: &mut Commands) {
+fn send_debug_action(construct: Entity, commands: &mut Commands, registry: &TypeRegistry) {
     dispatch_action(
         commands,
         construct,
         "DEBUG".into(),
         vec!["debug".into()],
-        Box::new(DebugPrintPart::with_message("Hello parts!")),
+        PartActionData::reflect(&DebugPrintPart::with_message("Hello parts!"), registry)
+            .expect("DebugPrintPart is registered"),
     );
 }
 
@@ -24,19 +24,12 @@ use std::f32::consts::SQRT_2;
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    math::FloatOrd,
     prelude::*,
-    render::{
-        RenderPlugin,
-        camera::{ImageRenderTarget, RenderTarget},
-        render_resource::{
-            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-        },
-    },
+    render::RenderPlugin,
     window::PresentMode,
 };
-use bevy_image_export::{ImageExport, ImageExportPlugin, ImageExportSettings, ImageExportSource};
 use derive_builder::Builder;
+use loot_and_roam::app::renderer::capture::{CaptureEvent, CaptureOutput, CaptureSettings};
 use loot_and_roam::prelude::*;
 use rand::distr::Uniform;
 
@@ -163,7 +156,7 @@ fn watchtower_request_spit_system(
             entity,
             "spit".into(),
             vec!["spitter".into()],
-            Box::from(()),
+            PartActionData::Empty,
         );
     }
 }
@@ -322,42 +315,7 @@ fn spawn_watchtower(
     commands.run_system_cached_with(_watchtower_part_printer, watchtower);
 }
 
-/// Point netowrk snapping market component.
-#[derive(Component, Default)]
-struct SnapToPointNet;
-
 fn apply_example_systems(app: &mut App) {
-    // Center cube on the average of its physics points, and orient it into the
-    // point as a sort of cage.
-    app.add_systems(
-        Update,
-        |mut query: Query<(&mut Transform, &PointNetwork), With<SnapToPointNet>>| {
-            for (mut transform, network) in query.iter_mut() {
-                if !network.points.is_empty() {
-                    let len = network.points.len() as f32;
-                    let avg: Vec3 = network
-                        .points
-                        .iter()
-                        .map(|point| point.pos)
-                        .fold(Vec3::ZERO, |acc, pos| acc + pos);
-                    let avg = avg / len;
-
-                    // since the first 8 vertices are all cube corner vertices,
-                    // we can assume that they're orthogonal enough that any
-                    // arbitrary pick within these bounds will allow for
-                    // sufficient reorientation of the snapped cube mesh.
-
-                    let front = network.points[0].pos;
-                    let up = network.points[2].pos;
-                    let up = (up - avg).normalize();
-
-                    transform.translation = avg;
-                    transform.look_at(front, up);
-                }
-            }
-        },
-    );
-
     app.add_systems(Startup, setup);
 
     app.add_systems(
@@ -372,7 +330,7 @@ fn apply_example_systems(app: &mut App) {
     app.add_observer(obs_spitter_spit_action);
 }
 
-// Resolution for exporting demo images.
+// Resolution of the window, and so of captured demo frames.
 const WIDTH: u32 = 1280;
 const HEIGHT: u32 = 720;
 
@@ -381,36 +339,8 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
-    export_sources: Option<ResMut<Assets<ImageExportSource>>>,
+    mut capture: EventWriter<CaptureEvent>,
 ) {
-    // output texture for image sequence rendering
-    let output_texture_handle = {
-        let size = Extent3d {
-            width: WIDTH,
-            height: HEIGHT,
-            ..default()
-        };
-        let mut export_texture = Image {
-            texture_descriptor: TextureDescriptor {
-                label: None,
-                size,
-                dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8UnormSrgb,
-                mip_level_count: 1,
-                sample_count: 1,
-                usage: TextureUsages::COPY_DST
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            },
-            ..default()
-        };
-        export_texture.resize(size);
-
-        images.add(export_texture)
-    };
-
     // circular base
     commands.spawn((
         Mesh3d(meshes.add(Circle::new(4.0))),
@@ -434,22 +364,12 @@ fn setup(
     ));
 
     // camera
-    commands
+    let camera = commands
         .spawn((
             Camera3d::default(),
             Transform::from_xyz(-5.0, 9.0, 18.0).looking_at(Vec3::Y * -0.5, Vec3::Y),
         ))
-        .with_child((
-            Camera3d::default(),
-            Camera {
-                // Connect the output texture to a camera as a RenderTarget.
-                target: RenderTarget::Image(ImageRenderTarget {
-                    handle: output_texture_handle.clone(),
-                    scale_factor: FloatOrd(1.0),
-                }),
-                ..default()
-            },
-        ));
+        .id();
 
     // watchtower
     let request = WatchtowerSpawnRequest {
@@ -463,19 +383,16 @@ fn setup(
     };
     spawn_watchtower(request, &mut commands, &mut meshes, &mut materials);
 
-    // start image exportation
-    if let Some(mut export_sources) = export_sources {
-        commands.spawn((
-            ImageExport(export_sources.add(ImageExportSource(output_texture_handle.clone()))),
-            ImageExportSettings {
-                // Frames will be saved to "./out/construct-test-watchtower/[#####].png"
-                // [NOTE] update output dir when grafting this code onto other examples
-                output_dir: "out/construct-test-watchtower/".into(),
-
-                // Choose "exr" for HDR renders (requires feature on crate bevy_image_export)
-                extension: "png".into(),
+    // start capturing, in release builds
+    if cfg!(not(debug_assertions)) {
+        capture.write(CaptureEvent::Start(CaptureSettings {
+            camera,
+            // Frames will be saved to "./out/construct-test-watchtower/[#####].png"
+            output: CaptureOutput::ImageSequence {
+                directory: "out/construct-test-watchtower".into(),
             },
-        ));
+            frame_rate: Some(60.0),
+        }));
     }
 }
 
@@ -495,8 +412,12 @@ struct CubeBundle<M: Material> {
     #[builder(default)]
     gravity: Gravity,
 
-    #[builder(setter(skip), default)]
-    snap_to_points: SnapToPointNet,
+    // The first 8 points are the cube's corners, so any two of them orient it.
+    #[builder(
+        setter(skip),
+        default = "PointNetTransformSync::reference_points(0, 2)"
+    )]
+    snap_to_points: PointNetTransformSync,
 
     mesh: Mesh3d,
     material: MeshMaterial3d<M>,
@@ -609,8 +530,6 @@ fn spawn_cube(
 fn main() {
     let mut app = App::new();
 
-    // image export
-
     // default plugin & main properties
     app.add_plugins((DefaultPlugins
         .set(WindowPlugin {
@@ -618,6 +537,7 @@ fn main() {
                 title: "Loot & Roam Tech Demo - Watchtower".into(),
                 name: Some("bevy.loot-and-roam.techdemo.watchtower".into()),
                 present_mode: PresentMode::AutoNoVsync,
+                resolution: (WIDTH as f32, HEIGHT as f32).into(),
                 ..default()
             }),
             ..default()
@@ -627,21 +547,6 @@ fn main() {
             ..default()
         }),));
 
-    let export_plugin = if cfg!(not(debug_assertions)) {
-        Some(ImageExportPlugin::default())
-    } else {
-        None
-    };
-
-    let export_threads = if let Some(export_plugin) = export_plugin {
-        let threads = Some(export_plugin.threads.clone());
-        app.add_plugins(export_plugin);
-
-        threads
-    } else {
-        None
-    };
-
     // engine systems
     app.add_plugins((
         CommonPlugin,
@@ -657,11 +562,6 @@ fn main() {
 
     app.run();
 
-    // block till image sequence exportation is done
-    if let Some(export_threads) = export_threads {
-        export_threads.finish();
-    }
-
     // command to render to video:
     // $ ffmpeg -r 60 -i out/construct-test-watchtower/%05d.png -vcodec libx264 -crf 25 -pix_fmt yuv420p out/construct-test-watchtower.mp4
     // command to reset demo recordings:
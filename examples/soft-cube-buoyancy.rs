@@ -22,64 +22,24 @@ use std::f32::consts::SQRT_2;
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    math::FloatOrd,
     prelude::*,
-    render::{
-        RenderPlugin,
-        camera::{ImageRenderTarget, RenderTarget},
-        render_resource::{
-            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-        },
-    },
+    render::RenderPlugin,
     window::PresentMode,
 };
-use bevy_image_export::{ImageExport, ImageExportPlugin, ImageExportSettings, ImageExportSource};
 use derive_builder::Builder;
 use loot_and_roam::{
-    app::renderer::object::ObjectRendererPlugin,
+    app::renderer::{
+        capture::{CaptureEvent, CaptureOutput, CapturePlugin, CaptureSettings},
+        object::{ObjectRendererPlugin, sync::PointNetTransformSync},
+    },
     common::physics::{prelude::*, volume::VolumeCloneSpawner, water::WaterPhysics},
 };
 
-/// Point netowrk snapping market component.
-#[derive(Component, Default)]
-struct SnapToPointNet;
-
 fn apply_example_systems(app: &mut App) {
-    // Center cube on the average of its physics points, and orient it into the
-    // point as a sort of cage.
-    app.add_systems(
-        Update,
-        |mut query: Query<(&mut Transform, &PointNetwork), With<SnapToPointNet>>| {
-            for (mut transform, network) in query.iter_mut() {
-                if !network.points.is_empty() {
-                    let len = network.points.len() as f32;
-                    let avg: Vec3 = network
-                        .points
-                        .iter()
-                        .map(|point| point.pos)
-                        .fold(Vec3::ZERO, |acc, pos| acc + pos);
-                    let avg = avg / len;
-
-                    // since the first 8 vertices are all cube corner vertices,
-                    // we can assume that they're orthogonal enough that any
-                    // arbitrary pick within these bounds will allow for
-                    // sufficient reorientation of the snapped cube mesh.
-
-                    let front = network.points[0].pos;
-                    let up = network.points[2].pos;
-                    let up = (up - avg).normalize();
-
-                    transform.translation = avg;
-                    transform.look_at(front, up);
-                }
-            }
-        },
-    );
-
     app.add_systems(Startup, setup);
 }
 
-// Resolution for exporting demo images.
+// Resolution of the window, and so of captured demo frames.
 const WIDTH: u32 = 1280;
 const HEIGHT: u32 = 720;
 
@@ -88,36 +48,8 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
-    export_sources: Option<ResMut<Assets<ImageExportSource>>>,
+    mut capture: EventWriter<CaptureEvent>,
 ) {
-    // output texture for image sequence rendering
-    let output_texture_handle = {
-        let size = Extent3d {
-            width: WIDTH,
-            height: HEIGHT,
-            ..default()
-        };
-        let mut export_texture = Image {
-            texture_descriptor: TextureDescriptor {
-                label: None,
-                size,
-                dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8UnormSrgb,
-                mip_level_count: 1,
-                sample_count: 1,
-                usage: TextureUsages::COPY_DST
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            },
-            ..default()
-        };
-        export_texture.resize(size);
-
-        images.add(export_texture)
-    };
-
     // circular base
     commands.spawn((
         Mesh3d(meshes.add(Circle::new(4.0))),
@@ -140,22 +72,12 @@ fn setup(
     ));
 
     // camera
-    commands
+    let camera = commands
         .spawn((
             Camera3d::default(),
             Transform::from_xyz(-5.0, 9.0, 18.0).looking_at(Vec3::Y * -0.5, Vec3::Y),
         ))
-        .with_child((
-            Camera3d::default(),
-            Camera {
-                // Connect the output texture to a camera as a RenderTarget.
-                target: RenderTarget::Image(ImageRenderTarget {
-                    handle: output_texture_handle.clone(),
-                    scale_factor: FloatOrd(1.0),
-                }),
-                ..default()
-            },
-        ));
+        .id();
 
     // cubes
     for at in [
@@ -181,19 +103,16 @@ fn setup(
         );
     }
 
-    // start image exportation
-    if let Some(mut export_sources) = export_sources {
-        commands.spawn((
-            ImageExport(export_sources.add(ImageExportSource(output_texture_handle.clone()))),
-            ImageExportSettings {
-                // Frames will be saved to "./out/soft-cube-buoyancy/[#####].png"
-                // [NOTE] update output dir when grafting this code onto other examples
-                output_dir: "out/soft-cube-buoyancy/".into(),
-
-                // Choose "exr" for HDR renders (requires feature on crate bevy_image_export)
-                extension: "png".into(),
+    // start capturing, in release builds
+    if cfg!(not(debug_assertions)) {
+        capture.write(CaptureEvent::Start(CaptureSettings {
+            camera,
+            // Frames will be saved to "./out/soft-cube-buoyancy/[#####].png"
+            output: CaptureOutput::ImageSequence {
+                directory: "out/soft-cube-buoyancy".into(),
             },
-        ));
+            frame_rate: Some(60.0),
+        }));
     }
 }
 
@@ -213,8 +132,12 @@ struct CubeBundle<M: Material> {
     #[builder(default)]
     gravity: Gravity,
 
-    #[builder(setter(skip), default)]
-    snap_to_points: SnapToPointNet,
+    // The first 8 points are the cube's corners, so any two of them orient it.
+    #[builder(
+        setter(skip),
+        default = "PointNetTransformSync::reference_points(0, 2)"
+    )]
+    snap_to_points: PointNetTransformSync,
 
     mesh: Mesh3d,
     material: MeshMaterial3d<M>,
@@ -345,8 +268,6 @@ fn spawn_cube(
 fn main() {
     let mut app = App::new();
 
-    // image export
-
     // default plugin & main properties
     app.add_plugins((DefaultPlugins
         .set(WindowPlugin {
@@ -354,6 +275,7 @@ fn main() {
                 title: "Loot & Roam Tech Demo - Soft Body Cube".into(),
                 name: Some("bevy.loot-and-roam.techdemo.softbody".into()),
                 present_mode: PresentMode::AutoNoVsync,
+                resolution: (WIDTH as f32, HEIGHT as f32).into(),
                 ..default()
             }),
             ..default()
@@ -363,27 +285,13 @@ fn main() {
             ..default()
         }),));
 
-    let export_plugin = if cfg!(not(debug_assertions)) {
-        Some(ImageExportPlugin::default())
-    } else {
-        None
-    };
-
-    let export_threads = if let Some(export_plugin) = export_plugin {
-        let threads = Some(export_plugin.threads.clone());
-        app.add_plugins(export_plugin);
-
-        threads
-    } else {
-        None
-    };
-
     // engine systems
     app.add_plugins((
         FrameTimeDiagnosticsPlugin::default(),
-        BasicPhysicsPlugin,
+        BasicPhysicsPlugin::default(),
         CollisionPlugin,
         ObjectRendererPlugin,
+        CapturePlugin,
     ));
 
     // system registration
@@ -394,11 +302,6 @@ fn main() {
 
     app.run();
 
-    // block till image sequence exportation is done
-    if let Some(export_threads) = export_threads {
-        export_threads.finish();
-    }
-
     // command to render to video:
     // $ ffmpeg -r 60 -i out/soft-cube-buoyancy/%05d.png -vcodec libx264 -crf 25 -pix_fmt yuv420p out/soft-cube-buoyancy.mp4
     // command to reset demo recordings:
@@ -24,71 +24,23 @@ use std::f32::consts::{SQRT_2, TAU};
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    math::FloatOrd,
     prelude::*,
-    render::{
-        RenderPlugin,
-        camera::{ImageRenderTarget, RenderTarget},
-        render_resource::{
-            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-        },
-    },
+    render::RenderPlugin,
     window::PresentMode,
 };
-use bevy_image_export::{ImageExport, ImageExportPlugin, ImageExportSettings, ImageExportSource};
 use loot_and_roam::{
-    app::renderer::object::ObjectRendererPlugin,
+    app::renderer::{
+        capture::{CaptureEvent, CaptureOutput, CapturePlugin, CaptureSettings},
+        object::{ObjectRendererPlugin, sync::PointNetTransformSync},
+    },
     common::physics::{prelude::*, volume::VolumeCloneSpawner},
 };
 
-/// Point netowrk snapping market component.
-#[derive(Component)]
-struct SnapToPointNet;
-
 fn apply_example_systems(app: &mut App) {
-    // Center cube on the average of its physics points, and orient it into the
-    // point as a sort of cage.
-    app.add_systems(
-        Update,
-        |mut query: Query<(&mut Transform, &PointNetwork), With<SnapToPointNet>>| {
-            for (mut transform, network) in query.iter_mut() {
-                if !network.points.is_empty() {
-                    let len = network.points.len() as f32;
-                    let avg: Vec3 = network
-                        .points
-                        .iter()
-                        .map(|point| point.pos)
-                        .fold(Vec3::ZERO, |acc, pos| acc + pos);
-                    let avg = avg / len;
-
-                    // since the first 8 vertices are all cube corner vertices,
-                    // we can assume that they're orthogonal enough that any
-                    // arbitrary pick within these bounds will allow for
-                    // sufficient reorientation of the snapped cube mesh.
-
-                    let front = network.points[0].pos;
-                    let up = network.points[2].pos;
-                    let up = (up - avg).normalize();
-
-                    transform.translation = avg;
-                    transform.look_at(front, up);
-
-                    // the cube is facing the 'front' vertex now; we need to
-                    // rotate it slightly so it aligns corner-wise rather than
-                    // face-wise. (so it... "corners" the vertex? badum-tss!)
-                    transform.rotate_local_x(TAU * 0.125);
-                    transform.rotate_local_y(TAU * 0.125);
-                } else {
-                    panic!("Tried to reflect empty PointNetwork onto a Transform!");
-                }
-            }
-        },
-    );
-
     app.add_systems(Startup, setup);
 }
 
-// Resolution for exporting demo images.
+// Resolution of the window, and so of captured demo frames.
 const WIDTH: u32 = 1280;
 const HEIGHT: u32 = 720;
 
@@ -97,36 +49,8 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
-    mut export_sources: ResMut<Assets<ImageExportSource>>,
+    mut capture: EventWriter<CaptureEvent>,
 ) {
-    // output texture for image sequence rendering
-    let output_texture_handle = {
-        let size = Extent3d {
-            width: WIDTH,
-            height: HEIGHT,
-            ..default()
-        };
-        let mut export_texture = Image {
-            texture_descriptor: TextureDescriptor {
-                label: None,
-                size,
-                dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8UnormSrgb,
-                mip_level_count: 1,
-                sample_count: 1,
-                usage: TextureUsages::COPY_DST
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            },
-            ..default()
-        };
-        export_texture.resize(size);
-
-        images.add(export_texture)
-    };
-
     // circular base
     commands.spawn((
         Mesh3d(meshes.add(Circle::new(4.0))),
@@ -149,22 +73,12 @@ fn setup(
     ));
 
     // camera
-    commands
+    let camera = commands
         .spawn((
             Camera3d::default(),
             Transform::from_xyz(-5.0, 9.0, 18.0).looking_at(Vec3::Y * 0.5, Vec3::Y),
         ))
-        .with_child((
-            Camera3d::default(),
-            Camera {
-                // Connect the output texture to a camera as a RenderTarget.
-                target: RenderTarget::Image(ImageRenderTarget {
-                    handle: output_texture_handle.clone(),
-                    scale_factor: FloatOrd(1.0),
-                }),
-                ..default()
-            },
-        ));
+        .id();
 
     // cubes
     for at in [
@@ -190,18 +104,15 @@ fn setup(
         );
     }
 
-    // start image exportation
-    commands.spawn((
-        ImageExport(export_sources.add(ImageExportSource(output_texture_handle.clone()))),
-        ImageExportSettings {
-            // Frames will be saved to "./out/soft-cube-collision/[#####].png"
-            // [NOTE] update output dir when grafting this code onto other examples
-            output_dir: "out/soft-cube-collision/".into(),
-
-            // Choose "exr" for HDR renders (requires feature on crate bevy_image_export)
-            extension: "png".into(),
+    // start capturing
+    capture.write(CaptureEvent::Start(CaptureSettings {
+        camera,
+        // Frames will be saved to "./out/soft-cube-collision/[#####].png"
+        output: CaptureOutput::ImageSequence {
+            directory: "out/soft-cube-collision".into(),
         },
-    ));
+        frame_rate: Some(60.0),
+    }));
 }
 
 fn spawn_cube(
@@ -306,7 +217,12 @@ fn spawn_cube(
                 // low grav for development purposes
                 force: Vec3::Y * -3.0,
             },
-            SnapToPointNet,
+            // The first 8 points are the cube's corners, so any two of them
+            // orient it; it is then turned so it aligns corner-wise rather
+            // than face-wise.
+            PointNetTransformSync::reference_points(0, 2).with_rotation_offset(
+                Quat::from_rotation_x(TAU * 0.125) * Quat::from_rotation_y(TAU * 0.125),
+            ),
             //CameraFocus::default(),
         ))
         .id();
@@ -319,18 +235,15 @@ fn spawn_cube(
 fn main() {
     let mut app = App::new();
 
-    // image export
-    let export_plugin = ImageExportPlugin::default();
-    let export_threads = export_plugin.threads.clone();
-
     // default plugin & main properties
-    app.add_plugins((
+    app.add_plugins(
         DefaultPlugins
             .set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "Loot & Roam Tech Demo - Soft Body Cube".into(),
                     name: Some("bevy.loot-and-roam.techdemo.softbody".into()),
                     present_mode: PresentMode::AutoNoVsync,
+                    resolution: (WIDTH as f32, HEIGHT as f32).into(),
                     ..default()
                 }),
                 ..default()
@@ -339,15 +252,15 @@ fn main() {
                 synchronous_pipeline_compilation: true,
                 ..default()
             }),
-        export_plugin,
-    ));
+    );
 
     // engine systems
     app.add_plugins((
         FrameTimeDiagnosticsPlugin::default(),
-        BasicPhysicsPlugin,
+        BasicPhysicsPlugin::default(),
         CollisionPlugin,
         ObjectRendererPlugin,
+        CapturePlugin,
     ));
 
     // system registration
@@ -358,9 +271,6 @@ fn main() {
 
     app.run();
 
-    // block till image sequence exportation is done
-    export_threads.finish();
-
     // command to render to video:
     // $ ffmpeg -r 60 -i out/soft-cube-collision/%05d.png -vcodec libx264 -crf 25 -pix_fmt yuv420p out/soft-cube-collision.mp4
     // command to reset demo recordings:
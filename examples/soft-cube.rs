@@ -32,50 +32,7 @@ use loot_and_roam::{
     common::{CommonPlugin, prelude::*},
 };
 
-/// Point netowrk snapping market component.
-#[derive(Component)]
-pub struct SnapToPointNet;
-
 pub fn apply_example_systems(app: &mut App) {
-    // Center cube on the average of its physics points, and orient it into the
-    // point as a sort of cage.
-    app.add_systems(
-        Update,
-        |mut query: Query<(&mut Transform, &PointNetwork), With<SnapToPointNet>>| {
-            for (mut transform, network) in query.iter_mut() {
-                if !network.points.is_empty() {
-                    let len = network.points.len() as f32;
-                    let avg: Vec3 = network
-                        .points
-                        .iter()
-                        .map(|point| point.pos)
-                        .fold(Vec3::ZERO, |acc, pos| acc + pos);
-                    let avg = avg / len;
-
-                    // since the first 8 vertices are all cube corner vertices,
-                    // we can assume that they're orthogonal enough that any
-                    // arbitrary pick within these bounds will allow for
-                    // sufficient reorientation of the snapped cube mesh.
-
-                    let front = network.points[0].pos;
-                    let up = network.points[2].pos;
-                    let up = (up - avg).normalize();
-
-                    transform.translation = avg;
-                    transform.look_at(front, up);
-
-                    // the cube is facing the 'front' vertex now; we need to
-                    // rotate it slightly so it aligns corner-wise rather than
-                    // face-wise. (so it... "corners" the vertex? badum-tss!)
-                    transform.rotate_local_x(TAU * 0.125);
-                    transform.rotate_local_y(TAU * 0.125);
-                } else {
-                    panic!("Tried to reflect empty PointNetwork onto a Transform!");
-                }
-            }
-        },
-    );
-
     app.add_systems(Startup, setup);
 }
 
@@ -189,7 +146,12 @@ pub fn setup(
                 // low grav for development purposes
                 force: Vec3::Y * -3.0,
             },
-            SnapToPointNet,
+            // The first 8 points are the cube's corners, so any two of them
+            // orient it; it is then turned so it aligns corner-wise rather
+            // than face-wise.
+            PointNetTransformSync::reference_points(0, 2).with_rotation_offset(
+                Quat::from_rotation_x(TAU * 0.125) * Quat::from_rotation_y(TAU * 0.125),
+            ),
             // CameraFocus::default(),
         ))
         .id();
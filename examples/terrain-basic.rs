@@ -41,6 +41,7 @@ fn generate_terrain() -> TerrainBuffer {
             CenterPoint::new(Vec2::new(40.0, 20.0), 0.6),
         ])
         .resolution(6.0)
+        .erosion(Some(ErosionParams::default()))
         .build()
         .unwrap();
 
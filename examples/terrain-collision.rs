@@ -15,27 +15,16 @@
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    math::FloatOrd,
     prelude::*,
-    render::{
-        RenderPlugin,
-        camera::{ImageRenderTarget, RenderTarget},
-        render_resource::{
-            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
-        },
-    },
+    render::RenderPlugin,
     window::PresentMode,
 };
-use bevy_image_export::{ImageExport, ImageExportPlugin, ImageExportSettings, ImageExportSource};
+use loot_and_roam::app::renderer::capture::{CaptureEvent, CaptureOutput, CaptureSettings};
 use loot_and_roam::app::{AppPlugin, prelude::*};
 use loot_and_roam::common::physics::volume::VolumeCloneSpawner;
 use loot_and_roam::common::prelude::*;
 use loot_and_roam::common::terrain::buffer::TerrainBuffer;
 
-/// Point netowrk snapping market component.
-#[derive(Component)]
-struct SnapToPointNet;
-
 fn generate_terrain() -> TerrainBuffer {
     // initialize terrain generator
     let mut rng = rand::rng();
@@ -64,7 +53,7 @@ fn generate_terrain() -> TerrainBuffer {
     TerrainBuffer::generate(terragen, 0.3, 3.0, 80.0)
 }
 
-// Resolution for exporting demo images.
+// Resolution of the window, and so of captured demo frames.
 const WIDTH: u32 = 1280;
 const HEIGHT: u32 = 720;
 
@@ -72,41 +61,13 @@ fn scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut images: ResMut<Assets<Image>>,
-    export_sources: Option<ResMut<Assets<ImageExportSource>>>,
+    mut capture: EventWriter<CaptureEvent>,
 ) {
-    // output texture for image sequence rendering
-    let output_texture_handle = {
-        let size = Extent3d {
-            width: WIDTH,
-            height: HEIGHT,
-            ..default()
-        };
-        let mut export_texture = Image {
-            texture_descriptor: TextureDescriptor {
-                label: None,
-                size,
-                dimension: TextureDimension::D2,
-                format: TextureFormat::Rgba8UnormSrgb,
-                mip_level_count: 1,
-                sample_count: 1,
-                usage: TextureUsages::COPY_DST
-                    | TextureUsages::COPY_SRC
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            },
-            ..default()
-        };
-        export_texture.resize(size);
-
-        images.add(export_texture)
-    };
-
     // get terrain mesh
     let terrain = generate_terrain();
 
     // spawn camera
-    commands
+    let camera = commands
         .spawn((
             Camera3d::default(),
             DevCamera {
@@ -115,17 +76,7 @@ fn scene(
             },
             Transform::from_xyz(200.0, 110.0, 200.0).looking_at(Vec3::Y * 10.0, Vec3::Y),
         ))
-        .with_child((
-            Camera3d::default(),
-            Camera {
-                // Connect the output texture to a camera as a RenderTarget.
-                target: RenderTarget::Image(ImageRenderTarget {
-                    handle: output_texture_handle.clone(),
-                    scale_factor: FloatOrd(1.0),
-                }),
-                ..default()
-            },
-        ));
+        .id();
 
     // spawn light
     commands.spawn((
@@ -181,19 +132,16 @@ fn scene(
         );
     }
 
-    // start image exportation
-    if let Some(mut export_sources) = export_sources {
-        commands.spawn((
-            ImageExport(export_sources.add(ImageExportSource(output_texture_handle.clone()))),
-            ImageExportSettings {
-                // Frames will be saved to "./out/terrain-collision/[#####].png"
-                // [NOTE] update output dir when grafting this code onto other examples
-                output_dir: "out/terrain-collision/".into(),
-
-                // Choose "exr" for HDR renders (requires feature on crate bevy_image_export)
-                extension: "png".into(),
+    // start capturing, in release builds
+    if cfg!(not(debug_assertions)) {
+        capture.write(CaptureEvent::Start(CaptureSettings {
+            camera,
+            // Frames will be saved to "./out/terrain-collision/[#####].png"
+            output: CaptureOutput::ImageSequence {
+                directory: "out/terrain-collision".into(),
             },
-        ));
+            frame_rate: Some(60.0),
+        }));
     }
 }
 
@@ -298,7 +246,9 @@ fn spawn_cube(
 
                 ..Default::default()
             },
-            SnapToPointNet,
+            // The first 8 points are the cube's corners, so any two of them
+            // orient it.
+            PointNetTransformSync::reference_points(0, 2),
             //CameraFocus::default(),
         ))
         .id();
@@ -308,46 +258,8 @@ fn spawn_cube(
     cube
 }
 
-struct SnapToPointNetPlugin;
-
-impl Plugin for SnapToPointNetPlugin {
-    fn build(&self, app: &mut App) {
-        // Center cube on the average of its physics points, and orient it into the
-        // point as a sort of cage.
-        app.add_systems(
-            Update,
-            |mut query: Query<(&mut Transform, &PointNetwork), With<SnapToPointNet>>| {
-                for (mut transform, network) in query.iter_mut() {
-                    if !network.points.is_empty() {
-                        let len = network.points.len() as f32;
-                        let avg: Vec3 = network
-                            .points
-                            .iter()
-                            .map(|point| point.pos)
-                            .fold(Vec3::ZERO, |acc, pos| acc + pos);
-                        let avg = avg / len;
-
-                        // since the first 8 vertices are all cube corner vertices,
-                        // we can assume that they're orthogonal enough that any
-                        // arbitrary pick within these bounds will allow for
-                        // sufficient reorientation of the snapped cube mesh.
-
-                        let front = network.points[0].pos;
-                        let up = network.points[2].pos;
-                        let up = (up - avg).normalize();
-
-                        transform.translation = avg;
-                        transform.look_at(front, up);
-                    }
-                }
-            },
-        );
-    }
-}
-
 fn apply_example(app: &mut App) {
     app.add_systems(Startup, scene);
-    app.add_plugins((SnapToPointNetPlugin,));
 }
 
 fn main() {
@@ -360,6 +272,7 @@ fn main() {
                 title: "Loot & Roam Tech Demo - Terrain Collision".into(),
                 name: Some("bevy.loot-and-roam.techdemo.terrain-collision".into()),
                 present_mode: PresentMode::AutoNoVsync,
+                resolution: (WIDTH as f32, HEIGHT as f32).into(),
                 ..default()
             }),
             ..default()
@@ -369,21 +282,6 @@ fn main() {
             ..default()
         }),));
 
-    let export_plugin = if cfg!(not(debug_assertions)) {
-        Some(ImageExportPlugin::default())
-    } else {
-        None
-    };
-
-    let export_threads = if let Some(export_plugin) = export_plugin {
-        let threads = Some(export_plugin.threads.clone());
-        app.add_plugins(export_plugin);
-
-        threads
-    } else {
-        None
-    };
-
     apply_example(&mut app);
 
     // engine systems
@@ -398,11 +296,6 @@ fn main() {
 
     app.run();
 
-    // block till image sequence exportation is done
-    if let Some(export_threads) = export_threads {
-        export_threads.finish();
-    }
-
     // command to render to video:
     // $ ffmpeg -r 60 -i out/terrain-collision/%05d.png -vcodec libx264 -crf 25 -pix_fmt yuv420p out/terrain-collision.mp4
     // command to reset demo recordings:
@@ -0,0 +1,365 @@
+//! # Audio
+//!
+//! Plays the [AudioEvent]s emitted by the simulation (see
+//! [sound](crate::common::sound)), and music.
+//!
+//! Every [AudioKind] is played with a [SoundPool] from the [SoundRegistry]:
+//! one of its sounds is picked at random, at a slightly random pitch, so that
+//! repeated sounds do not grate. Sounds coming from somewhere in the world are
+//! quieter the farther they are from the [PlayerCamera], and not played at
+//! all past [MAX_AUDIBLE_DISTANCE].
+//!
+//! Music is played from the playlist of the current [MusicMood], which follows
+//! the app and game states, one track after another, looping around.
+//!
+//! Volumes are taken from the player's
+//! [AudioSettings](super::settings::AudioSettings).
+//!
+//! Only built with the `audio` feature (on by default); without it, nothing
+//! reads the [AudioEvent]s, as in headless instances.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Stereo panning of positional sounds.
+
+use bevy::{audio::Volume, platform::collections::HashMap, prelude::*};
+use rand::Rng;
+
+use super::{
+    camera::PlayerCamera,
+    renderer::ui::event::{UiEvent, UiEventKind},
+    resource::{AssetKind, AssetManifestAppExt},
+    settings::Settings,
+    state::AppState,
+};
+use crate::common::{
+    sound::{AudioEvent, AudioKind},
+    state::GameState,
+};
+
+/// Sounds closer than this to the listener are played at full volume.
+pub const REFERENCE_DISTANCE: f32 = 10.0;
+
+/// Sounds farther than this from the listener are not played.
+pub const MAX_AUDIBLE_DISTANCE: f32 = 400.0;
+
+/// Sounds quieter than this are not played.
+const MIN_VOLUME: f32 = 0.01;
+
+/// The sound files of each kind of sound: their path, without the variation
+/// number and extension, how many variations there are, their volume, and
+/// pitch variation.
+const SOUND_FILES: [(AudioKind, &str, usize, f32, f32); 6] = [
+    (AudioKind::CannonFire, "sounds/cannon_fire", 3, 1.0, 0.1),
+    (AudioKind::Explosion, "sounds/explosion", 2, 1.0, 0.1),
+    (AudioKind::Splash, "sounds/splash", 3, 0.6, 0.15),
+    (AudioKind::Creak, "sounds/creak", 3, 0.8, 0.1),
+    (AudioKind::Pickup, "sounds/pickup", 1, 0.7, 0.05),
+    (AudioKind::UiClick, "sounds/ui_click", 1, 0.5, 0.0),
+];
+
+/// The music tracks of each mood.
+const MUSIC_FILES: [(MusicMood, &[&str]); 4] = [
+    (MusicMood::MainMenu, &["music/main_menu.ogg"]),
+    (MusicMood::Start, &["music/setting_out.ogg"]),
+    (
+        MusicMood::Overworld,
+        &["music/open_sea_1.ogg", "music/open_sea_2.ogg"],
+    ),
+    (MusicMood::Intermission, &["music/harbor.ogg"]),
+];
+
+/// The sounds an [AudioKind] can be played with.
+#[derive(Clone, Debug)]
+pub struct SoundPool {
+    /// The variations of the sound, one of which is picked at random.
+    pub sounds: Vec<Handle<AudioSource>>,
+
+    /// How loud the sounds are played, before any other volume.
+    pub volume: f32,
+
+    /// How much faster or slower, and so higher or lower pitched, sounds may
+    /// be played, as a fraction of their usual speed.
+    pub pitch_variation: f32,
+}
+
+impl SoundPool {
+    pub fn new(sounds: Vec<Handle<AudioSource>>) -> Self {
+        Self {
+            sounds,
+            volume: 1.0,
+            pitch_variation: 0.0,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn with_pitch_variation(mut self, pitch_variation: f32) -> Self {
+        self.pitch_variation = pitch_variation;
+        self
+    }
+
+    /// Picks a sound, and the speed to play it at, at random.
+    pub fn pick(&self, rng: &mut impl Rng) -> Option<(Handle<AudioSource>, f32)> {
+        if self.sounds.is_empty() {
+            return None;
+        }
+
+        let sound = self.sounds[rng.random_range(0..self.sounds.len())].clone();
+        let speed = if self.pitch_variation > 0.0 {
+            1.0 + rng.random_range(-self.pitch_variation..=self.pitch_variation)
+        } else {
+            1.0
+        };
+        Some((sound, speed))
+    }
+}
+
+/// The sound pool of every kind of sound.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SoundRegistry {
+    pools: HashMap<AudioKind, SoundPool>,
+}
+
+impl SoundRegistry {
+    /// Sets the sounds a kind of sound is played with.
+    pub fn insert(&mut self, kind: AudioKind, pool: SoundPool) {
+        self.pools.insert(kind, pool);
+    }
+
+    pub fn get(&self, kind: AudioKind) -> Option<&SoundPool> {
+        self.pools.get(&kind)
+    }
+}
+
+/// Which music fits the current state of the app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MusicMood {
+    MainMenu,
+    Start,
+    Overworld,
+    Intermission,
+}
+
+impl MusicMood {
+    /// The mood of an app and game state, if any music fits them.
+    pub fn of(app_state: &AppState, game_state: &GameState) -> Option<Self> {
+        match (app_state, game_state) {
+            (AppState::MainMenu, _) => Some(Self::MainMenu),
+            (_, GameState::Start) => Some(Self::Start),
+            (_, GameState::Overworld) => Some(Self::Overworld),
+            (_, GameState::Intermission) => Some(Self::Intermission),
+            (_, GameState::None) => None,
+        }
+    }
+}
+
+/// The music tracks played in each mood, in order.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MusicPlaylists(pub HashMap<MusicMood, Vec<Handle<AudioSource>>>);
+
+/// Marks the music track being played.
+#[derive(Component, Clone, Copy, Debug)]
+struct MusicTrack;
+
+/// What music is being played.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+struct MusicState {
+    mood: Option<MusicMood>,
+
+    /// Index of the next track of the mood's playlist.
+    next: usize,
+}
+
+/// How loud a sound is, from 0.0 to 1.0, at a distance from the listener.
+pub fn attenuation(distance: f32) -> f32 {
+    let falloff = REFERENCE_DISTANCE / distance.max(REFERENCE_DISTANCE);
+    let fade = (1.0 - distance / MAX_AUDIBLE_DISTANCE).clamp(0.0, 1.0);
+    falloff * fade
+}
+
+fn load_sounds(
+    asset_server: Res<AssetServer>,
+    mut registry: ResMut<SoundRegistry>,
+    mut playlists: ResMut<MusicPlaylists>,
+) {
+    for (kind, path, variations, volume, pitch_variation) in SOUND_FILES {
+        let sounds = (1..=variations)
+            .map(|variation| asset_server.load(format!("{path}_{variation}.ogg")))
+            .collect();
+        registry.insert(
+            kind,
+            SoundPool::new(sounds)
+                .with_volume(volume)
+                .with_pitch_variation(pitch_variation),
+        );
+    }
+
+    for (mood, tracks) in MUSIC_FILES {
+        playlists.0.insert(
+            mood,
+            tracks
+                .iter()
+                .map(|&track| asset_server.load(track))
+                .collect(),
+        );
+    }
+}
+
+fn ui_click_sounds(mut ui_events: EventReader<UiEvent>, mut sounds: EventWriter<AudioEvent>) {
+    for event in ui_events.read() {
+        if let UiEventKind::Click(_) = event.kind {
+            sounds.write(AudioEvent::ui(AudioKind::UiClick));
+        }
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<AudioEvent>,
+    registry: Res<SoundRegistry>,
+    settings: Res<Settings>,
+    listeners: Query<&GlobalTransform, With<PlayerCamera>>,
+) {
+    let mut rng = rand::rng();
+    let listener = listeners.single().ok().map(GlobalTransform::translation);
+
+    for event in events.read() {
+        let Some(pool) = registry.get(event.kind) else {
+            continue;
+        };
+        let Some((sound, speed)) = pool.pick(&mut rng) else {
+            continue;
+        };
+
+        let distance = match (event.position, listener) {
+            (Some(position), Some(listener)) => position.distance(listener),
+            _ => 0.0,
+        };
+        let volume =
+            event.volume * pool.volume * settings.audio.effects_volume() * attenuation(distance);
+        if volume < MIN_VOLUME {
+            continue;
+        }
+
+        commands.spawn((
+            AudioPlayer(sound),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(volume))
+                .with_speed(speed),
+        ));
+    }
+}
+
+fn play_music(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    game_state: Res<State<GameState>>,
+    playlists: Res<MusicPlaylists>,
+    settings: Res<Settings>,
+    mut music: ResMut<MusicState>,
+    tracks: Query<Entity, With<MusicTrack>>,
+) {
+    let mood = MusicMood::of(app_state.get(), game_state.get());
+    let mut playing = !tracks.is_empty();
+
+    if mood != music.mood {
+        for track in &tracks {
+            commands.entity(track).despawn();
+        }
+        *music = MusicState { mood, next: 0 };
+        playing = false;
+    }
+
+    // Finished tracks despawn themselves, so the next one starts once the
+    // last is gone.
+    if playing {
+        return;
+    }
+    let Some(playlist) = mood.and_then(|mood| playlists.0.get(&mood)) else {
+        return;
+    };
+    if playlist.is_empty() {
+        return;
+    }
+
+    let track = playlist[music.next % playlist.len()].clone();
+    music.next = (music.next + 1) % playlist.len();
+    commands.spawn((
+        MusicTrack,
+        Name::new("Music"),
+        AudioPlayer(track),
+        PlaybackSettings::DESPAWN.with_volume(Volume::Linear(settings.audio.music_volume())),
+    ));
+}
+
+fn apply_music_volume(settings: Res<Settings>, mut sinks: Query<&mut AudioSink, With<MusicTrack>>) {
+    for mut sink in &mut sinks {
+        sink.set_volume(Volume::Linear(settings.audio.music_volume()));
+    }
+}
+
+/// Audio playback plugin.
+///
+/// Included in [AppPlugin](super::AppPlugin).
+pub struct AudioPlaybackPlugin;
+
+impl Plugin for AudioPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AudioEvent>();
+        app.init_resource::<SoundRegistry>();
+        app.init_resource::<MusicPlaylists>();
+        app.init_resource::<MusicState>();
+        app.add_systems(Startup, load_sounds);
+
+        // Sounds are not required; the game is merely quieter without them.
+        for (_, path, variations, _, _) in SOUND_FILES {
+            for variation in 1..=variations {
+                app.preload_asset::<AudioSource>(
+                    AssetKind::Sound,
+                    format!("{path}_{variation}.ogg"),
+                );
+            }
+        }
+        for (_, tracks) in MUSIC_FILES {
+            for &track in tracks {
+                app.preload_asset::<AudioSource>(AssetKind::Sound, track);
+            }
+        }
+        app.add_systems(
+            Update,
+            (
+                (ui_click_sounds, play_sounds).chain(),
+                play_music,
+                apply_music_volume.run_if(resource_changed::<Settings>),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn sounds_fade_with_distance() {
+        assert_eq!(attenuation(0.0), attenuation(REFERENCE_DISTANCE * 0.5));
+        assert!(attenuation(REFERENCE_DISTANCE * 2.0) < attenuation(REFERENCE_DISTANCE));
+        assert!(attenuation(MAX_AUDIBLE_DISTANCE * 0.5) > 0.0);
+        assert_eq!(attenuation(MAX_AUDIBLE_DISTANCE), 0.0);
+    }
+}
@@ -2,6 +2,9 @@
 //!
 //! Different camera types, such as the [PlayerCamera] and the
 //! [DevCamera].
+//!
+//! The player camera can be zoomed in and out of the ship with the
+//! [InputAction::CameraZoomIn] and [InputAction::CameraZoomOut] actions.
 
 // Written by:
 // * perospirone (https://codeberg.org/perospirone)
@@ -23,13 +26,59 @@ use bevy::{
     window::{CursorGrabMode, PrimaryWindow},
 };
 
+use super::input::{ActionState, InputAction};
+use crate::common::{physics::base::PointNetwork, player::PlayerControlled};
+
+/// How fast the player camera zooms, as a factor per second.
+const ZOOM_RATE: f32 = 2.0;
+
 /// The player camera.
 ///
 /// Cameras with this component will be instructed to follow the local instance
-/// player ship, if one is available and queriable.
+/// player ship, if one is available and queriable (see [CameraFollow]).
 #[derive(Component)]
 pub struct PlayerCamera;
 
+/// How a [PlayerCamera] follows the player's ship.
+///
+/// Optional; cameras without it follow with the default settings.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraFollow {
+    /// Where the camera sits, relative to the ship.
+    pub offset: Vec3,
+
+    /// How quickly the camera catches up with the ship, per second.
+    pub stiffness: f32,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::new(0.0, 30.0, 45.0),
+            stiffness: 4.0,
+        }
+    }
+}
+
+/// How far the player camera is zoomed out, as a factor of its
+/// [CameraFollow] offset.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct CameraZoom {
+    pub factor: f32,
+
+    /// The range the factor is kept within.
+    pub range: (f32, f32),
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            range: (0.5, 2.5),
+        }
+    }
+}
+
 /// Setups the player camera on the world.
 ///
 /// Run whenever an island state is entered.
@@ -39,15 +88,38 @@ pub fn setup_camera(mut commands: Commands) {
     commands.spawn((Camera3d::default(), PlayerCamera));
 }
 
+fn player_camera_zoom(time: Res<Time>, actions: Res<ActionState>, mut zoom: ResMut<CameraZoom>) {
+    let direction = actions.axis(InputAction::CameraZoomOut, InputAction::CameraZoomIn);
+    if direction != 0.0 {
+        let (min, max) = zoom.range;
+        zoom.factor = (zoom.factor * ZOOM_RATE.powf(direction * time.delta_secs())).clamp(min, max);
+    }
+}
+
 fn player_camera_controller(
     time: Res<Time>,
-    mut query: Query<&mut Transform, With<PlayerCamera>>,
+    zoom: Res<CameraZoom>,
+    mut query: Query<(&mut Transform, Option<&CameraFollow>), With<PlayerCamera>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    q_player: Query<&PointNetwork, With<PlayerControlled>>,
 ) {
-    // [TODO] Add ship follow functionality
-    // (PREREQ: ships, player data)
+    // Follow the player's ship, if there is one.
+    if let Ok(ship) = q_player.single() {
+        let target = ship.center_of_mass();
+
+        for (mut transform, follow) in query.iter_mut() {
+            let follow = follow.copied().unwrap_or_default();
+            let eye = target + follow.offset * zoom.factor;
+            let alpha = 1.0 - (-follow.stiffness * time.delta_secs()).exp();
+
+            transform.translation = transform.translation.lerp(eye, alpha);
+            transform.look_at(target, Vec3::Y);
+        }
+
+        return;
+    }
 
-    for mut transform in query.iter_mut() {
+    for (mut transform, _) in query.iter_mut() {
         let mut move_direction = Vec3::ZERO;
         let speed = 5.0;
 
@@ -182,17 +254,27 @@ fn dev_camera_controller(
 ///
 /// Necessary in order to properly use [PlayerCamera] amd [DevCamera].
 ///
-/// Included in [crate::app::AppPlugin].
+/// Included in [crate::app::AppPlugin]. Requires the
+/// [InputActionPlugin](super::input::InputActionPlugin).
 pub struct CameraControlPlugin;
 
 impl Plugin for CameraControlPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (player_camera_controller, dev_camera_controller));
+        app.init_resource::<CameraZoom>();
+        app.add_systems(
+            Update,
+            (
+                (player_camera_zoom, player_camera_controller).chain(),
+                dev_camera_controller,
+            ),
+        );
     }
 }
 
 pub mod prelude {
     pub use super::CameraControlPlugin;
+    pub use super::CameraFollow;
+    pub use super::CameraZoom;
     pub use super::DevCamera;
     pub use super::PlayerCamera;
 }
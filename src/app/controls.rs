@@ -0,0 +1,155 @@
+//! # Player ship controls
+//!
+//! Fills in the [PlayerInput] of the local player's ship from the keyboard
+//! and mouse, through [InputAction]s. By default:
+//!
+//! * W and S throttle up ahead and astern;
+//! * A and D steer to port and starboard;
+//! * the mouse cursor aims, where it points at the water, as seen from the
+//!   [PlayerCamera];
+//! * the left mouse button fires.
+//!
+//! With a gamepad, the left stick steers and throttles, and the right stick
+//! aims, relative to the camera. Aim assist then snaps the aim to the
+//! nearest enemy ship within a cone, if any (see [GamepadSettings]).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::common::{
+    fleet::InFleet,
+    makeup::Ship,
+    physics::{base::PointNetwork, water::WaterPhysics},
+    player::{PlayerControlled, PlayerInput},
+};
+
+use super::{
+    camera::PlayerCamera,
+    input::{ActionState, GamepadSettings, InputAction, InputAxis},
+};
+
+/// Where the cursor points at a horizontal plane, if anywhere.
+fn cursor_aim_point(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    plane_height: f32,
+) -> Option<Vec3> {
+    let cursor = window.cursor_position()?;
+    let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+    let distance = ray.intersect_plane(Vec3::Y * plane_height, InfinitePlane3d::new(Vec3::Y))?;
+
+    Some(ray.get_point(distance))
+}
+
+/// Where a gamepad stick aims, from a ship, relative to the camera.
+fn stick_aim_direction(stick: Vec2, camera_transform: &GlobalTransform) -> Vec3 {
+    let forward = camera_transform
+        .forward()
+        .with_y(0.0)
+        .normalize_or(Vec3::NEG_Z);
+    let right = Vec3::new(-forward.z, 0.0, forward.x);
+
+    (right * stick.x + forward * stick.y).normalize_or_zero()
+}
+
+/// The target closest to an aim direction, among those within a cone around
+/// it, and within range.
+fn aim_assist(
+    origin: Vec3,
+    direction: Vec3,
+    cone: f32,
+    range: f32,
+    targets: impl Iterator<Item = Vec3>,
+) -> Option<Vec3> {
+    targets
+        .filter_map(|target| {
+            let offset = (target - origin).with_y(0.0);
+            let distance = offset.length();
+            if distance == 0.0 || distance > range {
+                return None;
+            }
+
+            let angle = offset.angle_between(direction);
+            (angle <= cone / 2.0).then_some((angle, target))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, target)| target)
+}
+
+fn player_ship_controls(
+    actions: Res<ActionState>,
+    gamepad: Res<GamepadSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    mut players: Query<
+        (&mut PlayerInput, &PointNetwork, Option<&WaterPhysics>),
+        With<PlayerControlled>,
+    >,
+    targets: Query<&PointNetwork, (With<Ship>, Without<PlayerControlled>, Without<InFleet>)>,
+) {
+    let stick = Vec2::new(
+        actions.value(InputAxis::AimX),
+        actions.value(InputAxis::AimY),
+    );
+
+    for (mut input, network, water) in &mut players {
+        input.throttle = actions.value(InputAxis::Throttle);
+        input.rudder = actions.value(InputAxis::Rudder);
+        input.firing = actions.pressed(InputAction::FirePrimary);
+
+        let Ok((camera, camera_transform)) = cameras.single() else {
+            input.aim_point = None;
+            continue;
+        };
+
+        if stick == Vec2::ZERO {
+            let water_level = water.map_or(0.0, |water| water.water_level);
+            input.aim_point = windows
+                .single()
+                .ok()
+                .and_then(|window| cursor_aim_point(window, camera, camera_transform, water_level));
+            continue;
+        }
+
+        let origin = network.center_of_mass();
+        let direction = stick_aim_direction(stick, camera_transform);
+        let assisted = gamepad
+            .aim_assist
+            .then(|| {
+                aim_assist(
+                    origin,
+                    direction,
+                    gamepad.aim_assist_cone,
+                    gamepad.aim_range,
+                    targets.iter().map(PointNetwork::center_of_mass),
+                )
+            })
+            .flatten();
+        input.aim_point = Some(assisted.unwrap_or(origin + direction * gamepad.aim_range));
+    }
+}
+
+/// Player ship controls plugin.
+///
+/// Included in [crate::app::AppPlugin]. Requires the
+/// [InputActionPlugin](super::input::InputActionPlugin).
+pub struct PlayerControlsPlugin;
+
+impl Plugin for PlayerControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, player_ship_controls);
+    }
+}
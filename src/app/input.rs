@@ -0,0 +1,868 @@
+//! # Input actions
+//!
+//! Gameplay code does not read keys and buttons directly; instead, it reads
+//! logical [InputAction]s, such as throttling up or firing. The [InputMap]
+//! resource binds each action to any number of keys, mouse buttons and
+//! gamepad buttons.
+//!
+//! Every frame, the bindings are checked, and the result kept in the
+//! [ActionState] resource. Actions being pressed or released are also sent
+//! as [ActionPressed] and [ActionReleased] events.
+//!
+//! Gamepad sticks feed the analog [InputAxis] values instead: the left stick
+//! steers and throttles, and the right stick aims. How far sticks must be
+//! pushed to count, and how sensitive they are, is set in [GamepadSettings].
+//!
+//! Menus are navigated with the `Menu*` actions, bound to the arrow keys and
+//! the d-pad by default. Holding a direction repeats it, as [MenuNavigate]
+//! events.
+//!
+//! Gameplay and menu actions are in different [InputContext]s, so they may
+//! share keys and buttons, e.g. Escape both pauses and goes back from menus.
+//! Within a context, every key or button is bound to one action at most.
+//!
+//! Bindings can be changed at runtime, either directly through the
+//! [InputMap], or by sending [StartRebind], which binds the next key or
+//! button pressed to an action (Escape cancels), and answers with
+//! [ActionRebound].
+//!
+//! The input map and gamepad settings are part of the player's
+//! [Settings](super::settings::Settings), and saved along with them.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{
+    input::InputSystem,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+/// Something the player can do with a key or button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    /// Throttle up ahead.
+    ThrottleAhead,
+
+    /// Throttle up astern.
+    ThrottleAstern,
+
+    /// Steer to port.
+    SteerPort,
+
+    /// Steer to starboard.
+    SteerStarboard,
+
+    /// Fire at the aim point.
+    FirePrimary,
+
+    /// Bring the camera closer to the ship.
+    CameraZoomIn,
+
+    /// Take the camera farther from the ship.
+    CameraZoomOut,
+
+    /// Cycle through the minimap's zoom levels.
+    MinimapZoom,
+
+    /// Open the ship's inventory.
+    OpenInventory,
+
+    /// Pause the game.
+    Pause,
+
+    /// Move the menu focus up.
+    MenuUp,
+
+    /// Move the menu focus down.
+    MenuDown,
+
+    /// Move the menu focus left.
+    MenuLeft,
+
+    /// Move the menu focus right.
+    MenuRight,
+
+    /// Activate the focused menu element.
+    MenuConfirm,
+
+    /// Go back to the previous menu.
+    MenuBack,
+}
+
+impl InputAction {
+    /// Every action.
+    pub const ALL: [InputAction; 16] = [
+        Self::ThrottleAhead,
+        Self::ThrottleAstern,
+        Self::SteerPort,
+        Self::SteerStarboard,
+        Self::FirePrimary,
+        Self::CameraZoomIn,
+        Self::CameraZoomOut,
+        Self::MinimapZoom,
+        Self::OpenInventory,
+        Self::Pause,
+        Self::MenuUp,
+        Self::MenuDown,
+        Self::MenuLeft,
+        Self::MenuRight,
+        Self::MenuConfirm,
+        Self::MenuBack,
+    ];
+
+    /// The context this action is used in.
+    pub fn context(&self) -> InputContext {
+        match self {
+            Self::MenuUp
+            | Self::MenuDown
+            | Self::MenuLeft
+            | Self::MenuRight
+            | Self::MenuConfirm
+            | Self::MenuBack => InputContext::Menu,
+            _ => InputContext::Gameplay,
+        }
+    }
+}
+
+/// Where an [InputAction] is used.
+///
+/// Actions of different contexts may be bound to the same keys and buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputContext {
+    /// Sailing, fighting, and everything else on the overworld.
+    Gameplay,
+
+    /// Navigating menus.
+    Menu,
+}
+
+/// A continuous input, from -1.0 to 1.0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputAxis {
+    /// Positive ahead, negative astern.
+    Throttle,
+
+    /// Positive to port, negative to starboard.
+    Rudder,
+
+    /// Aim direction, positive to the right of the camera.
+    AimX,
+
+    /// Aim direction, positive away from the camera.
+    AimY,
+}
+
+/// A key or button an action can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+
+    /// A button of any gamepad.
+    Gamepad(GamepadButton),
+}
+
+impl InputBinding {
+    /// Whether this is a gamepad button, rather than a keyboard or mouse
+    /// one.
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, Self::Gamepad(_))
+    }
+}
+
+/// What a binding reads from.
+#[derive(Clone, Copy)]
+struct InputSources<'a, 'w, 's, 'g> {
+    keys: &'a ButtonInput<KeyCode>,
+    mouse_buttons: &'a ButtonInput<MouseButton>,
+    gamepads: &'a Query<'w, 's, &'g Gamepad>,
+}
+
+impl InputSources<'_, '_, '_, '_> {
+    fn pressed(&self, binding: InputBinding) -> bool {
+        match binding {
+            InputBinding::Key(key) => self.keys.pressed(key),
+            InputBinding::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputBinding::Gamepad(button) => {
+                self.gamepads.iter().any(|gamepad| gamepad.pressed(button))
+            }
+        }
+    }
+
+    /// Any key or button pressed this frame.
+    fn just_pressed(&self) -> Option<InputBinding> {
+        let key = self
+            .keys
+            .get_just_pressed()
+            .next()
+            .copied()
+            .map(InputBinding::Key);
+        let mouse = || {
+            self.mouse_buttons
+                .get_just_pressed()
+                .next()
+                .copied()
+                .map(InputBinding::Mouse)
+        };
+        let gamepad = || {
+            self.gamepads.iter().find_map(|gamepad| {
+                gamepad
+                    .get_just_pressed()
+                    .next()
+                    .copied()
+                    .map(InputBinding::Gamepad)
+            })
+        };
+
+        key.or_else(mouse).or_else(gamepad)
+    }
+}
+
+/// Which keys and buttons each action is bound to.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMap {
+    bindings: HashMap<InputAction, Vec<InputBinding>>,
+}
+
+impl InputMap {
+    /// An input map with no bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::default(),
+        }
+    }
+
+    /// The keys and buttons an action is bound to.
+    pub fn bindings(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The actions a key or button is bound to.
+    pub fn actions(&self, binding: InputBinding) -> impl Iterator<Item = InputAction> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bindings)| bindings.contains(&binding))
+            .map(|(&action, _)| action)
+    }
+
+    /// Binds an action to a key or button, besides whatever it is already
+    /// bound to.
+    pub fn bind(&mut self, action: InputAction, binding: InputBinding) -> &mut Self {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Unbinds an action from a key or button.
+    pub fn unbind(&mut self, action: InputAction, binding: InputBinding) -> &mut Self {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|&bound| bound != binding);
+        }
+        self
+    }
+
+    /// Unbinds an action from every key and button.
+    pub fn clear(&mut self, action: InputAction) -> &mut Self {
+        self.bindings.remove(&action);
+        self
+    }
+
+    /// Binds an action to a key or button, in place of its other bindings
+    /// on the same kind of device (gamepad, or keyboard and mouse).
+    ///
+    /// The key or button is taken away from any other action of the same
+    /// [InputContext] it was bound to; those actions are returned.
+    pub fn rebind(&mut self, action: InputAction, binding: InputBinding) -> Vec<InputAction> {
+        let displaced: Vec<_> = self
+            .actions(binding)
+            .filter(|&other| other != action && other.context() == action.context())
+            .collect();
+        for &other in &displaced {
+            self.unbind(other, binding);
+        }
+
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|bound| bound.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+
+        displaced
+    }
+
+    /// Whether any of an action's keys or buttons is held.
+    fn pressed(&self, action: InputAction, sources: InputSources) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|&binding| sources.pressed(binding))
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputAction::*;
+        use InputBinding::*;
+
+        let mut map = Self::empty();
+        map.bind(ThrottleAhead, Key(KeyCode::KeyW))
+            .bind(ThrottleAhead, Gamepad(GamepadButton::RightTrigger2))
+            .bind(ThrottleAstern, Key(KeyCode::KeyS))
+            .bind(ThrottleAstern, Gamepad(GamepadButton::LeftTrigger2))
+            .bind(SteerPort, Key(KeyCode::KeyA))
+            .bind(SteerPort, Gamepad(GamepadButton::DPadLeft))
+            .bind(SteerStarboard, Key(KeyCode::KeyD))
+            .bind(SteerStarboard, Gamepad(GamepadButton::DPadRight))
+            .bind(FirePrimary, Mouse(MouseButton::Left))
+            .bind(FirePrimary, Gamepad(GamepadButton::South))
+            .bind(CameraZoomIn, Key(KeyCode::Equal))
+            .bind(CameraZoomIn, Gamepad(GamepadButton::DPadUp))
+            .bind(CameraZoomOut, Key(KeyCode::Minus))
+            .bind(CameraZoomOut, Gamepad(GamepadButton::DPadDown))
+            .bind(MinimapZoom, Key(KeyCode::KeyM))
+            .bind(MinimapZoom, Gamepad(GamepadButton::Select))
+            .bind(OpenInventory, Key(KeyCode::Tab))
+            .bind(OpenInventory, Gamepad(GamepadButton::North))
+            .bind(Pause, Key(KeyCode::Escape))
+            .bind(Pause, Gamepad(GamepadButton::Start))
+            .bind(MenuUp, Key(KeyCode::ArrowUp))
+            .bind(MenuUp, Gamepad(GamepadButton::DPadUp))
+            .bind(MenuDown, Key(KeyCode::ArrowDown))
+            .bind(MenuDown, Gamepad(GamepadButton::DPadDown))
+            .bind(MenuLeft, Key(KeyCode::ArrowLeft))
+            .bind(MenuLeft, Gamepad(GamepadButton::DPadLeft))
+            .bind(MenuRight, Key(KeyCode::ArrowRight))
+            .bind(MenuRight, Gamepad(GamepadButton::DPadRight))
+            .bind(MenuConfirm, Key(KeyCode::Enter))
+            .bind(MenuConfirm, Gamepad(GamepadButton::South))
+            .bind(MenuBack, Key(KeyCode::Escape))
+            .bind(MenuBack, Gamepad(GamepadButton::East));
+        map
+    }
+}
+
+/// How gamepad sticks are read.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    /// How far a stick must be pushed before it counts, from 0.0 to 1.0.
+    ///
+    /// Sticks rarely rest exactly at the center.
+    pub dead_zone: f32,
+
+    /// Scales the left stick, for steering and throttling.
+    pub move_sensitivity: f32,
+
+    /// Scales the right stick, for aiming.
+    pub aim_sensitivity: f32,
+
+    /// Whether aiming snaps to the nearest target near the aim direction.
+    pub aim_assist: bool,
+
+    /// How wide the cone targets are snapped to within is, in radians.
+    pub aim_assist_cone: f32,
+
+    /// How far away the right stick aims, and targets are snapped to.
+    pub aim_range: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            move_sensitivity: 1.0,
+            aim_sensitivity: 1.0,
+            aim_assist: true,
+            aim_assist_cone: 20f32.to_radians(),
+            aim_range: 60.0,
+        }
+    }
+}
+
+impl GamepadSettings {
+    /// Applies the dead zone and a sensitivity to a stick position.
+    ///
+    /// Past the dead zone, the stick is rescaled to start back from zero,
+    /// so that small motions are not lost.
+    pub fn stick(&self, stick: Vec2, sensitivity: f32) -> Vec2 {
+        let dead_zone = self.dead_zone.clamp(0.0, 0.99);
+        let length = stick.length();
+        if length <= dead_zone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = (length - dead_zone) / (1.0 - dead_zone) * sensitivity;
+        stick / length * scaled.min(1.0)
+    }
+}
+
+/// Which actions are held, and which were pressed or released this frame.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    just_released: HashSet<InputAction>,
+    axes: HashMap<InputAxis, f32>,
+}
+
+impl ActionState {
+    /// Whether an action is held.
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Whether an action was pressed this frame.
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether an action was released this frame.
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// 1.0 if only the positive action is held, -1.0 if only the negative
+    /// one is, and 0.0 otherwise.
+    pub fn axis(&self, positive: InputAction, negative: InputAction) -> f32 {
+        self.pressed(positive) as i8 as f32 - self.pressed(negative) as i8 as f32
+    }
+
+    /// The value of an analog input.
+    ///
+    /// Throttle and rudder also count their actions, as if pushed fully.
+    pub fn value(&self, axis: InputAxis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or_default()
+    }
+}
+
+/// Sent when an action is pressed.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActionPressed(pub InputAction);
+
+/// Sent when an action is released.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActionReleased(pub InputAction);
+
+/// Sent when the menu focus should move, by one element in each direction.
+///
+/// Sent on pressing a `Menu*` direction action, and repeatedly while it is
+/// held.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MenuNavigate(pub IVec2);
+
+/// How long a menu direction must be held before it repeats, in seconds.
+const MENU_REPEAT_DELAY: f32 = 0.4;
+
+/// How often a held menu direction repeats, in seconds.
+const MENU_REPEAT_INTERVAL: f32 = 0.12;
+
+/// Requests the next key or button pressed to be bound to an action.
+///
+/// See [InputMap::rebind].
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StartRebind(pub InputAction);
+
+/// Sent when an action was bound to a new key or button through
+/// [StartRebind].
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ActionRebound {
+    pub action: InputAction,
+    pub binding: InputBinding,
+
+    /// The actions the key or button was taken away from.
+    pub displaced: Vec<InputAction>,
+}
+
+/// The action waiting for a key or button to be bound to, if any.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PendingRebind(pub Option<InputAction>);
+
+fn update_action_state(
+    map: Res<InputMap>,
+    gamepad_settings: Res<GamepadSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    pending: Res<PendingRebind>,
+    mut state: ResMut<ActionState>,
+    mut pressed_events: EventWriter<ActionPressed>,
+    mut released_events: EventWriter<ActionReleased>,
+) {
+    let sources = InputSources {
+        keys: &keys,
+        mouse_buttons: &mouse_buttons,
+        gamepads: &gamepads,
+    };
+
+    // Whatever is pressed while rebinding is meant for the rebind.
+    let pressed: HashSet<_> = if pending.0.is_some() {
+        HashSet::default()
+    } else {
+        InputAction::ALL
+            .into_iter()
+            .filter(|&action| map.pressed(action, sources))
+            .collect()
+    };
+
+    state.just_pressed = pressed.difference(&state.pressed).copied().collect();
+    state.just_released = state.pressed.difference(&pressed).copied().collect();
+    state.pressed = pressed;
+
+    let sticks = |stick: fn(&Gamepad) -> Vec2, sensitivity| {
+        let sum = gamepads
+            .iter()
+            .map(|gamepad| gamepad_settings.stick(stick(gamepad), sensitivity))
+            .sum::<Vec2>();
+        sum.clamp(Vec2::NEG_ONE, Vec2::ONE)
+    };
+    let movement = sticks(Gamepad::left_stick, gamepad_settings.move_sensitivity);
+    let aim = sticks(Gamepad::right_stick, gamepad_settings.aim_sensitivity);
+
+    let throttle = state.axis(InputAction::ThrottleAhead, InputAction::ThrottleAstern);
+    let rudder = state.axis(InputAction::SteerPort, InputAction::SteerStarboard);
+    state.axes = HashMap::from_iter([
+        (
+            InputAxis::Throttle,
+            (throttle + movement.y).clamp(-1.0, 1.0),
+        ),
+        (InputAxis::Rudder, (rudder - movement.x).clamp(-1.0, 1.0)),
+        (InputAxis::AimX, aim.x),
+        (InputAxis::AimY, aim.y),
+    ]);
+
+    pressed_events.write_batch(state.just_pressed.iter().copied().map(ActionPressed));
+    released_events.write_batch(state.just_released.iter().copied().map(ActionReleased));
+}
+
+fn navigate_menus(
+    time: Res<Time<Real>>,
+    actions: Res<ActionState>,
+    mut held_for: Local<f32>,
+    mut navigate: EventWriter<MenuNavigate>,
+) {
+    let direction = IVec2::new(
+        actions.axis(InputAction::MenuRight, InputAction::MenuLeft) as i32,
+        actions.axis(InputAction::MenuUp, InputAction::MenuDown) as i32,
+    );
+    if direction == IVec2::ZERO {
+        *held_for = 0.0;
+        return;
+    }
+
+    let pressed = [
+        InputAction::MenuUp,
+        InputAction::MenuDown,
+        InputAction::MenuLeft,
+        InputAction::MenuRight,
+    ]
+    .into_iter()
+    .any(|action| actions.just_pressed(action));
+    if pressed {
+        *held_for = 0.0;
+        navigate.write(MenuNavigate(direction));
+        return;
+    }
+
+    let before = *held_for;
+    *held_for += time.delta_secs();
+    if menu_repeats(before, *held_for) {
+        navigate.write(MenuNavigate(direction));
+    }
+}
+
+/// Whether a menu direction held from `before` to `after` seconds repeats
+/// in between, once past the delay and then every interval.
+fn menu_repeats(before: f32, after: f32) -> bool {
+    let repeats = |held: f32| {
+        ((held - MENU_REPEAT_DELAY) / MENU_REPEAT_INTERVAL)
+            .floor()
+            .max(-1.0)
+    };
+    repeats(after) > repeats(before)
+}
+
+fn capture_rebind(
+    mut requests: EventReader<StartRebind>,
+    mut pending: ResMut<PendingRebind>,
+    mut map: ResMut<InputMap>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut rebound: EventWriter<ActionRebound>,
+) {
+    if let Some(&StartRebind(action)) = requests.read().last() {
+        pending.0 = Some(action);
+        return;
+    }
+
+    let Some(action) = pending.0 else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        pending.0 = None;
+        return;
+    }
+
+    let sources = InputSources {
+        keys: &keys,
+        mouse_buttons: &mouse_buttons,
+        gamepads: &gamepads,
+    };
+    let Some(binding) = sources.just_pressed() else {
+        return;
+    };
+
+    pending.0 = None;
+    let displaced = map.rebind(action, binding);
+    info!("Bound {action:?} to {binding:?}");
+    rebound.write(ActionRebound {
+        action,
+        binding,
+        displaced,
+    });
+}
+
+/// Input action plugin.
+///
+/// Included in [crate::app::AppPlugin].
+pub struct InputActionPlugin;
+
+impl Plugin for InputActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>();
+        app.init_resource::<GamepadSettings>();
+        app.init_resource::<ActionState>();
+        app.init_resource::<PendingRebind>();
+        app.add_event::<ActionPressed>();
+        app.add_event::<ActionReleased>();
+        app.add_event::<MenuNavigate>();
+        app.add_event::<StartRebind>();
+        app.add_event::<ActionRebound>();
+        app.add_systems(
+            PreUpdate,
+            (capture_rebind, update_action_state, navigate_menus)
+                .chain()
+                .after(InputSystem),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_only_share_keys_across_contexts() {
+        let map = InputMap::default();
+
+        for action in InputAction::ALL {
+            assert!(!map.bindings(action).is_empty(), "{action:?} is unbound");
+
+            for &binding in map.bindings(action) {
+                let conflicts: Vec<_> = map
+                    .actions(binding)
+                    .filter(|&other| other != action && other.context() == action.context())
+                    .collect();
+                assert!(
+                    conflicts.is_empty(),
+                    "{binding:?} is bound to {action:?} and {conflicts:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rebinding_displaces_actions_of_the_same_context() {
+        use InputAction::*;
+        use InputBinding::*;
+
+        let mut map = InputMap::default();
+
+        // W is taken away from throttling.
+        assert_eq!(map.rebind(FirePrimary, Key(KeyCode::KeyW)), [ThrottleAhead]);
+        assert_eq!(
+            map.bindings(ThrottleAhead),
+            [Gamepad(GamepadButton::RightTrigger2)]
+        );
+
+        // The mouse button is replaced, but not the gamepad button.
+        assert_eq!(
+            map.bindings(FirePrimary),
+            [Gamepad(GamepadButton::South), Key(KeyCode::KeyW)]
+        );
+
+        // Menus keep the keys they share with gameplay.
+        assert!(map.rebind(Pause, Key(KeyCode::Backspace)).is_empty());
+        assert!(map.rebind(Pause, Key(KeyCode::Escape)).is_empty());
+        assert_eq!(map.bindings(MenuBack)[0], Key(KeyCode::Escape));
+        assert!(map.rebind(MenuConfirm, Key(KeyCode::Tab)).is_empty());
+        assert_eq!(map.bindings(OpenInventory)[0], Key(KeyCode::Tab));
+        assert_eq!(map.rebind(MenuBack, Key(KeyCode::Tab)), [MenuConfirm]);
+    }
+
+    #[test]
+    fn actions_follow_their_bindings() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputActionPlugin));
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+
+        let press = |app: &mut App, key, pressed| {
+            let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keys.clear();
+            if pressed {
+                keys.press(key);
+            } else {
+                keys.release(key);
+            }
+            app.update();
+        };
+        let events = |app: &mut App| {
+            let pressed: Vec<_> = app
+                .world_mut()
+                .resource_mut::<Events<ActionPressed>>()
+                .drain()
+                .map(|event| event.0)
+                .collect();
+            let released: Vec<_> = app
+                .world_mut()
+                .resource_mut::<Events<ActionReleased>>()
+                .drain()
+                .map(|event| event.0)
+                .collect();
+            (pressed, released)
+        };
+
+        press(&mut app, KeyCode::KeyW, true);
+        let state = app.world().resource::<ActionState>();
+        assert!(state.pressed(InputAction::ThrottleAhead));
+        assert!(state.just_pressed(InputAction::ThrottleAhead));
+        assert_eq!(state.value(InputAxis::Throttle), 1.0);
+        assert_eq!(events(&mut app), (vec![InputAction::ThrottleAhead], vec![]));
+
+        // Escape is both pause and back, in their own contexts.
+        press(&mut app, KeyCode::Escape, true);
+        let state = app.world().resource::<ActionState>();
+        assert!(state.pressed(InputAction::ThrottleAhead));
+        assert!(!state.just_pressed(InputAction::ThrottleAhead));
+        assert!(state.just_pressed(InputAction::Pause));
+        assert!(state.just_pressed(InputAction::MenuBack));
+        events(&mut app);
+
+        press(&mut app, KeyCode::KeyW, false);
+        let state = app.world().resource::<ActionState>();
+        assert!(state.just_released(InputAction::ThrottleAhead));
+        assert_eq!(state.value(InputAxis::Throttle), 0.0);
+        assert_eq!(events(&mut app), (vec![], vec![InputAction::ThrottleAhead]));
+
+        // Held actions are let go while waiting for a rebind.
+        press(&mut app, KeyCode::Escape, false);
+        press(&mut app, KeyCode::KeyW, true);
+        events(&mut app);
+        app.world_mut()
+            .send_event(StartRebind(InputAction::SteerPort));
+        app.update();
+        assert!(app.world().resource::<ActionState>().pressed.is_empty());
+        assert_eq!(events(&mut app), (vec![], vec![InputAction::ThrottleAhead]));
+
+        press(&mut app, KeyCode::KeyQ, true);
+        let map = app.world().resource::<InputMap>();
+        assert!(
+            map.bindings(InputAction::SteerPort)
+                .contains(&InputBinding::Key(KeyCode::KeyQ))
+        );
+        assert!(
+            !map.bindings(InputAction::SteerPort)
+                .contains(&InputBinding::Key(KeyCode::KeyA))
+        );
+        assert!(
+            app.world()
+                .resource::<ActionState>()
+                .pressed(InputAction::ThrottleAhead)
+        );
+    }
+
+    #[test]
+    fn sticks_are_read_past_the_dead_zone() {
+        let settings = GamepadSettings {
+            dead_zone: 0.2,
+            ..default()
+        };
+
+        // Within the dead zone, the stick is at rest.
+        assert_eq!(settings.stick(Vec2::new(0.1, 0.1), 1.0), Vec2::ZERO);
+        assert_eq!(settings.stick(Vec2::X * 0.2, 1.0), Vec2::ZERO);
+
+        // Past it, the stick is rescaled to start back from zero...
+        let stick = settings.stick(Vec2::Y * 0.6, 1.0);
+        assert!(stick.abs_diff_eq(Vec2::Y * 0.5, 1e-5));
+
+        // ...keeping its direction, and reaching 1.0 when pushed fully.
+        let stick = settings.stick(Vec2::new(-0.6, 0.8), 1.0);
+        assert!(stick.abs_diff_eq(Vec2::new(-0.6, 0.8), 1e-5));
+    }
+
+    #[test]
+    fn stick_sensitivity_scales_up_to_full() {
+        let settings = GamepadSettings {
+            dead_zone: 0.0,
+            ..default()
+        };
+
+        assert!(
+            settings
+                .stick(Vec2::X * 0.4, 0.5)
+                .abs_diff_eq(Vec2::X * 0.2, 1e-5)
+        );
+        assert!(
+            settings
+                .stick(Vec2::X * 0.4, 2.0)
+                .abs_diff_eq(Vec2::X * 0.8, 1e-5)
+        );
+        assert!(
+            settings
+                .stick(Vec2::X * 0.8, 2.0)
+                .abs_diff_eq(Vec2::X, 1e-5)
+        );
+
+        // An oversized dead zone does not divide by zero.
+        let settings = GamepadSettings {
+            dead_zone: 1.0,
+            ..default()
+        };
+        assert!(settings.stick(Vec2::X, 1.0).is_finite());
+    }
+
+    #[test]
+    fn held_menu_directions_repeat_after_a_delay() {
+        let step = 0.01;
+        let repeats: Vec<f32> = (0..95)
+            .map(|frame| frame as f32 * step)
+            .filter(|&held| menu_repeats(held, held + step))
+            .map(|held| held + step)
+            .collect();
+
+        // No repeat before the delay, then one every interval.
+        assert!(repeats[0] >= MENU_REPEAT_DELAY);
+        assert!(repeats[0] < MENU_REPEAT_DELAY + step);
+        for pair in repeats.windows(2) {
+            assert!((pair[1] - pair[0] - MENU_REPEAT_INTERVAL).abs() <= step * 1.5);
+        }
+        assert_eq!(repeats.len(), 5);
+
+        // A long frame still repeats only once.
+        assert!(menu_repeats(0.0, 1.0));
+        assert!(!menu_repeats(0.0, MENU_REPEAT_DELAY * 0.5));
+    }
+}
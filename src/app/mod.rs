@@ -17,11 +17,15 @@
 // permitted by applicable law.  See the CNPL for details.
 
 // [TODO] Please uncomment *only* implemented modules.
-// pub mod audio;
-// pub mod resource;
-// pub mod input; [NOTE] a lot of input code is in common, maybe we should move it into the app tree?
+#[cfg(feature = "audio")]
+pub mod audio; // Sound effects and music playback
 pub mod camera; // Camera controls & updates
+pub mod controls; // Player ship controls
+pub mod input; // Input actions, and what they are bound to
+pub mod platform; // Platform services integration
 pub mod renderer; // Rendering code
+pub mod resource; // Required assets, and loading them before the main menu
+pub mod settings; // Player settings and profile, and where they are kept
 pub mod state;
 
 /// Loot & Roam app plugin.
@@ -35,14 +39,25 @@ impl bevy::prelude::Plugin for AppPlugin {
         app.add_plugins((
             renderer::RendererPlugin,
             camera::CameraControlPlugin,
+            controls::PlayerControlsPlugin,
+            input::InputActionPlugin,
+            platform::PlatformPlugin,
+            resource::ResourcePlugin,
             state::AppStatePlugin,
+            settings::SettingsPlugin,
         ));
+
+        #[cfg(feature = "audio")]
+        app.add_plugins(audio::AudioPlaybackPlugin);
     }
 }
 
 pub mod prelude {
     pub use super::AppPlugin;
     pub use super::camera::prelude::*;
+    pub use super::input::{ActionState, InputAction, InputBinding, InputMap};
+    pub use super::platform::{Platform, PlatformEvent, PlatformIntegration};
     pub use super::renderer::prelude::*;
+    pub use super::settings::Settings;
     pub use super::state::prelude::*;
 }
@@ -0,0 +1,174 @@
+//! # Platform integration
+//!
+//! An abstraction over storefront and platform services, such as rich
+//! presence, platform achievements and cloud saves.
+//!
+//! Gameplay code never talks to a platform directly; instead, it sends
+//! [PlatformEvent]s, which are forwarded to whichever [Platform] is
+//! installed in the [PlatformIntegration] resource. By default, that is
+//! [NoPlatform], which does nothing; platform-specific crates can replace it
+//! without touching any gameplay code.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Trigger achievements from run statistics milestones, once those
+// are tracked.
+
+use bevy::prelude::*;
+
+use crate::common::state::GameState;
+
+/// What the player is currently up to, as shown to their friends by the
+/// platform.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RichPresence {
+    /// A short description of the current state, e.g. "Raiding an island".
+    pub state: String,
+
+    /// The name of the current island, if any.
+    pub island_name: Option<String>,
+
+    /// The current in-game day, if any.
+    pub day: Option<u32>,
+}
+
+/// Interface to a platform's services.
+///
+/// Every method has a no-op default, so platforms only need to implement
+/// what they actually support.
+pub trait Platform: Send + Sync + 'static {
+    /// A human readable name for this platform, used in logs.
+    fn name(&self) -> &str;
+
+    /// Updates the player's rich presence.
+    fn set_rich_presence(&mut self, _presence: &RichPresence) {}
+
+    /// Unlocks an achievement on the platform.
+    ///
+    /// Achievements are identified by keyword, such as "first_sinking".
+    fn unlock_achievement(&mut self, _id: &str) {}
+
+    /// Uploads a save file to the platform's cloud storage.
+    ///
+    /// Returns whether the upload succeeded.
+    fn cloud_save_upload(&mut self, _name: &str, _data: &[u8]) -> bool {
+        false
+    }
+
+    /// Downloads a save file from the platform's cloud storage, if present.
+    fn cloud_save_download(&mut self, _name: &str) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The default platform, which does nothing.
+pub struct NoPlatform;
+
+impl Platform for NoPlatform {
+    fn name(&self) -> &str {
+        "none"
+    }
+}
+
+/// The platform currently in use.
+#[derive(Resource)]
+pub struct PlatformIntegration(pub Box<dyn Platform>);
+
+impl Default for PlatformIntegration {
+    fn default() -> Self {
+        Self(Box::new(NoPlatform))
+    }
+}
+
+/// Requests sent to the platform by gameplay code.
+#[derive(Event, Clone, Debug)]
+pub enum PlatformEvent {
+    /// Updates the island name and day shown in the rich presence.
+    ///
+    /// The state description is kept up to date automatically from the
+    /// [GameState].
+    SetLocation {
+        island_name: Option<String>,
+        day: Option<u32>,
+    },
+
+    /// Unlocks an achievement.
+    UnlockAchievement(String),
+}
+
+/// The rich presence last sent to the platform.
+#[derive(Resource, Default)]
+struct CurrentPresence(RichPresence);
+
+fn game_state_description(state: &GameState) -> &'static str {
+    match state {
+        GameState::None => "In the main menu",
+        GameState::Start => "Setting sail",
+        GameState::Overworld => "Raiding an island",
+        GameState::Intermission => "Docked at port",
+    }
+}
+
+fn update_presence_state(
+    state: Res<State<GameState>>,
+    mut presence: ResMut<CurrentPresence>,
+    mut platform: ResMut<PlatformIntegration>,
+) {
+    presence.0.state = game_state_description(state.get()).to_owned();
+    platform.0.set_rich_presence(&presence.0);
+}
+
+fn ev_platform(
+    mut events: EventReader<PlatformEvent>,
+    mut presence: ResMut<CurrentPresence>,
+    mut platform: ResMut<PlatformIntegration>,
+) {
+    for event in events.read() {
+        match event {
+            PlatformEvent::SetLocation { island_name, day } => {
+                presence.0.island_name = island_name.clone();
+                presence.0.day = *day;
+                platform.0.set_rich_presence(&presence.0);
+            }
+            PlatformEvent::UnlockAchievement(id) => {
+                info!(
+                    "Unlocking achievement {id} on platform {}",
+                    platform.0.name()
+                );
+                platform.0.unlock_achievement(id);
+            }
+        }
+    }
+}
+
+/// Platform integration plugin.
+///
+/// Included in [super::AppPlugin].
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlatformIntegration>();
+        app.init_resource::<CurrentPresence>();
+        app.add_event::<PlatformEvent>();
+        app.add_systems(
+            Update,
+            (
+                update_presence_state.run_if(resource_exists_and_changed::<State<GameState>>),
+                ev_platform,
+            )
+                .chain(),
+        );
+    }
+}
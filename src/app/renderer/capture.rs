@@ -0,0 +1,350 @@
+//! # Gameplay capture
+//!
+//! Records the output of any camera, either as an image sequence on disk, or
+//! by piping raw frames into an external encoder process (such as ffmpeg).
+//!
+//! Capture is controlled at runtime, through [CaptureEvent]s. This is meant
+//! for trailers, bug reports, and the replay viewer.
+//!
+//! Cameras rendering to an image, rather than a window, can be captured too,
+//! so capture works in windowless setups as well.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk},
+    time::TimeUpdateStrategy,
+};
+
+/// Where captured frames go.
+#[derive(Clone, Debug)]
+pub enum CaptureOutput {
+    /// Numbered PNG files in a directory, e.g. `out/00042.png`.
+    ///
+    /// The directory is created if it does not exist.
+    ImageSequence { directory: PathBuf },
+
+    /// Raw RGBA8 frames, written back to back into the standard input of an
+    /// encoder process.
+    ///
+    /// The encoder must be told the frame size and pixel format, e.g.:
+    ///
+    /// ```sh
+    /// ffmpeg -f rawvideo -pix_fmt rgba -s 1280x720 -r 60 -i - out.mp4
+    /// ```
+    EncoderPipe { program: String, args: Vec<String> },
+}
+
+/// Settings of a capture.
+#[derive(Clone, Debug)]
+pub struct CaptureSettings {
+    /// The camera to capture.
+    pub camera: Entity,
+
+    /// Where captured frames go.
+    pub output: CaptureOutput,
+
+    /// If set, time advances by a fixed step every frame while capturing,
+    /// so that the recording plays back smoothly at this frame rate, no
+    /// matter how long each frame actually took to render.
+    pub frame_rate: Option<f64>,
+}
+
+/// Controls gameplay capture.
+#[derive(Event, Clone, Debug)]
+pub enum CaptureEvent {
+    /// Starts capturing, stopping any capture already in progress.
+    Start(CaptureSettings),
+
+    /// Stops the capture in progress, if any.
+    Stop,
+}
+
+/// The sink of a capture in progress.
+enum CaptureSink {
+    ImageSequence {
+        directory: PathBuf,
+    },
+    EncoderPipe {
+        child: Child,
+        stdin: Arc<Mutex<Option<ChildStdin>>>,
+    },
+}
+
+/// The capture in progress.
+struct ActiveCapture {
+    camera: Entity,
+    sink: CaptureSink,
+    frame: u64,
+
+    /// The time update strategy from before the capture, if it was replaced
+    /// to capture at a fixed frame rate.
+    previous_time_strategy: Option<TimeUpdateStrategy>,
+}
+
+impl ActiveCapture {
+    fn stop(self, time_strategy: &mut TimeUpdateStrategy) {
+        info!("Stopped capture after {} frames", self.frame);
+        self.sink.close();
+
+        if let Some(previous) = self.previous_time_strategy {
+            *time_strategy = previous;
+        }
+    }
+}
+
+/// Gameplay capture state.
+#[derive(Resource, Default)]
+pub struct CaptureState {
+    active: Option<ActiveCapture>,
+}
+
+impl CaptureState {
+    /// Whether a capture is in progress.
+    pub fn is_capturing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Number of frames captured so far, if a capture is in progress.
+    pub fn frames_captured(&self) -> Option<u64> {
+        self.active.as_ref().map(|active| active.frame)
+    }
+}
+
+impl CaptureSink {
+    fn open(output: &CaptureOutput) -> std::io::Result<Self> {
+        match output {
+            CaptureOutput::ImageSequence { directory } => {
+                std::fs::create_dir_all(directory)?;
+                Ok(Self::ImageSequence {
+                    directory: directory.clone(),
+                })
+            }
+            CaptureOutput::EncoderPipe { program, args } => {
+                let mut child = Command::new(program)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                let stdin = child.stdin.take();
+                Ok(Self::EncoderPipe {
+                    child,
+                    stdin: Arc::new(Mutex::new(stdin)),
+                })
+            }
+        }
+    }
+
+    /// Closes the sink.
+    ///
+    /// Closing the encoder's standard input signals it to finish; it is then
+    /// waited on in the background, so that the game doesn't hitch.
+    fn close(self) {
+        if let CaptureSink::EncoderPipe { mut child, stdin } = self {
+            stdin.lock().unwrap().take();
+
+            std::thread::spawn(move || match child.wait() {
+                Ok(status) if status.success() => info!("Capture encoder finished"),
+                Ok(status) => warn!("Capture encoder exited with {status}"),
+                Err(err) => error!("Could not wait on capture encoder: {err}"),
+            });
+        }
+    }
+}
+
+/// Writes a captured frame into an encoder's standard input.
+fn pipe_frame(stdin: Arc<Mutex<Option<ChildStdin>>>) -> impl FnMut(Trigger<ScreenshotCaptured>) {
+    move |trigger| {
+        let mut stdin = stdin.lock().unwrap();
+
+        // The capture was stopped while this frame was in flight.
+        let Some(pipe) = stdin.as_mut() else {
+            return;
+        };
+
+        let frame = match trigger.event().0.clone().try_into_dynamic() {
+            Ok(image) => image.to_rgba8(),
+            Err(err) => {
+                error!("Cannot convert captured frame: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = pipe.write_all(frame.as_raw()) {
+            error!("Cannot write frame to capture encoder, stopping: {err}");
+            stdin.take();
+        }
+    }
+}
+
+fn handle_capture_events(
+    mut events: EventReader<CaptureEvent>,
+    mut state: ResMut<CaptureState>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    for event in events.read() {
+        if let Some(active) = state.active.take() {
+            active.stop(&mut time_strategy);
+        }
+
+        let CaptureEvent::Start(settings) = event else {
+            continue;
+        };
+
+        let sink = match CaptureSink::open(&settings.output) {
+            Ok(sink) => sink,
+            Err(err) => {
+                error!("Cannot start capture: {err}");
+                continue;
+            }
+        };
+
+        let previous_time_strategy = settings.frame_rate.map(|frame_rate| {
+            std::mem::replace(
+                &mut *time_strategy,
+                TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / frame_rate)),
+            )
+        });
+
+        info!("Started capture of camera {}", settings.camera);
+        state.active = Some(ActiveCapture {
+            camera: settings.camera,
+            sink,
+            frame: 0,
+            previous_time_strategy,
+        });
+    }
+}
+
+fn capture_frames(
+    mut commands: Commands,
+    mut state: ResMut<CaptureState>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    cameras: Query<&Camera>,
+) {
+    let Some(active) = state.active.as_mut() else {
+        return;
+    };
+
+    let Ok(camera) = cameras.get(active.camera) else {
+        warn!(
+            "Captured camera {} is gone, stopping capture",
+            active.camera
+        );
+        state.active.take().unwrap().stop(&mut time_strategy);
+        return;
+    };
+
+    // [NOTE] Frames are read back from the GPU asynchronously. They arrive in
+    // order in practice, but if that ever stops being the case, the encoder
+    // pipe will need to reorder them by frame number.
+
+    let mut screenshot = commands.spawn(Screenshot(camera.target.clone()));
+
+    match &active.sink {
+        CaptureSink::ImageSequence { directory } => {
+            screenshot.observe(save_to_disk(
+                directory.join(format!("{:05}.png", active.frame)),
+            ));
+        }
+        CaptureSink::EncoderPipe { stdin, .. } => {
+            screenshot.observe(pipe_frame(stdin.clone()));
+        }
+    }
+
+    active.frame += 1;
+}
+
+/// Gameplay capture plugin.
+///
+/// Send [CaptureEvent]s to start and stop capturing.
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CaptureEvent>();
+        app.init_resource::<CaptureState>();
+        app.init_resource::<TimeUpdateStrategy>();
+        app.add_systems(PostUpdate, (handle_capture_events, capture_frames).chain());
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_start_and_stop_at_a_fixed_frame_rate() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CapturePlugin));
+        app.insert_resource(TimeUpdateStrategy::Automatic);
+
+        let directory = std::env::temp_dir().join(format!("lnr-capture-{}", std::process::id()));
+        let camera = app.world_mut().spawn(Camera::default()).id();
+        app.world_mut()
+            .send_event(CaptureEvent::Start(CaptureSettings {
+                camera,
+                output: CaptureOutput::ImageSequence {
+                    directory: directory.clone(),
+                },
+                frame_rate: Some(30.0),
+            }));
+        app.update();
+        app.update();
+
+        // Time is stepped at the capture's frame rate, and a frame is taken
+        // every update.
+        let state = app.world().resource::<CaptureState>();
+        assert_eq!(state.frames_captured(), Some(2));
+        assert!(matches!(
+            app.world().resource::<TimeUpdateStrategy>(),
+            TimeUpdateStrategy::ManualDuration(step) if *step == Duration::from_secs_f64(1.0 / 30.0)
+        ));
+        assert!(directory.is_dir());
+
+        // Stopping restores how time was updated before.
+        app.world_mut().send_event(CaptureEvent::Stop);
+        app.update();
+        assert!(!app.world().resource::<CaptureState>().is_capturing());
+        assert!(matches!(
+            app.world().resource::<TimeUpdateStrategy>(),
+            TimeUpdateStrategy::Automatic
+        ));
+
+        // Captures stop by themselves when their camera is gone.
+        app.world_mut()
+            .send_event(CaptureEvent::Start(CaptureSettings {
+                camera,
+                output: CaptureOutput::ImageSequence {
+                    directory: directory.clone(),
+                },
+                frame_rate: None,
+            }));
+        app.update();
+        assert!(app.world().resource::<CaptureState>().is_capturing());
+        app.world_mut().despawn(camera);
+        app.update();
+        assert!(!app.world().resource::<CaptureState>().is_capturing());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,400 @@
+//! # Procedural emblems
+//!
+//! Flags and emblems for factions and players, composed from a field
+//! pattern, a palette and an optional central charge.
+//!
+//! Emblems are generated from a seed, so the same faction or player always
+//! gets the same flag. They are rasterized to small textures at runtime,
+//! which are then applied to whichever entity carries an [Emblem].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Customization screen for the player's own flag, once the UI
+// renderer is usable.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::{Rng, SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+
+/// Colors emblems are allowed to use.
+///
+/// Loosely based on heraldic tinctures. Keeping to a fixed palette keeps
+/// generated flags readable from afar.
+pub const EMBLEM_PALETTE: [Color; 8] = [
+    Color::srgb(0.95, 0.93, 0.86), // white
+    Color::srgb(0.96, 0.76, 0.18), // yellow
+    Color::srgb(0.72, 0.12, 0.12), // red
+    Color::srgb(0.12, 0.25, 0.62), // blue
+    Color::srgb(0.13, 0.45, 0.22), // green
+    Color::srgb(0.42, 0.18, 0.48), // purple
+    Color::srgb(0.08, 0.08, 0.09), // black
+    Color::srgb(0.84, 0.42, 0.12), // orange
+];
+
+/// The background layer of an emblem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmblemPattern {
+    /// A single color.
+    Solid,
+
+    /// Alternating horizontal stripes.
+    HorizontalStripes(u8),
+
+    /// Alternating vertical stripes.
+    VerticalStripes(u8),
+
+    /// Split into four quarters.
+    Quartered,
+
+    /// A diagonal cross.
+    Saltire,
+
+    /// An upright cross, offset towards the hoist.
+    Cross,
+
+    /// A thick border around the field.
+    Bordure,
+}
+
+impl EmblemPattern {
+    /// Whether the secondary color is used at the given texture coordinate.
+    fn is_secondary(&self, uv: Vec2) -> bool {
+        match *self {
+            Self::Solid => false,
+            Self::HorizontalStripes(count) => (uv.y * count.max(1) as f32) as u32 % 2 == 1,
+            Self::VerticalStripes(count) => (uv.x * count.max(1) as f32) as u32 % 2 == 1,
+            Self::Quartered => (uv.x < 0.5) != (uv.y < 0.5),
+            Self::Saltire => (uv.x - uv.y).abs() < 0.1 || (uv.x + uv.y - 1.0).abs() < 0.1,
+            Self::Cross => (uv.x - 0.35).abs() < 0.08 || (uv.y - 0.5).abs() < 0.1,
+            Self::Bordure => uv.min_element() < 0.1 || uv.max_element() > 0.9,
+        }
+    }
+}
+
+/// A shape drawn over the center of the field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmblemCharge {
+    /// A filled circle.
+    Disc,
+
+    /// A hollow circle.
+    Ring,
+
+    /// A filled diamond.
+    Diamond,
+
+    /// A five-pointed star.
+    Star,
+}
+
+impl EmblemCharge {
+    /// Whether the charge covers the given point.
+    ///
+    /// The point is relative to the charge's center, and scaled such that
+    /// the charge fits within a unit circle.
+    fn covers(&self, point: Vec2) -> bool {
+        match *self {
+            Self::Disc => point.length() < 1.0,
+            Self::Ring => (0.65..1.0).contains(&point.length()),
+            Self::Diamond => point.x.abs() + point.y.abs() < 1.0,
+            Self::Star => {
+                // Star outline in polar coordinates, pointing upwards.
+                let angle = point
+                    .x
+                    .atan2(point.y)
+                    .rem_euclid(std::f32::consts::TAU / 5.0)
+                    - std::f32::consts::TAU / 10.0;
+                let spike = 1.0 - angle.abs() / (std::f32::consts::TAU / 10.0);
+                point.length() < 0.4 + 0.6 * spike * spike
+            }
+        }
+    }
+}
+
+/// Definition of an emblem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmblemDef {
+    /// Background pattern.
+    pub pattern: EmblemPattern,
+
+    /// Main color of the field.
+    pub primary: Color,
+
+    /// Secondary color of the field, used by the pattern.
+    pub secondary: Color,
+
+    /// Central charge, if any.
+    pub charge: Option<EmblemCharge>,
+
+    /// Color of the central charge.
+    pub charge_color: Color,
+}
+
+impl EmblemDef {
+    /// Generates an emblem from a seed.
+    ///
+    /// The same seed always produces the same emblem; use e.g. a hash of a
+    /// faction's name, or the player's profile seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::random(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Generates a random emblem.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let pattern = match rng.random_range(0..7) {
+            0 => EmblemPattern::Solid,
+            1 => EmblemPattern::HorizontalStripes(rng.random_range(2..6)),
+            2 => EmblemPattern::VerticalStripes(rng.random_range(2..4)),
+            3 => EmblemPattern::Quartered,
+            4 => EmblemPattern::Saltire,
+            5 => EmblemPattern::Cross,
+            _ => EmblemPattern::Bordure,
+        };
+
+        let mut colors = EMBLEM_PALETTE.choose_multiple(rng, 3);
+        let primary = *colors.next().unwrap();
+        let secondary = *colors.next().unwrap();
+        let charge_color = *colors.next().unwrap();
+
+        let charge = [
+            None,
+            Some(EmblemCharge::Disc),
+            Some(EmblemCharge::Ring),
+            Some(EmblemCharge::Diamond),
+            Some(EmblemCharge::Star),
+        ]
+        .choose(rng)
+        .copied()
+        .flatten();
+
+        Self {
+            pattern,
+            primary,
+            secondary,
+            charge,
+            charge_color,
+        }
+    }
+
+    /// Samples the emblem's color at a texture coordinate.
+    pub fn sample(&self, uv: Vec2) -> Color {
+        if let Some(charge) = self.charge {
+            // Charges are centered and take up about half of the flag's
+            // height, regardless of its aspect ratio.
+            let point = (uv - Vec2::new(0.5, 0.5)) * Vec2::new(2.0, -2.0) / 0.5;
+            if charge.covers(point) {
+                return self.charge_color;
+            }
+        }
+
+        if self.pattern.is_secondary(uv) {
+            self.secondary
+        } else {
+            self.primary
+        }
+    }
+
+    /// Rasterizes the emblem into a texture.
+    pub fn rasterize(&self, width: u32, height: u32) -> Image {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let uv = Vec2::new(
+                    (x as f32 + 0.5) / width as f32,
+                    (y as f32 + 0.5) / height as f32,
+                );
+                self.sample(uv).to_srgba().to_u8_array()
+            })
+            .collect::<Vec<u8>>();
+
+        Image::new(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+}
+
+/// Displays an emblem on this entity.
+///
+/// If the entity has a [StandardMaterial], the emblem's texture is applied
+/// to a copy of it. Either way, the texture is made available through an
+/// [EmblemTexture], e.g. for use in UI elements.
+#[derive(Component, Clone, Debug)]
+pub struct Emblem {
+    /// The emblem to display.
+    pub def: EmblemDef,
+
+    /// Size of the texture to rasterize, in pixels.
+    pub resolution: UVec2,
+}
+
+impl Emblem {
+    /// Creates an emblem component at the default resolution.
+    pub fn new(def: EmblemDef) -> Self {
+        Self {
+            def,
+            resolution: UVec2::new(48, 32),
+        }
+    }
+
+    /// Creates an emblem component from a seed, at the default resolution.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(EmblemDef::from_seed(seed))
+    }
+}
+
+/// The rasterized texture of an [Emblem].
+///
+/// Inserted automatically.
+#[derive(Component, Clone, Debug)]
+pub struct EmblemTexture(pub Handle<Image>);
+
+/// The copy of its material an [Emblem] is applied to.
+#[derive(Component, Clone, Debug)]
+struct EmblemMaterial(Handle<StandardMaterial>);
+
+fn apply_emblems(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<
+        (
+            Entity,
+            &Emblem,
+            Option<&EmblemTexture>,
+            Option<&EmblemMaterial>,
+        ),
+        Changed<Emblem>,
+    >,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>>,
+) {
+    for (entity, emblem, texture, own_material) in query.iter() {
+        let raster = emblem
+            .def
+            .rasterize(emblem.resolution.x.max(1), emblem.resolution.y.max(1));
+
+        // Changed emblems are drawn over their old texture, rather than
+        // getting a new one every time.
+        let image = match texture.filter(|texture| images.contains(&texture.0)) {
+            Some(texture) => {
+                images.insert(&texture.0, raster);
+                texture.0.clone()
+            }
+            None => {
+                let image = images.add(raster);
+                commands.entity(entity).insert(EmblemTexture(image.clone()));
+                image
+            }
+        };
+
+        let Ok(current) = material_query.get(entity) else {
+            continue;
+        };
+        if own_material.is_some_and(|own| own.0 == current.0) {
+            // Already the emblem's own copy, showing the same texture.
+            continue;
+        }
+        if let Some(own) = own_material {
+            // The entity was given another material since; the old copy is
+            // of no use anymore.
+            materials.remove(&own.0);
+        }
+
+        // Materials may be shared, so the emblem gets its own copy.
+        if let Some(material) = materials.get(&current.0).cloned() {
+            let material = materials.add(StandardMaterial {
+                base_color_texture: Some(image),
+                ..material
+            });
+            commands
+                .entity(entity)
+                .insert((MeshMaterial3d(material.clone()), EmblemMaterial(material)));
+        }
+    }
+}
+
+/// Emblem rendering plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct EmblemPlugin;
+
+impl Plugin for EmblemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, apply_emblems);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn emblems_are_seeded_and_redrawn_in_place() {
+        let colors = |def: &EmblemDef| [def.sample(Vec2::splat(0.1)), def.sample(Vec2::splat(0.5))];
+        assert_eq!(
+            colors(&EmblemDef::from_seed(7)),
+            colors(&EmblemDef::from_seed(7))
+        );
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, EmblemPlugin));
+        app.init_resource::<Assets<Image>>();
+        app.init_resource::<Assets<StandardMaterial>>();
+
+        let shared = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let entity = app
+            .world_mut()
+            .spawn((Emblem::from_seed(1), MeshMaterial3d(shared.clone())))
+            .id();
+        app.update();
+
+        let texture = app.world().get::<EmblemTexture>(entity).unwrap().0.clone();
+        let material = app
+            .world()
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .unwrap()
+            .0
+            .clone();
+        assert_ne!(material, shared);
+
+        // Changing the emblem reuses both its texture and its material.
+        app.world_mut().get_mut::<Emblem>(entity).unwrap().def = EmblemDef::from_seed(2);
+        app.update();
+
+        assert_eq!(app.world().get::<EmblemTexture>(entity).unwrap().0, texture);
+        assert_eq!(
+            app.world()
+                .get::<MeshMaterial3d<StandardMaterial>>(entity)
+                .unwrap()
+                .0,
+            material
+        );
+        assert_eq!(app.world().resource::<Assets<Image>>().len(), 1);
+        assert_eq!(app.world().resource::<Assets<StandardMaterial>>().len(), 2);
+    }
+}
@@ -0,0 +1,77 @@
+//! # Buoy rendering
+//!
+//! Gives player-placed buoys a visible float, colored by what they mark, and
+//! a small light so that they can be found in the dark.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::markers::{BuoyFloat, BuoySettings, MapMarker, MarkerKind};
+
+/// Paint color of a buoy marking a kind of thing.
+fn marker_color(kind: MarkerKind) -> Color {
+    match kind {
+        MarkerKind::Loot => Color::srgb(1.0, 0.8, 0.1),
+        MarkerKind::SafeChannel => Color::srgb(0.1, 0.8, 0.2),
+        MarkerKind::Danger => Color::srgb(0.9, 0.1, 0.1),
+        MarkerKind::Generic => Color::srgb(0.9, 0.9, 0.9),
+    }
+}
+
+fn add_buoy_visuals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<BuoySettings>,
+    floats: Query<(Entity, &ChildOf), Added<BuoyFloat>>,
+    markers: Query<&MapMarker>,
+) {
+    for (entity, child_of) in floats.iter() {
+        let kind = markers
+            .get(child_of.parent())
+            .map(|marker| marker.kind)
+            .unwrap_or_default();
+        let color = marker_color(kind);
+
+        // [TODO] Only light buoys up at night, once there is a day/night
+        // cycle.
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(Sphere::new(settings.radius))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color.to_linear() * 0.5,
+                ..default()
+            })),
+            children![(
+                PointLight {
+                    color,
+                    intensity: 20000.0,
+                    range: 30.0,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, settings.radius * 2.0, 0.0),
+            )],
+        ));
+    }
+}
+
+/// Buoy rendering plugin.
+pub struct MarkerRenderingPlugin;
+
+impl Plugin for MarkerRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, add_buoy_visuals);
+    }
+}
@@ -19,10 +19,18 @@
 
 // [TODO] Please uncomment *only* implemented modules.
 // pub mod lighting;  // Scene lighting definitions
+pub mod capture; // Gameplay video capture
+pub mod emblem; // Procedural flags and emblems
+pub mod markers; // Buoy rendering
 pub mod object; // Common object rendering code
+pub mod particles; // Particle effects
+pub mod postprocess; // Post-processing stack
+pub mod quality; // Dynamic render quality
 pub mod sky; // Sky/background
 pub mod terrain; // Terrain renderer
 pub mod ui; // UI renderer
+pub mod wake; // Ship wakes
+pub mod water; // Water surface rendering
 
 /// Renderer plugin.
 ///
@@ -31,10 +39,35 @@ pub struct RendererPlugin;
 
 impl bevy::prelude::Plugin for RendererPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_plugins((sky::SkyRenderingPlugin, object::ObjectRendererPlugin));
+        app.add_plugins((
+            sky::SkyRenderingPlugin,
+            capture::CapturePlugin,
+            emblem::EmblemPlugin,
+            markers::MarkerRenderingPlugin,
+            object::ObjectRendererPlugin,
+            particles::ParticlePlugin,
+            postprocess::PostProcessPlugin,
+            quality::RenderQualityPlugin,
+            terrain::TerrainRenderingPlugin,
+            (
+                ui::draw::UiDrawPlugin,
+                ui::drydock::DrydockScreenPlugin,
+                ui::event::UiEventPlugin,
+                ui::hud::HudPlugin,
+                ui::minimap::MinimapPlugin,
+                ui::nameplate::NameplatePlugin,
+            ),
+            water::WaterRenderingPlugin,
+        ));
+        app.add_plugins(wake::WakeRenderingPlugin);
     }
 }
 
 pub mod prelude {
+    pub use super::capture::{CaptureEvent, CaptureOutput, CaptureSettings, CaptureState};
+    pub use super::emblem::{Emblem, EmblemDef};
+    pub use super::object::sync::{PointNetTransformSync, SyncOrientation};
+    pub use super::postprocess::{PostProcessConfig, PostProcessEvent};
+    pub use super::quality::{RenderQuality, RenderQualityConfig};
     pub use super::sky::SkyRenderingPlugin;
 }
@@ -0,0 +1,369 @@
+//! # Ship hulls
+//!
+//! Every [Ship] is given a hull mesh, built from the [HullShape] of its make,
+//! in a child entity painted with the ship's livery (see [super::livery]).
+//!
+//! The hull is built as a series of cross-sections from stern to bow, each a
+//! rounded U from the deck on one side, down to the keel, and up to the deck
+//! on the other, closed off by a flat deck on top and a transom at either end.
+//! Meshes are cached by shape, so ships of the same make share theirs.
+//!
+//! Hulls follow the ship's point network: they are centered on its center of
+//! mass, and point from its stern point to its bow point, or, if the shape
+//! has none, towards where the ship is going. They are rolled and pitched
+//! along with the network's [principal axes], when its points are spread out
+//! enough to tell.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::PI;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    platform::collections::HashMap,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use super::sync::principal_axes;
+use crate::common::{
+    makeup::{Ship, hull::HullShape},
+    physics::base::PointNetwork,
+    scratch::ScratchBuffer,
+};
+
+/// How many cross-sections a hull is built from, less one.
+const LENGTH_SEGMENTS: u32 = 24;
+
+/// How many segments each side of a cross-section has, from deck to keel.
+const SIDE_SEGMENTS: u32 = 6;
+
+/// Ships slower than this keep facing the same way, if their hull has no
+/// reference points.
+const MIN_HEADING_SPEED: f32 = 0.5;
+
+/// Marks the child entity of a [Ship] holding its hull mesh.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct HullMesh;
+
+/// Hull meshes already built, by the shape they were built from.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct HullMeshCache {
+    meshes: HashMap<Vec<u32>, Handle<Mesh>>,
+}
+
+impl HullMeshCache {
+    /// The mesh of a hull shape, built if it is not cached yet.
+    pub fn get_or_build(&mut self, shape: &HullShape, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.meshes
+            .entry(cache_key(shape))
+            .or_insert_with(|| meshes.add(hull_mesh(shape)))
+            .clone()
+    }
+}
+
+/// Identifies the parts of a hull shape which affect its mesh.
+fn cache_key(shape: &HullShape) -> Vec<u32> {
+    [shape.length, shape.beam, shape.depth, shape.keel]
+        .into_iter()
+        .chain(
+            shape
+                .stations
+                .iter()
+                .flat_map(|station| [station.at, station.beam, station.deck_height]),
+        )
+        .map(f32::to_bits)
+        .collect()
+}
+
+/// Builds the mesh of a hull shape.
+///
+/// See the module documentation.
+pub fn hull_mesh(shape: &HullShape) -> Mesh {
+    let ring_len = SIDE_SEGMENTS * 2 + 1;
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    // Each cross-section, from port deck, down to the keel, up to starboard
+    // deck.
+    let rings = (0..=LENGTH_SEGMENTS)
+        .map(|slice| {
+            let at = slice as f32 / LENGTH_SEGMENTS as f32;
+            let station = shape.station_at(at);
+            let z = (0.5 - at) * shape.length;
+            let half_beam = station.beam * 0.5;
+            let deck = station.deck_height - shape.keel;
+
+            (0..ring_len)
+                .map(|step| {
+                    let angle = PI * step as f32 / (ring_len - 1) as f32;
+                    Vec3::new(
+                        -half_beam * angle.cos(),
+                        deck - station.deck_height * angle.sin(),
+                        z,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Hull sides.
+    for (slice, ring) in rings.iter().enumerate() {
+        for (step, point) in ring.iter().enumerate() {
+            positions.push(point.to_array());
+            uvs.push([
+                slice as f32 / LENGTH_SEGMENTS as f32,
+                step as f32 / (ring_len - 1) as f32,
+            ]);
+        }
+    }
+    for slice in 0..LENGTH_SEGMENTS {
+        for step in 0..ring_len - 1 {
+            let a = slice * ring_len + step;
+            let b = a + 1;
+            let c = a + ring_len;
+            let d = c + 1;
+            indices.extend([a, c, b, b, c, d]);
+        }
+    }
+
+    // Deck.
+    let deck_start = positions.len() as u32;
+    for (slice, ring) in rings.iter().enumerate() {
+        let u = slice as f32 / LENGTH_SEGMENTS as f32;
+        positions.push(ring[0].to_array());
+        positions.push(ring[ring.len() - 1].to_array());
+        uvs.extend([[u, 0.0], [u, 1.0]]);
+    }
+    for slice in 0..LENGTH_SEGMENTS {
+        let port = deck_start + slice * 2;
+        let starboard = port + 1;
+        indices.extend([
+            port,
+            starboard,
+            port + 2,
+            starboard,
+            starboard + 2,
+            port + 2,
+        ]);
+    }
+
+    // Stern and bow, each a fan around the middle of its cross-section.
+    for (ring, facing_aft) in [(&rings[0], true), (&rings[rings.len() - 1], false)] {
+        let center = ring.iter().sum::<Vec3>() / ring.len() as f32;
+        let center_idx = positions.len() as u32;
+        positions.push(center.to_array());
+        uvs.push([0.5, 0.5]);
+        for point in ring {
+            positions.push(point.to_array());
+            uvs.push([0.5, 0.5]);
+        }
+        for step in 0..ring_len - 1 {
+            let a = center_idx + 1 + step;
+            let b = a + 1;
+            if facing_aft {
+                indices.extend([center_idx, a, b]);
+            } else {
+                indices.extend([center_idx, b, a]);
+            }
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+    .with_computed_normals()
+}
+
+/// Which way, in world space, a hull should face, if it can be told, given
+/// which way it faced last.
+///
+/// The hull faces from stern to bow, or, lacking either, along the points'
+/// major axis, or where the ship is going. Its up is whichever other
+/// principal axis of the points is nearest to its last up, so that it rolls
+/// with the network; networks which are not spread out that much keep it
+/// upright.
+pub fn hull_rotation(
+    shape: &HullShape,
+    network: &PointNetwork,
+    current: Quat,
+    scratch: &mut ScratchBuffer<Vec3>,
+) -> Option<Quat> {
+    let point = |idx: Option<usize>| idx.and_then(|idx| network.points.get(idx));
+    let points = scratch.fill(network.points.iter().map(|point| point.pos));
+    let axes = principal_axes(points, network.center_of_mass());
+
+    let forward = match (point(shape.bow_point), point(shape.stern_point)) {
+        (Some(bow), Some(stern)) => bow.pos - stern.pos,
+        _ => {
+            let velocity = network.linear_velocity().with_y(0.0);
+            if velocity.length() < MIN_HEADING_SPEED {
+                return None;
+            }
+
+            match axes {
+                Some([major, ..]) if major.dot(velocity) < 0.0 => -major,
+                Some([major, ..]) => major,
+                None => velocity,
+            }
+        }
+    };
+    if forward.length_squared() == 0.0 {
+        return None;
+    }
+
+    let last_up = current * Vec3::Y;
+    let up = axes
+        .and_then(|[_, middle, minor]| {
+            [middle, minor]
+                .into_iter()
+                .map(|axis| axis.reject_from(forward).normalize_or_zero())
+                .max_by(|a, b| a.dot(last_up).abs().total_cmp(&b.dot(last_up).abs()))
+        })
+        .filter(|up| *up != Vec3::ZERO)
+        .map(|up| if up.dot(last_up) < 0.0 { -up } else { up })
+        .unwrap_or(Vec3::Y);
+
+    Some(Transform::IDENTITY.looking_to(forward, up).rotation)
+}
+
+/// Gives new ships in the world a hull, unless they already have a mesh.
+pub(super) fn spawn_hull_meshes(
+    mut commands: Commands,
+    mut cache: ResMut<HullMeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ships: Query<(Entity, &Ship), (Added<Ship>, With<PointNetwork>, Without<Mesh3d>)>,
+) {
+    for (entity, ship) in &ships {
+        let mesh = cache.get_or_build(&ship.makeup.make().hull, &mut meshes);
+
+        // Painted afterwards, along with ships' own meshes.
+        let material = materials.add(StandardMaterial {
+            perceptual_roughness: 0.8,
+            ..default()
+        });
+
+        commands.entity(entity).with_child((
+            HullMesh,
+            Name::new("Hull"),
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::default(),
+        ));
+    }
+}
+
+/// Snaps hulls to the point network of their ship.
+fn snap_hull_meshes(
+    mut scratch: Local<ScratchBuffer<Vec3>>,
+    mut hulls: Query<(&ChildOf, &mut Transform), With<HullMesh>>,
+    ships: Query<(&Ship, &PointNetwork, &GlobalTransform), Without<HullMesh>>,
+) {
+    for (child_of, mut transform) in &mut hulls {
+        let Ok((ship, network, ship_transform)) = ships.get(child_of.parent()) else {
+            continue;
+        };
+        if network.points.is_empty() {
+            continue;
+        }
+
+        let (_, ship_rotation, ship_translation) = ship_transform.to_scale_rotation_translation();
+        let inverse = ship_rotation.inverse();
+
+        transform.translation = inverse * (network.center_of_mass() - ship_translation);
+        let current = ship_rotation * transform.rotation;
+        if let Some(rotation) =
+            hull_rotation(&ship.makeup.make().hull, network, current, &mut scratch)
+        {
+            transform.rotation = inverse * rotation;
+        }
+    }
+}
+
+/// Ship hull rendering plugin.
+///
+/// Included in [ObjectRendererPlugin](super::ObjectRendererPlugin).
+pub struct HullRenderingPlugin;
+
+impl Plugin for HullRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HullMeshCache>();
+        app.add_systems(Update, (spawn_hull_meshes, snap_hull_meshes).chain());
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::common::physics::base::PhysPoint;
+
+    #[test]
+    fn hulls_face_from_stern_to_bow() {
+        let shape = HullShape::default().with_reference_points(0, 1);
+        let network = PointNetwork::from(
+            [
+                PhysPoint::from_pos(Vec3::new(4.0, 0.0, 0.0)),
+                PhysPoint::from_pos(Vec3::new(-4.0, 0.0, 0.0)),
+            ]
+            .into_iter(),
+        );
+
+        let mut scratch = ScratchBuffer::default();
+        let rotation = hull_rotation(&shape, &network, Quat::IDENTITY, &mut scratch).unwrap();
+        assert!((rotation * Vec3::NEG_Z).distance(Vec3::X) < 1e-4);
+
+        // Ships at rest, without reference points, can not be told apart.
+        let network = PointNetwork::from([PhysPoint::zero()].into_iter());
+        assert_eq!(
+            hull_rotation(
+                &HullShape::default(),
+                &network,
+                Quat::IDENTITY,
+                &mut scratch
+            ),
+            None
+        );
+
+        // Hulls roll with their network: here, one rolled about its length,
+        // with its points spread across the deck, and one at the keel.
+        let roll = Quat::from_rotation_x(0.3);
+        let network = PointNetwork::from(
+            [
+                Vec3::new(4.0, 0.0, 0.0),
+                Vec3::new(-4.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.5),
+                Vec3::new(0.0, 0.0, -1.5),
+                Vec3::new(0.0, -0.5, 0.0),
+            ]
+            .map(|pos| PhysPoint::from_pos(roll * pos))
+            .into_iter(),
+        );
+        let rotation = hull_rotation(&shape, &network, Quat::IDENTITY, &mut scratch).unwrap();
+        assert!((rotation * Vec3::NEG_Z).distance(Vec3::X) < 1e-4);
+        assert!((rotation * Vec3::Y).distance(roll * Vec3::Y) < 1e-3);
+
+        let mut cache = HullMeshCache::default();
+        let mut meshes = Assets::<Mesh>::default();
+        let first = cache.get_or_build(&shape, &mut meshes);
+        let second = cache.get_or_build(&HullShape::default(), &mut meshes);
+        assert_eq!(first, second);
+        assert_eq!(meshes.len(), 1);
+    }
+}
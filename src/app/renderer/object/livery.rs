@@ -0,0 +1,341 @@
+//! # Ship liveries
+//!
+//! Paints ships according to their [ShipLivery]: the hull in its primary
+//! color, with the secondary color laid out in its [PaintPattern], and a
+//! figurehead at the bow, if it has one.
+//!
+//! Patterns are drawn into a small texture, mapped onto the hull by its UVs,
+//! which run from stern to bow, and from the port deck, down to the keel, up
+//! to the starboard deck.
+//!
+//! Ships are painted on their own mesh, if they have one, or else on their
+//! [HullMesh]. They are painted again whenever their livery changes.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Figurehead models, by keyword; they are placeholder shapes for now.
+
+use std::f32::consts::{FRAC_PI_4, PI};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use super::hull::{HullMesh, spawn_hull_meshes};
+use crate::common::makeup::{
+    Ship,
+    hull::HullShape,
+    livery::{HullPaint, PaintColor, PaintPattern, ShipLivery},
+};
+
+/// Width of pattern textures, from stern to bow.
+const PATTERN_WIDTH: u32 = 64;
+
+/// Height of pattern textures, from deck to deck, around the keel.
+const PATTERN_HEIGHT: u32 = 32;
+
+/// How many bands [PaintPattern::Banded] hulls have.
+const BANDS: f32 = 8.0;
+
+/// The livery an entity was last painted with.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct PaintedLivery(pub ShipLivery);
+
+/// Marks the figurehead of a painted ship.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Figurehead;
+
+/// The paint color at a point of a hull, by its UV coordinates.
+pub fn paint_at(paint: &HullPaint, uv: Vec2) -> PaintColor {
+    // How far down from the deck to the keel, from 0.0 to 1.0.
+    let depth = (PI * uv.y.clamp(0.0, 1.0)).sin();
+
+    let secondary = match paint.pattern {
+        PaintPattern::Plain => false,
+        PaintPattern::Stripe => (0.15..0.3).contains(&depth),
+        PaintPattern::Waterline => depth > 0.6,
+        PaintPattern::Banded => (uv.x * BANDS).floor() as i32 % 2 == 1,
+    };
+
+    if secondary {
+        paint.secondary
+    } else {
+        paint.primary
+    }
+}
+
+/// A texture of a hull paint's pattern.
+pub fn pattern_image(paint: &HullPaint) -> Image {
+    let data = (0..PATTERN_HEIGHT)
+        .flat_map(|y| (0..PATTERN_WIDTH).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let uv = Vec2::new(
+                (x as f32 + 0.5) / PATTERN_WIDTH as f32,
+                (y as f32 + 0.5) / PATTERN_HEIGHT as f32,
+            );
+            let [r, g, b] = paint_at(paint, uv).0;
+            [r, g, b, 255]
+        })
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width: PATTERN_WIDTH,
+            height: PATTERN_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// A material painted with a livery, based on another material.
+///
+/// Plain hulls are only tinted; patterned ones are given a texture.
+pub fn livery_material(
+    livery: &ShipLivery,
+    base: StandardMaterial,
+    images: &mut Assets<Image>,
+) -> StandardMaterial {
+    let paint = &livery.paint;
+
+    match paint.pattern {
+        PaintPattern::Plain => StandardMaterial {
+            base_color: paint.primary.to_color(),
+            base_color_texture: None,
+            ..base
+        },
+        _ => StandardMaterial {
+            base_color: Color::WHITE,
+            base_color_texture: Some(images.add(pattern_image(paint))),
+            ..base
+        },
+    }
+}
+
+/// Where a figurehead sits on a hull, in the hull's space.
+fn figurehead_transform(shape: &HullShape) -> Transform {
+    let bow = shape.station_at(1.0);
+
+    Transform::from_xyz(0.0, bow.deck_height - shape.keel, -shape.length * 0.5)
+        .with_rotation(Quat::from_rotation_x(-FRAC_PI_4))
+}
+
+/// Paints ships whose livery changed, or which were never painted.
+fn paint_ship_liveries(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    ships: Query<(Entity, &Ship, Option<&Children>), Changed<Ship>>,
+    mut painted: Query<(
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&PaintedLivery>,
+        Option<&Children>,
+    )>,
+    hulls: Query<(), With<HullMesh>>,
+    figureheads: Query<(), With<Figurehead>>,
+) {
+    for (ship, Ship { makeup }, children) in &ships {
+        let hull = if painted.contains(ship) {
+            Some(ship)
+        } else {
+            children
+                .into_iter()
+                .flatten()
+                .copied()
+                .find(|&child| hulls.contains(child))
+        };
+        let Some((hull, (mut material, last, hull_children))) =
+            hull.and_then(|hull| Some((hull, painted.get_mut(hull).ok()?)))
+        else {
+            continue;
+        };
+
+        let livery = makeup.livery();
+        if last.is_some_and(|last| last.0 == *livery) {
+            continue;
+        }
+
+        // Materials may be shared, so every ship gets its own copy.
+        let base = materials.get(&material.0).cloned().unwrap_or_default();
+        material.0 = materials.add(livery_material(livery, base, &mut images));
+
+        for &child in hull_children.into_iter().flatten() {
+            if figureheads.contains(child) {
+                commands.entity(child).despawn();
+            }
+        }
+        if let Some(figurehead) = &livery.figurehead {
+            let shape = &makeup.make().hull;
+            let mesh = meshes.add(Capsule3d::new(shape.beam * 0.08, shape.depth * 0.5));
+            let material = materials.add(StandardMaterial {
+                base_color: livery.paint.secondary.to_color(),
+                perceptual_roughness: 0.6,
+                ..default()
+            });
+
+            commands.entity(hull).with_child((
+                Figurehead,
+                Name::new(format!("Figurehead ({figurehead})")),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                figurehead_transform(shape),
+            ));
+        }
+
+        commands.entity(hull).insert(PaintedLivery(livery.clone()));
+    }
+}
+
+/// Ship livery rendering plugin.
+///
+/// Included in [ObjectRendererPlugin](super::ObjectRendererPlugin).
+pub struct LiveryRenderingPlugin;
+
+impl Plugin for LiveryRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, paint_ship_liveries.after(spawn_hull_meshes));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::common::{
+        fixtures::makeup,
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    const PRIMARY: PaintColor = PaintColor([200, 0, 0]);
+    const SECONDARY: PaintColor = PaintColor([0, 0, 200]);
+
+    fn paint(pattern: PaintPattern) -> HullPaint {
+        HullPaint {
+            primary: PRIMARY,
+            secondary: SECONDARY,
+            pattern,
+        }
+    }
+
+    #[test]
+    fn patterns_lay_out_the_secondary_color() {
+        let deck = Vec2::new(0.5, 0.0);
+        let stripe = Vec2::new(0.5, 0.07);
+        let keel = Vec2::new(0.5, 0.5);
+
+        let plain = paint(PaintPattern::Plain);
+        assert!(
+            [deck, stripe, keel]
+                .iter()
+                .all(|&uv| paint_at(&plain, uv) == PRIMARY)
+        );
+
+        let striped = paint(PaintPattern::Stripe);
+        assert_eq!(paint_at(&striped, deck), PRIMARY);
+        assert_eq!(paint_at(&striped, stripe), SECONDARY);
+        assert_eq!(paint_at(&striped, keel), PRIMARY);
+
+        let waterline = paint(PaintPattern::Waterline);
+        assert_eq!(paint_at(&waterline, deck), PRIMARY);
+        assert_eq!(paint_at(&waterline, keel), SECONDARY);
+        assert_eq!(paint_at(&waterline, Vec2::new(0.5, 0.95)), PRIMARY);
+
+        // Bands alternate from stern to bow.
+        let banded = paint(PaintPattern::Banded);
+        let bands: Vec<_> = (0..4)
+            .map(|band| paint_at(&banded, Vec2::new((band as f32 + 0.5) / BANDS, 0.5)))
+            .collect();
+        assert_eq!(bands, [PRIMARY, SECONDARY, PRIMARY, SECONDARY]);
+
+        let image = pattern_image(&striped);
+        assert_eq!(image.width(), PATTERN_WIDTH);
+        assert_eq!(image.height(), PATTERN_HEIGHT);
+    }
+
+    #[test]
+    fn ships_are_repainted_when_their_livery_changes() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<Assets<Mesh>>();
+        app.init_resource::<Assets<Image>>();
+        app.init_resource::<Assets<StandardMaterial>>();
+        app.add_plugins((
+            super::super::hull::HullRenderingPlugin,
+            LiveryRenderingPlugin,
+        ));
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                Ship {
+                    makeup: makeup(vec![]),
+                },
+                PointNetwork::from([PhysPoint::zero()].into_iter()),
+                Transform::default(),
+            ))
+            .id();
+        app.update();
+
+        let hull_material = |app: &mut App| {
+            let mut hulls = app
+                .world_mut()
+                .query_filtered::<&MeshMaterial3d<StandardMaterial>, With<HullMesh>>();
+            let handle = hulls.single(app.world()).unwrap().0.clone();
+            app.world()
+                .resource::<Assets<StandardMaterial>>()
+                .get(&handle)
+                .unwrap()
+                .clone()
+        };
+        let figurehead_count = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<(), With<Figurehead>>()
+                .iter(app.world())
+                .count()
+        };
+
+        let default_paint = HullPaint::default();
+        assert_eq!(
+            hull_material(&mut app).base_color,
+            default_paint.primary.to_color()
+        );
+        assert_eq!(figurehead_count(&mut app), 0);
+
+        {
+            let mut ship = app.world_mut().get_mut::<Ship>(ship).unwrap();
+            let livery = ship.makeup.livery_mut();
+            livery.paint = paint(PaintPattern::Waterline);
+            livery.figurehead = Some("mermaid".into());
+        }
+        app.update();
+
+        let material = hull_material(&mut app);
+        assert_eq!(material.base_color, Color::WHITE);
+        assert!(material.base_color_texture.is_some());
+        assert_eq!(figurehead_count(&mut app), 1);
+
+        // Taking the figurehead off.
+        {
+            let mut ship = app.world_mut().get_mut::<Ship>(ship).unwrap();
+            ship.makeup.livery_mut().figurehead = None;
+        }
+        app.update();
+        assert_eq!(figurehead_count(&mut app), 0);
+    }
+}
@@ -20,6 +20,10 @@
 
 use bevy::prelude::*;
 
+pub mod hull; // Procedural ship hull meshes
+pub mod livery; // Hull paint and figureheads
+pub mod sync; // Transforms following point networks
+
 /// Camera target component.
 #[derive(Component, Default)]
 pub struct CameraFocus {
@@ -49,6 +53,11 @@ pub struct ObjectRendererPlugin;
 
 impl Plugin for ObjectRendererPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (camera_focus_system,));
+        app.add_plugins((
+            hull::HullRenderingPlugin,
+            livery::LiveryRenderingPlugin,
+            sync::PointNetTransformSyncPlugin,
+        ));
+        app.add_systems(Update, camera_focus_system);
     }
 }
@@ -0,0 +1,219 @@
+//! # Point network transform sync
+//!
+//! An entity's [Transform] does not follow its [PointNetwork] on its own;
+//! physics only ever moves the points. Entities with a
+//! [PointNetTransformSync] have their transform centered on the average of
+//! their points every frame, and turned to match them, so that their mesh
+//! moves along with the network.
+//!
+//! How the entity is turned is up to its [SyncOrientation]: either towards a
+//! pair of reference points, which suits networks whose points keep their
+//! roles, such as soft body cubes, or along the principal axes of the
+//! points, which suits networks without any obvious front or top.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::{
+    diagnostics::AllocationScope, physics::base::PointNetwork, scratch::ScratchBuffer,
+};
+
+/// How many power iterations are used to find each principal axis.
+const POWER_ITERATIONS: usize = 24;
+
+/// Variances below this are considered to be no spread at all.
+const MIN_VARIANCE: f32 = 1e-6;
+
+/// How a [PointNetTransformSync] turns its entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyncOrientation {
+    /// The transform is only moved, never turned.
+    None,
+
+    /// The transform faces the `front` point, with its up towards the `up`
+    /// point.
+    ReferencePoints { front: usize, up: usize },
+
+    /// The transform faces along the axis the points are most spread out
+    /// along, with its up along the axis they are least spread out along.
+    ///
+    /// Each axis is kept pointing the same way it pointed last frame, so the
+    /// transform does not flip about. Networks which are about as spread out
+    /// along every axis, such as cubes, have no stable principal axes; give
+    /// them reference points instead.
+    PrincipalAxes,
+}
+
+/// Keeps an entity's [Transform] on its [PointNetwork].
+///
+/// See the module documentation.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct PointNetTransformSync {
+    pub orientation: SyncOrientation,
+
+    /// A rotation applied to the transform after it is turned, in its own
+    /// space, e.g. to line a mesh up with the points.
+    pub rotation_offset: Quat,
+}
+
+impl Default for PointNetTransformSync {
+    fn default() -> Self {
+        Self::new(SyncOrientation::PrincipalAxes)
+    }
+}
+
+impl PointNetTransformSync {
+    pub fn new(orientation: SyncOrientation) -> Self {
+        Self {
+            orientation,
+            rotation_offset: Quat::IDENTITY,
+        }
+    }
+
+    /// Faces the `front` point, with up towards the `up` point.
+    pub fn reference_points(front: usize, up: usize) -> Self {
+        Self::new(SyncOrientation::ReferencePoints { front, up })
+    }
+
+    pub fn with_rotation_offset(mut self, rotation_offset: Quat) -> Self {
+        self.rotation_offset = rotation_offset;
+        self
+    }
+
+    /// The rotation the points call for, if it can be told, given the
+    /// current rotation.
+    pub fn rotation(&self, points: &[Vec3], center: Vec3, current: Quat) -> Option<Quat> {
+        let (forward, up) = match self.orientation {
+            SyncOrientation::None => return None,
+            SyncOrientation::ReferencePoints { front, up } => {
+                (points.get(front)? - center, points.get(up)? - center)
+            }
+            SyncOrientation::PrincipalAxes => {
+                let [major, _, minor] = principal_axes(points, center)?;
+                let keep_sign = |axis: Vec3, last: Vec3| {
+                    if axis.dot(last) < 0.0 { -axis } else { axis }
+                };
+                (
+                    keep_sign(major, current * Vec3::NEG_Z),
+                    keep_sign(minor, current * Vec3::Y),
+                )
+            }
+        };
+
+        if forward.length_squared() < MIN_VARIANCE || up.length_squared() < MIN_VARIANCE {
+            return None;
+        }
+
+        let rotation = Transform::IDENTITY.looking_to(forward, up).rotation;
+        Some(rotation * self.rotation_offset)
+    }
+}
+
+/// The principal axes of a set of points around their center, from the one
+/// they are most spread out along to the one they are least spread out along.
+///
+/// [None] if the points are not spread out at all.
+pub fn principal_axes(points: &[Vec3], center: Vec3) -> Option<[Vec3; 3]> {
+    let covariance = points.iter().fold(Mat3::ZERO, |sum, point| {
+        let offset = *point - center;
+        sum + Mat3::from_cols(offset * offset.x, offset * offset.y, offset * offset.z)
+    });
+
+    let dominant = |matrix: Mat3, seed: Vec3| -> Option<(Vec3, f32)> {
+        let mut axis = seed;
+        for _ in 0..POWER_ITERATIONS {
+            axis = (matrix * axis).try_normalize()?;
+        }
+        let variance = axis.dot(matrix * axis);
+        (variance > MIN_VARIANCE).then_some((axis, variance))
+    };
+
+    let (major, variance) = dominant(covariance, Vec3::new(1.0, 0.7, 0.3).normalize())?;
+
+    // Remove the major axis, so the next dominant one is found.
+    let deflated =
+        covariance - Mat3::from_cols(major * major.x, major * major.y, major * major.z) * variance;
+    let seed = Vec3::new(0.3, 0.7, 1.0)
+        .reject_from_normalized(major)
+        .try_normalize()
+        .unwrap_or_else(|| major.any_orthonormal_vector());
+    let middle = dominant(deflated, seed)
+        .map(|(axis, _)| axis)
+        .unwrap_or_else(|| major.any_orthonormal_vector());
+
+    Some([major, middle, major.cross(middle).normalize()])
+}
+
+fn sync_point_net_transforms(
+    mut scratch: Local<ScratchBuffer<Vec3>>,
+    mut query: Query<(&mut Transform, &PointNetwork, &PointNetTransformSync)>,
+) {
+    let _scope = AllocationScope::new("sync_point_net_transforms");
+
+    for (mut transform, network, sync) in &mut query {
+        if network.points.is_empty() {
+            continue;
+        }
+
+        let points = scratch.fill(network.points.iter().map(|point| point.pos));
+        let center = points.iter().sum::<Vec3>() / points.len() as f32;
+
+        transform.translation = center;
+        if let Some(rotation) = sync.rotation(points, center, transform.rotation) {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// Point network transform sync plugin.
+///
+/// Included in [ObjectRendererPlugin](super::ObjectRendererPlugin).
+pub struct PointNetTransformSyncPlugin;
+
+impl Plugin for PointNetTransformSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, sync_point_net_transforms);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn principal_axes_follow_the_spread() {
+        // A long, wide, flat box, turned a quarter turn about Y.
+        let turn = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let points = [-1.0f32, 1.0]
+            .into_iter()
+            .flat_map(|x| [-1.0f32, 1.0].map(|y| (x, y)))
+            .flat_map(|(x, y)| [-1.0f32, 1.0].map(|z| Vec3::new(x * 2.0, y * 0.5, z * 4.0)))
+            .map(|point| turn * point)
+            .collect::<Vec<_>>();
+
+        let [major, middle, minor] = principal_axes(&points, Vec3::ZERO).unwrap();
+        assert!(major.dot(Vec3::X).abs() > 0.999);
+        assert!(middle.dot(Vec3::Z).abs() > 0.999);
+        assert!(minor.dot(Vec3::Y).abs() > 0.999);
+
+        // Facing along the major axis, the same way as before.
+        let sync = PointNetTransformSync::default();
+        let rotation = sync.rotation(&points, Vec3::ZERO, turn).unwrap();
+        assert!((rotation * Vec3::NEG_Z).distance(turn * Vec3::NEG_Z) < 1e-3);
+        assert!((rotation * Vec3::Y).distance(Vec3::Y) < 1e-3);
+
+        assert_eq!(principal_axes(&[Vec3::ONE; 4], Vec3::ONE), None);
+    }
+}
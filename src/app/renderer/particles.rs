@@ -0,0 +1,526 @@
+//! # Particle effects
+//!
+//! Draws the [FxEvent]s cued by the simulation as bursts of particles: flat,
+//! camera-facing sprites (billboards), simulated on the CPU.
+//!
+//! Every particle lives in a slot of the [ParticlePool], which is allocated
+//! once and never grows, along with one hidden sprite entity per slot. Bursts
+//! take free slots, and particles give them back when they fade out, so busy
+//! battles cause no allocation spikes; when the pool is full, new particles
+//! are simply dropped.
+//!
+//! How many particles a burst has follows the
+//! [particle density](super::quality::RenderQuality::particle_density).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::Rng;
+
+use super::quality::RenderQuality;
+use crate::common::fx::{FxEvent, FxKind};
+
+/// How many particles there can be at once.
+const POOL_SIZE: usize = 2048;
+
+/// The largest effect scale drawn; bigger effects are drawn this big.
+const MAX_SCALE: f32 = 4.0;
+
+/// The width and height of the particle sprite texture, in pixels.
+const SPRITE_SIZE: u32 = 32;
+
+/// How a particle looks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleLook {
+    /// A bright, glowing flash.
+    Flash,
+
+    /// A puff of grey smoke.
+    Smoke,
+
+    /// Water spray.
+    Spray,
+}
+
+impl ParticleLook {
+    const ALL: [Self; 3] = [Self::Flash, Self::Smoke, Self::Spray];
+
+    fn material(self) -> StandardMaterial {
+        let (color, alpha_mode) = match self {
+            Self::Flash => (Color::srgb(1.0, 0.75, 0.35), AlphaMode::Add),
+            Self::Smoke => (Color::srgba(0.35, 0.35, 0.35, 0.6), AlphaMode::Blend),
+            Self::Spray => (Color::srgba(0.88, 0.94, 0.96, 0.75), AlphaMode::Blend),
+        };
+
+        StandardMaterial {
+            base_color: color,
+            alpha_mode,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        }
+    }
+}
+
+/// A single particle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub look: ParticleLook,
+    pub pos: Vec3,
+    pub vel: Vec3,
+
+    /// How long the particle has been around, in seconds.
+    pub age: f32,
+
+    /// How long the particle lasts, in seconds.
+    pub lifetime: f32,
+
+    /// The width of the particle when it appears, and when it fades out.
+    pub size: (f32, f32),
+
+    /// Vertical acceleration; negative to fall, positive to rise.
+    pub lift: f32,
+
+    /// Fraction of its velocity the particle loses every second.
+    pub drag: f32,
+}
+
+impl Particle {
+    /// The width of the particle at its current age.
+    pub fn current_size(&self) -> f32 {
+        let progress = (self.age / self.lifetime).clamp(0.0, 1.0);
+        self.size.0.lerp(self.size.1, progress)
+    }
+
+    fn step(&mut self, delta_secs: f32) {
+        self.age += delta_secs;
+        self.vel.y += self.lift * delta_secs;
+        self.vel *= (1.0 - self.drag * delta_secs).max(0.0);
+        self.pos += self.vel * delta_secs;
+    }
+}
+
+/// A fixed set of particle slots, reused as particles come and go.
+///
+/// See the module documentation.
+#[derive(Resource, Clone, Debug)]
+pub struct ParticlePool {
+    slots: Vec<Option<Particle>>,
+
+    /// Indices of the empty slots.
+    free: Vec<usize>,
+}
+
+impl Default for ParticlePool {
+    fn default() -> Self {
+        Self::new(POOL_SIZE)
+    }
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![None; capacity],
+            // Lowest slots are taken first.
+            free: (0..capacity).rev().collect(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// How many particles are alive.
+    pub fn len(&self) -> usize {
+        self.capacity() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.len() == self.capacity()
+    }
+
+    /// Puts a particle in a free slot, returning the slot, or [None] if the
+    /// pool is full.
+    pub fn emit(&mut self, particle: Particle) -> Option<usize> {
+        let slot = self.free.pop()?;
+        self.slots[slot] = Some(particle);
+        Some(slot)
+    }
+
+    /// Moves every particle along, freeing the slots of those which faded out.
+    pub fn step(&mut self, delta_secs: f32) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            let Some(particle) = slot else {
+                continue;
+            };
+
+            particle.step(delta_secs);
+            if particle.age >= particle.lifetime {
+                *slot = None;
+                self.free.push(index);
+            }
+        }
+    }
+
+    /// Every slot, and the particle in it, if any.
+    pub fn slots(&self) -> impl Iterator<Item = (usize, Option<&Particle>)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| (index, slot.as_ref()))
+    }
+}
+
+/// A burst of particles, part of an effect.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleBurst {
+    pub look: ParticleLook,
+
+    /// How many particles there are in an effect of scale 1.0, at full
+    /// particle density.
+    pub count: u32,
+
+    /// How fast the particles leave.
+    pub speed: f32,
+
+    /// How far off the effect's direction particles may leave, between 0.0
+    /// (all along it) and 1.0 (in any direction).
+    pub spread: f32,
+
+    /// The shortest and longest a particle lasts, in seconds.
+    pub lifetime: (f32, f32),
+
+    pub size: (f32, f32),
+    pub lift: f32,
+    pub drag: f32,
+}
+
+impl ParticleBurst {
+    /// The bursts an effect is drawn with.
+    pub fn for_effect(kind: FxKind) -> &'static [ParticleBurst] {
+        const FLASH: ParticleBurst = ParticleBurst {
+            look: ParticleLook::Flash,
+            count: 6,
+            speed: 6.0,
+            spread: 0.25,
+            lifetime: (0.08, 0.15),
+            size: (1.2, 0.4),
+            lift: 0.0,
+            drag: 4.0,
+        };
+        const SMOKE: ParticleBurst = ParticleBurst {
+            look: ParticleLook::Smoke,
+            count: 12,
+            speed: 3.0,
+            spread: 0.4,
+            lifetime: (1.5, 3.0),
+            size: (0.8, 3.5),
+            lift: 0.6,
+            drag: 1.2,
+        };
+        const SPRAY: ParticleBurst = ParticleBurst {
+            look: ParticleLook::Spray,
+            count: 16,
+            speed: 7.0,
+            spread: 0.35,
+            lifetime: (0.6, 1.2),
+            size: (0.5, 1.2),
+            lift: -9.8,
+            drag: 0.3,
+        };
+
+        match kind {
+            FxKind::MuzzleFlash => &[FLASH, SMOKE],
+            FxKind::Splash => &[SPRAY],
+            FxKind::ExplosionSmoke => &[
+                ParticleBurst {
+                    count: 16,
+                    speed: 14.0,
+                    spread: 1.0,
+                    lifetime: (0.15, 0.3),
+                    size: (3.0, 1.0),
+                    ..FLASH
+                },
+                ParticleBurst {
+                    count: 32,
+                    speed: 6.0,
+                    spread: 0.8,
+                    lifetime: (3.0, 6.0),
+                    size: (2.0, 8.0),
+                    ..SMOKE
+                },
+                ParticleBurst {
+                    count: 24,
+                    speed: 12.0,
+                    ..SPRAY
+                },
+            ],
+            FxKind::EngineWake => &[ParticleBurst {
+                count: 3,
+                speed: 2.0,
+                spread: 0.5,
+                lifetime: (0.8, 1.5),
+                size: (0.6, 1.8),
+                lift: -2.0,
+                drag: 1.0,
+                ..SPRAY
+            }],
+        }
+    }
+
+    /// How many particles this burst has, for an effect of a scale, at a
+    /// particle density.
+    pub fn count_for(&self, scale: f32, density: f32) -> u32 {
+        (self.count as f32 * scale.clamp(0.0, MAX_SCALE) * density).round() as u32
+    }
+
+    /// Emits the particles of this burst into a pool.
+    fn emit(&self, effect: &FxEvent, density: f32, pool: &mut ParticlePool, rng: &mut impl Rng) {
+        let scale = effect.scale.clamp(0.0, MAX_SCALE);
+        let reach = scale.sqrt();
+        let direction = if effect.direction == Vec3::ZERO {
+            Vec3::Y
+        } else {
+            effect.direction
+        };
+
+        for _ in 0..self.count_for(scale, density) {
+            let scatter = Vec3::new(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            );
+            let heading = (direction + scatter * self.spread * 2.0).normalize_or(direction);
+
+            let emitted = pool.emit(Particle {
+                look: self.look,
+                pos: effect.at,
+                vel: heading * self.speed * reach * rng.random_range(0.5..1.0),
+                age: 0.0,
+                lifetime: rng.random_range(self.lifetime.0..=self.lifetime.1),
+                size: (self.size.0 * reach, self.size.1 * reach),
+                lift: self.lift,
+                drag: self.drag,
+            });
+            if emitted.is_none() {
+                return;
+            }
+        }
+    }
+}
+
+/// Marks the sprite entity of a [ParticlePool] slot.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ParticleSprite;
+
+/// The sprite entity of every [ParticlePool] slot, and the materials of
+/// every [ParticleLook].
+#[derive(Resource, Clone, Debug)]
+struct ParticleSprites {
+    entities: Vec<Entity>,
+    materials: Vec<(ParticleLook, Handle<StandardMaterial>)>,
+}
+
+impl ParticleSprites {
+    fn material(&self, look: ParticleLook) -> &Handle<StandardMaterial> {
+        &self
+            .materials
+            .iter()
+            .find(|(of, _)| *of == look)
+            .expect("every particle look has a material")
+            .1
+    }
+}
+
+/// A soft, round dot, which fades out towards its edges.
+fn soft_dot_image() -> Image {
+    let half = SPRITE_SIZE as f32 / 2.0;
+    let data = (0..SPRITE_SIZE)
+        .flat_map(|y| (0..SPRITE_SIZE).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let offset = Vec2::new(x as f32 + 0.5 - half, y as f32 + 0.5 - half) / half;
+            let alpha = (1.0 - offset.length()).clamp(0.0, 1.0).powi(2);
+            [255, 255, 255, (alpha * 255.0) as u8]
+        })
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width: SPRITE_SIZE,
+            height: SPRITE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn spawn_particle_sprites(
+    mut commands: Commands,
+    pool: Res<ParticlePool>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let texture = images.add(soft_dot_image());
+    let materials = ParticleLook::ALL
+        .into_iter()
+        .map(|look| {
+            let material = materials.add(StandardMaterial {
+                base_color_texture: Some(texture.clone()),
+                ..look.material()
+            });
+            (look, material)
+        })
+        .collect::<Vec<_>>();
+
+    let mesh = meshes.add(Rectangle::new(1.0, 1.0));
+    let entities = (0..pool.capacity())
+        .map(|_| {
+            commands
+                .spawn((
+                    ParticleSprite,
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(materials[0].1.clone()),
+                    Transform::default(),
+                    Visibility::Hidden,
+                    NotShadowCaster,
+                ))
+                .id()
+        })
+        .collect();
+
+    commands.insert_resource(ParticleSprites {
+        entities,
+        materials,
+    });
+}
+
+fn emit_particles(
+    mut effects: EventReader<FxEvent>,
+    quality: Option<Res<RenderQuality>>,
+    mut pool: ResMut<ParticlePool>,
+) {
+    let density = quality.map_or(1.0, |quality| quality.particle_density);
+    let mut rng = rand::rng();
+
+    for effect in effects.read() {
+        for burst in ParticleBurst::for_effect(effect.kind) {
+            burst.emit(effect, density, &mut pool, &mut rng);
+        }
+    }
+}
+
+fn simulate_particles(time: Res<Time>, mut pool: ResMut<ParticlePool>) {
+    pool.step(time.delta_secs());
+}
+
+/// Moves particle sprites onto their particles, turned to face the camera,
+/// and hides those of empty slots.
+fn draw_particles(
+    pool: Res<ParticlePool>,
+    sprites: Res<ParticleSprites>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &mut MeshMaterial3d<StandardMaterial>,
+        ),
+        With<ParticleSprite>,
+    >,
+) {
+    let facing = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .max_by_key(|(camera, _)| camera.order)
+        .map_or(Quat::IDENTITY, |(_, transform)| transform.rotation());
+
+    for (slot, particle) in pool.slots() {
+        let Ok((mut transform, mut visibility, mut material)) =
+            query.get_mut(sprites.entities[slot])
+        else {
+            continue;
+        };
+
+        let Some(particle) = particle else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        visibility.set_if_neq(Visibility::Visible);
+        *transform = Transform::from_translation(particle.pos)
+            .with_rotation(facing)
+            .with_scale(Vec3::splat(particle.current_size()));
+
+        let look_material = sprites.material(particle.look);
+        if material.0 != *look_material {
+            material.0 = look_material.clone();
+        }
+    }
+}
+
+/// Particle effect rendering plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticlePool>();
+        app.add_systems(Startup, spawn_particle_sprites);
+        app.add_systems(
+            Update,
+            (emit_particles, simulate_particles, draw_particles).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_reuses_slots_without_growing() {
+        let mut pool = ParticlePool::new(8);
+        let effect = FxEvent::new(FxKind::Splash, Vec3::ZERO);
+        let burst = ParticleBurst::for_effect(FxKind::Splash)[0];
+        let mut rng = rand::rng();
+
+        // More particles than fit; the rest are dropped.
+        assert!(burst.count_for(1.0, 1.0) > 8);
+        burst.emit(&effect, 1.0, &mut pool, &mut rng);
+        assert_eq!(pool.len(), 8);
+
+        // Every particle fades out, freeing its slot.
+        pool.step(burst.lifetime.1 + 0.1);
+        assert!(pool.is_empty());
+
+        burst.emit(&effect, 1.0, &mut pool, &mut rng);
+        assert_eq!(pool.len(), 8);
+        assert_eq!(pool.capacity(), 8);
+
+        // Bursts thin out at lower particle densities.
+        assert_eq!(burst.count_for(1.0, 0.25), burst.count / 4);
+        assert_eq!(burst.count_for(100.0, 1.0), burst.count_for(MAX_SCALE, 1.0));
+    }
+}
@@ -0,0 +1,445 @@
+//! # Post-processing stack
+//!
+//! Configurable post-processing applied on top of in-game 3D cameras:
+//! tonemapping selection, a subtle vignette, and gameplay-driven overlays
+//! (being underwater, or the player's ship taking a hit).
+//!
+//! Tonemapping is delegated to Bevy's own tonemapping pass. Everything else
+//! is drawn in a single full-screen pass which runs right after it.
+//!
+//! Overlays are driven by [PostProcessEvent]s, and fade out on their own
+//! over time. This plugin sends them itself when the player's ship is
+//! damaged, and when the [PlayerCamera] dips under the water; other code may
+//! send more.
+//!
+//! The vignette, and whether overlays are shown at all, follow the player's
+//! [GraphicsSettings](crate::app::settings::GraphicsSettings).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{
+    asset::embedded_asset,
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+        tonemapping::Tonemapping,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        RenderApp,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponentPlugin, UniformComponentPlugin,
+        },
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::ViewTarget,
+    },
+};
+
+use crate::{
+    app::{camera::PlayerCamera, settings::Settings},
+    common::{
+        damage::{DamageEvent, Health},
+        physics::water::WaterPhysics,
+        player::PlayerControlled,
+    },
+};
+
+/// Damage to the player's ship, as a fraction of its health, which shows
+/// the damage overlay at full intensity.
+const FULL_DAMAGE_FRACTION: f32 = 0.25;
+
+/// Asset path of the embedded post-processing shader.
+const SHADER_ASSET_PATH: &str = "embedded://loot_and_roam/app/renderer/postprocess.wgsl";
+
+/// User-facing post-processing configuration.
+///
+/// Applied to every 3D camera every frame, so changing it takes effect
+/// immediately. The vignette and overlays are set from the player's
+/// [Settings] whenever they change.
+#[derive(Resource, Clone, Debug)]
+pub struct PostProcessConfig {
+    /// Which tonemapping algorithm to use.
+    pub tonemapping: Tonemapping,
+
+    /// Strength of the vignette, between 0.0 (disabled) and 1.0.
+    pub vignette: f32,
+
+    /// Whether gameplay-driven overlays (underwater, damage) are shown.
+    pub overlays: bool,
+
+    /// How much of the damage overlay fades away per second.
+    pub damage_decay: f32,
+
+    /// How quickly the underwater overlay fades in and out, per second.
+    pub underwater_fade: f32,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            tonemapping: Tonemapping::TonyMcMapface,
+            vignette: 0.25,
+            overlays: true,
+            damage_decay: 0.8,
+            underwater_fade: 4.0,
+        }
+    }
+}
+
+/// Gameplay events which drive the post-processing overlays.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum PostProcessEvent {
+    /// The player's ship took damage.
+    ///
+    /// The intensity is between 0.0 and 1.0; weaker hits will not override
+    /// the overlay of a stronger, still fading, hit.
+    Damage(f32),
+
+    /// The camera went underwater (true) or back above water (false).
+    Submerged(bool),
+}
+
+/// Current state of the gameplay-driven overlays.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PostProcessOverlays {
+    /// Current intensity of the damage overlay.
+    pub damage: f32,
+
+    /// Current intensity of the underwater overlay.
+    pub underwater: f32,
+
+    /// Whether the camera is currently submerged.
+    pub submerged: bool,
+}
+
+pub use effects::PostProcessEffects;
+
+// [NOTE] ShaderType generates layout checks which newer compilers flag as
+// dead code; the uniform lives in its own module to scope the allow.
+#[allow(dead_code)]
+mod effects {
+    use bevy::{
+        prelude::*,
+        render::{extract_component::ExtractComponent, render_resource::ShaderType},
+    };
+
+    /// Per-camera post-processing parameters, uploaded to the GPU.
+    ///
+    /// Inserted automatically on every 3D camera; there is no need to add it
+    /// manually.
+    #[derive(Component, Clone, Copy, Default, Debug, ExtractComponent, ShaderType)]
+    pub struct PostProcessEffects {
+        /// Vignette strength.
+        pub vignette: f32,
+
+        /// Damage overlay intensity.
+        pub damage: f32,
+
+        /// Underwater overlay intensity.
+        pub underwater: f32,
+
+        // [NOTE] Pads the uniform to 16 bytes, as required by WebGL2.
+        pub(super) _padding: f32,
+    }
+}
+
+fn player_damage_overlay(
+    mut damage: EventReader<DamageEvent>,
+    players: Query<&Health, With<PlayerControlled>>,
+    mut events: EventWriter<PostProcessEvent>,
+) {
+    for event in damage.read() {
+        let Ok(health) = players.get(event.target) else {
+            continue;
+        };
+
+        if health.max > 0.0 {
+            events.write(PostProcessEvent::Damage(
+                event.amount / (health.max * FULL_DAMAGE_FRACTION),
+            ));
+        }
+    }
+}
+
+fn camera_submersion(
+    cameras: Query<&GlobalTransform, With<PlayerCamera>>,
+    players: Query<&WaterPhysics, With<PlayerControlled>>,
+    overlays: Res<PostProcessOverlays>,
+    mut events: EventWriter<PostProcessEvent>,
+) {
+    let water_level = players.single().map_or(0.0, |water| water.water_level);
+    let submerged = cameras
+        .iter()
+        .any(|camera| camera.translation().y < water_level);
+
+    if submerged != overlays.submerged {
+        events.write(PostProcessEvent::Submerged(submerged));
+    }
+}
+
+fn apply_post_process_settings(settings: Res<Settings>, mut config: ResMut<PostProcessConfig>) {
+    config.vignette = settings.graphics.vignette;
+    config.overlays = settings.graphics.screen_effects;
+}
+
+fn ev_post_process(
+    mut events: EventReader<PostProcessEvent>,
+    mut overlays: ResMut<PostProcessOverlays>,
+) {
+    for event in events.read() {
+        match *event {
+            PostProcessEvent::Damage(intensity) => {
+                overlays.damage = overlays.damage.max(intensity.clamp(0.0, 1.0));
+            }
+            PostProcessEvent::Submerged(submerged) => {
+                overlays.submerged = submerged;
+            }
+        }
+    }
+}
+
+fn update_post_process_overlays(
+    time: Res<Time>,
+    config: Res<PostProcessConfig>,
+    mut overlays: ResMut<PostProcessOverlays>,
+) {
+    let delta_secs = time.delta_secs();
+
+    overlays.damage = (overlays.damage - config.damage_decay * delta_secs).max(0.0);
+
+    let underwater_target = if overlays.submerged { 1.0 } else { 0.0 };
+    let underwater_step = config.underwater_fade * delta_secs;
+    overlays.underwater +=
+        (underwater_target - overlays.underwater).clamp(-underwater_step, underwater_step);
+}
+
+fn sync_camera_post_process(
+    mut commands: Commands,
+    config: Res<PostProcessConfig>,
+    overlays: Res<PostProcessOverlays>,
+    mut query: Query<(Entity, &mut Tonemapping, Option<&mut PostProcessEffects>), With<Camera3d>>,
+) {
+    let effects = PostProcessEffects {
+        vignette: config.vignette.clamp(0.0, 1.0),
+        damage: if config.overlays {
+            overlays.damage
+        } else {
+            0.0
+        },
+        underwater: if config.overlays {
+            overlays.underwater
+        } else {
+            0.0
+        },
+        _padding: 0.0,
+    };
+
+    for (camera_id, mut tonemapping, camera_effects) in query.iter_mut() {
+        tonemapping.set_if_neq(config.tonemapping);
+
+        match camera_effects {
+            Some(mut camera_effects) => *camera_effects = effects,
+            None => {
+                commands.entity(camera_id).insert(effects);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct PostProcessLabel;
+
+/// Render graph node which draws the post-processing pass.
+#[derive(Default)]
+struct PostProcessNode;
+
+impl ViewNode for PostProcessNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static DynamicUniformIndex<PostProcessEffects>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, effects_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let post_process_pipeline = world.resource::<PostProcessPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        // The pipeline may still be compiling.
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        let effects_uniforms = world.resource::<ComponentUniforms<PostProcessEffects>>();
+        let Some(effects_binding) = effects_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "lnr_post_process_bind_group",
+            &post_process_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &post_process_pipeline.sampler,
+                effects_binding.clone(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("lnr_post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[effects_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+/// GPU resources of the post-processing pass.
+#[derive(Resource)]
+struct PostProcessPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for PostProcessPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "lnr_post_process_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<PostProcessEffects>(true),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let shader = world.load_asset(SHADER_ASSET_PATH);
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("lnr_post_process_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format: TextureFormat::bevy_default(),
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
+
+/// Post-processing plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "postprocess.wgsl");
+
+        app.init_resource::<PostProcessConfig>();
+        app.init_resource::<PostProcessOverlays>();
+        app.add_event::<PostProcessEvent>();
+        app.add_systems(
+            Update,
+            (
+                apply_post_process_settings.run_if(resource_exists_and_changed::<Settings>),
+                (player_damage_overlay, camera_submersion),
+                ev_post_process,
+                update_post_process_overlays,
+                sync_camera_post_process,
+            )
+                .chain(),
+        );
+
+        app.add_plugins((
+            ExtractComponentPlugin::<PostProcessEffects>::default(),
+            UniformComponentPlugin::<PostProcessEffects>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode>>(Core3d, PostProcessLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    PostProcessLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<PostProcessPipeline>();
+    }
+}
@@ -0,0 +1,42 @@
+// Loot & Roam post-processing pass.
+//
+// Runs after tonemapping. Applies the vignette and the gameplay-driven
+// overlays; see postprocess.rs for details.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct PostProcessEffects {
+    vignette: f32,
+    damage: f32,
+    underwater: f32,
+    _padding: f32,
+}
+
+@group(0) @binding(2) var<uniform> effects: PostProcessEffects;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let sample = textureSample(screen_texture, texture_sampler, in.uv);
+    var color = sample.rgb;
+
+    // 0.0 at the center of the screen, 1.0 at the corners.
+    let edge = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+
+    // Underwater: murky blue-green tint.
+    let murky = color * vec3<f32>(0.4, 0.75, 0.9) + vec3<f32>(0.015, 0.075, 0.1);
+    color = mix(color, murky, effects.underwater);
+
+    // Damage: desaturate, and creep soot-like grime in from the edges.
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = mix(color, vec3<f32>(luma), effects.damage * 0.7);
+    let grime = smoothstep(0.45, 1.0, edge) * effects.damage;
+    color = mix(color, color * vec3<f32>(0.35, 0.3, 0.25), grime);
+
+    // Vignette.
+    color *= 1.0 - smoothstep(0.6, 1.2, edge) * effects.vignette;
+
+    return vec4<f32>(color, sample.a);
+}
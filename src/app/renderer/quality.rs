@@ -0,0 +1,233 @@
+//! # Dynamic render quality
+//!
+//! Monitors frame times, and when they go over budget, progressively lowers
+//! the particle density. When there is headroom again, it is progressively
+//! raised back.
+//!
+//! This keeps big battles playable on weaker GPUs. The current quality is
+//! exposed as the [RenderQuality] resource, which expensive renderers are
+//! expected to honor.
+//!
+//! Whether quality is scaled, and the frame rate it aims for, follow the
+//! player's [GraphicsSettings](crate::app::settings::GraphicsSettings).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Scale the render resolution too. That needs the main pass rendered
+// to a smaller target and upscaled, which the renderer doesn't do yet.
+
+use bevy::prelude::*;
+
+use crate::app::settings::Settings;
+
+/// Configuration of the dynamic render quality.
+///
+/// Meant to be exposed in the settings menu.
+#[derive(Resource, Clone, Debug)]
+pub struct RenderQualityConfig {
+    /// Whether quality is scaled automatically at all.
+    ///
+    /// When disabled, quality is reset to the maximum.
+    pub enabled: bool,
+
+    /// The frame time budget, in seconds.
+    pub frame_budget: f32,
+
+    /// Fraction of the budget below which quality is raised back.
+    ///
+    /// Should be comfortably below 1.0, so quality doesn't oscillate.
+    pub headroom: f32,
+
+    /// Smoothing factor of the frame time average, between 0.0 and 1.0.
+    ///
+    /// Lower values react slower, but are less sensitive to single hitches.
+    pub smoothing: f32,
+
+    /// Minimum time between two quality changes, in seconds.
+    pub cooldown: f32,
+
+    /// How much quality is changed by at a time.
+    pub step: f32,
+
+    /// Lowest allowed particle density.
+    pub min_particle_density: f32,
+}
+
+impl Default for RenderQualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            frame_budget: 1.0 / 60.0,
+            headroom: 0.75,
+            smoothing: 0.1,
+            cooldown: 0.5,
+            step: 0.1,
+            min_particle_density: 0.25,
+        }
+    }
+}
+
+/// The current render quality.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct RenderQuality {
+    /// Particle density, as a fraction of the full amount of particles.
+    pub particle_density: f32,
+}
+
+impl Default for RenderQuality {
+    fn default() -> Self {
+        Self {
+            particle_density: 1.0,
+        }
+    }
+}
+
+/// Frame time bookkeeping.
+#[derive(Resource, Default)]
+struct FrameTimeMonitor {
+    /// Exponential moving average of the frame time, in seconds.
+    average: f32,
+
+    /// Time left until quality may be changed again, in seconds.
+    cooldown: f32,
+}
+
+impl FrameTimeMonitor {
+    /// Accounts for a frame, adapting the quality to the frame times so far.
+    ///
+    /// Quality is lowered while over budget and raised while well under it,
+    /// but left alone in between, and for a while after every change, so
+    /// that it doesn't oscillate.
+    fn update(&mut self, delta: f32, config: &RenderQualityConfig, quality: &mut RenderQuality) {
+        self.average = if self.average == 0.0 {
+            delta
+        } else {
+            self.average + (delta - self.average) * config.smoothing.clamp(0.0, 1.0)
+        };
+
+        self.cooldown -= delta;
+        if self.cooldown > 0.0 {
+            return;
+        }
+
+        let step = if self.average > config.frame_budget {
+            -config.step
+        } else if self.average < config.frame_budget * config.headroom {
+            config.step
+        } else {
+            return;
+        };
+
+        let particle_density =
+            (quality.particle_density + step).clamp(config.min_particle_density, 1.0);
+        if particle_density != quality.particle_density {
+            quality.particle_density = particle_density;
+            self.cooldown = config.cooldown;
+        }
+    }
+}
+
+fn monitor_frame_time(
+    time: Res<Time<Real>>,
+    config: Res<RenderQualityConfig>,
+    mut monitor: ResMut<FrameTimeMonitor>,
+    mut quality: ResMut<RenderQuality>,
+) {
+    if !config.enabled {
+        if *quality != RenderQuality::default() {
+            *quality = RenderQuality::default();
+        }
+        return;
+    }
+
+    monitor.update(time.delta_secs(), &config, &mut quality);
+}
+
+fn apply_graphics_settings(settings: Res<Settings>, mut config: ResMut<RenderQualityConfig>) {
+    config.enabled = settings.graphics.dynamic_quality;
+    config.frame_budget = 1.0 / settings.graphics.target_fps.max(1) as f32;
+}
+
+/// Dynamic render quality plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct RenderQualityPlugin;
+
+impl Plugin for RenderQualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderQualityConfig>();
+        app.init_resource::<RenderQuality>();
+        app.init_resource::<FrameTimeMonitor>();
+        app.add_systems(Last, monitor_frame_time);
+        app.add_systems(
+            Update,
+            apply_graphics_settings.run_if(resource_exists_and_changed::<Settings>),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Runs frames of a given duration for a number of seconds.
+    fn run_frames(
+        monitor: &mut FrameTimeMonitor,
+        quality: &mut RenderQuality,
+        frame_time: f32,
+        seconds: f32,
+    ) {
+        let config = RenderQualityConfig::default();
+        for _ in 0..(seconds / frame_time) as usize {
+            monitor.update(frame_time, &config, quality);
+        }
+    }
+
+    #[test]
+    fn quality_drops_over_budget_and_recovers_with_headroom() {
+        let config = RenderQualityConfig::default();
+        let mut monitor = FrameTimeMonitor::default();
+        let mut quality = RenderQuality::default();
+
+        // Over budget, quality drops a step per cooldown, down to the
+        // minimum.
+        run_frames(&mut monitor, &mut quality, 1.0 / 30.0, 1.0);
+        assert!(quality.particle_density < 1.0);
+        assert!(quality.particle_density > config.min_particle_density);
+        run_frames(&mut monitor, &mut quality, 1.0 / 30.0, 10.0);
+        assert_eq!(quality.particle_density, config.min_particle_density);
+
+        // With headroom, it is raised back, up to the maximum.
+        run_frames(&mut monitor, &mut quality, 1.0 / 120.0, 10.0);
+        assert_eq!(quality.particle_density, 1.0);
+    }
+
+    #[test]
+    fn quality_holds_just_under_budget() {
+        let mut monitor = FrameTimeMonitor::default();
+        let mut quality = RenderQuality {
+            particle_density: 0.5,
+        };
+
+        // Under budget, but without enough headroom to raise quality back,
+        // so it neither drops nor rises.
+        run_frames(&mut monitor, &mut quality, 1.0 / 70.0, 10.0);
+        assert_eq!(quality.particle_density, 0.5);
+
+        // A single slow frame is smoothed out.
+        monitor.update(1.0 / 40.0, &RenderQualityConfig::default(), &mut quality);
+        run_frames(&mut monitor, &mut quality, 1.0 / 70.0, 1.0);
+        assert_eq!(quality.particle_density, 0.5);
+    }
+}
@@ -1,6 +1,21 @@
 //! # Terrain rendering.
 //!
-//! Unused stub as of now.
+//! Terrain is drawn with the [TerrainMaterial], which extends the standard
+//! material by blending four layers of texture: sand along the shore, snow
+//! high up, rock on steep slopes, and ground everywhere else. The ground
+//! takes the color of the biome, which terrain meshes carry in their vertex
+//! colors, so grassland, forest and volcanic ash all tell apart.
+//!
+//! Layers are projected onto the terrain from above, except on steep slopes,
+//! where that would stretch them; there, they are projected from all three
+//! axes (tri-planar projection) and blended by how much the slope faces each.
+//!
+//! Terrain entities, and anything else drawn with the material of the
+//! [TerrainChunkIndex], such as caves, have their standard material replaced
+//! with the terrain material when spawned.
+//!
+//! Each layer is also tinted, so terrain looks the part before, or without,
+//! its textures, which are loaded from `textures/terrain/`.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -15,4 +30,272 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-// [TODO] Implement custom low level terrain renderer, checking for a single Terrain entity
+use bevy::{
+    asset::embedded_asset,
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::common::terrain::{buffer::TerrainMarker, chunk::TerrainChunkIndex};
+
+/// Asset path of the embedded terrain shader.
+const SHADER_ASSET_PATH: &str = "embedded://loot_and_roam/app/renderer/terrain.wgsl";
+
+pub use params::TerrainSplatParams;
+
+// [NOTE] ShaderType generates layout checks which newer compilers flag as
+// dead code; the uniform lives in its own module to scope the allow.
+#[allow(dead_code)]
+mod params {
+    use bevy::{prelude::*, render::render_resource::ShaderType};
+
+    /// How the layers of the [TerrainMaterial](super::TerrainMaterial) are
+    /// blended.
+    ///
+    /// Heights are in world space units above sea level; slopes go from 0.0
+    /// on flat ground to 1.0 on sheer cliffs.
+    #[derive(Clone, Copy, Debug, PartialEq, ShaderType)]
+    pub struct TerrainSplatParams {
+        pub sand_color: LinearRgba,
+        pub rock_color: LinearRgba,
+        pub snow_color: LinearRgba,
+
+        /// World space height of the sea.
+        pub sea_level: f32,
+
+        /// How high the sand reaches.
+        pub beach_height: f32,
+
+        /// How high the snow starts.
+        pub snow_height: f32,
+
+        /// How tall the blend between layers of different heights is.
+        pub blend: f32,
+
+        /// The slope at which rock starts to show.
+        pub rock_slope_start: f32,
+
+        /// The slope at which there is only rock.
+        pub rock_slope_end: f32,
+
+        /// The slope from which layers are projected tri-planarly.
+        pub triplanar_slope: f32,
+
+        /// How wide a texture is on the terrain, in world space units.
+        pub texture_scale: f32,
+    }
+}
+
+impl TerrainSplatParams {
+    /// The parameters for terrain between two world space heights, the
+    /// lowest of which is sea level.
+    pub fn for_heights(lowest: f32, highest: f32) -> Self {
+        let range = highest - lowest;
+
+        Self {
+            sand_color: Color::srgb_u8(215, 200, 140).to_linear(),
+            rock_color: Color::srgb_u8(120, 125, 115).to_linear(),
+            snow_color: Color::srgb_u8(240, 244, 250).to_linear(),
+            sea_level: lowest,
+            beach_height: range * 0.04,
+            snow_height: range * 0.8,
+            blend: range * 0.03,
+            rock_slope_start: 0.35,
+            rock_slope_end: 0.55,
+            triplanar_slope: 0.3,
+            texture_scale: 8.0,
+        }
+    }
+}
+
+/// The terrain layers the [TerrainMaterial] blends; see the module
+/// documentation.
+#[derive(Asset, AsBindGroup, TypePath, Clone, Debug)]
+pub struct TerrainSplat {
+    #[uniform(100)]
+    pub params: TerrainSplatParams,
+
+    #[texture(101)]
+    #[sampler(102)]
+    pub sand_texture: Option<Handle<Image>>,
+
+    #[texture(103)]
+    #[sampler(104)]
+    pub ground_texture: Option<Handle<Image>>,
+
+    #[texture(105)]
+    #[sampler(106)]
+    pub rock_texture: Option<Handle<Image>>,
+
+    #[texture(107)]
+    #[sampler(108)]
+    pub snow_texture: Option<Handle<Image>>,
+}
+
+impl MaterialExtension for TerrainSplat {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+}
+
+/// The material terrain is drawn with.
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainSplat>;
+
+/// The textures of every terrain layer, bound to terrain materials once
+/// loaded.
+#[derive(Resource, Clone, Debug)]
+struct TerrainTextures {
+    sand: Handle<Image>,
+    ground: Handle<Image>,
+    rock: Handle<Image>,
+    snow: Handle<Image>,
+}
+
+/// The terrain material last made, and the heights it was made for.
+#[derive(Resource, Clone, Debug, Default)]
+struct CurrentTerrainMaterial(Option<((f32, f32), Handle<TerrainMaterial>)>);
+
+fn load_terrain_textures(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // Textures tile across the terrain.
+    let load = |name: &str| {
+        asset_server.load_with_settings(
+            format!("textures/terrain/{name}.png"),
+            |settings: &mut ImageLoaderSettings| {
+                settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                    address_mode_u: ImageAddressMode::Repeat,
+                    address_mode_v: ImageAddressMode::Repeat,
+                    ..ImageSamplerDescriptor::linear()
+                });
+            },
+        )
+    };
+
+    commands.insert_resource(TerrainTextures {
+        sand: load("sand"),
+        ground: load("ground"),
+        rock: load("rock"),
+        snow: load("snow"),
+    });
+}
+
+/// Binds terrain textures to every terrain material as they are loaded.
+fn bind_terrain_textures(
+    mut events: EventReader<AssetEvent<Image>>,
+    textures: Res<TerrainTextures>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+) {
+    for event in events.read() {
+        let AssetEvent::LoadedWithDependencies { id } = event else {
+            continue;
+        };
+
+        for (_, material) in materials.iter_mut() {
+            let splat = &mut material.extension;
+            for (texture, slot) in [
+                (&textures.sand, &mut splat.sand_texture),
+                (&textures.ground, &mut splat.ground_texture),
+                (&textures.rock, &mut splat.rock_texture),
+                (&textures.snow, &mut splat.snow_texture),
+            ] {
+                if texture.id() == *id {
+                    *slot = Some(texture.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Replaces the standard material of terrain with the [TerrainMaterial].
+fn apply_terrain_material(
+    mut commands: Commands,
+    index: Option<Res<TerrainChunkIndex>>,
+    textures: Res<TerrainTextures>,
+    images: Res<Assets<Image>>,
+    mut current: ResMut<CurrentTerrainMaterial>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    query: Query<
+        (
+            Entity,
+            &MeshMaterial3d<StandardMaterial>,
+            Has<TerrainMarker>,
+        ),
+        Added<MeshMaterial3d<StandardMaterial>>,
+    >,
+) {
+    let index_material = index.as_ref().and_then(|index| index.material.as_ref());
+
+    for (entity, material, is_terrain) in &query {
+        if !is_terrain && index_material != Some(&material.0) {
+            continue;
+        }
+
+        let heights = index
+            .as_ref()
+            .map_or((0.0, 80.0), |index| index.source().height_range());
+
+        let handle = match &current.0 {
+            Some((made_for, handle)) if *made_for == heights => handle.clone(),
+            _ => {
+                let loaded =
+                    |texture: &Handle<Image>| images.contains(texture).then(|| texture.clone());
+                let handle = materials.add(TerrainMaterial {
+                    base: StandardMaterial {
+                        perceptual_roughness: 0.9,
+                        ..default()
+                    },
+                    extension: TerrainSplat {
+                        params: TerrainSplatParams::for_heights(heights.0, heights.1),
+                        sand_texture: loaded(&textures.sand),
+                        ground_texture: loaded(&textures.ground),
+                        rock_texture: loaded(&textures.rock),
+                        snow_texture: loaded(&textures.snow),
+                    },
+                });
+                current.0 = Some((heights, handle.clone()));
+                handle
+            }
+        };
+
+        commands
+            .entity(entity)
+            .remove::<MeshMaterial3d<StandardMaterial>>()
+            .insert(MeshMaterial3d(handle));
+    }
+}
+
+/// Terrain rendering plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct TerrainRenderingPlugin;
+
+impl Plugin for TerrainRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "terrain.wgsl");
+
+        app.add_plugins(MaterialPlugin::<TerrainMaterial>::default());
+        app.init_resource::<CurrentTerrainMaterial>();
+        app.add_systems(Startup, load_terrain_textures);
+        app.add_systems(PostUpdate, (apply_terrain_material, bind_terrain_textures));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_are_stacked_by_height() {
+        let params = TerrainSplatParams::for_heights(-40.0, 40.0);
+        assert_eq!(params.sea_level, -40.0);
+        assert!(0.0 < params.beach_height);
+        assert!(params.beach_height + params.blend < params.snow_height);
+        assert!(params.snow_height + params.blend < 80.0);
+        assert!(params.triplanar_slope < params.rock_slope_end);
+    }
+}
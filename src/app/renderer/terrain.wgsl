@@ -0,0 +1,110 @@
+// Loot & Roam terrain splat material.
+//
+// Extends the standard material, blending sand, ground, rock and snow by
+// height and slope; see terrain.rs for details.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct TerrainSplatParams {
+    sand_color: vec4<f32>,
+    rock_color: vec4<f32>,
+    snow_color: vec4<f32>,
+    sea_level: f32,
+    beach_height: f32,
+    snow_height: f32,
+    blend: f32,
+    rock_slope_start: f32,
+    rock_slope_end: f32,
+    triplanar_slope: f32,
+    texture_scale: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> splat: TerrainSplatParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var sand_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var sand_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103) var ground_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104) var ground_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(105) var rock_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(106) var rock_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(107) var snow_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(108) var snow_sampler: sampler;
+
+// Samples a layer projected from above, or, as `triplanar` goes to 1.0, from
+// all three axes, weighted by how much the surface faces each.
+fn sample_layer(
+    layer: texture_2d<f32>,
+    layer_sampler: sampler,
+    position: vec3<f32>,
+    normal: vec3<f32>,
+    triplanar: f32,
+) -> vec3<f32> {
+    let uv = position / splat.texture_scale;
+    let top = textureSample(layer, layer_sampler, uv.xz).rgb;
+    let side_x = textureSample(layer, layer_sampler, uv.zy).rgb;
+    let side_z = textureSample(layer, layer_sampler, uv.xy).rgb;
+
+    var weights = pow(abs(normal), vec3<f32>(4.0));
+    weights = weights / (weights.x + weights.y + weights.z);
+    let projected = side_x * weights.x + top * weights.y + side_z * weights.z;
+
+    return mix(top, projected, triplanar);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    let position = in.world_position.xyz;
+    let normal = normalize(in.world_normal);
+    let height = position.y - splat.sea_level;
+    let slope = 1.0 - normal.y;
+    let triplanar = smoothstep(splat.triplanar_slope, splat.triplanar_slope + 0.1, slope);
+
+    // The ground takes the biome color, given through the vertex colors.
+    let biome = pbr_input.material.base_color.rgb;
+
+    let sand = sample_layer(sand_texture, sand_sampler, position, normal, triplanar)
+        * splat.sand_color.rgb;
+    let ground = sample_layer(ground_texture, ground_sampler, position, normal, triplanar)
+        * biome;
+    let rock = sample_layer(rock_texture, rock_sampler, position, normal, triplanar)
+        * splat.rock_color.rgb;
+    let snow = sample_layer(snow_texture, snow_sampler, position, normal, triplanar)
+        * splat.snow_color.rgb;
+
+    // Sand on the shore, snow up high, and rock on steep slopes over both.
+    var color = mix(sand, ground, smoothstep(splat.beach_height, splat.beach_height + splat.blend, height));
+    color = mix(color, snow, smoothstep(splat.snow_height, splat.snow_height + splat.blend, height));
+    color = mix(color, rock, smoothstep(splat.rock_slope_start, splat.rock_slope_end, slope));
+
+    pbr_input.material.base_color = vec4<f32>(color, pbr_input.material.base_color.a);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
@@ -0,0 +1,296 @@
+//! # UI drawing
+//!
+//! Lays out every [UiContext] once per frame, and draws the [UiCommand]s it
+//! produces, through a 2D camera drawn over every other one.
+//!
+//! Commands are drawn with pooled sprite and text entities, which are reused
+//! across frames rather than respawned, as the commands themselves are
+//! produced anew every frame. Entities left over are hidden.
+//!
+//! Contexts are drawn in order of their [UiLayer], and the commands of each
+//! in the order they were produced, so that parents are drawn below their
+//! children.
+//!
+//! The UI is scaled by the player's [Settings::ui_scale]: contexts are laid
+//! out within a viewport smaller or larger than the window by that much,
+//! which the camera then stretches over the window.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{prelude::*, render::camera::ScalingMode, sprite::Anchor, window::PrimaryWindow};
+
+use super::layouter::{UiCommand, UiContext};
+use crate::app::settings::Settings;
+
+/// The order of the UI camera; above every other camera.
+const UI_CAMERA_ORDER: isize = 100;
+
+/// How far apart each command is drawn, along Z.
+const COMMAND_DEPTH: f32 = 0.01;
+
+/// How far apart each layer is drawn, along Z.
+const LAYER_DEPTH: f32 = 100.0;
+
+/// Marks the camera the UI is drawn through.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct UiCamera;
+
+/// The layer of an UI context; contexts on higher layers are drawn above
+/// those on lower ones.
+///
+/// Contexts without one are on layer 0.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UiLayer(pub i32);
+
+/// Marks an entity used to draw UI commands.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct UiDrawn;
+
+/// The entities used to draw UI commands, reused across frames.
+#[derive(Resource, Clone, Debug, Default)]
+struct UiDrawPool {
+    sprites: Vec<Entity>,
+    texts: Vec<Entity>,
+}
+
+/// The viewport UI contexts are laid out within, in a window.
+pub fn ui_viewport(window: &Window, settings: &Settings) -> Rect {
+    Rect::from_corners(Vec2::ZERO, window.size() / ui_scale(settings))
+}
+
+/// Converts a cursor position in a window to UI coordinates.
+pub fn ui_cursor(window: &Window, settings: &Settings) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .map(|cursor| ui_point(cursor, settings))
+}
+
+/// Converts a logical position in a window, such as one returned by
+/// [Camera::world_to_viewport], to UI coordinates.
+pub fn ui_point(point: Vec2, settings: &Settings) -> Vec2 {
+    point / ui_scale(settings)
+}
+
+fn ui_scale(settings: &Settings) -> f32 {
+    settings.ui_scale.max(0.1)
+}
+
+fn spawn_ui_camera(mut commands: Commands) {
+    commands.spawn((
+        UiCamera,
+        Camera2d,
+        Camera {
+            order: UI_CAMERA_ORDER,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+    ));
+}
+
+fn scale_ui_camera(settings: Res<Settings>, mut cameras: Query<&mut Projection, With<UiCamera>>) {
+    for mut projection in &mut cameras {
+        if let Projection::Orthographic(ortho) = projection.as_mut() {
+            ortho.scaling_mode = ScalingMode::WindowSize;
+            ortho.scale = 1.0 / ui_scale(&settings);
+        }
+    }
+}
+
+/// Where a rect in UI coordinates is, relative to the UI camera, which
+/// has its origin at the center of the viewport and Y pointing up.
+fn ui_to_world(viewport: Rect, point: Vec2, depth: f32) -> Vec3 {
+    let center = viewport.center();
+    Vec3::new(point.x - center.x, center.y - point.y, depth)
+}
+
+fn draw_ui_contexts(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut contexts: Query<(&mut UiContext, Option<&UiLayer>)>,
+    mut pool: ResMut<UiDrawPool>,
+    mut sprites: Query<(&mut Sprite, &mut Transform, &mut Visibility), Without<Text2d>>,
+    mut texts: Query<
+        (
+            &mut Text2d,
+            &mut TextFont,
+            &mut TextColor,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        Without<Sprite>,
+    >,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let viewport = ui_viewport(window, &settings);
+
+    let mut contexts = contexts.iter_mut().collect::<Vec<_>>();
+    contexts.sort_by_key(|(_, layer)| layer.copied().unwrap_or_default());
+
+    let mut used_sprites = 0;
+    let mut used_texts = 0;
+
+    for (mut context, layer) in contexts {
+        let base = layer.copied().unwrap_or_default().0 as f32 * LAYER_DEPTH;
+
+        for (index, command) in context.layout(viewport).into_iter().enumerate() {
+            let depth = base + index as f32 * COMMAND_DEPTH;
+
+            match command {
+                UiCommand::Rect { rect, color } => {
+                    draw_sprite(
+                        &mut commands,
+                        &mut pool,
+                        &mut sprites,
+                        &mut used_sprites,
+                        Sprite {
+                            color,
+                            custom_size: Some(rect.size()),
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        ui_to_world(viewport, rect.min, depth),
+                    );
+                }
+
+                UiCommand::Image {
+                    rect,
+                    image,
+                    color,
+                    region,
+                } => {
+                    draw_sprite(
+                        &mut commands,
+                        &mut pool,
+                        &mut sprites,
+                        &mut used_sprites,
+                        Sprite {
+                            image,
+                            color,
+                            rect: region,
+                            custom_size: Some(rect.size()),
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        ui_to_world(viewport, rect.min, depth),
+                    );
+                }
+
+                UiCommand::Text {
+                    rect,
+                    text,
+                    font_size,
+                    color,
+                } => {
+                    let translation = ui_to_world(viewport, rect.min, depth);
+
+                    let Some(&entity) = pool.texts.get(used_texts) else {
+                        let entity = commands
+                            .spawn((
+                                UiDrawn,
+                                Text2d(text),
+                                TextFont::from_font_size(font_size),
+                                TextColor(color),
+                                Anchor::TopLeft,
+                                Transform::from_translation(translation),
+                            ))
+                            .id();
+                        pool.texts.push(entity);
+                        used_texts += 1;
+                        continue;
+                    };
+                    used_texts += 1;
+
+                    let Ok((mut text2d, mut font, mut text_color, mut transform, mut visibility)) =
+                        texts.get_mut(entity)
+                    else {
+                        continue;
+                    };
+
+                    // Only touch what changed, so that text is not
+                    // measured and rendered again every frame.
+                    if text2d.0 != text {
+                        text2d.0 = text;
+                    }
+                    if font.font_size != font_size {
+                        font.font_size = font_size;
+                    }
+                    text_color.set_if_neq(TextColor(color));
+                    transform.translation = translation;
+                    visibility.set_if_neq(Visibility::Inherited);
+                }
+            }
+        }
+    }
+
+    for &entity in &pool.sprites[used_sprites..] {
+        if let Ok((_, _, mut visibility)) = sprites.get_mut(entity) {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+    for &entity in &pool.texts[used_texts..] {
+        if let Ok((_, _, _, _, mut visibility)) = texts.get_mut(entity) {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+/// Draws a sprite with the next entity of the pool, spawning one if there
+/// are none left.
+fn draw_sprite(
+    commands: &mut Commands,
+    pool: &mut UiDrawPool,
+    sprites: &mut Query<(&mut Sprite, &mut Transform, &mut Visibility), Without<Text2d>>,
+    used: &mut usize,
+    sprite: Sprite,
+    translation: Vec3,
+) {
+    let entity = pool.sprites.get(*used).copied();
+    *used += 1;
+
+    let Some(entity) = entity else {
+        let entity = commands
+            .spawn((UiDrawn, sprite, Transform::from_translation(translation)))
+            .id();
+        pool.sprites.push(entity);
+        return;
+    };
+
+    if let Ok((mut old, mut transform, mut visibility)) = sprites.get_mut(entity) {
+        *old = sprite;
+        transform.translation = translation;
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+/// UI drawing plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct UiDrawPlugin;
+
+impl Plugin for UiDrawPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiDrawPool>();
+        app.add_systems(Startup, spawn_ui_camera);
+        app.add_systems(
+            PostUpdate,
+            (
+                scale_ui_camera.run_if(resource_changed::<Settings>),
+                draw_ui_contexts,
+            ),
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! # Drydock screen
+//!
+//! Shows the ships of the player's fleet while in the Drydock, each with its
+//! part slots and hold, alongside the town's shop stock, if any.
+//!
+//! Items are dragged from a hold, the stock or a slot, and dropped onto
+//! another hold, the stock or a slot, which sends a [MoveItem] request; the
+//! [drydock](crate::common::intermission::drydock) logic carries it out. While
+//! a part is dragged, the slots it can be installed on are highlighted, as
+//! checked with [PartInstallQuery::can_install]. Why the last move failed,
+//! if it did, is shown at the bottom, along with the fleet's funds.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Repainting ships' liveries.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use slotmap::DefaultKey;
+
+use super::{
+    event::{UiEvent, UiEventKind, UiEventRouter},
+    layouter::{
+        Anchor, Axis, Content, Edges, ElementId, Layout, Size, UiContext, UiElement, UiKey, UiTree,
+    },
+};
+use crate::{
+    app::input::{ActionState, InputAction},
+    common::{
+        construct::{
+            install::{PartInstallError, PartInstallErrorReason, PartInstallQuery},
+            slot::{ConstructSlots, PartSlotInfo},
+        },
+        fleet::{Funds, InFleet},
+        intermission::{
+            IntermissionAppExt, IntermissionBuilding, LeaveBuilding,
+            drydock::{
+                DrydockPartData, ItemHolder, ItemMoved, MoveItem, MoveRejected, MoveRejectedReason,
+                MoveTarget, ShopStock, find_part_entity, item_price,
+            },
+        },
+        inventory::{InventoryDef, container::Inventory},
+        makeup::{Ship, sync::MakeupSlot},
+        player::PlayerControlled,
+    },
+};
+
+const TITLE_FONT_SIZE: f32 = 20.0;
+const FONT_SIZE: f32 = 15.0;
+const PANEL_COLOR: Color = Color::srgba(0.05, 0.07, 0.1, 0.8);
+const ROW_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.05);
+const HOLD_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.3);
+const FITS_COLOR: Color = Color::srgba(0.3, 0.8, 0.3, 0.35);
+const MISFITS_COLOR: Color = Color::srgba(0.8, 0.3, 0.3, 0.2);
+const TEXT_COLOR: Color = Color::srgb(0.92, 0.9, 0.84);
+const DIM_TEXT_COLOR: Color = Color::srgb(0.6, 0.6, 0.58);
+const ERROR_COLOR: Color = Color::srgb(0.95, 0.45, 0.4);
+const PANEL_WIDTH: f32 = 260.0;
+
+/// Something on the Drydock screen which can be dragged or dropped onto.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DrydockElement {
+    /// An item in a hold or the stock.
+    Item(ItemHolder, DefaultKey),
+
+    /// A hold or the stock itself.
+    Hold(ItemHolder),
+
+    /// A part slot of a ship, and the part installed on it, if any.
+    Slot {
+        slot: Entity,
+        ship: Entity,
+        installed: Option<DefaultKey>,
+    },
+}
+
+impl DrydockElement {
+    fn key(self) -> UiKey {
+        match self {
+            Self::Item(holder, key) => UiKey::new(("drydock item", holder, key)),
+            Self::Hold(holder) => UiKey::new(("drydock hold", holder)),
+            Self::Slot { slot, .. } => UiKey::new(("drydock slot", slot)),
+        }
+    }
+
+    /// The item dragged when dragging this element, if any.
+    fn dragged(self) -> Option<(ItemHolder, DefaultKey)> {
+        match self {
+            Self::Item(holder, key) => Some((holder, key)),
+            Self::Slot {
+                ship,
+                installed: Some(key),
+                ..
+            } => Some((ItemHolder::Ship(ship), key)),
+            _ => None,
+        }
+    }
+
+    /// Where an item dropped onto this element goes.
+    fn target(self) -> MoveTarget {
+        match self {
+            Self::Item(holder, _) | Self::Hold(holder) => MoveTarget::Hold(holder),
+            Self::Slot { slot, .. } => MoveTarget::Slot(slot),
+        }
+    }
+}
+
+/// An item being dragged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Dragged {
+    from: ItemHolder,
+    key: DefaultKey,
+}
+
+/// The state of the Drydock screen, kept on its UI context.
+#[derive(Component, Clone, Debug, Default)]
+struct DrydockScreen {
+    /// What each keyed element of the last frame is.
+    elements: HashMap<UiKey, DrydockElement>,
+
+    dragging: Option<Dragged>,
+
+    /// Where the cursor was last seen while dragging.
+    cursor: Vec2,
+
+    /// Tells the player what happened with their last move.
+    message: Option<(String, Color)>,
+}
+
+fn item_label(item: &InventoryDef) -> String {
+    if item.is_fungible() {
+        format!("{} x{:.0}", item.name, item.amount)
+    } else {
+        item.name.clone()
+    }
+}
+
+fn install_error_text(reason: &PartInstallErrorReason) -> String {
+    match reason {
+        PartInstallErrorReason::TagMismatch { slot_type } => {
+            format!("That part does not fit a {slot_type} slot")
+        }
+        PartInstallErrorReason::SlotOccupied => "That slot is taken".to_string(),
+        PartInstallErrorReason::AlreadyInstalled => "That part is installed already".to_string(),
+        reason => format!("Can't install that part: {reason:?}"),
+    }
+}
+
+fn reject_text(reason: &MoveRejectedReason) -> String {
+    match reason {
+        MoveRejectedReason::NoSuchItem | MoveRejectedReason::NoSuchTarget => {
+            "That item is gone".to_string()
+        }
+        MoveRejectedReason::NotAPart => "Only parts can be installed".to_string(),
+        MoveRejectedReason::CannotInstall(reason) => install_error_text(reason),
+        MoveRejectedReason::InsufficientFunds { price } => {
+            format!("Not enough funds; that costs {price}")
+        }
+        MoveRejectedReason::NoRoom => "There is no room for that".to_string(),
+    }
+}
+
+fn spawn_drydock_screen(mut commands: Commands) {
+    commands.spawn((
+        DrydockScreen::default(),
+        Name::new("Drydock"),
+        UiContext::default(),
+        UiEventRouter::default(),
+    ));
+}
+
+fn despawn_drydock_screen(mut commands: Commands, screens: Query<Entity, With<DrydockScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drydock_input(
+    actions: Res<ActionState>,
+    mut ui_events: EventReader<UiEvent>,
+    mut moved: EventReader<ItemMoved>,
+    mut rejected: EventReader<MoveRejected>,
+    mut install_errors: EventReader<PartInstallError>,
+    mut screens: Query<(Entity, &UiContext, &mut DrydockScreen)>,
+    mut moves: EventWriter<MoveItem>,
+    mut leave: EventWriter<LeaveBuilding>,
+) {
+    let Ok((entity, context, mut screen)) = screens.single_mut() else {
+        return;
+    };
+
+    if actions.just_pressed(InputAction::MenuBack) {
+        leave.write(LeaveBuilding);
+    }
+
+    for event in ui_events.read().filter(|event| event.context == entity) {
+        let element = screen.elements.get(&event.key).copied();
+
+        match event.kind {
+            UiEventKind::DragStart => {
+                screen.dragging = element
+                    .and_then(DrydockElement::dragged)
+                    .map(|(from, key)| Dragged { from, key });
+                screen.cursor = event.position;
+            }
+            UiEventKind::Drag { .. } => screen.cursor = event.position,
+            UiEventKind::DragEnd => {
+                let Some(dragged) = screen.dragging.take() else {
+                    continue;
+                };
+                let target = context
+                    .tree
+                    .hit_test(context.rects(), event.position)
+                    .and_then(|key| screen.elements.get(&key))
+                    .map(|element| element.target());
+                if let Some(to) = target {
+                    moves.write(MoveItem {
+                        from: dragged.from,
+                        key: dragged.key,
+                        to,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for ItemMoved { name, price, .. } in moved.read() {
+        let text = if *price > 0 {
+            format!("Traded {name} for {price}")
+        } else {
+            format!("Moved {name}")
+        };
+        screen.message = Some((text, DIM_TEXT_COLOR));
+    }
+    for MoveRejected { reason, .. } in rejected.read() {
+        screen.message = Some((reject_text(reason), ERROR_COLOR));
+    }
+    for PartInstallError { reason, .. } in install_errors.read() {
+        screen.message = Some((install_error_text(reason), ERROR_COLOR));
+    }
+}
+
+fn panel(tree: &mut UiTree, parent: ElementId, title: &str) -> ElementId {
+    let panel = tree.add_child(
+        parent,
+        UiElement::new(Content::Rect { color: PANEL_COLOR })
+            .with_size(Size::Fixed(PANEL_WIDTH), Size::Auto)
+            .with_padding(Edges::all(8.0))
+            .with_layout(Layout::Stack {
+                axis: Axis::Vertical,
+                gap: 4.0,
+            }),
+    );
+    tree.add_child(panel, text(title, TITLE_FONT_SIZE, TEXT_COLOR));
+    panel
+}
+
+fn text(text: impl Into<String>, font_size: f32, color: Color) -> UiElement {
+    UiElement::new(Content::Text {
+        text: text.into(),
+        font_size,
+        color,
+    })
+}
+
+/// Adds a draggable row, for an element, with a label.
+fn add_row(
+    tree: &mut UiTree,
+    elements: &mut HashMap<UiKey, DrydockElement>,
+    parent: ElementId,
+    element: DrydockElement,
+    color: Color,
+    label: String,
+    text_color: Color,
+) {
+    let row = tree.add_child(
+        parent,
+        UiElement::new(Content::Rect { color })
+            .with_size(Size::FILL, Size::Auto)
+            .with_padding(Edges::symmetric(6.0, 3.0))
+            .with_key(element.key()),
+    );
+    tree.add_child(row, text(label, FONT_SIZE, text_color));
+    elements.insert(element.key(), element);
+}
+
+/// Adds a hold, or the stock, and its items.
+fn add_hold<'a>(
+    tree: &mut UiTree,
+    elements: &mut HashMap<UiKey, DrydockElement>,
+    parent: ElementId,
+    holder: ItemHolder,
+    items: impl Iterator<Item = (DefaultKey, &'a InventoryDef)>,
+    priced: bool,
+) {
+    let element = DrydockElement::Hold(holder);
+    let hold = tree.add_child(
+        parent,
+        UiElement::new(Content::Rect { color: HOLD_COLOR })
+            .with_size(Size::FILL, Size::Auto)
+            .with_min_size(Vec2::new(0.0, 40.0))
+            .with_padding(Edges::all(4.0))
+            .with_layout(Layout::Stack {
+                axis: Axis::Vertical,
+                gap: 2.0,
+            })
+            .with_key(element.key()),
+    );
+    elements.insert(element.key(), element);
+
+    for (key, item) in items {
+        let label = if priced {
+            format!("{} ({})", item_label(item), item_price(item))
+        } else {
+            item_label(item)
+        };
+        add_row(
+            tree,
+            elements,
+            hold,
+            DrydockElement::Item(holder, key),
+            ROW_COLOR,
+            label,
+            TEXT_COLOR,
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_drydock_screen(
+    fleet: Query<
+        (
+            Entity,
+            &Ship,
+            Option<&ConstructSlots>,
+            Has<PlayerControlled>,
+        ),
+        Or<(With<InFleet>, With<PlayerControlled>)>,
+    >,
+    slots: Query<(&MakeupSlot, &PartSlotInfo)>,
+    stocks: Query<(Entity, &Inventory), With<ShopStock>>,
+    parts: Query<DrydockPartData>,
+    installs: PartInstallQuery,
+    funds: Res<Funds>,
+    mut screens: Query<(&mut UiContext, &mut DrydockScreen)>,
+) {
+    let Ok((mut context, mut screen)) = screens.single_mut() else {
+        return;
+    };
+    let screen = &mut *screen;
+    let tree = &mut context.tree;
+    tree.clear();
+    screen.elements.clear();
+
+    let mut fleet = fleet.iter().collect::<Vec<_>>();
+    fleet.sort_by_key(|&(entity, _, _, flagship)| (!flagship, entity));
+
+    // The part being dragged, if any, and its entity if it is not
+    // installed, to check slots against.
+    let dragged_part = screen.dragging.as_ref().and_then(|dragged| {
+        let ItemHolder::Ship(ship) = dragged.from else {
+            return None;
+        };
+        find_part_entity(&parts, ship, dragged.key)
+            .filter(|&(_, installed)| !installed)
+            .map(|(part, _)| part)
+    });
+
+    let columns = tree.add_root(
+        UiElement::default()
+            .with_anchor(Anchor::Top)
+            .with_offset(Vec2::new(0.0, 24.0))
+            .with_layout(Layout::Stack {
+                axis: Axis::Horizontal,
+                gap: 12.0,
+            }),
+    );
+
+    for (index, &(ship_id, ship, construct_slots, flagship)) in fleet.iter().enumerate() {
+        let title = if flagship {
+            "Flagship".to_string()
+        } else {
+            format!("Ship {}", index + 1)
+        };
+        let column = panel(tree, columns, &title);
+
+        tree.add_child(column, text("Slots", FONT_SIZE, DIM_TEXT_COLOR));
+        for &slot in construct_slots.into_iter().flat_map(ConstructSlots::iter) {
+            let Ok((&MakeupSlot(idx), slot_info)) = slots.get(slot) else {
+                continue;
+            };
+            let installed = ship.makeup.installed(idx);
+            let part_name = installed
+                .and_then(|key| ship.makeup.item(key))
+                .map_or("-", |item| item.name.as_str());
+
+            let color = match dragged_part {
+                Some(part) if installs.can_install(part, slot).is_ok() => FITS_COLOR,
+                Some(_) => MISFITS_COLOR,
+                None => ROW_COLOR,
+            };
+            add_row(
+                tree,
+                &mut screen.elements,
+                column,
+                DrydockElement::Slot {
+                    slot,
+                    ship: ship_id,
+                    installed,
+                },
+                color,
+                format!("[{}] {part_name}", slot_info.slot_type),
+                if installed.is_some() {
+                    TEXT_COLOR
+                } else {
+                    DIM_TEXT_COLOR
+                },
+            );
+        }
+
+        // Installed parts are shown on their slots instead.
+        tree.add_child(column, text("Hold", FONT_SIZE, DIM_TEXT_COLOR));
+        let installed = (0..ship.makeup.make().slots.len())
+            .filter_map(|idx| ship.makeup.installed(idx))
+            .collect::<Vec<_>>();
+        add_hold(
+            tree,
+            &mut screen.elements,
+            column,
+            ItemHolder::Ship(ship_id),
+            ship.makeup
+                .items()
+                .filter(|(key, _)| !installed.contains(key)),
+            false,
+        );
+    }
+
+    for (stock_id, stock) in &stocks {
+        let column = panel(tree, columns, "Shop stock");
+        add_hold(
+            tree,
+            &mut screen.elements,
+            column,
+            ItemHolder::Stock(stock_id),
+            stock.iter(),
+            true,
+        );
+    }
+
+    let footer = tree.add_root(
+        UiElement::new(Content::Rect { color: PANEL_COLOR })
+            .with_anchor(Anchor::Bottom)
+            .with_offset(Vec2::new(0.0, -24.0))
+            .with_padding(Edges::symmetric(12.0, 6.0))
+            .with_layout(Layout::Stack {
+                axis: Axis::Horizontal,
+                gap: 24.0,
+            }),
+    );
+    tree.add_child(
+        footer,
+        text(format!("Funds: {}", funds.0), FONT_SIZE, TEXT_COLOR),
+    );
+    if let Some((message, color)) = &screen.message {
+        tree.add_child(footer, text(message.clone(), FONT_SIZE, *color));
+    }
+
+    // The dragged item follows the cursor, above everything else.
+    let dragged_item = screen
+        .dragging
+        .as_ref()
+        .and_then(|dragged| match dragged.from {
+            ItemHolder::Ship(ship) => fleet
+                .iter()
+                .find(|&&(entity, ..)| entity == ship)
+                .and_then(|(_, ship, ..)| ship.makeup.item(dragged.key)),
+            ItemHolder::Stock(stock) => stocks
+                .get(stock)
+                .ok()
+                .and_then(|(_, stock)| stock.get(dragged.key)),
+        });
+    if let Some(item) = dragged_item {
+        let ghost = tree.add_root(
+            UiElement::new(Content::Rect { color: PANEL_COLOR })
+                .with_offset(screen.cursor + Vec2::splat(12.0))
+                .with_padding(Edges::symmetric(6.0, 3.0)),
+        );
+        tree.add_child(ghost, text(item_label(item), FONT_SIZE, TEXT_COLOR));
+    }
+}
+
+/// Drydock screen plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct DrydockScreenPlugin;
+
+impl Plugin for DrydockScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(IntermissionBuilding::Drydock), spawn_drydock_screen);
+        app.add_systems(
+            OnExit(IntermissionBuilding::Drydock),
+            despawn_drydock_screen,
+        );
+        app.add_building_systems(
+            IntermissionBuilding::Drydock,
+            (drydock_input, draw_drydock_screen).chain(),
+        );
+    }
+}
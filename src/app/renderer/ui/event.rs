@@ -0,0 +1,339 @@
+//! # UI events
+//!
+//! Routes the pointer to the elements of each [UiContext], as [UiEvent]s,
+//! which element producers read back to update their state.
+//!
+//! Elements are identified by their [UiKey], since the tree is rebuilt
+//! every frame; elements without a key neither receive events nor block
+//! them. The element under the cursor is the topmost keyed one whose rect,
+//! as of the last layout, contains it.
+//!
+//! Pressing a button on an element captures the pointer: the element gets
+//! every event until the button is released, even if the cursor leaves it.
+//! Moving the cursor far enough while captured drags the element; releasing
+//! over the element without dragging clicks it. Pressing also focuses the
+//! element, or unfocuses everything if pressed outside any element.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{
+    draw::ui_cursor,
+    layouter::{UiContext, UiKey, UiTree},
+};
+use crate::app::settings::Settings;
+
+/// How far the cursor must move while pressed to start dragging, in
+/// logical pixels.
+pub const DRAG_THRESHOLD: f32 = 4.0;
+
+/// What happened to an element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UiEventKind {
+    /// The cursor moved onto the element.
+    HoverStart,
+
+    /// The cursor moved off the element.
+    HoverEnd,
+
+    /// A button was pressed on the element.
+    Press(MouseButton),
+
+    /// The button pressed on the element was released, wherever the cursor
+    /// is.
+    Release(MouseButton),
+
+    /// A button was pressed and released on the element, without dragging.
+    Click(MouseButton),
+
+    /// The cursor started dragging the element.
+    DragStart,
+
+    /// The cursor moved while dragging the element.
+    Drag { delta: Vec2 },
+
+    /// The element was let go of.
+    DragEnd,
+
+    /// The element was focused.
+    Focus,
+
+    /// The element lost focus.
+    Blur,
+}
+
+/// Something happened to an element of an UI context.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct UiEvent {
+    /// The entity of the [UiContext].
+    pub context: Entity,
+
+    pub key: UiKey,
+    pub kind: UiEventKind,
+
+    /// Where the cursor was.
+    pub position: Vec2,
+}
+
+/// The state of the pointer this frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PointerFrame {
+    /// Where the cursor is, if it is over the viewport.
+    pub position: Option<Vec2>,
+
+    /// The button pressed this frame, if any.
+    pub pressed: Option<MouseButton>,
+
+    /// The button released this frame, if any.
+    pub released: Option<MouseButton>,
+}
+
+/// An element holding the pointer, from a press until the release.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Capture {
+    key: UiKey,
+    button: MouseButton,
+    origin: Vec2,
+    last: Vec2,
+    dragging: bool,
+}
+
+/// The pointer state of an UI context, kept across frames.
+#[derive(Component, Clone, Debug, Default)]
+pub struct UiEventRouter {
+    hovered: Option<UiKey>,
+    focused: Option<UiKey>,
+    capture: Option<Capture>,
+}
+
+impl UiEventRouter {
+    /// The element under the cursor, if any.
+    pub fn hovered(&self) -> Option<UiKey> {
+        self.hovered
+    }
+
+    /// The focused element, if any.
+    pub fn focused(&self) -> Option<UiKey> {
+        self.focused
+    }
+
+    /// The element holding the pointer, if any.
+    pub fn captured(&self) -> Option<UiKey> {
+        self.capture.map(|capture| capture.key)
+    }
+
+    /// Routes a frame of pointer input to the elements of a laid out tree.
+    pub fn route(
+        &mut self,
+        context: Entity,
+        tree: &UiTree,
+        rects: &[Rect],
+        pointer: PointerFrame,
+    ) -> Vec<UiEvent> {
+        let mut events = vec![];
+        let position = pointer
+            .position
+            .or(self.capture.map(|capture| capture.last))
+            .unwrap_or_default();
+        let mut send = |key, kind| {
+            events.push(UiEvent {
+                context,
+                key,
+                kind,
+                position,
+            })
+        };
+
+        let hit = pointer
+            .position
+            .and_then(|point| tree.hit_test(rects, point));
+        if hit != self.hovered {
+            if let Some(old) = self.hovered {
+                send(old, UiEventKind::HoverEnd);
+            }
+            if let Some(new) = hit {
+                send(new, UiEventKind::HoverStart);
+            }
+            self.hovered = hit;
+        }
+
+        if let (Some(capture), Some(point)) = (&mut self.capture, pointer.position) {
+            if !capture.dragging && point.distance(capture.origin) > DRAG_THRESHOLD {
+                capture.dragging = true;
+                send(capture.key, UiEventKind::DragStart);
+            }
+            if capture.dragging && point != capture.last {
+                send(
+                    capture.key,
+                    UiEventKind::Drag {
+                        delta: point - capture.last,
+                    },
+                );
+            }
+            capture.last = point;
+        }
+
+        if let Some(button) = pointer.pressed.filter(|_| self.capture.is_none()) {
+            if let Some(key) = hit {
+                send(key, UiEventKind::Press(button));
+                self.capture = Some(Capture {
+                    key,
+                    button,
+                    origin: position,
+                    last: position,
+                    dragging: false,
+                });
+            }
+
+            if hit != self.focused {
+                if let Some(old) = self.focused {
+                    send(old, UiEventKind::Blur);
+                }
+                if let Some(new) = hit {
+                    send(new, UiEventKind::Focus);
+                }
+                self.focused = hit;
+            }
+        }
+
+        let released = pointer.released.and_then(|button| {
+            self.capture
+                .take_if(|capture| capture.button == button)
+                .map(|capture| (button, capture))
+        });
+        if let Some((button, capture)) = released {
+            send(capture.key, UiEventKind::Release(button));
+            if capture.dragging {
+                send(capture.key, UiEventKind::DragEnd);
+            } else if hit == Some(capture.key) {
+                send(capture.key, UiEventKind::Click(button));
+            }
+        }
+
+        events
+    }
+}
+
+fn route_ui_events(
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut contexts: Query<(Entity, &UiContext, &mut UiEventRouter)>,
+    mut events: EventWriter<UiEvent>,
+) {
+    let pointer = PointerFrame {
+        position: windows
+            .single()
+            .ok()
+            .and_then(|window| ui_cursor(window, &settings)),
+        pressed: mouse_buttons.get_just_pressed().next().copied(),
+        released: mouse_buttons.get_just_released().next().copied(),
+    };
+
+    for (entity, context, mut router) in &mut contexts {
+        events.write_batch(router.route(entity, &context.tree, context.rects(), pointer));
+    }
+}
+
+/// UI event routing plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct UiEventPlugin;
+
+impl Plugin for UiEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UiEvent>();
+        app.add_systems(PreUpdate, route_ui_events);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::app::renderer::ui::layouter::{Anchor, Content, Size, UiElement};
+
+    #[test]
+    fn drags_capture_the_pointer() {
+        let button = UiKey::new("button");
+        let mut tree = UiTree::default();
+        tree.add_root(
+            UiElement::new(Content::Rect {
+                color: Color::WHITE,
+            })
+            .with_size(Size::Fixed(100.0), Size::Fixed(100.0))
+            .with_anchor(Anchor::TopLeft)
+            .with_key(button),
+        );
+        let rects = tree.resolve(Rect::new(0.0, 0.0, 800.0, 600.0));
+
+        let mut router = UiEventRouter::default();
+        let mut route = |position: Vec2, pressed, released| {
+            router
+                .route(
+                    Entity::PLACEHOLDER,
+                    &tree,
+                    &rects,
+                    PointerFrame {
+                        position: Some(position),
+                        pressed,
+                        released,
+                    },
+                )
+                .into_iter()
+                .map(|event| event.kind)
+                .collect::<Vec<_>>()
+        };
+        let left = Some(MouseButton::Left);
+
+        assert_eq!(
+            route(Vec2::new(50.0, 50.0), left, left),
+            [
+                UiEventKind::HoverStart,
+                UiEventKind::Press(MouseButton::Left),
+                UiEventKind::Focus,
+                UiEventKind::Release(MouseButton::Left),
+                UiEventKind::Click(MouseButton::Left),
+            ]
+        );
+
+        // Dragging out of the element keeps it captured.
+        route(Vec2::new(50.0, 50.0), left, None);
+        assert_eq!(
+            route(Vec2::new(300.0, 50.0), None, None),
+            [
+                UiEventKind::HoverEnd,
+                UiEventKind::DragStart,
+                UiEventKind::Drag {
+                    delta: Vec2::new(250.0, 0.0)
+                },
+            ]
+        );
+        assert_eq!(
+            route(Vec2::new(300.0, 50.0), None, left),
+            [
+                UiEventKind::Release(MouseButton::Left),
+                UiEventKind::DragEnd
+            ]
+        );
+
+        // Pressing outside any element unfocuses.
+        assert_eq!(
+            route(Vec2::new(300.0, 50.0), left, None),
+            [UiEventKind::Blur]
+        );
+    }
+}
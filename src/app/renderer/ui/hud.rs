@@ -0,0 +1,401 @@
+//! # In-game HUD
+//!
+//! Shows the status of the player's ship while sailing the overworld: hull
+//! health, the status of each installed part and how long until weapons are
+//! reloaded, ammunition, fuel and food left, speed, and a compass showing
+//! the ship's course and the wind.
+//!
+//! The HUD is an UI context spawned when the overworld is entered, and
+//! despawned when it is left. Its elements are generated every frame from
+//! the player's ship, as a [ShipStatus]; without a player ship, it is empty.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    event::UiEventRouter,
+    layouter::{
+        Anchor, Axis, Content, Edges, ElementId, Layout, Size, UiContext, UiElement, UiTree,
+    },
+};
+use crate::common::{
+    construct::part::ConstructParts,
+    damage::{Health, Wrecked},
+    inventory::FuelType,
+    makeup::{
+        Ship,
+        parts::{ballista::BallistaPart, cannon::CannonPart, minelayer::MinelayerPart},
+        sync::MakeupItem,
+    },
+    physics::{base::PointNetwork, forces::Wind},
+    player::PlayerControlled,
+    state::GameState,
+};
+
+/// Meters per second in a knot.
+const METERS_PER_KNOT: f32 = 0.514_444;
+
+/// Slower than this, in meters per second, the ship has no course.
+const MIN_COURSE_SPEED: f32 = 0.2;
+
+const FONT_SIZE: f32 = 16.0;
+const SMALL_FONT_SIZE: f32 = 13.0;
+const BAR_SIZE: Vec2 = Vec2::new(180.0, 10.0);
+const PANEL_COLOR: Color = Color::srgba(0.05, 0.07, 0.1, 0.6);
+const TEXT_COLOR: Color = Color::srgb(0.92, 0.9, 0.84);
+const DIM_TEXT_COLOR: Color = Color::srgb(0.6, 0.6, 0.58);
+const BAR_BACK_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+
+/// Marks the HUD's UI context.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Hud;
+
+/// The status of an installed part.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartStatus {
+    pub name: String,
+
+    /// Health left, between 0.0 and 1.0, if the part can be damaged.
+    pub health: Option<f32>,
+
+    /// Time left until the part can fire again, in seconds, if it is a
+    /// weapon.
+    pub cooldown: Option<f32>,
+
+    pub wrecked: bool,
+}
+
+/// The status of the player's ship, as shown on the HUD.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShipStatus {
+    /// Hull health left, between 0.0 and 1.0.
+    pub hull: f32,
+
+    pub parts: Vec<PartStatus>,
+
+    /// How much of each kind of ammunition is left, by item name.
+    pub ammo: Vec<(String, f32)>,
+
+    pub coal: f32,
+    pub diesel: f32,
+    pub food: f32,
+
+    /// Speed over the water, in meters per second.
+    pub speed: f32,
+
+    /// The direction the ship is heading towards, in degrees clockwise from
+    /// north, if it is moving.
+    pub course: Option<f32>,
+
+    /// The direction the wind blows towards, in degrees clockwise from
+    /// north.
+    pub wind_direction: f32,
+
+    pub wind_strength: f32,
+}
+
+/// The bearing of a horizontal direction, in degrees clockwise from north
+/// (negative Z), between 0.0 and 360.0.
+pub fn bearing(direction: Vec3) -> f32 {
+    direction
+        .x
+        .atan2(-direction.z)
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+/// The nearest of the eight compass points to a bearing.
+pub fn compass_point(bearing: f32) -> &'static str {
+    const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    POINTS[((bearing.rem_euclid(360.0) / 45.0).round() as usize) % POINTS.len()]
+}
+
+/// A color from red to green, for a fraction between 0.0 and 1.0.
+fn status_color(fraction: f32) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0);
+    Color::srgb(0.9 * (1.0 - fraction) + 0.2, 0.2 + 0.6 * fraction, 0.2)
+}
+
+fn text(text: impl Into<String>, font_size: f32, color: Color) -> UiElement {
+    UiElement::new(Content::Text {
+        text: text.into(),
+        font_size,
+        color,
+    })
+}
+
+fn panel(anchor: Anchor) -> UiElement {
+    UiElement::new(Content::Rect { color: PANEL_COLOR })
+        .with_anchor(anchor)
+        .with_padding(Edges::all(8.0))
+        .with_layout(Layout::Stack {
+            axis: Axis::Vertical,
+            gap: 4.0,
+        })
+}
+
+/// Adds a bar filled up to a fraction, between 0.0 and 1.0.
+fn add_bar(tree: &mut UiTree, parent: ElementId, fraction: f32, color: Color) {
+    let bar = tree.add_child(
+        parent,
+        UiElement::new(Content::Rect {
+            color: BAR_BACK_COLOR,
+        })
+        .with_size(Size::Fixed(BAR_SIZE.x), Size::Fixed(BAR_SIZE.y)),
+    );
+    tree.add_child(
+        bar,
+        UiElement::new(Content::Rect { color })
+            .with_size(Size::Fraction(fraction.clamp(0.0, 1.0)), Size::FILL),
+    );
+}
+
+/// Adds the elements of the HUD for a ship's status.
+pub fn build_hud(tree: &mut UiTree, status: &ShipStatus) {
+    // Hull and parts, top left.
+    let ship = tree.add_root(panel(Anchor::TopLeft).with_offset(Vec2::splat(12.0)));
+    tree.add_child(
+        ship,
+        text(
+            format!("Hull {:.0}%", status.hull * 100.0),
+            FONT_SIZE,
+            TEXT_COLOR,
+        ),
+    );
+    add_bar(tree, ship, status.hull, status_color(status.hull));
+
+    for part in &status.parts {
+        let state = if part.wrecked {
+            "wrecked".to_string()
+        } else {
+            match part.cooldown {
+                Some(cooldown) if cooldown > 0.0 => format!("reloading {cooldown:.1}s"),
+                Some(_) => "ready".to_string(),
+                None => String::new(),
+            }
+        };
+        let color = if part.wrecked {
+            DIM_TEXT_COLOR
+        } else {
+            TEXT_COLOR
+        };
+
+        let row = tree.add_child(
+            ship,
+            UiElement::default().with_layout(Layout::Stack {
+                axis: Axis::Horizontal,
+                gap: 8.0,
+            }),
+        );
+        tree.add_child(row, text(&part.name, SMALL_FONT_SIZE, color));
+        if !state.is_empty() {
+            tree.add_child(row, text(state, SMALL_FONT_SIZE, DIM_TEXT_COLOR));
+        }
+        if let Some(health) = part.health.filter(|_| !part.wrecked) {
+            add_bar(tree, ship, health, status_color(health));
+        }
+    }
+
+    // Supplies, bottom left.
+    let supplies = tree.add_root(panel(Anchor::BottomLeft).with_offset(Vec2::new(12.0, -12.0)));
+    for (name, amount) in &status.ammo {
+        tree.add_child(
+            supplies,
+            text(format!("{name}: {amount:.0}"), FONT_SIZE, TEXT_COLOR),
+        );
+    }
+    for (name, amount) in [("Coal", status.coal), ("Diesel", status.diesel)] {
+        if amount > 0.0 {
+            tree.add_child(
+                supplies,
+                text(format!("{name}: {amount:.1}"), FONT_SIZE, TEXT_COLOR),
+            );
+        }
+    }
+    tree.add_child(
+        supplies,
+        text(
+            format!("Food: {:.0}", status.food),
+            FONT_SIZE,
+            if status.food > 0.0 {
+                TEXT_COLOR
+            } else {
+                status_color(0.0)
+            },
+        ),
+    );
+
+    // Speed and compass, top right.
+    let compass = tree.add_root(panel(Anchor::TopRight).with_offset(Vec2::new(-12.0, 12.0)));
+    tree.add_child(
+        compass,
+        text(
+            format!("{:.1} kn", status.speed / METERS_PER_KNOT),
+            FONT_SIZE,
+            TEXT_COLOR,
+        ),
+    );
+    tree.add_child(
+        compass,
+        text(
+            match status.course {
+                Some(course) => format!("Course {course:03.0}° {}", compass_point(course)),
+                None => "Course ---".to_string(),
+            },
+            FONT_SIZE,
+            TEXT_COLOR,
+        ),
+    );
+    tree.add_child(
+        compass,
+        text(
+            format!(
+                "Wind {:.1} towards {}",
+                status.wind_strength,
+                compass_point(status.wind_direction)
+            ),
+            FONT_SIZE,
+            TEXT_COLOR,
+        ),
+    );
+}
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        Hud,
+        Name::new("HUD"),
+        UiContext::default(),
+        UiEventRouter::default(),
+    ));
+}
+
+fn despawn_hud(mut commands: Commands, huds: Query<Entity, With<Hud>>) {
+    for hud in &huds {
+        commands.entity(hud).despawn();
+    }
+}
+
+type PartQueryData = (
+    Option<&'static MakeupItem>,
+    Option<&'static Health>,
+    Has<Wrecked>,
+    Option<&'static CannonPart>,
+    Option<&'static BallistaPart>,
+    Option<&'static MinelayerPart>,
+);
+
+fn update_hud(
+    wind: Option<Res<Wind>>,
+    ships: Query<
+        (
+            &Ship,
+            &PointNetwork,
+            Option<&Health>,
+            Option<&ConstructParts>,
+        ),
+        With<PlayerControlled>,
+    >,
+    parts: Query<PartQueryData>,
+    mut huds: Query<&mut UiContext, With<Hud>>,
+) {
+    let status = ships
+        .single()
+        .ok()
+        .map(|(ship, network, health, construct_parts)| {
+            let makeup = &ship.makeup;
+
+            let parts = construct_parts
+                .iter()
+                .flat_map(|parts| parts.iter())
+                .filter_map(|&part| parts.get(part).ok())
+                .map(|(item, health, wrecked, cannon, ballista, minelayer)| {
+                    let name = item
+                        .and_then(|item| makeup.item(item.0))
+                        .map(|item| item.name.clone())
+                        .unwrap_or_else(|| "Part".to_string());
+                    let cooldown = cannon
+                        .map(CannonPart::cooldown)
+                        .or(ballista.map(BallistaPart::cooldown))
+                        .or(minelayer.map(MinelayerPart::cooldown));
+
+                    PartStatus {
+                        name,
+                        health: health.map(Health::fraction),
+                        cooldown,
+                        wrecked,
+                    }
+                })
+                .collect();
+
+            let velocity = network.linear_velocity().with_y(0.0);
+            let speed = velocity.length();
+
+            let (wind_direction, wind_strength) = wind
+                .as_ref()
+                .map(|wind| (bearing(wind.horizontal_direction()), wind.strength))
+                .unwrap_or_default();
+
+            ShipStatus {
+                hull: health.map_or(1.0, Health::fraction),
+                parts,
+                ammo: makeup
+                    .ammo_left()
+                    .into_iter()
+                    .map(|(name, amount)| (name.to_string(), amount))
+                    .collect(),
+                coal: makeup.fuel_left(FuelType::Coal),
+                diesel: makeup.fuel_left(FuelType::Diesel),
+                food: makeup.food_left(),
+                speed,
+                course: (speed >= MIN_COURSE_SPEED).then(|| bearing(velocity)),
+                wind_direction,
+                wind_strength,
+            }
+        });
+
+    for mut context in &mut huds {
+        context.tree.clear();
+        if let Some(status) = &status {
+            build_hud(&mut context.tree, status);
+        }
+    }
+}
+
+/// In-game HUD plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Overworld), spawn_hud);
+        app.add_systems(OnExit(GameState::Overworld), despawn_hud);
+        app.add_systems(Update, update_hud.run_if(in_state(GameState::Overworld)));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn bearings_point_clockwise_from_north() {
+        assert_eq!(compass_point(bearing(Vec3::NEG_Z)), "N");
+        assert_eq!(compass_point(bearing(Vec3::X)), "E");
+        assert_eq!(compass_point(bearing(Vec3::Z)), "S");
+        assert_eq!(compass_point(bearing(Vec3::new(-1.0, 0.0, -1.0))), "NW");
+        assert!((bearing(Vec3::NEG_X) - 270.0).abs() < 1e-3);
+    }
+}
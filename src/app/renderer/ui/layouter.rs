@@ -0,0 +1,787 @@
+//! # UI layouter
+//!
+//! Lays out the elements of an UI context, and produces the [UiCommand]s
+//! which render them.
+//!
+//! Elements form a tree, kept in a [UiTree]. Each [UiElement] has a size
+//! along each axis (see [Size]), clamped to its minimum and maximum size,
+//! and padding, which its children are laid out within. How its children are
+//! laid out depends on its [Layout]:
+//!
+//! * [Layout::Free] places each child at its [Anchor], plus an offset;
+//! * [Layout::Stack] places children one after the other, along an
+//!   [Axis], with a gap between them. Children with a [Size::Flex] size
+//!   along that axis share the space left over, by weight, which is how flex
+//!   containers are made.
+//!
+//! Layout takes two passes: sizes are first measured bottom-up, for
+//! [Size::Auto] elements to fit their content and children, then rects are
+//! assigned top-down, from the viewport. Commands are produced in tree
+//! order, so parents are drawn below their children.
+//!
+//! Elements can be given a [UiKey], which identifies them across frames, as
+//! the tree is rebuilt every frame. Only keyed elements receive UI events
+//! (see [event](super::event)).
+//!
+//! Coordinates are in logical pixels, from the top left corner of the
+//! viewport, with Y pointing down.
+//!
+//! [NOTE] Text is measured with a fixed glyph width for now, as the layouter
+//! has no access to font metrics.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use bevy::prelude::*;
+
+/// Width of a text glyph, as a fraction of the font size.
+const GLYPH_WIDTH: f32 = 0.6;
+
+/// Height of a line of text, as a fraction of the font size.
+const LINE_HEIGHT: f32 = 1.2;
+
+/// Identifies an element in its [UiTree].
+pub type ElementId = usize;
+
+/// Identifies an element across frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UiKey(pub u64);
+
+impl UiKey {
+    /// A key made from anything hashable, such as a name, or an entity
+    /// and a name.
+    pub fn new(source: impl Hash) -> Self {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// A direction elements can be stacked in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Axis {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    /// The component of a vector along this axis.
+    fn main(self, vec: Vec2) -> f32 {
+        match self {
+            Self::Horizontal => vec.x,
+            Self::Vertical => vec.y,
+        }
+    }
+
+    /// The component of a vector across this axis.
+    fn cross(self, vec: Vec2) -> f32 {
+        match self {
+            Self::Horizontal => vec.y,
+            Self::Vertical => vec.x,
+        }
+    }
+
+    /// A vector from its components along and across this axis.
+    fn vec(self, main: f32, cross: f32) -> Vec2 {
+        match self {
+            Self::Horizontal => Vec2::new(main, cross),
+            Self::Vertical => Vec2::new(cross, main),
+        }
+    }
+}
+
+/// Where an element sits within the space its parent gives it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// How far along the available space the element sits, on each axis,
+    /// from 0.0 to 1.0.
+    pub fn fraction(self) -> Vec2 {
+        match self {
+            Self::TopLeft => Vec2::new(0.0, 0.0),
+            Self::Top => Vec2::new(0.5, 0.0),
+            Self::TopRight => Vec2::new(1.0, 0.0),
+            Self::Left => Vec2::new(0.0, 0.5),
+            Self::Center => Vec2::new(0.5, 0.5),
+            Self::Right => Vec2::new(1.0, 0.5),
+            Self::BottomLeft => Vec2::new(0.0, 1.0),
+            Self::Bottom => Vec2::new(0.5, 1.0),
+            Self::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+}
+
+/// Space around the inside of an element.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Edges {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Edges {
+    /// The same space on every side.
+    pub fn all(space: f32) -> Self {
+        Self::symmetric(space, space)
+    }
+
+    /// The same space on the left and right, and on the top and bottom.
+    pub fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self {
+            left: horizontal,
+            right: horizontal,
+            top: vertical,
+            bottom: vertical,
+        }
+    }
+
+    /// The total space on each axis.
+    pub fn total(&self) -> Vec2 {
+        Vec2::new(self.left + self.right, self.top + self.bottom)
+    }
+}
+
+/// How big an element is along an axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Size {
+    /// Fits its content and children.
+    #[default]
+    Auto,
+
+    /// A fixed size, in logical pixels.
+    Fixed(f32),
+
+    /// A fraction of the space its parent lays it out within.
+    Fraction(f32),
+
+    /// A share of the space left over by its siblings, by weight, along the
+    /// main axis of a [Layout::Stack]; all of the space it is laid out
+    /// within otherwise.
+    Flex(f32),
+}
+
+impl Size {
+    /// Takes all the space available.
+    pub const FILL: Self = Self::Flex(1.0);
+}
+
+/// How an element lays out its children.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Layout {
+    /// Each child is placed at its anchor.
+    #[default]
+    Free,
+
+    /// Children are placed one after the other.
+    ///
+    /// Across the axis, children are placed at their anchor.
+    Stack { axis: Axis, gap: f32 },
+}
+
+/// What an element displays.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Content {
+    /// Nothing; only lays out its children.
+    #[default]
+    None,
+
+    /// A filled rectangle.
+    Rect { color: Color },
+
+    /// An image, drawn at its natural size unless sized otherwise.
+    Image {
+        image: Handle<Image>,
+        size: Vec2,
+        color: Color,
+
+        /// The part of the image to draw, in pixels; all of it if [None].
+        region: Option<Rect>,
+    },
+
+    /// A single line of text.
+    Text {
+        text: String,
+        font_size: f32,
+        color: Color,
+    },
+}
+
+impl Content {
+    /// The size this content would like to have.
+    fn natural_size(&self) -> Vec2 {
+        match self {
+            Self::None | Self::Rect { .. } => Vec2::ZERO,
+            Self::Image { size, .. } => *size,
+            Self::Text {
+                text, font_size, ..
+            } => Vec2::new(
+                text.chars().count() as f32 * font_size * GLYPH_WIDTH,
+                font_size * LINE_HEIGHT,
+            ),
+        }
+    }
+}
+
+/// An UI element, before layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiElement {
+    pub content: Content,
+    pub width: Size,
+    pub height: Size,
+    pub min_size: Vec2,
+    pub max_size: Vec2,
+
+    /// Where the element sits within the space its parent gives it.
+    pub anchor: Anchor,
+
+    /// Moves the element after it is laid out.
+    pub offset: Vec2,
+
+    pub padding: Edges,
+    pub layout: Layout,
+
+    /// Identifies the element across frames, for it to receive events.
+    pub key: Option<UiKey>,
+
+    children: Vec<ElementId>,
+}
+
+impl Default for UiElement {
+    fn default() -> Self {
+        Self {
+            content: Content::None,
+            width: Size::Auto,
+            height: Size::Auto,
+            min_size: Vec2::ZERO,
+            max_size: Vec2::INFINITY,
+            anchor: Anchor::TopLeft,
+            offset: Vec2::ZERO,
+            padding: Edges::default(),
+            layout: Layout::Free,
+            key: None,
+            children: vec![],
+        }
+    }
+}
+
+impl UiElement {
+    /// An automatically sized element, displaying some content.
+    pub fn new(content: Content) -> Self {
+        Self {
+            content,
+            ..default()
+        }
+    }
+
+    pub fn with_size(mut self, width: Size, height: Size) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_min_size(mut self, min_size: Vec2) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: Vec2) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Edges) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_key(mut self, key: UiKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// The children of this element, in layout order.
+    pub fn children(&self) -> &[ElementId] {
+        &self.children
+    }
+
+    /// Keeps a size within this element's minimum and maximum size.
+    fn clamp(&self, size: Vec2) -> Vec2 {
+        size.max(self.min_size)
+            .min(self.max_size.max(self.min_size))
+    }
+}
+
+/// A low-level command to render a laid out element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiCommand {
+    Rect {
+        rect: Rect,
+        color: Color,
+    },
+    Image {
+        rect: Rect,
+        image: Handle<Image>,
+        color: Color,
+        region: Option<Rect>,
+    },
+    Text {
+        rect: Rect,
+        text: String,
+        font_size: f32,
+        color: Color,
+    },
+}
+
+/// The elements of an UI context.
+#[derive(Clone, Debug, Default)]
+pub struct UiTree {
+    elements: Vec<UiElement>,
+    roots: Vec<ElementId>,
+}
+
+impl UiTree {
+    /// Adds an element at the top level, laid out within the viewport.
+    pub fn add_root(&mut self, element: UiElement) -> ElementId {
+        let id = self.push(element);
+        self.roots.push(id);
+        id
+    }
+
+    /// Adds an element as the last child of another.
+    ///
+    /// # Panics
+    ///
+    /// If the parent is not in this tree.
+    pub fn add_child(&mut self, parent: ElementId, element: UiElement) -> ElementId {
+        let id = self.push(element);
+        self.elements[parent].children.push(id);
+        id
+    }
+
+    fn push(&mut self, element: UiElement) -> ElementId {
+        self.elements.push(element);
+        self.elements.len() - 1
+    }
+
+    pub fn get(&self, id: ElementId) -> Option<&UiElement> {
+        self.elements.get(id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Removes every element, e.g. before the next frame's are added.
+    pub fn clear(&mut self) {
+        self.elements.clear();
+        self.roots.clear();
+    }
+
+    /// The rect of every element, by ID, within a viewport.
+    pub fn resolve(&self, viewport: Rect) -> Vec<Rect> {
+        // Children are always added after their parents, so measuring
+        // backwards measures them first.
+        let mut measured = vec![Vec2::ZERO; self.elements.len()];
+        for id in (0..self.elements.len()).rev() {
+            measured[id] = self.measure(id, &measured);
+        }
+
+        let mut rects = vec![Rect::default(); self.elements.len()];
+        self.arrange_children(&self.roots, Layout::Free, viewport, &measured, &mut rects);
+        rects
+    }
+
+    /// Lays out every element within a viewport, and produces the commands
+    /// to render them, parents first.
+    pub fn layout(&self, viewport: Rect) -> Vec<UiCommand> {
+        self.commands(&self.resolve(viewport))
+    }
+
+    /// Every element, in the order they are drawn in.
+    pub fn draw_order(&self) -> Vec<ElementId> {
+        let mut order = Vec::with_capacity(self.elements.len());
+        let mut stack: Vec<_> = self.roots.iter().rev().copied().collect();
+
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.elements[id].children.iter().rev());
+        }
+
+        order
+    }
+
+    /// The topmost keyed element at a point, given the rect of every
+    /// element.
+    pub fn hit_test(&self, rects: &[Rect], point: Vec2) -> Option<UiKey> {
+        self.draw_order()
+            .into_iter()
+            .rev()
+            .filter(|&id| rects.get(id).is_some_and(|rect| rect.contains(point)))
+            .find_map(|id| self.elements[id].key)
+    }
+
+    /// The commands to render every element, given their rects.
+    pub fn commands(&self, rects: &[Rect]) -> Vec<UiCommand> {
+        let mut commands = Vec::with_capacity(self.elements.len());
+
+        for id in self.draw_order() {
+            let element = &self.elements[id];
+            let rect = rects[id];
+            commands.extend(match &element.content {
+                Content::None => None,
+                Content::Rect { color } => Some(UiCommand::Rect {
+                    rect,
+                    color: *color,
+                }),
+                Content::Image {
+                    image,
+                    color,
+                    region,
+                    ..
+                } => Some(UiCommand::Image {
+                    rect,
+                    image: image.clone(),
+                    color: *color,
+                    region: *region,
+                }),
+                Content::Text {
+                    text,
+                    font_size,
+                    color,
+                } => Some(UiCommand::Text {
+                    rect,
+                    text: text.clone(),
+                    font_size: *font_size,
+                    color: *color,
+                }),
+            });
+        }
+
+        commands
+    }
+
+    /// The size an element would like to have, regardless of its parent,
+    /// given the sizes of its children.
+    fn measure(&self, id: ElementId, measured: &[Vec2]) -> Vec2 {
+        let element = &self.elements[id];
+        let children = element.children.iter().map(|&child| {
+            let child_element = &self.elements[child];
+            let size = measured[child];
+            // Sizes relative to the parent can't contribute to its own.
+            let fixed = |size: Size, measured: f32| match size {
+                Size::Auto | Size::Fixed(_) => measured,
+                Size::Fraction(_) | Size::Flex(_) => 0.0,
+            };
+            Vec2::new(
+                fixed(child_element.width, size.x),
+                fixed(child_element.height, size.y),
+            ) + child_element.offset.max(Vec2::ZERO)
+        });
+
+        let children_size = match element.layout {
+            Layout::Free => children.fold(Vec2::ZERO, Vec2::max),
+            Layout::Stack { axis, gap } => {
+                let (count, main, cross) =
+                    children.fold((0_usize, 0.0_f32, 0.0_f32), |(count, main, cross), size| {
+                        (
+                            count + 1,
+                            main + axis.main(size),
+                            cross.max(axis.cross(size)),
+                        )
+                    });
+                axis.vec(main + gap * count.saturating_sub(1) as f32, cross)
+            }
+        };
+
+        let content = element
+            .content
+            .natural_size()
+            .max(children_size + element.padding.total());
+        let size = |size: Size, content: f32| match size {
+            Size::Fixed(fixed) => fixed,
+            _ => content,
+        };
+
+        element.clamp(Vec2::new(
+            size(element.width, content.x),
+            size(element.height, content.y),
+        ))
+    }
+
+    /// The size of an element laid out within some space, along one axis.
+    fn size_within(size: Size, measured: f32, available: f32) -> f32 {
+        match size {
+            Size::Auto | Size::Fixed(_) => measured,
+            Size::Fraction(fraction) => available * fraction,
+            Size::Flex(_) => available,
+        }
+    }
+
+    /// Assigns rects to an element and its descendants.
+    fn arrange(&self, id: ElementId, rect: Rect, measured: &[Vec2], rects: &mut [Rect]) {
+        rects[id] = rect;
+
+        let element = &self.elements[id];
+        let padding = element.padding;
+        let inner = Rect::new(
+            rect.min.x + padding.left,
+            rect.min.y + padding.top,
+            (rect.max.x - padding.right).max(rect.min.x + padding.left),
+            (rect.max.y - padding.bottom).max(rect.min.y + padding.top),
+        );
+
+        self.arrange_children(&element.children, element.layout, inner, measured, rects);
+    }
+
+    /// Assigns rects to some elements, laid out within a rect.
+    fn arrange_children(
+        &self,
+        children: &[ElementId],
+        layout: Layout,
+        inner: Rect,
+        measured: &[Vec2],
+        rects: &mut [Rect],
+    ) {
+        let available = inner.size();
+
+        match layout {
+            Layout::Free => {
+                for &child in children {
+                    let element = &self.elements[child];
+                    let size = element.clamp(Vec2::new(
+                        Self::size_within(element.width, measured[child].x, available.x),
+                        Self::size_within(element.height, measured[child].y, available.y),
+                    ));
+                    let min =
+                        inner.min + (available - size) * element.anchor.fraction() + element.offset;
+
+                    self.arrange(child, Rect::from_corners(min, min + size), measured, rects);
+                }
+            }
+
+            Layout::Stack { axis, gap } => {
+                let main_size = |element: &UiElement| match axis {
+                    Axis::Horizontal => element.width,
+                    Axis::Vertical => element.height,
+                };
+                let cross_size = |element: &UiElement| match axis {
+                    Axis::Horizontal => element.height,
+                    Axis::Vertical => element.width,
+                };
+
+                // Everything but flex children takes its space first.
+                let gaps = gap * children.len().saturating_sub(1) as f32;
+                let (taken, weights) =
+                    children
+                        .iter()
+                        .fold((gaps, 0.0), |(taken, weights), &child| {
+                            let element = &self.elements[child];
+                            match main_size(element) {
+                                Size::Flex(weight) => (taken, weights + weight.max(0.0)),
+                                size => (
+                                    taken
+                                        + axis.main(element.clamp(axis.vec(
+                                            Self::size_within(
+                                                size,
+                                                axis.main(measured[child]),
+                                                axis.main(available),
+                                            ),
+                                            0.0,
+                                        ))),
+                                    weights,
+                                ),
+                            }
+                        });
+                let leftover = (axis.main(available) - taken).max(0.0);
+
+                let mut cursor = axis.main(inner.min);
+                for &child in children {
+                    let element = &self.elements[child];
+                    let main = match main_size(element) {
+                        Size::Flex(weight) if weights > 0.0 => leftover * weight.max(0.0) / weights,
+                        size => Self::size_within(
+                            size,
+                            axis.main(measured[child]),
+                            axis.main(available),
+                        ),
+                    };
+                    let cross = Self::size_within(
+                        cross_size(element),
+                        axis.cross(measured[child]),
+                        axis.cross(available),
+                    );
+                    let size = element.clamp(axis.vec(main, cross));
+
+                    let cross_offset = (axis.cross(available) - axis.cross(size))
+                        * axis.cross(element.anchor.fraction());
+                    let min =
+                        axis.vec(cursor, axis.cross(inner.min) + cross_offset) + element.offset;
+
+                    self.arrange(child, Rect::from_corners(min, min + size), measured, rects);
+                    cursor += axis.main(size) + gap;
+                }
+            }
+        }
+    }
+}
+
+/// An UI context: the elements it produced this frame, and where they were
+/// laid out.
+#[derive(Component, Clone, Debug, Default)]
+pub struct UiContext {
+    pub tree: UiTree,
+    rects: Vec<Rect>,
+}
+
+impl UiContext {
+    /// Lays out the context's elements within a viewport, and produces the
+    /// commands to render them.
+    pub fn layout(&mut self, viewport: Rect) -> Vec<UiCommand> {
+        self.rects = self.tree.resolve(viewport);
+        self.tree.commands(&self.rects)
+    }
+
+    /// The rect of every element, by ID, as of the last layout.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn viewport() -> Rect {
+        Rect::new(0.0, 0.0, 800.0, 600.0)
+    }
+
+    fn fixed(width: f32, height: f32) -> UiElement {
+        UiElement::new(Content::Rect {
+            color: Color::WHITE,
+        })
+        .with_size(Size::Fixed(width), Size::Fixed(height))
+    }
+
+    #[test]
+    fn anchors_place_elements_in_their_parent() {
+        let mut tree = UiTree::default();
+        let centered = tree.add_root(fixed(100.0, 50.0).with_anchor(Anchor::Center));
+        let corner = tree.add_root(
+            fixed(100.0, 50.0)
+                .with_anchor(Anchor::BottomRight)
+                .with_offset(Vec2::new(-10.0, -10.0)),
+        );
+
+        let rects = tree.resolve(viewport());
+        assert_eq!(rects[centered], Rect::new(350.0, 275.0, 450.0, 325.0));
+        assert_eq!(rects[corner], Rect::new(690.0, 540.0, 790.0, 590.0));
+    }
+
+    #[test]
+    fn stacks_share_leftover_space_by_weight() {
+        let mut tree = UiTree::default();
+        let row = tree.add_root(
+            UiElement::default()
+                .with_size(Size::FILL, Size::Fixed(100.0))
+                .with_padding(Edges::all(10.0))
+                .with_layout(Layout::Stack {
+                    axis: Axis::Horizontal,
+                    gap: 20.0,
+                }),
+        );
+        let fixed_child = tree.add_child(row, fixed(100.0, 30.0).with_anchor(Anchor::Left));
+        let one = tree.add_child(
+            row,
+            UiElement::default().with_size(Size::Flex(1.0), Size::FILL),
+        );
+        let three = tree.add_child(
+            row,
+            UiElement::default().with_size(Size::Flex(3.0), Size::FILL),
+        );
+
+        let rects = tree.resolve(viewport());
+        assert_eq!(rects[row], Rect::new(0.0, 0.0, 800.0, 100.0));
+
+        // 780 wide inside, minus 100 fixed, minus two gaps of 20.
+        assert_eq!(rects[fixed_child], Rect::new(10.0, 35.0, 110.0, 65.0));
+        assert_eq!(rects[one], Rect::new(130.0, 10.0, 290.0, 90.0));
+        assert_eq!(rects[three], Rect::new(310.0, 10.0, 790.0, 90.0));
+    }
+
+    #[test]
+    fn auto_sizes_fit_children_within_limits() {
+        let mut tree = UiTree::default();
+        let column = tree.add_root(
+            UiElement::new(Content::Rect {
+                color: Color::BLACK,
+            })
+            .with_padding(Edges::symmetric(5.0, 10.0))
+            .with_layout(Layout::Stack {
+                axis: Axis::Vertical,
+                gap: 4.0,
+            }),
+        );
+        tree.add_child(column, fixed(50.0, 20.0));
+        tree.add_child(column, fixed(80.0, 20.0));
+
+        let limited = tree.add_root(
+            fixed(500.0, 10.0)
+                .with_max_size(Vec2::new(200.0, f32::INFINITY))
+                .with_min_size(Vec2::new(0.0, 40.0))
+                .with_anchor(Anchor::Right),
+        );
+
+        let rects = tree.resolve(viewport());
+        assert_eq!(rects[column], Rect::new(0.0, 0.0, 90.0, 64.0));
+        assert_eq!(rects[limited], Rect::new(600.0, 280.0, 800.0, 320.0));
+
+        // Parents are drawn below their children.
+        let commands = tree.layout(viewport());
+        assert_eq!(commands.len(), 4);
+        assert!(matches!(
+            commands[0],
+            UiCommand::Rect { color, .. } if color == Color::BLACK
+        ));
+    }
+}
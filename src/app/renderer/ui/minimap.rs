@@ -0,0 +1,521 @@
+//! # Minimap
+//!
+//! Shows a chart of the island on the HUD, with the player's ship, the rest
+//! of the fleet, nearby NPC ships, props, map markers and pickups on it.
+//!
+//! The chart is rendered top-down into a texture once per raid, from the
+//! island's terrain: either its single [TerrainMarker], or the whole of a
+//! chunked terrain (see [TerrainChunkIndex]). North (negative Z) is up.
+//!
+//! The minimap shows an area around the player's ship, as wide as the current
+//! zoom level (see [MinimapSettings::zoom_levels]), cycled through with
+//! [InputAction::MinimapZoom]. Pickups near each other are shown as a single
+//! icon.
+//!
+//! With [MinimapSettings::fog_of_discovery], only what the fleet has sailed
+//! near is charted; props and markers elsewhere are hidden too. Ships and
+//! pickups are only shown while near the fleet regardless.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    time::common_conditions::on_timer,
+};
+
+use super::layouter::{Anchor, Content, Edges, ElementId, Size, UiContext, UiElement, UiTree};
+use crate::{
+    app::{
+        input::{ActionState, InputAction},
+        settings::{MinimapSettings, Settings},
+    },
+    common::{
+        diagnostics::AllocationScope,
+        fleet::InFleet,
+        inventory::pickup::Pickup,
+        makeup::Ship,
+        markers::{MapMarker, MarkerKind},
+        physics::{base::PointNetwork, water::WaterPhysics},
+        player::PlayerControlled,
+        props::Prop,
+        scratch::ScratchBuffer,
+        state::GameState,
+        terrain::{
+            biome::TerrainBiome,
+            buffer::TerrainMarker,
+            chunk::{TerrainChunk, TerrainChunkIndex},
+        },
+    },
+};
+
+/// Width and height of the chart texture, in pixels.
+const CHART_SIZE: u32 = 256;
+
+/// Width and height of the minimap on the HUD.
+const MINIMAP_SIZE: f32 = 200.0;
+
+/// How far from the fleet the chart is discovered, in meters.
+const DISCOVERY_RADIUS: f32 = 120.0;
+
+/// How far from the fleet NPC ships and pickups are shown, in meters.
+const DETECTION_RANGE: f32 = 250.0;
+
+/// Pickups within the same square this wide are shown as one icon.
+const CLUSTER_SIZE: f32 = 30.0;
+
+/// Water this much deeper than the water level is charted as deep water.
+const DEEP_WATER: f32 = 20.0;
+
+const PANEL_COLOR: Color = Color::srgba(0.05, 0.07, 0.1, 0.6);
+const FOG_COLOR: Color = Color::srgb(0.12, 0.13, 0.15);
+const SHALLOW_WATER_COLOR: Color = Color::srgb(0.35, 0.6, 0.7);
+const DEEP_WATER_COLOR: Color = Color::srgb(0.08, 0.18, 0.35);
+const PLAYER_COLOR: Color = Color::srgb(1.0, 0.95, 0.6);
+const FLEET_COLOR: Color = Color::srgb(0.4, 0.9, 0.4);
+const NPC_COLOR: Color = Color::srgb(0.95, 0.35, 0.3);
+const PROP_COLOR: Color = Color::srgb(0.45, 0.35, 0.25);
+const PICKUP_COLOR: Color = Color::srgb(0.95, 0.8, 0.3);
+
+/// The minimap's UI context.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Minimap {
+    /// Index of the current zoom level, in [MinimapSettings::zoom_levels].
+    pub zoom: usize,
+}
+
+/// The chart of the current island.
+#[derive(Resource, Clone, Debug)]
+pub struct MinimapChart {
+    pub image: Handle<Image>,
+
+    /// The area charted, on the XZ plane; always square.
+    pub bounds: Rect,
+
+    /// The color of each pixel, once discovered.
+    colors: Vec<[u8; 4]>,
+
+    discovered: Vec<bool>,
+
+    /// Whether the image currently hides undiscovered pixels.
+    fogged: bool,
+}
+
+impl MinimapChart {
+    /// The pixel of the chart at a world space XZ position, if charted.
+    fn pixel_at(&self, at: Vec2) -> Option<usize> {
+        let pixel = ((at - self.bounds.min) / self.bounds.size() * CHART_SIZE as f32).floor();
+        if pixel.cmplt(Vec2::ZERO).any() || pixel.cmpge(Vec2::splat(CHART_SIZE as f32)).any() {
+            return None;
+        }
+        Some(pixel.y as usize * CHART_SIZE as usize + pixel.x as usize)
+    }
+
+    /// Whether a world space XZ position has been discovered.
+    pub fn is_discovered(&self, at: Vec2) -> bool {
+        self.pixel_at(at)
+            .is_some_and(|pixel| self.discovered[pixel])
+    }
+
+    /// Discovers the chart within a radius of a world space XZ position.
+    ///
+    /// Returns whether anything new was discovered.
+    pub fn discover(&mut self, center: Vec2, radius: f32) -> bool {
+        let pixel_size = self.bounds.width() / CHART_SIZE as f32;
+        let reach = (radius / pixel_size).ceil() as i32;
+        let mut changed = false;
+
+        for y in -reach..=reach {
+            for x in -reach..=reach {
+                let offset = IVec2::new(x, y).as_vec2() * pixel_size;
+                if offset.length() > radius {
+                    continue;
+                }
+                if let Some(pixel) = self.pixel_at(center + offset) {
+                    changed |= !self.discovered[pixel];
+                    self.discovered[pixel] = true;
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// The texture data of the chart, hiding undiscovered pixels if fogged.
+    fn texture_data(&self, fogged: bool) -> Vec<u8> {
+        let fog = FOG_COLOR.to_srgba().to_u8_array();
+        self.colors
+            .iter()
+            .zip(&self.discovered)
+            .flat_map(
+                |(&color, &discovered)| {
+                    if fogged && !discovered { fog } else { color }
+                },
+            )
+            .collect()
+    }
+}
+
+/// The color of a point of the chart, given the height of the terrain, and
+/// its biome if known.
+pub fn chart_color(height: f32, biome: Option<TerrainBiome>, water_level: f32) -> Color {
+    if height < water_level {
+        let depth = ((water_level - height) / DEEP_WATER).clamp(0.0, 1.0);
+        SHALLOW_WATER_COLOR.mix(&DEEP_WATER_COLOR, depth)
+    } else {
+        biome.unwrap_or_default().color()
+    }
+}
+
+/// The area shown by the minimap, as wide as `span` and centered as close to
+/// `center` as it can be while staying within the chart.
+pub fn chart_view(bounds: Rect, center: Vec2, span: f32) -> Rect {
+    let span = span.min(bounds.width());
+    let half = Vec2::splat(span * 0.5);
+    let center = center.clamp(bounds.min + half, bounds.max - half);
+    Rect::from_center_size(center, Vec2::splat(span))
+}
+
+/// Groups points into clusters, by which square of a grid they are in,
+/// returning the average position and number of points of each.
+pub fn cluster(points: impl Iterator<Item = Vec2>, cell_size: f32) -> Vec<(Vec2, usize)> {
+    let mut cells = HashMap::<IVec2, (Vec2, usize)>::default();
+    for point in points {
+        let cell = cells
+            .entry((point / cell_size).floor().as_ivec2())
+            .or_default();
+        cell.0 += point;
+        cell.1 += 1;
+    }
+
+    cells
+        .into_values()
+        .map(|(sum, count)| (sum / count as f32, count))
+        .collect()
+}
+
+fn marker_color(kind: MarkerKind) -> Color {
+    match kind {
+        MarkerKind::Loot => PICKUP_COLOR,
+        MarkerKind::SafeChannel => Color::srgb(0.4, 0.85, 0.95),
+        MarkerKind::Danger => NPC_COLOR,
+        MarkerKind::Generic => Color::WHITE,
+    }
+}
+
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        Minimap::default(),
+        Name::new("Minimap"),
+        UiContext::default(),
+    ));
+}
+
+fn despawn_minimap(mut commands: Commands, minimaps: Query<Entity, With<Minimap>>) {
+    for minimap in &minimaps {
+        commands.entity(minimap).despawn();
+    }
+
+    // Every raid is on a different island.
+    commands.remove_resource::<MinimapChart>();
+}
+
+fn build_chart(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut images: ResMut<Assets<Image>>,
+    index: Option<Res<TerrainChunkIndex>>,
+    terrains: Query<(&TerrainMarker, &GlobalTransform), Without<TerrainChunk>>,
+    players: Query<&WaterPhysics, With<PlayerControlled>>,
+    mut scratch: Local<ScratchBuffer<f32>>,
+) {
+    let _scope = AllocationScope::new("build_chart");
+
+    let water_level = players.single().map_or(0.0, |water| water.water_level);
+
+    let (bounds, colors) = if let Some(index) = index {
+        let bounds = index.bounds();
+        let bounds =
+            Rect::from_center_size(bounds.center(), Vec2::splat(bounds.size().max_element()));
+        let colors = chart_pixels(bounds, |at| {
+            let height = index.height_at_with(at, &mut scratch);
+            let biome = index.source().biome_at(at, &mut scratch);
+            chart_color(height, Some(biome), water_level)
+        });
+        (bounds, colors)
+    } else if let Some((terrain, transform)) = terrains.iter().next() {
+        let buffer = &terrain.buffer;
+        let center = transform.translation();
+        let size = Vec2::new(buffer.get_real_width(), buffer.get_real_height());
+        let bounds = Rect::from_center_size(center.xz(), Vec2::splat(size.max_element()));
+        let colors = chart_pixels(bounds, |at| {
+            let local = at - center.xz();
+            let height = buffer.get_height_at(local.x, local.y) + center.y;
+            chart_color(height, buffer.get_biome_at(local.x, local.y), water_level)
+        });
+        (bounds, colors)
+    } else {
+        return;
+    };
+
+    let mut chart = MinimapChart {
+        image: Handle::default(),
+        bounds,
+        discovered: vec![false; colors.len()],
+        colors,
+        fogged: settings.minimap.fog_of_discovery,
+    };
+    chart.image = images.add(Image::new(
+        Extent3d {
+            width: CHART_SIZE,
+            height: CHART_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        chart.texture_data(chart.fogged),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ));
+    commands.insert_resource(chart);
+}
+
+/// The color of every pixel of a chart of an area, row by row from its
+/// northmost edge.
+fn chart_pixels(bounds: Rect, mut color_at: impl FnMut(Vec2) -> Color) -> Vec<[u8; 4]> {
+    let pixel_size = bounds.size() / CHART_SIZE as f32;
+    (0..CHART_SIZE)
+        .flat_map(|y| (0..CHART_SIZE).map(move |x| UVec2::new(x, y)))
+        .map(|pixel| {
+            let at = bounds.min + (pixel.as_vec2() + 0.5) * pixel_size;
+            color_at(at).to_srgba().to_u8_array()
+        })
+        .collect()
+}
+
+fn discover_chart(
+    settings: Res<Settings>,
+    mut chart: ResMut<MinimapChart>,
+    mut images: ResMut<Assets<Image>>,
+    fleet: Query<&PointNetwork, (With<Ship>, Or<(With<InFleet>, With<PlayerControlled>)>)>,
+) {
+    let mut changed = false;
+    for network in &fleet {
+        changed |= chart.discover(network.center_of_mass().xz(), DISCOVERY_RADIUS);
+    }
+
+    let fogged = settings.minimap.fog_of_discovery;
+    if !(changed && fogged) && chart.fogged == fogged {
+        return;
+    }
+    chart.fogged = fogged;
+
+    if let Some(image) = images.get_mut(&chart.image) {
+        image.data = Some(chart.texture_data(fogged));
+    }
+}
+
+fn minimap_zoom(
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    mut minimaps: Query<&mut Minimap>,
+) {
+    if !actions.just_pressed(InputAction::MinimapZoom) {
+        return;
+    }
+    let levels = settings.minimap.zoom_levels.len().max(1);
+    for mut minimap in &mut minimaps {
+        minimap.zoom = (minimap.zoom + 1) % levels;
+    }
+}
+
+/// Adds an icon to the minimap.
+fn add_icon(tree: &mut UiTree, map: ElementId, view: Rect, at: Vec2, size: f32, color: Color) {
+    let position = (at - view.min) / view.size() * MINIMAP_SIZE;
+    if position.cmplt(Vec2::ZERO).any() || position.cmpgt(Vec2::splat(MINIMAP_SIZE)).any() {
+        return;
+    }
+
+    tree.add_child(
+        map,
+        UiElement::new(Content::Rect { color })
+            .with_size(Size::Fixed(size), Size::Fixed(size))
+            .with_offset(position - size * 0.5),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_minimap(
+    settings: Res<Settings>,
+    chart: Option<Res<MinimapChart>>,
+    fleet: Query<
+        (&PointNetwork, Has<PlayerControlled>),
+        (With<Ship>, Or<(With<InFleet>, With<PlayerControlled>)>),
+    >,
+    npcs: Query<&PointNetwork, (With<Ship>, Without<PlayerControlled>, Without<InFleet>)>,
+    pickups: Query<&PointNetwork, With<Pickup>>,
+    props: Query<&GlobalTransform, With<Prop>>,
+    markers: Query<(&MapMarker, &GlobalTransform, Option<&PointNetwork>)>,
+    mut minimaps: Query<(&mut UiContext, &Minimap)>,
+) {
+    let Ok((mut context, minimap)) = minimaps.single_mut() else {
+        return;
+    };
+    let tree = &mut context.tree;
+    tree.clear();
+
+    let Some(chart) = chart else {
+        return;
+    };
+    let MinimapSettings {
+        fog_of_discovery,
+        zoom_levels,
+    } = &settings.minimap;
+
+    let fleet = fleet
+        .iter()
+        .map(|(network, player)| (network.center_of_mass().xz(), player))
+        .collect::<Vec<_>>();
+    let player = fleet.iter().find(|(_, player)| *player).map(|&(at, _)| at);
+    let detected = |at: Vec2| {
+        fleet
+            .iter()
+            .any(|&(ship, _)| ship.distance(at) <= DETECTION_RANGE)
+    };
+    let charted = |at: Vec2| !fog_of_discovery || chart.is_discovered(at);
+
+    let span = zoom_levels
+        .get(minimap.zoom)
+        .copied()
+        .unwrap_or(f32::INFINITY);
+    let view = chart_view(chart.bounds, player.unwrap_or(chart.bounds.center()), span);
+    let region = Rect::from_corners(
+        (view.min - chart.bounds.min) / chart.bounds.size() * CHART_SIZE as f32,
+        (view.max - chart.bounds.min) / chart.bounds.size() * CHART_SIZE as f32,
+    );
+
+    let frame = tree.add_root(
+        UiElement::new(Content::Rect { color: PANEL_COLOR })
+            .with_anchor(Anchor::BottomRight)
+            .with_offset(Vec2::new(-12.0, -12.0))
+            .with_padding(Edges::all(4.0)),
+    );
+    let map = tree.add_child(
+        frame,
+        UiElement::new(Content::Image {
+            image: chart.image.clone(),
+            size: Vec2::splat(MINIMAP_SIZE),
+            color: Color::WHITE,
+            region: Some(region),
+        }),
+    );
+
+    for transform in &props {
+        let at = transform.translation().xz();
+        if charted(at) {
+            add_icon(tree, map, view, at, 3.0, PROP_COLOR);
+        }
+    }
+    for (marker, transform, network) in &markers {
+        let at = network
+            .map_or(transform.translation(), PointNetwork::center_of_mass)
+            .xz();
+        if charted(at) {
+            add_icon(tree, map, view, at, 5.0, marker_color(marker.kind));
+        }
+    }
+
+    let nearby_pickups = pickups
+        .iter()
+        .map(|network| network.center_of_mass().xz())
+        .filter(|&at| detected(at));
+    for (at, count) in cluster(nearby_pickups, CLUSTER_SIZE) {
+        add_icon(tree, map, view, at, 3.0 + count.min(4) as f32, PICKUP_COLOR);
+    }
+
+    for network in &npcs {
+        let at = network.center_of_mass().xz();
+        if detected(at) {
+            add_icon(tree, map, view, at, 5.0, NPC_COLOR);
+        }
+    }
+
+    // The player's ship goes last, above everything else.
+    for &(at, _) in fleet.iter().filter(|(_, player)| !player) {
+        add_icon(tree, map, view, at, 5.0, FLEET_COLOR);
+    }
+    if let Some(at) = player {
+        add_icon(tree, map, view, at, 7.0, PLAYER_COLOR);
+    }
+}
+
+/// Minimap plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Overworld), spawn_minimap);
+        app.add_systems(OnExit(GameState::Overworld), despawn_minimap);
+        app.add_systems(
+            Update,
+            (
+                build_chart.run_if(not(resource_exists::<MinimapChart>)),
+                discover_chart
+                    .run_if(resource_exists::<MinimapChart>)
+                    .run_if(on_timer(Duration::from_millis(500))),
+                minimap_zoom,
+                draw_minimap,
+            )
+                .chain()
+                .run_if(in_state(GameState::Overworld)),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn minimap_view_stays_within_the_chart() {
+        let bounds = Rect::new(-500.0, -500.0, 500.0, 500.0);
+
+        let view = chart_view(bounds, Vec2::new(480.0, 0.0), 200.0);
+        assert_eq!(view, Rect::new(300.0, -100.0, 500.0, 100.0));
+
+        // Wider than the chart shows all of it.
+        assert_eq!(chart_view(bounds, Vec2::new(480.0, 0.0), 5000.0), bounds);
+    }
+
+    #[test]
+    fn nearby_pickups_are_clustered() {
+        let points = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 5.0),
+            Vec2::new(100.0, 100.0),
+        ];
+        let mut clusters = cluster(points.into_iter(), 10.0);
+        clusters.sort_by_key(|&(_, count)| count);
+
+        assert_eq!(
+            clusters,
+            vec![(Vec2::new(100.0, 100.0), 1), (Vec2::new(2.0, 3.0), 2)]
+        );
+    }
+}
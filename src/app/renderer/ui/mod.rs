@@ -40,6 +40,11 @@
 // permitted by applicable law.  See the CNPL for details.
 
 // [TODO] Please uncomment *only* implemented modules.
-// pub mod layouter;
-// pub mod event;
+pub mod draw; // Drawing UI commands
+pub mod drydock; // Refitting the fleet's ships at the Drydock
+pub mod event; // Routing the pointer to elements, as UI events
+pub mod hud; // In-game ship status
+pub mod layouter; // Element layout, and the UI commands to render them
+pub mod minimap; // The island chart on the HUD
+pub mod nameplate; // Names and health bars above ships
 // pub mod builder;
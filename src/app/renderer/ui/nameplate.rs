@@ -0,0 +1,262 @@
+//! # Nameplates
+//!
+//! Entities with a [Nameplate] have their name, and a health bar if they have
+//! [Health], shown floating above them, in the UI layer.
+//!
+//! Nameplates are positioned by projecting a point above the entity through
+//! the [PlayerCamera], and are laid out, like the rest of the UI, anew every
+//! frame, in a single UI context below the HUD. They fade out with distance
+//! from the camera, and are hidden when the terrain is between them and the
+//! camera.
+//!
+//! Ships other than the player's own are given a nameplate when spawned, if
+//! they do not have one already.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    draw::{UiLayer, ui_point},
+    layouter::{Anchor, Axis, Content, Layout, Size, UiContext, UiElement},
+};
+use crate::{
+    app::{camera::PlayerCamera, settings::Settings},
+    common::{
+        damage::Health, diagnostics::AllocationScope, makeup::Ship, physics::base::PointNetwork,
+        player::PlayerControlled, scratch::ScratchBuffer, state::GameState,
+        terrain::chunk::TerrainChunkIndex,
+    },
+};
+
+/// Nameplates closer than this to the camera are fully opaque.
+const FADE_START: f32 = 150.0;
+
+/// Nameplates farther than this from the camera are not shown.
+const FADE_END: f32 = 300.0;
+
+/// How many points between the camera and a nameplate are checked against
+/// the terrain.
+const OCCLUSION_SAMPLES: usize = 16;
+
+const FONT_SIZE: f32 = 14.0;
+const BAR_SIZE: Vec2 = Vec2::new(80.0, 5.0);
+const TEXT_COLOR: Color = Color::srgb(0.92, 0.9, 0.84);
+const BAR_BACK_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const BAR_COLOR: Color = Color::srgb(0.85, 0.25, 0.2);
+
+/// Shows a name, and health bar, above an entity.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Nameplate {
+    pub name: String,
+
+    /// How far above the entity the nameplate floats, in world space units.
+    pub height: f32,
+}
+
+impl Nameplate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            height: 4.0,
+        }
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+}
+
+/// Marks the UI context nameplates are drawn in.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Nameplates;
+
+/// How opaque a nameplate is, from 0.0 to 1.0, at a distance from the
+/// camera.
+pub fn distance_fade(distance: f32) -> f32 {
+    1.0 - ((distance - FADE_START) / (FADE_END - FADE_START)).clamp(0.0, 1.0)
+}
+
+/// Whether the terrain, given its height at each XZ position, is between
+/// two points.
+pub fn occluded_by_terrain(from: Vec3, to: Vec3, mut height_at: impl FnMut(Vec2) -> f32) -> bool {
+    (1..OCCLUSION_SAMPLES).any(|step| {
+        let point = from.lerp(to, step as f32 / OCCLUSION_SAMPLES as f32);
+        height_at(point.xz()) > point.y
+    })
+}
+
+fn add_ship_nameplates(
+    mut commands: Commands,
+    ships: Query<
+        (Entity, Option<&Name>),
+        (Added<Ship>, Without<Nameplate>, Without<PlayerControlled>),
+    >,
+) {
+    for (ship, name) in &ships {
+        let name = name.map_or("Ship", Name::as_str);
+        commands.entity(ship).insert(Nameplate::new(name));
+    }
+}
+
+fn spawn_nameplates(mut commands: Commands) {
+    commands.spawn((
+        Nameplates,
+        Name::new("Nameplates"),
+        UiContext::default(),
+        UiLayer(-1),
+    ));
+}
+
+fn despawn_nameplates(mut commands: Commands, contexts: Query<Entity, With<Nameplates>>) {
+    for context in &contexts {
+        commands.entity(context).despawn();
+    }
+}
+
+fn update_nameplates(
+    settings: Res<Settings>,
+    terrain: Option<Res<TerrainChunkIndex>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    plated: Query<(
+        &Nameplate,
+        &GlobalTransform,
+        Option<&PointNetwork>,
+        Option<&Health>,
+    )>,
+    mut contexts: Query<&mut UiContext, With<Nameplates>>,
+    mut scratch: Local<ScratchBuffer<f32>>,
+) {
+    let _scope = AllocationScope::new("update_nameplates");
+
+    let Ok(mut context) = contexts.single_mut() else {
+        return;
+    };
+    let tree = &mut context.tree;
+    tree.clear();
+
+    let Ok((camera, camera_transform)) = cameras.single() else {
+        return;
+    };
+    let eye = camera_transform.translation();
+
+    for (nameplate, transform, network, health) in &plated {
+        let base = network.map_or(transform.translation(), PointNetwork::center_of_mass);
+        let position = base + Vec3::Y * nameplate.height;
+
+        let alpha = distance_fade(eye.distance(position));
+        if alpha <= 0.0 {
+            continue;
+        }
+
+        let occluded = terrain.as_ref().is_some_and(|terrain| {
+            occluded_by_terrain(eye, position, |at| terrain.height_at_with(at, &mut scratch))
+        });
+        if occluded {
+            continue;
+        }
+
+        let Ok(point) = camera.world_to_viewport(camera_transform, position) else {
+            continue;
+        };
+
+        // A zero-sized root at the projected point, which the plate sits
+        // centered above.
+        let root = tree.add_root(
+            UiElement::default()
+                .with_size(Size::Fixed(0.0), Size::Fixed(0.0))
+                .with_offset(ui_point(point, &settings)),
+        );
+        let plate = tree.add_child(
+            root,
+            UiElement::default()
+                .with_anchor(Anchor::Bottom)
+                .with_layout(Layout::Stack {
+                    axis: Axis::Vertical,
+                    gap: 2.0,
+                }),
+        );
+        tree.add_child(
+            plate,
+            UiElement::new(Content::Text {
+                text: nameplate.name.clone(),
+                font_size: FONT_SIZE,
+                color: TEXT_COLOR.with_alpha(alpha),
+            })
+            .with_anchor(Anchor::Top),
+        );
+
+        if let Some(health) = health {
+            let bar = tree.add_child(
+                plate,
+                UiElement::new(Content::Rect {
+                    color: BAR_BACK_COLOR.with_alpha(0.5 * alpha),
+                })
+                .with_size(Size::Fixed(BAR_SIZE.x), Size::Fixed(BAR_SIZE.y))
+                .with_anchor(Anchor::Top),
+            );
+            tree.add_child(
+                bar,
+                UiElement::new(Content::Rect {
+                    color: BAR_COLOR.with_alpha(alpha),
+                })
+                .with_size(Size::Fraction(health.fraction()), Size::FILL),
+            );
+        }
+    }
+}
+
+/// Nameplate plugin.
+///
+/// Included in [RendererPlugin](crate::app::renderer::RendererPlugin).
+pub struct NameplatePlugin;
+
+impl Plugin for NameplatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Overworld), spawn_nameplates);
+        app.add_systems(OnExit(GameState::Overworld), despawn_nameplates);
+        app.add_systems(
+            Update,
+            (add_ship_nameplates, update_nameplates)
+                .chain()
+                .run_if(in_state(GameState::Overworld)),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn terrain_hides_nameplates_behind_it() {
+        let eye = Vec3::new(0.0, 20.0, 0.0);
+        let ship = Vec3::new(100.0, 4.0, 0.0);
+
+        // A hill halfway between the camera and the ship.
+        let hill = |at: Vec2| {
+            if (at.x - 50.0).abs() < 10.0 {
+                30.0
+            } else {
+                0.0
+            }
+        };
+        assert!(occluded_by_terrain(eye, ship, hill));
+        assert!(!occluded_by_terrain(eye, ship, |_| 0.0));
+
+        assert_eq!(distance_fade(FADE_START * 0.5), 1.0);
+        assert_eq!(distance_fade(FADE_END), 0.0);
+    }
+}
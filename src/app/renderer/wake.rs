@@ -0,0 +1,422 @@
+//! # Ship wakes
+//!
+//! Ships moving through the water leave a trail of foam behind them: a
+//! ribbon laid on the water surface, which widens and fades out as it ages.
+//!
+//! Ribbons are laid after the [WaterWake] of the ship, which the water
+//! physics fills in from the ship's submerged points. The faster a ship goes,
+//! the stronger its wake is, the faster it spreads out, and the longer it
+//! lasts; the wider its hull is at the waterline, the wider the wake starts.
+//!
+//! Ribbons ride the same [WaveField] as the water surface, slightly above
+//! it, and are drawn over it.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        view::NoFrustumCulling,
+    },
+    transform::TransformSystem,
+};
+
+use crate::common::{
+    makeup::Ship,
+    physics::water::{WaterPhysics, WaterWake, WaveField},
+};
+
+/// How many nodes a wake ribbon has at most.
+const MAX_NODES: usize = 48;
+
+/// How far a ship moves before another node is laid.
+const NODE_SPACING: f32 = 1.5;
+
+/// How far a ship may move between two nodes before its wake is broken off,
+/// e.g. when it is moved elsewhere.
+const MAX_GAP: f32 = NODE_SPACING * 8.0;
+
+/// Ships slower than this leave no wake.
+const MIN_WAKE_SPEED: f32 = 0.5;
+
+/// Ships this fast leave the strongest wake.
+const FULL_WAKE_SPEED: f32 = 8.0;
+
+/// How long the wake of a ship barely moving lasts, in seconds.
+const BASE_LIFETIME: f32 = 2.0;
+
+/// How much longer wakes last for every unit of speed, in seconds.
+const LIFETIME_PER_SPEED: f32 = 0.4;
+
+const MAX_LIFETIME: f32 = 8.0;
+
+/// How fast wakes widen for every unit of speed, in units per second.
+const SPREAD_PER_SPEED: f32 = 0.15;
+
+/// The narrowest a wake starts.
+const MIN_WIDTH: f32 = 0.5;
+
+/// How far above the water surface wakes are laid.
+const SURFACE_OFFSET: f32 = 0.05;
+
+const FOAM_COLOR: Color = Color::srgb(0.95, 0.97, 0.96);
+
+/// A point along a wake ribbon.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WakeNode {
+    /// Where the node is, on the XZ plane.
+    pub at: Vec2,
+
+    /// How wide the wake was when laid.
+    pub width: f32,
+
+    /// How fast the wake widens, in units per second.
+    pub spread: f32,
+
+    /// How opaque the wake was when laid, between 0.0 and 1.0.
+    pub strength: f32,
+
+    /// How long the node has been around, in seconds.
+    pub age: f32,
+
+    /// How long the node lasts, in seconds.
+    pub lifetime: f32,
+}
+
+impl WakeNode {
+    /// The node laid by an object moving through the water, if it is moving
+    /// fast enough.
+    pub fn for_motion(wake: &WaterWake) -> Option<Self> {
+        let speed = wake.velocity.xz().length();
+        if !wake.is_submerged() || speed < MIN_WAKE_SPEED {
+            return None;
+        }
+
+        Some(Self {
+            at: wake.center.xz(),
+            width: wake.width.max(MIN_WIDTH),
+            spread: speed * SPREAD_PER_SPEED,
+            strength: (speed / FULL_WAKE_SPEED).clamp(0.0, 1.0),
+            age: 0.0,
+            lifetime: (BASE_LIFETIME + speed * LIFETIME_PER_SPEED).min(MAX_LIFETIME),
+        })
+    }
+
+    /// How wide the node is at its current age.
+    pub fn current_width(&self) -> f32 {
+        self.width + self.spread * self.age
+    }
+
+    /// How opaque the node is at its current age.
+    pub fn current_alpha(&self) -> f32 {
+        self.strength * (1.0 - self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// The foam trail of a ship.
+///
+/// Lives on its own entity, in world space, so it stays behind when its ship
+/// is gone, until it fades out.
+#[derive(Component, Clone, Debug)]
+pub struct WakeRibbon {
+    /// The ship laying the wake.
+    pub source: Entity,
+
+    /// The nodes of the ribbon, from the oldest to the newest.
+    nodes: VecDeque<WakeNode>,
+
+    /// The calm water level of the wake.
+    water_level: f32,
+}
+
+impl WakeRibbon {
+    pub fn new(source: Entity) -> Self {
+        Self {
+            source,
+            nodes: VecDeque::with_capacity(MAX_NODES),
+            water_level: 0.0,
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &WakeNode> {
+        self.nodes.iter()
+    }
+
+    /// Ages every node, dropping those which faded out.
+    pub fn age(&mut self, delta_secs: f32) {
+        for node in &mut self.nodes {
+            node.age += delta_secs;
+        }
+        self.nodes.retain(|node| node.age < node.lifetime);
+    }
+
+    /// Lays a node for a ship moving through the water, if it moved far
+    /// enough since the last one.
+    pub fn record(&mut self, wake: &WaterWake) {
+        let Some(node) = WakeNode::for_motion(wake) else {
+            return;
+        };
+
+        if let Some(last) = self.nodes.back() {
+            let moved = last.at.distance(node.at);
+            if moved < NODE_SPACING {
+                return;
+            }
+            if moved > MAX_GAP {
+                self.nodes.clear();
+            }
+        }
+
+        if self.nodes.len() == MAX_NODES {
+            self.nodes.pop_front();
+        }
+        self.nodes.push_back(node);
+        self.water_level = wake.center.y;
+    }
+
+    /// Writes the vertices of the ribbon, two per node, with their colors.
+    ///
+    /// The vertices of missing nodes are all laid on the newest one, fully
+    /// transparent, so the mesh never changes size.
+    fn write_vertices(
+        &self,
+        positions: &mut [[f32; 3]],
+        colors: &mut [[f32; 4]],
+        surface_at: impl Fn(Vec2) -> f32,
+    ) {
+        let last = self.nodes.len().saturating_sub(1);
+        let mut vertices = positions
+            .chunks_exact_mut(2)
+            .zip(colors.chunks_exact_mut(2));
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let Some((position, color)) = vertices.next() else {
+                return;
+            };
+
+            let before = self.nodes[index.saturating_sub(1)].at;
+            let after = self.nodes[(index + 1).min(last)].at;
+            let side = (after - before).perp().normalize_or_zero() * node.current_width() / 2.0;
+            let height = surface_at(node.at) + SURFACE_OFFSET;
+
+            for (vertex, at) in position.iter_mut().zip([node.at - side, node.at + side]) {
+                *vertex = [at.x, height, at.y];
+            }
+            color.fill(
+                FOAM_COLOR
+                    .with_alpha(node.current_alpha())
+                    .to_linear()
+                    .to_f32_array(),
+            );
+        }
+
+        let rest = self
+            .nodes
+            .back()
+            .map_or([0.0; 3], |node| [node.at.x, self.water_level, node.at.y]);
+        for (position, color) in vertices {
+            position.fill(rest);
+            color.fill([0.0; 4]);
+        }
+    }
+}
+
+/// The mesh of a wake ribbon, with room for every node.
+fn ribbon_mesh() -> Mesh {
+    let num_vertices = MAX_NODES * 2;
+    let indices = (0..MAX_NODES as u32 - 1)
+        .flat_map(|node| {
+            let a = node * 2;
+            [a, a + 2, a + 1, a + 1, a + 2, a + 3]
+        })
+        .collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]; num_vertices])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; num_vertices])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.0; 4]; num_vertices])
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// The material every wake ribbon is drawn with.
+#[derive(Resource, Clone, Debug)]
+struct WakeMaterial(Handle<StandardMaterial>);
+
+impl FromWorld for WakeMaterial {
+    fn from_world(world: &mut World) -> Self {
+        // The vertex colors give the foam its color.
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self(materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        }))
+    }
+}
+
+/// Has the water physics record how new ships move through the water, and
+/// gives them a wake ribbon.
+fn spawn_wake_ribbons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    material: Res<WakeMaterial>,
+    ships: Query<Entity, (Added<Ship>, With<WaterPhysics>, Without<WaterWake>)>,
+) {
+    for ship in &ships {
+        commands.entity(ship).insert(WaterWake::default());
+        commands.spawn((
+            WakeRibbon::new(ship),
+            Mesh3d(meshes.add(ribbon_mesh())),
+            MeshMaterial3d(material.0.clone()),
+            Transform::default(),
+            NotShadowCaster,
+            // The ribbon's vertices move about in world space.
+            NoFrustumCulling,
+        ));
+    }
+}
+
+/// Ages wake ribbons and lays new nodes, and despawns the ribbons of ships
+/// which are gone once they fade out.
+fn lay_wakes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ribbons: Query<(Entity, &mut WakeRibbon)>,
+    ships: Query<&WaterWake>,
+) {
+    for (entity, mut ribbon) in &mut ribbons {
+        ribbon.age(time.delta_secs());
+
+        match ships.get(ribbon.source) {
+            Ok(wake) => ribbon.record(wake),
+            Err(_) if ribbon.nodes.is_empty() => commands.entity(entity).despawn(),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Lays the meshes of wake ribbons on the water surface.
+fn draw_wakes(
+    time: Res<Time>,
+    waves: Res<WaveField>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    ribbons: Query<(&WakeRibbon, &Mesh3d)>,
+) {
+    let elapsed_secs = time.elapsed_secs();
+
+    for (ribbon, mesh) in &ribbons {
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+
+        // Positions are taken out while the colors are written alongside.
+        let Some(VertexAttributeValues::Float32x3(mut positions)) =
+            mesh.remove_attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x4(colors)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+        else {
+            continue;
+        };
+
+        ribbon.write_vertices(&mut positions, colors, |at| {
+            ribbon.water_level + waves.height_at(at, elapsed_secs)
+        });
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    }
+}
+
+/// Ship wake rendering plugin.
+///
+/// Included in [super::RendererPlugin].
+pub struct WakeRenderingPlugin;
+
+impl Plugin for WakeRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WakeMaterial>();
+        app.add_systems(Update, (spawn_wake_ribbons, lay_wakes).chain());
+        app.add_systems(
+            PostUpdate,
+            draw_wakes.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn moving(x: f32, speed: f32, width: f32) -> WaterWake {
+        WaterWake {
+            center: Vec3::new(x, 0.0, 0.0),
+            velocity: Vec3::X * speed,
+            width,
+            wetted_area: 1.0,
+        }
+    }
+
+    #[test]
+    fn wakes_follow_speed_and_hull_width() {
+        let mut ribbon = WakeRibbon::new(Entity::PLACEHOLDER);
+
+        // Too slow to leave a wake.
+        ribbon.record(&moving(0.0, MIN_WAKE_SPEED * 0.5, 2.0));
+        assert_eq!(ribbon.nodes().count(), 0);
+
+        // Nodes are laid as the ship moves on, never more than fit.
+        for step in 0..MAX_NODES * 2 {
+            ribbon.record(&moving(step as f32 * NODE_SPACING * 1.1, 4.0, 2.0));
+        }
+        assert_eq!(ribbon.nodes().count(), MAX_NODES);
+
+        // Faster ships leave stronger, longer, faster spreading wakes; wider
+        // ones leave wider wakes.
+        let slow = WakeNode::for_motion(&moving(0.0, 2.0, 2.0)).unwrap();
+        let fast = WakeNode::for_motion(&moving(0.0, 6.0, 2.0)).unwrap();
+        let wide = WakeNode::for_motion(&moving(0.0, 2.0, 4.0)).unwrap();
+        assert!(slow.strength < fast.strength);
+        assert!(slow.lifetime < fast.lifetime);
+        assert!(slow.spread < fast.spread);
+        assert!(slow.width < wide.width);
+
+        // Wakes widen and fade, until they are gone.
+        let first = *ribbon.nodes().next().unwrap();
+        ribbon.age(first.lifetime * 0.5);
+        let aged = ribbon.nodes().next().unwrap();
+        assert!(aged.current_width() > first.current_width());
+        assert!(aged.current_alpha() < first.current_alpha());
+
+        ribbon.age(MAX_LIFETIME);
+        assert_eq!(ribbon.nodes().count(), 0);
+
+        // Missing nodes are laid out of sight.
+        let mut positions = vec![[1.0; 3]; MAX_NODES * 2];
+        let mut colors = vec![[1.0; 4]; MAX_NODES * 2];
+        ribbon.write_vertices(&mut positions, &mut colors, |_| 0.0);
+        assert!(colors.iter().all(|color| color[3] == 0.0));
+    }
+}
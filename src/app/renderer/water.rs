@@ -0,0 +1,335 @@
+//! # Water surface rendering
+//!
+//! Displaces [WaterSurface] meshes according to the [WaveField], the same
+//! one used for buoyancy, so that floating objects visibly ride the waves.
+//!
+//! Water surfaces with a material are turned into a [WaterGrid] when spawned:
+//! a grid whose cells grow wider away from its center, which follows the
+//! camera around, so the water is detailed up close and still reaches the
+//! horizon. The grid is colored per vertex after the depth of the terrain
+//! beneath it; the water turns lighter and more transparent towards the
+//! shore, and foams where the terrain nearly meets the surface.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Move the displacement to a vertex shader once the water has its own
+// material; this is done on the CPU for now.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+    transform::TransformSystem,
+};
+
+use crate::common::{
+    diagnostics::AllocationScope,
+    physics::water::{WaterSurface, WaveField},
+    scratch::ScratchBuffer,
+    terrain::chunk::TerrainChunkIndex,
+};
+
+/// How much of the spread of a [WaterGrid]'s vertices is even; the rest
+/// grows quadratically away from the center.
+const LINEAR_SHARE: f32 = 0.25;
+
+/// Water shallower than this is lighter and more transparent.
+const SHALLOW_DEPTH: f32 = 8.0;
+
+/// Water shallower than this foams.
+const FOAM_DEPTH: f32 = 1.0;
+
+/// How opaque the shallowest water is.
+const MIN_ALPHA: f32 = 0.35;
+
+const SHALLOW_COLOR: Color = Color::srgb(0.35, 0.75, 0.72);
+const FOAM_COLOR: Color = Color::srgb(0.95, 0.97, 0.96);
+
+/// A water surface which follows the camera, colored after the depth of the
+/// terrain beneath it.
+///
+/// See the module documentation.
+#[derive(Component, Clone, Debug)]
+pub struct WaterGrid {
+    /// How far the grid reaches from its center.
+    pub radius: f32,
+
+    /// How many vertices there are along each side of the grid.
+    pub resolution: u32,
+
+    /// The color of deep water.
+    pub deep_color: Color,
+
+    /// Where the grid was centered when last colored.
+    colored_at: Option<Vec2>,
+}
+
+impl WaterGrid {
+    pub fn new(deep_color: Color) -> Self {
+        Self {
+            radius: 1000.0,
+            resolution: 128,
+            deep_color,
+            colored_at: None,
+        }
+    }
+
+    /// The size of the cells at the center of the grid.
+    pub fn inner_cell_size(&self) -> f32 {
+        self.radius * LINEAR_SHARE * 2.0 / (self.resolution - 1) as f32
+    }
+
+    /// Builds the mesh of the grid.
+    pub fn mesh(&self) -> Mesh {
+        let last = self.resolution - 1;
+        let spread = |step: u32| {
+            let u = step as f32 / last as f32 * 2.0 - 1.0;
+            self.radius * (LINEAR_SHARE * u + (1.0 - LINEAR_SHARE) * u * u.abs())
+        };
+
+        let mut positions = vec![];
+        let mut uvs = vec![];
+        for row in 0..self.resolution {
+            for column in 0..self.resolution {
+                positions.push([spread(column), 0.0, spread(row)]);
+                uvs.push([column as f32 / last as f32, row as f32 / last as f32]);
+            }
+        }
+        let num_vertices = positions.len();
+
+        let mut indices = vec![];
+        for row in 0..last {
+            for column in 0..last {
+                let a = row * self.resolution + column;
+                let b = a + 1;
+                let c = a + self.resolution;
+                let d = c + 1;
+                indices.extend([a, c, b, b, c, d]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; num_vertices])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; num_vertices])
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// The color of water over terrain this deep beneath the surface.
+pub fn shore_color(depth: f32, deep_color: Color) -> Color {
+    let shallowness = (1.0 - depth / SHALLOW_DEPTH).clamp(0.0, 1.0);
+    let foam = (1.0 - depth / FOAM_DEPTH).clamp(0.0, 1.0);
+
+    deep_color
+        .mix(&SHALLOW_COLOR, shallowness)
+        .mix(&FOAM_COLOR, foam)
+        .with_alpha(1.0 - (1.0 - MIN_ALPHA) * shallowness * (1.0 - foam))
+}
+
+/// Turns newly spawned water surfaces into [WaterGrid]s.
+fn setup_water_grids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<
+        (Entity, &MeshMaterial3d<StandardMaterial>),
+        (Added<WaterSurface>, Without<WaterGrid>),
+    >,
+) {
+    for (entity, material) in &query {
+        let deep_color = materials
+            .get(&material.0)
+            .map_or(Color::srgb(0.1, 0.3, 0.5), |material| material.base_color);
+        let grid = WaterGrid::new(deep_color);
+
+        // The vertex colors give the water its color.
+        let material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 0.15,
+            ..default()
+        });
+
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(grid.mesh())),
+            MeshMaterial3d(material),
+            grid,
+        ));
+    }
+}
+
+/// Keeps water grids centered under the rendering camera.
+///
+/// Grids move a whole inner cell at a time, so the waves do not swim about
+/// along with the camera.
+fn follow_camera(
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut grids: Query<(&mut Transform, &WaterGrid)>,
+) {
+    let Some((_, camera_transform)) = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .max_by_key(|(camera, _)| camera.order)
+    else {
+        return;
+    };
+    let eye = camera_transform.translation().xz();
+
+    for (mut transform, grid) in &mut grids {
+        let cell = grid.inner_cell_size();
+        let center = (eye / cell).round() * cell;
+        transform.translation.x = center.x;
+        transform.translation.z = center.y;
+    }
+}
+
+/// Colors water grids after the depth of the terrain beneath them, whenever
+/// they move.
+fn color_water_shores(
+    terrain: Option<Res<TerrainChunkIndex>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut grids: Query<(&Mesh3d, &GlobalTransform, &mut WaterGrid)>,
+    mut scratch: Local<ScratchBuffer<f32>>,
+) {
+    let _scope = AllocationScope::new("color_water_shores");
+
+    for (mesh, transform, mut grid) in &mut grids {
+        let center = transform.translation().xz();
+        let terrain_added = terrain.as_ref().is_some_and(|terrain| terrain.is_added());
+        if grid.colored_at == Some(center) && !terrain_added {
+            continue;
+        }
+
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+
+        let water_level = transform.translation().y;
+        let colors = positions
+            .iter()
+            .map(|position| {
+                let depth = terrain.as_ref().map_or(f32::INFINITY, |terrain| {
+                    let at = transform
+                        .transform_point(Vec3::new(position[0], 0.0, position[2]))
+                        .xz();
+                    water_level - terrain.height_at_with(at, &mut scratch)
+                });
+                shore_color(depth, grid.deep_color)
+                    .to_linear()
+                    .to_f32_array()
+            })
+            .collect::<Vec<_>>();
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        grid.colored_at = Some(center);
+    }
+}
+
+/// Displaces the vertices of water surfaces to match the [WaveField].
+fn displace_water_surfaces(
+    time: Res<Time>,
+    waves: Res<WaveField>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surface_points: Local<ScratchBuffer<Vec2>>,
+    query: Query<(&Mesh3d, &GlobalTransform), With<WaterSurface>>,
+) {
+    let elapsed_secs = time.elapsed_secs();
+
+    for (mesh, transform) in query.iter() {
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+
+        // World space XZ position of every vertex.
+        let surface_points = surface_points.fill(positions.iter_mut().map(|position| {
+            let at = transform
+                .transform_point(Vec3::new(position[0], 0.0, position[2]))
+                .xz();
+
+            position[1] = waves.height_at(at, elapsed_secs) / transform.scale().y;
+            at
+        }));
+
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+        {
+            for (normal, at) in normals.iter_mut().zip(surface_points.iter()) {
+                *normal = waves.normal_at(*at, elapsed_secs).to_array();
+            }
+        }
+    }
+}
+
+/// Water surface rendering plugin.
+pub struct WaterRenderingPlugin;
+
+impl Plugin for WaterRenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (setup_water_grids, follow_camera).chain());
+        app.add_systems(
+            PostUpdate,
+            (color_water_shores, displace_water_surfaces)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn water_fades_and_foams_towards_the_shore() {
+        let deep = Color::srgb(0.1, 0.3, 0.5);
+        let close = |a: Color, b: Color| {
+            let (a, b) = (a.to_srgba().to_f32_array(), b.to_srgba().to_f32_array());
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-3)
+        };
+        assert!(close(shore_color(SHALLOW_DEPTH * 2.0, deep), deep));
+        assert!(shore_color(SHALLOW_DEPTH * 0.5, deep).alpha() < 1.0);
+        assert!(close(shore_color(0.0, deep), FOAM_COLOR));
+
+        // Cells are finest at the center, and grow wider outwards.
+        let grid = WaterGrid::new(deep);
+        let mesh = grid.mesh();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("water grid has no positions");
+        };
+        let row = &positions[..grid.resolution as usize];
+        let middle = row.len() / 2;
+        let inner = row[middle][0] - row[middle - 1][0];
+        let outer = row[row.len() - 1][0] - row[row.len() - 2][0];
+        assert!(inner < outer);
+        assert!((inner - grid.inner_cell_size()).abs() < grid.inner_cell_size() * 0.1);
+        assert!((row[0][0] + grid.radius).abs() < 1e-3);
+    }
+}
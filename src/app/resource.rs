@@ -0,0 +1,439 @@
+//! # Client resources
+//!
+//! Plugins declare the assets the client needs in the [AssetManifest], with
+//! the [AssetManifestAppExt] methods: meshes, textures, sounds, definitions
+//! files, and so on. Every asset in the manifest is loaded while in
+//! [AppState::Loading], the first state of the app, which shows a loading
+//! screen with the [LoadingProgress].
+//!
+//! Once every asset is either loaded or failed to load, the app goes on to
+//! the main menu. Assets can be required or not; if a required asset fails to
+//! load, the loading screen instead lists every asset that failed, and why,
+//! letting the player carry on regardless or quit. Other assets which fail to
+//! load are only warned about.
+//!
+//! The handles of loaded assets are kept for as long as the app runs, so that
+//! they are never unloaded.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::fmt;
+
+use bevy::{asset::LoadState, prelude::*};
+
+use super::{
+    input::{ActionState, InputAction},
+    renderer::ui::layouter::{Anchor, Axis, Content, Edges, Layout, Size, UiContext, UiElement},
+    state::AppState,
+};
+
+const TITLE_FONT_SIZE: f32 = 32.0;
+const FONT_SIZE: f32 = 16.0;
+const BAR_SIZE: Vec2 = Vec2::new(360.0, 12.0);
+const TEXT_COLOR: Color = Color::srgb(0.85, 0.83, 0.78);
+const ERROR_COLOR: Color = Color::srgb(0.95, 0.45, 0.4);
+const BAR_BACK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.1);
+const BAR_COLOR: Color = Color::srgb(1.0, 0.85, 0.4);
+
+/// What kind of asset an entry of the [AssetManifest] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Mesh,
+    Texture,
+    Sound,
+
+    /// Definitions files; see [defs](crate::common::defs).
+    Defs,
+}
+
+impl fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Mesh => "mesh",
+            Self::Texture => "texture",
+            Self::Sound => "sound",
+            Self::Defs => "definitions",
+        })
+    }
+}
+
+/// An asset to load while in [AppState::Loading].
+#[derive(Clone, Debug)]
+pub struct AssetEntry {
+    pub kind: AssetKind,
+
+    /// The path of the asset, or folder, in the assets directory.
+    pub path: String,
+
+    /// Whether the app can not do without this asset.
+    pub required: bool,
+
+    /// Starts loading the asset.
+    load: fn(&AssetServer, &str) -> UntypedHandle,
+}
+
+fn load_asset<A: Asset>(server: &AssetServer, path: &str) -> UntypedHandle {
+    server.load::<A>(path.to_owned()).untyped()
+}
+
+fn load_folder(server: &AssetServer, path: &str) -> UntypedHandle {
+    server.load_folder(path.to_owned()).untyped()
+}
+
+/// Every asset the client loads before the main menu.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AssetManifest {
+    pub entries: Vec<AssetEntry>,
+}
+
+/// Methods for declaring assets in the [AssetManifest].
+pub trait AssetManifestAppExt {
+    /// Declares an asset the app can not do without.
+    fn require_asset<A: Asset>(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self;
+
+    /// Declares an asset to load, which the app can do without.
+    fn preload_asset<A: Asset>(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self;
+
+    /// Declares a folder of assets the app can not do without.
+    fn require_folder(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self;
+}
+
+fn add_entry(app: &mut App, entry: AssetEntry) {
+    app.world_mut()
+        .get_resource_or_init::<AssetManifest>()
+        .entries
+        .push(entry);
+}
+
+impl AssetManifestAppExt for App {
+    fn require_asset<A: Asset>(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self {
+        add_entry(
+            self,
+            AssetEntry {
+                kind,
+                path: path.into(),
+                required: true,
+                load: load_asset::<A>,
+            },
+        );
+        self
+    }
+
+    fn preload_asset<A: Asset>(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self {
+        add_entry(
+            self,
+            AssetEntry {
+                kind,
+                path: path.into(),
+                required: false,
+                load: load_asset::<A>,
+            },
+        );
+        self
+    }
+
+    fn require_folder(&mut self, kind: AssetKind, path: impl Into<String>) -> &mut Self {
+        add_entry(
+            self,
+            AssetEntry {
+                kind,
+                path: path.into(),
+                required: true,
+                load: load_folder,
+            },
+        );
+        self
+    }
+}
+
+/// The handles of every asset of the [AssetManifest], in the same order.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LoadedAssets(pub Vec<UntypedHandle>);
+
+/// An asset which failed to load.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedAsset {
+    pub kind: AssetKind,
+    pub path: String,
+    pub required: bool,
+    pub error: String,
+}
+
+/// How far along loading the [AssetManifest] is.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct LoadingProgress {
+    /// How many assets are loaded.
+    pub loaded: usize,
+
+    /// How many assets there are in total.
+    pub total: usize,
+
+    pub failed: Vec<FailedAsset>,
+}
+
+impl LoadingProgress {
+    /// How much of the manifest is loaded or failed, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.loaded + self.failed.len()) as f32 / self.total as f32
+        }
+    }
+
+    /// Whether every asset is either loaded or failed.
+    pub fn is_settled(&self) -> bool {
+        self.loaded + self.failed.len() >= self.total
+    }
+
+    /// Whether a required asset failed to load.
+    pub fn has_required_failures(&self) -> bool {
+        self.failed.iter().any(|failed| failed.required)
+    }
+}
+
+/// Marks the loading screen's UI context.
+#[derive(Component, Clone, Copy, Debug, Default)]
+struct LoadingScreen;
+
+fn start_loading(mut commands: Commands, server: Res<AssetServer>, manifest: Res<AssetManifest>) {
+    info!("Loading {} assets", manifest.entries.len());
+
+    commands.insert_resource(LoadedAssets(
+        manifest
+            .entries
+            .iter()
+            .map(|entry| (entry.load)(&server, &entry.path))
+            .collect(),
+    ));
+    commands.insert_resource(LoadingProgress {
+        total: manifest.entries.len(),
+        ..default()
+    });
+    commands.spawn((LoadingScreen, Name::new("Loading"), UiContext::default()));
+}
+
+fn despawn_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn();
+    }
+}
+
+fn track_loading(
+    server: Res<AssetServer>,
+    manifest: Res<AssetManifest>,
+    handles: Res<LoadedAssets>,
+    mut progress: ResMut<LoadingProgress>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if progress.is_settled() {
+        return;
+    }
+
+    let mut loaded = 0;
+    let mut failed = vec![];
+    for (entry, handle) in manifest.entries.iter().zip(&handles.0) {
+        match server.load_state(handle.id()) {
+            LoadState::Failed(error) => failed.push(FailedAsset {
+                kind: entry.kind,
+                path: entry.path.clone(),
+                required: entry.required,
+                error: error.to_string(),
+            }),
+            LoadState::Loaded if server.is_loaded_with_dependencies(handle.id()) => loaded += 1,
+            _ => {}
+        }
+    }
+    progress.loaded = loaded;
+    progress.failed = failed;
+
+    if !progress.is_settled() {
+        return;
+    }
+
+    for failed in &progress.failed {
+        warn!(
+            "Could not load {} {:?}: {}",
+            failed.kind, failed.path, failed.error
+        );
+    }
+
+    if !progress.has_required_failures() {
+        next_app_state.set(AppState::MainMenu);
+    }
+}
+
+/// Lets the player carry on without the assets which failed to load, or
+/// quit.
+fn loading_failure_input(
+    actions: Res<ActionState>,
+    progress: Res<LoadingProgress>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !progress.is_settled() || !progress.has_required_failures() {
+        return;
+    }
+
+    if actions.just_pressed(InputAction::MenuConfirm) {
+        next_app_state.set(AppState::MainMenu);
+    } else if actions.just_pressed(InputAction::MenuBack) {
+        exit.write(AppExit::Success);
+    }
+}
+
+fn text(text: impl Into<String>, font_size: f32, color: Color) -> UiElement {
+    UiElement::new(Content::Text {
+        text: text.into(),
+        font_size,
+        color,
+    })
+}
+
+fn draw_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut screens: Query<&mut UiContext, With<LoadingScreen>>,
+) {
+    for mut context in &mut screens {
+        let tree = &mut context.tree;
+        tree.clear();
+
+        let column = tree.add_root(
+            UiElement::default()
+                .with_anchor(Anchor::Center)
+                .with_layout(Layout::Stack {
+                    axis: Axis::Vertical,
+                    gap: 8.0,
+                }),
+        );
+
+        if !progress.has_required_failures() {
+            tree.add_child(
+                column,
+                text("Loading", TITLE_FONT_SIZE, TEXT_COLOR).with_anchor(Anchor::Center),
+            );
+            let bar = tree.add_child(
+                column,
+                UiElement::new(Content::Rect {
+                    color: BAR_BACK_COLOR,
+                })
+                .with_size(Size::Fixed(BAR_SIZE.x), Size::Fixed(BAR_SIZE.y))
+                .with_anchor(Anchor::Center),
+            );
+            tree.add_child(
+                bar,
+                UiElement::new(Content::Rect { color: BAR_COLOR })
+                    .with_size(Size::Fraction(progress.fraction()), Size::FILL),
+            );
+            tree.add_child(
+                column,
+                text(
+                    format!("{} of {} assets", progress.loaded, progress.total),
+                    FONT_SIZE,
+                    TEXT_COLOR,
+                )
+                .with_anchor(Anchor::Center),
+            );
+            continue;
+        }
+
+        // Some required assets are missing; tell the player which.
+        tree.add_child(
+            column,
+            text("Some assets are missing", TITLE_FONT_SIZE, ERROR_COLOR),
+        );
+        let list = tree.add_child(
+            column,
+            UiElement::default()
+                .with_padding(Edges::all(8.0))
+                .with_layout(Layout::Stack {
+                    axis: Axis::Vertical,
+                    gap: 4.0,
+                }),
+        );
+        for failed in &progress.failed {
+            let color = if failed.required {
+                ERROR_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            tree.add_child(
+                list,
+                text(
+                    format!("{} {:?}: {}", failed.kind, failed.path, failed.error),
+                    FONT_SIZE,
+                    color,
+                ),
+            );
+        }
+        tree.add_child(
+            column,
+            text(
+                "Press Enter to carry on regardless, or Escape to quit.",
+                FONT_SIZE,
+                TEXT_COLOR,
+            ),
+        );
+    }
+}
+
+/// Client resource loading plugin.
+///
+/// Included in [AppPlugin](super::AppPlugin).
+pub struct ResourcePlugin;
+
+impl Plugin for ResourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetManifest>();
+        app.init_resource::<LoadingProgress>();
+        app.require_folder(AssetKind::Defs, "defs");
+
+        app.add_systems(OnEnter(AppState::Loading), start_loading);
+        app.add_systems(OnExit(AppState::Loading), despawn_loading_screen);
+        app.add_systems(
+            Update,
+            (track_loading, loading_failure_input, draw_loading_screen)
+                .chain()
+                .run_if(in_state(AppState::Loading)),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_failures_do_not_block_loading() {
+        let mut progress = LoadingProgress {
+            loaded: 1,
+            total: 3,
+            failed: vec![FailedAsset {
+                kind: AssetKind::Sound,
+                path: "sounds/splash_1.ogg".into(),
+                required: false,
+                error: "not found".into(),
+            }],
+        };
+        assert!(!progress.is_settled());
+
+        progress.loaded = 2;
+        assert!(progress.is_settled());
+        assert_eq!(progress.fraction(), 1.0);
+        assert!(!progress.has_required_failures());
+
+        progress.failed[0].required = true;
+        assert!(progress.has_required_failures());
+    }
+}
@@ -0,0 +1,412 @@
+//! # Player settings
+//!
+//! The player's [Settings] and profile: key bindings, graphics options,
+//! audio volumes, UI scale, and the name they last played as. They are kept
+//! in `settings.ron`, in the platform's config directory (see
+//! [settings_path]), loaded when the [SettingsPlugin] is built, and written
+//! back once the resource stops changing for [SAVE_DELAY], or on exit.
+//!
+//! Settings are applied by the plugins they concern; e.g. graphics options
+//! by the [RenderQualityPlugin](super::renderer::quality::RenderQualityPlugin)
+//! and [PostProcessPlugin](super::renderer::postprocess::PostProcessPlugin),
+//! audio volumes by the [AudioPlaybackPlugin](super::audio::AudioPlaybackPlugin),
+//! and the UI scale by the [UiDrawPlugin](super::renderer::ui::draw::UiDrawPlugin).
+//! Key bindings and gamepad settings are kept in sync, both ways, with the
+//! [InputMap] and [GamepadSettings] resources, which are what input code
+//! reads, and changes at runtime.
+//!
+//! [NOTE] Platforms without a config directory, such as the web, get the
+//! default settings every time.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::ron,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::input::{GamepadSettings, InputMap};
+use crate::common::error::{Context, LnrError};
+
+/// How long the settings must go unchanged before they are saved, in
+/// seconds, so that e.g. dragging a slider does not write them every frame.
+pub const SAVE_DELAY: f32 = 1.0;
+
+/// Graphics options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Whether to wait for the display's vertical sync.
+    pub vsync: bool,
+
+    /// Whether render quality is lowered automatically when frames take
+    /// too long.
+    pub dynamic_quality: bool,
+
+    /// The frame rate dynamic quality aims for.
+    pub target_fps: u32,
+
+    /// Strength of the vignette, between 0.0 (none) and 1.0.
+    pub vignette: f32,
+
+    /// Whether the screen reacts to the game, e.g. flashing when the
+    /// player's ship is hit.
+    pub screen_effects: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            dynamic_quality: true,
+            target_fps: 60,
+            vignette: 0.25,
+            screen_effects: true,
+        }
+    }
+}
+
+/// Audio volumes, between 0.0 and 1.0.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Scales every other volume.
+    pub master: f32,
+
+    pub music: f32,
+
+    pub effects: f32,
+}
+
+impl AudioSettings {
+    /// The volume music is played at.
+    pub fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    /// The volume sound effects are played at.
+    pub fn effects_volume(&self) -> f32 {
+        self.master * self.effects
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            effects: 1.0,
+        }
+    }
+}
+
+/// Minimap options.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinimapSettings {
+    /// Whether the chart only shows what the fleet has sailed near.
+    pub fog_of_discovery: bool,
+
+    /// How wide an area each zoom level shows, in meters.
+    pub zoom_levels: Vec<f32>,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            fog_of_discovery: true,
+            zoom_levels: vec![2000.0, 800.0, 300.0],
+        }
+    }
+}
+
+/// The player's settings and profile.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The name the player last played as.
+    pub name: String,
+
+    /// Which keys and buttons each action is bound to.
+    pub controls: InputMap,
+
+    pub gamepad: GamepadSettings,
+
+    pub graphics: GraphicsSettings,
+
+    pub audio: AudioSettings,
+
+    pub minimap: MinimapSettings,
+
+    /// Scale of the UI, relative to the window's own scale factor.
+    pub ui_scale: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            controls: InputMap::default(),
+            gamepad: GamepadSettings::default(),
+            graphics: GraphicsSettings::default(),
+            audio: AudioSettings::default(),
+            minimap: MinimapSettings::default(),
+            ui_scale: 1.0,
+        }
+    }
+}
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("net", "GameCircular", "Loot & Roam")
+}
+
+/// Where the settings are kept, if the platform has a config directory.
+pub fn settings_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join("settings.ron"))
+}
+
+/// Where campaign saves are kept, if the platform has a data directory.
+pub fn saves_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.data_dir().join("saves"))
+}
+
+impl Settings {
+    /// Parses settings from RON.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, LnrError> {
+        ron::de::from_bytes(bytes).context("parsing settings")
+    }
+
+    /// Writes these settings as RON.
+    pub fn to_ron(&self) -> Result<String, LnrError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .context("writing settings")
+    }
+
+    /// The saved settings, or the defaults if there are none or they can't
+    /// be read.
+    pub fn load() -> Self {
+        settings_path().map_or_else(Self::default, |path| Self::load_from(&path))
+    }
+
+    /// The settings saved at a path, or the defaults if there are none or
+    /// they can't be read.
+    pub fn load_from(path: &Path) -> Self {
+        let Ok(bytes) = std::fs::read(path) else {
+            info!("No settings at {}, using the defaults", path.display());
+            return Self::default();
+        };
+
+        Self::from_ron(&bytes).unwrap_or_else(|err| {
+            warn!("Could not read the settings at {}: {err}", path.display());
+            Self::default()
+        })
+    }
+
+    /// Saves these settings, if the platform has a config directory.
+    pub fn save(&self) -> Result<(), LnrError> {
+        match settings_path() {
+            Some(path) => self.save_to(&path),
+            None => Ok(()),
+        }
+    }
+
+    /// Saves these settings at a path.
+    pub fn save_to(&self, path: &Path) -> Result<(), LnrError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        std::fs::write(path, self.to_ron()?)
+            .with_context(|| format!("saving settings to {}", path.display()))
+    }
+}
+
+/// Saves the settings once they have gone unchanged for [SAVE_DELAY], or
+/// when the app exits.
+///
+/// Failing to save is only warned about; the settings still apply for this
+/// session.
+fn save_changed_settings(
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+    exit: EventReader<AppExit>,
+    mut unchanged_for: Local<Option<f32>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *unchanged_for = Some(0.0);
+    }
+    let Some(unchanged) = unchanged_for.as_mut() else {
+        return;
+    };
+
+    *unchanged += time.delta_secs();
+    if *unchanged < SAVE_DELAY && exit.is_empty() {
+        return;
+    }
+
+    *unchanged_for = None;
+    if let Err(err) = settings.save() {
+        warn!("{err}");
+    }
+}
+
+/// Keeps the input settings and resources in sync.
+///
+/// Changes to the resources, e.g. rebinds, are the more recent, so they
+/// win over changes to the settings made in the same frame.
+fn sync_input_settings(
+    mut settings: ResMut<Settings>,
+    mut map: ResMut<InputMap>,
+    mut gamepad: ResMut<GamepadSettings>,
+) {
+    let settings_changed = settings.is_changed();
+
+    if map.is_changed() && !map.is_added() {
+        if settings.controls != *map {
+            settings.controls = map.clone();
+        }
+    } else if settings_changed && settings.controls != *map {
+        *map = settings.controls.clone();
+    }
+
+    if gamepad.is_changed() && !gamepad.is_added() {
+        if settings.gamepad != *gamepad {
+            settings.gamepad = gamepad.clone();
+        }
+    } else if settings_changed && settings.gamepad != *gamepad {
+        *gamepad = settings.gamepad.clone();
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let present_mode = if settings.graphics.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+
+    for mut window in &mut windows {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+/// Player settings plugin.
+///
+/// Included in [crate::app::AppPlugin].
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load();
+        app.insert_resource(settings.controls.clone());
+        app.insert_resource(settings.gamepad.clone());
+        app.insert_resource(settings);
+        app.add_systems(
+            Update,
+            apply_window_settings.run_if(resource_changed::<Settings>),
+        );
+        app.add_systems(Last, (sync_input_settings, save_changed_settings).chain());
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::app::input::{InputAction, InputBinding};
+
+    #[test]
+    fn settings_are_saved_and_loaded_back() {
+        let dir = std::env::temp_dir().join(format!("lnr-settings-{}", std::process::id()));
+        let path = dir.join("settings.ron");
+
+        let mut settings = Settings {
+            name: "Ahab".into(),
+            ui_scale: 1.25,
+            ..default()
+        };
+        settings.audio.music = 0.3;
+        settings.gamepad.dead_zone = 0.1;
+        settings
+            .controls
+            .rebind(InputAction::FirePrimary, InputBinding::Key(KeyCode::Space));
+
+        settings.save_to(&path).unwrap();
+        assert_eq!(Settings::load_from(&path), settings);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unreadable_settings_fall_back_to_the_defaults() {
+        let dir = std::env::temp_dir().join(format!("lnr-corrupt-settings-{}", std::process::id()));
+        let path = dir.join("settings.ron");
+
+        assert_eq!(Settings::load_from(&path), Settings::default());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "(name: \"Ahab\", ui_scale: [oops").unwrap();
+        assert_eq!(Settings::load_from(&path), Settings::default());
+
+        // Missing fields are defaulted, rather than discarding the rest.
+        std::fs::write(&path, "(name: \"Ahab\")").unwrap();
+        let settings = Settings::load_from(&path);
+        assert_eq!(settings.name, "Ahab");
+        assert_eq!(settings.audio, AudioSettings::default());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn input_settings_are_synced_both_ways() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<Settings>();
+        app.init_resource::<InputMap>();
+        app.init_resource::<GamepadSettings>();
+        app.add_systems(Last, sync_input_settings);
+        app.update();
+
+        let space = InputBinding::Key(KeyCode::Space);
+
+        // Rebinding at runtime changes the settings...
+        app.world_mut()
+            .resource_mut::<InputMap>()
+            .rebind(InputAction::FirePrimary, space);
+        app.update();
+        let settings = app.world().resource::<Settings>();
+        assert!(
+            settings
+                .controls
+                .bindings(InputAction::FirePrimary)
+                .contains(&space)
+        );
+
+        // ...and changing the settings, e.g. resetting them, changes what
+        // input code reads.
+        app.world_mut().resource_mut::<Settings>().controls = InputMap::default();
+        app.world_mut().resource_mut::<Settings>().gamepad.dead_zone = 0.3;
+        app.update();
+        assert_eq!(*app.world().resource::<InputMap>(), InputMap::default());
+        assert_eq!(app.world().resource::<GamepadSettings>().dead_zone, 0.3);
+    }
+}
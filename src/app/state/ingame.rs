@@ -2,6 +2,9 @@
 //!
 //! Sets up the internal game logic (pretty much everything in the `common`
 //! tree).
+//!
+//! Loading a campaign, e.g. from the main menu, moves the application into
+//! the game.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -18,7 +21,7 @@
 
 use bevy::prelude::*;
 
-use crate::common::state::GameState;
+use crate::common::{save::campaign::CampaignLoaded, state::GameState};
 
 use super::AppState;
 
@@ -26,10 +29,20 @@ fn ingame_state_exit(mut next_game_state: ResMut<NextState<GameState>>) {
     next_game_state.set(GameState::None);
 }
 
+fn enter_loaded_campaign(
+    mut loads: EventReader<CampaignLoaded>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if loads.read().count() > 0 {
+        next_app_state.set(AppState::InGame);
+    }
+}
+
 pub struct AppInGameStatePlugin;
 
 impl Plugin for AppInGameStatePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnExit(AppState::InGame), ingame_state_exit);
+        app.add_systems(Update, enter_loaded_campaign);
     }
 }
@@ -1,6 +1,15 @@
 //! # Main menu state.
 //!
 //! Entering this state creates and displays a main menu to the screen.
+//!
+//! The main menu is made of screens: the title screen, setting up a new
+//! campaign, loading a saved one, and changing settings. Which screen is
+//! shown is kept in the [MenuStack]; opening a screen pushes it, and going
+//! back pops it, down to the title screen.
+//!
+//! Each screen is a list of [MenuItem]s, drawn every frame to the main
+//! menu's UI context. Items are picked by clicking them, or by moving the
+//! selection with the `Menu*` actions and confirming.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -15,26 +24,334 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use std::path::PathBuf;
 
-use crate::common::state::GameState;
+use bevy::prelude::*;
+
+use crate::{
+    app::{
+        input::{ActionState, InputAction, MenuNavigate},
+        renderer::ui::{
+            event::{UiEvent, UiEventKind, UiEventRouter},
+            layouter::{Anchor, Axis, Content, Edges, Layout, Size, UiContext, UiElement, UiKey},
+        },
+        settings::{Settings, saves_dir},
+    },
+    common::{
+        save::campaign::{LoadFailed, LoadRequested},
+        seed::WorldSeed,
+        state::GameState,
+    },
+};
 
 use super::AppState;
 
+/// The UI scales the settings screen cycles through.
+const UI_SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// How much a volume changes with each step.
+const VOLUME_STEP: f32 = 0.1;
+
+const MENU_WIDTH: f32 = 420.0;
+const TITLE_FONT_SIZE: f32 = 40.0;
+const ITEM_FONT_SIZE: f32 = 20.0;
+const ITEM_COLOR: Color = Color::srgb(0.85, 0.83, 0.78);
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.4);
+const SELECTED_BACK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.08);
+
+/// A screen of the main menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuScreen {
+    Title,
+    NewCampaign,
+    LoadCampaign,
+    Settings,
+}
+
+impl MenuScreen {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Title => "Loot & Roam",
+            Self::NewCampaign => "New campaign",
+            Self::LoadCampaign => "Load campaign",
+            Self::Settings => "Settings",
+        }
+    }
+}
+
+/// The screens of the main menu that were opened, the current one last.
+///
+/// The title screen is always at the bottom.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct MenuStack {
+    screens: Vec<MenuScreen>,
+
+    /// The selected item of the current screen.
+    selected: usize,
+}
+
+impl Default for MenuStack {
+    fn default() -> Self {
+        Self {
+            screens: vec![MenuScreen::Title],
+            selected: 0,
+        }
+    }
+}
+
+impl MenuStack {
+    /// The screen being shown.
+    pub fn current(&self) -> MenuScreen {
+        self.screens.last().copied().unwrap_or(MenuScreen::Title)
+    }
+
+    /// How many screens deep the menu is, the title screen being 1.
+    pub fn depth(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// The index of the selected item of the current screen.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Opens a screen over the current one.
+    pub fn push(&mut self, screen: MenuScreen) {
+        self.screens.push(screen);
+        self.selected = 0;
+    }
+
+    /// Goes back to the previous screen, unless on the title screen.
+    ///
+    /// Returns the screen that was closed, if any.
+    pub fn pop(&mut self) -> Option<MenuScreen> {
+        if self.screens.len() <= 1 {
+            return None;
+        }
+
+        self.selected = 0;
+        self.screens.pop()
+    }
+
+    /// Goes back to the title screen.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Moves the selection by some items, wrapping around a screen with a
+    /// number of items.
+    pub fn select_by(&mut self, by: i32, items: usize) {
+        if items > 0 {
+            self.selected = (self.selected as i32 + by).rem_euclid(items as i32) as usize;
+        }
+    }
+
+    /// Selects an item.
+    pub fn select(&mut self, item: usize) {
+        self.selected = item;
+    }
+}
+
+/// The campaign being set up on the new campaign screen.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct NewCampaignSetup {
+    pub seed: WorldSeed,
+}
+
+/// The saves found when the load campaign screen was opened.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SaveList {
+    pub saves: Vec<PathBuf>,
+
+    /// Why the last save picked could not be loaded, if it couldn't.
+    pub failure: Option<String>,
+}
+
+impl SaveList {
+    /// Lists the saves in the saves directory, most recent first.
+    pub fn scan() -> Self {
+        let Some(entries) = saves_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Self::default();
+        };
+
+        let mut saves = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .map(|entry| {
+                let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+                (modified, entry.path())
+            })
+            .collect::<Vec<_>>();
+        saves.sort_by_key(|save| std::cmp::Reverse(save.0));
+
+        Self {
+            saves: saves.into_iter().map(|(_, path)| path).collect(),
+            failure: None,
+        }
+    }
+}
+
+/// A volume of the [AudioSettings](crate::app::settings::AudioSettings).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Volume {
+    Master,
+    Music,
+    Effects,
+}
+
+/// What picking a menu item does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuAction {
+    Open(MenuScreen),
+    Back,
+    RerollSeed,
+    SetSail,
+    Load(PathBuf),
+    ToggleVsync,
+    ToggleDynamicQuality,
+    ToggleScreenEffects,
+    CycleUiScale,
+    CycleVolume(Volume),
+    Quit,
+
+    /// Only shows information.
+    None,
+}
+
+/// An item of a menu screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuItem {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+impl MenuItem {
+    pub fn new(label: impl Into<String>, action: MenuAction) -> Self {
+        Self {
+            label: label.into(),
+            action,
+        }
+    }
+}
+
+/// The items of a menu screen.
+pub fn menu_items(
+    screen: MenuScreen,
+    settings: &Settings,
+    setup: &NewCampaignSetup,
+    saves: &SaveList,
+) -> Vec<MenuItem> {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    let percent = |volume: f32| format!("{:.0}%", volume * 100.0);
+
+    match screen {
+        MenuScreen::Title => vec![
+            MenuItem::new("New campaign", MenuAction::Open(MenuScreen::NewCampaign)),
+            MenuItem::new("Load campaign", MenuAction::Open(MenuScreen::LoadCampaign)),
+            MenuItem::new("Settings", MenuAction::Open(MenuScreen::Settings)),
+            MenuItem::new("Quit", MenuAction::Quit),
+        ],
+
+        MenuScreen::NewCampaign => vec![
+            MenuItem::new(
+                format!("World seed: {:016X}", setup.seed.0),
+                MenuAction::RerollSeed,
+            ),
+            MenuItem::new("Set sail", MenuAction::SetSail),
+            MenuItem::new("Back", MenuAction::Back),
+        ],
+
+        MenuScreen::LoadCampaign => {
+            let mut items = saves
+                .failure
+                .iter()
+                .map(|failure| MenuItem::new(failure.clone(), MenuAction::None))
+                .collect::<Vec<_>>();
+            items.extend(saves.saves.iter().map(|path| {
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string());
+                MenuItem::new(name, MenuAction::Load(path.clone()))
+            }));
+            if saves.saves.is_empty() {
+                items.push(MenuItem::new("No saved campaigns", MenuAction::None));
+            }
+            items.push(MenuItem::new("Back", MenuAction::Back));
+            items
+        }
+
+        MenuScreen::Settings => vec![
+            MenuItem::new(
+                format!("VSync: {}", on_off(settings.graphics.vsync)),
+                MenuAction::ToggleVsync,
+            ),
+            MenuItem::new(
+                format!(
+                    "Dynamic quality: {}",
+                    on_off(settings.graphics.dynamic_quality)
+                ),
+                MenuAction::ToggleDynamicQuality,
+            ),
+            MenuItem::new(
+                format!(
+                    "Screen effects: {}",
+                    on_off(settings.graphics.screen_effects)
+                ),
+                MenuAction::ToggleScreenEffects,
+            ),
+            MenuItem::new(
+                format!("UI scale: {:.0}%", settings.ui_scale * 100.0),
+                MenuAction::CycleUiScale,
+            ),
+            MenuItem::new(
+                format!("Master volume: {}", percent(settings.audio.master)),
+                MenuAction::CycleVolume(Volume::Master),
+            ),
+            MenuItem::new(
+                format!("Music volume: {}", percent(settings.audio.music)),
+                MenuAction::CycleVolume(Volume::Music),
+            ),
+            MenuItem::new(
+                format!("Effects volume: {}", percent(settings.audio.effects)),
+                MenuAction::CycleVolume(Volume::Effects),
+            ),
+            MenuItem::new("Back", MenuAction::Back),
+        ],
+    }
+}
+
+/// The next value of a cycle of values after the one nearest to a value.
+fn cycle_after(values: &[f32], value: f32) -> f32 {
+    let nearest = values
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - value).abs().total_cmp(&(*b - value).abs()))
+        .map_or(0, |(index, _)| index);
+    values[(nearest + 1) % values.len()]
+}
+
+/// The UI key of a menu item, by index.
+fn item_key(index: usize) -> UiKey {
+    UiKey::new(("main menu item", index))
+}
+
 #[derive(Component)]
 struct MainMenuMarker;
 
-fn main_menu_setup(mut commands: Commands, mut next_game_state: ResMut<NextState<GameState>>) {
+fn main_menu_setup(
+    mut commands: Commands,
+    mut stack: ResMut<MenuStack>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
     info!("Setting up main menu");
     next_game_state.set(GameState::None);
+    stack.reset();
     commands.spawn((
         MainMenuMarker,
-        Text2d("Loot & Roam".to_owned()),
-        TextFont {
-            font_size: 20.0,
-            ..Default::default()
-        },
-        Transform::default(),
+        Name::new("Main menu"),
+        UiContext::default(),
+        UiEventRouter::default(),
     ));
 }
 
@@ -47,19 +364,178 @@ fn main_menu_cleanup(
     }
 }
 
+fn draw_main_menu(
+    stack: Res<MenuStack>,
+    settings: Res<Settings>,
+    setup: Res<NewCampaignSetup>,
+    saves: Res<SaveList>,
+    mut q_mainmenu: Query<&mut UiContext, With<MainMenuMarker>>,
+) {
+    let screen = stack.current();
+    let items = menu_items(screen, &settings, &setup, &saves);
+
+    for mut context in &mut q_mainmenu {
+        let tree = &mut context.tree;
+        tree.clear();
+
+        let column = tree.add_root(
+            UiElement::default()
+                .with_anchor(Anchor::Center)
+                .with_min_size(Vec2::new(MENU_WIDTH, 0.0))
+                .with_layout(Layout::Stack {
+                    axis: Axis::Vertical,
+                    gap: 8.0,
+                }),
+        );
+        tree.add_child(
+            column,
+            UiElement::new(Content::Text {
+                text: screen.title().to_owned(),
+                font_size: TITLE_FONT_SIZE,
+                color: ITEM_COLOR,
+            })
+            .with_anchor(Anchor::Center),
+        );
+        tree.add_child(
+            column,
+            UiElement::default().with_size(Size::Auto, Size::Fixed(16.0)),
+        );
+
+        for (index, item) in items.iter().enumerate() {
+            let selected = index == stack.selected();
+            let row = tree.add_child(
+                column,
+                UiElement::new(Content::Rect {
+                    color: if selected {
+                        SELECTED_BACK_COLOR
+                    } else {
+                        Color::NONE
+                    },
+                })
+                .with_size(Size::FILL, Size::Auto)
+                .with_padding(Edges::symmetric(16.0, 4.0))
+                .with_key(item_key(index)),
+            );
+            tree.add_child(
+                row,
+                UiElement::new(Content::Text {
+                    text: item.label.clone(),
+                    font_size: ITEM_FONT_SIZE,
+                    color: if selected { SELECTED_COLOR } else { ITEM_COLOR },
+                })
+                .with_anchor(Anchor::Center),
+            );
+        }
+    }
+}
+
+fn show_load_failures(mut failures: EventReader<LoadFailed>, mut saves: ResMut<SaveList>) {
+    if let Some(LoadFailed { path, reason }) = failures.read().last() {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        saves.failure = Some(format!("Could not load {name}: {reason}"));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn input_handler_main_menu(
-    keys: Res<ButtonInput<KeyCode>>,
-    // TODO: use when implementing main menu
-    _mouse_buttons: Res<ButtonInput<MouseButton>>,
-    // TODO: use when implementing main menu
-    _q_windows: Query<&Window, With<PrimaryWindow>>,
+    actions: Res<ActionState>,
+    mut navigation: EventReader<MenuNavigate>,
+    mut ui_events: EventReader<UiEvent>,
+    q_mainmenu: Query<Entity, With<MainMenuMarker>>,
+    mut stack: ResMut<MenuStack>,
+    mut settings: ResMut<Settings>,
+    mut setup: ResMut<NewCampaignSetup>,
+    mut saves: ResMut<SaveList>,
+    mut commands: Commands,
+    mut loads: EventWriter<LoadRequested>,
+    mut exit: EventWriter<AppExit>,
     mut next_app_state: ResMut<NextState<AppState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    if keys.just_pressed(KeyCode::Enter) {
-        info!("Leaving main menu for GameState::Start");
-        next_game_state.set(GameState::Start);
-        next_app_state.set(AppState::InGame);
+    let items = menu_items(stack.current(), &settings, &setup, &saves);
+    let mut picked = None;
+
+    for MenuNavigate(direction) in navigation.read() {
+        stack.select_by(-direction.y, items.len());
+    }
+
+    for event in ui_events
+        .read()
+        .filter(|event| q_mainmenu.contains(event.context))
+    {
+        let Some(index) = (0..items.len()).find(|&index| item_key(index) == event.key) else {
+            continue;
+        };
+        match event.kind {
+            UiEventKind::HoverStart => stack.select(index),
+            UiEventKind::Click(MouseButton::Left) => picked = Some(index),
+            _ => {}
+        }
+    }
+
+    if actions.just_pressed(InputAction::MenuConfirm) {
+        picked = Some(stack.selected());
+    }
+
+    let action = if actions.just_pressed(InputAction::MenuBack) {
+        MenuAction::Back
+    } else {
+        match picked.and_then(|index| items.get(index)) {
+            Some(item) => item.action.clone(),
+            None => return,
+        }
+    };
+
+    match action {
+        MenuAction::Open(screen) => {
+            match screen {
+                MenuScreen::NewCampaign => *setup = NewCampaignSetup::default(),
+                MenuScreen::LoadCampaign => *saves = SaveList::scan(),
+                _ => {}
+            }
+            stack.push(screen);
+        }
+        MenuAction::Back => {
+            stack.pop();
+        }
+        MenuAction::RerollSeed => setup.seed = WorldSeed::default(),
+        MenuAction::SetSail => {
+            info!("Leaving main menu for GameState::Start");
+            commands.insert_resource(setup.seed);
+            next_game_state.set(GameState::Start);
+            next_app_state.set(AppState::InGame);
+        }
+        MenuAction::Load(path) => {
+            loads.write(LoadRequested { path });
+        }
+        MenuAction::ToggleVsync => settings.graphics.vsync = !settings.graphics.vsync,
+        MenuAction::ToggleDynamicQuality => {
+            settings.graphics.dynamic_quality = !settings.graphics.dynamic_quality
+        }
+        MenuAction::ToggleScreenEffects => {
+            settings.graphics.screen_effects = !settings.graphics.screen_effects
+        }
+        MenuAction::CycleUiScale => settings.ui_scale = cycle_after(&UI_SCALES, settings.ui_scale),
+        MenuAction::CycleVolume(volume) => {
+            let volume = match volume {
+                Volume::Master => &mut settings.audio.master,
+                Volume::Music => &mut settings.audio.music,
+                Volume::Effects => &mut settings.audio.effects,
+            };
+            // Past full volume, wrap around to silence.
+            *volume = if *volume >= 1.0 - VOLUME_STEP / 2.0 {
+                0.0
+            } else {
+                (*volume + VOLUME_STEP).min(1.0)
+            };
+        }
+        MenuAction::Quit => {
+            exit.write(AppExit::Success);
+        }
+        MenuAction::None => {}
     }
 }
 
@@ -67,12 +543,38 @@ pub struct MainMenuStatePlugin;
 
 impl Plugin for MainMenuStatePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<MenuStack>();
+        app.init_resource::<NewCampaignSetup>();
+        app.init_resource::<SaveList>();
+
         app.add_systems(OnEnter(AppState::MainMenu), main_menu_setup);
         app.add_systems(OnExit(AppState::MainMenu), main_menu_cleanup);
 
         app.add_systems(
             Update,
-            input_handler_main_menu.run_if(in_state(AppState::MainMenu)),
+            (show_load_failures, input_handler_main_menu, draw_main_menu)
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
         );
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_stack_keeps_the_title_screen() {
+        let mut stack = MenuStack::default();
+        stack.push(MenuScreen::Settings);
+        stack.select_by(-1, 7);
+        assert_eq!(stack.current(), MenuScreen::Settings);
+        assert_eq!(stack.selected(), 6);
+
+        assert_eq!(stack.pop(), Some(MenuScreen::Settings));
+        assert_eq!(stack.selected(), 0);
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.current(), MenuScreen::Title);
+        assert_eq!(stack.depth(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 //! # App states.
 //!
-//! The main game application can be in the menu, or in the game.
+//! The main game application can be loading its assets, in the menu, or in
+//! the game.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -23,10 +24,16 @@ pub mod mainmenu;
 /// The applicaiton state of the game.
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AppState {
-    /// The application is in the main menu.
+    /// The application is loading the assets it needs, before the main menu.
     ///
-    /// Submenu states are handled by the menu UI stack resource.
+    /// See [resource](crate::app::resource).
     #[default]
+    Loading,
+
+    /// The application is in the main menu.
+    ///
+    /// Submenu states are handled by the menu UI stack resource, the
+    /// [MenuStack](mainmenu::MenuStack).
     MainMenu,
 
     /// The application is currently in the game.
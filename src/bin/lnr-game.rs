@@ -9,6 +9,10 @@
 //!
 //! The program can also be run "headless", that is, without displaying the
 //! game.
+//!
+//! Run as `lnr-game --migrate-saves <dir>`, it instead checks every save file
+//! in a directory, migrating older ones to the current version in place, and
+//! exits, failing if any save is invalid.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -23,12 +27,85 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
+use std::path::Path;
+use std::process::ExitCode;
+
 use bevy::diagnostic::LogDiagnosticsPlugin;
 use bevy::prelude::*;
 use bevy::window::PresentMode;
 use loot_and_roam::LootAndRoamEnginePlugin;
+use loot_and_roam::common::error::{ErrorPolicy, install_error_handler};
+use loot_and_roam::common::save::campaign::{CampaignMigrations, SavePlugin, migrate_save_dir};
+
+/// Checks and migrates every save in a directory, printing what happened to
+/// each.
+fn migrate_saves(dir: &Path) -> ExitCode {
+    // Migrations are registered by the plugins, as when loading in game.
+    let mut app = App::new();
+    app.add_plugins(SavePlugin);
+    let migrations = app.world().resource::<CampaignMigrations>();
+
+    let results = match migrate_save_dir(dir, migrations) {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failed = 0;
+    for (path, result) in &results {
+        match result {
+            Ok(report) if report.from == report.to => {
+                println!("{}: up to date", path.display());
+            }
+            Ok(report) if report.is_lossless() => {
+                println!(
+                    "{}: migrated from version {} to {}",
+                    path.display(),
+                    report.from,
+                    report.to
+                );
+            }
+            Ok(report) => {
+                println!(
+                    "{}: left at version {}, as migrating would lose:",
+                    path.display(),
+                    report.from
+                );
+                for loss in &report.losses {
+                    println!("  {loss}");
+                }
+            }
+            Err(err) => {
+                failed += 1;
+                println!("{}: invalid: {err}", path.display());
+            }
+        }
+    }
+
+    println!("{} saves checked, {failed} invalid", results.len());
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [flag, dir] if flag == "--migrate-saves" => return migrate_saves(Path::new(dir)),
+        [flag, ..] if flag == "--migrate-saves" => {
+            eprintln!("Usage: lnr-game --migrate-saves <dir>");
+            return ExitCode::FAILURE;
+        }
+        _ => {}
+    }
+
+    // Must be set before the app is initialized.
+    install_error_handler(ErrorPolicy::default());
 
-fn main() {
     let mut app = App::new();
 
     // default plugin & main properties
@@ -51,5 +128,8 @@ fn main() {
     // logger
     app.add_plugins(LogDiagnosticsPlugin::default());
 
-    app.run();
+    match app.run() {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(code) => ExitCode::from(code.get()),
+    }
 }
@@ -0,0 +1,549 @@
+//! # NPC ship controller
+//!
+//! NPC ships are driven by an [AiController], which holds what the ship is
+//! currently up to (its [AiState]), and turns that into a desired velocity
+//! every tick, by blending a few steering behaviors:
+//!
+//! * [seek] and [arrive], towards the next waypoint of the ship's path;
+//! * [flee], away from threats;
+//! * [avoid_terrain], feeling ahead on the [NavGrid] for shallows and land;
+//! * [separation], from other ships.
+//!
+//! Paths are planned on the [NavGrid], for the ship's draft, whenever the
+//! ship's destination changes. Patrolling ships follow routes around the
+//! island; see [patrol]. Escorting ships keep up with their leader, e.g.
+//! the player's fleet with its flagship; see [super::fleet].
+//!
+//! The desired velocity is then turned into a throttle and a rudder angle,
+//! which are dispatched to the ship's parts every tick as `"thrust"` and
+//! `"steer"` [PartAction](super::construct::action::PartAction)s; see
+//! [ThrustCommand] and [SteerCommand]. How they move the ship is up to its
+//! engines and rudders.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+pub mod patrol; // Patrol route generation and assignment
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use super::{
+    construct::action::{SteerCommand, ThrustCommand, dispatch_action},
+    makeup::Ship,
+    math::angle::{heading_direction, heading_of, shortest_arc},
+    physics::base::PointNetwork,
+    terrain::navigation::NavGrid,
+};
+
+/// What an NPC ship is up to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AiState {
+    /// Holding position.
+    #[default]
+    Idle,
+
+    /// Sailing to a destination, on the XZ plane; idles once there.
+    Travel { destination: Vec2 },
+
+    /// Running away from a threat; idles once far enough away, or if the
+    /// threat is gone.
+    Flee { threat: Entity },
+
+    /// Keeping within range of a target; idles if the target is gone.
+    Engage { target: Entity },
+
+    /// Sailing along a loop of waypoints, on the XZ plane.
+    Patrol { waypoints: Vec<Vec2>, next: usize },
+
+    /// Keeping up with a leader, within a distance of it; idles if the
+    /// leader is gone.
+    Escort { leader: Entity, distance: f32 },
+}
+
+/// Drives an NPC ship; see the module documentation.
+#[derive(Component, Clone, Debug)]
+pub struct AiController {
+    /// What the ship is up to.
+    pub state: AiState,
+
+    /// Fastest speed the ship tries to sail at.
+    pub max_speed: f32,
+
+    /// How close to a waypoint the ship needs to be to have reached it.
+    pub arrive_radius: f32,
+
+    /// The ship starts slowing down this far from its destination.
+    pub slowing_radius: f32,
+
+    /// The least water depth the ship can sail in.
+    pub draft: f32,
+
+    /// How far ahead to feel for shallows and land.
+    pub look_ahead: f32,
+
+    /// How far the ship keeps from other ships.
+    pub separation_radius: f32,
+
+    /// How far the ship keeps from targets it engages.
+    pub engage_range: f32,
+
+    /// How far from a threat the ship needs to be to stop fleeing.
+    pub flee_distance: f32,
+
+    /// The planned path to the current destination, if any, without the
+    /// waypoints already reached.
+    path: Vec<Vec2>,
+
+    /// The destination the path was planned for.
+    path_goal: Option<Vec2>,
+
+    /// The latest throttle, between -1.0 and 1.0.
+    throttle: f32,
+
+    /// The latest rudder, between -1.0 and 1.0.
+    rudder: f32,
+}
+
+impl Default for AiController {
+    fn default() -> Self {
+        Self {
+            state: AiState::Idle,
+            max_speed: 8.0,
+            arrive_radius: 6.0,
+            slowing_radius: 30.0,
+            draft: 1.5,
+            look_ahead: 25.0,
+            separation_radius: 15.0,
+            engage_range: 40.0,
+            flee_distance: 200.0,
+            path: vec![],
+            path_goal: None,
+            throttle: 0.0,
+            rudder: 0.0,
+        }
+    }
+}
+
+impl AiController {
+    /// An AI controller starting in a given state.
+    pub fn new(state: AiState) -> Self {
+        Self { state, ..default() }
+    }
+
+    /// The remaining waypoints of the planned path.
+    pub fn path(&self) -> &[Vec2] {
+        &self.path
+    }
+
+    /// The latest throttle, between -1.0 and 1.0.
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// The latest rudder, between -1.0 and 1.0; see [SteerCommand].
+    pub fn rudder(&self) -> f32 {
+        self.rudder
+    }
+
+    /// Where the ship is headed, if anywhere, given where other entities
+    /// are.
+    ///
+    /// Also updates the state, if it is over.
+    fn update_state(
+        &mut self,
+        at: Vec2,
+        position_of: impl Fn(Entity) -> Option<Vec2>,
+    ) -> Option<Vec2> {
+        match &mut self.state {
+            AiState::Idle => None,
+
+            AiState::Travel { destination } => {
+                let destination = *destination;
+                if at.distance(destination) <= self.arrive_radius {
+                    self.state = AiState::Idle;
+                    return None;
+                }
+                Some(destination)
+            }
+
+            AiState::Flee { threat } => {
+                let threat_at = position_of(*threat)
+                    .filter(|threat_at| at.distance(*threat_at) < self.flee_distance);
+                let Some(threat_at) = threat_at else {
+                    self.state = AiState::Idle;
+                    return None;
+                };
+
+                // Flee in a straight line; terrain avoidance keeps the ship
+                // off the shore.
+                let away = (at - threat_at).normalize_or(Vec2::X);
+                Some(at + away * self.look_ahead)
+            }
+
+            AiState::Engage { target } => {
+                let Some(target_at) = position_of(*target) else {
+                    self.state = AiState::Idle;
+                    return None;
+                };
+
+                // Keep within range, on the side the ship is on.
+                let side = (at - target_at).normalize_or(Vec2::X);
+                Some(target_at + side * self.engage_range)
+            }
+
+            AiState::Escort { leader, distance } => {
+                let Some(leader_at) = position_of(*leader) else {
+                    self.state = AiState::Idle;
+                    return None;
+                };
+
+                // Hold position while close enough, and catch up otherwise,
+                // to the side the ship is on.
+                if at.distance(leader_at) <= *distance {
+                    return None;
+                }
+                let side = (at - leader_at).normalize_or(Vec2::X);
+                Some(leader_at + side * *distance * 0.5)
+            }
+
+            AiState::Patrol { waypoints, next } => {
+                if waypoints.is_empty() {
+                    self.state = AiState::Idle;
+                    return None;
+                }
+
+                *next %= waypoints.len();
+                if at.distance(waypoints[*next]) <= self.arrive_radius {
+                    *next = (*next + 1) % waypoints.len();
+                }
+                Some(waypoints[*next])
+            }
+        }
+    }
+
+    /// Plans a path to a destination, unless one was already planned.
+    fn plan(&mut self, at: Vec2, goal: Vec2, nav_grid: Option<&NavGrid>) {
+        let replan = self
+            .path_goal
+            .is_none_or(|planned| planned.distance(goal) > self.arrive_radius);
+
+        if replan {
+            self.path = nav_grid
+                .and_then(|nav_grid| nav_grid.find_path(at, goal, self.draft))
+                .map(|path| path.into_iter().skip(1).collect())
+                .unwrap_or_else(|| vec![goal]);
+            self.path_goal = Some(goal);
+        }
+
+        while self.path.len() > 1 && at.distance(self.path[0]) <= self.arrive_radius {
+            self.path.remove(0);
+        }
+    }
+
+    /// Works out the desired velocity, on the XZ plane.
+    fn desired_velocity(
+        &mut self,
+        at: Vec2,
+        velocity: Vec2,
+        goal: Option<Vec2>,
+        nav_grid: Option<&NavGrid>,
+        neighbours: &[Vec2],
+    ) -> Vec2 {
+        let mut desired = match goal {
+            Some(goal) => {
+                self.plan(at, goal, nav_grid);
+
+                match self.path.as_slice() {
+                    [last] => arrive(at, *last, self.max_speed, self.slowing_radius),
+                    [next, ..] => seek(at, *next, self.max_speed),
+                    [] => Vec2::ZERO,
+                }
+            }
+            None => {
+                self.path.clear();
+                self.path_goal = None;
+                Vec2::ZERO
+            }
+        };
+
+        if let Some(nav_grid) = nav_grid {
+            desired += avoid_terrain(
+                nav_grid,
+                at,
+                velocity,
+                self.draft,
+                self.look_ahead,
+                self.max_speed,
+            );
+        }
+
+        desired += separation(at, neighbours, self.separation_radius, self.max_speed);
+
+        desired.clamp_length_max(self.max_speed)
+    }
+
+    /// Turns a desired velocity into a throttle and a rudder.
+    fn actuate(&mut self, heading: f32, desired: Vec2) {
+        let Some(desired_heading) = heading_of(desired.extend(0.0).xzy()) else {
+            self.throttle = 0.0;
+            self.rudder = 0.0;
+            return;
+        };
+
+        let arc = shortest_arc(heading, desired_heading);
+        let forward = heading_direction(heading).xz();
+
+        self.rudder = (arc / FRAC_PI_2).clamp(-1.0, 1.0);
+
+        // Slow down while turning hard, rather than sailing off course, but
+        // keep some way on, since rudders need it to turn.
+        self.throttle = (forward.dot(desired) / self.max_speed).clamp(0.2, 1.0);
+    }
+}
+
+/// Steers straight towards a target, at full speed.
+pub fn seek(at: Vec2, target: Vec2, max_speed: f32) -> Vec2 {
+    (target - at).normalize_or_zero() * max_speed
+}
+
+/// Steers towards a target, slowing down within `slowing_radius` of it, so
+/// as to stop there.
+pub fn arrive(at: Vec2, target: Vec2, max_speed: f32, slowing_radius: f32) -> Vec2 {
+    let offset = target - at;
+    let distance = offset.length();
+
+    if distance <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+
+    offset / distance * max_speed * (distance / slowing_radius).min(1.0)
+}
+
+/// Steers straight away from a threat, at full speed.
+pub fn flee(at: Vec2, threat: Vec2, max_speed: f32) -> Vec2 {
+    -seek(at, threat, max_speed)
+}
+
+/// Steers away from shallows and land ahead, feeling ahead along and to both
+/// sides of the current velocity.
+pub fn avoid_terrain(
+    nav_grid: &NavGrid,
+    at: Vec2,
+    velocity: Vec2,
+    draft: f32,
+    look_ahead: f32,
+    max_speed: f32,
+) -> Vec2 {
+    let Some(forward) = velocity.try_normalize() else {
+        return Vec2::ZERO;
+    };
+
+    let mut steering = Vec2::ZERO;
+
+    for (angle, reach) in [(0.0, 1.0), (0.5, 0.6), (-0.5, 0.6)] {
+        let feeler = Vec2::from_angle(angle).rotate(forward);
+
+        if !nav_grid.is_clear(at, at + feeler * look_ahead * reach, draft) {
+            // Turn away from blocked feelers, and away from the left if the
+            // way straight ahead is blocked.
+            let lateral = if angle == 0.0 {
+                -forward.perp()
+            } else {
+                -feeler.perp() * angle.signum()
+            };
+            steering += (lateral - forward) * max_speed;
+        }
+    }
+
+    steering
+}
+
+/// Steers away from other ships within `radius`, the closer the harder.
+pub fn separation(at: Vec2, neighbours: &[Vec2], radius: f32, max_speed: f32) -> Vec2 {
+    neighbours
+        .iter()
+        .map(|&other| at - other)
+        .filter(|offset| *offset != Vec2::ZERO && offset.length() < radius)
+        .map(|offset| offset.normalize() * max_speed * (1.0 - offset.length() / radius))
+        .sum()
+}
+
+fn update_ai_controllers(
+    mut controllers: Query<(Entity, &mut AiController, &PointNetwork, &Transform)>,
+    networks: Query<&PointNetwork>,
+    ships: Query<(Entity, &PointNetwork), With<Ship>>,
+    nav_grid: Option<Res<NavGrid>>,
+) {
+    let ship_positions = ships
+        .iter()
+        .map(|(entity, network)| (entity, network.center_of_mass().xz()))
+        .collect::<Vec<_>>();
+
+    for (entity, mut controller, network, transform) in &mut controllers {
+        let at = network.center_of_mass().xz();
+        let velocity = network.linear_velocity().xz();
+
+        let goal = controller.update_state(at, |other| {
+            networks
+                .get(other)
+                .ok()
+                .map(|network| network.center_of_mass().xz())
+        });
+
+        let neighbours = ship_positions
+            .iter()
+            .filter(|(other, _)| *other != entity)
+            .map(|(_, position)| *position)
+            .collect::<Vec<_>>();
+
+        let desired =
+            controller.desired_velocity(at, velocity, goal, nav_grid.as_deref(), &neighbours);
+
+        // Ships face where they are going, or where they were placed facing
+        // if still.
+        let heading = heading_of(velocity.extend(0.0).xzy())
+            .filter(|_| velocity.length() > 0.5)
+            .or_else(|| heading_of(transform.forward().as_vec3()))
+            .unwrap_or(0.0);
+
+        controller.actuate(heading, desired);
+    }
+}
+
+fn dispatch_ai_actions(mut commands: Commands, controllers: Query<(Entity, &AiController)>) {
+    for (entity, controller) in &controllers {
+        dispatch_action(
+            &mut commands,
+            entity,
+            "thrust".into(),
+            vec![],
+            ThrustCommand {
+                throttle: controller.throttle,
+            },
+        );
+        dispatch_action(
+            &mut commands,
+            entity,
+            "steer".into(),
+            vec![],
+            SteerCommand {
+                rudder: controller.rudder,
+            },
+        );
+    }
+}
+
+/// NPC ship controller plugin.
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                patrol::claim_patrol_routes.run_if(resource_exists::<patrol::PatrolRoutes>),
+                update_ai_controllers,
+                dispatch_ai_actions,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{construct::action::PartActionDispatchRequest, physics::base::PhysPoint};
+
+    use super::*;
+
+    #[test]
+    fn ships_sail_around_land() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AiPlugin));
+        app.add_event::<PartActionDispatchRequest>();
+
+        // A wall of land across the X axis, with a gap at x > 40.
+        app.insert_resource(NavGrid::from_heightfield(
+            Rect::new(-100.0, -100.0, 100.0, 100.0),
+            |at| {
+                if at.y.abs() < 10.0 && at.x < 40.0 {
+                    5.0
+                } else {
+                    -10.0
+                }
+            },
+            0.0,
+            4.0,
+        ));
+
+        let destination = Vec2::new(0.0, 60.0);
+        let ship = app
+            .world_mut()
+            .spawn((
+                AiController::new(AiState::Travel { destination }),
+                PointNetwork::from(
+                    [PhysPoint::new(Vec3::new(0.0, 0.0, -60.0), Vec3::ZERO, 1.0)].into_iter(),
+                ),
+                Transform::default(),
+            ))
+            .id();
+        app.update();
+
+        let controller = app.world().get::<AiController>(ship).unwrap();
+        assert_eq!(controller.path().last(), Some(&destination));
+        assert!(controller.path().iter().any(|at| at.x >= 40.0));
+        assert!(controller.throttle() > 0.0);
+
+        // The ship faces -Z, and its first waypoint is ahead and to the
+        // right, so it turns clockwise.
+        assert!(controller.rudder() < 0.0);
+
+        let events = app.world().resource::<Events<PartActionDispatchRequest>>();
+        let tags = events
+            .iter_current_update_events()
+            .map(|request| request.action.action_tag.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(tags, ["thrust", "steer"]);
+
+        // Once there, the ship idles.
+        app.world_mut()
+            .get_mut::<PointNetwork>(ship)
+            .unwrap()
+            .points[0]
+            .pos = destination.extend(0.0).xzy();
+        app.update();
+        assert_eq!(
+            app.world().get::<AiController>(ship).unwrap().state,
+            AiState::Idle
+        );
+    }
+
+    #[test]
+    fn steering_behaviors() {
+        let at = Vec2::ZERO;
+
+        assert_eq!(seek(at, Vec2::new(10.0, 0.0), 5.0), Vec2::new(5.0, 0.0));
+        assert_eq!(flee(at, Vec2::new(10.0, 0.0), 5.0), Vec2::new(-5.0, 0.0));
+        assert_eq!(
+            arrive(at, Vec2::new(2.0, 0.0), 5.0, 4.0),
+            Vec2::new(2.5, 0.0)
+        );
+
+        let away = separation(at, &[Vec2::new(1.0, 0.0), Vec2::new(50.0, 0.0)], 10.0, 5.0);
+        assert!(away.x < 0.0 && away.y == 0.0);
+    }
+}
@@ -0,0 +1,295 @@
+//! # Patrol routes
+//!
+//! Armed ships may patrol an island, sailing in loops around its shore.
+//! [PatrolRoutes] are generated when an overworld scene is set up, as many as
+//! its [OverworldSceneParams::patrol_paths], each following a roughly
+//! constant water depth contour, further out to sea for every route.
+//!
+//! Armed NPC ships which are set to patrol (see [NpcShip::patrolling], rolled
+//! from [OverworldSceneParams::patrol_occupancy]) claim a free route, if
+//! there is any, and follow it with their [AiController].
+//!
+//! [OverworldSceneParams::patrol_paths]: crate::common::scene::init::OverworldSceneParams::patrol_paths
+//! [OverworldSceneParams::patrol_occupancy]: crate::common::scene::init::OverworldSceneParams::patrol_occupancy
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::common::{physics::base::PointNetwork, spawner::NpcShip, terrain::navigation::NavGrid};
+
+use super::{AiController, AiState};
+
+/// A loop of waypoints around an island.
+#[derive(Clone, Debug, Default)]
+pub struct PatrolRoute {
+    /// The waypoints, on the XZ plane, in order.
+    pub waypoints: Vec<Vec2>,
+
+    /// The water depth the route follows.
+    pub depth: f32,
+
+    /// The ship patrolling this route, if any.
+    pub claimed_by: Option<Entity>,
+}
+
+/// The patrol routes of the overworld scene.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PatrolRoutes {
+    pub routes: Vec<PatrolRoute>,
+}
+
+impl PatrolRoutes {
+    /// Generates patrol routes around an island.
+    ///
+    /// * `center` - Center of the island, on the XZ plane.
+    /// * `count` - How many routes to generate.
+    /// * `min_depth` - Water depth followed by the innermost route.
+    /// * `depth_step` - How much deeper every further route is.
+    ///
+    /// Routes are traced along rays cast from the island's center, so they
+    /// are only loose approximations of each contour. Routes which would not
+    /// go around anything, e.g. in open sea, are left out.
+    pub fn generate(
+        nav_grid: &NavGrid,
+        center: Vec2,
+        count: u8,
+        min_depth: f32,
+        depth_step: f32,
+    ) -> Self {
+        let routes = (0..count)
+            .filter_map(|idx| {
+                let depth = min_depth + idx as f32 * depth_step;
+                let waypoints = trace_contour(nav_grid, center, depth)?;
+
+                Some(PatrolRoute {
+                    waypoints,
+                    depth,
+                    claimed_by: None,
+                })
+            })
+            .collect();
+
+        Self { routes }
+    }
+
+    /// Claims a free route for a ship, if any.
+    ///
+    /// Routes claimed by ships for which `exists` is false are freed first.
+    pub fn claim(&mut self, ship: Entity, exists: impl Fn(Entity) -> bool) -> Option<&PatrolRoute> {
+        for route in &mut self.routes {
+            if route.claimed_by.is_some_and(|claimant| !exists(claimant)) {
+                route.claimed_by = None;
+            }
+        }
+
+        let route = self
+            .routes
+            .iter_mut()
+            .find(|route| route.claimed_by.is_none())?;
+        route.claimed_by = Some(ship);
+
+        Some(route)
+    }
+}
+
+/// How many rays to trace every contour along.
+const CONTOUR_RAYS: usize = 48;
+
+/// Traces a loop along a water depth contour, around a center.
+fn trace_contour(nav_grid: &NavGrid, center: Vec2, depth: f32) -> Option<Vec<Vec2>> {
+    let bounds = nav_grid.bounds();
+    let reach = (bounds.max - center).max(center - bounds.min).length();
+    let step = nav_grid.cell_size() * 0.5;
+
+    // On every ray, the first point from the outside in where the water gets
+    // too shallow.
+    let crossings = (0..CONTOUR_RAYS)
+        .filter_map(|ray| {
+            let direction = Vec2::from_angle(ray as f32 / CONTOUR_RAYS as f32 * TAU);
+            let steps = (reach / step) as usize;
+
+            (0..steps)
+                .map(|idx| reach - idx as f32 * step)
+                .find(|&distance| nav_grid.depth_at(center + direction * distance) < depth)
+                .map(|distance| center + direction * (distance + step))
+        })
+        .collect::<Vec<_>>();
+
+    // Routes must go all around something.
+    if crossings.len() < CONTOUR_RAYS {
+        return None;
+    }
+
+    // Link waypoints around anything in the way, e.g. across bays.
+    let mut waypoints = vec![];
+    for (idx, &from) in crossings.iter().enumerate() {
+        let to = crossings[(idx + 1) % crossings.len()];
+        waypoints.push(from);
+
+        if !nav_grid.is_clear(from, to, depth) {
+            let detour = nav_grid.find_path(from, to, depth)?;
+            waypoints.extend(&detour[1..detour.len() - 1]);
+        }
+    }
+
+    Some(waypoints)
+}
+
+pub(super) fn claim_patrol_routes(
+    mut routes: ResMut<PatrolRoutes>,
+    mut ships: Query<(Entity, &mut NpcShip, &mut AiController, &PointNetwork), Added<NpcShip>>,
+    existing: Query<(), With<NpcShip>>,
+) {
+    for (entity, mut npc, mut controller, network) in &mut ships {
+        if !npc.armed || !npc.patrolling {
+            continue;
+        }
+
+        let Some(route) = routes.claim(entity, |other| existing.contains(other)) else {
+            // No free route; the ship goes about its business instead.
+            npc.patrolling = false;
+            continue;
+        };
+
+        // Join the route at its nearest waypoint.
+        let at = network.center_of_mass().xz();
+        let next = route
+            .waypoints
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.distance(at).total_cmp(&b.distance(at)))
+            .map_or(0, |(idx, _)| idx);
+
+        controller.state = AiState::Patrol {
+            waypoints: route.waypoints.clone(),
+            next,
+        };
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        ai::AiController,
+        physics::base::{PhysPoint, PointNetwork},
+        spawner::NpcShip,
+        terrain::navigation::NavGrid,
+    };
+
+    use super::*;
+
+    /// A round island, 60 units across at sea level, sloping down 1 unit of
+    /// depth per unit of distance.
+    fn island() -> NavGrid {
+        NavGrid::from_heightfield(
+            Rect::new(-150.0, -150.0, 150.0, 150.0),
+            |at| 30.0 - at.length(),
+            0.0,
+            4.0,
+        )
+    }
+
+    #[test]
+    fn patrols_follow_depth_contours() {
+        let nav_grid = island();
+        let routes = PatrolRoutes::generate(&nav_grid, Vec2::ZERO, 2, 5.0, 20.0);
+        assert_eq!(routes.routes.len(), 2);
+
+        for route in &routes.routes {
+            let radius = 30.0 + route.depth;
+
+            for waypoint in &route.waypoints {
+                assert!((waypoint.length() - radius).abs() < 6.0, "{waypoint}");
+                assert!(nav_grid.depth_at(*waypoint) >= route.depth - 4.0);
+            }
+        }
+
+        // Open sea has nothing to go around.
+        let open_sea = NavGrid::from_heightfield(
+            Rect::new(-150.0, -150.0, 150.0, 150.0),
+            |_| -100.0,
+            0.0,
+            4.0,
+        );
+        assert!(
+            PatrolRoutes::generate(&open_sea, Vec2::ZERO, 2, 5.0, 20.0)
+                .routes
+                .is_empty()
+        );
+
+        // Patrolling ships claim a free route each.
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(routes);
+        app.add_systems(Update, claim_patrol_routes);
+
+        let ships = (0..3)
+            .map(|idx| {
+                app.world_mut()
+                    .spawn((
+                        NpcShip {
+                            armed: true,
+                            patrolling: true,
+                            visitor: false,
+                        },
+                        AiController::default(),
+                        PointNetwork::from(
+                            [PhysPoint::new(
+                                Vec3::X * (100.0 + idx as f32),
+                                Vec3::ZERO,
+                                1.0,
+                            )]
+                            .into_iter(),
+                        ),
+                    ))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+        app.update();
+
+        let patrolling = ships
+            .iter()
+            .filter(|&&ship| {
+                matches!(
+                    app.world().get::<AiController>(ship).unwrap().state,
+                    AiState::Patrol { .. }
+                )
+            })
+            .count();
+        assert_eq!(patrolling, 2);
+        assert!(!app.world().get::<NpcShip>(ships[2]).unwrap().patrolling);
+
+        // Routes of despawned ships are freed for new ones.
+        app.world_mut().despawn(ships[0]);
+        let newcomer = app
+            .world_mut()
+            .spawn((
+                NpcShip {
+                    armed: true,
+                    patrolling: true,
+                    visitor: true,
+                },
+                AiController::default(),
+                PointNetwork::from([PhysPoint::new(Vec3::X * 100.0, Vec3::ZERO, 1.0)].into_iter()),
+            ))
+            .id();
+        app.update();
+        assert!(app.world().get::<NpcShip>(newcomer).unwrap().patrolling);
+    }
+}
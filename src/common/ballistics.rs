@@ -0,0 +1,471 @@
+//! # Ballistics
+//!
+//! Trajectory prediction for cannonballs and other projectiles.
+//!
+//! Given a cannon and the environment, [solve_launch] finds how to aim and
+//! how much power to use to hit a point, and [LaunchSolution::trajectory]
+//! samples the resulting flight path as a polyline. NPC aiming and the HUD
+//! aiming indicator both use these, so that they always agree.
+//!
+//! Projectiles are modelled as point masses under gravity, optionally with
+//! linear drag, matching [crate::common::physics::forces::AirDrag]. Gravity
+//! always points down the Y axis.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Moving targets. For now, callers can lead a target by re-solving
+// against its predicted position after the flight time.
+
+use std::{f32::consts::FRAC_PI_2, ops::Range};
+
+use bevy::prelude::*;
+
+use super::physics::forces::Gravity;
+
+/// Iterations used by the numeric solvers.
+const SOLVER_ITERATIONS: usize = 48;
+
+/// Steepest elevation considered, just short of straight up or down.
+const MAX_ELEVATION: f32 = FRAC_PI_2 - 0.01;
+
+/// The properties of a cannon that matter for aiming.
+#[derive(Clone, Debug)]
+pub struct CannonSpec {
+    /// Range of muzzle speeds the cannon can fire at.
+    pub power_range: Range<f32>,
+
+    /// Maximum angle by which a shot may deviate from where it is aimed, in
+    /// radians.
+    pub spread: f32,
+}
+
+/// The environment projectiles fly through.
+#[derive(Clone, Copy, Debug)]
+pub struct BallisticsEnv {
+    /// Downwards acceleration of gravity.
+    pub gravity: f32,
+
+    /// Linear drag coefficient, i.e. velocity lost per second per unit of
+    /// velocity, if any.
+    ///
+    /// For an object with [crate::common::physics::forces::AirDrag], this is
+    /// its surface area times the drag factor, divided by its mass.
+    pub drag: Option<f32>,
+}
+
+impl BallisticsEnv {
+    /// Environment with the given [Gravity], and no drag.
+    pub fn from_gravity(gravity: &Gravity) -> Self {
+        Self {
+            gravity: -gravity.force.y,
+            drag: None,
+        }
+    }
+
+    /// Sets the linear drag coefficient.
+    pub fn with_drag(self, drag: f32) -> Self {
+        Self {
+            drag: Some(drag),
+            ..self
+        }
+    }
+
+    /// Drag coefficient, with no drag as zero.
+    fn drag_coefficient(&self) -> f32 {
+        self.drag.unwrap_or(0.0).max(0.0)
+    }
+
+    /// Position of a projectile some time after it was launched.
+    pub fn position_at(&self, from: Vec3, velocity: Vec3, time: f32) -> Vec3 {
+        let gravity = Vec3::NEG_Y * self.gravity;
+        let drag = self.drag_coefficient();
+
+        if drag <= f32::EPSILON {
+            return from + velocity * time + 0.5 * gravity * time * time;
+        }
+
+        // Velocity decays exponentially towards the terminal velocity.
+        let terminal = gravity / drag;
+        from + terminal * time + (velocity - terminal) * (1.0 - (-drag * time).exp()) / drag
+    }
+
+    /// Velocity of a projectile some time after it was launched.
+    pub fn velocity_at(&self, velocity: Vec3, time: f32) -> Vec3 {
+        let gravity = Vec3::NEG_Y * self.gravity;
+        let drag = self.drag_coefficient();
+
+        if drag <= f32::EPSILON {
+            return velocity + gravity * time;
+        }
+
+        let terminal = gravity / drag;
+        terminal + (velocity - terminal) * (-drag * time).exp()
+    }
+
+    /// How long a projectile takes to cover a horizontal distance, if it
+    /// ever does.
+    fn time_to_distance(&self, horizontal_speed: f32, distance: f32) -> Option<f32> {
+        let drag = self.drag_coefficient();
+
+        if drag <= f32::EPSILON {
+            return (horizontal_speed > 0.0).then(|| distance / horizontal_speed);
+        }
+
+        // With drag, horizontal travel is bounded by speed / drag.
+        let fraction = drag * distance / horizontal_speed;
+        (fraction < 1.0).then(|| -(1.0 - fraction).ln() / drag)
+    }
+
+    /// Height reached relative to the launch point, once the horizontal
+    /// distance is covered, and when.
+    fn height_at_distance(&self, speed: f32, elevation: f32, distance: f32) -> Option<(f32, f32)> {
+        let time = self.time_to_distance(speed * elevation.cos(), distance)?;
+        let velocity = Vec3::new(elevation.cos(), elevation.sin(), 0.0) * speed;
+
+        Some((self.position_at(Vec3::ZERO, velocity, time).y, time))
+    }
+}
+
+/// Which of the two possible arcs to prefer, when aiming.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ArcPreference {
+    /// The flatter arc, at full power; the fastest shot.
+    #[default]
+    Direct,
+
+    /// The higher arc, at full power; lobs over obstacles.
+    Lob,
+
+    /// The least power that still reaches the target.
+    MinimumPower,
+}
+
+/// How to fire a cannon to hit a target.
+#[derive(Clone, Debug)]
+pub struct LaunchSolution {
+    /// Direction to fire at.
+    pub direction: Vec3,
+
+    /// Angle above the horizon to fire at, in radians.
+    pub elevation: f32,
+
+    /// Muzzle speed to fire at.
+    pub speed: f32,
+
+    /// How long the projectile takes to reach the target.
+    pub flight_time: f32,
+
+    /// Roughly how far from the target shots may land, due to spread.
+    pub spread_radius: f32,
+}
+
+impl LaunchSolution {
+    /// Initial velocity of the projectile.
+    pub fn velocity(&self) -> Vec3 {
+        self.direction * self.speed
+    }
+
+    /// Samples the trajectory of the projectile, from launch until it
+    /// reaches the target, as a polyline of `samples` points (at least 2).
+    pub fn trajectory(&self, from: Vec3, env: &BallisticsEnv, samples: usize) -> Vec<Vec3> {
+        sample_trajectory(from, self.velocity(), env, self.flight_time, samples)
+    }
+}
+
+/// Samples the trajectory of a projectile as a polyline of `samples` points
+/// (at least 2), evenly spaced in time, from launch until `duration`.
+pub fn sample_trajectory(
+    from: Vec3,
+    velocity: Vec3,
+    env: &BallisticsEnv,
+    duration: f32,
+    samples: usize,
+) -> Vec<Vec3> {
+    let samples = samples.max(2);
+
+    (0..samples)
+        .map(|idx| {
+            let time = duration * idx as f32 / (samples - 1) as f32;
+            env.position_at(from, velocity, time)
+        })
+        .collect()
+}
+
+/// Finds where the height reached at a distance peaks, over elevations.
+///
+/// Height at a distance is unimodal over elevation, so a golden-section
+/// search finds it. Returns the elevation and the height.
+fn peak_elevation(env: &BallisticsEnv, speed: f32, distance: f32) -> (f32, f32) {
+    let height = |elevation: f32| {
+        env.height_at_distance(speed, elevation, distance)
+            .map_or(f32::NEG_INFINITY, |(height, _)| height)
+    };
+
+    let ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = (-MAX_ELEVATION, MAX_ELEVATION);
+
+    for _ in 0..SOLVER_ITERATIONS {
+        let mid_low = high - ratio * (high - low);
+        let mid_high = low + ratio * (high - low);
+
+        if height(mid_low) < height(mid_high) {
+            low = mid_low;
+        } else {
+            high = mid_high;
+        }
+    }
+
+    let elevation = (low + high) / 2.0;
+    (elevation, height(elevation))
+}
+
+/// Finds the elevation at which the height at a distance equals `target`,
+/// between two elevations whose heights are on either side of it.
+fn bisect_elevation(
+    env: &BallisticsEnv,
+    speed: f32,
+    distance: f32,
+    target: f32,
+    below: f32,
+    above: f32,
+) -> f32 {
+    let (mut below, mut above) = (below, above);
+
+    for _ in 0..SOLVER_ITERATIONS {
+        let mid = (below + above) / 2.0;
+        let reaches = env
+            .height_at_distance(speed, mid, distance)
+            .is_some_and(|(height, _)| height >= target);
+
+        if reaches {
+            above = mid;
+        } else {
+            below = mid;
+        }
+    }
+
+    (below + above) / 2.0
+}
+
+/// Finds an elevation to hit a point at a horizontal distance and relative
+/// height, at a given speed. Returns the elevation and flight time.
+fn solve_elevation(
+    env: &BallisticsEnv,
+    speed: f32,
+    distance: f32,
+    height: f32,
+    lob: bool,
+) -> Option<(f32, f32)> {
+    let (peak, peak_height) = peak_elevation(env, speed, distance);
+
+    if peak_height < height {
+        return None;
+    }
+
+    let elevation = if lob {
+        bisect_elevation(env, speed, distance, height, MAX_ELEVATION, peak)
+    } else {
+        bisect_elevation(env, speed, distance, height, -MAX_ELEVATION, peak)
+    };
+
+    let (_, time) = env.height_at_distance(speed, elevation, distance)?;
+    Some((elevation, time))
+}
+
+/// Computes how to fire a cannon from a point to hit another.
+///
+/// Returns [None] if the target is out of the cannon's range.
+pub fn solve_launch(
+    from: Vec3,
+    target: Vec3,
+    cannon: &CannonSpec,
+    env: &BallisticsEnv,
+    preference: ArcPreference,
+) -> Option<LaunchSolution> {
+    let offset = target - from;
+    let horizontal = Vec3::new(offset.x, 0.0, offset.z);
+    let distance = horizontal.length();
+    let heading = horizontal.normalize_or(Vec3::X);
+    let max_speed = cannon.power_range.end;
+
+    let speed = match preference {
+        ArcPreference::Direct | ArcPreference::Lob => max_speed,
+        ArcPreference::MinimumPower => {
+            // Whether the target can be reached at all is monotonic in speed.
+            let reaches = |speed: f32| peak_elevation(env, speed, distance).1 >= offset.y;
+
+            if !reaches(max_speed) {
+                return None;
+            }
+
+            let (mut low, mut high) = (cannon.power_range.start, max_speed);
+
+            if !reaches(low) {
+                for _ in 0..SOLVER_ITERATIONS {
+                    let mid = (low + high) / 2.0;
+                    if reaches(mid) {
+                        high = mid;
+                    } else {
+                        low = mid;
+                    }
+                }
+                low = high;
+            }
+
+            low
+        }
+    };
+
+    let (elevation, flight_time) = solve_elevation(
+        env,
+        speed,
+        distance,
+        offset.y,
+        preference == ArcPreference::Lob,
+    )?;
+
+    Some(LaunchSolution {
+        direction: heading * elevation.cos() + Vec3::Y * elevation.sin(),
+        elevation,
+        speed,
+        flight_time,
+        spread_radius: offset.length() * cannon.spread.tan(),
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use super::{ArcPreference, BallisticsEnv, CannonSpec, solve_launch};
+
+    const CANNON: CannonSpec = CannonSpec {
+        power_range: 10.0..40.0,
+        spread: 0.02,
+    };
+
+    const NO_DRAG: BallisticsEnv = BallisticsEnv {
+        gravity: 10.0,
+        drag: None,
+    };
+
+    const DRAG: BallisticsEnv = BallisticsEnv {
+        gravity: 10.0,
+        drag: Some(0.1),
+    };
+
+    fn assert_hits(env: &BallisticsEnv, from: Vec3, target: Vec3, preference: ArcPreference) {
+        let solution = solve_launch(from, target, &CANNON, env, preference).unwrap();
+        let landing = env.position_at(from, solution.velocity(), solution.flight_time);
+
+        assert!(
+            landing.distance(target) < 0.05,
+            "{preference:?} shot landed at {landing}, aimed at {target}"
+        );
+        assert!(CANNON.power_range.start <= solution.speed);
+        assert!(solution.speed <= CANNON.power_range.end);
+    }
+
+    #[test]
+    fn hits_target() {
+        let from = Vec3::new(1.0, 2.0, -3.0);
+
+        for target in [
+            Vec3::new(60.0, 0.0, 20.0),
+            Vec3::new(-30.0, 10.0, 45.0),
+            Vec3::new(5.0, -5.0, 0.0),
+        ] {
+            for preference in [
+                ArcPreference::Direct,
+                ArcPreference::Lob,
+                ArcPreference::MinimumPower,
+            ] {
+                assert_hits(&NO_DRAG, from, target, preference);
+                assert_hits(&DRAG, from, target, preference);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_analytic_solution() {
+        // Without drag, the flat arc at full power has a closed form.
+        let (distance, speed, gravity) = (100.0_f32, 40.0_f32, 10.0_f32);
+        let expected = ((speed.powi(2)
+            - (speed.powi(4) - gravity * gravity * distance * distance).sqrt())
+            / (gravity * distance))
+            .atan();
+
+        let solution = solve_launch(
+            Vec3::ZERO,
+            Vec3::X * distance,
+            &CANNON,
+            &NO_DRAG,
+            ArcPreference::Direct,
+        )
+        .unwrap();
+
+        assert!((solution.elevation - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn out_of_range() {
+        // Maximum range without drag is speed² / gravity = 160.
+        let target = Vec3::X * 170.0;
+
+        for preference in [
+            ArcPreference::Direct,
+            ArcPreference::Lob,
+            ArcPreference::MinimumPower,
+        ] {
+            assert!(solve_launch(Vec3::ZERO, target, &CANNON, &NO_DRAG, preference).is_none());
+        }
+
+        // Drag shortens the range.
+        let target = Vec3::X * 150.0;
+        assert!(
+            solve_launch(Vec3::ZERO, target, &CANNON, &NO_DRAG, ArcPreference::Direct).is_some()
+        );
+        assert!(solve_launch(Vec3::ZERO, target, &CANNON, &DRAG, ArcPreference::Direct).is_none());
+    }
+
+    #[test]
+    fn arc_preferences() {
+        let target = Vec3::new(80.0, 0.0, 0.0);
+        let solve =
+            |preference| solve_launch(Vec3::ZERO, target, &CANNON, &NO_DRAG, preference).unwrap();
+
+        let direct = solve(ArcPreference::Direct);
+        let lob = solve(ArcPreference::Lob);
+        let minimum = solve(ArcPreference::MinimumPower);
+
+        assert!(direct.elevation < lob.elevation);
+        assert!(direct.flight_time < lob.flight_time);
+        assert!(minimum.speed < direct.speed);
+
+        // The least power is needed at 45 degrees, when there is no drag.
+        assert!((minimum.elevation - std::f32::consts::FRAC_PI_4).abs() < 0.01);
+    }
+
+    #[test]
+    fn trajectory_ends_at_target() {
+        let from = Vec3::new(0.0, 3.0, 0.0);
+        let target = Vec3::new(20.0, 0.0, 40.0);
+        let solution = solve_launch(from, target, &CANNON, &DRAG, ArcPreference::Direct).unwrap();
+        let trajectory = solution.trajectory(from, &DRAG, 16);
+
+        assert_eq!(trajectory.len(), 16);
+        assert_eq!(trajectory[0], from);
+        assert!(trajectory[15].distance(target) < 0.05);
+    }
+}
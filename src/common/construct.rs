@@ -7,22 +7,29 @@
 
 use bevy::prelude::*;
 
+use super::physics::base::point_base_physics;
+
 pub mod action;
 pub mod install;
+pub mod layout;
 pub mod part;
 pub mod slot;
 
 pub mod prelude {
     pub use super::action::{
-        DebugPrintPart, PartAction, PartActionDispatchRequest, dispatch_action,
+        DebugPrintPart, FireWeaponCommand, PartAction, PartActionData, PartActionDispatchRequest,
+        PartActionOutcome, PartActionResult, SteerCommand, ThrustCommand, dispatch_action,
     };
     pub use super::install::{
-        TryInstallPartOnConstruct, TryInstallPartOnSlot, TryUninstallPart,
-        install_part_on_construct, install_part_on_slot, uninstall_part,
+        PartInstallError, PartInstallErrorReason, PartInstallQuery, TryInstallPartOnConstruct,
+        TryInstallPartOnSlot, TryUninstallPart, install_part_on_construct, install_part_on_slot,
+        uninstall_part,
     };
+    pub use super::layout::{PartMass, PartMassDistribution};
     pub use super::part::{ConstructParts, PartInstalledOn};
     pub use super::slot::{
-        ConstructSlots, PartInfo, PartSlotInfo, SlotOfConstruct, part_slot, part_tag, part_tags,
+        ConstructSlots, PartInfo, PartSlotInfo, SlotAttachment, SlotOfConstruct, part_slot,
+        part_tag, part_tags,
     };
 }
 
@@ -36,12 +43,22 @@ impl Plugin for ConstructPlugin {
         app.add_event::<install::TryInstallPartOnSlot>();
         app.add_event::<install::TryInstallPartOnConstruct>();
         app.add_event::<install::TryUninstallPart>();
+        app.add_event::<install::PartInstallError>();
         app.add_event::<action::PartAction>();
         app.add_event::<action::PartActionDispatchRequest>();
+        app.add_event::<action::PartActionOutcome>();
         app.add_systems(Update, action::ev_dispatch_part_actions);
+        app.add_systems(
+            FixedUpdate,
+            (
+                layout::snap_parts_to_slots.after(point_base_physics),
+                layout::distribute_part_mass,
+            ),
+        );
         app.add_observer(install::ev_try_install_part_on_slot);
         app.add_observer(install::ev_try_install_part_on_construct);
         app.add_observer(install::ev_try_uninstall_part);
+        app.register_type::<action::DebugPrintPart>();
         app.add_observer(action::obs_debug_part_action);
     }
 }
@@ -13,23 +13,34 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use std::{fmt::Debug, sync::Arc};
+use std::fmt::Debug;
 
 use bevy::{
+    asset::ron,
     ecs::{
         entity::Entity,
+        error::Result,
         event::{Event, EventReader, EventWriter},
         observer::Trigger,
-        system::{Commands, In, Query},
+        reflect::AppTypeRegistry,
+        system::{Commands, In, Query, Res},
     },
     log::{debug, info},
-    reflect::Reflect,
+    math::Vec3,
+    reflect::{
+        FromReflect, PartialReflect, Reflect, TypePath, TypeRegistry,
+        serde::{ReflectDeserializer, ReflectSerializer},
+    },
 };
+use serde::{Deserialize, Serialize, de::DeserializeSeed};
 
-use crate::common::construct::{part::ConstructParts, slot::PartInfo};
+use crate::common::{
+    construct::{part::ConstructParts, slot::PartInfo},
+    error::{Context, LnrResult},
+};
 
 /// A part action event.
-#[derive(Event)]
+#[derive(Event, Serialize, Deserialize)]
 pub struct PartAction {
     /// The action tag of this event.
     ///
@@ -49,26 +60,96 @@ pub struct PartAction {
 
     /// Any data passed to the action handler.
     ///
-    /// For example, a `"fire_weapon"` event may have its data be a
-    /// WeaponFireOptions struct, which informs a weapon
+    /// For example, a `"fire_weapon"` event has its data be a
+    /// [FireWeaponCommand], which informs a weapon
     /// * The desired position to shoot at, if possible, assuming the weapon can
     ///   back-calculate requested power (Newtons) and angle (radians) from this
     /// * A descriptor or selector for which ammunition type to shoot if
     ///   available
-    ///   TODO: transplant the below into the WeaponFireArgs documentation
-    ///   * May cascade with fallbacks. For example,
-    ///     ```
-    ///     [
-    ///       'cannonball 40mm incendiary',
-    ///       'cannonball 40mm propeller_gum',
-    ///       'cannonball 40mm'
-    ///     ]
-    ///     ```
-    ///     tries to find 40mm with the incendiary charge and propeller gum
-    ///     modifiers first, and fires a vanilla round if not found.
-    ///   * Ammunition that is incompatible is ignored (e.g. cannon and
-    ///     cannonball with mismatching callibers)
-    pub data: Arc<Box<dyn Reflect>>,
+    pub data: PartActionData,
+}
+
+/// The data of a [PartAction].
+///
+/// Parts should treat data they cannot make sense of as
+/// [PartActionResult::Incompatible].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum PartActionData {
+    /// No data, for actions which need none.
+    #[default]
+    Empty,
+
+    /// Data of a `"fire_weapon"` action.
+    FireWeapon(FireWeaponCommand),
+
+    /// Data of a `"thrust"` action.
+    Thrust(ThrustCommand),
+
+    /// Data of a `"steer"` action.
+    Steer(SteerCommand),
+
+    /// Data of any other action, serialized by whoever defines it.
+    Custom(Vec<u8>),
+
+    /// Data of any other action, as any registered [Reflect] type,
+    /// serialized to RON along with its type path.
+    ///
+    /// Meant for mods and debugging. See [PartActionData::reflect] and
+    /// [PartActionData::reflected].
+    Reflect(String),
+}
+
+impl PartActionData {
+    /// Wraps any [Reflect] type as action data.
+    ///
+    /// The type must be registered in the type registry, as must be the
+    /// types of its fields.
+    pub fn reflect(data: &dyn PartialReflect, registry: &TypeRegistry) -> LnrResult<Self> {
+        let serializer = ReflectSerializer::new(data, registry);
+        let data = ron::ser::to_string(&serializer).context("serializing reflected action data")?;
+
+        Ok(Self::Reflect(data))
+    }
+
+    /// The data as a [Reflect] type, if it was passed as one and is of type
+    /// `T`.
+    pub fn reflected<T: FromReflect + TypePath>(&self, registry: &TypeRegistry) -> Option<T> {
+        let Self::Reflect(data) = self else {
+            return None;
+        };
+
+        let mut deserializer = ron::Deserializer::from_str(data).ok()?;
+        let data = ReflectDeserializer::new(registry)
+            .deserialize(&mut deserializer)
+            .ok()?;
+        if data
+            .get_represented_type_info()
+            .map(|info| info.type_path())
+            != Some(T::type_path())
+        {
+            return None;
+        }
+
+        T::from_reflect(&*data)
+    }
+}
+
+impl From<FireWeaponCommand> for PartActionData {
+    fn from(command: FireWeaponCommand) -> Self {
+        Self::FireWeapon(command)
+    }
+}
+
+impl From<ThrustCommand> for PartActionData {
+    fn from(command: ThrustCommand) -> Self {
+        Self::Thrust(command)
+    }
+}
+
+impl From<SteerCommand> for PartActionData {
+    fn from(command: SteerCommand) -> Self {
+        Self::Steer(command)
+    }
 }
 
 impl Debug for PartAction {
@@ -92,6 +173,43 @@ impl Clone for PartAction {
     }
 }
 
+/// What came of a [PartAction], for a part which handles it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartActionResult {
+    /// The part did what it was asked to.
+    Performed,
+
+    /// The part is still reloading, or otherwise not ready.
+    Cooldown,
+
+    /// The part had nothing to act with, such as compatible ammunition.
+    NoAmmo,
+
+    /// The part lacks the crew to man it.
+    Unmanned,
+
+    /// The part could not make sense of the action's data, or is not
+    /// installed on a construct it can act on.
+    Incompatible,
+}
+
+/// Sent by parts whenever they handle a [PartAction], e.g. for the AI to
+/// re-plan, or the UI to play a click when a weapon is out of ammunition.
+///
+/// Parts only send this for actions they handle, e.g. engines do not for
+/// `"fire_weapon"` actions.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartActionOutcome {
+    /// The [PartAction::trace_id] of the action.
+    pub trace_id: u64,
+
+    /// The part which handled the action.
+    pub part: Entity,
+
+    /// What came of it.
+    pub result: PartActionResult,
+}
+
 /// An action request that a construct should dispatch to its parts.
 #[derive(Event)]
 pub struct PartActionDispatchRequest {
@@ -116,7 +234,7 @@ pub fn ev_dispatch_part_actions(
     mut all_events: EventReader<PartActionDispatchRequest>,
     list_parts_query: Query<&ConstructParts>,
     part_info_query: Query<&PartInfo>,
-) {
+) -> Result {
     for construct_event in all_events.read() {
         let target = construct_event.construct_ref;
         let action = &construct_event.action;
@@ -126,7 +244,9 @@ pub fn ev_dispatch_part_actions(
         );
         if let Ok(parts) = list_parts_query.get(target) {
             for &part_id in parts.iter() {
-                let part_info = part_info_query.get(part_id).unwrap();
+                let part_info = part_info_query
+                    .get(part_id)
+                    .context("dispatching a part action")?;
                 // If the part tag selector is empty, skip matching check
                 if !construct_event.part_tag_selectors.is_empty() {
                     // Skip parts that don't match any part tag selector
@@ -151,6 +271,8 @@ pub fn ev_dispatch_part_actions(
             }
         }
     }
+
+    Ok(())
 }
 
 pub fn dispatch_action(
@@ -158,7 +280,7 @@ pub fn dispatch_action(
     construct_ref: Entity,
     action_tag: String,
     part_tag_selectors: Vec<String>,
-    data: Box<dyn Reflect>,
+    data: impl Into<PartActionData>,
 ) {
     debug!("Action dispatch requested: {}", action_tag);
     fn _inner(
@@ -178,13 +300,59 @@ pub fn dispatch_action(
             part_tag_selectors,
             PartAction {
                 action_tag,
-                data: Arc::from(data),
+                data: data.into(),
                 trace_id: rand::random(),
             },
         ),
     );
 }
 
+/// Data of a `"thrust"` action, e.g. for engines.
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThrustCommand {
+    /// How much thrust to apply, between -1.0 (full reverse) and 1.0 (full
+    /// ahead).
+    pub throttle: f32,
+}
+
+/// Data of a `"steer"` action, e.g. for rudders.
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SteerCommand {
+    /// How hard to turn, between -1.0 (fully clockwise, i.e. to starboard)
+    /// and 1.0 (fully counter-clockwise, i.e. to port).
+    pub rudder: f32,
+}
+
+/// Data of a `"fire_weapon"` action.
+#[derive(Reflect, Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FireWeaponCommand {
+    /// The world space position to shoot at.
+    ///
+    /// Weapons work out the power and angle to fire at from this, as far as
+    /// they are able to.
+    pub aim_point: Vec3,
+
+    /// Selectors of which ammunition to fire, tried in order; any
+    /// ammunition the weapon is compatible with if empty.
+    ///
+    /// Selectors name ammunition and its modifiers, and may cascade with
+    /// fallbacks. For example,
+    /// ```text
+    /// [
+    ///   "cannonball 40mm incendiary",
+    ///   "cannonball 40mm propeller_gum",
+    ///   "cannonball 40mm",
+    /// ]
+    /// ```
+    /// tries to find 40mm cannonballs with the incendiary modifier first,
+    /// then with propeller gum, and fires a plain round if neither is found.
+    /// Ammunition that is incompatible is ignored (e.g. cannon and
+    /// cannonball with mismatching calibers).
+    ///
+    /// See [ShipMakeup::take_ammo](crate::common::makeup::ShipMakeup::take_ammo).
+    pub ammo: Vec<String>,
+}
+
 #[derive(Reflect, Default, Debug, Clone)]
 pub struct DebugPrintPart {
     extra_message: Option<String>,
@@ -199,13 +367,54 @@ impl DebugPrintPart {
 }
 
 // Observer
-pub fn obs_debug_part_action(trigger: Trigger<PartAction>, query: Query<&PartInfo>) {
-    let part_info = query.get(trigger.target()).unwrap();
-    if let Some(data) = trigger.data.as_reflect().downcast_ref::<DebugPrintPart>() {
+pub fn obs_debug_part_action(
+    trigger: Trigger<PartAction>,
+    query: Query<&PartInfo>,
+    registry: Res<AppTypeRegistry>,
+) -> Result {
+    let part_info = query
+        .get(trigger.target())
+        .context("printing a debug part action")?;
+    if let Some(data) = trigger.data.reflected::<DebugPrintPart>(&registry.read()) {
         info!(
             "Part with tags {:?} received debug action: {}",
             part_info.tags,
             data.extra_message.clone().unwrap_or("".to_owned())
         );
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn reflected_data_survives_serialization() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<DebugPrintPart>();
+        registry.register::<SteerCommand>();
+
+        let action = PartAction {
+            action_tag: "DEBUG".into(),
+            trace_id: 1,
+            data: PartActionData::reflect(&DebugPrintPart::with_message("Ahoy!"), &registry)
+                .unwrap(),
+        };
+
+        let json = serde_json::to_string(&action).unwrap();
+        let action: PartAction = serde_json::from_str(&json).unwrap();
+
+        let data = action.data.reflected::<DebugPrintPart>(&registry).unwrap();
+        assert_eq!(data.extra_message.as_deref(), Some("Ahoy!"));
+
+        // Only as the type it was passed as.
+        assert!(action.data.reflected::<SteerCommand>(&registry).is_none());
+        assert!(
+            PartActionData::from(SteerCommand { rudder: 1.0 })
+                .reflected::<SteerCommand>(&registry)
+                .is_none()
+        );
+    }
 }
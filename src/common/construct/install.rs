@@ -13,30 +13,180 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use bevy::ecs::{
-    entity::Entity,
-    event::Event,
-    observer::Trigger,
-    system::{Commands, Query},
+use bevy::{
+    ecs::{
+        entity::Entity,
+        error::Result,
+        event::{Event, EventWriter},
+        hierarchy::{ChildOf, Children},
+        observer::Trigger,
+        query::{Has, With},
+        system::{Commands, Query, SystemParam},
+    },
+    log::debug,
 };
 
-use crate::common::construct::{
-    part::PartInstalledOn,
-    slot::{ConstructSlots, PartInfo, PartSlotInfo, SlotOfConstruct},
+use crate::common::{
+    construct::{
+        part::PartInstalledOn,
+        slot::{ConstructSlots, PartInfo, PartSlotInfo, SlotOfConstruct},
+    },
+    error::{Context, LnrError},
 };
 
+/// Why a part could not be installed or uninstalled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartInstallErrorReason {
+    /// The part entity does not exist, or has no [PartInfo].
+    NotAPart,
+
+    /// The slot entity does not exist, or has no [PartSlotInfo].
+    NotASlot,
+
+    /// The slot does not belong to any construct.
+    NoConstruct,
+
+    /// The part is already installed on a construct.
+    AlreadyInstalled,
+
+    /// The part is not installed on any construct.
+    NotInstalled,
+
+    /// None of the part's tags match the slot's type.
+    TagMismatch {
+        /// The type of the slot.
+        slot_type: String,
+    },
+
+    /// Another part is already installed on the slot.
+    SlotOccupied,
+
+    /// The construct has no vacant slot matching the part.
+    NoVacantSlot,
+}
+
+/// Sent when a request to install or uninstall a part could not be carried
+/// out, e.g. for the UI to tell the player why.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct PartInstallError {
+    /// The part that was to be installed or uninstalled.
+    pub part: Entity,
+
+    /// The slot or construct the part was to be installed on, if any.
+    pub target: Option<Entity>,
+
+    /// Why it could not be.
+    pub reason: PartInstallErrorReason,
+}
+
+/// Checks whether parts can be installed, without installing them.
+///
+/// Used by the install observers themselves, and e.g. by the UI to validate
+/// a part before it is dropped onto a slot.
+#[derive(SystemParam)]
+pub struct PartInstallQuery<'w, 's> {
+    parts: Query<'w, 's, (&'static PartInfo, Has<PartInstalledOn>)>,
+    slots: Query<
+        'w,
+        's,
+        (
+            &'static PartSlotInfo,
+            Option<&'static SlotOfConstruct>,
+            Option<&'static Children>,
+        ),
+    >,
+    constructs: Query<'w, 's, &'static ConstructSlots>,
+}
+
+impl PartInstallQuery<'_, '_> {
+    /// Whether a part can be installed on a slot, and why not if it cannot.
+    pub fn can_install(&self, part: Entity, slot: Entity) -> Result<(), PartInstallErrorReason> {
+        self.check_install(part, slot).map(|_| ())
+    }
+
+    /// Whether a slot has no part installed on it.
+    pub fn is_vacant(&self, slot: Entity) -> bool {
+        self.slots
+            .get(slot)
+            .ok()
+            .and_then(|(_, _, children)| children)
+            .is_none_or(|children| {
+                !children
+                    .into_iter()
+                    .any(|&child| self.parts.contains(child))
+            })
+    }
+
+    /// The first vacant slot of a construct a part can be installed on.
+    pub fn vacant_slot(
+        &self,
+        part: Entity,
+        construct: Entity,
+    ) -> Result<Entity, PartInstallErrorReason> {
+        let (_, installed) = self
+            .parts
+            .get(part)
+            .map_err(|_| PartInstallErrorReason::NotAPart)?;
+        if installed {
+            return Err(PartInstallErrorReason::AlreadyInstalled);
+        }
+
+        self.constructs
+            .get(construct)
+            .ok()
+            .and_then(|slots| {
+                slots
+                    .iter()
+                    .copied()
+                    .find(|&slot| self.can_install(part, slot).is_ok())
+            })
+            .ok_or(PartInstallErrorReason::NoVacantSlot)
+    }
+
+    /// Checks whether a part can be installed on a slot, returning the
+    /// construct it would be installed on.
+    fn check_install(&self, part: Entity, slot: Entity) -> Result<Entity, PartInstallErrorReason> {
+        let (part_info, installed) = self
+            .parts
+            .get(part)
+            .map_err(|_| PartInstallErrorReason::NotAPart)?;
+        if installed {
+            return Err(PartInstallErrorReason::AlreadyInstalled);
+        }
+
+        let (slot_info, construct, _) = self
+            .slots
+            .get(slot)
+            .map_err(|_| PartInstallErrorReason::NotASlot)?;
+        let construct = construct.ok_or(PartInstallErrorReason::NoConstruct)?;
+
+        if !part_info.tags.contains(&slot_info.slot_type) {
+            return Err(PartInstallErrorReason::TagMismatch {
+                slot_type: slot_info.slot_type.clone(),
+            });
+        }
+
+        if !self.is_vacant(slot) {
+            return Err(PartInstallErrorReason::SlotOccupied);
+        }
+
+        Ok(construct.get())
+    }
+}
+
 /// Event request to install a part onto a Construct on a givne slot.
 ///
 /// This event must be targeted on the part.
 ///
-/// May panic if the part is already installed to a construct or the slot
-/// does not match the part.
+/// Sends a [PartInstallError] if the part is already installed to a
+/// construct, or the slot does not match the part or is occupied.
 #[derive(Event)]
 pub struct TryInstallPartOnSlot {
     /// Which slot to install this part onto.
     ///
     /// The referred to entity must have a [`PartSlotInfo`], and must have a
-    /// [`Parent`] - the construct onto which the part should be installed.
+    /// [`SlotOfConstruct`] - the construct onto which the part should be
+    /// installed.
     which_slot: Entity,
 }
 
@@ -49,38 +199,24 @@ impl TryInstallPartOnSlot {
 pub fn ev_try_install_part_on_slot(
     trigger: Trigger<TryInstallPartOnSlot>,
     mut commands: Commands,
-    parent_query: Query<&SlotOfConstruct>,
-    installation_query: Query<&PartInstalledOn>,
-    part_query: Query<&PartInfo>,
-    slot_query: Query<&PartSlotInfo>,
+    installs: PartInstallQuery,
+    mut errors: EventWriter<PartInstallError>,
 ) {
     let part_id = trigger.target();
-    assert!(!installation_query.contains(part_id));
-
-    let event = trigger.event();
-    let slot_id = event.which_slot;
+    let slot_id = trigger.event().which_slot;
 
-    let construct_id = match parent_query.get(slot_id) {
-        Err(slot_query_err) => {
-            panic!(
-                "TryInstallPart triggered for a part slot with no or corrupted construct reference: {}",
-                slot_query_err
-            );
+    let construct_id = match installs.check_install(part_id, slot_id) {
+        Ok(construct_id) => construct_id,
+        Err(reason) => {
+            debug!("Could not install part {part_id:?} on slot {slot_id:?}: {reason:?}");
+            errors.write(PartInstallError {
+                part: part_id,
+                target: Some(slot_id),
+                reason,
+            });
+            return;
         }
-        Ok(child_of) => child_of.get(),
     };
-    let part_info = part_query.get(part_id).unwrap();
-    let slot_info = slot_query.get(slot_id).unwrap();
-
-    if !part_info.tags.contains(&slot_info.slot_type) {
-        panic!(
-            "Tried to install part {:?} (with tags [{}]) onto slot {:?} (of type {})",
-            part_id,
-            part_info.tags.join(", "),
-            slot_id,
-            slot_info.slot_type
-        );
-    }
 
     {
         commands
@@ -100,13 +236,14 @@ pub fn ev_try_install_part_on_slot(
 ///
 /// This event must be targeted on the part.
 ///
-/// May panic if the part is already installed to a construct or there are no
-/// vacant matching slots on the referred to construct.
+/// Sends a [PartInstallError] if the part is already installed to a
+/// construct or there are no vacant matching slots on the referred to
+/// construct.
 #[derive(Event)]
 pub struct TryInstallPartOnConstruct {
     /// Which construct to install this part onto.
     ///
-    /// The referred to entity must have at least one child entity with
+    /// The referred to entity must have at least one slot with
     /// [`PartSlotInfo`] which is compatible with the targeted part and vacant
     /// (lacks children with are parts, i.e. bear [`PartInfo`]).
     which_construct: Entity,
@@ -121,92 +258,104 @@ impl TryInstallPartOnConstruct {
 pub fn ev_try_install_part_on_construct(
     trigger: Trigger<TryInstallPartOnConstruct>,
     mut commands: Commands,
-    installation_query: Query<&PartInstalledOn>,
-    part_query: Query<&PartInfo>,
-    slot_query: Query<&PartSlotInfo>,
-    children_query: Query<&ConstructSlots>,
+    installs: PartInstallQuery,
+    mut errors: EventWriter<PartInstallError>,
 ) {
     let part_id = trigger.target();
-    let mut part = commands.entity(part_id);
-    assert!(!installation_query.contains(part_id));
-    let part_info = part_query.get(part_id).unwrap();
-
-    let event = trigger.event();
-    let construct_id = event.which_construct;
-
-    let available_slot: Option<Entity> = match children_query.get(construct_id) {
-        Ok(children) => children.iter().copied().find(|construct_child| {
-            if let Ok(slot_info) = slot_query.get(*construct_child) {
-                // this is a part slot
-
-                // skip if incompatible
-                if !part_info.tags.contains(&slot_info.slot_type) {
-                    return false;
-                }
-
-                // skip if not vacant
-                children_query
-                    .get(*construct_child)
-                    .map(|slot_children| {
-                        !slot_children
-                            .iter()
-                            .any(|slot_child| part_query.contains(*slot_child))
-                    })
-                    .unwrap_or(true)
-            } else {
-                false
-            }
-        }),
-        _ => None,
-    };
+    let construct_id = trigger.event().which_construct;
 
-    match available_slot {
-        Some(slot_id) => {
-            part.trigger(TryInstallPartOnSlot {
+    match installs.vacant_slot(part_id, construct_id) {
+        Ok(slot_id) => {
+            commands.entity(part_id).trigger(TryInstallPartOnSlot {
                 which_slot: slot_id,
             });
         }
-        None => {
-            panic!(
-                "No available slot found on construct {:?} for part {:?}",
-                construct_id, part_id
-            );
+        Err(reason) => {
+            debug!("Could not install part {part_id:?} on construct {construct_id:?}: {reason:?}");
+            errors.write(PartInstallError {
+                part: part_id,
+                target: Some(construct_id),
+                reason,
+            });
         }
     }
 }
 
 /// Event request to uninstall a Part from its Construct.
 ///
-/// Must be targeted on a part which contains [`PartInstalledOn`].
+/// Must be targeted on a part which contains [`PartInstalledOn`]; sends a
+/// [PartInstallError] otherwise.
 #[derive(Event)]
 pub struct TryUninstallPart;
 
+/// Looks up where parts are installed, to uninstall them.
+#[derive(SystemParam)]
+pub struct PartUninstallQuery<'w, 's> {
+    parts:
+        Query<'w, 's, (Option<&'static PartInstalledOn>, Option<&'static ChildOf>), With<PartInfo>>,
+    slots: Query<'w, 's, Option<&'static SlotOfConstruct>, With<PartSlotInfo>>,
+}
+
 pub fn ev_try_uninstall_part(
     trigger: Trigger<TryUninstallPart>,
     mut commands: Commands,
-    parent_query: Query<&SlotOfConstruct>,
-    part_query: Query<&PartInfo>,
-    slot_query: Query<&PartSlotInfo>,
-    installation_query: Query<&PartInstalledOn>,
-) {
-    let part_id = trigger.target();
-    assert!(part_query.contains(part_id));
+    uninstalls: PartUninstallQuery,
+    mut errors: EventWriter<PartInstallError>,
+) -> Result {
+    try_uninstall_part(trigger.target(), &mut commands, &uninstalls, &mut errors)
+}
 
-    {
-        let mut part = commands.entity(part_id);
-        part.remove::<PartInstalledOn>();
-    }
+/// Uninstalls a part, if it is installed.
+///
+/// Everything is looked up and checked before any command is queued, so
+/// that the part is left installed as it was if anything is amiss.
+fn try_uninstall_part(
+    part_id: Entity,
+    commands: &mut Commands,
+    uninstalls: &PartUninstallQuery,
+    errors: &mut EventWriter<PartInstallError>,
+) -> Result {
+    let (construct_id, slot_id) = match uninstalls.parts.get(part_id) {
+        Ok((Some(installation), slot)) => (installation.get(), slot.map(ChildOf::parent)),
+        result => {
+            let reason = if result.is_err() {
+                PartInstallErrorReason::NotAPart
+            } else {
+                PartInstallErrorReason::NotInstalled
+            };
+            debug!("Could not uninstall part {part_id:?}: {reason:?}");
+            errors.write(PartInstallError {
+                part: part_id,
+                target: None,
+                reason,
+            });
+            return Ok(());
+        }
+    };
 
-    {
-        let slot_id = parent_query.get(part_id).unwrap().get();
-        assert!(slot_query.contains(slot_id));
-        let mut slot = commands.entity(slot_id);
+    let slot_id = slot_id
+        .ok_or_else(|| LnrError::missing_component::<ChildOf>(part_id))
+        .context("uninstalling a part from its slot")?;
+    let slot_construct_id = uninstalls
+        .slots
+        .get(slot_id)
+        .map_err(|_| LnrError::missing_component::<PartSlotInfo>(slot_id))
+        .context("uninstalling a part from its slot")?
+        .ok_or_else(|| LnrError::missing_component::<SlotOfConstruct>(slot_id))
+        .context("uninstalling a part from its construct")?
+        .get();
+    if slot_construct_id != construct_id {
+        return Err(LnrError::invalid_state(format!(
+            "Part {part_id:?} is installed on construct {construct_id:?}, but its slot \
+             {slot_id:?} belongs to {slot_construct_id:?}"
+        ))
+        .into());
+    }
 
-        let construct_id = installation_query.get(part_id).unwrap().get();
-        assert_eq!(parent_query.get(slot_id).unwrap().get(), construct_id);
+    commands.entity(part_id).remove::<PartInstalledOn>();
+    commands.entity(slot_id).remove_children(&[part_id]);
 
-        slot.remove_children(&[part_id]);
-    }
+    Ok(())
 }
 
 /// Request the installation of a part on a slot.
@@ -233,3 +382,161 @@ pub fn install_part_on_construct(commands: &mut Commands, part: Entity, construc
 pub fn uninstall_part(commands: &mut Commands, part: Entity) {
     commands.entity(part).trigger(TryUninstallPart);
 }
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::common::construct::ConstructPlugin;
+
+    use super::*;
+
+    fn last_error(app: &mut App) -> Option<PartInstallErrorReason> {
+        app.world_mut()
+            .resource_mut::<Events<PartInstallError>>()
+            .drain()
+            .last()
+            .map(|error| error.reason)
+    }
+
+    #[test]
+    fn bad_install_requests_send_errors() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin));
+
+        let construct = app.world_mut().spawn_empty().id();
+        let slot = app
+            .world_mut()
+            .spawn((
+                PartSlotInfo {
+                    slot_type: "gun".into(),
+                },
+                SlotOfConstruct::new(construct),
+            ))
+            .id();
+        let gun = app
+            .world_mut()
+            .spawn(PartInfo {
+                tags: vec!["gun".into()],
+            })
+            .id();
+        let sail = app
+            .world_mut()
+            .spawn(PartInfo {
+                tags: vec!["sail".into()],
+            })
+            .id();
+
+        let can_install = |app: &mut App, part, slot| {
+            app.world_mut()
+                .run_system_once(move |installs: PartInstallQuery| installs.can_install(part, slot))
+                .unwrap()
+        };
+        assert_eq!(
+            can_install(&mut app, sail, slot),
+            Err(PartInstallErrorReason::TagMismatch {
+                slot_type: "gun".into()
+            })
+        );
+        assert_eq!(can_install(&mut app, gun, slot), Ok(()));
+
+        install_part_on_slot(&mut app.world_mut().commands(), sail, slot);
+        app.world_mut().flush();
+        assert_eq!(
+            last_error(&mut app),
+            Some(PartInstallErrorReason::TagMismatch {
+                slot_type: "gun".into()
+            })
+        );
+
+        install_part_on_construct(&mut app.world_mut().commands(), gun, construct);
+        app.world_mut().flush();
+        assert_eq!(last_error(&mut app), None);
+        assert_eq!(
+            can_install(&mut app, gun, slot),
+            Err(PartInstallErrorReason::AlreadyInstalled)
+        );
+
+        // The only gun slot is taken.
+        let other_gun = app
+            .world_mut()
+            .spawn(PartInfo {
+                tags: vec!["gun".into()],
+            })
+            .id();
+        install_part_on_slot(&mut app.world_mut().commands(), other_gun, slot);
+        app.world_mut().flush();
+        assert_eq!(
+            last_error(&mut app),
+            Some(PartInstallErrorReason::SlotOccupied)
+        );
+        install_part_on_construct(&mut app.world_mut().commands(), other_gun, construct);
+        app.world_mut().flush();
+        assert_eq!(
+            last_error(&mut app),
+            Some(PartInstallErrorReason::NoVacantSlot)
+        );
+
+        uninstall_part(&mut app.world_mut().commands(), other_gun);
+        app.world_mut().flush();
+        assert_eq!(
+            last_error(&mut app),
+            Some(PartInstallErrorReason::NotInstalled)
+        );
+
+        uninstall_part(&mut app.world_mut().commands(), gun);
+        app.world_mut().flush();
+        assert_eq!(last_error(&mut app), None);
+        assert_eq!(can_install(&mut app, other_gun, slot), Ok(()));
+    }
+
+    #[test]
+    fn failed_uninstalls_leave_parts_installed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin));
+
+        let construct = app.world_mut().spawn_empty().id();
+        let slot = app
+            .world_mut()
+            .spawn((
+                PartSlotInfo {
+                    slot_type: "gun".into(),
+                },
+                SlotOfConstruct::new(construct),
+            ))
+            .id();
+        let gun = app
+            .world_mut()
+            .spawn(PartInfo {
+                tags: vec!["gun".into()],
+            })
+            .id();
+        install_part_on_slot(&mut app.world_mut().commands(), gun, slot);
+        app.world_mut().flush();
+
+        // The slot is moved to another construct, behind the part's back.
+        let other_construct = app.world_mut().spawn_empty().id();
+        app.world_mut()
+            .entity_mut(slot)
+            .insert(SlotOfConstruct::new(other_construct));
+
+        let result = app
+            .world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      uninstalls: PartUninstallQuery,
+                      mut errors: EventWriter<PartInstallError>| {
+                    try_uninstall_part(gun, &mut commands, &uninstalls, &mut errors)
+                },
+            )
+            .unwrap();
+        assert!(result.is_err());
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<PartInstalledOn>(gun).map(PartInstalledOn::get),
+            Some(construct)
+        );
+        assert_eq!(world.get::<ChildOf>(gun).map(ChildOf::parent), Some(slot));
+    }
+}
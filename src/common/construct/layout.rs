@@ -0,0 +1,271 @@
+//! Part layout: where installed parts are, and how they weigh their
+//! construct down.
+//!
+//! Installed parts with a [Transform] are snapped to their slot every tick:
+//! to the point the slot is attached to, plus the slot's offset, turned with
+//! the construct (see [SlotAttachment]). Slots are not children of their
+//! construct, so this is done in world space; slots should keep an identity
+//! [Transform], if any.
+//!
+//! Parts with a [PartMass] add it to the point their slot is attached to, or
+//! spread it over all of the construct's points if the slot is not attached
+//! to any; it is taken off again when they are uninstalled. How much was
+//! added to each point is kept in the construct's [PartMassDistribution].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::{
+    construct::{
+        part::{ConstructParts, PartInstalledOn},
+        slot::SlotAttachment,
+    },
+    error::LnrError,
+    physics::base::PointNetwork,
+};
+
+/// The mass of a part, which weighs its construct down where it is
+/// installed.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct PartMass(pub f32);
+
+/// How much mass of installed parts was last added to each point of a
+/// construct's [PointNetwork].
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct PartMassDistribution {
+    applied: Vec<f32>,
+}
+
+impl PartMassDistribution {
+    /// The mass of installed parts added to a point.
+    pub fn at(&self, point_idx: usize) -> f32 {
+        self.applied.get(point_idx).copied().unwrap_or(0.0)
+    }
+
+    /// The total mass of installed parts.
+    pub fn total(&self) -> f32 {
+        self.applied.iter().sum()
+    }
+}
+
+/// The world space position of a slot's attachment point, or the
+/// construct's center of mass if it is not attached to any.
+fn slot_point(
+    slot: Entity,
+    attachment: Option<&SlotAttachment>,
+    network: &PointNetwork,
+) -> Result<Vec3> {
+    let Some(attachment) = attachment else {
+        return Ok(network.center_of_mass());
+    };
+
+    match network.points.get(attachment.point_idx) {
+        Some(point) => Ok(point.pos),
+        None => Err(LnrError::invalid_state(format!(
+            "slot {} is attached to point {}, but its construct has {} points",
+            slot,
+            attachment.point_idx,
+            network.points.len()
+        ))
+        .into()),
+    }
+}
+
+// Always runs after point_base_physics.
+pub fn snap_parts_to_slots(
+    mut parts: Query<(&PartInstalledOn, &ChildOf, &mut Transform)>,
+    slots: Query<&SlotAttachment>,
+    constructs: Query<(&PointNetwork, Option<&Transform>), Without<PartInstalledOn>>,
+) -> Result {
+    for (installed_on, slot, mut transform) in &mut parts {
+        let Ok((network, construct_transform)) = constructs.get(installed_on.get()) else {
+            continue;
+        };
+
+        let attachment = slots.get(slot.parent()).ok();
+        let rotation = construct_transform.map_or(Quat::IDENTITY, |transform| transform.rotation);
+        let offset = attachment.map_or(Vec3::ZERO, |attachment| attachment.offset);
+        let translation = slot_point(slot.parent(), attachment, network)? + rotation * offset;
+
+        transform.set_if_neq(Transform {
+            translation,
+            rotation,
+            scale: transform.scale,
+        });
+    }
+
+    Ok(())
+}
+
+/// Constructs whose mass may need to be distributed, and what is needed to do
+/// so.
+type MassiveConstruct = (
+    Entity,
+    &'static mut PointNetwork,
+    Option<&'static ConstructParts>,
+    Option<&'static mut PartMassDistribution>,
+);
+
+pub fn distribute_part_mass(
+    mut commands: Commands,
+    mut constructs: Query<MassiveConstruct, Or<(With<ConstructParts>, With<PartMassDistribution>)>>,
+    parts: Query<(&PartMass, &ChildOf)>,
+    slots: Query<&SlotAttachment>,
+) -> Result {
+    for (construct, mut network, installed, distribution) in &mut constructs {
+        let num_points = network.points.len();
+        if num_points == 0 {
+            continue;
+        }
+
+        let mut desired = vec![0.0; num_points];
+        for &part in installed.iter().flat_map(|installed| installed.iter()) {
+            let Ok((mass, slot)) = parts.get(part) else {
+                continue;
+            };
+
+            match slots.get(slot.parent()) {
+                Ok(attachment) => {
+                    let Some(share) = desired.get_mut(attachment.point_idx) else {
+                        return Err(LnrError::invalid_state(format!(
+                            "part {} is attached to point {}, but its construct has {} points",
+                            part, attachment.point_idx, num_points
+                        ))
+                        .into());
+                    };
+                    *share += mass.0;
+                }
+                Err(_) => {
+                    for share in &mut desired {
+                        *share += mass.0 / num_points as f32;
+                    }
+                }
+            }
+        }
+
+        let applied = distribution
+            .as_ref()
+            .map_or(&[][..], |distribution| &distribution.applied[..]);
+        if applied == &desired[..] {
+            continue;
+        }
+
+        for (idx, point) in network.points.iter_mut().enumerate() {
+            point.mass += desired[idx] - applied.get(idx).copied().unwrap_or(0.0);
+        }
+
+        match distribution {
+            Some(mut distribution) => distribution.applied = desired,
+            None => {
+                commands
+                    .entity(construct)
+                    .insert(PartMassDistribution { applied: desired });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::prelude::*;
+
+    use crate::common::{
+        construct::{
+            ConstructPlugin,
+            install::{install_part_on_slot, uninstall_part},
+            slot::{PartInfo, PartSlotInfo, SlotOfConstruct},
+        },
+        physics::base::PhysPoint,
+    };
+
+    use super::*;
+
+    #[test]
+    fn parts_follow_and_weigh_down_their_slots() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin));
+
+        let construct = app
+            .world_mut()
+            .spawn((
+                PointNetwork::from(
+                    [
+                        PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 5.0),
+                        PhysPoint::new(Vec3::X * 4.0, Vec3::ZERO, 5.0),
+                    ]
+                    .into_iter(),
+                ),
+                Transform::from_rotation(Quat::from_rotation_y(FRAC_PI_2)),
+            ))
+            .id();
+        let slot = app
+            .world_mut()
+            .spawn((
+                PartSlotInfo {
+                    slot_type: "gun".into(),
+                },
+                SlotAttachment {
+                    point_idx: 1,
+                    offset: Vec3::X,
+                },
+                SlotOfConstruct::new(construct),
+                Transform::default(),
+            ))
+            .id();
+        let part = app
+            .world_mut()
+            .spawn((
+                PartInfo {
+                    tags: vec!["gun".into()],
+                },
+                PartMass(2.0),
+                Transform::default(),
+            ))
+            .id();
+
+        install_part_on_slot(&mut app.world_mut().commands(), part, slot);
+        app.world_mut().flush();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        // At the slot's point, offset as turned with the construct.
+        let transform = app.world().get::<Transform>(part).unwrap();
+        assert!(
+            transform
+                .translation
+                .abs_diff_eq(Vec3::new(4.0, 0.0, -1.0), 1e-5),
+            "{}",
+            transform.translation
+        );
+
+        let masses = |app: &App| {
+            let network = app.world().get::<PointNetwork>(construct).unwrap();
+            [network.points[0].mass, network.points[1].mass]
+        };
+        assert_eq!(masses(&app), [5.0, 7.0]);
+
+        // Only added once.
+        app.world_mut().run_schedule(FixedUpdate);
+        assert_eq!(masses(&app), [5.0, 7.0]);
+
+        uninstall_part(&mut app.world_mut().commands(), part);
+        app.world_mut().flush();
+        app.world_mut().run_schedule(FixedUpdate);
+        assert_eq!(masses(&app), [5.0, 5.0]);
+    }
+}
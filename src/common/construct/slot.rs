@@ -15,7 +15,10 @@
 
 use std::ops::Deref;
 
-use bevy::ecs::{component::Component, entity::Entity};
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    math::Vec3,
+};
 
 /// Refers to a construct entity, of which this one is a part slot.
 ///
@@ -71,6 +74,22 @@ pub struct PartSlotInfo {
     pub slot_type: String,
 }
 
+/// Which point of its construct's [PointNetwork] a part slot is attached to.
+///
+/// Parts which act on the construct physically, such as engines, do so at
+/// this point. Slots without it act on the construct as a whole.
+///
+/// [PointNetwork]: crate::common::physics::base::PointNetwork
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SlotAttachment {
+    /// The index of the physics point on the construct's point network.
+    pub point_idx: usize,
+
+    /// Where parts installed on this slot are, relative to the point, in the
+    /// construct's space.
+    pub offset: Vec3,
+}
+
 /// A part which can be installed on a construct via one of its [`PartSlot`]s.
 #[derive(Component)]
 pub struct PartInfo {
@@ -0,0 +1,361 @@
+//! # Crew
+//!
+//! Constructs can have a [Crew], who man their parts. Parts which need
+//! manning say so with a [Manning] component; every tick, crew members are
+//! assigned to the parts of their construct, in order, until they run out.
+//! Parts left without enough crew are marked [Unmanned], and refuse
+//! `"fire_weapon"` and `"thrust"` actions.
+//!
+//! Crew are hired with [hire_crew], and may fall when their construct is
+//! damaged. They also need to eat; hungry crew are weaker, and slower to
+//! reload weapons (see [hunger]).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    construct::part::ConstructParts, damage::DamageEvent, inventory::ManningType,
+    physics::determinism::PhysicsRng,
+};
+
+pub mod hunger; // Crew eating food, and going hungry without it
+
+/// How much damage a construct must take for one of its crew to surely fall.
+const CASUALTY_DAMAGE: f32 = 50.0;
+
+/// A member of a construct's crew.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrewMember {
+    /// The name of this crew member.
+    pub name: String,
+
+    /// How strong this crew member is, for parts which need a certain
+    /// strength to man (see [ManningType::StrengthManned]).
+    pub strength: u8,
+
+    /// How skilled this crew member is. The most skilled are picked first
+    /// for parts which need any crew.
+    pub skill: u8,
+
+    /// The part this crew member mans, if any.
+    pub station: Option<Entity>,
+
+    /// How hungry this crew member is, between 0.0 (fed) and 1.0 (starving).
+    ///
+    /// See [hunger].
+    pub hunger: f32,
+}
+
+impl CrewMember {
+    /// A new crew member, not manning anything yet.
+    pub fn new(name: impl Into<String>, strength: u8, skill: u8) -> Self {
+        Self {
+            name: name.into(),
+            strength,
+            skill,
+            station: None,
+            hunger: 0.0,
+        }
+    }
+
+    /// How well this crew member performs, between 0.5 (starving) and 1.0
+    /// (fed).
+    pub fn efficiency(&self) -> f32 {
+        1.0 - self.hunger.clamp(0.0, 1.0) * 0.5
+    }
+
+    /// How strong this crew member effectively is, after hunger.
+    pub fn effective_strength(&self) -> u32 {
+        (self.strength as f32 * self.efficiency()) as u32
+    }
+}
+
+/// The crew of a construct.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Crew {
+    /// Everyone on board.
+    pub members: Vec<CrewMember>,
+
+    /// Time left until the next meal, in seconds.
+    pub until_meal: f32,
+}
+
+impl Crew {
+    /// How well the crew manning a part perform, between 0.5 and 1.0.
+    ///
+    /// Parts nobody mans are not affected by hunger, so this is 1.0 for them.
+    pub fn efficiency_at(&self, part: Entity) -> f32 {
+        let (sum, count) = self
+            .members
+            .iter()
+            .filter(|member| member.station == Some(part))
+            .fold((0.0, 0), |(sum, count), member| {
+                (sum + member.efficiency(), count + 1)
+            });
+
+        if count == 0 { 1.0 } else { sum / count as f32 }
+    }
+}
+
+/// How a part needs to be manned.
+///
+/// Parts without this need no crew.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Manning(pub ManningType);
+
+/// Marks parts which lack the crew to man them.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Unmanned;
+
+/// Request to hire a crew member onto a construct.
+///
+/// This event must be targeted on the construct.
+#[derive(Event, Clone, Debug)]
+pub struct HireCrew {
+    /// The crew member to hire.
+    pub member: CrewMember,
+}
+
+/// Sent whenever a crew member is hired.
+#[derive(Event, Clone, Debug)]
+pub struct CrewHired {
+    /// The construct which hired the crew member.
+    pub construct: Entity,
+
+    /// The name of the crew member.
+    pub name: String,
+}
+
+/// Sent whenever a crew member falls.
+#[derive(Event, Clone, Debug)]
+pub struct CrewCasualty {
+    /// The construct the crew member was on.
+    pub construct: Entity,
+
+    /// The fallen crew member.
+    pub member: CrewMember,
+}
+
+/// Request the hiring of a crew member onto a construct.
+///
+/// Wraps around [HireCrew].
+pub fn hire_crew(commands: &mut Commands, construct: Entity, member: CrewMember) {
+    commands.entity(construct).trigger(HireCrew { member });
+}
+
+// Observer
+pub fn obs_hire_crew(
+    trigger: Trigger<HireCrew>,
+    mut commands: Commands,
+    mut crews: Query<&mut Crew>,
+    mut hired: EventWriter<CrewHired>,
+) {
+    let construct = trigger.target();
+    let mut member = trigger.member.clone();
+    member.station = None;
+
+    hired.write(CrewHired {
+        construct,
+        name: member.name.clone(),
+    });
+
+    match crews.get_mut(construct) {
+        Ok(mut crew) => crew.members.push(member),
+        Err(_) => {
+            commands.entity(construct).insert(Crew {
+                members: vec![member],
+                until_meal: hunger::MEAL_INTERVAL,
+            });
+        }
+    }
+}
+
+/// Assigns free crew members to man a part, if there are enough of them.
+///
+/// Returns whether the part is manned.
+fn man_part(members: &mut [CrewMember], part: Entity, manning: ManningType) -> bool {
+    let mut free = members
+        .iter_mut()
+        .filter(|member| member.station.is_none())
+        .collect::<Vec<_>>();
+
+    match manning {
+        ManningType::Unmanned => true,
+
+        ManningType::AnyManned => {
+            let Some(member) = free.into_iter().max_by_key(|member| member.skill) else {
+                return false;
+            };
+
+            member.station = Some(part);
+            true
+        }
+
+        ManningType::StrengthManned(needed) => {
+            free.sort_by_key(|member| std::cmp::Reverse(member.effective_strength()));
+
+            let mut strength = 0;
+            let enough = free.iter().position(|member| {
+                strength += member.effective_strength();
+                strength >= needed as u32
+            });
+
+            let Some(last) = enough else {
+                return false;
+            };
+
+            for member in &mut free[..=last] {
+                member.station = Some(part);
+            }
+            true
+        }
+    }
+}
+
+fn assign_crew(
+    mut commands: Commands,
+    mut constructs: Query<(&ConstructParts, Option<&mut Crew>)>,
+    parts: Query<(&Manning, Has<Unmanned>)>,
+) {
+    for (construct_parts, mut crew) in &mut constructs {
+        let members = crew
+            .as_mut()
+            .map_or(&mut [][..], |crew| &mut crew.members[..]);
+
+        for member in members.iter_mut() {
+            member.station = None;
+        }
+
+        for &part in construct_parts.iter() {
+            let Ok((manning, was_unmanned)) = parts.get(part) else {
+                continue;
+            };
+
+            let manned = man_part(members, part, manning.0);
+
+            if manned && was_unmanned {
+                commands.entity(part).remove::<Unmanned>();
+            } else if !manned && !was_unmanned {
+                commands.entity(part).insert(Unmanned);
+            }
+        }
+    }
+}
+
+fn crew_casualties(
+    mut damage: EventReader<DamageEvent>,
+    mut crews: Query<&mut Crew>,
+    mut casualties: EventWriter<CrewCasualty>,
+    mut rng: ResMut<PhysicsRng>,
+) {
+    let rng = &mut rng.0;
+
+    for event in damage.read() {
+        let Ok(mut crew) = crews.get_mut(event.target) else {
+            continue;
+        };
+
+        if crew.members.is_empty()
+            || !rng.random_bool((event.amount / CASUALTY_DAMAGE).clamp(0.0, 1.0) as f64)
+        {
+            continue;
+        }
+
+        let fallen = rng.random_range(0..crew.members.len());
+        casualties.write(CrewCasualty {
+            construct: event.target,
+            member: crew.members.remove(fallen),
+        });
+    }
+}
+
+/// Crew plugin.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct CrewPlugin;
+
+impl Plugin for CrewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CrewHired>();
+        app.add_event::<CrewCasualty>();
+        app.init_resource::<PhysicsRng>();
+        app.add_observer(obs_hire_crew);
+        app.add_event::<hunger::StarvationEvent>();
+        app.add_systems(
+            FixedUpdate,
+            (crew_casualties, hunger::feed_crew, assign_crew).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        construct::{ConstructPlugin, part::PartInstalledOn},
+        inventory::ManningType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn crew_man_parts_in_order() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin, CrewPlugin));
+        app.add_event::<DamageEvent>();
+
+        let construct = app.world_mut().spawn_empty().id();
+        let mut part = |manning: ManningType| {
+            app.world_mut()
+                .spawn((Manning(manning), PartInstalledOn::new(construct)))
+                .id()
+        };
+        let heavy = part(ManningType::StrengthManned(10));
+        let light = part(ManningType::AnyManned);
+        let spare = part(ManningType::AnyManned);
+        let free = part(ManningType::Unmanned);
+
+        let unmanned = |app: &mut App| {
+            app.world_mut().run_schedule(FixedUpdate);
+            [heavy, light, spare, free].map(|part| app.world().get::<Unmanned>(part).is_some())
+        };
+
+        // Nobody aboard.
+        assert_eq!(unmanned(&mut app), [true, true, true, false]);
+
+        // Two strong sailors together man the heavy part, and the skilled
+        // one mans the next.
+        for member in [
+            CrewMember::new("Ada", 6, 1),
+            CrewMember::new("Bo", 1, 9),
+            CrewMember::new("Cy", 5, 2),
+        ] {
+            let mut commands = app.world_mut().commands();
+            hire_crew(&mut commands, construct, member);
+        }
+        app.world_mut().flush();
+        assert_eq!(unmanned(&mut app), [false, false, true, false]);
+
+        let crew = app.world().get::<Crew>(construct).unwrap();
+        let stations = crew
+            .members
+            .iter()
+            .map(|member| member.station)
+            .collect::<Vec<_>>();
+        assert_eq!(stations, [Some(heavy), Some(light), Some(heavy)]);
+    }
+}
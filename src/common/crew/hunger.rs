@@ -0,0 +1,152 @@
+//! # Hunger
+//!
+//! Crew eat at regular intervals, each taking a meal's worth of food points
+//! out of their ship's inventory (see
+//! [FoodDef](crate::common::inventory::FoodDef)). Crew who go without get
+//! hungrier, and so weaker at manning parts and slower at reloading weapons;
+//! crew who eat recover. Whenever there is not enough food to go around, a
+//! [StarvationEvent] is sent.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::Crew;
+use crate::common::makeup::Ship;
+
+/// How often crew eat, in seconds.
+pub const MEAL_INTERVAL: f32 = 60.0;
+
+/// How many food points each crew member eats per meal.
+pub const FOOD_PER_MEAL: f32 = 10.0;
+
+/// How much hungrier crew get per missed meal.
+const HUNGER_PER_MISSED_MEAL: f32 = 0.25;
+
+/// How much hunger crew recover from per full meal.
+const RECOVERY_PER_MEAL: f32 = 0.5;
+
+/// Sent whenever a construct does not have enough food for its crew.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StarvationEvent {
+    /// The construct running out of food.
+    pub construct: Entity,
+
+    /// How many food points the crew went without.
+    pub shortfall: f32,
+}
+
+pub fn feed_crew(
+    time: Res<Time>,
+    mut crews: Query<(Entity, &mut Crew, Option<&mut Ship>)>,
+    mut starvation: EventWriter<StarvationEvent>,
+) {
+    for (construct, mut crew, ship) in &mut crews {
+        crew.until_meal -= time.delta_secs();
+
+        if crew.until_meal > 0.0 {
+            continue;
+        }
+        crew.until_meal += MEAL_INTERVAL;
+
+        if crew.members.is_empty() {
+            continue;
+        }
+
+        let needed = FOOD_PER_MEAL * crew.members.len() as f32;
+        let eaten = ship.map_or(0.0, |mut ship| ship.makeup.take_food(needed));
+        let fed = eaten / needed;
+
+        for member in &mut crew.members {
+            member.hunger = (member.hunger + HUNGER_PER_MISSED_MEAL * (1.0 - fed)
+                - RECOVERY_PER_MEAL * fed)
+                .clamp(0.0, 1.0);
+        }
+
+        if eaten < needed {
+            starvation.write(StarvationEvent {
+                construct,
+                shortfall: needed - eaten,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use crate::common::{
+        crew::{CrewMember, CrewPlugin},
+        damage::DamageEvent,
+        fixtures::{item, makeup},
+        inventory::{FoodDef, ItemType},
+        makeup::Ship,
+    };
+
+    use super::*;
+
+    #[test]
+    fn crew_go_hungry_when_food_runs_out() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CrewPlugin));
+        app.add_event::<DamageEvent>();
+
+        // Enough biscuits for a meal and a quarter, for a crew of two.
+        let mut makeup = makeup(vec![]);
+        makeup.add_item(item(
+            "biscuits",
+            ItemType::Food(FoodDef { food_points: 5 }),
+            0.1,
+            0.1,
+            5.0,
+        ));
+
+        let construct = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                Crew {
+                    members: vec![CrewMember::new("Ada", 5, 5), CrewMember::new("Bo", 5, 5)],
+                    until_meal: 0.0,
+                },
+            ))
+            .id();
+
+        let meal = |app: &mut App| {
+            app.world_mut().run_schedule(FixedUpdate);
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(MEAL_INTERVAL));
+
+            let events = app.world().resource::<Events<StarvationEvent>>();
+            let starved = events.iter_current_update_events().count();
+            app.world_mut()
+                .resource_mut::<Events<StarvationEvent>>()
+                .clear();
+
+            let crew = app.world().get::<Crew>(construct).unwrap();
+            (starved, crew.members[0].hunger)
+        };
+
+        assert_eq!(meal(&mut app), (0, 0.0));
+        assert_eq!(meal(&mut app), (1, 0.0625));
+        assert_eq!(meal(&mut app), (1, 0.3125));
+
+        let crew = app.world().get::<Crew>(construct).unwrap();
+        assert!(crew.members[0].effective_strength() < 5);
+    }
+}
@@ -0,0 +1,537 @@
+//! # Damage and health
+//!
+//! Constructs and their parts can have [Health], which is lowered by
+//! [DamageEvent]s. Damage comes from:
+//!
+//! * projectiles with [ImpactDamage], such as cannonballs, whenever they hit
+//!   anything, after which they are despawned; a [ProjectileHit] event is
+//!   sent as well;
+//! * explosions, such as naval mines detonating, to everything around them,
+//!   less the further away it is.
+//!
+//! Damage to a construct is reduced by its [ArmorPart]s installed near where
+//! it was hit, which may deflect projectiles that hit at a glancing angle,
+//! and wear down from the damage they absorb. The rest is dealt to the construct as well as to its part
+//! nearest to where it was hit, if any is close enough.
+//!
+//! Parts are destroyed when their health runs out: they are uninstalled, a
+//! [PartDestroyed] event is sent, and they are despawned on the next tick,
+//! unless they were dropped as loot (see [DropChance]).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{platform::collections::HashSet, prelude::*};
+use rand::Rng;
+
+use super::{
+    construct::{
+        install::uninstall_part,
+        part::{ConstructParts, PartInstalledOn},
+        slot::SlotAttachment,
+    },
+    inventory::modifier::ProjectileModifiers,
+    makeup::parts::{armor::ArmorPart, attachment_point, minelayer::MineDetonated},
+    physics::{
+        base::PointNetwork, collision::VolumeVolumeCollisionDetectionEvent,
+        determinism::PhysicsRng, projectile::FastProjectile,
+    },
+};
+
+/// Damage dealt per unit of kinetic energy, by [ImpactDamage] projectiles.
+const DAMAGE_PER_ENERGY: f32 = 0.02;
+
+/// How far from where a construct is hit its parts can be damaged as well.
+const PART_HIT_RADIUS: f32 = 2.0;
+
+/// How far from where it is installed armor protects a construct.
+const ARMOR_COVER_RADIUS: f32 = 4.0;
+
+/// The health of a construct or part.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Health {
+    /// Health left.
+    pub current: f32,
+
+    /// Health when fully repaired.
+    pub max: f32,
+}
+
+impl Health {
+    /// Full health.
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Health left, between 0.0 and 1.0.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            (self.current / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether there is no health left.
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+/// The kind of damage dealt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageType {
+    /// Hit by a projectile.
+    Impact,
+
+    /// Caught in an explosion.
+    Explosion,
+
+    /// Burning, e.g. after being hit by an incendiary projectile.
+    Fire,
+}
+
+/// Deals damage to a construct or part.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    /// The object which dealt the damage, if any, e.g. the ship which fired
+    /// a cannonball.
+    pub source: Option<Entity>,
+
+    /// The damaged object.
+    pub target: Entity,
+
+    /// How much damage, before armor.
+    pub amount: f32,
+
+    /// The kind of damage.
+    pub damage_type: DamageType,
+
+    /// Where the target was hit, in world space.
+    pub at: Vec3,
+
+    /// Which way the damage was headed, e.g. a projectile's velocity, if it
+    /// came from any particular way.
+    ///
+    /// Armor can only deflect damage which does (see [ArmorPart]).
+    pub direction: Option<Vec3>,
+}
+
+/// Sent whenever a projectile with [ImpactDamage] hits something, e.g. for
+/// its [ProjectileModifiers] to act on it.
+#[derive(Event, Clone, Debug)]
+pub struct ProjectileHit {
+    /// The object which fired the projectile, if any.
+    pub source: Option<Entity>,
+
+    /// The object hit.
+    pub target: Entity,
+
+    /// Where it was hit, in world space.
+    pub at: Vec3,
+
+    /// How much damage the hit dealt, before armor.
+    pub damage: f32,
+
+    /// The modifiers of the projectile.
+    pub modifiers: ProjectileModifiers,
+}
+
+/// Projectiles which deal damage to whatever they hit, and are then
+/// despawned.
+///
+/// Requires [FastProjectile].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ImpactDamage {
+    /// How much damage the projectile deals.
+    pub amount: f32,
+}
+
+impl ImpactDamage {
+    /// Damage from the kinetic energy of a projectile, as launched.
+    pub fn from_kinetic_energy(mass: f32, velocity: Vec3) -> Self {
+        Self {
+            amount: 0.5 * mass * velocity.length_squared() * DAMAGE_PER_ENERGY,
+        }
+    }
+}
+
+/// Chance, in percent, that a destroyed part is dropped as loot, rather than
+/// lost.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DropChance(pub u8);
+
+/// Sent whenever a part is destroyed.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PartDestroyed {
+    /// The destroyed part.
+    pub part: Entity,
+
+    /// The construct the part was installed on.
+    pub construct: Entity,
+
+    /// Where the part was, in world space.
+    pub at: Vec3,
+
+    /// Whether the part is dropped as loot.
+    ///
+    /// If so, it is up to whoever drops it to despawn it, or make something
+    /// out of it; otherwise, it is despawned on the next tick.
+    pub dropped: bool,
+}
+
+/// Marks a destroyed part.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Wrecked {
+    /// Whether the part is dropped as loot.
+    pub dropped: bool,
+}
+
+/// The radius of an explosion, from its power.
+pub fn explosion_radius(power: f32) -> f32 {
+    power.max(0.0).sqrt()
+}
+
+fn projectile_impact_damage(
+    mut commands: Commands,
+    mut collisions: EventReader<VolumeVolumeCollisionDetectionEvent>,
+    projectiles: Query<(
+        &FastProjectile,
+        &ImpactDamage,
+        &PointNetwork,
+        Option<&ProjectileModifiers>,
+    )>,
+    mut damage: EventWriter<DamageEvent>,
+    mut hits: EventWriter<ProjectileHit>,
+) {
+    let mut spent = HashSet::new();
+
+    for collision in collisions.read() {
+        for (projectile, target) in [
+            (collision.entity_ref, collision.entity_other),
+            (collision.entity_other, collision.entity_ref),
+        ] {
+            let Ok((fast, impact, points, modifiers)) = projectiles.get(projectile) else {
+                continue;
+            };
+
+            // Projectiles only hit once.
+            if !spent.insert(projectile) {
+                continue;
+            }
+
+            let at = points.center_of_mass();
+            let momentum = points
+                .points
+                .iter()
+                .map(|point| point.vel * point.mass)
+                .sum();
+            damage.write(DamageEvent {
+                source: fast.source,
+                target,
+                amount: impact.amount,
+                damage_type: DamageType::Impact,
+                at,
+                direction: Some(momentum),
+            });
+            hits.write(ProjectileHit {
+                source: fast.source,
+                target,
+                at,
+                damage: impact.amount,
+                modifiers: modifiers.cloned().unwrap_or_default(),
+            });
+            commands.entity(projectile).despawn();
+        }
+    }
+}
+
+fn explosion_damage(
+    mut detonations: EventReader<MineDetonated>,
+    targets: Query<(Entity, &PointNetwork), With<Health>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for detonation in detonations.read() {
+        let radius = explosion_radius(detonation.power);
+
+        for (target, points) in &targets {
+            let distance = points.center_of_mass().distance(detonation.at);
+
+            if distance < radius {
+                damage.write(DamageEvent {
+                    source: Some(detonation.source),
+                    target,
+                    amount: detonation.power * (1.0 - distance / radius),
+                    damage_type: DamageType::Explosion,
+                    at: detonation.at,
+                    direction: None,
+                });
+            }
+        }
+    }
+}
+
+fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut health: Query<&mut Health, Without<Wrecked>>,
+    constructs: Query<(&PointNetwork, Option<&ConstructParts>)>,
+    parts: Query<Option<&ChildOf>, (With<Health>, Without<Wrecked>)>,
+    mut armor: Query<(&mut ArmorPart, Option<&ChildOf>), Without<Wrecked>>,
+    slots: Query<&SlotAttachment>,
+    mut rng: ResMut<PhysicsRng>,
+) -> Result {
+    for event in events.read() {
+        let mut amount = event.amount;
+        let mut hit_part = None;
+
+        if let Ok((network, construct_parts)) = constructs.get(event.target) {
+            let installed = construct_parts.iter().flat_map(|parts| parts.iter());
+
+            // The angle between the damage's path and the hull, taken to be
+            // facing away from the construct's center of mass.
+            let normal = (event.at - network.center_of_mass()).normalize_or_zero();
+            let impact_angle = event
+                .direction
+                .filter(|direction| *direction != Vec3::ZERO && normal != Vec3::ZERO)
+                .map(|direction| normal.angle_between(-direction).min(FRAC_PI_2));
+
+            for &part in installed.clone() {
+                let Ok((mut armor, slot)) = armor.get_mut(part) else {
+                    continue;
+                };
+
+                let distance = attachment_point(part, slot, &slots, network)?.distance(event.at);
+                if distance <= ARMOR_COVER_RADIUS {
+                    amount = armor.absorb(amount, impact_angle, &mut rng.0);
+                }
+            }
+
+            // The part nearest to the hit, if close enough, is damaged too.
+            let mut nearest = PART_HIT_RADIUS;
+            for &part in installed {
+                let Ok(slot) = parts.get(part) else {
+                    continue;
+                };
+
+                let distance = attachment_point(part, slot, &slots, network)?.distance(event.at);
+                if distance <= nearest {
+                    nearest = distance;
+                    hit_part = Some(part);
+                }
+            }
+        }
+
+        for target in [Some(event.target), hit_part].into_iter().flatten() {
+            if let Ok(mut health) = health.get_mut(target) {
+                health.current -= amount;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An installed part which may be destroyed, and what is needed to do so.
+type DestructiblePart = (
+    Entity,
+    &'static Health,
+    &'static PartInstalledOn,
+    Option<&'static ChildOf>,
+    Option<&'static DropChance>,
+);
+
+fn destroy_parts(
+    mut commands: Commands,
+    parts: Query<DestructiblePart, Without<Wrecked>>,
+    constructs: Query<&PointNetwork>,
+    slots: Query<&SlotAttachment>,
+    mut destroyed: EventWriter<PartDestroyed>,
+    mut rng: ResMut<PhysicsRng>,
+) -> Result {
+    for (part, health, installed_on, slot, drop_chance) in &parts {
+        if !health.is_depleted() {
+            continue;
+        }
+
+        let construct = installed_on.get();
+        let at = match constructs.get(construct) {
+            Ok(network) => attachment_point(part, slot, &slots, network)?,
+            Err(_) => Vec3::ZERO,
+        };
+        let dropped = drop_chance.is_some_and(|chance| rng.0.random_range(0..100) < chance.0);
+
+        uninstall_part(&mut commands, part);
+        commands.entity(part).insert(Wrecked { dropped });
+        destroyed.write(PartDestroyed {
+            part,
+            construct,
+            at,
+            dropped,
+        });
+    }
+
+    Ok(())
+}
+
+fn despawn_wrecked_parts(
+    mut commands: Commands,
+    wrecks: Query<(Entity, &Wrecked), Without<PartInstalledOn>>,
+) {
+    for (entity, wreck) in &wrecks {
+        if !wreck.dropped {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Damage and health plugin.
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>();
+        app.add_event::<PartDestroyed>();
+        app.add_event::<ProjectileHit>();
+        app.init_resource::<PhysicsRng>();
+        app.add_systems(
+            FixedUpdate,
+            (
+                despawn_wrecked_parts,
+                (projectile_impact_damage, explosion_damage),
+                apply_damage,
+                destroy_parts,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        construct::{
+            ConstructPlugin,
+            part::{ConstructParts, PartInstalledOn},
+            slot::{PartInfo, PartSlotInfo, SlotAttachment, SlotOfConstruct},
+        },
+        inventory::ArmorDef,
+        makeup::parts::armor::ArmorPart,
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    fn install(app: &mut App, construct: Entity, point_idx: usize, part: Entity) {
+        let slot = app
+            .world_mut()
+            .spawn((
+                PartSlotInfo {
+                    slot_type: "any".into(),
+                },
+                SlotAttachment {
+                    point_idx,
+                    offset: Vec3::ZERO,
+                },
+                SlotOfConstruct::new(construct),
+            ))
+            .id();
+        app.world_mut()
+            .entity_mut(part)
+            .insert((PartInstalledOn::new(construct), ChildOf(slot)));
+    }
+
+    #[test]
+    fn armor_mitigates_and_parts_get_destroyed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin, DamagePlugin));
+        app.add_event::<VolumeVolumeCollisionDetectionEvent>();
+        app.add_event::<MineDetonated>();
+
+        let construct = app
+            .world_mut()
+            .spawn((
+                Health::new(100.0),
+                PointNetwork::from(
+                    [
+                        PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 1.0),
+                        PhysPoint::new(Vec3::X * 10.0, Vec3::ZERO, 1.0),
+                    ]
+                    .into_iter(),
+                ),
+            ))
+            .id();
+
+        // Armor at the center, and a fragile part at the far end.
+
+        let armor = app
+            .world_mut()
+            .spawn((
+                PartInfo {
+                    tags: vec!["any".into()],
+                },
+                Health::new(1000.0),
+                ArmorPart::new(ArmorDef {
+                    defense_factor: 100,
+                    wear_factor: 0,
+                    deflect_factor: 0,
+                    overwhelm_factor: 0,
+                    wear: 0.0,
+                }),
+            ))
+            .id();
+        let fragile = app
+            .world_mut()
+            .spawn((
+                PartInfo {
+                    tags: vec!["any".into()],
+                },
+                Health::new(10.0),
+            ))
+            .id();
+        install(&mut app, construct, 0, armor);
+        install(&mut app, construct, 1, fragile);
+
+        let hit = |app: &mut App, at: Vec3| {
+            app.world_mut().send_event(DamageEvent {
+                source: None,
+                target: construct,
+                amount: 30.0,
+                damage_type: DamageType::Impact,
+                at,
+                direction: None,
+            });
+            app.world_mut().run_schedule(FixedUpdate);
+        };
+
+        // Halved by the armor, and far from the fragile part.
+        hit(&mut app, Vec3::ZERO);
+        assert_eq!(app.world().get::<Health>(construct).unwrap().current, 85.0);
+        assert_eq!(app.world().get::<Health>(armor).unwrap().current, 985.0);
+        assert_eq!(app.world().get::<Health>(fragile).unwrap().current, 10.0);
+
+        // Right at the fragile part, which is destroyed; too far from the
+        // armor for it to help.
+        hit(&mut app, Vec3::X * 10.0);
+        assert_eq!(app.world().get::<Health>(construct).unwrap().current, 55.0);
+        assert!(app.world().get::<Wrecked>(fragile).is_some());
+
+        app.world_mut().run_schedule(FixedUpdate);
+        assert!(app.world().get_entity(fragile).is_err());
+        let installed = app.world().get::<ConstructParts>(construct).unwrap();
+        assert_eq!(installed.iter().copied().collect::<Vec<_>>(), [armor]);
+    }
+}
@@ -0,0 +1,353 @@
+//! # Definitions
+//!
+//! Ship makes, items (parts, ammo and the like) and props are all data. The
+//! [DefRegistry] holds every such definition, keyed by a string id, so that
+//! spawners, shops and the like can refer to them with a [DefId] rather than
+//! build them in code.
+//!
+//! The registry starts out with the built-in definitions. At startup, every
+//! definitions file (`*.defs.ron`) in the `defs` asset folder is loaded and
+//! merged into it, overriding built-in definitions of the same id; props are
+//! also added to the [PropCatalog]. Content can thus be added without
+//! recompiling the game.
+//!
+//! A definitions file is a [DefsFile] in [RON](https://github.com/ron-rs/ron)
+//! form, e.g.:
+//!
+//! ```ron
+//! (
+//!     makes: {
+//!         "brig": (
+//!             hull_mass: 20.0,
+//!             hull: (length: 12.0, beam: 4.0, depth: 2.5),
+//!             slots: [
+//!                 (part_type: "cannon", offset: (1.0, 1.0, 0.0), point_attachment: 0),
+//!                 (part_type: "engine", offset: (0.0, 0.0, -2.0), point_attachment: 0),
+//!             ],
+//!         ),
+//!     },
+//! )
+//! ```
+//!
+//! [NOTE] Only RON is supported for now; TOML would need a new dependency.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{fmt, marker::PhantomData};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedFolder, io::Reader, ron},
+    platform::collections::HashMap,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::{Context, LnrError},
+    inventory::InventoryDef,
+    makeup::{PartSlot, ShipMake, hull::HullShape},
+    props::{PropCatalog, PropDef},
+};
+
+/// The id of the built-in make of unarmed NPC ships.
+pub const SLOOP: &str = "sloop";
+
+/// The id of the built-in make of armed NPC ships.
+pub const GUNBOAT: &str = "gunboat";
+
+/// A reference to a definition in the [DefRegistry], by id.
+pub struct DefId<T> {
+    /// The id of the definition.
+    pub id: String,
+
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DefId<T> {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for DefId<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id.clone())
+    }
+}
+
+impl<T> PartialEq for DefId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> fmt::Debug for DefId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DefId({:?})", self.id)
+    }
+}
+
+/// A kind of definition held by the [DefRegistry].
+pub trait Def: Sized + 'static {
+    /// The definitions of this kind in a registry.
+    fn table(registry: &DefRegistry) -> &HashMap<String, Self>;
+}
+
+impl Def for ShipMake {
+    fn table(registry: &DefRegistry) -> &HashMap<String, Self> {
+        &registry.makes
+    }
+}
+
+impl Def for InventoryDef {
+    fn table(registry: &DefRegistry) -> &HashMap<String, Self> {
+        &registry.items
+    }
+}
+
+impl Def for PropDef {
+    fn table(registry: &DefRegistry) -> &HashMap<String, Self> {
+        &registry.props
+    }
+}
+
+/// A file of definitions, to be merged into the [DefRegistry].
+#[derive(Asset, TypePath, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefsFile {
+    /// Ship makes, by id.
+    pub makes: HashMap<String, ShipMake>,
+
+    /// Items, such as parts and ammo, by id.
+    pub items: HashMap<String, InventoryDef>,
+
+    /// Props, by id.
+    pub props: HashMap<String, PropDef>,
+}
+
+impl DefsFile {
+    /// Parses a definitions file from RON.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, LnrError> {
+        ron::de::from_bytes(bytes).context("parsing a definitions file")
+    }
+}
+
+/// Every definition known to the game, by id.
+///
+/// See the module documentation.
+#[derive(Resource, Clone, Debug)]
+pub struct DefRegistry {
+    makes: HashMap<String, ShipMake>,
+    items: HashMap<String, InventoryDef>,
+    props: HashMap<String, PropDef>,
+}
+
+impl Default for DefRegistry {
+    /// The built-in definitions.
+    fn default() -> Self {
+        let makes = HashMap::from_iter([
+            (
+                SLOOP.to_string(),
+                ShipMake {
+                    hull_mass: 8.0,
+                    hull: HullShape::new(7.0, 2.5, 1.6),
+                    slots: vec![],
+                },
+            ),
+            (
+                GUNBOAT.to_string(),
+                ShipMake {
+                    hull_mass: 12.0,
+                    hull: HullShape::new(9.0, 3.2, 2.0),
+                    slots: vec![PartSlot {
+                        part_type: "cannon".into(),
+                        offset: Vec3::Y,
+                        point_attachment: 0,
+                    }],
+                },
+            ),
+        ]);
+
+        let props = PropCatalog::default()
+            .defs
+            .into_iter()
+            .map(|def| (def.mesh.clone(), def))
+            .collect();
+
+        Self {
+            makes,
+            items: HashMap::default(),
+            props,
+        }
+    }
+}
+
+impl DefRegistry {
+    /// Looks up a definition.
+    pub fn get<T: Def>(&self, id: &DefId<T>) -> Option<&T> {
+        T::table(self).get(&id.id)
+    }
+
+    /// Iterate on all definitions of a kind, with their ids.
+    pub fn iter<T: Def>(&self) -> impl Iterator<Item = (DefId<T>, &T)> {
+        T::table(self)
+            .iter()
+            .map(|(id, def)| (DefId::new(id.clone()), def))
+    }
+
+    /// Adds all definitions of a file, overriding any with the same ids.
+    pub fn merge(&mut self, file: DefsFile) {
+        self.makes.extend(file.makes);
+        self.items.extend(file.items);
+        self.props.extend(file.props);
+    }
+}
+
+/// Loads [DefsFile]s from `.defs.ron` files.
+#[derive(Default)]
+pub struct DefsLoader;
+
+impl AssetLoader for DefsLoader {
+    type Asset = DefsFile;
+    type Settings = ();
+    type Error = LnrError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .context("reading a definitions file")?;
+
+        DefsFile::from_ron(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["defs.ron"]
+    }
+}
+
+/// The folder of definitions files being loaded, kept so it stays loaded.
+#[derive(Resource)]
+struct DefsFolder(#[allow(dead_code)] Handle<LoadedFolder>);
+
+fn load_defs(mut commands: Commands, server: Res<AssetServer>) {
+    commands.insert_resource(DefsFolder(server.load_folder("defs")));
+}
+
+fn merge_loaded_defs(
+    mut events: EventReader<AssetEvent<DefsFile>>,
+    files: Res<Assets<DefsFile>>,
+    mut registry: ResMut<DefRegistry>,
+    mut catalog: ResMut<PropCatalog>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(file) = files.get(*id) else {
+            continue;
+        };
+
+        for def in file.props.values() {
+            match catalog.defs.iter_mut().find(|other| other.name == def.name) {
+                Some(other) => *other = def.clone(),
+                None => catalog.defs.push(def.clone()),
+            }
+        }
+
+        info!(
+            "Loaded {} makes, {} items and {} props",
+            file.makes.len(),
+            file.items.len(),
+            file.props.len()
+        );
+        registry.merge(file.clone());
+    }
+}
+
+/// Definitions plugin.
+///
+/// Definitions files are only loaded if there is an [AssetServer], i.e. the
+/// [AssetPlugin] was added beforehand; otherwise, only the built-in
+/// definitions are available.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct DefsPlugin;
+
+impl Plugin for DefsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DefRegistry>();
+        app.init_resource::<PropCatalog>();
+
+        if app.world().contains_resource::<AssetServer>() {
+            app.init_asset::<DefsFile>();
+            app.init_asset_loader::<DefsLoader>();
+            app.add_systems(Startup, load_defs);
+            app.add_systems(Update, merge_loaded_defs);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::common::inventory::ItemType;
+
+    use super::*;
+
+    #[test]
+    fn files_override_built_in_defs() {
+        let file = DefsFile::from_ron(
+            br#"(
+                makes: {
+                    "gunboat": (hull_mass: 15.0, slots: []),
+                },
+                items: {
+                    "shot": (
+                        item_type: Ammo((ammo_type: Cannonball((caliber: 120)))),
+                        name: "Cannonballs",
+                        mass: 0.5,
+                        unit_cost: 2,
+                        drop_chance: 50,
+                        vulnerability: 0,
+                        repair_cost_scale: 0,
+                        volume: 0.01,
+                        amount: 20.0,
+                    ),
+                },
+            )"#,
+        )
+        .unwrap();
+
+        let mut registry = DefRegistry::default();
+        registry.merge(file);
+
+        let gunboat = registry.get(&DefId::<ShipMake>::new(GUNBOAT)).unwrap();
+        assert_eq!(gunboat.hull_mass, 15.0);
+        assert!(registry.get(&DefId::<ShipMake>::new(SLOOP)).is_some());
+
+        let shot = registry.get(&DefId::<InventoryDef>::new("shot")).unwrap();
+        assert!(matches!(shot.item_type, ItemType::Ammo(_)));
+
+        assert!(DefsFile::from_ron(b"(makes: 3)").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! # Allocation diagnostics
+//!
+//! Tracks heap allocations in hot paths, so that code which allocates every
+//! frame (e.g. temporary Vecs in collision or terrain sampling) can be found
+//! and migrated to reused buffers.
+//!
+//! Hot code is marked with an [AllocationScope]. Allocations made while a
+//! scope is alive, on the same thread, are attributed to it. Allocations are
+//! only counted when the `alloc_diagnostics` feature is enabled in a debug
+//! build, which installs [CountingAllocator] as the global allocator;
+//! otherwise scopes are free.
+//!
+//! [AllocationDiagnosticsPlugin] publishes the counts of every scope as Bevy
+//! diagnostics, under `alloc/<scope>/count` and `alloc/<scope>/bytes`, and
+//! warns about scopes which allocate every frame.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::HashMap,
+    sync::Mutex,
+};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore},
+    platform::time::Instant,
+    prelude::*,
+};
+
+#[cfg(all(debug_assertions, feature = "alloc_diagnostics"))]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Whether allocations are being counted at all.
+pub const ALLOCATIONS_COUNTED: bool = cfg!(all(debug_assertions, feature = "alloc_diagnostics"));
+
+/// After allocating for this many frames in a row, a scope is flagged.
+const FLAG_AFTER_FRAMES: u32 = 120;
+
+thread_local! {
+    /// Allocations made on this thread so far, as (count, bytes).
+    static THREAD_ALLOCATIONS: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// Allocations attributed to each scope since they were last published.
+static SCOPE_ALLOCATIONS: Mutex<Vec<(&'static str, u64, u64)>> = Mutex::new(Vec::new());
+
+/// A global allocator which counts allocations on each thread.
+///
+/// Wraps the [System] allocator.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // [NOTE] Thread locals with a const initializer and no destructor
+        // never allocate, so this can't recurse. It may fail while the
+        // thread is shutting down, in which case the allocation is simply not
+        // counted.
+        let _ = THREAD_ALLOCATIONS.try_with(|counts| {
+            let (count, bytes) = counts.get();
+            counts.set((count + 1, bytes + layout.size() as u64));
+        });
+
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Attributes allocations made on the current thread to a named scope,
+/// until dropped.
+///
+/// ```ignore
+/// fn hot_system() {
+///     let _scope = AllocationScope::new("hot_system");
+///     // ...
+/// }
+/// ```
+pub struct AllocationScope {
+    name: &'static str,
+    start: (u64, u64),
+}
+
+impl AllocationScope {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            start: if ALLOCATIONS_COUNTED {
+                THREAD_ALLOCATIONS.with(Cell::get)
+            } else {
+                (0, 0)
+            },
+        }
+    }
+}
+
+impl Drop for AllocationScope {
+    fn drop(&mut self) {
+        if !ALLOCATIONS_COUNTED {
+            return;
+        }
+
+        let (count, bytes) = THREAD_ALLOCATIONS.with(Cell::get);
+        let (count, bytes) = (count - self.start.0, bytes - self.start.1);
+
+        let mut scopes = SCOPE_ALLOCATIONS.lock().unwrap();
+        match scopes.iter_mut().find(|(name, _, _)| *name == self.name) {
+            Some((_, scope_count, scope_bytes)) => {
+                *scope_count += count;
+                *scope_bytes += bytes;
+            }
+            None => scopes.push((self.name, count, bytes)),
+        }
+    }
+}
+
+fn diagnostic_paths(scope: &str) -> (DiagnosticPath, DiagnosticPath) {
+    (
+        DiagnosticPath::from_components(["alloc", scope, "count"]),
+        DiagnosticPath::from_components(["alloc", scope, "bytes"]),
+    )
+}
+
+/// Publishes the allocations of every scope since the last frame.
+fn publish_allocations(
+    mut store: ResMut<DiagnosticsStore>,
+    mut frames_allocating: Local<HashMap<&'static str, u32>>,
+) {
+    let scopes = std::mem::take(&mut *SCOPE_ALLOCATIONS.lock().unwrap());
+    let time = Instant::now();
+
+    for (name, streak) in frames_allocating.iter_mut() {
+        let allocated = scopes
+            .iter()
+            .any(|(scope, count, _)| scope == name && *count > 0);
+
+        if !allocated {
+            *streak = 0;
+        }
+    }
+
+    for (name, count, bytes) in scopes {
+        let (count_path, bytes_path) = diagnostic_paths(name);
+
+        if store.get(&count_path).is_none() {
+            store.add(Diagnostic::new(count_path.clone()));
+            store.add(Diagnostic::new(bytes_path.clone()).with_suffix(" B"));
+        }
+
+        for (path, value) in [(count_path, count), (bytes_path, bytes)] {
+            store
+                .get_mut(&path)
+                .unwrap()
+                .add_measurement(DiagnosticMeasurement {
+                    time,
+                    value: value as f64,
+                });
+        }
+
+        if count == 0 {
+            continue;
+        }
+
+        let streak = frames_allocating.entry(name).or_default();
+        *streak += 1;
+
+        if *streak == FLAG_AFTER_FRAMES {
+            warn!(
+                "'{name}' has allocated every frame for {FLAG_AFTER_FRAMES} frames; \
+                 consider reusing buffers"
+            );
+        }
+    }
+}
+
+/// Allocation diagnostics plugin.
+///
+/// Does nothing unless allocations are counted; see the module
+/// documentation.
+pub struct AllocationDiagnosticsPlugin;
+
+impl Plugin for AllocationDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        if !ALLOCATIONS_COUNTED {
+            return;
+        }
+
+        app.init_resource::<DiagnosticsStore>();
+        app.add_systems(Last, publish_allocations);
+    }
+}
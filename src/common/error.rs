@@ -0,0 +1,409 @@
+//! # Errors
+//!
+//! [LnrError] is the error type shared across the crate. It has a kind, and
+//! a chain of context messages describing what was being done when it
+//! happened, which are added using [Context].
+//!
+//! Systems and observers which can fail should return Bevy's [Result] and
+//! propagate errors with `?`, rather than unwrapping. Failures are then
+//! handled centrally, according to the [ErrorPolicy]: logged, reported as a
+//! [SystemErrorEvent], or panicked on. By default, debug builds panic, so
+//! that bugs don't go unnoticed, while release builds report and keep going,
+//! which matters for long-running servers.
+//!
+//! [NOTE] Bevy only takes a global function pointer as its error handler,
+//! which applications must set before initializing the app; see
+//! [install_error_handler]. Hence the policy and pending reports are global
+//! too.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+use bevy::{
+    asset::ron,
+    ecs::{
+        error::{BevyError, ErrorContext, GLOBAL_ERROR_HANDLER},
+        query::{QueryEntityError, QuerySingleError},
+    },
+    prelude::*,
+};
+
+/// What went wrong.
+#[derive(Clone, Debug)]
+pub enum LnrErrorKind {
+    /// An entity lacks a component it should have.
+    MissingComponent {
+        entity: Entity,
+        component: &'static str,
+    },
+
+    /// An entity does not exist.
+    MissingEntity { entity: Entity },
+
+    /// Something is in a state it should never be in.
+    InvalidState(String),
+
+    /// A file could not be read or written.
+    Io(Arc<std::io::Error>),
+
+    /// Data could not be read from, or written to, its format, e.g. RON.
+    Parse(Arc<dyn std::error::Error + Send + Sync>),
+}
+
+impl PartialEq for LnrErrorKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::MissingComponent { entity, component },
+                Self::MissingComponent {
+                    entity: other_entity,
+                    component: other_component,
+                },
+            ) => entity == other_entity && component == other_component,
+            (Self::MissingEntity { entity }, Self::MissingEntity { entity: other }) => {
+                entity == other
+            }
+            (Self::InvalidState(description), Self::InvalidState(other)) => description == other,
+            // [NOTE] Source errors can't be compared; only the same one is
+            // equal to itself.
+            (Self::Io(error), Self::Io(other)) => Arc::ptr_eq(error, other),
+            (Self::Parse(error), Self::Parse(other)) => Arc::ptr_eq(error, other),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for LnrErrorKind {}
+
+impl fmt::Display for LnrErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingComponent { entity, component } => {
+                write!(f, "entity {entity} has no {component}")
+            }
+            Self::MissingEntity { entity } => write!(f, "entity {entity} does not exist"),
+            Self::InvalidState(description) => write!(f, "{description}"),
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// The crate-wide error type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LnrError {
+    /// What went wrong.
+    pub kind: LnrErrorKind,
+
+    /// What was being done, innermost first.
+    pub context: Vec<Cow<'static, str>>,
+}
+
+impl LnrError {
+    /// An error without context.
+    pub fn new(kind: LnrErrorKind) -> Self {
+        Self {
+            kind,
+            context: Vec::new(),
+        }
+    }
+
+    /// An entity lacks a component it should have.
+    pub fn missing_component<T: Component>(entity: Entity) -> Self {
+        Self::new(LnrErrorKind::MissingComponent {
+            entity,
+            component: std::any::type_name::<T>()
+                .rsplit("::")
+                .next()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Something is in a state it should never be in.
+    pub fn invalid_state(description: impl Into<String>) -> Self {
+        Self::new(LnrErrorKind::InvalidState(description.into()))
+    }
+
+    /// A file could not be read or written.
+    pub fn io(error: std::io::Error) -> Self {
+        Self::new(LnrErrorKind::Io(Arc::new(error)))
+    }
+
+    /// Data could not be read from, or written to, its format.
+    pub fn parse(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::new(LnrErrorKind::Parse(Arc::new(error)))
+    }
+
+    /// Adds context to this error.
+    pub fn context(mut self, context: impl Into<Cow<'static, str>>) -> Self {
+        self.context.push(context.into());
+        self
+    }
+}
+
+impl fmt::Display for LnrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for context in self.context.iter().rev() {
+            write!(f, "{context}: ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for LnrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LnrErrorKind::Io(error) => Some(error.as_ref()),
+            LnrErrorKind::Parse(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LnrError {
+    fn from(error: std::io::Error) -> Self {
+        Self::io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for LnrError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::parse(error)
+    }
+}
+
+impl From<ron::Error> for LnrError {
+    fn from(error: ron::Error) -> Self {
+        Self::parse(error)
+    }
+}
+
+impl From<QueryEntityError> for LnrError {
+    fn from(error: QueryEntityError) -> Self {
+        match error {
+            QueryEntityError::EntityDoesNotExist(error) => Self::new(LnrErrorKind::MissingEntity {
+                entity: error.entity,
+            }),
+            QueryEntityError::QueryDoesNotMatch(entity, _) => {
+                Self::new(LnrErrorKind::MissingComponent {
+                    entity,
+                    component: "components matching the query",
+                })
+            }
+            QueryEntityError::AliasedMutability(entity) => Self::invalid_state(format!(
+                "entity {entity} was requested mutably more than once"
+            )),
+        }
+    }
+}
+
+impl From<QuerySingleError> for LnrError {
+    fn from(error: QuerySingleError) -> Self {
+        Self::invalid_state(error.to_string())
+    }
+}
+
+/// Shorthand for results with an [LnrError].
+pub type LnrResult<T = ()> = std::result::Result<T, LnrError>;
+
+/// Adds context to errors.
+pub trait Context<T> {
+    /// Adds context to the error, if any.
+    fn context(self, context: impl Into<Cow<'static, str>>) -> LnrResult<T>;
+
+    /// Adds lazily made context to the error, if any.
+    fn with_context<C: Into<Cow<'static, str>>>(self, context: impl FnOnce() -> C) -> LnrResult<T>;
+}
+
+impl<T, E: Into<LnrError>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<Cow<'static, str>>) -> LnrResult<T> {
+        self.map_err(|error| error.into().context(context))
+    }
+
+    fn with_context<C: Into<Cow<'static, str>>>(self, context: impl FnOnce() -> C) -> LnrResult<T> {
+        self.map_err(|error| error.into().context(context()))
+    }
+}
+
+/// How failing systems and observers are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorPolicy {
+    /// Log the error.
+    Log,
+
+    /// Log the error, and send a [SystemErrorEvent].
+    Report,
+
+    /// Panic.
+    Panic,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Panic
+        } else {
+            Self::Report
+        }
+    }
+}
+
+static ERROR_POLICY: AtomicU8 = AtomicU8::new(ErrorPolicy::Panic as u8);
+
+impl ErrorPolicy {
+    /// The error policy in effect.
+    pub fn current() -> Self {
+        match ERROR_POLICY.load(Ordering::Relaxed) {
+            0 => Self::Log,
+            1 => Self::Report,
+            _ => Self::Panic,
+        }
+    }
+
+    /// Puts this error policy in effect.
+    pub fn apply(self) {
+        ERROR_POLICY.store(self as u8, Ordering::Relaxed);
+    }
+}
+
+/// A system, observer or command failed.
+///
+/// Only sent under [ErrorPolicy::Report].
+#[derive(Event, Clone, Debug)]
+pub struct SystemErrorEvent {
+    /// What failed, e.g. "system".
+    pub kind: String,
+
+    /// The name of what failed.
+    pub name: String,
+
+    /// The error.
+    pub message: String,
+}
+
+/// Errors reported since the last [SystemErrorEvent]s were sent.
+static PENDING_REPORTS: Mutex<Vec<SystemErrorEvent>> = Mutex::new(Vec::new());
+
+/// Handles errors according to the [ErrorPolicy].
+pub fn handle_error(error: BevyError, context: ErrorContext) {
+    match ErrorPolicy::current() {
+        ErrorPolicy::Panic => bevy::ecs::error::panic(error, context),
+        ErrorPolicy::Log => bevy::ecs::error::error(error, context),
+        ErrorPolicy::Report => {
+            let report = SystemErrorEvent {
+                kind: context.kind().to_owned(),
+                name: context.name().to_owned(),
+                message: error.to_string(),
+            };
+            bevy::ecs::error::error(error, context);
+
+            if let Ok(mut pending) = PENDING_REPORTS.lock() {
+                pending.push(report);
+            }
+        }
+    }
+}
+
+/// Puts an error policy in effect, and installs [handle_error] as Bevy's
+/// global error handler.
+///
+/// Must be called before initializing the app, and only by applications, not
+/// libraries.
+pub fn install_error_handler(policy: ErrorPolicy) {
+    policy.apply();
+
+    if GLOBAL_ERROR_HANDLER.set(handle_error).is_err() {
+        warn!("A global error handler was already set; the error policy may not apply");
+    }
+}
+
+fn send_error_reports(mut events: EventWriter<SystemErrorEvent>) {
+    let Ok(mut pending) = PENDING_REPORTS.lock() else {
+        return;
+    };
+
+    events.write_batch(pending.drain(..));
+}
+
+/// Error reporting plugin.
+///
+/// Sends [SystemErrorEvent]s for errors reported under
+/// [ErrorPolicy::Report].
+pub struct ErrorReportingPlugin;
+
+impl Plugin for ErrorReportingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SystemErrorEvent>();
+        app.add_systems(Last, send_error_reports);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+
+    #[derive(Component, Debug)]
+    struct Hull;
+
+    #[test]
+    fn context_chains() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        let mut query = world.query::<&Hull>();
+
+        let error = query
+            .get(&world, entity)
+            .context("measuring the hull")
+            .context("applying buoyancy")
+            .unwrap_err();
+
+        assert!(matches!(
+            error.kind,
+            LnrErrorKind::MissingComponent { entity: missing, .. } if missing == entity
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "applying buoyancy: measuring the hull: entity {entity} has no components matching the query"
+            )
+        );
+
+        let error = LnrError::missing_component::<Hull>(entity);
+        assert_eq!(error.to_string(), format!("entity {entity} has no Hull"));
+
+        // File and format errors keep their source.
+        let error = std::fs::read("/nonexistent/save.ron")
+            .context("loading the save")
+            .unwrap_err();
+        assert!(matches!(error.kind, LnrErrorKind::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = ron::de::from_str::<u32>("not a number")
+            .context("reading settings")
+            .unwrap_err();
+        assert!(matches!(error.kind, LnrErrorKind::Parse(_)));
+        assert!(error.to_string().starts_with("reading settings: "));
+    }
+}
@@ -0,0 +1,63 @@
+//! # Test fixtures
+//!
+//! Items and ships which the unit tests of many modules need, without caring
+//! much about their details. Tests which do care override the fields they
+//! care about, e.g. `InventoryDef { unit_cost: 2, ..item(…) }`.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    inventory::{InventoryDef, ItemType},
+    makeup::{PartSlot, ShipMake, ShipMakeup, livery::ShipLivery},
+};
+
+/// An item which costs 1 per unit, is never dropped, and is never damaged.
+pub fn item(name: &str, item_type: ItemType, mass: f32, volume: f32, amount: f32) -> InventoryDef {
+    InventoryDef {
+        item_type,
+        name: name.into(),
+        mass,
+        unit_cost: 1,
+        drop_chance: 0,
+        vulnerability: 0,
+        repair_cost_scale: 0,
+        volume,
+        amount,
+    }
+}
+
+/// A slot for parts of the given type, at the first physics point.
+pub fn slot(part_type: &str) -> PartSlot {
+    PartSlot {
+        part_type: part_type.into(),
+        offset: Vec3::ZERO,
+        point_attachment: 0,
+    }
+}
+
+/// A ship make with a hull of mass 10 and the default shape.
+pub fn make(slots: Vec<PartSlot>) -> ShipMake {
+    ShipMake {
+        hull_mass: 10.0,
+        hull: default(),
+        slots,
+    }
+}
+
+/// The makeup of a new ship of [make], with the default livery and no items.
+pub fn makeup(slots: Vec<PartSlot>) -> ShipMakeup {
+    ShipMakeup::new(make(slots), ShipLivery::default())
+}
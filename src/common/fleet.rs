@@ -0,0 +1,296 @@
+//! # Player fleets
+//!
+//! A player sails with a fleet of ships, rather than a single one. A fleet
+//! is an entity with a [Fleet] component; its ships are marked with
+//! [InFleet], and listed in its [FleetShips].
+//!
+//! One ship of the fleet, its flagship, is directly controlled by the
+//! player, and gets the [PlayerControlled] component. The rest of the fleet
+//! is driven by an [AiController], escorting the flagship (see
+//! [AiState::Escort]). If no flagship was picked, or it leaves the fleet,
+//! another ship of the fleet is picked.
+//!
+//! Ships join and leave the fleet through [add_to_fleet] and
+//! [remove_from_fleet], e.g. when bought or sold at the Harbor, and the
+//! flagship is picked through [select_flagship]. Ships leaving the fleet are
+//! no longer controlled by the player nor escort the flagship.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::ops::Deref;
+
+use bevy::prelude::*;
+
+use super::{
+    ai::{AiController, AiState},
+    player::PlayerControlled,
+};
+
+/// How far escorts keep from their flagship.
+pub const ESCORT_DISTANCE: f32 = 40.0;
+
+/// The money of the player's fleet.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Funds(pub u64);
+
+/// A fleet of player ships.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Fleet {
+    /// The ship the player directly controls.
+    flagship: Option<Entity>,
+}
+
+impl Fleet {
+    /// The ship the player directly controls, if the fleet has any ships.
+    pub fn flagship(&self) -> Option<Entity> {
+        self.flagship
+    }
+}
+
+/// Marks a ship as part of a fleet.
+///
+/// Wraps a reference to the fleet entity.
+#[derive(Component)]
+#[relationship(relationship_target = FleetShips)]
+pub struct InFleet(Entity);
+
+impl InFleet {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+
+    pub fn new(fleet_id: Entity) -> Self {
+        Self(fleet_id)
+    }
+}
+
+impl Deref for InFleet {
+    type Target = Entity;
+
+    fn deref(&self) -> &Entity {
+        &self.0
+    }
+}
+
+/// Lists the ships of a fleet.
+#[derive(Component)]
+#[relationship_target(relationship = InFleet)]
+pub struct FleetShips(Vec<Entity>);
+
+impl FleetShips {
+    pub fn iter(&self) -> std::slice::Iter<'_, Entity> {
+        self.0.iter()
+    }
+}
+
+/// Event request to make a ship the flagship of its fleet.
+///
+/// This event must be targeted on the ship. Ignored if the ship is not part
+/// of a fleet.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct SelectFlagship;
+
+pub fn obs_select_flagship(
+    trigger: Trigger<SelectFlagship>,
+    ships: Query<&InFleet>,
+    mut fleets: Query<&mut Fleet>,
+) {
+    let ship = trigger.target();
+    let Ok(in_fleet) = ships.get(ship) else {
+        warn!("Tried to make {ship:?} a flagship, but it is not part of any fleet");
+        return;
+    };
+
+    if let Ok(mut fleet) = fleets.get_mut(in_fleet.get()) {
+        fleet.flagship = Some(ship);
+    }
+}
+
+/// Ships leaving a fleet are no longer the player's to control.
+pub fn obs_leave_fleet(
+    trigger: Trigger<OnRemove, InFleet>,
+    mut commands: Commands,
+    ships: Query<Option<&AiController>>,
+) {
+    let ship = trigger.target();
+    let escorting = ships
+        .get(ship)
+        .ok()
+        .flatten()
+        .is_some_and(|ai| matches!(ai.state, AiState::Escort { .. }));
+
+    // The ship may be leaving the fleet because it is being despawned.
+    let Ok(mut ship) = commands.get_entity(ship) else {
+        return;
+    };
+    ship.try_remove::<PlayerControlled>();
+    if escorting {
+        ship.try_remove::<AiController>();
+    }
+}
+
+/// Keeps the flagship of each fleet controlled by the player, and the rest
+/// of the fleet escorting it.
+pub fn sync_fleet_roles(
+    mut commands: Commands,
+    mut fleets: Query<(&mut Fleet, Option<&FleetShips>)>,
+    ships: Query<(Has<PlayerControlled>, Option<&AiController>)>,
+) {
+    for (mut fleet, fleet_ships) in &mut fleets {
+        let fleet_ships = fleet_ships.map_or(&[][..], |ships| &ships.0[..]);
+
+        if fleet
+            .flagship
+            .is_none_or(|flagship| !fleet_ships.contains(&flagship))
+        {
+            fleet.flagship = fleet_ships.first().copied();
+        }
+        let Some(flagship) = fleet.flagship else {
+            continue;
+        };
+
+        for &ship in fleet_ships {
+            let Ok((controlled, ai)) = ships.get(ship) else {
+                continue;
+            };
+
+            if ship == flagship {
+                if !controlled {
+                    commands
+                        .entity(ship)
+                        .remove::<AiController>()
+                        .insert(PlayerControlled);
+                }
+                continue;
+            }
+
+            let escorting = ai.is_some_and(
+                |ai| matches!(ai.state, AiState::Escort { leader, .. } if leader == flagship),
+            );
+            if controlled || !escorting {
+                commands
+                    .entity(ship)
+                    .remove::<PlayerControlled>()
+                    .insert(AiController::new(AiState::Escort {
+                        leader: flagship,
+                        distance: ESCORT_DISTANCE,
+                    }));
+            }
+        }
+    }
+}
+
+/// Spawns an empty fleet.
+pub fn spawn_fleet(commands: &mut Commands) -> Entity {
+    commands.spawn(Fleet::default()).id()
+}
+
+/// Adds a ship to a fleet, taking it out of any other fleet.
+pub fn add_to_fleet(commands: &mut Commands, fleet: Entity, ship: Entity) {
+    commands.entity(ship).insert(InFleet::new(fleet));
+}
+
+/// Takes a ship out of its fleet.
+pub fn remove_from_fleet(commands: &mut Commands, ship: Entity) {
+    commands.entity(ship).remove::<InFleet>();
+}
+
+/// Makes a ship the flagship of its fleet.
+///
+/// Wraps around [SelectFlagship].
+pub fn select_flagship(commands: &mut Commands, ship: Entity) {
+    commands.entity(ship).trigger(SelectFlagship);
+}
+
+/// Player fleet plugin.
+pub struct FleetPlugin;
+
+impl Plugin for FleetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Funds>();
+        app.add_event::<SelectFlagship>();
+        app.add_observer(obs_select_flagship);
+        app.add_observer(obs_leave_fleet);
+        app.add_systems(Update, sync_fleet_roles);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn flagships_are_controlled_and_escorted() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FleetPlugin));
+
+        let fleet = spawn_fleet(&mut app.world_mut().commands());
+        let ships: Vec<_> = (0..3).map(|_| app.world_mut().spawn_empty().id()).collect();
+        for &ship in &ships {
+            add_to_fleet(&mut app.world_mut().commands(), fleet, ship);
+        }
+        app.world_mut().flush();
+        app.update();
+
+        let roles = |app: &App| {
+            ships
+                .iter()
+                .map(|&ship| {
+                    let controlled = app.world().get::<PlayerControlled>(ship).is_some();
+                    let ai = app.world().get::<AiController>(ship);
+                    (controlled, ai.map(|ai| ai.state.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let escort = |leader| {
+            Some(AiState::Escort {
+                leader,
+                distance: ESCORT_DISTANCE,
+            })
+        };
+
+        // The first ship leads, unless told otherwise.
+        assert_eq!(
+            roles(&app),
+            [
+                (true, None),
+                (false, escort(ships[0])),
+                (false, escort(ships[0]))
+            ]
+        );
+
+        select_flagship(&mut app.world_mut().commands(), ships[2]);
+        app.world_mut().flush();
+        app.update();
+        assert_eq!(
+            roles(&app),
+            [
+                (false, escort(ships[2])),
+                (false, escort(ships[2])),
+                (true, None)
+            ]
+        );
+
+        // Ships leaving the fleet are left alone, and a new flagship picked.
+        remove_from_fleet(&mut app.world_mut().commands(), ships[2]);
+        app.world_mut().flush();
+        app.update();
+        assert_eq!(
+            roles(&app),
+            [(true, None), (false, escort(ships[0])), (false, None)]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! # Visual effect cues
+//!
+//! Like [sound cues](super::sound), visual effects are only ever cued by the
+//! simulation, with [FxEvent]s; the client decides how to draw them (see
+//! [the particle renderer](crate::app::renderer::particles)), and headless
+//! instances just never read them.
+//!
+//! Cues are emitted here, from other simulation events and state: cannons
+//! firing, mines detonating, projectiles splashing into the water, and
+//! running engines churning it up behind them.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    construct::{part::PartInstalledOn, slot::SlotAttachment},
+    makeup::parts::{
+        attachment_point,
+        cannon::Cannonball,
+        engine::{EnginePart, EngineState},
+        minelayer::MineDetonated,
+    },
+    physics::{base::PointNetwork, projectile::FastProjectile, water::WaveField},
+};
+
+/// The height of the sea, without waves.
+const SEA_LEVEL: f32 = 0.0;
+
+/// The cannonball caliber of a muzzle flash of scale 1.0.
+const REFERENCE_CALIBER: f32 = 40.0;
+
+/// The mine power of an explosion of scale 1.0.
+const REFERENCE_POWER: f32 = 50.0;
+
+/// The impact speed of a splash of scale 1.0.
+const REFERENCE_IMPACT_SPEED: f32 = 40.0;
+
+/// How often running engines cue their wake, in seconds.
+const WAKE_INTERVAL: f32 = 0.1;
+
+/// What kind of visual effect to draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FxKind {
+    /// The flash and smoke of a cannon firing.
+    MuzzleFlash,
+
+    /// Water thrown up by something falling into it.
+    Splash,
+
+    /// The fireball and smoke of an explosion.
+    ExplosionSmoke,
+
+    /// Water churned up by an engine.
+    EngineWake,
+}
+
+/// A visual effect to be drawn.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct FxEvent {
+    pub kind: FxKind,
+
+    /// Where the effect happens, in world space.
+    pub at: Vec3,
+
+    /// The main direction of the effect, e.g. where a cannon was pointed;
+    /// zero if it has none.
+    pub direction: Vec3,
+
+    /// How big the effect is, relative to its usual size.
+    pub scale: f32,
+}
+
+impl FxEvent {
+    pub fn new(kind: FxKind, at: Vec3) -> Self {
+        Self {
+            kind,
+            at,
+            direction: Vec3::ZERO,
+            scale: 1.0,
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Vec3) -> Self {
+        self.direction = direction.normalize_or_zero();
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Flashes the muzzles of cannons which just fired.
+fn muzzle_flash_effects(
+    cannonballs: Query<(&PointNetwork, &Cannonball), Added<Cannonball>>,
+    mut effects: EventWriter<FxEvent>,
+) {
+    for (network, cannonball) in &cannonballs {
+        let Some(point) = network.points.first() else {
+            continue;
+        };
+        effects.write(
+            FxEvent::new(FxKind::MuzzleFlash, point.pos)
+                .with_direction(point.vel)
+                .with_scale(cannonball.def.caliber as f32 / REFERENCE_CALIBER),
+        );
+    }
+}
+
+fn explosion_effects(
+    mut detonations: EventReader<MineDetonated>,
+    mut effects: EventWriter<FxEvent>,
+) {
+    effects.write_batch(detonations.read().map(|detonation| {
+        FxEvent::new(FxKind::ExplosionSmoke, detonation.at)
+            .with_direction(Vec3::Y)
+            .with_scale(detonation.power / REFERENCE_POWER)
+    }));
+}
+
+/// Splashes projectiles which went below the water since the last tick, the
+/// bigger the faster they hit it.
+fn splash_effects(
+    time: Res<Time>,
+    waves: Res<WaveField>,
+    projectiles: Query<&PointNetwork, With<FastProjectile>>,
+    mut effects: EventWriter<FxEvent>,
+) {
+    for network in &projectiles {
+        let velocity = network.linear_velocity();
+        let at = network.center_of_mass();
+        let last_at = at - velocity * time.delta_secs();
+        let surface = SEA_LEVEL + waves.height_at(at.xz(), time.elapsed_secs());
+
+        if at.y < surface && last_at.y >= surface {
+            effects.write(
+                FxEvent::new(FxKind::Splash, at.with_y(surface))
+                    .with_direction(Vec3::Y)
+                    .with_scale(velocity.length() / REFERENCE_IMPACT_SPEED),
+            );
+        }
+    }
+}
+
+/// Churns the water behind running engines, the more the harder they thrust.
+fn engine_wake_effects(
+    time: Res<Time>,
+    mut since_last: Local<f32>,
+    engines: Query<(Entity, &EnginePart, &PartInstalledOn, Option<&ChildOf>)>,
+    slots: Query<&SlotAttachment>,
+    constructs: Query<(&PointNetwork, &Transform)>,
+    mut effects: EventWriter<FxEvent>,
+) -> Result {
+    *since_last += time.delta_secs();
+    if *since_last < WAKE_INTERVAL {
+        return Ok(());
+    }
+    *since_last = 0.0;
+
+    for (entity, engine, installed_on, slot) in &engines {
+        if engine.state() != EngineState::Running {
+            continue;
+        }
+        let Ok((network, transform)) = constructs.get(installed_on.get()) else {
+            continue;
+        };
+
+        let at = attachment_point(entity, slot, &slots, network)?;
+        effects.write(
+            FxEvent::new(FxKind::EngineWake, at)
+                .with_direction(transform.back() * engine.throttle().signum())
+                .with_scale(engine.throttle().abs()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Emits visual effect cues from the simulation.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct FxCuePlugin;
+
+impl Plugin for FxCuePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FxEvent>();
+        app.add_systems(FixedUpdate, (splash_effects, engine_wake_effects));
+        app.add_systems(Update, (muzzle_flash_effects, explosion_effects));
+    }
+}
@@ -0,0 +1,261 @@
+//! # Intermission
+//!
+//! Between island raids, the player's fleet is moored at a town, where they
+//! can visit its buildings: the Shop to trade, the Drydock to refit ships,
+//! and so on. Which building the player is in, if any, is tracked by the
+//! [IntermissionBuilding] sub-state of [GameState::Intermission]; they start
+//! out at the [IntermissionBuilding::Town] overview.
+//!
+//! The player moves between buildings by sending [EnterBuilding] and
+//! [LeaveBuilding] events. Not every town has every building (see
+//! [TownBuildings]); requests to enter a building the town lacks are
+//! answered with a [BuildingUnavailable] event instead.
+//!
+//! The logic of each building hooks into the sub-state: on entering and
+//! leaving it, through `OnEnter(IntermissionBuilding::Shop)` and the like,
+//! and while the player is in it, through
+//! [add_building_systems](IntermissionAppExt::add_building_systems).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Shop and tavern logic, once the economy exists.
+
+use bevy::{ecs::system::ScheduleSystem, platform::collections::HashSet, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::state::GameState;
+
+pub mod drydock; // Refitting ships, and moving items between holds
+pub mod observatory; // Island offers, and picking the next island to raid
+
+/// Where in town the player is, during the intermission.
+#[derive(SubStates, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[source(GameState = GameState::Intermission)]
+pub enum IntermissionBuilding {
+    /// The town overview, from which buildings are entered.
+    #[default]
+    Town,
+
+    /// Buy and sell items.
+    Shop,
+
+    /// Install, uninstall and repair parts, and repaint ships.
+    Drydock,
+
+    /// Hire crew, and hear rumors.
+    Tavern,
+
+    /// Chart the islands to raid next.
+    Observatory,
+
+    /// Take on contracts.
+    Guild,
+
+    /// Buy and sell ships.
+    Harbor,
+}
+
+/// Which buildings the current town has.
+///
+/// The [IntermissionBuilding::Town] overview is always available.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct TownBuildings {
+    available: HashSet<IntermissionBuilding>,
+}
+
+impl TownBuildings {
+    /// A town with only the given buildings.
+    pub fn new(buildings: impl IntoIterator<Item = IntermissionBuilding>) -> Self {
+        Self {
+            available: buildings.into_iter().collect(),
+        }
+    }
+
+    /// The buildings the town has, besides the town overview.
+    pub fn iter(&self) -> impl Iterator<Item = IntermissionBuilding> + '_ {
+        self.available.iter().copied()
+    }
+
+    /// Whether the town has a building.
+    pub fn is_available(&self, building: IntermissionBuilding) -> bool {
+        building == IntermissionBuilding::Town || self.available.contains(&building)
+    }
+
+    /// Adds a building to, or removes it from, the town.
+    pub fn set_available(&mut self, building: IntermissionBuilding, available: bool) {
+        if available {
+            self.available.insert(building);
+        } else {
+            self.available.remove(&building);
+        }
+    }
+}
+
+impl Default for TownBuildings {
+    /// Every town has at least a Shop, a Drydock and a Tavern.
+    fn default() -> Self {
+        Self::new([
+            IntermissionBuilding::Shop,
+            IntermissionBuilding::Drydock,
+            IntermissionBuilding::Tavern,
+        ])
+    }
+}
+
+/// Requests the player to enter a building.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnterBuilding(pub IntermissionBuilding);
+
+/// Requests the player to leave the building they are in, back to the
+/// [IntermissionBuilding::Town] overview.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LeaveBuilding;
+
+/// Sent when the player asks to enter a building the town does not have.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildingUnavailable(pub IntermissionBuilding);
+
+fn handle_building_requests(
+    mut enters: EventReader<EnterBuilding>,
+    mut leaves: EventReader<LeaveBuilding>,
+    buildings: Res<TownBuildings>,
+    mut next_building: ResMut<NextState<IntermissionBuilding>>,
+    mut unavailable: EventWriter<BuildingUnavailable>,
+) {
+    for &EnterBuilding(building) in enters.read() {
+        if buildings.is_available(building) {
+            next_building.set(building);
+        } else {
+            info!("Tried to enter {building:?}, which this town does not have");
+            unavailable.write(BuildingUnavailable(building));
+        }
+    }
+
+    if leaves.read().count() > 0 {
+        next_building.set(IntermissionBuilding::Town);
+    }
+}
+
+fn log_building_transitions(
+    mut transitions: EventReader<StateTransitionEvent<IntermissionBuilding>>,
+) {
+    for transition in transitions.read() {
+        if let Some(building) = transition.entered {
+            info!("Entered {building:?}");
+        }
+    }
+}
+
+/// Hooks building logic into the intermission.
+pub trait IntermissionAppExt {
+    /// Adds systems which run every frame while the player is in a building.
+    fn add_building_systems<M>(
+        &mut self,
+        building: IntermissionBuilding,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self;
+}
+
+impl IntermissionAppExt for App {
+    fn add_building_systems<M>(
+        &mut self,
+        building: IntermissionBuilding,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self {
+        self.add_systems(Update, systems.run_if(in_state(building)))
+    }
+}
+
+/// Town buildings and moving between them during the intermission.
+///
+/// Requires the [GameState] to be initialized, e.g. by the
+/// [BaseStatePlugin](super::state::BaseStatePlugin).
+pub struct IntermissionPlugin;
+
+impl Plugin for IntermissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<IntermissionBuilding>();
+        app.add_plugins((drydock::DrydockPlugin, observatory::ObservatoryPlugin));
+        app.init_resource::<TownBuildings>();
+        app.add_event::<EnterBuilding>();
+        app.add_event::<LeaveBuilding>();
+        app.add_event::<BuildingUnavailable>();
+        app.add_systems(
+            Update,
+            (
+                handle_building_requests.run_if(in_state(GameState::Intermission)),
+                log_building_transitions,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+
+    use super::*;
+
+    #[test]
+    fn buildings_are_entered_only_if_available() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.init_state::<GameState>();
+        app.add_plugins(IntermissionPlugin);
+
+        let building = |app: &App| {
+            app.world()
+                .get_resource::<State<IntermissionBuilding>>()
+                .map(|state| *state.get())
+        };
+
+        app.update();
+        assert_eq!(building(&app), None);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Intermission);
+        app.update();
+        assert_eq!(building(&app), Some(IntermissionBuilding::Town));
+
+        // Not every town has a Guild.
+        app.world_mut()
+            .send_event(EnterBuilding(IntermissionBuilding::Guild));
+        app.update();
+        app.update();
+        assert_eq!(building(&app), Some(IntermissionBuilding::Town));
+        assert_eq!(
+            app.world().resource::<Events<BuildingUnavailable>>().len(),
+            1
+        );
+
+        app.world_mut()
+            .send_event(EnterBuilding(IntermissionBuilding::Shop));
+        app.update();
+        app.update();
+        assert_eq!(building(&app), Some(IntermissionBuilding::Shop));
+
+        app.world_mut().send_event(LeaveBuilding);
+        app.update();
+        app.update();
+        assert_eq!(building(&app), Some(IntermissionBuilding::Town));
+
+        // The sub-state only exists during the intermission.
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Overworld);
+        app.update();
+        assert_eq!(building(&app), None);
+    }
+}
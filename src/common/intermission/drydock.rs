@@ -0,0 +1,558 @@
+//! # Drydock
+//!
+//! At the Drydock, the player refits the ships of their fleet: they move
+//! items between the holds of their ships and the town's shop stock, and
+//! install parts on, or uninstall them from, the slots of their ships.
+//! Each move is requested with a [MoveItem] event, and answered with an
+//! [ItemMoved] event, or a [MoveRejected] event if it can't be carried out.
+//!
+//! Items taken from the shop stock are bought, at their unit cost, out of
+//! the fleet's [Funds]; items put into it are sold at the same price. The
+//! shop stock is the [Inventory] of the entity marked with [ShopStock], if
+//! the town has one.
+//!
+//! Parts are installed and uninstalled through the construct system (see
+//! [install](crate::common::construct::install)), which the ships' makeups
+//! are kept in sync with. While the player is in the Drydock, every part in
+//! the hold of a fleet ship has a part entity, marked with [DrydockPart],
+//! so that it can be checked against slots with
+//! [can_install](PartInstallQuery::can_install) before it is installed.
+//! Those which are not installed by the time the player leaves are
+//! despawned.
+//!
+//! Items are always moved whole, i.e. the whole stack at once.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Separate buying and selling prices, once the economy exists.
+
+use bevy::{ecs::system::SystemParam, platform::collections::HashSet, prelude::*};
+use slotmap::DefaultKey;
+
+use super::{IntermissionAppExt, IntermissionBuilding};
+use crate::common::{
+    construct::{
+        install::{PartInstallErrorReason, PartInstallQuery, install_part_on_slot, uninstall_part},
+        part::PartInstalledOn,
+        slot::{PartSlotInfo, SlotOfConstruct},
+    },
+    fleet::{Funds, InFleet},
+    inventory::{InventoryDef, ItemType, container::Inventory},
+    makeup::{
+        Ship,
+        sync::{MakeupItem, part_tags, spawn_makeup_part},
+    },
+    player::PlayerControlled,
+};
+
+/// Marks the entity whose [Inventory] is the town's shop stock.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ShopStock;
+
+/// Marks a part entity spawned by the Drydock, for a part in the hold of a
+/// ship.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DrydockPart {
+    /// The ship whose hold the part is in.
+    pub ship: Entity,
+}
+
+/// Something which holds items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ItemHolder {
+    /// The hold of a ship, i.e. its makeup's inventory.
+    Ship(Entity),
+
+    /// A shop stock; see [ShopStock].
+    Stock(Entity),
+}
+
+/// Where an item is moved to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MoveTarget {
+    /// Into something which holds items.
+    Hold(ItemHolder),
+
+    /// Onto a part slot of a ship, installing the item, which must be a
+    /// part, and moving it into the ship's hold first if need be.
+    Slot(Entity),
+}
+
+/// Requests an item to be moved.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct MoveItem {
+    pub from: ItemHolder,
+    pub key: DefaultKey,
+    pub to: MoveTarget,
+}
+
+/// Sent whenever an item is moved.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct ItemMoved {
+    pub from: ItemHolder,
+    pub to: MoveTarget,
+
+    /// The name of the item.
+    pub name: String,
+
+    /// How much was paid for the item if it was bought, or received for it
+    /// if it was sold.
+    pub price: u64,
+}
+
+/// Why an item could not be moved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveRejectedReason {
+    /// There is no such item, or nothing holding it.
+    NoSuchItem,
+
+    /// There is nothing to move the item into.
+    NoSuchTarget,
+
+    /// Only parts can be moved onto slots.
+    NotAPart,
+
+    /// The part does not fit the slot.
+    CannotInstall(PartInstallErrorReason),
+
+    /// Buying the item costs more than the fleet has.
+    InsufficientFunds { price: u64 },
+
+    /// There is no room for the item.
+    NoRoom,
+}
+
+/// Sent when a [MoveItem] request could not be carried out.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MoveRejected {
+    pub request: MoveItem,
+    pub reason: MoveRejectedReason,
+}
+
+/// The price of an item, as bought from or sold to a shop.
+pub fn item_price(item: &InventoryDef) -> u64 {
+    (item.unit_cost as f32 * item.amount).round() as u64
+}
+
+/// The data of part entities the Drydock looks for parts in.
+pub type DrydockPartData = (
+    Entity,
+    &'static MakeupItem,
+    Option<&'static PartInstalledOn>,
+    Option<&'static DrydockPart>,
+);
+
+/// The part entity of a part in the hold of a ship, and whether it is
+/// installed.
+pub fn find_part_entity(
+    parts: &Query<DrydockPartData>,
+    ship: Entity,
+    key: DefaultKey,
+) -> Option<(Entity, bool)> {
+    parts
+        .iter()
+        .find(|(_, item, installed, drydock)| {
+            item.0 == key
+                && (installed.map(|on| on.get()) == Some(ship)
+                    || drydock.map(|part| part.ship) == Some(ship))
+        })
+        .map(|(entity, _, installed, _)| (entity, installed.is_some()))
+}
+
+fn sync_drydock_parts(
+    mut commands: Commands,
+    fleet: Query<(Entity, &Ship), Or<(With<InFleet>, With<PlayerControlled>)>>,
+    ships: Query<&Ship>,
+    parts: Query<DrydockPartData>,
+) {
+    let mut spawned = HashSet::new();
+
+    for (entity, item, installed, drydock) in &parts {
+        if let Some(installed) = installed {
+            spawned.insert((installed.get(), item.0));
+            continue;
+        }
+        let Some(drydock) = drydock else {
+            continue;
+        };
+
+        // Parts which left the hold are no longer needed.
+        let held = ships
+            .get(drydock.ship)
+            .ok()
+            .and_then(|ship| ship.makeup.item(item.0))
+            .is_some_and(|item| matches!(item.item_type, ItemType::Part(_)));
+        if held {
+            spawned.insert((drydock.ship, item.0));
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for (ship_id, ship) in &fleet {
+        let missing = ship
+            .makeup
+            .items()
+            .filter(|(key, item)| {
+                matches!(item.item_type, ItemType::Part(_)) && !spawned.contains(&(ship_id, *key))
+            })
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+
+        for key in missing {
+            if let Some(part) = spawn_makeup_part(&mut commands, &ship.makeup, key) {
+                commands.entity(part).insert(DrydockPart { ship: ship_id });
+            }
+        }
+    }
+}
+
+fn clear_drydock_parts(
+    mut commands: Commands,
+    parts: Query<(Entity, Has<PartInstalledOn>), With<DrydockPart>>,
+) {
+    for (part, installed) in &parts {
+        if installed {
+            commands.entity(part).remove::<DrydockPart>();
+        } else {
+            commands.entity(part).despawn();
+        }
+    }
+}
+
+/// Moves items around the Drydock.
+#[derive(SystemParam)]
+pub struct DrydockItems<'w, 's> {
+    commands: Commands<'w, 's>,
+    ships: Query<'w, 's, &'static mut Ship>,
+    stocks: Query<'w, 's, &'static mut Inventory, With<ShopStock>>,
+    parts: Query<'w, 's, DrydockPartData>,
+    slots: Query<'w, 's, (&'static PartSlotInfo, &'static SlotOfConstruct)>,
+    installs: PartInstallQuery<'w, 's>,
+    funds: ResMut<'w, Funds>,
+}
+
+impl DrydockItems<'_, '_> {
+    /// The part entity of a part in the hold of a ship, and whether it is
+    /// installed.
+    pub fn part_entity(&self, ship: Entity, key: DefaultKey) -> Option<(Entity, bool)> {
+        find_part_entity(&self.parts, ship, key)
+    }
+
+    /// An item, wherever it is held.
+    pub fn item(&self, holder: ItemHolder, key: DefaultKey) -> Option<&InventoryDef> {
+        match holder {
+            ItemHolder::Ship(ship) => self
+                .ships
+                .get(ship)
+                .ok()
+                .and_then(|ship| ship.makeup.item(key)),
+            ItemHolder::Stock(stock) => {
+                self.stocks.get(stock).ok().and_then(|stock| stock.get(key))
+            }
+        }
+    }
+
+    fn take(&mut self, holder: ItemHolder, key: DefaultKey) -> Option<InventoryDef> {
+        match holder {
+            ItemHolder::Ship(ship) => {
+                let mut ship = self.ships.get_mut(ship).ok()?;
+                ship.makeup.take_item(key, f32::INFINITY)
+            }
+            ItemHolder::Stock(stock) => {
+                let mut stock = self.stocks.get_mut(stock).ok()?;
+                stock.take(key, f32::INFINITY)
+            }
+        }
+    }
+
+    /// Stores an item, giving it back if there is no room for it.
+    fn put(&mut self, holder: ItemHolder, item: InventoryDef) -> Result<DefaultKey, InventoryDef> {
+        match holder {
+            ItemHolder::Ship(ship) => match self.ships.get_mut(ship) {
+                Ok(mut ship) => Ok(ship.makeup.add_item(item)),
+                Err(_) => Err(item),
+            },
+            ItemHolder::Stock(stock) => match self.stocks.get_mut(stock) {
+                Ok(mut stock) => stock.add(item),
+                Err(_) => Err(item),
+            },
+        }
+    }
+
+    /// Carries out a move, returning the name of the item moved and the
+    /// price paid or received for it, if it was moved anywhere.
+    pub fn move_item(
+        &mut self,
+        request: MoveItem,
+    ) -> Result<Option<(String, u64)>, MoveRejectedReason> {
+        let MoveItem { from, key, to } = request;
+        let item = self
+            .item(from, key)
+            .cloned()
+            .ok_or(MoveRejectedReason::NoSuchItem)?;
+
+        if to == MoveTarget::Hold(from) {
+            return Ok(None);
+        }
+
+        // Where the item ends up, and the slot to install it on, if any.
+        let (dest, slot) = match to {
+            MoveTarget::Hold(holder) => (holder, None),
+            MoveTarget::Slot(slot) => {
+                let (slot_info, construct) = self.slots.get(slot).map_err(|_| {
+                    MoveRejectedReason::CannotInstall(PartInstallErrorReason::NotASlot)
+                })?;
+                let ItemType::Part(def) = &item.item_type else {
+                    return Err(MoveRejectedReason::NotAPart);
+                };
+                if !part_tags(def).contains(&slot_info.slot_type) {
+                    return Err(MoveRejectedReason::CannotInstall(
+                        PartInstallErrorReason::TagMismatch {
+                            slot_type: slot_info.slot_type.clone(),
+                        },
+                    ));
+                }
+
+                (ItemHolder::Ship(construct.get()), Some(slot))
+            }
+        };
+
+        let source_part = match from {
+            ItemHolder::Ship(ship) => self.part_entity(ship, key),
+            ItemHolder::Stock(_) => None,
+        };
+        if let Some(slot) = slot {
+            match source_part {
+                Some((part, false)) => self
+                    .installs
+                    .can_install(part, slot)
+                    .map_err(MoveRejectedReason::CannotInstall)?,
+                _ if !self.installs.is_vacant(slot) => {
+                    return Err(MoveRejectedReason::CannotInstall(
+                        PartInstallErrorReason::SlotOccupied,
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let price = item_price(&item);
+        let buying = matches!((from, dest), (ItemHolder::Stock(_), ItemHolder::Ship(_)));
+        let selling = matches!((from, dest), (ItemHolder::Ship(_), ItemHolder::Stock(_)));
+        if buying && self.funds.0 < price {
+            return Err(MoveRejectedReason::InsufficientFunds { price });
+        }
+        let has_room = match dest {
+            ItemHolder::Ship(ship) => self.ships.contains(ship).then_some(true),
+            ItemHolder::Stock(stock) => self
+                .stocks
+                .get(stock)
+                .ok()
+                .map(|stock| stock.room_for(&item) >= item.amount),
+        };
+        match has_room {
+            None => return Err(MoveRejectedReason::NoSuchTarget),
+            Some(false) => return Err(MoveRejectedReason::NoRoom),
+            Some(true) => {}
+        }
+
+        // Installed parts are uninstalled before they leave their slot.
+        if let (ItemHolder::Ship(ship), Some((part, true))) = (from, source_part) {
+            uninstall_part(&mut self.commands, part);
+            self.commands.entity(part).insert(DrydockPart { ship });
+        }
+
+        // Moving a part to another slot of the same ship keeps it in the
+        // same hold.
+        if let (true, Some(slot), Some((part, _))) = (dest == from, slot, source_part) {
+            install_part_on_slot(&mut self.commands, part, slot);
+            return Ok(Some((item.name, 0)));
+        }
+
+        let taken = self.take(from, key).ok_or(MoveRejectedReason::NoSuchItem)?;
+        let new_key = match self.put(dest, taken) {
+            Ok(new_key) => new_key,
+            Err(taken) => {
+                // Put it back where it was.
+                let _ = self.put(from, taken);
+                return Err(MoveRejectedReason::NoRoom);
+            }
+        };
+
+        if let (ItemHolder::Ship(ship_id), Some(slot)) = (dest, slot) {
+            let part = self
+                .ships
+                .get(ship_id)
+                .ok()
+                .and_then(|ship| spawn_makeup_part(&mut self.commands, &ship.makeup, new_key));
+            if let Some(part) = part {
+                self.commands
+                    .entity(part)
+                    .insert(DrydockPart { ship: ship_id });
+                install_part_on_slot(&mut self.commands, part, slot);
+            }
+        }
+
+        if buying {
+            self.funds.0 -= price;
+        } else if selling {
+            self.funds.0 += price;
+        }
+
+        Ok(Some((item.name, if buying || selling { price } else { 0 })))
+    }
+}
+
+fn move_items(
+    mut requests: EventReader<MoveItem>,
+    mut drydock: DrydockItems,
+    mut moved: EventWriter<ItemMoved>,
+    mut rejected: EventWriter<MoveRejected>,
+) {
+    for &request in requests.read() {
+        let MoveItem { from, key, to } = request;
+
+        match drydock.move_item(request) {
+            Ok(Some((name, price))) => {
+                moved.write(ItemMoved {
+                    from,
+                    to,
+                    name,
+                    price,
+                });
+            }
+            Ok(None) => {}
+            Err(reason) => {
+                debug!("Could not move item {key:?} from {from:?} to {to:?}: {reason:?}");
+                rejected.write(MoveRejected { request, reason });
+            }
+        }
+    }
+}
+
+/// Refitting ships in the Drydock.
+///
+/// Already included in the [IntermissionPlugin](super::IntermissionPlugin).
+pub struct DrydockPlugin;
+
+impl Plugin for DrydockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Funds>();
+        app.add_event::<MoveItem>();
+        app.add_event::<ItemMoved>();
+        app.add_event::<MoveRejected>();
+        app.add_systems(OnExit(IntermissionBuilding::Drydock), clear_drydock_parts);
+        app.add_building_systems(
+            IntermissionBuilding::Drydock,
+            (sync_drydock_parts, move_items).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::common::{
+        construct::ConstructPlugin,
+        fixtures::{item, makeup},
+        inventory::{FoodDef, container::InventoryCapacity},
+    };
+
+    use super::*;
+
+    fn rations(amount: f32) -> InventoryDef {
+        InventoryDef {
+            unit_cost: 2,
+            ..item(
+                "rations",
+                ItemType::Food(FoodDef { food_points: 10 }),
+                0.1,
+                0.1,
+                amount,
+            )
+        }
+    }
+
+    fn move_item(app: &mut App, request: MoveItem) -> Result<(), MoveRejectedReason> {
+        app.world_mut()
+            .run_system_once(move |mut drydock: DrydockItems| drydock.move_item(request))
+            .unwrap()
+            .map(|_| ())
+    }
+
+    #[test]
+    fn stock_items_are_bought_and_sold() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin));
+        app.insert_resource(Funds(30));
+
+        let ship = app
+            .world_mut()
+            .spawn(Ship {
+                makeup: makeup(vec![]),
+            })
+            .id();
+        let mut inventory = Inventory::new(InventoryCapacity::default());
+        let cheap = inventory.add(rations(10.0)).unwrap();
+        let stock = app.world_mut().spawn((ShopStock, inventory)).id();
+
+        let buy = MoveItem {
+            from: ItemHolder::Stock(stock),
+            key: cheap,
+            to: MoveTarget::Hold(ItemHolder::Ship(ship)),
+        };
+        assert_eq!(move_item(&mut app, buy), Ok(()));
+        assert_eq!(app.world().resource::<Funds>().0, 10);
+        assert!(app.world().get::<Inventory>(stock).unwrap().is_empty());
+
+        // Too expensive.
+        let pricey = app
+            .world_mut()
+            .get_mut::<Inventory>(stock)
+            .unwrap()
+            .add(rations(20.0))
+            .unwrap();
+        assert_eq!(
+            move_item(&mut app, MoveItem { key: pricey, ..buy }),
+            Err(MoveRejectedReason::InsufficientFunds { price: 40 })
+        );
+
+        let (held, _) = app
+            .world()
+            .get::<Ship>(ship)
+            .unwrap()
+            .makeup
+            .items()
+            .next()
+            .unwrap();
+        let sell = MoveItem {
+            from: ItemHolder::Ship(ship),
+            key: held,
+            to: MoveTarget::Hold(ItemHolder::Stock(stock)),
+        };
+        assert_eq!(move_item(&mut app, sell), Ok(()));
+        assert_eq!(app.world().resource::<Funds>().0, 30);
+        assert_eq!(
+            app.world()
+                .get::<Ship>(ship)
+                .unwrap()
+                .makeup
+                .items()
+                .count(),
+            0
+        );
+    }
+}
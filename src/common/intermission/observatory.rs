@@ -0,0 +1,431 @@
+//! # Observatory
+//!
+//! Whenever the intermission begins, the Observatory charts a few islands the
+//! fleet could raid next, as [IslandOffers]. Offers are drawn from the
+//! [WorldSeed] and how many raids the player has been on, so a world always
+//! offers the same islands at the same point of a playthrough.
+//!
+//! Sailing to an island takes days, for which the fleet needs food for its
+//! crew and fuel for its engines; see [IslandOffer::supplies]. Choosing an
+//! island with [ChooseIsland] sets up the [OverworldSceneInitializer] for the
+//! next raid, if the fleet has the supplies to get there, or sends
+//! [InsufficientSupplies] otherwise. Every island raided is recorded in
+//! [VisitedIslands].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{IntermissionAppExt, IntermissionBuilding};
+use crate::common::{
+    crew::{Crew, hunger::FOOD_PER_MEAL},
+    fleet::InFleet,
+    inventory::{EngineDef, FuelType, ItemPartDef, ItemType, PartTypeDef},
+    makeup::Ship,
+    player::PlayerControlled,
+    scene::{
+        biome::IslandBiome,
+        init::{OverworldSceneInitializer, OverworldSceneParams},
+    },
+    seed::{IslandId, WorldRng, WorldSeed},
+    state::GameState,
+};
+
+/// How many islands the Observatory offers each intermission.
+pub const OFFER_COUNT: usize = 4;
+
+/// How many meals each crew member eats per day at sea.
+pub const MEALS_PER_DAY: f32 = 3.0;
+
+/// How long engines run per day at sea, in seconds at full throttle.
+pub const ENGINE_SECONDS_PER_DAY: f32 = 600.0;
+
+/// An island the fleet could raid next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IslandOffer {
+    /// The island to generate.
+    pub island: IslandId,
+
+    /// How many days of sailing the island is away.
+    pub distance_days: u8,
+
+    /// How well defended the island is, from 0 to 255.
+    pub difficulty: u8,
+
+    /// Roughly how much the loot of the island is worth.
+    pub predicted_loot: u32,
+
+    /// The biome of the island.
+    pub biome: IslandBiome,
+
+    /// The size of the island; see [OverworldSceneParams::island_size].
+    pub size: u8,
+}
+
+/// How much of a supply the fleet needs, and how much it has.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SupplyNeed {
+    pub needed: f32,
+    pub available: f32,
+}
+
+impl SupplyNeed {
+    /// Whether there is enough of the supply.
+    pub fn is_met(&self) -> bool {
+        self.available >= self.needed
+    }
+}
+
+/// The supplies the fleet needs to reach an island.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Supplies {
+    /// Food points, for the crew.
+    pub food: SupplyNeed,
+
+    /// Fuel, for each fuel type the fleet's engines burn.
+    pub fuel: Vec<(FuelType, SupplyNeed)>,
+}
+
+impl Supplies {
+    /// Whether the fleet has enough of every supply.
+    pub fn is_met(&self) -> bool {
+        self.food.is_met() && self.fuel.iter().all(|(_, need)| need.is_met())
+    }
+}
+
+/// The fuel type and consumption of every fuel-burning engine installed on
+/// a ship.
+fn engine_consumption(ship: &Ship) -> impl Iterator<Item = (FuelType, u16)> + '_ {
+    let makeup = &ship.makeup;
+    (0..makeup.make().slots.len())
+        .filter_map(|idx| makeup.installed(idx))
+        .filter_map(|key| makeup.item(key))
+        .filter_map(|item| match &item.item_type {
+            ItemType::Part(ItemPartDef {
+                part_type:
+                    PartTypeDef::Engine(EngineDef {
+                        fuel_type: Some(fuel_type),
+                        fuel_consumption,
+                        ..
+                    }),
+                ..
+            }) => Some((*fuel_type, *fuel_consumption)),
+            _ => None,
+        })
+}
+
+impl IslandOffer {
+    /// Charts a random island.
+    ///
+    /// Islands get more dangerous the further away they are, and the more
+    /// raids the player has been on.
+    pub fn generate(rng: &mut WorldRng, raids: u32) -> Self {
+        let distance_days: u8 = rng.random_range(1..=6);
+        let danger = (raids * 12 + distance_days as u32 * 8).min(200);
+
+        let mut offer = Self {
+            island: IslandId(rng.random()),
+            distance_days,
+            difficulty: (danger + rng.random_range(0..=55)) as u8,
+            predicted_loot: 0,
+            biome: match rng.random_range(0..3) {
+                0 => IslandBiome::Tropical,
+                1 => IslandBiome::RockyNorth,
+                _ => IslandBiome::Volcanic,
+            },
+            size: rng.random_range(16..=96),
+        };
+
+        // Observatories are not always right.
+        let params = offer.params();
+        let worth = params.spawn_unarmed as u32 * 20
+            + params.spawn_armed as u32 * 60
+            + params.prop_defense as u32 * 10;
+        offer.predicted_loot = worth * rng.random_range(80..=120) / 100;
+
+        offer
+    }
+
+    /// The parameters to generate the island with.
+    pub fn params(&self) -> OverworldSceneParams {
+        OverworldSceneParams {
+            island_size: self.size,
+            biome: self.biome,
+            prop_defense: self.difficulty / 8,
+            patrol_paths: 1 + self.difficulty / 64,
+            spawn_unarmed: 10 + self.size / 4,
+            spawn_armed: 1 + self.difficulty / 24,
+            patrol_occupancy: self.difficulty,
+            ..default()
+        }
+    }
+
+    /// The supplies a fleet needs to reach the island, and how much of
+    /// them it has.
+    pub fn supplies<'a>(
+        &self,
+        fleet: impl IntoIterator<Item = (&'a Ship, Option<&'a Crew>)>,
+    ) -> Supplies {
+        let fleet: Vec<_> = fleet.into_iter().collect();
+        let days = self.distance_days as f32;
+
+        let crew_size: usize = fleet
+            .iter()
+            .filter_map(|(_, crew)| *crew)
+            .map(|crew| crew.members.len())
+            .sum();
+        let food = SupplyNeed {
+            needed: crew_size as f32 * FOOD_PER_MEAL * MEALS_PER_DAY * days,
+            available: fleet.iter().map(|(ship, _)| ship.makeup.food_left()).sum(),
+        };
+
+        let mut fuel: Vec<(FuelType, SupplyNeed)> = vec![];
+        for (fuel_type, consumption) in fleet.iter().flat_map(|(ship, _)| engine_consumption(ship))
+        {
+            let needed = consumption as f32 / 1000.0 * ENGINE_SECONDS_PER_DAY * days;
+            match fuel.iter_mut().find(|(other, _)| *other == fuel_type) {
+                Some((_, need)) => need.needed += needed,
+                None => fuel.push((
+                    fuel_type,
+                    SupplyNeed {
+                        needed,
+                        available: 0.0,
+                    },
+                )),
+            }
+        }
+
+        // Fuel can be moved between ships of the fleet, so it is pooled.
+        for (fuel_type, need) in &mut fuel {
+            need.available = fleet
+                .iter()
+                .map(|(ship, _)| ship.makeup.fuel_left(*fuel_type))
+                .sum();
+        }
+
+        Supplies { food, fuel }
+    }
+}
+
+/// How many raids the player has been on.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RaidCount(pub u32);
+
+/// Every island the player has raided, in order.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VisitedIslands(pub Vec<IslandId>);
+
+/// The islands the Observatory offers this intermission.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct IslandOffers(pub Vec<IslandOffer>);
+
+impl IslandOffers {
+    /// Charts the islands offered after a number of raids.
+    pub fn generate(seed: &WorldSeed, raids: u32) -> Self {
+        let mut rng = seed.island(raids).rng("offers");
+        Self(
+            (0..OFFER_COUNT)
+                .map(|_| IslandOffer::generate(&mut rng, raids))
+                .collect(),
+        )
+    }
+}
+
+/// Requests to raid one of the [IslandOffers] next, by index.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChooseIsland(pub usize);
+
+/// Sent when an island was chosen, and the next raid set up for it.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct IslandChosen(pub IslandOffer);
+
+/// Sent when the fleet lacks the supplies to reach a chosen island.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct InsufficientSupplies {
+    /// The index of the offer.
+    pub offer: usize,
+
+    /// What the fleet needs, and has.
+    pub supplies: Supplies,
+}
+
+fn count_raids(mut raids: ResMut<RaidCount>) {
+    raids.0 += 1;
+}
+
+fn record_visit(
+    mut visited: ResMut<VisitedIslands>,
+    initializer: Res<OverworldSceneInitializer>,
+    seed: Res<WorldSeed>,
+) {
+    visited
+        .0
+        .push(initializer.island.unwrap_or_else(|| seed.island(0)));
+}
+
+fn generate_island_offers(mut commands: Commands, seed: Res<WorldSeed>, raids: Res<RaidCount>) {
+    commands.insert_resource(IslandOffers::generate(&seed, raids.0));
+}
+
+fn choose_island(
+    mut choices: EventReader<ChooseIsland>,
+    offers: Res<IslandOffers>,
+    fleet: Query<(&Ship, Option<&Crew>), Or<(With<InFleet>, With<PlayerControlled>)>>,
+    mut initializer: ResMut<OverworldSceneInitializer>,
+    mut chosen: EventWriter<IslandChosen>,
+    mut insufficient: EventWriter<InsufficientSupplies>,
+) {
+    for &ChooseIsland(idx) in choices.read() {
+        let Some(offer) = offers.0.get(idx) else {
+            warn!(
+                "Tried to choose island offer {idx}, but there are only {}",
+                offers.0.len()
+            );
+            continue;
+        };
+
+        let supplies = offer.supplies(fleet.iter());
+        if !supplies.is_met() {
+            insufficient.write(InsufficientSupplies {
+                offer: idx,
+                supplies,
+            });
+            continue;
+        }
+
+        info!("Chose island {} for the next raid", offer.island);
+        initializer.params = offer.params();
+        initializer.island = Some(offer.island);
+        chosen.write(IslandChosen(offer.clone()));
+    }
+}
+
+/// Island offers in the Observatory.
+///
+/// Already included in the [IntermissionPlugin](super::IntermissionPlugin).
+pub struct ObservatoryPlugin;
+
+impl Plugin for ObservatoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RaidCount>();
+        app.init_resource::<VisitedIslands>();
+        app.init_resource::<IslandOffers>();
+        app.init_resource::<WorldSeed>();
+        app.init_resource::<OverworldSceneInitializer>();
+        app.add_event::<ChooseIsland>();
+        app.add_event::<IslandChosen>();
+        app.add_event::<InsufficientSupplies>();
+        app.add_systems(OnEnter(GameState::Overworld), record_visit);
+        app.add_systems(OnExit(GameState::Overworld), count_raids);
+        app.add_systems(OnEnter(GameState::Intermission), generate_island_offers);
+        app.add_building_systems(IntermissionBuilding::Observatory, choose_island);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+
+    use crate::common::{
+        crew::CrewMember,
+        fixtures::{item, makeup},
+        intermission::{EnterBuilding, IntermissionPlugin, TownBuildings},
+        inventory::{FoodDef, InventoryDef},
+    };
+
+    use super::*;
+
+    fn rations(amount: f32) -> InventoryDef {
+        item(
+            "rations",
+            ItemType::Food(FoodDef { food_points: 10 }),
+            0.1,
+            0.1,
+            amount,
+        )
+    }
+
+    #[test]
+    fn offers_are_reproducible() {
+        let seed = WorldSeed(0xD0_0D);
+        assert_eq!(
+            IslandOffers::generate(&seed, 2),
+            IslandOffers::generate(&seed, 2)
+        );
+        assert_ne!(
+            IslandOffers::generate(&seed, 2),
+            IslandOffers::generate(&seed, 3)
+        );
+    }
+
+    #[test]
+    fn islands_are_chosen_only_with_enough_supplies() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.init_state::<GameState>();
+        app.add_plugins(IntermissionPlugin);
+        app.insert_resource(WorldSeed(0xD0_0D));
+        app.insert_resource(TownBuildings::new([IntermissionBuilding::Observatory]));
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                PlayerControlled,
+                Ship {
+                    makeup: makeup(vec![]),
+                },
+                Crew {
+                    members: vec![CrewMember::new("Ada", 5, 5)],
+                    ..default()
+                },
+            ))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Intermission);
+        app.update();
+        app.world_mut()
+            .send_event(EnterBuilding(IntermissionBuilding::Observatory));
+        app.update();
+        app.update();
+
+        let offer = app.world().resource::<IslandOffers>().0[0].clone();
+        assert_eq!(offer, IslandOffers::generate(&WorldSeed(0xD0_0D), 0).0[0],);
+
+        // No food on board.
+        app.world_mut().send_event(ChooseIsland(0));
+        app.update();
+        assert_eq!(
+            app.world().resource::<Events<InsufficientSupplies>>().len(),
+            1
+        );
+        assert_eq!(
+            app.world().resource::<OverworldSceneInitializer>().island,
+            None
+        );
+
+        app.world_mut()
+            .get_mut::<Ship>(ship)
+            .unwrap()
+            .makeup
+            .add_item(rations(100.0));
+        app.world_mut().send_event(ChooseIsland(0));
+        app.update();
+        let initializer = app.world().resource::<OverworldSceneInitializer>();
+        assert_eq!(initializer.island, Some(offer.island));
+        assert_eq!(initializer.params.island_size, offer.size);
+    }
+}
@@ -0,0 +1,397 @@
+//! # Inventories
+//!
+//! Any construct can hold items in an [Inventory]. Fungible items (see
+//! [InventoryDef::is_fungible]) stack together by amount; others, like parts,
+//! are each kept apart.
+//!
+//! Inventories may be limited in how much mass and volume they can hold (see
+//! [InventoryCapacity]). The mass of their items weighs their construct down,
+//! adding to the mass of its [PointNetwork].
+//!
+//! Items are moved between inventories, e.g. from ship to ship, or from ship
+//! to shop, with [transfer_items]; an [ItemsTransferred] event is sent for
+//! each transfer. Only as much as there is room for is moved.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use slotmap::{DefaultKey, SlotMap};
+
+use super::InventoryDef;
+use crate::common::{
+    error::{Context, LnrError},
+    physics::base::PointNetwork,
+};
+
+/// How much an [Inventory] can hold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InventoryCapacity {
+    /// The most total mass of items.
+    pub max_mass: f32,
+
+    /// The most total volume of items, in cubic meters.
+    pub max_volume: f32,
+}
+
+impl Default for InventoryCapacity {
+    /// No limits.
+    fn default() -> Self {
+        Self {
+            max_mass: f32::INFINITY,
+            max_volume: f32::INFINITY,
+        }
+    }
+}
+
+/// The items held by a construct.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Inventory {
+    /// The items.
+    items: SlotMap<DefaultKey, InventoryDef>,
+
+    /// How much this inventory can hold.
+    pub capacity: InventoryCapacity,
+
+    /// How much of the mass of items was last added to the construct's
+    /// [PointNetwork].
+    applied_mass: f32,
+}
+
+impl Inventory {
+    /// An empty inventory, which can hold up to a capacity.
+    pub fn new(capacity: InventoryCapacity) -> Self {
+        Self {
+            capacity,
+            ..default()
+        }
+    }
+
+    /// An item in this inventory.
+    pub fn get(&self, key: DefaultKey) -> Option<&InventoryDef> {
+        self.items.get(key)
+    }
+
+    /// Iterate on all items in this inventory.
+    pub fn iter(&self) -> impl Iterator<Item = (DefaultKey, &InventoryDef)> {
+        self.items.iter()
+    }
+
+    /// Whether this inventory has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The total mass of all items.
+    pub fn total_mass(&self) -> f32 {
+        self.items
+            .values()
+            .map(|item| item.mass * item.amount)
+            .sum()
+    }
+
+    /// The total volume of all items, in cubic meters.
+    pub fn total_volume(&self) -> f32 {
+        self.items
+            .values()
+            .map(|item| item.volume * item.amount)
+            .sum()
+    }
+
+    /// How many units of an item there is room left for.
+    pub fn room_for(&self, item: &InventoryDef) -> f32 {
+        let by_mass = room_by(self.capacity.max_mass - self.total_mass(), item.mass);
+        let by_volume = room_by(self.capacity.max_volume - self.total_volume(), item.volume);
+
+        by_mass.min(by_volume)
+    }
+
+    /// Stores an item, stacking it onto a matching one if there is any.
+    ///
+    /// Returns where the item went, or gives it back if there is no room for
+    /// all of it.
+    pub fn add(&mut self, item: InventoryDef) -> Result<DefaultKey, InventoryDef> {
+        if self.room_for(&item) < item.amount {
+            return Err(item);
+        }
+
+        let stack = self
+            .items
+            .iter_mut()
+            .find(|(_, other)| other.stacks_with(&item));
+
+        match stack {
+            Some((key, stack)) => {
+                stack.amount += item.amount;
+                Ok(key)
+            }
+            None => Ok(self.items.insert(item)),
+        }
+    }
+
+    /// Takes up to an amount of an item out of this inventory.
+    ///
+    /// Fungible items are split off; others are only ever taken whole.
+    /// Items which run out are removed.
+    pub fn take(&mut self, key: DefaultKey, amount: f32) -> Option<InventoryDef> {
+        let item = self.items.get_mut(key)?;
+
+        if !item.is_fungible() || amount >= item.amount {
+            return self.items.remove(key);
+        }
+
+        if amount <= 0.0 {
+            return None;
+        }
+
+        item.amount -= amount;
+        Some(InventoryDef {
+            amount,
+            ..item.clone()
+        })
+    }
+
+    /// Removes an item entirely.
+    pub fn remove(&mut self, key: DefaultKey) -> Option<InventoryDef> {
+        self.items.remove(key)
+    }
+}
+
+/// How many units of something fit in the room left, by one measure.
+fn room_by(room: f32, per_unit: f32) -> f32 {
+    if per_unit > 0.0 {
+        (room / per_unit).max(0.0)
+    } else {
+        f32::INFINITY
+    }
+}
+
+/// Request to move items from one inventory to another.
+///
+/// This event must be targeted on the construct to take the items from.
+///
+/// Only as much as there is room for is moved, if any. Fails if either
+/// construct has no [Inventory], or there is no such item.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TransferItems {
+    /// The construct to give the items to.
+    pub to: Entity,
+
+    /// The item to move.
+    pub key: DefaultKey,
+
+    /// How much of it to move.
+    pub amount: f32,
+}
+
+/// Sent whenever items are moved between inventories.
+#[derive(Event, Clone, Debug)]
+pub struct ItemsTransferred {
+    /// The construct the items were taken from.
+    pub from: Entity,
+
+    /// The construct the items were given to.
+    pub to: Entity,
+
+    /// Where the items went in the inventory they were given to.
+    pub key: DefaultKey,
+
+    /// The name of the items.
+    pub name: String,
+
+    /// How much was moved.
+    pub amount: f32,
+}
+
+/// Request the moving of items between inventories.
+///
+/// Wraps around [TransferItems].
+pub fn transfer_items(
+    commands: &mut Commands,
+    from: Entity,
+    to: Entity,
+    key: DefaultKey,
+    amount: f32,
+) {
+    commands
+        .entity(from)
+        .trigger(TransferItems { to, key, amount });
+}
+
+// Observer
+pub fn obs_transfer_items(
+    trigger: Trigger<TransferItems>,
+    mut inventories: Query<&mut Inventory>,
+    mut transferred: EventWriter<ItemsTransferred>,
+) -> Result {
+    let from = trigger.target();
+    let TransferItems { to, key, amount } = *trigger.event();
+
+    let [mut source, mut dest] = inventories
+        .get_many_mut([from, to])
+        .context("transferring items")?;
+
+    let item = source.get(key).ok_or_else(|| {
+        LnrError::invalid_state(format!(
+            "Tried to transfer missing item {key:?} from {from}"
+        ))
+    })?;
+
+    // As much as there is room for; parts only ever go whole.
+    let room = dest.room_for(item);
+    let moved = if item.is_fungible() {
+        amount.min(item.amount).min(room)
+    } else if room >= item.amount {
+        item.amount
+    } else {
+        0.0
+    };
+    if moved <= 0.0 {
+        return Ok(());
+    }
+
+    let Some(item) = source.take(key, moved) else {
+        return Ok(());
+    };
+    let name = item.name.clone();
+    let key = match dest.add(item) {
+        Ok(key) => key,
+        Err(item) => {
+            // Put it back where it was.
+            let _ = source.add(item);
+            return Ok(());
+        }
+    };
+
+    transferred.write(ItemsTransferred {
+        from,
+        to,
+        key,
+        name,
+        amount: moved,
+    });
+
+    Ok(())
+}
+
+/// Adds the mass of inventories to their constructs' physics.
+fn apply_inventory_mass(
+    mut constructs: Query<(&mut Inventory, &mut PointNetwork), Changed<Inventory>>,
+) {
+    for (mut inventory, mut network) in &mut constructs {
+        let mass = inventory.total_mass();
+        if mass == inventory.applied_mass {
+            continue;
+        }
+
+        let old_total = network.points.iter().map(|point| point.mass).sum::<f32>();
+        let new_total = old_total - inventory.applied_mass + mass;
+
+        if old_total > 0.0 && new_total > 0.0 {
+            let scale = new_total / old_total;
+            for point in &mut network.points {
+                point.mass *= scale;
+            }
+        }
+
+        inventory.bypass_change_detection().applied_mass = mass;
+    }
+}
+
+/// Inventory plugin.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ItemsTransferred>();
+        app.add_observer(obs_transfer_items);
+        app.add_systems(FixedUpdate, apply_inventory_mass);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        fixtures::item,
+        inventory::{FoodDef, InventoryDef, ItemType},
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    fn biscuits(amount: f32) -> InventoryDef {
+        item(
+            "biscuits",
+            ItemType::Food(FoodDef { food_points: 5 }),
+            1.0,
+            0.5,
+            amount,
+        )
+    }
+
+    #[test]
+    fn transfers_stack_within_capacity() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InventoryPlugin));
+
+        let mut hold = Inventory::default();
+        let key = hold.add(biscuits(10.0)).unwrap();
+        assert_eq!(hold.add(biscuits(5.0)).ok(), Some(key));
+
+        // The shop has room for 8 biscuits by volume, and already has 2.
+        let mut shelf = Inventory::new(InventoryCapacity {
+            max_mass: 100.0,
+            max_volume: 4.0,
+        });
+        shelf.add(biscuits(2.0)).unwrap();
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                hold,
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 10.0)].into_iter()),
+            ))
+            .id();
+        let shop = app.world_mut().spawn(shelf).id();
+
+        let transfer = |app: &mut App, amount: f32| {
+            let mut commands = app.world_mut().commands();
+            transfer_items(&mut commands, ship, shop, key, amount);
+            app.world_mut().flush();
+        };
+
+        app.world_mut().run_schedule(FixedUpdate);
+        let mass = |app: &App| app.world().get::<PointNetwork>(ship).unwrap().points[0].mass;
+        assert_eq!(mass(&app), 25.0);
+
+        transfer(&mut app, 6.0);
+        transfer(&mut app, 6.0);
+        app.world_mut().run_schedule(FixedUpdate);
+
+        // Only 6 fit, so the second transfer did not move anything.
+        let amounts = |app: &App, entity| {
+            let inventory = app.world().get::<Inventory>(entity).unwrap();
+            inventory
+                .iter()
+                .map(|(_, item)| item.amount)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(amounts(&app, ship), [9.0]);
+        assert_eq!(amounts(&app, shop), [8.0]);
+        assert_eq!(mass(&app), 19.0);
+    }
+}
@@ -17,6 +17,15 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
+use serde::{Deserialize, Serialize};
+
+use self::modifier::ProjectileModifier;
+
+pub mod container; // Inventories of constructs, and moving items between them
+pub mod modifier; // Projectile modifiers, and what they do
+pub mod pickup; // Items dropped at sea, and picking them up
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CannonDef {
     /// The minimum amount of power with which to launch a cannonball.
     pub min_power: f32,
@@ -34,6 +43,7 @@ pub struct CannonDef {
     pub caliber: u8,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BallistaDef {
     /// The power with which to fire a ballista bolt.
     pub power: f32,
@@ -48,6 +58,7 @@ pub struct BallistaDef {
     pub fire_rate: u16,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MinelayerDef {
     /// The power with which to launch a mine backward.
     pub power: f32,
@@ -56,16 +67,19 @@ pub struct MinelayerDef {
     pub fire_rate: u16,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum GunTypeDef {
     Cannon(CannonDef),
     Ballista(BallistaDef),
     Minelayer(MinelayerDef),
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GunDef {
     pub gun_type: GunTypeDef,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EngineDef {
     /// The type of fuel used by this engine.
     ///
@@ -79,18 +93,25 @@ pub struct EngineDef {
     pub fuel_consumption: u16,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ArmorDef {
     pub defense_factor: u8,
     pub wear_factor: u8,
     pub deflect_factor: u8,
     pub overwhelm_factor: u8,
+
+    /// How worn this armor is, between 0.0 (pristine) and 1.0 (useless).
+    #[serde(default)]
+    pub wear: f32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct VacuumDef {
     pub suck_radius: f32,
     pub suck_strength: f32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PartTypeDef {
     Gun(GunDef),
     Engine(EngineDef),
@@ -98,35 +119,42 @@ pub enum PartTypeDef {
     Armor(ArmorDef),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ManningType {
     Unmanned,
     AnyManned,
     StrengthManned(u8),
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ItemPartDef {
     pub part_type: PartTypeDef,
     pub manned: ManningType,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FoodDef {
     pub food_points: u8,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FuelType {
     Coal,
     Diesel,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FuelDef {
     pub fuel_type: FuelType,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CannonballDef {
     /// Cannonball caliber, in tenths of millimeters.
     pub caliber: u8,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GrenadeDef {
     /// Fuse length, in centiseconds.
     pub fuse_time: u16,
@@ -135,6 +163,7 @@ pub struct GrenadeDef {
     pub power: f32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MineDef {
     /// Proximity detection range.
     pub trigger_range: f32,
@@ -143,6 +172,7 @@ pub struct MineDef {
     pub power: f32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum AmmoType {
     Cannonball(CannonballDef),
     BallistaBolt,
@@ -150,12 +180,16 @@ pub enum AmmoType {
     NavalMine(MineDef),
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AmmoDef {
     pub ammo_type: AmmoType,
-    // [TODO] Projectile modifier list, to implement in a submodule.
-    // pub modifiers: Vec<ProjectileModifier>,
+
+    /// Modifiers of the projectiles fired from this ammunition.
+    #[serde(default)]
+    pub modifiers: Vec<ProjectileModifier>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ItemType {
     Part(ItemPartDef),
     Food(FoodDef),
@@ -164,6 +198,7 @@ pub enum ItemType {
 }
 
 /// An inventory item definition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InventoryDef {
     pub item_type: ItemType,
     pub name: String,
@@ -173,6 +208,36 @@ pub struct InventoryDef {
     pub vulnerability: u8,
     pub repair_cost_scale: u16,
 
+    /// Volume of each unit of this item, in cubic meters.
+    pub volume: f32,
+
     /// Amount of this item.
     pub amount: f32,
 }
+
+impl InventoryDef {
+    /// Whether this item is fungible, i.e. any amount of it is as good as
+    /// any other, like food, fuel and ammunition, but not parts.
+    pub fn is_fungible(&self) -> bool {
+        !matches!(self.item_type, ItemType::Part(_))
+    }
+
+    /// Whether this item can be stacked together with another, i.e. they
+    /// are both the same fungible item, with the same modifiers if any.
+    pub fn stacks_with(&self, other: &InventoryDef) -> bool {
+        self.is_fungible()
+            && other.is_fungible()
+            && self.name == other.name
+            && self.mass == other.mass
+            && self.volume == other.volume
+            && self.modifiers() == other.modifiers()
+    }
+
+    /// The projectile modifiers of this item, if it is ammunition.
+    pub fn modifiers(&self) -> &[ProjectileModifier] {
+        match &self.item_type {
+            ItemType::Ammo(ammo) => &ammo.modifiers,
+            _ => &[],
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! # Projectile modifiers
+//!
+//! Ammunition may carry [ProjectileModifier]s, such as incendiary charges or
+//! propeller gum, which are passed on to the projectiles fired from it as a
+//! [ProjectileModifiers] component.
+//!
+//! What each modifier does is up to its [ModifierBehavior], in the
+//! [ModifierRegistry]: it may act on the projectile every tick it is in
+//! flight, and on whatever it hits. Modifiers compose; a projectile with
+//! several of them has all of their behaviors.
+//!
+//! Ammunition with modifiers is picked by naming them in ammunition
+//! selectors, e.g. `"cannonball 40mm incendiary"` (see
+//! [FireWeaponCommand](crate::common::construct::action::FireWeaponCommand)).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{
+    damage::{DamageEvent, DamageType, ProjectileHit},
+    physics::base::PointNetwork,
+};
+
+/// How long targets hit by incendiary projectiles burn, in seconds.
+const BURN_TIME: f32 = 8.0;
+
+/// Fire damage per second, as a fraction of the incendiary hit's damage.
+const BURN_DAMAGE_FACTOR: f32 = 0.05;
+
+/// How long propeller gum slows down engines, in seconds.
+const GUM_TIME: f32 = 12.0;
+
+/// How much of their thrust gummed up engines still deliver.
+pub const GUM_THRUST_FACTOR: f32 = 0.4;
+
+/// How long smoke clouds last, in seconds.
+const SMOKE_TIME: f32 = 15.0;
+
+/// The radius of smoke clouds.
+const SMOKE_RADIUS: f32 = 8.0;
+
+/// Drag of chained shot in flight, as the fraction of its speed lost per
+/// second.
+const CHAIN_DRAG: f32 = 0.15;
+
+/// Extra damage dealt by chained shot, as a fraction of the hit's damage.
+const CHAIN_DAMAGE_FACTOR: f32 = 0.5;
+
+/// A modifier of projectiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProjectileModifier {
+    /// Sets whatever it hits on fire.
+    Incendiary,
+
+    /// Gums up the engines of whatever it hits.
+    PropellerGum,
+
+    /// Leaves a cloud of smoke where it hits.
+    Smoke,
+
+    /// Two shots chained together, which tear through rigging, but slow
+    /// down quickly.
+    ChainedShot,
+}
+
+impl ProjectileModifier {
+    /// The keyword which names this modifier in ammunition selectors.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ProjectileModifier::Incendiary => "incendiary",
+            ProjectileModifier::PropellerGum => "propeller_gum",
+            ProjectileModifier::Smoke => "smoke",
+            ProjectileModifier::ChainedShot => "chained_shot",
+        }
+    }
+}
+
+/// The modifiers of a projectile in flight.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ProjectileModifiers(pub Vec<ProjectileModifier>);
+
+/// A projectile with modifiers hitting something.
+#[derive(Clone, Copy, Debug)]
+pub struct ModifierHit {
+    /// The object which fired the projectile, if any.
+    pub source: Option<Entity>,
+
+    /// The object hit.
+    pub target: Entity,
+
+    /// Where it was hit, in world space.
+    pub at: Vec3,
+
+    /// How much damage the hit itself dealt, before armor.
+    pub damage: f32,
+}
+
+/// What a [ProjectileModifier] does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModifierBehavior {
+    /// Called on the projectile's points every tick it is in flight, with the
+    /// tick's duration in seconds.
+    pub on_flight: Option<fn(&mut PointNetwork, f32)>,
+
+    /// Called whenever the projectile hits something.
+    pub on_hit: Option<fn(&ModifierHit, &mut Commands)>,
+}
+
+/// The behaviors of all projectile modifiers.
+#[derive(Resource, Clone, Debug)]
+pub struct ModifierRegistry {
+    behaviors: HashMap<ProjectileModifier, ModifierBehavior>,
+}
+
+impl Default for ModifierRegistry {
+    /// The built-in behaviors.
+    fn default() -> Self {
+        let mut registry = Self {
+            behaviors: HashMap::default(),
+        };
+
+        registry.register(
+            ProjectileModifier::Incendiary,
+            ModifierBehavior {
+                on_flight: None,
+                on_hit: Some(ignite),
+            },
+        );
+        registry.register(
+            ProjectileModifier::PropellerGum,
+            ModifierBehavior {
+                on_flight: None,
+                on_hit: Some(gum_up),
+            },
+        );
+        registry.register(
+            ProjectileModifier::Smoke,
+            ModifierBehavior {
+                on_flight: None,
+                on_hit: Some(release_smoke),
+            },
+        );
+        registry.register(
+            ProjectileModifier::ChainedShot,
+            ModifierBehavior {
+                on_flight: Some(chain_drag),
+                on_hit: Some(tear_rigging),
+            },
+        );
+
+        registry
+    }
+}
+
+impl ModifierRegistry {
+    /// Sets the behavior of a modifier, replacing any it had.
+    pub fn register(&mut self, modifier: ProjectileModifier, behavior: ModifierBehavior) {
+        self.behaviors.insert(modifier, behavior);
+    }
+
+    /// The behavior of a modifier, if it has any.
+    pub fn get(&self, modifier: ProjectileModifier) -> Option<&ModifierBehavior> {
+        self.behaviors.get(&modifier)
+    }
+}
+
+/// Sets a construct on fire, dealing damage over time.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Burning {
+    /// Who set it on fire, if anyone.
+    pub source: Option<Entity>,
+
+    /// Damage dealt per second.
+    pub damage_per_sec: f32,
+
+    /// How long it keeps burning, in seconds.
+    pub time_left: f32,
+}
+
+/// Gums up a construct's engines, which deliver only part of their thrust
+/// (see [GUM_THRUST_FACTOR]).
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Gummed {
+    /// How long the engines stay gummed up, in seconds.
+    pub time_left: f32,
+}
+
+/// A cloud of smoke, which blocks sight.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct SmokeCloud {
+    /// The center of the cloud, in world space.
+    pub center: Vec3,
+
+    /// The radius of the cloud.
+    pub radius: f32,
+
+    /// How long the cloud lasts, in seconds.
+    pub time_left: f32,
+}
+
+fn ignite(hit: &ModifierHit, commands: &mut Commands) {
+    commands.entity(hit.target).try_insert(Burning {
+        source: hit.source,
+        damage_per_sec: hit.damage * BURN_DAMAGE_FACTOR,
+        time_left: BURN_TIME,
+    });
+}
+
+fn gum_up(hit: &ModifierHit, commands: &mut Commands) {
+    commands.entity(hit.target).try_insert(Gummed {
+        time_left: GUM_TIME,
+    });
+}
+
+fn release_smoke(hit: &ModifierHit, commands: &mut Commands) {
+    commands.spawn(SmokeCloud {
+        center: hit.at,
+        radius: SMOKE_RADIUS,
+        time_left: SMOKE_TIME,
+    });
+}
+
+fn chain_drag(network: &mut PointNetwork, delta_secs: f32) {
+    let keep = (1.0 - CHAIN_DRAG * delta_secs).max(0.0);
+
+    for point in &mut network.points {
+        point.vel *= keep;
+    }
+}
+
+fn tear_rigging(hit: &ModifierHit, commands: &mut Commands) {
+    commands.send_event(DamageEvent {
+        source: hit.source,
+        target: hit.target,
+        amount: hit.damage * CHAIN_DAMAGE_FACTOR,
+        damage_type: DamageType::Impact,
+        at: hit.at,
+        direction: None,
+    });
+}
+
+fn apply_flight_modifiers(
+    time: Res<Time>,
+    registry: Res<ModifierRegistry>,
+    mut projectiles: Query<(&ProjectileModifiers, &mut PointNetwork)>,
+) {
+    let delta_secs = time.delta_secs();
+
+    for (modifiers, mut network) in &mut projectiles {
+        for modifier in &modifiers.0 {
+            if let Some(on_flight) = registry
+                .get(*modifier)
+                .and_then(|behavior| behavior.on_flight)
+            {
+                on_flight(&mut network, delta_secs);
+            }
+        }
+    }
+}
+
+fn apply_hit_modifiers(
+    mut commands: Commands,
+    registry: Res<ModifierRegistry>,
+    mut hits: EventReader<ProjectileHit>,
+) {
+    for hit in hits.read() {
+        let modifier_hit = ModifierHit {
+            source: hit.source,
+            target: hit.target,
+            at: hit.at,
+            damage: hit.damage,
+        };
+
+        for modifier in &hit.modifiers.0 {
+            if let Some(on_hit) = registry.get(*modifier).and_then(|behavior| behavior.on_hit) {
+                on_hit(&modifier_hit, &mut commands);
+            }
+        }
+    }
+}
+
+fn burn(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut burning: Query<(Entity, &mut Burning, Option<&PointNetwork>)>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let delta_secs = time.delta_secs();
+
+    for (entity, mut fire, network) in &mut burning {
+        damage.write(DamageEvent {
+            source: fire.source,
+            target: entity,
+            amount: fire.damage_per_sec * delta_secs,
+            damage_type: DamageType::Fire,
+            at: network.map_or(Vec3::ZERO, PointNetwork::center_of_mass),
+            direction: None,
+        });
+
+        fire.time_left -= delta_secs;
+        if fire.time_left <= 0.0 {
+            commands.entity(entity).remove::<Burning>();
+        }
+    }
+}
+
+fn wear_off_gum(time: Res<Time>, mut commands: Commands, mut gummed: Query<(Entity, &mut Gummed)>) {
+    for (entity, mut gum) in &mut gummed {
+        gum.time_left -= time.delta_secs();
+
+        if gum.time_left <= 0.0 {
+            commands.entity(entity).remove::<Gummed>();
+        }
+    }
+}
+
+fn clear_smoke(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut clouds: Query<(Entity, &mut SmokeCloud)>,
+) {
+    for (entity, mut cloud) in &mut clouds {
+        cloud.time_left -= time.delta_secs();
+
+        if cloud.time_left <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Projectile modifier plugin.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct ModifierPlugin;
+
+impl Plugin for ModifierPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ModifierRegistry>();
+        app.add_event::<ProjectileHit>();
+        app.add_event::<DamageEvent>();
+        app.add_systems(
+            FixedUpdate,
+            (
+                apply_flight_modifiers,
+                apply_hit_modifiers,
+                burn,
+                wear_off_gum,
+                clear_smoke,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::{
+        fixtures::{item, makeup},
+        inventory::{AmmoDef, AmmoType, CannonballDef, ItemType},
+        physics::base::PhysPoint,
+    };
+
+    use super::*;
+
+    #[test]
+    fn modifiers_act_on_hit_and_in_flight() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ModifierPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        let target = app.world_mut().spawn_empty().id();
+        let projectile = app
+            .world_mut()
+            .spawn((
+                ProjectileModifiers(vec![ProjectileModifier::ChainedShot]),
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::X * 10.0, 1.0)].into_iter()),
+            ))
+            .id();
+
+        app.world_mut().send_event(ProjectileHit {
+            source: None,
+            target,
+            at: Vec3::ZERO,
+            damage: 20.0,
+            modifiers: ProjectileModifiers(vec![
+                ProjectileModifier::Incendiary,
+                ProjectileModifier::PropellerGum,
+                ProjectileModifier::Smoke,
+            ]),
+        });
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let fire = app.world().get::<Burning>(target).unwrap();
+        assert_eq!(fire.damage_per_sec, 20.0 * BURN_DAMAGE_FACTOR);
+        assert!(app.world().get::<Gummed>(target).is_some());
+        assert_eq!(
+            app.world_mut()
+                .query::<&SmokeCloud>()
+                .iter(app.world())
+                .count(),
+            1
+        );
+
+        // Chained shot slows down in flight.
+        for _ in 0..10 {
+            app.update();
+        }
+        let speed = app.world().get::<PointNetwork>(projectile).unwrap().points[0]
+            .vel
+            .length();
+        assert!(speed < 10.0, "{speed}");
+    }
+
+    #[test]
+    fn behaviors_can_be_replaced() {
+        let mut registry = ModifierRegistry::default();
+        assert!(
+            registry
+                .get(ProjectileModifier::Smoke)
+                .unwrap()
+                .on_hit
+                .is_some()
+        );
+
+        registry.register(ProjectileModifier::Smoke, ModifierBehavior::default());
+        assert!(
+            registry
+                .get(ProjectileModifier::Smoke)
+                .unwrap()
+                .on_hit
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn selectors_fall_back_to_plain_ammo() {
+        let cannonballs = |modifiers: Vec<ProjectileModifier>| {
+            item(
+                "cannonball 40mm",
+                ItemType::Ammo(AmmoDef {
+                    ammo_type: AmmoType::Cannonball(CannonballDef { caliber: 40 }),
+                    modifiers,
+                }),
+                0.5,
+                0.1,
+                1.0,
+            )
+        };
+
+        let mut makeup = makeup(vec![]);
+        makeup.add_item(cannonballs(vec![ProjectileModifier::Incendiary]));
+        makeup.add_item(cannonballs(vec![]));
+        makeup.add_item(cannonballs(vec![ProjectileModifier::PropellerGum]));
+
+        let selectors = [
+            "cannonball 40mm incendiary".to_string(),
+            "cannonball 40mm".to_string(),
+        ];
+        let mut take = || {
+            makeup
+                .take_ammo(&selectors, |_| true)
+                .map(|(ammo, _)| ammo.modifiers)
+        };
+
+        // Plain rounds are preferred over ones with modifiers not asked for.
+        assert_eq!(take(), Some(vec![ProjectileModifier::Incendiary]));
+        assert_eq!(take(), Some(vec![]));
+        assert_eq!(take(), Some(vec![ProjectileModifier::PropellerGum]));
+        assert_eq!(take(), None);
+    }
+}
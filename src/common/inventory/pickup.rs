@@ -0,0 +1,105 @@
+//! # Pickups
+//!
+//! Items dropped at sea, such as the cargo of a sunken ship, float around as
+//! [Pickup] entities, until someone picks them up, e.g. with a
+//! [VacuumPart](crate::common::makeup::parts::vacuum::VacuumPart).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use slotmap::DefaultKey;
+
+use super::InventoryDef;
+use crate::common::physics::{
+    base::{PhysPoint, PointNetwork},
+    forces::Gravity,
+    volume::{PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+    water::WaterPhysics,
+};
+
+/// The radius of pickups.
+pub const PICKUP_RADIUS: f32 = 0.3;
+
+/// The least mass a pickup can have, so that even empty crates float
+/// sensibly.
+const MIN_PICKUP_MASS: f32 = 0.1;
+
+/// An item floating at sea, waiting to be picked up.
+#[derive(Component)]
+pub struct Pickup {
+    /// The item, until it is picked up.
+    item: Option<InventoryDef>,
+}
+
+impl Pickup {
+    /// The item in this pickup, unless it was already picked up.
+    pub fn item(&self) -> Option<&InventoryDef> {
+        self.item.as_ref()
+    }
+
+    /// Takes the item out of this pickup, leaving it empty.
+    ///
+    /// Empty pickups should be despawned.
+    pub fn take(&mut self) -> Option<InventoryDef> {
+        self.item.take()
+    }
+}
+
+/// Sent whenever a construct picks up an item.
+#[derive(Event, Clone, Debug)]
+pub struct ItemCollectedEvent {
+    /// The pickup, which is despawned.
+    pub pickup: Entity,
+
+    /// The construct which picked up the item.
+    pub collector: Entity,
+
+    /// The name of the item.
+    pub name: String,
+
+    /// The amount of the item.
+    pub amount: f32,
+
+    /// Where the item went in the collector's inventory.
+    pub key: DefaultKey,
+}
+
+/// Drops an item at sea, spawning a floating [Pickup] for it.
+///
+/// The pickup floats on the given water, and starts with the given velocity.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    item: InventoryDef,
+    at: Vec3,
+    velocity: Vec3,
+    water: WaterPhysics,
+) -> Entity {
+    let mass = (item.mass * item.amount).max(MIN_PICKUP_MASS);
+
+    commands
+        .spawn((
+            Pickup { item: Some(item) },
+            PointNetwork::from([PhysPoint::new(at, velocity, mass)].into_iter()),
+            VolumeCollection {
+                volumes: vec![PhysicsVolume {
+                    point_idx: 0,
+                    volume_type: VolumeType::Sphere(SphereDef::new(PICKUP_RADIUS)),
+                }],
+            },
+            Gravity::default(),
+            water,
+            Transform::from_translation(at),
+        ))
+        .id()
+}
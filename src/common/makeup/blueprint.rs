@@ -0,0 +1,233 @@
+//! # Ship blueprints
+//!
+//! A [ConstructBlueprint] is a ship's design, in a serializable form: its
+//! make (and so its slots), livery, inventory, and which parts of the
+//! inventory are installed on which slots. It is what save games, the
+//! drydock, NPC templates and network sync store ships as.
+//!
+//! Blueprints are taken from ship entities with [to_blueprint], and spawned
+//! as new ships with [spawn_from_blueprint]. Their slots and parts are then
+//! spawned as for any other ship (see [sync](super::sync)).
+//!
+//! Blueprints describe designs, not physical state: the spawned ship is at
+//! rest at the origin, and it is up to the caller to place it and give it
+//! any volumes, buoyancy or controller it needs.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{asset::ron, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{Ship, ShipMake, ShipMakeup, livery::ShipLivery};
+use crate::common::{
+    error::{Context, LnrError},
+    inventory::InventoryDef,
+    physics::base::{PhysPoint, PointNetwork},
+};
+
+/// The design of a ship, in a serializable form.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConstructBlueprint {
+    /// The make of the ship, including its slots.
+    pub make: ShipMake,
+
+    /// The cosmetic livery of the ship.
+    #[serde(default)]
+    pub livery: ShipLivery,
+
+    /// Every item in the ship's inventory, installed parts included.
+    #[serde(default)]
+    pub inventory: Vec<InventoryDef>,
+
+    /// For each slot of the make, the index in [Self::inventory] of the
+    /// part installed on it, if any.
+    ///
+    /// Missing trailing entries mean vacant slots.
+    #[serde(default)]
+    pub installed: Vec<Option<usize>>,
+}
+
+impl ConstructBlueprint {
+    /// A blueprint of a ship of the given make, with no parts installed and
+    /// an empty inventory.
+    pub fn new(make: ShipMake) -> Self {
+        Self {
+            make,
+            livery: ShipLivery::default(),
+            inventory: vec![],
+            installed: vec![],
+        }
+    }
+
+    /// Parses a blueprint from RON.
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, LnrError> {
+        ron::de::from_bytes(bytes).context("parsing a blueprint")
+    }
+
+    /// Writes this blueprint as RON.
+    pub fn to_ron(&self) -> Result<String, LnrError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .context("writing a blueprint")
+    }
+}
+
+impl ShipMakeup {
+    /// The design of this ship, as a blueprint.
+    pub fn to_blueprint(&self) -> ConstructBlueprint {
+        let keys: Vec<_> = self.ship_inventory.keys().collect();
+        let installed = self
+            .parts
+            .iter()
+            .map(|part| part.and_then(|key| keys.iter().position(|&item| item == key)))
+            .collect();
+
+        ConstructBlueprint {
+            make: self.make.clone(),
+            livery: self.livery.clone(),
+            inventory: self.ship_inventory.values().cloned().collect(),
+            installed,
+        }
+    }
+
+    /// A ship built after a blueprint.
+    ///
+    /// Fails if the blueprint installs parts on slots its make lacks, or
+    /// installs items which are not parts, or the same part twice.
+    pub fn from_blueprint(blueprint: ConstructBlueprint) -> Result<Self> {
+        let ConstructBlueprint {
+            make,
+            livery,
+            inventory,
+            installed,
+        } = blueprint;
+
+        let mut makeup = ShipMakeup::new(make, livery);
+        let keys: Vec<_> = inventory
+            .into_iter()
+            .map(|item| makeup.add_item(item))
+            .collect();
+
+        for (slot_idx, item_idx) in installed.into_iter().enumerate() {
+            let Some(item_idx) = item_idx else {
+                continue;
+            };
+            let Some(&key) = keys.get(item_idx) else {
+                return Err(LnrError::invalid_state(format!(
+                    "Blueprint installs item {item_idx} on slot {slot_idx}, but only has {} items",
+                    keys.len()
+                ))
+                .into());
+            };
+            if makeup.parts.contains(&Some(key)) {
+                return Err(LnrError::invalid_state(format!(
+                    "Blueprint installs item {item_idx} on more than one slot"
+                ))
+                .into());
+            }
+
+            makeup.set_installed(slot_idx, Some(key))?;
+        }
+
+        Ok(makeup)
+    }
+}
+
+/// The blueprint of a ship entity.
+///
+/// Fails if the entity is not a [Ship].
+pub fn to_blueprint(world: &World, construct: Entity) -> Result<ConstructBlueprint> {
+    let ship = world
+        .get::<Ship>(construct)
+        .ok_or_else(|| LnrError::missing_component::<Ship>(construct))
+        .context("taking the blueprint of a ship")?;
+
+    Ok(ship.makeup.to_blueprint())
+}
+
+/// Spawns a ship after a blueprint, at rest at the origin.
+///
+/// Fails if the blueprint is inconsistent; see [ShipMakeup::from_blueprint].
+pub fn spawn_from_blueprint(world: &mut World, blueprint: ConstructBlueprint) -> Result<Entity> {
+    let makeup = ShipMakeup::from_blueprint(blueprint)?;
+    let points = PointNetwork::from(
+        [PhysPoint::new(
+            Vec3::ZERO,
+            Vec3::ZERO,
+            makeup.get_total_mass(),
+        )]
+        .into_iter(),
+    );
+
+    Ok(world
+        .spawn((Ship { makeup }, points, Transform::default()))
+        .id())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        construct::{ConstructPlugin, part::ConstructParts},
+        fixtures::{item, make, slot},
+        inventory::{EngineDef, InventoryDef, ItemPartDef, ItemType, ManningType, PartTypeDef},
+        makeup::sync::MakeupSyncPlugin,
+    };
+
+    use super::*;
+
+    fn engine() -> InventoryDef {
+        item(
+            "oars",
+            ItemType::Part(ItemPartDef {
+                part_type: PartTypeDef::Engine(EngineDef {
+                    fuel_type: None,
+                    power: 100,
+                    fuel_consumption: 0,
+                }),
+                manned: ManningType::Unmanned,
+            }),
+            2.0,
+            1.0,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn blueprints_round_trip_through_ships() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin, MakeupSyncPlugin));
+
+        let mut blueprint = ConstructBlueprint::new(make(vec![slot("engine"), slot("engine")]));
+        blueprint.inventory = vec![engine(), engine()];
+        blueprint.installed = vec![None, Some(1)];
+
+        let blueprint =
+            ConstructBlueprint::from_ron(blueprint.to_ron().unwrap().as_bytes()).unwrap();
+        let ship = spawn_from_blueprint(app.world_mut(), blueprint).unwrap();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let parts = app.world().get::<ConstructParts>(ship).unwrap();
+        assert_eq!(parts.iter().count(), 1);
+
+        let blueprint = to_blueprint(app.world(), ship).unwrap();
+        assert_eq!(blueprint.inventory.len(), 2);
+        assert_eq!(blueprint.installed, [None, Some(1)]);
+
+        // The same part cannot be installed twice.
+        let mut broken = blueprint.clone();
+        broken.installed = vec![Some(0), Some(0)];
+        assert!(ShipMakeup::from_blueprint(broken).is_err());
+    }
+}
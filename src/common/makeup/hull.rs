@@ -0,0 +1,205 @@
+//! # Hull shapes
+//!
+//! The [HullShape] of a [ShipMake](super::ShipMake) describes the outline of
+//! its hull: its overall length, beam and depth, and how the beam and deck
+//! height vary from stern to bow, as a spline through a few [HullStation]s.
+//!
+//! The shape has no effect on gameplay; it is what the client builds the
+//! ship's hull mesh from. It also says which points of the ship's point
+//! network mark its bow and stern, so the mesh can follow the network.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use serde::{Deserialize, Serialize};
+
+/// A cross-section of a hull, somewhere along its length.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HullStation {
+    /// Where along the hull the station is, from 0.0 at the stern to 1.0 at
+    /// the bow.
+    pub at: f32,
+
+    /// The beam at this station, as a fraction of the hull's beam.
+    pub beam: f32,
+
+    /// The height of the deck above the keel at this station, as a fraction
+    /// of the hull's depth.
+    pub deck_height: f32,
+}
+
+impl HullStation {
+    pub fn new(at: f32, beam: f32, deck_height: f32) -> Self {
+        Self {
+            at,
+            beam,
+            deck_height,
+        }
+    }
+}
+
+/// The outline of a ship's hull.
+///
+/// The hull runs along the Z axis, with its bow towards -Z, and the origin at
+/// the center of the ship's point network.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HullShape {
+    /// The length of the hull, from stern to bow.
+    pub length: f32,
+
+    /// The width of the hull at its widest.
+    pub beam: f32,
+
+    /// The height of the hull, from keel to deck, at its tallest.
+    pub depth: f32,
+
+    /// How far below the origin the keel is.
+    pub keel: f32,
+
+    /// The cross-sections the hull is interpolated through, from stern to
+    /// bow.
+    ///
+    /// If empty, the hull is a box of full beam and depth.
+    pub stations: Vec<HullStation>,
+
+    /// The point of the network at the bow, if any.
+    pub bow_point: Option<usize>,
+
+    /// The point of the network at the stern, if any.
+    ///
+    /// If either this or [Self::bow_point] is missing, the hull is turned
+    /// towards where the ship is going instead.
+    pub stern_point: Option<usize>,
+}
+
+impl Default for HullShape {
+    fn default() -> Self {
+        Self::new(8.0, 3.0, 2.0)
+    }
+}
+
+impl HullShape {
+    /// A hull of the given dimensions, with a transom stern and a pointed
+    /// bow.
+    pub fn new(length: f32, beam: f32, depth: f32) -> Self {
+        Self {
+            length,
+            beam,
+            depth,
+            keel: depth * 0.5,
+            stations: vec![
+                HullStation::new(0.0, 0.75, 0.95),
+                HullStation::new(0.35, 1.0, 0.9),
+                HullStation::new(0.7, 0.9, 0.9),
+                HullStation::new(1.0, 0.0, 1.0),
+            ],
+            bow_point: None,
+            stern_point: None,
+        }
+    }
+
+    pub fn with_keel(mut self, keel: f32) -> Self {
+        self.keel = keel;
+        self
+    }
+
+    pub fn with_stations(mut self, stations: Vec<HullStation>) -> Self {
+        self.stations = stations;
+        self
+    }
+
+    pub fn with_reference_points(mut self, bow: usize, stern: usize) -> Self {
+        self.bow_point = Some(bow);
+        self.stern_point = Some(stern);
+        self
+    }
+
+    /// The cross-section at a point along the hull, from 0.0 at the stern to
+    /// 1.0 at the bow, with its beam and deck height in world units.
+    ///
+    /// Interpolated through the stations with a Catmull-Rom spline.
+    pub fn station_at(&self, at: f32) -> HullStation {
+        let at = at.clamp(0.0, 1.0);
+        let fractions = match self.stations.as_slice() {
+            [] => (1.0, 1.0),
+            [only] => (only.beam, only.deck_height),
+            stations => {
+                let next = stations
+                    .iter()
+                    .position(|station| station.at > at)
+                    .unwrap_or(stations.len() - 1)
+                    .max(1);
+                let get = |idx: usize| stations[idx.clamp(0, stations.len() - 1)];
+                let (p0, p1, p2, p3) = (
+                    get(next.saturating_sub(2)),
+                    get(next - 1),
+                    get(next),
+                    get(next + 1),
+                );
+
+                let span = p2.at - p1.at;
+                let s = if span > 0.0 {
+                    ((at - p1.at) / span).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (
+                    catmull_rom(p0.beam, p1.beam, p2.beam, p3.beam, s).max(0.0),
+                    catmull_rom(
+                        p0.deck_height,
+                        p1.deck_height,
+                        p2.deck_height,
+                        p3.deck_height,
+                        s,
+                    )
+                    .max(0.0),
+                )
+            }
+        };
+
+        HullStation::new(at, fractions.0 * self.beam, fractions.1 * self.depth)
+    }
+}
+
+/// Catmull-Rom interpolation between `p1` and `p2`.
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, s: f32) -> f32 {
+    let s2 = s * s;
+    let s3 = s2 * s;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * s
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * s2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * s3)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn stations_are_interpolated_through() {
+        let hull = HullShape::new(10.0, 4.0, 2.0);
+
+        for station in &hull.stations {
+            let at = hull.station_at(station.at);
+            assert!((at.beam - station.beam * 4.0).abs() < 1e-4);
+            assert!((at.deck_height - station.deck_height * 2.0).abs() < 1e-4);
+        }
+
+        // Between stations, the beam stays within the hull's.
+        for step in 0..=20 {
+            let beam = hull.station_at(step as f32 / 20.0).beam;
+            assert!((0.0..=4.2).contains(&beam));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! # Ship livery
+//!
+//! Cosmetic customization of a ship: hull paint and figurehead.
+//!
+//! A livery has no effect on gameplay. It is part of the [super::ShipMakeup],
+//! so it is picked at the Drydock and persisted alongside the rest of the
+//! ship.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Livery customization in the drydock UI.
+// [TODO] Replicate liveries to other players, once multiplayer exists.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A paint color, in 8-bit sRGB.
+///
+/// Stored as plain bytes so that liveries serialize compactly and
+/// independently of Bevy's color types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaintColor(pub [u8; 3]);
+
+impl PaintColor {
+    /// Converts this paint color into a Bevy [Color].
+    pub fn to_color(self) -> Color {
+        let [r, g, b] = self.0;
+        Color::srgb_u8(r, g, b)
+    }
+}
+
+/// How the secondary paint color is laid out on the hull.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaintPattern {
+    /// The whole hull is painted with the primary color.
+    #[default]
+    Plain,
+
+    /// A stripe of the secondary color runs along the hull.
+    Stripe,
+
+    /// The hull is painted with the secondary color below the waterline.
+    Waterline,
+
+    /// Alternating bands of both colors, from bow to stern.
+    Banded,
+}
+
+/// The paint scheme of a ship's hull.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HullPaint {
+    /// Main hull color.
+    pub primary: PaintColor,
+
+    /// Secondary hull color, laid out according to the pattern.
+    pub secondary: PaintColor,
+
+    /// The paint pattern.
+    pub pattern: PaintPattern,
+}
+
+impl Default for HullPaint {
+    fn default() -> Self {
+        Self {
+            primary: PaintColor([122, 84, 52]),
+            secondary: PaintColor([60, 40, 28]),
+            pattern: PaintPattern::Plain,
+        }
+    }
+}
+
+/// The cosmetic livery of a ship.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShipLivery {
+    /// The hull paint scheme.
+    pub paint: HullPaint,
+
+    /// The figurehead mounted on the bow, if any.
+    ///
+    /// Figureheads are identified by keyword, such as "mermaid" or "lion",
+    /// similarly to part types.
+    pub figurehead: Option<String>,
+}
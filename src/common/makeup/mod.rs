@@ -14,12 +14,23 @@
 // permitted by applicable law.  See the CNPL for details.
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use slotmap::{DefaultKey, SlotMap};
 
-use super::inventory::InventoryDef;
+use self::{hull::HullShape, livery::ShipLivery};
+use super::{
+    error::LnrError,
+    inventory::{
+        AmmoDef, FoodDef, FuelDef, FuelType, InventoryDef, ItemType, modifier::ProjectileModifier,
+    },
+};
 
-// [TODO] Please uncomment *only* implemented modules.
-// pub mod parts; // Ship parts.
+pub mod blueprint; // Ship designs, to save and spawn ships from.
+pub mod hull; // Hull shapes of ship makes.
+pub mod livery; // Cosmetic ship livery.
+pub mod parts; // Ship parts.
+pub mod sinking; // Sinking ships, and the loot they drop.
+pub mod sync; // Ship makeups as construct, slot and part entities.
 
 /// Marks an entity as a ship.
 #[derive(Component)]
@@ -32,6 +43,7 @@ pub struct Ship {
 ///
 /// Each [ShipMake] has a list of slots to which parts can be installed by
 /// type.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PartSlot {
     /// The type of part that can be instlaled here.
     ///
@@ -58,10 +70,15 @@ pub struct PartSlot {
 /// The make of the ship.
 ///
 // This defines the ship's base hull, as well as part slot definitions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShipMake {
     /// The hull mass.
     pub hull_mass: f32,
 
+    /// The shape of the hull.
+    #[serde(default)]
+    pub hull: HullShape,
+
     /// Part slots.
     pub slots: Vec<PartSlot>,
 }
@@ -78,9 +95,25 @@ pub struct ShipMakeup {
 
     /// The inventory of this ship.
     ship_inventory: SlotMap<DefaultKey, InventoryDef>,
+
+    /// The cosmetic livery of this ship.
+    livery: ShipLivery,
 }
 
 impl ShipMakeup {
+    /// A new ship of the given make, with no parts installed and an empty
+    /// inventory.
+    pub fn new(make: ShipMake, livery: ShipLivery) -> Self {
+        let parts = make.slots.iter().map(|_| None).collect();
+
+        Self {
+            make,
+            parts,
+            ship_inventory: SlotMap::new(),
+            livery,
+        }
+    }
+
     /// Sums up the total mass of the ship,
     pub fn get_total_mass(&self) -> f32 {
         self.make.hull_mass
@@ -91,6 +124,289 @@ impl ShipMakeup {
                 .sum::<f32>()
     }
 
+    /// The make of this ship.
+    pub fn make(&self) -> &ShipMake {
+        &self.make
+    }
+
+    /// An item in the inventory of this ship.
+    pub fn item(&self, key: DefaultKey) -> Option<&InventoryDef> {
+        self.ship_inventory.get(key)
+    }
+
+    /// An item in the inventory of this ship, to be changed in place.
+    pub fn item_mut(&mut self, key: DefaultKey) -> Option<&mut InventoryDef> {
+        self.ship_inventory.get_mut(key)
+    }
+
+    /// The item of the part installed on a slot, if any.
+    pub fn installed(&self, slot_idx: usize) -> Option<DefaultKey> {
+        self.parts.get(slot_idx).copied().flatten()
+    }
+
+    /// Marks a part in the inventory as installed on a slot, or the slot as
+    /// vacant.
+    ///
+    /// Fails if there is no such slot, or the item is not a part in the
+    /// inventory.
+    pub fn set_installed(&mut self, slot_idx: usize, part: Option<DefaultKey>) -> Result {
+        let is_part = |key| {
+            matches!(
+                self.ship_inventory.get(key),
+                Some(InventoryDef {
+                    item_type: ItemType::Part(_),
+                    ..
+                })
+            )
+        };
+
+        if let Some(key) = part.filter(|key| !is_part(*key)) {
+            return Err(LnrError::invalid_state(format!(
+                "Tried to install item {key:?}, which is not a part in the inventory"
+            ))
+            .into());
+        }
+
+        let Some(slot) = self.parts.get_mut(slot_idx) else {
+            return Err(LnrError::invalid_state(format!(
+                "Tried to install a part on slot {slot_idx}, but the ship only has {} slots",
+                self.make.slots.len()
+            ))
+            .into());
+        };
+
+        *slot = part;
+        Ok(())
+    }
+
+    /// The cosmetic livery of this ship.
+    pub fn livery(&self) -> &ShipLivery {
+        &self.livery
+    }
+
+    /// Mutably access the cosmetic livery of this ship.
+    pub fn livery_mut(&mut self) -> &mut ShipLivery {
+        &mut self.livery
+    }
+
+    /// Iterate on all items in the inventory of this ship.
+    pub fn items(&self) -> impl Iterator<Item = (DefaultKey, &InventoryDef)> {
+        self.ship_inventory.iter()
+    }
+
+    /// Stores an item in the inventory of this ship.
+    pub fn add_item(&mut self, item: InventoryDef) -> DefaultKey {
+        self.ship_inventory.insert(item)
+    }
+
+    /// Takes up to an amount of an item out of the inventory.
+    ///
+    /// Fungible items are split off; others are only ever taken whole, and
+    /// are uninstalled from their slot if they are installed on one.
+    pub fn take_item(&mut self, key: DefaultKey, amount: f32) -> Option<InventoryDef> {
+        let item = self.ship_inventory.get_mut(key)?;
+
+        if item.is_fungible() && amount < item.amount {
+            if amount <= 0.0 {
+                return None;
+            }
+
+            item.amount -= amount;
+            return Some(InventoryDef {
+                amount,
+                ..item.clone()
+            });
+        }
+
+        for slot in self.parts.iter_mut().filter(|slot| **slot == Some(key)) {
+            *slot = None;
+        }
+        self.ship_inventory.remove(key)
+    }
+
+    /// How much fuel of the given type is in the inventory.
+    pub fn fuel_left(&self, fuel_type: FuelType) -> f32 {
+        self.ship_inventory
+            .values()
+            .filter_map(|item| match item.item_type {
+                ItemType::Fuel(FuelDef {
+                    fuel_type: item_fuel,
+                }) if item_fuel == fuel_type => Some(item.amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// How many food points are in the inventory.
+    pub fn food_left(&self) -> f32 {
+        self.ship_inventory
+            .values()
+            .filter_map(|item| match item.item_type {
+                ItemType::Food(FoodDef { food_points }) => Some(food_points as f32 * item.amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// How much of each kind of ammunition is in the inventory, by item
+    /// name.
+    pub fn ammo_left(&self) -> Vec<(&str, f32)> {
+        let mut ammo: Vec<(&str, f32)> = vec![];
+
+        for item in self.ship_inventory.values() {
+            if !matches!(item.item_type, ItemType::Ammo(_)) {
+                continue;
+            }
+
+            match ammo.iter_mut().find(|(name, _)| *name == item.name) {
+                Some((_, amount)) => *amount += item.amount,
+                None => ammo.push((&item.name, item.amount)),
+            }
+        }
+
+        ammo
+    }
+
+    /// Takes up to an amount of fuel of the given type from the inventory.
+    ///
+    /// Items which run out are removed. Returns how much fuel was actually
+    /// taken, which is less than asked for if there was not enough of it.
+    pub fn take_fuel(&mut self, fuel_type: FuelType, amount: f32) -> f32 {
+        let mut taken = 0.0;
+
+        self.ship_inventory.retain(|_, item| {
+            let ItemType::Fuel(FuelDef {
+                fuel_type: item_fuel,
+            }) = item.item_type
+            else {
+                return true;
+            };
+
+            if item_fuel != fuel_type || taken >= amount {
+                return true;
+            }
+
+            let take = item.amount.min(amount - taken);
+            item.amount -= take;
+            taken += take;
+
+            item.amount > 0.0
+        });
+
+        taken
+    }
+
+    /// Takes up to an amount of food points from the inventory, eating
+    /// through food items in order.
+    ///
+    /// Items which run out are removed. Returns how many food points were
+    /// actually taken, which is less than asked for if there was not enough
+    /// food.
+    pub fn take_food(&mut self, points: f32) -> f32 {
+        let mut taken = 0.0;
+
+        self.ship_inventory.retain(|_, item| {
+            let ItemType::Food(FoodDef { food_points }) = item.item_type else {
+                return true;
+            };
+
+            if food_points == 0 || taken >= points {
+                return true;
+            }
+
+            let units = item.amount.min((points - taken) / food_points as f32);
+            item.amount -= units;
+            taken += units * food_points as f32;
+
+            item.amount > 0.0
+        });
+
+        taken
+    }
+
+    /// Takes a single unit of ammunition from the inventory.
+    ///
+    /// Only ammunition for which `compatible` is true is considered. Of it,
+    /// the first of the `selectors` to match any item picks which one is
+    /// taken; a selector matches items whose names or modifiers (see
+    /// [ProjectileModifier::keyword]) have all of its words, e.g.
+    /// `"cannonball incendiary"`. Without any selectors, any compatible
+    /// ammunition is taken.
+    ///
+    /// Of the items a selector matches, those with the fewest modifiers it
+    /// does not name are preferred, so that e.g. `"cannonball"` takes plain
+    /// cannonballs over incendiary ones, if there are any.
+    ///
+    /// Returns the ammunition taken and its mass, if any was found.
+    pub fn take_ammo(
+        &mut self,
+        selectors: &[String],
+        compatible: impl Fn(&AmmoDef) -> bool,
+    ) -> Option<(AmmoDef, f32)> {
+        let candidates = self
+            .ship_inventory
+            .iter()
+            .filter(|(_, item)| item.amount >= 1.0)
+            .filter_map(|(key, item)| match &item.item_type {
+                ItemType::Ammo(ammo) if compatible(ammo) => {
+                    Some((key, item.name.as_str(), &ammo.modifiers))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Modifiers of an item which a selector does not name.
+        let unrequested = |selector: &str, modifiers: &[ProjectileModifier]| {
+            modifiers
+                .iter()
+                .filter(|modifier| {
+                    !selector
+                        .split_whitespace()
+                        .any(|word| word == modifier.keyword())
+                })
+                .count()
+        };
+
+        let key = if selectors.is_empty() {
+            candidates
+                .iter()
+                .min_by_key(|(_, _, modifiers)| modifiers.len())
+                .map(|(key, _, _)| *key)
+        } else {
+            selectors.iter().find_map(|selector| {
+                candidates
+                    .iter()
+                    .filter(|(_, name, modifiers)| {
+                        selector.split_whitespace().all(|word| {
+                            name.split_whitespace().any(|other| other == word)
+                                || modifiers.iter().any(|modifier| modifier.keyword() == word)
+                        })
+                    })
+                    .min_by_key(|(_, _, modifiers)| unrequested(selector, modifiers))
+                    .map(|(key, _, _)| *key)
+            })
+        }?;
+
+        let item = self.ship_inventory.get_mut(key)?;
+        let ItemType::Ammo(ammo) = &item.item_type else {
+            return None;
+        };
+        let taken = (ammo.clone(), item.mass);
+
+        item.amount -= 1.0;
+        if item.amount <= 0.0 {
+            self.ship_inventory.remove(key);
+        }
+
+        Some(taken)
+    }
+
+    /// Takes every item out of the inventory, uninstalling all parts.
+    pub fn drain_items(&mut self) -> impl Iterator<Item = InventoryDef> + '_ {
+        self.parts.fill(None);
+        self.ship_inventory.drain().map(|(_, item)| item)
+    }
+
     /// Iterate on all parts and their slots.
     pub fn part_iter(&self) -> impl Iterator<Item = (&InventoryDef, &PartSlot)> {
         self.parts
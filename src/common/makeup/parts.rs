@@ -0,0 +1,120 @@
+//! # Ship parts
+//!
+//! The behavior of parts installed on ships (or any other construct), in
+//! response to the [PartAction](crate::common::construct::action::PartAction)s
+//! dispatched to them. Parts report what came of each action they handle
+//! with a [PartActionOutcome].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{ecs::component::Mutable, prelude::*};
+
+use crate::common::{
+    construct::{action::PartActionOutcome, part::PartInstalledOn, slot::SlotAttachment},
+    crew::Crew,
+    error::LnrError,
+    inventory::pickup::ItemCollectedEvent,
+    physics::{base::PointNetwork, determinism::PhysicsRng},
+};
+
+pub mod armor; // Armor, which mitigates damage
+pub mod ballista; // Ballistas, which fire bolts
+pub mod cannon; // Cannons, which fire cannonballs
+pub mod engine; // Engines, which propel constructs
+pub mod minelayer; // Minelayers, which lay naval mines
+pub mod vacuum; // Vacuums, which collect pickups
+
+/// Where a part acts on its construct, in world space.
+///
+/// That is the point its slot (the part's parent) is attached to, if any (see
+/// [SlotAttachment]), or else the construct's center of mass.
+pub(crate) fn attachment_point(
+    part: Entity,
+    slot: Option<&ChildOf>,
+    slots: &Query<&SlotAttachment>,
+    network: &PointNetwork,
+) -> Result<Vec3> {
+    let Some(attachment) = slot.and_then(|slot| slots.get(slot.parent()).ok()) else {
+        return Ok(network.center_of_mass());
+    };
+
+    match network.points.get(attachment.point_idx) {
+        Some(point) => Ok(point.pos),
+        None => Err(LnrError::invalid_state(format!(
+            "part {} is attached to point {}, but its construct has {} points",
+            part,
+            attachment.point_idx,
+            network.points.len()
+        ))
+        .into()),
+    }
+}
+
+/// Weapon parts, which need to reload after every shot.
+trait Reloads: Component<Mutability = Mutable> {
+    /// Time left until the weapon can fire again, in seconds.
+    fn cooldown_mut(&mut self) -> &mut f32;
+}
+
+fn tick_reloads<T: Reloads>(
+    time: Res<Time>,
+    mut weapons: Query<(Entity, &mut T, Option<&PartInstalledOn>)>,
+    crews: Query<&Crew>,
+) {
+    for (entity, mut weapon, installed_on) in &mut weapons {
+        let cooldown = weapon.cooldown_mut();
+
+        // Hungry crew reload slower.
+        let efficiency = installed_on
+            .and_then(|installed_on| crews.get(installed_on.get()).ok())
+            .map_or(1.0, |crew| crew.efficiency_at(entity));
+
+        if *cooldown > 0.0 {
+            *cooldown = (*cooldown - time.delta_secs() * efficiency).max(0.0);
+        }
+    }
+}
+
+/// Enables the behavior of all ship parts.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct PartsPlugin;
+
+impl Plugin for PartsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PartActionOutcome>();
+        app.add_event::<engine::EngineStateChanged>();
+        app.add_event::<minelayer::MineDetonated>();
+        app.add_event::<ItemCollectedEvent>();
+        app.init_resource::<PhysicsRng>();
+        app.add_observer(engine::obs_engine_thrust);
+        app.add_observer(cannon::obs_cannon_fire);
+        app.add_observer(ballista::obs_ballista_fire);
+        app.add_observer(minelayer::obs_minelayer_fire);
+        app.add_systems(
+            FixedUpdate,
+            (
+                (engine::apply_engine_thrust, engine::update_fuel_gauges).chain(),
+                tick_reloads::<cannon::CannonPart>,
+                tick_reloads::<ballista::BallistaPart>,
+                tick_reloads::<minelayer::MinelayerPart>,
+                cannon::expire_cannonballs,
+                ballista::expire_ballista_bolts,
+                minelayer::arm_mines,
+                minelayer::detonate_mines,
+                vacuum::vacuum_pickups,
+            ),
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! # Armor
+//!
+//! An [ArmorPart] protects the construct it is installed on, reducing the
+//! damage of hits near where it is installed, to the construct and to its
+//! other parts (see [crate::common::damage]).
+//!
+//! Armor is described by its [ArmorDef] factors:
+//!
+//! * **defense**: every 100 points halve the damage, then a third it, and so
+//!   on;
+//! * **deflect**: the chance, in percent, to deflect a projectile entirely
+//!   when grazed; head-on hits are never deflected, and the more glancing
+//!   the hit, the likelier it is;
+//! * **wear**: how fast the armor wears, from the damage it absorbs. Worn
+//!   armor defends and deflects less, down to nothing at all. Wear is kept
+//!   on the definition, and so on the armor item (see
+//!   [sync](crate::common::makeup::sync)), staying worn when uninstalled;
+//! * **overwhelm**: how much of the damage of a hit beyond the armor's
+//!   defense (in damage points) goes right through it, in percent.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::common::inventory::ArmorDef;
+
+/// Wear gained per point of damage absorbed, per point of
+/// [ArmorDef::wear_factor].
+const WEAR_PER_DAMAGE: f32 = 0.0001;
+
+/// An armor part.
+#[derive(Component, Clone, Debug)]
+pub struct ArmorPart {
+    /// The definition of this armor, wear included.
+    pub def: ArmorDef,
+}
+
+impl ArmorPart {
+    /// An armor part, as worn as its definition says.
+    pub fn new(def: ArmorDef) -> Self {
+        Self { def }
+    }
+
+    /// How worn the armor is, between 0.0 (pristine) and 1.0 (useless).
+    pub fn wear(&self) -> f32 {
+        self.def.wear
+    }
+
+    /// The defense factor of this armor, less its wear.
+    pub fn defense(&self) -> f32 {
+        self.def.defense_factor as f32 * (1.0 - self.wear())
+    }
+
+    /// The chance of deflecting a projectile, between 0.0 and 1.0, from the
+    /// angle between its path and the hull's normal where it hits, in
+    /// radians.
+    pub fn deflect_chance(&self, impact_angle: f32) -> f32 {
+        let glancing = (impact_angle / FRAC_PI_2).clamp(0.0, 1.0);
+
+        (self.def.deflect_factor as f32 / 100.0 * glancing * (1.0 - self.wear())).clamp(0.0, 1.0)
+    }
+
+    /// How much of an amount of damage overwhelms this armor, going right
+    /// through it.
+    pub fn overwhelmed(&self, amount: f32) -> f32 {
+        let excess = (amount - self.defense()).max(0.0);
+
+        excess * (self.def.overwhelm_factor as f32 / 100.0).min(1.0)
+    }
+
+    /// How much of an amount of damage goes through this armor, not
+    /// counting deflection.
+    pub fn mitigate(&self, amount: f32) -> f32 {
+        let overwhelmed = self.overwhelmed(amount);
+
+        overwhelmed + (amount - overwhelmed) * 100.0 / (100.0 + self.defense())
+    }
+
+    /// Takes a hit, returning how much of its damage goes through, and
+    /// wearing the armor by how much it absorbs.
+    ///
+    /// Only projectiles, with an impact angle, can be deflected.
+    pub fn absorb(&mut self, amount: f32, impact_angle: Option<f32>, rng: &mut impl Rng) -> f32 {
+        if impact_angle.is_some_and(|angle| rng.random::<f32>() < self.deflect_chance(angle)) {
+            return 0.0;
+        }
+
+        let through = self.mitigate(amount);
+        let absorbed = amount - through;
+        self.def.wear =
+            (self.def.wear + absorbed * self.def.wear_factor as f32 * WEAR_PER_DAMAGE).min(1.0);
+
+        through
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn armor(defense_factor: u8, wear_factor: u8, deflect_factor: u8, overwhelm: u8) -> ArmorPart {
+        ArmorPart::new(ArmorDef {
+            defense_factor,
+            wear_factor,
+            deflect_factor,
+            overwhelm_factor: overwhelm,
+            wear: 0.0,
+        })
+    }
+
+    #[test]
+    fn armor_deflects_glancing_hits() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut sloped = armor(0, 0, 100, 0);
+
+        // Head-on hits are never deflected, grazing ones always are.
+        assert_eq!(sloped.absorb(10.0, Some(0.0), &mut rng), 10.0);
+        assert_eq!(sloped.absorb(10.0, Some(FRAC_PI_2), &mut rng), 0.0);
+
+        // Explosions are never deflected.
+        assert_eq!(sloped.absorb(10.0, None, &mut rng), 10.0);
+    }
+
+    #[test]
+    fn armor_wears_and_gets_overwhelmed() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut plate = armor(100, 100, 0, 0);
+
+        assert_eq!(plate.absorb(40.0, None, &mut rng), 20.0);
+        assert!(plate.wear() > 0.0);
+        assert!(plate.absorb(40.0, None, &mut rng) > 20.0);
+
+        // Half of the damage beyond 100 points goes right through.
+        let thin = armor(100, 0, 0, 50);
+        assert_eq!(thin.mitigate(80.0), 40.0);
+        assert_eq!(thin.mitigate(300.0), 100.0 + 200.0 / 2.0);
+    }
+}
@@ -0,0 +1,240 @@
+//! # Ballistas
+//!
+//! A [BallistaPart] fires a bolt in response to `"fire_weapon"`
+//! [PartAction]s (see [FireWeaponCommand]), as long as it has reloaded, and
+//! there are bolts in the construct's inventory.
+//!
+//! Unlike cannons, ballistas always fire at full power, at a fixed
+//! inclination; they only turn to face the aim point. Their bolts fly fast
+//! and flat, so they are best at close range.
+//!
+//! [FireWeaponCommand]: crate::common::construct::action::FireWeaponCommand
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::{
+    construct::{
+        action::{PartAction, PartActionData, PartActionOutcome, PartActionResult},
+        part::PartInstalledOn,
+        slot::SlotAttachment,
+    },
+    crew::Unmanned,
+    damage::ImpactDamage,
+    inventory::{AmmoType, BallistaDef, modifier::ProjectileModifiers},
+    makeup::Ship,
+    physics::{
+        base::{PhysPoint, PointNetwork},
+        forces::Gravity,
+        projectile::FastProjectile,
+        volume::{PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+    },
+};
+
+use super::{Reloads, attachment_point};
+
+/// How long ballista bolts last, in seconds, before they are despawned.
+const BOLT_LIFETIME: f32 = 10.0;
+
+/// The radius of ballista bolts.
+const BOLT_RADIUS: f32 = 0.05;
+
+/// A ballista part.
+#[derive(Component, Clone, Debug)]
+pub struct BallistaPart {
+    /// The definition of this ballista.
+    pub def: BallistaDef,
+
+    /// Time left until the ballista can fire again, in seconds.
+    cooldown: f32,
+}
+
+impl BallistaPart {
+    /// A loaded ballista.
+    pub fn new(def: BallistaDef) -> Self {
+        Self { def, cooldown: 0.0 }
+    }
+
+    /// Time left until the ballista can fire again, in seconds.
+    pub fn cooldown(&self) -> f32 {
+        self.cooldown
+    }
+
+    /// The velocity of a bolt fired from a point towards another.
+    ///
+    /// Its power is the muzzle speed of its bolts.
+    pub fn bolt_velocity(&self, from: Vec3, target: Vec3) -> Vec3 {
+        let heading = (target - from).with_y(0.0).normalize_or(Vec3::NEG_Z);
+        let axis = heading.cross(Vec3::Y);
+
+        Quat::from_axis_angle(axis, self.def.inclination) * heading * self.def.power
+    }
+}
+
+impl Reloads for BallistaPart {
+    fn cooldown_mut(&mut self) -> &mut f32 {
+        &mut self.cooldown
+    }
+}
+
+/// A ballista bolt in flight.
+#[derive(Component, Clone, Debug, Default)]
+pub struct BallistaBolt {
+    /// How long this bolt has been around, in seconds.
+    pub age: f32,
+}
+
+// Observer
+pub fn obs_ballista_fire(
+    trigger: Trigger<PartAction>,
+    mut commands: Commands,
+    mut ballistas: Query<(
+        &mut BallistaPart,
+        &PartInstalledOn,
+        Option<&ChildOf>,
+        Has<Unmanned>,
+    )>,
+    slots: Query<&SlotAttachment>,
+    mut constructs: Query<(&mut PointNetwork, Option<&mut Ship>, Option<&ChildOf>)>,
+    mut outcomes: EventWriter<PartActionOutcome>,
+) -> Result {
+    if trigger.action_tag != "fire_weapon" {
+        return Ok(());
+    }
+
+    let part = trigger.target();
+    let Ok((mut ballista, installed_on, slot, unmanned)) = ballistas.get_mut(part) else {
+        return Ok(());
+    };
+    let mut report = |result| {
+        outcomes.write(PartActionOutcome {
+            trace_id: trigger.trace_id,
+            part,
+            result,
+        });
+    };
+
+    let PartActionData::FireWeapon(command) = &trigger.data else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    if unmanned {
+        report(PartActionResult::Unmanned);
+        return Ok(());
+    }
+
+    if ballista.cooldown > 0.0 {
+        report(PartActionResult::Cooldown);
+        return Ok(());
+    }
+
+    let construct = installed_on.get();
+    let Ok((mut network, ship, scene)) = constructs.get_mut(construct) else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    let Some((ammo, mass)) = ship.and_then(|mut ship| {
+        ship.makeup.take_ammo(&command.ammo, |ammo| {
+            matches!(ammo.ammo_type, AmmoType::BallistaBolt)
+        })
+    }) else {
+        report(PartActionResult::NoAmmo);
+        return Ok(());
+    };
+
+    let muzzle = attachment_point(part, slot, &slots, &network)?;
+    let velocity = ballista.bolt_velocity(muzzle, command.aim_point);
+
+    let bolt = commands
+        .spawn((
+            BallistaBolt::default(),
+            ImpactDamage::from_kinetic_energy(mass, velocity),
+            ProjectileModifiers(ammo.modifiers),
+            FastProjectile::from_source(construct),
+            PointNetwork::from([PhysPoint::new(muzzle, velocity, mass)].into_iter()),
+            VolumeCollection {
+                volumes: vec![PhysicsVolume {
+                    point_idx: 0,
+                    volume_type: VolumeType::Sphere(SphereDef::new(BOLT_RADIUS)),
+                }],
+            },
+            Gravity::default(),
+            Transform::from_translation(muzzle),
+        ))
+        .id();
+
+    // Bolts belong to the same scene as the construct.
+    if let Some(scene) = scene {
+        commands.entity(scene.parent()).add_child(bolt);
+    }
+
+    // Recoil.
+    network.apply_impulse_at(muzzle, -velocity * mass);
+    ballista.cooldown = ballista.def.fire_rate as f32 / 100.0;
+    report(PartActionResult::Performed);
+
+    Ok(())
+}
+
+pub fn expire_ballista_bolts(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut bolts: Query<(Entity, &mut BallistaBolt)>,
+) {
+    for (entity, mut bolt) in &mut bolts {
+        bolt.age += time.delta_secs();
+
+        if bolt.age > BOLT_LIFETIME {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::f32::consts::FRAC_PI_6;
+
+    use bevy::prelude::*;
+
+    use crate::common::inventory::BallistaDef;
+
+    use super::*;
+
+    #[test]
+    fn bolts_fly_at_fixed_inclination() {
+        let ballista = BallistaPart::new(BallistaDef {
+            power: 60.0,
+            inclination: FRAC_PI_6,
+            fire_rate: 100,
+        });
+
+        // Near or far, high or low, only the heading changes.
+        for target in [Vec3::new(10.0, 0.0, 0.0), Vec3::new(0.0, 30.0, -500.0)] {
+            let velocity = ballista.bolt_velocity(Vec3::ZERO, target);
+
+            assert!((velocity.length() - 60.0).abs() < 1e-3);
+            assert!((velocity.y - 30.0).abs() < 1e-3);
+            assert!(
+                velocity
+                    .with_y(0.0)
+                    .normalize()
+                    .dot(target.with_y(0.0).normalize())
+                    > 0.999
+            );
+        }
+    }
+}
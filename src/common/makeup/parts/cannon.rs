@@ -0,0 +1,393 @@
+//! # Cannons
+//!
+//! A [CannonPart] fires a cannonball in response to `"fire_weapon"`
+//! [PartAction]s (see [FireWeaponCommand]), as long as it has reloaded, and
+//! there is ammunition of its caliber in the construct's inventory.
+//!
+//! Cannons work out how to hit the aim point with [solve_launch], then
+//! deviate from it by up to their spread. Targets beyond their range are
+//! fired at as far as they can reach. Firing pushes the construct back at
+//! the cannon's slot, by the momentum of the cannonball.
+//!
+//! [FireWeaponCommand]: crate::common::construct::action::FireWeaponCommand
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::common::{
+    ballistics::{ArcPreference, BallisticsEnv, CannonSpec, solve_launch},
+    construct::{
+        action::{PartAction, PartActionData, PartActionOutcome, PartActionResult},
+        part::PartInstalledOn,
+        slot::SlotAttachment,
+    },
+    crew::Unmanned,
+    damage::ImpactDamage,
+    inventory::{AmmoType, CannonDef, CannonballDef, modifier::ProjectileModifiers},
+    makeup::Ship,
+    physics::{
+        base::{PhysPoint, PointNetwork},
+        determinism::PhysicsRng,
+        forces::Gravity,
+        projectile::FastProjectile,
+        volume::{PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+    },
+};
+
+use super::{Reloads, attachment_point};
+
+/// How long cannonballs last, in seconds, before they are despawned.
+const CANNONBALL_LIFETIME: f32 = 20.0;
+
+/// A cannon part.
+#[derive(Component, Clone, Debug)]
+pub struct CannonPart {
+    /// The definition of this cannon.
+    pub def: CannonDef,
+
+    /// Time left until the cannon can fire again, in seconds.
+    cooldown: f32,
+}
+
+impl CannonPart {
+    /// A loaded cannon.
+    pub fn new(def: CannonDef) -> Self {
+        Self { def, cooldown: 0.0 }
+    }
+
+    /// Time left until the cannon can fire again, in seconds.
+    pub fn cooldown(&self) -> f32 {
+        self.cooldown
+    }
+
+    /// The properties of this cannon that matter for aiming.
+    ///
+    /// Its power is the muzzle speed of its cannonballs.
+    pub fn spec(&self) -> CannonSpec {
+        CannonSpec {
+            power_range: self.def.min_power..self.def.max_power,
+            spread: self.def.spread,
+        }
+    }
+}
+
+impl Reloads for CannonPart {
+    fn cooldown_mut(&mut self) -> &mut f32 {
+        &mut self.cooldown
+    }
+}
+
+/// A cannonball in flight.
+#[derive(Component, Clone, Debug)]
+pub struct Cannonball {
+    /// The ammunition this cannonball was fired from.
+    pub def: CannonballDef,
+
+    /// How long this cannonball has been around, in seconds.
+    pub age: f32,
+}
+
+/// The radius of a cannonball, from its caliber.
+fn cannonball_radius(caliber: u8) -> f32 {
+    // Caliber is a diameter, in tenths of millimeters.
+    caliber as f32 / 20_000.0
+}
+
+/// Which way to fire a cannon at, to hit a target.
+///
+/// Targets out of range are fired at from a 45° elevation, at full power.
+fn aim_velocity(spec: &CannonSpec, from: Vec3, target: Vec3) -> Vec3 {
+    let env = BallisticsEnv::from_gravity(&Gravity::default());
+
+    if let Some(solution) = solve_launch(from, target, spec, &env, ArcPreference::Direct) {
+        return solution.velocity();
+    }
+
+    let heading = (target - from).with_y(0.0).normalize_or(Vec3::NEG_Z);
+    (heading + Vec3::Y * FRAC_PI_4.tan()).normalize() * spec.power_range.end
+}
+
+/// Deviates a velocity randomly by up to an angle.
+fn apply_spread(velocity: Vec3, spread: f32, rng: &mut impl Rng) -> Vec3 {
+    let Some(direction) = velocity.try_normalize() else {
+        return velocity;
+    };
+    if spread <= 0.0 {
+        return velocity;
+    }
+
+    let axis = direction.any_orthonormal_vector();
+    let deviation = Quat::from_axis_angle(axis, rng.random_range(0.0..spread));
+    let roll = Quat::from_axis_angle(direction, rng.random_range(0.0..TAU));
+
+    roll * deviation * velocity
+}
+
+// Observer
+pub fn obs_cannon_fire(
+    trigger: Trigger<PartAction>,
+    mut commands: Commands,
+    mut cannons: Query<(
+        &mut CannonPart,
+        &PartInstalledOn,
+        Option<&ChildOf>,
+        Has<Unmanned>,
+    )>,
+    slots: Query<&SlotAttachment>,
+    mut constructs: Query<(&mut PointNetwork, Option<&mut Ship>, Option<&ChildOf>)>,
+    mut outcomes: EventWriter<PartActionOutcome>,
+    mut rng: ResMut<PhysicsRng>,
+) -> Result {
+    if trigger.action_tag != "fire_weapon" {
+        return Ok(());
+    }
+
+    let part = trigger.target();
+    let Ok((mut cannon, installed_on, slot, unmanned)) = cannons.get_mut(part) else {
+        return Ok(());
+    };
+    let mut report = |result| {
+        outcomes.write(PartActionOutcome {
+            trace_id: trigger.trace_id,
+            part,
+            result,
+        });
+    };
+
+    let PartActionData::FireWeapon(command) = &trigger.data else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    if unmanned {
+        report(PartActionResult::Unmanned);
+        return Ok(());
+    }
+
+    if cannon.cooldown > 0.0 {
+        report(PartActionResult::Cooldown);
+        return Ok(());
+    }
+
+    let construct = installed_on.get();
+    let Ok((mut network, ship, scene)) = constructs.get_mut(construct) else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    let caliber = cannon.def.caliber;
+    let Some((ammo, mass)) = ship.and_then(|mut ship| {
+        ship.makeup.take_ammo(
+            &command.ammo,
+            |ammo| matches!(&ammo.ammo_type, AmmoType::Cannonball(ball) if ball.caliber == caliber),
+        )
+    }) else {
+        report(PartActionResult::NoAmmo);
+        return Ok(());
+    };
+    let AmmoType::Cannonball(def) = ammo.ammo_type else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    let muzzle = attachment_point(part, slot, &slots, &network)?;
+    let velocity = apply_spread(
+        aim_velocity(&cannon.spec(), muzzle, command.aim_point),
+        cannon.def.spread,
+        &mut rng.0,
+    );
+
+    let cannonball = commands
+        .spawn((
+            Cannonball { def, age: 0.0 },
+            ImpactDamage::from_kinetic_energy(mass, velocity),
+            ProjectileModifiers(ammo.modifiers),
+            FastProjectile::from_source(construct),
+            PointNetwork::from([PhysPoint::new(muzzle, velocity, mass)].into_iter()),
+            VolumeCollection {
+                volumes: vec![PhysicsVolume {
+                    point_idx: 0,
+                    volume_type: VolumeType::Sphere(SphereDef::new(cannonball_radius(caliber))),
+                }],
+            },
+            Gravity::default(),
+            Transform::from_translation(muzzle),
+        ))
+        .id();
+
+    // Cannonballs belong to the same scene as the construct.
+    if let Some(scene) = scene {
+        commands.entity(scene.parent()).add_child(cannonball);
+    }
+
+    // Recoil.
+    network.apply_impulse_at(muzzle, -velocity * mass);
+    cannon.cooldown = cannon.def.fire_rate as f32 / 100.0;
+    report(PartActionResult::Performed);
+
+    Ok(())
+}
+
+pub fn expire_cannonballs(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut cannonballs: Query<(Entity, &mut Cannonball)>,
+) {
+    for (entity, mut cannonball) in &mut cannonballs {
+        cannonball.age += time.delta_secs();
+
+        if cannonball.age > CANNONBALL_LIFETIME {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::event::EventCursor, prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::{
+        construct::{
+            action::{FireWeaponCommand, PartAction},
+            part::PartInstalledOn,
+        },
+        fixtures::{item, makeup},
+        inventory::{AmmoDef, AmmoType, CannonDef, CannonballDef, InventoryDef, ItemType},
+        makeup::{Ship, parts::PartsPlugin},
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    fn cannonballs(caliber: u8, amount: f32) -> InventoryDef {
+        item(
+            &format!("cannonball {caliber}"),
+            ItemType::Ammo(AmmoDef {
+                ammo_type: AmmoType::Cannonball(CannonballDef { caliber }),
+                modifiers: vec![],
+            }),
+            0.5,
+            0.1,
+            amount,
+        )
+    }
+
+    #[test]
+    fn cannons_fire_matching_ammo_after_reloading() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PartsPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        // Two shots of the right caliber, and plenty of the wrong one.
+        let mut makeup = makeup(vec![]);
+        makeup.add_item(cannonballs(120, 2.0));
+        makeup.add_item(cannonballs(200, 50.0));
+
+        let construct = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 10.0)].into_iter()),
+            ))
+            .id();
+        let cannon = app
+            .world_mut()
+            .spawn((
+                CannonPart::new(CannonDef {
+                    min_power: 10.0,
+                    max_power: 40.0,
+                    spread: 0.0,
+                    fire_rate: 50,
+                    caliber: 120,
+                }),
+                PartInstalledOn::new(construct),
+            ))
+            .id();
+
+        let fire = |app: &mut App| {
+            app.world_mut().trigger_targets(
+                PartAction {
+                    action_tag: "fire_weapon".into(),
+                    trace_id: 0,
+                    data: PartActionData::FireWeapon(FireWeaponCommand {
+                        aim_point: Vec3::new(0.0, 0.0, -50.0),
+                        ammo: vec![],
+                    }),
+                },
+                cannon,
+            );
+            app.update();
+
+            app.world_mut()
+                .query::<&Cannonball>()
+                .iter(app.world())
+                .count()
+        };
+
+        let mut outcomes = EventCursor::<PartActionOutcome>::default();
+        let mut last_outcome = |app: &App| {
+            outcomes
+                .read(app.world().resource::<Events<PartActionOutcome>>())
+                .last()
+                .map(|outcome| outcome.result)
+        };
+
+        // Reloading after the first shot.
+        assert_eq!(fire(&mut app), 1);
+        assert_eq!(last_outcome(&app), Some(PartActionResult::Performed));
+        assert_eq!(fire(&mut app), 1);
+        assert_eq!(last_outcome(&app), Some(PartActionResult::Cooldown));
+
+        // The construct recoils away from the target.
+        let recoil = app.world().get::<PointNetwork>(construct).unwrap().points[0].vel;
+        assert!(recoil.z > 0.0);
+
+        for _ in 0..40 {
+            app.update();
+        }
+        assert_eq!(fire(&mut app), 2);
+
+        // Out of ammunition of the right caliber.
+        for _ in 0..40 {
+            app.update();
+        }
+        assert_eq!(fire(&mut app), 2);
+        assert_eq!(last_outcome(&app), Some(PartActionResult::NoAmmo));
+    }
+
+    #[test]
+    fn spread_is_reproducible_from_the_physics_seed() {
+        let velocity = Vec3::new(0.0, 10.0, -30.0);
+        let spread = |seed| {
+            let mut rng = PhysicsRng::from_seed(seed);
+            apply_spread(velocity, 0.1, &mut rng.0)
+        };
+
+        assert_eq!(spread(9), spread(9));
+        assert_ne!(spread(9), spread(10));
+
+        // Deviated by no more than the spread, at the same speed.
+        let deviated = spread(9);
+        assert!(deviated.angle_between(velocity) <= 0.1 + 1e-5);
+        assert!((deviated.length() - velocity.length()).abs() < 1e-3);
+    }
+}
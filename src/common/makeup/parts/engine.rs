@@ -0,0 +1,466 @@
+//! # Engines
+//!
+//! An [EnginePart] pushes the construct it is installed on forward, or
+//! backward, in response to `"thrust"` [PartAction]s (see [ThrustCommand]).
+//!
+//! The thrust is applied at the physics point its slot is attached to (see
+//! [SlotAttachment]), so engines off to one side of the construct also turn
+//! it. Engines which run on fuel burn it from the construct's inventory, and
+//! stall when there is none left. Engines of constructs hit by propeller gum
+//! (see [Gummed]) deliver less thrust until it wears off.
+//!
+//! Whenever an engine starts, stops or runs out of fuel, an
+//! [EngineStateChanged] event is sent, e.g. for sounds and particles.
+//!
+//! Ships with engines which run on fuel also get a [FuelGauge], which tells
+//! how much fuel is left, and how long it will last.
+//!
+//! [ThrustCommand]: crate::common::construct::action::ThrustCommand
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::common::{
+    construct::{
+        action::{PartAction, PartActionData, PartActionOutcome, PartActionResult},
+        part::PartInstalledOn,
+        slot::SlotAttachment,
+    },
+    crew::Unmanned,
+    inventory::{
+        EngineDef, FuelType,
+        modifier::{GUM_THRUST_FACTOR, Gummed},
+    },
+    makeup::Ship,
+    physics::base::PointNetwork,
+};
+
+use super::attachment_point;
+
+/// What an engine is doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EngineState {
+    /// Not asked to thrust.
+    #[default]
+    Idle,
+
+    /// Thrusting.
+    Running,
+
+    /// Asked to thrust, but out of fuel.
+    OutOfFuel,
+}
+
+/// An engine part.
+#[derive(Component, Clone, Debug)]
+pub struct EnginePart {
+    /// The definition of this engine.
+    pub def: EngineDef,
+
+    /// The last requested throttle, between -1.0 and 1.0.
+    throttle: f32,
+
+    /// What this engine is doing.
+    state: EngineState,
+}
+
+impl EnginePart {
+    /// An idle engine.
+    pub fn new(def: EngineDef) -> Self {
+        Self {
+            def,
+            throttle: 0.0,
+            state: EngineState::Idle,
+        }
+    }
+
+    /// How much fuel this engine burns per second, at a throttle.
+    pub fn burn_rate(&self, throttle: f32) -> f32 {
+        self.def.fuel_consumption as f32 / 1000.0 * throttle.abs()
+    }
+
+    /// The last requested throttle, between -1.0 (full reverse) and 1.0 (full
+    /// ahead).
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// What this engine is doing.
+    pub fn state(&self) -> EngineState {
+        self.state
+    }
+}
+
+/// Sent whenever an engine's [EngineState] changes.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EngineStateChanged {
+    /// The engine part.
+    pub engine: Entity,
+
+    /// The construct the engine is installed on.
+    pub construct: Entity,
+
+    /// The new state of the engine.
+    pub state: EngineState,
+
+    /// The throttle the engine was asked for.
+    pub throttle: f32,
+}
+
+/// The fuel of one type aboard a construct.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FuelTank {
+    /// The type of fuel.
+    pub fuel_type: FuelType,
+
+    /// How much of it is left.
+    pub left: f32,
+
+    /// How much of it the construct's engines burn per second, as they are
+    /// throttled now.
+    pub burn_rate: f32,
+
+    /// How much of it the construct's engines burn per second, at full
+    /// throttle.
+    pub max_burn_rate: f32,
+}
+
+impl FuelTank {
+    /// How long this fuel lasts, in seconds, at a burn rate.
+    ///
+    /// None if no fuel is burnt, i.e. it lasts forever.
+    fn endurance_at(&self, burn_rate: f32) -> Option<f32> {
+        (burn_rate > 0.0).then(|| self.left / burn_rate)
+    }
+}
+
+/// How much fuel a construct has for its engines, and how long it will last.
+///
+/// Kept up to date on every ship with engines which run on fuel, e.g. for
+/// the HUD and AI.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct FuelGauge {
+    /// Every type of fuel the construct's engines run on.
+    pub tanks: Vec<FuelTank>,
+}
+
+impl FuelGauge {
+    /// The fuel of a type, if any of the construct's engines run on it.
+    pub fn tank(&self, fuel_type: FuelType) -> Option<&FuelTank> {
+        self.tanks.iter().find(|tank| tank.fuel_type == fuel_type)
+    }
+
+    /// How long the engines can keep going as they are now, in seconds,
+    /// until the first of them runs out of fuel.
+    ///
+    /// None if no fuel is being burnt.
+    pub fn endurance(&self) -> Option<f32> {
+        self.tanks
+            .iter()
+            .filter_map(|tank| tank.endurance_at(tank.burn_rate))
+            .reduce(f32::min)
+    }
+
+    /// How long the engines can keep going at full throttle, in seconds,
+    /// until the first of them runs out of fuel.
+    pub fn endurance_at_full_throttle(&self) -> Option<f32> {
+        self.tanks
+            .iter()
+            .filter_map(|tank| tank.endurance_at(tank.max_burn_rate))
+            .reduce(f32::min)
+    }
+
+    /// How far the construct can go at a speed, as its engines are now,
+    /// until the first of them runs out of fuel.
+    ///
+    /// None if no fuel is being burnt.
+    pub fn range(&self, speed: f32) -> Option<f32> {
+        self.endurance().map(|endurance| endurance * speed)
+    }
+}
+
+// Observer
+pub fn obs_engine_thrust(
+    trigger: Trigger<PartAction>,
+    mut engines: Query<(&mut EnginePart, Has<Unmanned>)>,
+    mut outcomes: EventWriter<PartActionOutcome>,
+) {
+    if trigger.action_tag != "thrust" {
+        return;
+    }
+
+    let part = trigger.target();
+    let Ok((mut engine, unmanned)) = engines.get_mut(part) else {
+        return;
+    };
+
+    let result = match &trigger.data {
+        PartActionData::Thrust(_) if unmanned => PartActionResult::Unmanned,
+        PartActionData::Thrust(command) => {
+            engine.throttle = command.throttle.clamp(-1.0, 1.0);
+            PartActionResult::Performed
+        }
+        _ => PartActionResult::Incompatible,
+    };
+
+    outcomes.write(PartActionOutcome {
+        trace_id: trigger.trace_id,
+        part,
+        result,
+    });
+}
+
+pub fn apply_engine_thrust(
+    time: Res<Time>,
+    mut engines: Query<(Entity, &mut EnginePart, &PartInstalledOn, Option<&ChildOf>)>,
+    slots: Query<&SlotAttachment>,
+    mut constructs: Query<(
+        &mut PointNetwork,
+        &Transform,
+        Option<&mut Ship>,
+        Option<&Gummed>,
+    )>,
+    mut state_changes: EventWriter<EngineStateChanged>,
+) -> Result {
+    let delta_secs = time.delta_secs();
+
+    for (entity, mut engine, installed_on, slot) in &mut engines {
+        let Ok((mut network, transform, ship, gummed)) = constructs.get_mut(installed_on.get())
+        else {
+            continue;
+        };
+
+        // How much of the requested power the engine can deliver.
+        let mut output = engine.throttle;
+
+        if let Some(fuel_type) = engine.def.fuel_type.filter(|_| output != 0.0) {
+            let needed = engine.burn_rate(output) * delta_secs;
+            let taken = ship.map_or(0.0, |mut ship| ship.makeup.take_fuel(fuel_type, needed));
+
+            if needed > 0.0 {
+                output *= taken / needed;
+            }
+        }
+
+        let state = match (engine.throttle != 0.0, output != 0.0) {
+            (false, _) => EngineState::Idle,
+            (true, true) => EngineState::Running,
+            (true, false) => EngineState::OutOfFuel,
+        };
+
+        if state != engine.state {
+            engine.state = state;
+            state_changes.write(EngineStateChanged {
+                engine: entity,
+                construct: installed_on.get(),
+                state,
+                throttle: engine.throttle,
+            });
+        }
+
+        if output == 0.0 {
+            continue;
+        }
+
+        // Gummed up engines still burn fuel as usual, for less thrust.
+        if gummed.is_some() {
+            output *= GUM_THRUST_FACTOR;
+        }
+
+        let at = attachment_point(entity, slot, &slots, &network)?;
+
+        // [NOTE] EngineDef::power is treated as the thrust at full throttle,
+        // in Newtons.
+        let force = transform.forward() * engine.def.power as f32 * output;
+        network.apply_force_at(at, force, time.delta());
+    }
+
+    Ok(())
+}
+
+pub fn update_fuel_gauges(
+    mut commands: Commands,
+    engines: Query<(&EnginePart, &PartInstalledOn)>,
+    mut ships: Query<(&Ship, Option<&mut FuelGauge>)>,
+) {
+    let mut gauges = HashMap::<Entity, FuelGauge>::new();
+
+    for (engine, installed_on) in &engines {
+        let Some(fuel_type) = engine.def.fuel_type else {
+            continue;
+        };
+
+        let gauge = gauges.entry(installed_on.get()).or_default();
+        let tank = match gauge
+            .tanks
+            .iter()
+            .position(|tank| tank.fuel_type == fuel_type)
+        {
+            Some(idx) => &mut gauge.tanks[idx],
+            None => {
+                gauge.tanks.push(FuelTank {
+                    fuel_type,
+                    left: 0.0,
+                    burn_rate: 0.0,
+                    max_burn_rate: 0.0,
+                });
+                gauge.tanks.last_mut().unwrap()
+            }
+        };
+
+        if engine.state == EngineState::Running {
+            tank.burn_rate += engine.burn_rate(engine.throttle);
+        }
+        tank.max_burn_rate += engine.burn_rate(1.0);
+    }
+
+    for (construct, mut gauge) in gauges {
+        let Ok((ship, current)) = ships.get_mut(construct) else {
+            continue;
+        };
+
+        for tank in &mut gauge.tanks {
+            tank.left = ship.makeup.fuel_left(tank.fuel_type);
+        }
+
+        match current {
+            Some(mut current) => {
+                current.set_if_neq(gauge);
+            }
+            None => {
+                commands.entity(construct).insert(gauge);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::event::EventCursor, prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::{
+        construct::{
+            action::{PartAction, ThrustCommand},
+            part::PartInstalledOn,
+            slot::SlotAttachment,
+        },
+        fixtures::{item, make},
+        inventory::{EngineDef, FuelDef, FuelType, ItemType},
+        makeup::{Ship, ShipMake, ShipMakeup, livery::ShipLivery, parts::PartsPlugin},
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    #[test]
+    fn off_center_engines_push_and_turn() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PartsPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        // A bit of diesel, for a single tick of full thrust.
+        let mut makeup = ShipMakeup::new(
+            ShipMake {
+                hull_mass: 2.0,
+                ..make(vec![])
+            },
+            ShipLivery::default(),
+        );
+        makeup.add_item(item(
+            "diesel",
+            ItemType::Fuel(FuelDef {
+                fuel_type: FuelType::Diesel,
+            }),
+            1.0,
+            0.1,
+            0.01,
+        ));
+
+        // Facing -Z, with the engine to starboard.
+        let construct = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                Transform::default(),
+                PointNetwork::from(
+                    [
+                        PhysPoint::new(Vec3::NEG_X, Vec3::ZERO, 1.0),
+                        PhysPoint::new(Vec3::X, Vec3::ZERO, 1.0),
+                    ]
+                    .into_iter(),
+                ),
+            ))
+            .id();
+        let slot = app
+            .world_mut()
+            .spawn(SlotAttachment {
+                point_idx: 1,
+                offset: Vec3::ZERO,
+            })
+            .id();
+        let engine = app
+            .world_mut()
+            .spawn((
+                EnginePart::new(EngineDef {
+                    fuel_type: Some(FuelType::Diesel),
+                    power: 100,
+                    fuel_consumption: 1000,
+                }),
+                PartInstalledOn::new(construct),
+                ChildOf(slot),
+            ))
+            .id();
+
+        app.world_mut().trigger_targets(
+            PartAction {
+                action_tag: "thrust".into(),
+                trace_id: 0,
+                data: PartActionData::Thrust(ThrustCommand { throttle: 1.0 }),
+            },
+            engine,
+        );
+
+        let mut cursor = EventCursor::<EngineStateChanged>::default();
+        let mut states = vec![];
+        for _ in 0..8 {
+            app.update();
+            states.extend(
+                cursor
+                    .read(app.world().resource::<Events<EngineStateChanged>>())
+                    .map(|change| change.state),
+            );
+        }
+
+        // The engine stalls once the fuel runs out.
+        assert_eq!(states, [EngineState::Running, EngineState::OutOfFuel]);
+
+        let gauge = app.world().get::<FuelGauge>(construct).unwrap();
+        let tank = gauge.tank(FuelType::Diesel).unwrap();
+        assert_eq!(
+            (tank.left, tank.burn_rate, tank.max_burn_rate),
+            (0.0, 0.0, 1.0)
+        );
+        assert_eq!(gauge.endurance(), None);
+        assert_eq!(gauge.endurance_at_full_throttle(), Some(0.0));
+
+        let network = app.world().get::<PointNetwork>(construct).unwrap();
+        assert!(network.linear_velocity().z < 0.0);
+        assert!(network.angular_velocity().y > 0.0);
+    }
+}
@@ -0,0 +1,357 @@
+//! # Minelayers
+//!
+//! A [MinelayerPart] lays a naval mine in response to `"fire_weapon"`
+//! [PartAction]s (see [FireWeaponCommand]), as long as it has reloaded, and
+//! there are mines in the construct's inventory. The aim point is ignored;
+//! mines are always dropped behind the construct.
+//!
+//! Mines float, and arm after a short delay, so that they can be laid
+//! safely. Once armed, they detonate as soon as any ship comes within their
+//! trigger range, sending a [MineDetonated] event.
+//!
+//! [FireWeaponCommand]: crate::common::construct::action::FireWeaponCommand
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::{
+    construct::{
+        action::{PartAction, PartActionData, PartActionOutcome, PartActionResult},
+        part::PartInstalledOn,
+        slot::SlotAttachment,
+    },
+    crew::Unmanned,
+    inventory::{AmmoType, MineDef, MinelayerDef},
+    makeup::Ship,
+    physics::{
+        base::{PhysPoint, PointNetwork},
+        forces::Gravity,
+        projectile::FastProjectile,
+        volume::{PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+        water::WaterPhysics,
+    },
+};
+
+use super::{Reloads, attachment_point};
+
+/// How long mines take to arm after being laid, in seconds.
+const MINE_ARM_DELAY: f32 = 3.0;
+
+/// The radius of naval mines.
+const MINE_RADIUS: f32 = 0.4;
+
+/// A minelayer part.
+#[derive(Component, Clone, Debug)]
+pub struct MinelayerPart {
+    /// The definition of this minelayer.
+    pub def: MinelayerDef,
+
+    /// Time left until the minelayer can lay another mine, in seconds.
+    cooldown: f32,
+}
+
+impl MinelayerPart {
+    /// A loaded minelayer.
+    pub fn new(def: MinelayerDef) -> Self {
+        Self { def, cooldown: 0.0 }
+    }
+
+    /// Time left until the minelayer can lay another mine, in seconds.
+    pub fn cooldown(&self) -> f32 {
+        self.cooldown
+    }
+}
+
+impl Reloads for MinelayerPart {
+    fn cooldown_mut(&mut self) -> &mut f32 {
+        &mut self.cooldown
+    }
+}
+
+/// A naval mine, laid at sea.
+#[derive(Component, Clone, Debug)]
+pub struct NavalMine {
+    /// The ammunition this mine was laid from.
+    pub def: MineDef,
+
+    /// The construct which laid this mine.
+    pub source: Entity,
+
+    /// Time left until the mine is armed, in seconds.
+    arm_delay: f32,
+}
+
+impl NavalMine {
+    /// Whether the mine is armed, i.e. will detonate near ships.
+    pub fn is_armed(&self) -> bool {
+        self.arm_delay <= 0.0
+    }
+}
+
+/// Sent whenever a naval mine detonates.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct MineDetonated {
+    /// The mine, which is despawned.
+    pub mine: Entity,
+
+    /// The construct which laid the mine.
+    pub source: Entity,
+
+    /// Where the mine detonated, in world space.
+    pub at: Vec3,
+
+    /// Explosion power.
+    pub power: f32,
+}
+
+/// A construct laying a mine, and what is needed to lay it.
+type MineLayingConstruct = (
+    &'static PointNetwork,
+    &'static Transform,
+    Option<&'static mut Ship>,
+    Option<&'static WaterPhysics>,
+    Option<&'static ChildOf>,
+);
+
+// Observer
+pub fn obs_minelayer_fire(
+    trigger: Trigger<PartAction>,
+    mut commands: Commands,
+    mut minelayers: Query<(
+        &mut MinelayerPart,
+        &PartInstalledOn,
+        Option<&ChildOf>,
+        Has<Unmanned>,
+    )>,
+    slots: Query<&SlotAttachment>,
+    mut constructs: Query<MineLayingConstruct>,
+    mut outcomes: EventWriter<PartActionOutcome>,
+) -> Result {
+    if trigger.action_tag != "fire_weapon" {
+        return Ok(());
+    }
+
+    let part = trigger.target();
+    let Ok((mut minelayer, installed_on, slot, unmanned)) = minelayers.get_mut(part) else {
+        return Ok(());
+    };
+    let mut report = |result| {
+        outcomes.write(PartActionOutcome {
+            trace_id: trigger.trace_id,
+            part,
+            result,
+        });
+    };
+
+    let PartActionData::FireWeapon(command) = &trigger.data else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    if unmanned {
+        report(PartActionResult::Unmanned);
+        return Ok(());
+    }
+
+    if minelayer.cooldown > 0.0 {
+        report(PartActionResult::Cooldown);
+        return Ok(());
+    }
+
+    let construct = installed_on.get();
+    let Ok((network, transform, ship, water, scene)) = constructs.get_mut(construct) else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    let Some((ammo, mass)) = ship.and_then(|mut ship| {
+        ship.makeup.take_ammo(&command.ammo, |ammo| {
+            matches!(ammo.ammo_type, AmmoType::NavalMine(_))
+        })
+    }) else {
+        report(PartActionResult::NoAmmo);
+        return Ok(());
+    };
+    let AmmoType::NavalMine(def) = ammo.ammo_type else {
+        report(PartActionResult::Incompatible);
+        return Ok(());
+    };
+
+    let at = attachment_point(part, slot, &slots, network)?;
+    let velocity = network.linear_velocity() + transform.back() * minelayer.def.power;
+
+    let mine = commands
+        .spawn((
+            NavalMine {
+                def,
+                source: construct,
+                arm_delay: MINE_ARM_DELAY,
+            },
+            FastProjectile::from_source(construct),
+            PointNetwork::from([PhysPoint::new(at, velocity, mass)].into_iter()),
+            VolumeCollection {
+                volumes: vec![PhysicsVolume {
+                    point_idx: 0,
+                    volume_type: VolumeType::Sphere(SphereDef::new(MINE_RADIUS)),
+                }],
+            },
+            Gravity::default(),
+            water.cloned().unwrap_or_default(),
+            Transform::from_translation(at),
+        ))
+        .id();
+
+    // Mines belong to the same scene as the construct.
+    if let Some(scene) = scene {
+        commands.entity(scene.parent()).add_child(mine);
+    }
+
+    minelayer.cooldown = minelayer.def.fire_rate as f32 / 100.0;
+    report(PartActionResult::Performed);
+
+    Ok(())
+}
+
+pub fn arm_mines(time: Res<Time>, mut mines: Query<&mut NavalMine>) {
+    for mut mine in &mut mines {
+        if mine.arm_delay > 0.0 {
+            mine.arm_delay -= time.delta_secs();
+        }
+    }
+}
+
+pub fn detonate_mines(
+    mut commands: Commands,
+    mines: Query<(Entity, &NavalMine, &PointNetwork)>,
+    ships: Query<&PointNetwork, With<Ship>>,
+    mut detonations: EventWriter<MineDetonated>,
+) {
+    for (entity, mine, points) in &mines {
+        if !mine.is_armed() {
+            continue;
+        }
+
+        let at = points.center_of_mass();
+        let triggered = ships
+            .iter()
+            .any(|ship| ship.center_of_mass().distance(at) <= mine.def.trigger_range);
+
+        if triggered {
+            detonations.write(MineDetonated {
+                mine: entity,
+                source: mine.source,
+                at,
+                power: mine.def.power,
+            });
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::event::EventCursor, prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::{
+        construct::{
+            action::{FireWeaponCommand, PartAction},
+            part::PartInstalledOn,
+        },
+        fixtures::{item, makeup},
+        inventory::{AmmoDef, AmmoType, ItemType, MineDef, MinelayerDef},
+        makeup::{Ship, parts::PartsPlugin},
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    #[test]
+    fn mines_arm_before_detonating() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PartsPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        let mut makeup = makeup(vec![]);
+        makeup.add_item(item(
+            "mine",
+            ItemType::Ammo(AmmoDef {
+                ammo_type: AmmoType::NavalMine(MineDef {
+                    trigger_range: 3.0,
+                    power: 50.0,
+                }),
+                modifiers: vec![],
+            }),
+            2.0,
+            0.1,
+            1.0,
+        ));
+
+        // The layer stays put, right next to its own mine.
+        let construct = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                Transform::default(),
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 10.0)].into_iter()),
+            ))
+            .id();
+        let minelayer = app
+            .world_mut()
+            .spawn((
+                MinelayerPart::new(MinelayerDef {
+                    power: 1.0,
+                    fire_rate: 100,
+                }),
+                PartInstalledOn::new(construct),
+            ))
+            .id();
+
+        app.world_mut().trigger_targets(
+            PartAction {
+                action_tag: "fire_weapon".into(),
+                trace_id: 0,
+                data: PartActionData::FireWeapon(FireWeaponCommand::default()),
+            },
+            minelayer,
+        );
+
+        let mut cursor = EventCursor::<MineDetonated>::default();
+        let mut detonated_after = None;
+        for tick in 0..400 {
+            app.update();
+
+            let detonations = app.world().resource::<Events<MineDetonated>>();
+            if let Some(detonation) = cursor.read(detonations).next() {
+                assert_eq!(detonation.source, construct);
+                detonated_after = Some(tick as f32 / 64.0);
+                break;
+            }
+        }
+
+        let detonated_after = detonated_after.expect("mine should have detonated");
+        assert!(detonated_after >= MINE_ARM_DELAY - 0.1, "{detonated_after}");
+        assert_eq!(
+            app.world_mut()
+                .query::<&NavalMine>()
+                .iter(app.world())
+                .count(),
+            0
+        );
+    }
+}
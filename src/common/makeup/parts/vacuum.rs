@@ -0,0 +1,209 @@
+//! # Vacuums
+//!
+//! A [VacuumPart] pulls [Pickup]s within its suck radius towards itself, and
+//! stores those that get close enough in its construct's inventory, sending
+//! an [ItemCollectedEvent] for each.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::{
+    construct::{part::PartInstalledOn, slot::SlotAttachment},
+    inventory::{
+        VacuumDef,
+        pickup::{ItemCollectedEvent, Pickup},
+    },
+    makeup::Ship,
+    physics::base::PointNetwork,
+};
+
+use super::attachment_point;
+
+/// How close pickups need to get to a vacuum to be collected.
+const COLLECT_RANGE: f32 = 2.0;
+
+/// A vacuum part.
+#[derive(Component, Clone, Debug)]
+pub struct VacuumPart {
+    /// The definition of this vacuum.
+    pub def: VacuumDef,
+}
+
+impl VacuumPart {
+    /// A vacuum part.
+    pub fn new(def: VacuumDef) -> Self {
+        Self { def }
+    }
+}
+
+pub fn vacuum_pickups(
+    time: Res<Time>,
+    mut commands: Commands,
+    vacuums: Query<(Entity, &VacuumPart, &PartInstalledOn, Option<&ChildOf>)>,
+    slots: Query<&SlotAttachment>,
+    mut constructs: Query<(&PointNetwork, &mut Ship), Without<Pickup>>,
+    mut pickups: Query<(Entity, &mut Pickup, &mut PointNetwork)>,
+    mut collected: EventWriter<ItemCollectedEvent>,
+) -> Result {
+    for (part, vacuum, installed_on, slot) in &vacuums {
+        let construct = installed_on.get();
+        let Ok((network, mut ship)) = constructs.get_mut(construct) else {
+            continue;
+        };
+
+        let at = attachment_point(part, slot, &slots, network)?;
+
+        for (pickup, mut contents, mut points) in &mut pickups {
+            if contents.item().is_none() {
+                continue;
+            }
+
+            let offset = at - points.center_of_mass();
+            let distance = offset.length();
+
+            if distance <= COLLECT_RANGE {
+                let Some(item) = contents.take() else {
+                    continue;
+                };
+                let (name, amount) = (item.name.clone(), item.amount);
+                let key = ship.makeup.add_item(item);
+
+                collected.write(ItemCollectedEvent {
+                    pickup,
+                    collector: construct,
+                    name,
+                    amount,
+                    key,
+                });
+                commands.entity(pickup).despawn();
+            } else if distance <= vacuum.def.suck_radius {
+                points.apply_force_over_time(
+                    offset / distance * vacuum.def.suck_strength,
+                    time.delta_secs(),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::event::EventCursor, prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::{
+        construct::part::PartInstalledOn,
+        fixtures::{item, makeup},
+        inventory::{FoodDef, InventoryDef, ItemType, VacuumDef, pickup::spawn_pickup},
+        makeup::{Ship, parts::PartsPlugin},
+        physics::{
+            base::{PhysPoint, PointNetwork},
+            water::WaterPhysics,
+        },
+    };
+
+    use super::*;
+
+    fn biscuits(amount: f32) -> InventoryDef {
+        item(
+            "biscuits",
+            ItemType::Food(FoodDef { food_points: 5 }),
+            0.2,
+            0.1,
+            amount,
+        )
+    }
+
+    #[test]
+    fn vacuums_pull_and_collect_pickups() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PartsPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        let construct = app
+            .world_mut()
+            .spawn((
+                Ship {
+                    makeup: makeup(vec![]),
+                },
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 10.0)].into_iter()),
+            ))
+            .id();
+        app.world_mut().spawn((
+            VacuumPart::new(VacuumDef {
+                suck_radius: 10.0,
+                suck_strength: 5.0,
+            }),
+            PartInstalledOn::new(construct),
+        ));
+
+        let (near, far, away) = {
+            let mut commands = app.world_mut().commands();
+            let near = spawn_pickup(
+                &mut commands,
+                biscuits(3.0),
+                Vec3::X,
+                Vec3::ZERO,
+                WaterPhysics::default(),
+            );
+            let far = spawn_pickup(
+                &mut commands,
+                biscuits(1.0),
+                Vec3::X * 8.0,
+                Vec3::ZERO,
+                WaterPhysics::default(),
+            );
+            let away = spawn_pickup(
+                &mut commands,
+                biscuits(1.0),
+                Vec3::X * 20.0,
+                Vec3::ZERO,
+                WaterPhysics::default(),
+            );
+            (near, far, away)
+        };
+        app.world_mut().flush();
+
+        let mut cursor = EventCursor::<ItemCollectedEvent>::default();
+        let mut collected = vec![];
+        for _ in 0..4 {
+            app.update();
+
+            let events = app.world().resource::<Events<ItemCollectedEvent>>();
+            collected.extend(cursor.read(events).cloned());
+        }
+
+        // The near pickup is collected, the far one pulled in, and the one
+        // away left alone.
+        assert!(app.world().get_entity(near).is_err());
+        assert_eq!(collected.len(), 1);
+        assert_eq!(
+            (collected[0].collector, collected[0].amount),
+            (construct, 3.0)
+        );
+
+        let pulled = |entity| app.world().get::<PointNetwork>(entity).unwrap().points[0].vel;
+        assert!(pulled(far).x < 0.0);
+        assert_eq!(pulled(away), Vec3::ZERO);
+
+        let ship = app.world().get::<Ship>(construct).unwrap();
+        assert!(ship.makeup.get_total_mass() > 10.0);
+    }
+}
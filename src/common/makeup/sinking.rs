@@ -0,0 +1,279 @@
+//! # Sinking
+//!
+//! Ships whose hull [Health] runs out do not vanish; they start [Sinking].
+//! Right away, their crew abandon ship, their installed parts are wrecked,
+//! and their cargo is thrown overboard, each item floating off as a
+//! [Pickup](crate::common::inventory::pickup::Pickup) with its own drop
+//! chance, or else lost.
+//!
+//! Then, the sea floods in, and the ship's buoyancy dwindles until it goes
+//! under. Once deep enough, the ship is despawned, sending a [ShipSunkEvent].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::Ship;
+use crate::common::{
+    construct::{install::uninstall_part, part::ConstructParts},
+    crew::Crew,
+    damage::{Health, Wrecked},
+    inventory::pickup::spawn_pickup,
+    physics::{base::PointNetwork, determinism::PhysicsRng, water::WaterPhysics},
+};
+
+/// How long it takes for a sinking ship to lose all of its buoyancy, in
+/// seconds.
+const FLOOD_TIME: f32 = 10.0;
+
+/// How far below the water level a sinking ship must go to be despawned.
+const SUNK_DEPTH: f32 = 20.0;
+
+/// How fast cargo is thrown overboard, at most.
+const DROP_SPEED: f32 = 3.0;
+
+/// A ship going under.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Sinking {
+    /// How long the ship has been sinking, in seconds.
+    pub elapsed: f32,
+
+    /// The buoyancy factor of the ship before it began sinking.
+    initial_buoyancy: f32,
+}
+
+impl Sinking {
+    /// How flooded the ship is, between 0.0 and 1.0.
+    pub fn flooding(&self) -> f32 {
+        (self.elapsed / FLOOD_TIME).clamp(0.0, 1.0)
+    }
+}
+
+/// Sent whenever a sinking ship goes deep enough to be despawned.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ShipSunkEvent {
+    /// The ship, which is despawned.
+    pub ship: Entity,
+
+    /// Where the ship was last, in world space.
+    pub at: Vec3,
+}
+
+/// Whether a dropped item with the given drop chance, in percent, makes it.
+fn rolls_drop(drop_chance: u8, rng: &mut impl Rng) -> bool {
+    rng.random_range(0..100) < drop_chance
+}
+
+/// A ship which may start sinking, and what it drops when it does.
+type WreckedShip = (
+    Entity,
+    &'static Health,
+    &'static mut Ship,
+    &'static PointNetwork,
+    Option<&'static WaterPhysics>,
+    Option<&'static ConstructParts>,
+    Option<&'static ChildOf>,
+    Option<&'static mut Crew>,
+);
+
+fn start_sinking(
+    mut commands: Commands,
+    mut ships: Query<WreckedShip, Without<Sinking>>,
+    mut rng: ResMut<PhysicsRng>,
+) {
+    let rng = &mut rng.0;
+
+    for (entity, health, mut ship, points, water, parts, scene, crew) in &mut ships {
+        if !health.is_depleted() {
+            continue;
+        }
+
+        let water = water.cloned().unwrap_or_default();
+        let at = points.center_of_mass();
+        let velocity = points.linear_velocity();
+
+        for part in parts.iter().flat_map(|parts| parts.iter()) {
+            uninstall_part(&mut commands, *part);
+            commands.entity(*part).insert(Wrecked { dropped: false });
+        }
+
+        for item in ship.makeup.drain_items() {
+            if !rolls_drop(item.drop_chance, rng) {
+                continue;
+            }
+
+            let heading = Vec2::from_angle(rng.random_range(0.0..TAU));
+            let scatter = heading.extend(0.0).xzy() * rng.random_range(0.0..DROP_SPEED);
+            let pickup = spawn_pickup(
+                &mut commands,
+                item,
+                at + scatter.normalize_or_zero(),
+                velocity + scatter,
+                water.clone(),
+            );
+
+            // Pickups belong to the same scene as the ship.
+            if let Some(scene) = scene {
+                commands.entity(scene.parent()).add_child(pickup);
+            }
+        }
+
+        // Abandon ship!
+        if let Some(mut crew) = crew {
+            crew.members.clear();
+        }
+
+        commands.entity(entity).insert(Sinking {
+            elapsed: 0.0,
+            initial_buoyancy: water.buoyancy_factor,
+        });
+    }
+}
+
+fn flood_sinking_ships(
+    time: Res<Time>,
+    mut ships: Query<(&mut Sinking, Option<&mut WaterPhysics>)>,
+) {
+    for (mut sinking, water) in &mut ships {
+        sinking.elapsed += time.delta_secs();
+
+        if let Some(mut water) = water {
+            water.buoyancy_factor = sinking.initial_buoyancy * (1.0 - sinking.flooding());
+        }
+    }
+}
+
+fn despawn_sunk_ships(
+    mut commands: Commands,
+    ships: Query<(Entity, &PointNetwork, Option<&WaterPhysics>), With<Sinking>>,
+    mut sunk: EventWriter<ShipSunkEvent>,
+) {
+    for (entity, points, water) in &ships {
+        let water_level = water.map_or(0.0, |water| water.water_level);
+        let at = points.center_of_mass();
+
+        if at.y < water_level - SUNK_DEPTH {
+            sunk.write(ShipSunkEvent { ship: entity, at });
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Sinks ships whose health runs out.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct SinkingPlugin;
+
+impl Plugin for SinkingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShipSunkEvent>();
+        app.init_resource::<PhysicsRng>();
+        app.add_systems(
+            FixedUpdate,
+            (start_sinking, flood_sinking_ships, despawn_sunk_ships).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        damage::Health,
+        fixtures::{item, makeup},
+        inventory::{FoodDef, InventoryDef, ItemType, pickup::Pickup},
+        makeup::Ship,
+        physics::{
+            base::{PhysPoint, PointNetwork},
+            water::WaterPhysics,
+        },
+    };
+
+    use super::*;
+
+    fn cargo(drop_chance: u8) -> InventoryDef {
+        InventoryDef {
+            drop_chance,
+            ..item(
+                "biscuits",
+                ItemType::Food(FoodDef { food_points: 5 }),
+                1.0,
+                0.1,
+                1.0,
+            )
+        }
+    }
+
+    #[test]
+    fn wrecked_ships_drop_loot_then_sink() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, SinkingPlugin));
+
+        let mut makeup = makeup(vec![]);
+        makeup.add_item(cargo(100));
+        makeup.add_item(cargo(0));
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                Health {
+                    current: 0.0,
+                    max: 100.0,
+                },
+                WaterPhysics::default(),
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 10.0)].into_iter()),
+            ))
+            .id();
+
+        app.world_mut().run_schedule(FixedUpdate);
+
+        // Only the cargo sure to drop makes it.
+        assert!(app.world().get::<Sinking>(ship).is_some());
+        assert_eq!(
+            app.world_mut().query::<&Pickup>().iter(app.world()).count(),
+            1
+        );
+        let makeup = &app.world().get::<Ship>(ship).unwrap().makeup;
+        assert_eq!(makeup.get_total_mass(), 10.0);
+
+        // The sea floods in.
+        for _ in 0..3 {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_secs(5));
+            app.world_mut().run_schedule(FixedUpdate);
+        }
+        let water = app.world().get::<WaterPhysics>(ship).unwrap();
+        assert_eq!(water.buoyancy_factor, 0.0);
+        assert!(app.world().get_entity(ship).is_ok());
+
+        // Down it goes.
+        app.world_mut()
+            .get_mut::<PointNetwork>(ship)
+            .unwrap()
+            .points[0]
+            .pos
+            .y = -SUNK_DEPTH - 1.0;
+        app.world_mut().run_schedule(FixedUpdate);
+
+        assert!(app.world().get_entity(ship).is_err());
+        let events = app.world().resource::<Events<ShipSunkEvent>>();
+        assert_eq!(events.iter_current_update_events().count(), 1);
+    }
+}
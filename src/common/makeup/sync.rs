@@ -0,0 +1,373 @@
+//! # Ship makeups as constructs
+//!
+//! A [Ship]'s [ShipMakeup] and the construct entity system describe the same
+//! thing: the slots of the ship, and which parts are installed on them. This
+//! keeps both views in sync.
+//!
+//! * When a ship is spawned, a slot entity is spawned for every slot of its
+//!   [ShipMake](super::ShipMake), marked with [MakeupSlot]. Parts installed
+//!   on the makeup are spawned as part entities, marked with [MakeupItem],
+//!   and installed on their slots.
+//! * Whenever parts are installed or uninstalled through the construct
+//!   system, the makeup is updated to match.
+//! * Armor parts wearing down wear down their item as well, so that worn
+//!   armor stays worn when it is uninstalled, sold, or saved.
+//! * The total mass of the makeup, installed parts included, is the mass of
+//!   the ship's [PointNetwork], so that what a ship carries affects how it
+//!   handles. Installed parts weigh down the point their slot is attached to
+//!   (see [PartMass]); the rest of the mass is spread as the hull's is.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use slotmap::DefaultKey;
+
+use super::{
+    Ship, ShipMakeup,
+    parts::{
+        armor::ArmorPart, ballista::BallistaPart, cannon::CannonPart, engine::EnginePart,
+        minelayer::MinelayerPart, vacuum::VacuumPart,
+    },
+};
+use crate::common::{
+    construct::{
+        install::install_part_on_slot,
+        layout::{PartMass, PartMassDistribution, distribute_part_mass},
+        part::PartInstalledOn,
+        slot::{ConstructSlots, PartInfo, PartSlotInfo, SlotAttachment, SlotOfConstruct},
+    },
+    crew::Manning,
+    inventory::{GunTypeDef, InventoryDef, ItemPartDef, ItemType, PartTypeDef},
+    physics::base::PointNetwork,
+};
+
+/// A slot entity, spawned from a slot of a [ShipMake](super::ShipMake).
+///
+/// Wraps the index of the slot.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MakeupSlot(pub usize);
+
+/// A part entity, spawned from an item in a [ShipMakeup]'s inventory.
+///
+/// Wraps the key of the item.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MakeupItem(pub DefaultKey);
+
+/// The part tags of a part definition, i.e. which slot types it fits in.
+pub fn part_tags(def: &ItemPartDef) -> Vec<String> {
+    let tags: &[&str] = match &def.part_type {
+        PartTypeDef::Gun(gun) => match gun.gun_type {
+            GunTypeDef::Cannon(_) => &["gun", "cannon"],
+            GunTypeDef::Ballista(_) => &["gun", "ballista"],
+            GunTypeDef::Minelayer(_) => &["gun", "minelayer"],
+        },
+        PartTypeDef::Engine(_) => &["engine"],
+        PartTypeDef::Vacuum(_) => &["vacuum"],
+        PartTypeDef::Armor(_) => &["armor"],
+    };
+
+    tags.iter().map(|tag| tag.to_string()).collect()
+}
+
+/// Spawns a part entity for an item of a ship's inventory, with the
+/// behavior of its part definition.
+///
+/// Returns None if the item is not a part.
+pub fn spawn_makeup_part(
+    commands: &mut Commands,
+    makeup: &ShipMakeup,
+    key: DefaultKey,
+) -> Option<Entity> {
+    let ItemType::Part(def) = &makeup.item(key)?.item_type else {
+        return None;
+    };
+
+    let mass = makeup.item(key)?.mass;
+    let mut part = commands.spawn((
+        PartInfo {
+            tags: part_tags(def),
+        },
+        MakeupItem(key),
+        Manning(def.manned),
+        PartMass(mass),
+        Transform::default(),
+    ));
+
+    match &def.part_type {
+        PartTypeDef::Gun(gun) => match &gun.gun_type {
+            GunTypeDef::Cannon(def) => part.insert(CannonPart::new(def.clone())),
+            GunTypeDef::Ballista(def) => part.insert(BallistaPart::new(def.clone())),
+            GunTypeDef::Minelayer(def) => part.insert(MinelayerPart::new(def.clone())),
+        },
+        PartTypeDef::Engine(def) => part.insert(EnginePart::new(def.clone())),
+        PartTypeDef::Vacuum(def) => part.insert(VacuumPart::new(def.clone())),
+        PartTypeDef::Armor(def) => part.insert(ArmorPart::new(def.clone())),
+    };
+
+    Some(part.id())
+}
+
+fn spawn_makeup_slots(mut commands: Commands, ships: Query<(Entity, &Ship), Added<Ship>>) {
+    for (construct, ship) in &ships {
+        let makeup = &ship.makeup;
+
+        for (idx, slot) in makeup.make().slots.iter().enumerate() {
+            let slot_id = commands
+                .spawn((
+                    PartSlotInfo {
+                        slot_type: slot.part_type.clone(),
+                    },
+                    SlotAttachment {
+                        point_idx: slot.point_attachment,
+                        offset: slot.offset,
+                    },
+                    SlotOfConstruct::new(construct),
+                    MakeupSlot(idx),
+                    Transform::default(),
+                ))
+                .id();
+
+            let part = makeup
+                .installed(idx)
+                .and_then(|key| spawn_makeup_part(&mut commands, makeup, key));
+            if let Some(part) = part {
+                install_part_on_slot(&mut commands, part, slot_id);
+            }
+        }
+    }
+}
+
+fn sync_makeup_parts(
+    mut ships: Query<(&mut Ship, &ConstructSlots)>,
+    slots: Query<(&MakeupSlot, Option<&Children>)>,
+    parts: Query<&MakeupItem>,
+) -> Result {
+    for (mut ship, construct_slots) in &mut ships {
+        for &slot in construct_slots.iter() {
+            let Ok((&MakeupSlot(idx), children)) = slots.get(slot) else {
+                continue;
+            };
+
+            let installed = children
+                .into_iter()
+                .flatten()
+                .find_map(|&part| parts.get(part).ok())
+                .map(|item| item.0);
+
+            if ship.makeup.installed(idx) != installed {
+                ship.makeup.set_installed(idx, installed)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn sync_armor_wear(
+    parts: Query<(&ArmorPart, &MakeupItem, &PartInstalledOn), Changed<ArmorPart>>,
+    mut ships: Query<&mut Ship>,
+) {
+    for (armor, item, installed_on) in &parts {
+        let Ok(mut ship) = ships.get_mut(installed_on.get()) else {
+            continue;
+        };
+
+        let Some(InventoryDef {
+            item_type:
+                ItemType::Part(ItemPartDef {
+                    part_type: PartTypeDef::Armor(def),
+                    ..
+                }),
+            ..
+        }) = ship.bypass_change_detection().makeup.item_mut(item.0)
+        else {
+            continue;
+        };
+
+        if def.wear != armor.wear() {
+            def.wear = armor.wear();
+            ship.set_changed();
+        }
+    }
+}
+
+fn apply_makeup_mass(
+    mut ships: Query<(&Ship, &mut PointNetwork, Option<&PartMassDistribution>), Changed<Ship>>,
+) {
+    for (ship, mut network, parts) in &mut ships {
+        // Installed parts are weighed separately, where they are installed.
+        let installed = ship
+            .makeup
+            .part_iter()
+            .map(|(item, _)| item.mass)
+            .sum::<f32>();
+        let mass = ship.makeup.get_total_mass() - installed;
+
+        let part_mass = |idx: usize| parts.map_or(0.0, |parts| parts.at(idx));
+        let old_total = network
+            .points
+            .iter()
+            .enumerate()
+            .map(|(idx, point)| point.mass - part_mass(idx))
+            .sum::<f32>();
+
+        if old_total <= 0.0 || mass <= 0.0 || old_total == mass {
+            continue;
+        }
+
+        let scale = mass / old_total;
+        for (idx, point) in network.points.iter_mut().enumerate() {
+            point.mass = part_mass(idx) + (point.mass - part_mass(idx)) * scale;
+        }
+    }
+}
+
+/// Keeps ship makeups and their construct entities in sync.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct MakeupSyncPlugin;
+
+impl Plugin for MakeupSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                spawn_makeup_slots,
+                sync_makeup_parts,
+                sync_armor_wear,
+                apply_makeup_mass,
+            )
+                .chain()
+                .before(distribute_part_mass),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::{
+        construct::{ConstructPlugin, install::uninstall_part, part::ConstructParts},
+        fixtures::{item, makeup, slot},
+        inventory::{ArmorDef, EngineDef, ItemPartDef, ItemType, ManningType, PartTypeDef},
+        makeup::Ship,
+        physics::base::{PhysPoint, PointNetwork},
+    };
+
+    use super::*;
+
+    #[test]
+    fn makeup_parts_follow_construct_parts() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin, MakeupSyncPlugin));
+
+        let mut makeup = makeup(vec![slot("gun"), slot("engine")]);
+        let engine = makeup.add_item(item(
+            "oars",
+            ItemType::Part(ItemPartDef {
+                part_type: PartTypeDef::Engine(EngineDef {
+                    fuel_type: None,
+                    power: 100,
+                    fuel_consumption: 0,
+                }),
+                manned: ManningType::AnyManned,
+            }),
+            2.0,
+            1.0,
+            1.0,
+        ));
+        makeup.set_installed(1, Some(engine)).unwrap();
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 1.0)].into_iter()),
+            ))
+            .id();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        // The engine is installed on its own slot, and weighs the ship down.
+        let slots = app.world().get::<ConstructSlots>(ship).unwrap();
+        assert_eq!(slots.iter().count(), 2);
+        let parts = app.world().get::<ConstructParts>(ship).unwrap();
+        let part = *parts.iter().next().unwrap();
+        assert!(app.world().get::<EnginePart>(part).is_some());
+        let network = app.world().get::<PointNetwork>(ship).unwrap();
+        assert_eq!(network.points[0].mass, 12.0);
+
+        uninstall_part(&mut app.world_mut().commands(), part);
+        app.world_mut().flush();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let makeup = &app.world().get::<Ship>(ship).unwrap().makeup;
+        assert_eq!(makeup.installed(1), None);
+        assert!(makeup.item(engine).is_some());
+    }
+
+    #[test]
+    fn armor_wear_is_kept_on_its_item() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ConstructPlugin, MakeupSyncPlugin));
+
+        let mut makeup = makeup(vec![slot("armor")]);
+        let plate = makeup.add_item(item(
+            "plate",
+            ItemType::Part(ItemPartDef {
+                part_type: PartTypeDef::Armor(ArmorDef {
+                    defense_factor: 100,
+                    wear_factor: 100,
+                    deflect_factor: 0,
+                    overwhelm_factor: 0,
+                    wear: 0.0,
+                }),
+                manned: ManningType::Unmanned,
+            }),
+            2.0,
+            1.0,
+            1.0,
+        ));
+        makeup.set_installed(0, Some(plate)).unwrap();
+
+        let ship = app
+            .world_mut()
+            .spawn((
+                Ship { makeup },
+                PointNetwork::from([PhysPoint::new(Vec3::ZERO, Vec3::ZERO, 1.0)].into_iter()),
+            ))
+            .id();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let part = *app
+            .world()
+            .get::<ConstructParts>(ship)
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap();
+        app.world_mut().get_mut::<ArmorPart>(part).unwrap().def.wear = 0.5;
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let makeup = &app.world().get::<Ship>(ship).unwrap().makeup;
+        let Some(ItemType::Part(ItemPartDef {
+            part_type: PartTypeDef::Armor(def),
+            ..
+        })) = makeup.item(plate).map(|item| &item.item_type)
+        else {
+            panic!("the plate is no longer armor");
+        };
+        assert_eq!(def.wear, 0.5);
+    }
+}
@@ -0,0 +1,240 @@
+//! # Map markers and buoys
+//!
+//! Players can drop buoys on the water, to mark places worth remembering
+//! during a raid, such as loot-rich wrecks or safe channels between reefs.
+//!
+//! A buoy is a small floating physics object, with a [MapMarker] for the
+//! minimap to display. Buoys are placed by sending a [PlaceBuoyEvent], e.g.
+//! from a HUD action. Each owner may only have so many buoys at a time (see
+//! [BuoySettings]); placing more removes their oldest buoy.
+//!
+//! Buoys are part of the overworld scene tree, so they last until the end of
+//! the raid.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    physics::{
+        base::{PhysPoint, PointAttach, PointNetwork},
+        forces::Gravity,
+        volume::{PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+        water::WaterPhysics,
+    },
+    state::{GameState, SceneTree},
+};
+
+/// What a map marker is marking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MarkerKind {
+    /// Something worth looting, like a wreck.
+    Loot,
+
+    /// A safe passage, e.g. between reefs.
+    SafeChannel,
+
+    /// Something to steer clear of.
+    Danger,
+
+    /// Anything else.
+    #[default]
+    Generic,
+}
+
+/// A marker shown on the minimap, at the entity's position.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MapMarker {
+    /// What is being marked.
+    pub kind: MarkerKind,
+
+    /// A short note, if any.
+    pub label: Option<String>,
+
+    /// The player who placed this marker, if any.
+    pub owner: Option<Entity>,
+}
+
+/// A buoy placed by a player.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Buoy {
+    /// Placement order, used to remove the oldest buoy of an owner when they
+    /// go over the limit.
+    sequence: u64,
+}
+
+/// The floating part of a [Buoy], which bobs along with the water.
+///
+/// The renderer gives it a mesh, and a light.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct BuoyFloat;
+
+/// Places a buoy, along with its map marker.
+#[derive(Event, Clone, Debug)]
+pub struct PlaceBuoyEvent {
+    /// Where to drop the buoy; its height is taken as the water level.
+    pub position: Vec3,
+
+    /// The marker shown on the minimap.
+    pub marker: MapMarker,
+}
+
+/// Buoy placement settings.
+#[derive(Resource, Clone, Debug)]
+pub struct BuoySettings {
+    /// How many buoys each owner can have at once.
+    pub max_per_owner: usize,
+
+    /// Radius of the buoy's float.
+    pub radius: f32,
+
+    /// Mass of the buoy.
+    pub mass: f32,
+}
+
+impl Default for BuoySettings {
+    fn default() -> Self {
+        Self {
+            max_per_owner: 8,
+            radius: 0.6,
+            mass: 0.3,
+        }
+    }
+}
+
+fn place_buoys(
+    mut commands: Commands,
+    mut events: EventReader<PlaceBuoyEvent>,
+    mut next_sequence: Local<u64>,
+    settings: Res<BuoySettings>,
+    scene_tree: Query<Entity, With<SceneTree>>,
+    buoys: Query<(Entity, &Buoy, &MapMarker)>,
+) {
+    let Ok(scene_tree) = scene_tree.single() else {
+        events.clear();
+        return;
+    };
+
+    // Buoys despawned this run, since commands are only applied afterwards.
+    let mut removed = Vec::new();
+
+    for event in events.read() {
+        let mut owned = buoys
+            .iter()
+            .filter(|(entity, _, marker)| {
+                marker.owner == event.marker.owner && !removed.contains(entity)
+            })
+            .map(|(entity, buoy, _)| (buoy.sequence, entity))
+            .collect::<Vec<_>>();
+        owned.sort();
+
+        // Make room for the new buoy.
+        let excess = (owned.len() + 1).saturating_sub(settings.max_per_owner.max(1));
+        for (_, entity) in owned.into_iter().take(excess) {
+            commands.entity(entity).despawn();
+            removed.push(entity);
+        }
+
+        let points = PointNetwork::from(
+            [PhysPoint::new(event.position, Vec3::ZERO, settings.mass)].into_iter(),
+        );
+
+        let buoy = commands
+            .spawn((
+                Buoy {
+                    sequence: *next_sequence,
+                },
+                event.marker.clone(),
+                points,
+                VolumeCollection {
+                    volumes: vec![PhysicsVolume {
+                        point_idx: 0,
+                        volume_type: VolumeType::Sphere(SphereDef::new(settings.radius)),
+                    }],
+                },
+                WaterPhysics {
+                    water_level: event.position.y,
+                    ..default()
+                },
+                Gravity::default(),
+                Transform::from_translation(event.position),
+                Visibility::Visible,
+                children![(
+                    BuoyFloat,
+                    PointAttach { point_idx: 0 },
+                    Transform::default()
+                )],
+            ))
+            .id();
+
+        commands.entity(scene_tree).add_child(buoy);
+        *next_sequence += 1;
+    }
+}
+
+/// Buoy and map marker plugin.
+pub struct MarkerPlugin;
+
+impl Plugin for MarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaceBuoyEvent>();
+        app.init_resource::<BuoySettings>();
+        app.add_systems(Update, place_buoys.run_if(in_state(GameState::Overworld)));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+
+    use super::*;
+
+    #[test]
+    fn oldest_buoys_are_removed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, MarkerPlugin));
+        app.init_state::<GameState>();
+        app.insert_state(GameState::Overworld);
+        app.insert_resource(BuoySettings {
+            max_per_owner: 2,
+            ..default()
+        });
+        app.world_mut().spawn(SceneTree);
+
+        let player = app.world_mut().spawn_empty().id();
+
+        for (idx, owner) in [Some(player), Some(player), None, Some(player)]
+            .into_iter()
+            .enumerate()
+        {
+            app.world_mut().send_event(PlaceBuoyEvent {
+                position: Vec3::X * idx as f32,
+                marker: MapMarker { owner, ..default() },
+            });
+            app.update();
+        }
+
+        let mut query = app.world_mut().query::<(&Transform, &MapMarker)>();
+        let mut positions = query
+            .iter(app.world())
+            .filter(|(_, marker)| marker.owner == Some(player))
+            .map(|(transform, _)| transform.translation.x)
+            .collect::<Vec<_>>();
+        positions.sort_by(f32::total_cmp);
+
+        // The first buoy was removed; the unowned one doesn't count.
+        assert_eq!(positions, vec![1.0, 3.0]);
+        assert_eq!(query.iter(app.world()).count(), 3);
+    }
+}
@@ -1,5 +1,10 @@
 //! # Mathematical utility functions
 
+pub mod angle; // Angle wrapping and interpolation
+pub mod closest; // Closest points on segments and polylines
+pub mod easing; // Easing functions
+pub mod spline; // Bézier and Catmull-Rom splines
+
 /// Linearly interpolate between two values.
 pub fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
     from + alpha * (to - from)
@@ -0,0 +1,134 @@
+//! # Angle utilities
+//!
+//! Wrapping, shortest-arc interpolation and headings, mostly for steering.
+//!
+//! All angles are in radians. Headings are yaw angles around the Y axis, with
+//! zero facing forward (-Z), matching [Quat::from_rotation_y].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::math::{Quat, Vec3};
+
+/// Wraps an angle into the range (-π, π].
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(TAU) - PI;
+
+    if wrapped == -PI { PI } else { wrapped }
+}
+
+/// Wraps an angle into the range [0, 2π).
+pub fn wrap_angle_positive(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(TAU);
+
+    // rem_euclid may round up to TAU itself for tiny negative angles.
+    if wrapped >= TAU { 0.0 } else { wrapped }
+}
+
+/// The signed difference from one angle to another, along the shortest arc.
+///
+/// Positive is counter-clockwise; always in the range (-π, π].
+pub fn shortest_arc(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+/// Interpolates between two angles, along the shortest arc.
+pub fn lerp_angle(from: f32, to: f32, alpha: f32) -> f32 {
+    wrap_angle(from + shortest_arc(from, to) * alpha)
+}
+
+/// Turns an angle towards another, along the shortest arc, by at most
+/// `max_delta`.
+pub fn rotate_towards(from: f32, to: f32, max_delta: f32) -> f32 {
+    let arc = shortest_arc(from, to);
+    wrap_angle(from + arc.clamp(-max_delta, max_delta))
+}
+
+/// The heading of a direction, ignoring its vertical component.
+///
+/// Returns [None] for vertical (or zero) directions, which have no heading.
+pub fn heading_of(direction: Vec3) -> Option<f32> {
+    if direction.x == 0.0 && direction.z == 0.0 {
+        return None;
+    }
+
+    Some((-direction.x).atan2(-direction.z))
+}
+
+/// The horizontal unit direction of a heading.
+pub fn heading_direction(heading: f32) -> Vec3 {
+    Quat::from_rotation_y(heading) * Vec3::NEG_Z
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+    use bevy::math::Vec3;
+
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_close(wrap_angle(0.0), 0.0);
+        assert_close(wrap_angle(PI), PI);
+        assert_close(wrap_angle(-PI), PI);
+        assert_close(wrap_angle(3.0 * PI), PI);
+        assert_close(wrap_angle(TAU + 0.5), 0.5);
+        assert_close(wrap_angle(-TAU - 0.5), -0.5);
+
+        assert_close(wrap_angle_positive(-0.5), TAU - 0.5);
+        assert_close(wrap_angle_positive(TAU), 0.0);
+        assert!(wrap_angle_positive(-1e-9) < TAU);
+    }
+
+    #[test]
+    fn shortest_arcs() {
+        assert_close(shortest_arc(0.0, 1.0), 1.0);
+        assert_close(shortest_arc(1.0, 0.0), -1.0);
+
+        // Across the seam.
+        assert_close(shortest_arc(PI - 0.1, -PI + 0.1), 0.2);
+        assert_close(shortest_arc(-PI + 0.1, PI - 0.1), -0.2);
+
+        assert_close(lerp_angle(PI - 0.1, -PI + 0.1, 0.5), PI);
+        assert_close(lerp_angle(0.0, FRAC_PI_2, 0.5), FRAC_PI_2 / 2.0);
+    }
+
+    #[test]
+    fn rotating_towards() {
+        assert_close(rotate_towards(0.0, 1.0, 0.25), 0.25);
+        assert_close(rotate_towards(0.0, -1.0, 0.25), -0.25);
+        assert_close(rotate_towards(0.0, 0.1, 0.25), 0.1);
+        assert_close(rotate_towards(PI - 0.1, -PI + 0.1, 0.05), PI - 0.05);
+    }
+
+    #[test]
+    fn headings() {
+        assert_eq!(heading_of(Vec3::Y), None);
+        assert_close(heading_of(Vec3::NEG_Z).unwrap(), 0.0);
+
+        for heading in [0.3, -1.2, 2.9, -3.0] {
+            let direction = heading_direction(heading);
+            assert_close(direction.length(), 1.0);
+            assert_close(direction.y, 0.0);
+            assert_close(heading_of(direction * 5.0 + Vec3::Y).unwrap(), heading);
+        }
+    }
+}
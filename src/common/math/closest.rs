@@ -0,0 +1,337 @@
+//! # Closest point utilities
+//!
+//! Closest points on segments and polylines, in 2D and 3D; e.g. to find how
+//! far a ship is from its patrol route, or where a projectile passes closest
+//! to a target.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::math::{Vec2, Vec3};
+
+/// A point on a segment or polyline closest to another point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoint<T> {
+    /// The closest point.
+    pub point: T,
+
+    /// Index of the segment the point is on; always 0 for single segments.
+    pub segment: usize,
+
+    /// How far along its segment the point is, between 0.0 and 1.0.
+    pub t: f32,
+
+    /// Squared distance to the point which was queried.
+    pub distance_squared: f32,
+}
+
+/// Position along a segment closest to a point, between 0.0 and 1.0.
+fn segment_t(start_to_point: f32, length_squared: f32) -> f32 {
+    if length_squared <= f32::EPSILON {
+        0.0
+    } else {
+        (start_to_point / length_squared).clamp(0.0, 1.0)
+    }
+}
+
+/// The point on a 3D segment closest to another point.
+pub fn closest_point_on_segment(start: Vec3, end: Vec3, point: Vec3) -> ClosestPoint<Vec3> {
+    let segment = end - start;
+    let t = segment_t((point - start).dot(segment), segment.length_squared());
+    let closest = start + segment * t;
+
+    ClosestPoint {
+        point: closest,
+        segment: 0,
+        t,
+        distance_squared: closest.distance_squared(point),
+    }
+}
+
+/// The point on a 2D segment closest to another point.
+pub fn closest_point_on_segment_2d(start: Vec2, end: Vec2, point: Vec2) -> ClosestPoint<Vec2> {
+    let segment = end - start;
+    let t = segment_t((point - start).dot(segment), segment.length_squared());
+    let closest = start + segment * t;
+
+    ClosestPoint {
+        point: closest,
+        segment: 0,
+        t,
+        distance_squared: closest.distance_squared(point),
+    }
+}
+
+/// The point on a 3D polyline closest to another point.
+///
+/// Returns [None] if the polyline has no points.
+pub fn closest_point_on_polyline(polyline: &[Vec3], point: Vec3) -> Option<ClosestPoint<Vec3>> {
+    match polyline {
+        [] => None,
+        [only] => Some(ClosestPoint {
+            point: *only,
+            segment: 0,
+            t: 0.0,
+            distance_squared: only.distance_squared(point),
+        }),
+        _ => polyline
+            .windows(2)
+            .enumerate()
+            .map(|(segment, ends)| ClosestPoint {
+                segment,
+                ..closest_point_on_segment(ends[0], ends[1], point)
+            })
+            .min_by(|a, b| a.distance_squared.total_cmp(&b.distance_squared)),
+    }
+}
+
+/// The point on a 2D polyline closest to another point.
+///
+/// Returns [None] if the polyline has no points.
+pub fn closest_point_on_polyline_2d(polyline: &[Vec2], point: Vec2) -> Option<ClosestPoint<Vec2>> {
+    match polyline {
+        [] => None,
+        [only] => Some(ClosestPoint {
+            point: *only,
+            segment: 0,
+            t: 0.0,
+            distance_squared: only.distance_squared(point),
+        }),
+        _ => polyline
+            .windows(2)
+            .enumerate()
+            .map(|(segment, ends)| ClosestPoint {
+                segment,
+                ..closest_point_on_segment_2d(ends[0], ends[1], point)
+            })
+            .min_by(|a, b| a.distance_squared.total_cmp(&b.distance_squared)),
+    }
+}
+
+/// The point on a 3D triangle closest to another point.
+///
+/// Uses the Voronoi region method from Ericson, "Real-Time Collision
+/// Detection" (2004).
+pub fn closest_point_on_triangle(a: Vec3, b: Vec3, c: Vec3, point: Vec3) -> Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+
+    // Vertex region A.
+    let ap = point - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    // Vertex region B.
+    let bp = point - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    // Edge region AB.
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    // Vertex region C.
+    let cp = point - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    // Edge region AC.
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    // Edge region BC.
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // Inside the face.
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+/// The closest pair of points between two 3D segments, one on each.
+///
+/// Returns the point on the first segment, then the one on the second.
+pub fn closest_points_between_segments(
+    start_a: Vec3,
+    end_a: Vec3,
+    start_b: Vec3,
+    end_b: Vec3,
+) -> (Vec3, Vec3) {
+    let dir_a = end_a - start_a;
+    let dir_b = end_b - start_b;
+    let offset = start_a - start_b;
+
+    let len_a = dir_a.length_squared();
+    let len_b = dir_b.length_squared();
+    let proj_b = dir_b.dot(offset);
+
+    // Both segments are points.
+    if len_a <= f32::EPSILON && len_b <= f32::EPSILON {
+        return (start_a, start_b);
+    }
+
+    let (t_a, t_b) = if len_a <= f32::EPSILON {
+        (0.0, (proj_b / len_b).clamp(0.0, 1.0))
+    } else {
+        let proj_a = dir_a.dot(offset);
+
+        if len_b <= f32::EPSILON {
+            ((-proj_a / len_a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let cross = dir_a.dot(dir_b);
+            let denominator = len_a * len_b - cross * cross;
+
+            // Parallel segments have many closest pairs; any will do.
+            let t_a = if denominator > f32::EPSILON {
+                ((cross * proj_b - proj_a * len_b) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let t_b = (cross * t_a + proj_b) / len_b;
+
+            // Clamp the second segment, then recompute the first from it.
+            if t_b < 0.0 {
+                ((-proj_a / len_a).clamp(0.0, 1.0), 0.0)
+            } else if t_b > 1.0 {
+                (((cross - proj_a) / len_a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (t_a, t_b)
+            }
+        }
+    };
+
+    (start_a + dir_a * t_a, start_b + dir_b * t_b)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::math::{Vec2, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn segment() {
+        let (start, end) = (Vec3::ZERO, Vec3::X * 4.0);
+
+        let middle = closest_point_on_segment(start, end, Vec3::new(1.0, 2.0, 0.0));
+        assert_eq!(middle.point, Vec3::X);
+        assert_eq!(middle.t, 0.25);
+        assert_eq!(middle.distance_squared, 4.0);
+
+        // Clamped to the ends.
+        assert_eq!(
+            closest_point_on_segment(start, end, Vec3::X * -3.0).point,
+            start
+        );
+        assert_eq!(
+            closest_point_on_segment(start, end, Vec3::X * 9.0).point,
+            end
+        );
+
+        // Degenerate segment.
+        assert_eq!(closest_point_on_segment(end, end, Vec3::Y).point, end);
+
+        let flat = closest_point_on_segment_2d(Vec2::ZERO, Vec2::Y * 2.0, Vec2::new(3.0, 1.0));
+        assert_eq!(flat.point, Vec2::Y);
+    }
+
+    #[test]
+    fn polyline() {
+        let polyline = [Vec3::ZERO, Vec3::X * 2.0, Vec3::new(2.0, 0.0, 2.0)];
+
+        let closest = closest_point_on_polyline(&polyline, Vec3::new(3.0, 0.0, 1.5)).unwrap();
+        assert_eq!(closest.segment, 1);
+        assert_eq!(closest.point, Vec3::new(2.0, 0.0, 1.5));
+        assert_eq!(closest.t, 0.75);
+
+        assert!(closest_point_on_polyline(&[], Vec3::ZERO).is_none());
+        assert_eq!(
+            closest_point_on_polyline(&[Vec3::Y], Vec3::ZERO)
+                .unwrap()
+                .point,
+            Vec3::Y
+        );
+
+        let polyline = [Vec2::ZERO, Vec2::X, Vec2::ONE];
+        let closest = closest_point_on_polyline_2d(&polyline, Vec2::new(0.5, -1.0)).unwrap();
+        assert_eq!(closest.segment, 0);
+        assert_eq!(closest.point, Vec2::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn triangle() {
+        let [a, b, c] = [Vec3::ZERO, Vec3::X * 2.0, Vec3::Z * 2.0];
+
+        // Above the face.
+        let closest = closest_point_on_triangle(a, b, c, Vec3::new(0.5, 3.0, 0.5));
+        assert!(closest.distance(Vec3::new(0.5, 0.0, 0.5)) < 1e-5);
+
+        // Beyond a vertex, and beyond an edge.
+        assert_eq!(closest_point_on_triangle(a, b, c, Vec3::splat(-1.0)), a);
+        let closest = closest_point_on_triangle(a, b, c, Vec3::new(2.0, 1.0, 2.0));
+        assert!(closest.distance(Vec3::new(1.0, 0.0, 1.0)) < 1e-5);
+    }
+
+    #[test]
+    fn between_segments() {
+        // Crossing segments, one above the other.
+        let (a, b) = closest_points_between_segments(
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, -1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        );
+        assert!(a.distance(Vec3::ZERO) < 1e-5);
+        assert!(b.distance(Vec3::Y) < 1e-5);
+
+        // Segments that would cross if extended are clamped to their ends.
+        let (a, b) = closest_points_between_segments(
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(3.0, 0.0, 1.0),
+            Vec3::new(3.0, 0.0, 2.0),
+        );
+        assert!(a.distance(Vec3::X) < 1e-5);
+        assert!(b.distance(Vec3::new(3.0, 0.0, 1.0)) < 1e-5);
+
+        // Parallel segments.
+        let (a, b) = closest_points_between_segments(
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        );
+        assert!((a.distance(b) - 1.0).abs() < 1e-5);
+
+        // A degenerate segment.
+        let (a, b) = closest_points_between_segments(Vec3::Y, Vec3::Y, Vec3::ZERO, Vec3::X * 2.0);
+        assert_eq!(a, Vec3::Y);
+        assert!(b.distance(Vec3::ZERO) < 1e-5);
+    }
+}
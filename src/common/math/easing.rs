@@ -0,0 +1,203 @@
+//! # Easing functions
+//!
+//! Curves mapping animation progress (0.0 to 1.0) to eased progress, for UI
+//! and visual effects.
+//!
+//! Every easing maps 0.0 to 0.0 and 1.0 to 1.0. Some, like [Easing::BackOut]
+//! and [Easing::ElasticOut], overshoot in between.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use super::lerp;
+
+/// An easing curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    /// Pulls back slightly before moving.
+    BackIn,
+    /// Overshoots slightly before settling.
+    BackOut,
+    /// Springs past the end, and wobbles into place.
+    ElasticOut,
+    /// Bounces against the end, like a dropped ball.
+    BounceOut,
+}
+
+impl Easing {
+    /// Every easing curve.
+    pub const ALL: [Easing; 16] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::ElasticOut,
+        Easing::BounceOut,
+    ];
+
+    /// Eases progress, which is clamped between 0.0 and 1.0.
+    pub fn apply(&self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::SineIn => 1.0 - (t * FRAC_PI_2).cos(),
+            Easing::SineOut => (t * FRAC_PI_2).sin(),
+            Easing::SineInOut => 0.5 - 0.5 * (t * PI).cos(),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => 1.0 - expo_in(1.0 - t),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => 1.0 - back_in(1.0 - t),
+            Easing::ElasticOut => elastic_out(t),
+            Easing::BounceOut => bounce_out(t),
+        }
+    }
+
+    /// Interpolates between two values, with eased progress.
+    pub fn lerp(&self, from: f32, to: f32, progress: f32) -> f32 {
+        lerp(from, to, self.apply(progress))
+    }
+}
+
+/// Makes a symmetric in-out curve out of an 'in' curve.
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        0.5 * ease_in(2.0 * t)
+    } else {
+        1.0 - 0.5 * ease_in(2.0 - 2.0 * t)
+    }
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2.0_f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn back_in(t: f32) -> f32 {
+    const OVERSHOOT: f32 = 1.70158;
+    t * t * ((OVERSHOOT + 1.0) * t - OVERSHOOT)
+}
+
+fn elastic_out(t: f32) -> f32 {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+
+    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (TAU / 3.0)).sin() + 1.0
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::Easing;
+
+    #[test]
+    fn endpoints() {
+        for easing in Easing::ALL {
+            assert!(easing.apply(0.0).abs() < 1e-3, "{easing:?} at 0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-3, "{easing:?} at 1");
+
+            // Out of range progress is clamped.
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn continuous() {
+        for easing in Easing::ALL {
+            let mut previous = easing.apply(0.0);
+
+            for step in 1..=1000 {
+                let value = easing.apply(step as f32 / 1000.0);
+                assert!((value - previous).abs() < 0.05, "{easing:?} jumps");
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        // 'In' curves start slow, 'out' curves start fast.
+        assert!(Easing::QuadIn.apply(0.25) < 0.25);
+        assert!(Easing::QuadOut.apply(0.25) > 0.25);
+        assert!(Easing::ExpoIn.apply(0.5) < Easing::CubicIn.apply(0.5));
+
+        // In-out curves are symmetric.
+        for easing in [Easing::QuadInOut, Easing::CubicInOut, Easing::SineInOut] {
+            assert!((easing.apply(0.5) - 0.5).abs() < 1e-5);
+            assert!((easing.apply(0.2) + easing.apply(0.8) - 1.0).abs() < 1e-5);
+        }
+
+        // Back and elastic curves overshoot.
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+        assert!(Easing::BackOut.apply(0.8) > 1.0);
+        assert!(Easing::ElasticOut.apply(0.15) > 1.0);
+
+        // Bouncing never overshoots.
+        assert!((0..=100).all(|step| Easing::BounceOut.apply(step as f32 / 100.0) <= 1.0));
+
+        assert_eq!(Easing::QuadIn.lerp(10.0, 20.0, 0.5), 12.5);
+    }
+}
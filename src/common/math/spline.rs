@@ -0,0 +1,277 @@
+//! # Splines
+//!
+//! Smooth curves through or near control points, for camera paths, patrol
+//! routes and the like.
+//!
+//! * [CatmullRomSpline]s pass through every control point, which makes them
+//!   convenient for paths laid out by hand, or by a generator.
+//! * Bézier curves ([quadratic_bezier], [cubic_bezier]) only pass through
+//!   their end points, with the middle control points pulling the curve
+//!   towards them.
+//!
+//! Everything here works on both [Vec2] and [Vec3]; see [SplinePoint].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::ops::{Add, Mul, Sub};
+
+#[allow(unused_imports)] // For documentation
+use bevy::math::{Vec2, Vec3};
+
+/// A point type which splines can interpolate.
+pub trait SplinePoint:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+}
+
+impl<T> SplinePoint for T where T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> {}
+
+/// A point on a quadratic Bézier curve, at `t` between 0.0 and 1.0.
+pub fn quadratic_bezier<T: SplinePoint>(p0: T, p1: T, p2: T, t: f32) -> T {
+    let u = 1.0 - t;
+    p0 * (u * u) + p1 * (2.0 * u * t) + p2 * (t * t)
+}
+
+/// A point on a cubic Bézier curve, at `t` between 0.0 and 1.0.
+pub fn cubic_bezier<T: SplinePoint>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let u = 1.0 - t;
+    p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+}
+
+/// The derivative of a cubic Bézier curve, at `t` between 0.0 and 1.0.
+///
+/// Points along the curve; useful to orient objects following it.
+pub fn cubic_bezier_tangent<T: SplinePoint>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let u = 1.0 - t;
+    (p1 - p0) * (3.0 * u * u) + (p2 - p1) * (6.0 * u * t) + (p3 - p2) * (3.0 * t * t)
+}
+
+/// A point on a uniform Catmull-Rom segment between `p1` and `p2`, at `t`
+/// between 0.0 and 1.0.
+///
+/// `p0` and `p3` are the neighbouring control points, which shape the curve.
+pub fn catmull_rom<T: SplinePoint>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+/// The derivative of a uniform Catmull-Rom segment.
+pub fn catmull_rom_tangent<T: SplinePoint>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    ((p2 - p0)
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (2.0 * t)
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (3.0 * t * t))
+        * 0.5
+}
+
+/// A Catmull-Rom spline, passing through every control point.
+#[derive(Clone, Debug)]
+pub struct CatmullRomSpline<T: SplinePoint> {
+    /// The control points.
+    pub points: Vec<T>,
+
+    /// Whether the spline loops back from the last point to the first, e.g.
+    /// for patrol routes.
+    pub closed: bool,
+}
+
+impl<T: SplinePoint> CatmullRomSpline<T> {
+    /// An open spline through the given points.
+    pub fn new(points: Vec<T>) -> Self {
+        Self {
+            points,
+            closed: false,
+        }
+    }
+
+    /// A closed spline through the given points, looping back to the first.
+    pub fn closed(points: Vec<T>) -> Self {
+        Self {
+            points,
+            closed: true,
+        }
+    }
+
+    /// Number of segments, i.e. curves between two consecutive points.
+    pub fn segment_count(&self) -> usize {
+        match (self.points.len(), self.closed) {
+            (0 | 1, _) => 0,
+            (count, true) => count,
+            (count, false) => count - 1,
+        }
+    }
+
+    /// The control point at an index, wrapping around if closed, or
+    /// clamping to the ends otherwise.
+    fn point(&self, idx: isize) -> T {
+        let count = self.points.len() as isize;
+
+        let idx = if self.closed {
+            idx.rem_euclid(count)
+        } else {
+            idx.clamp(0, count - 1)
+        };
+
+        self.points[idx as usize]
+    }
+
+    /// The four control points of a segment, and the position within it.
+    fn segment_at(&self, t: f32) -> ([T; 4], f32) {
+        let segments = self.segment_count();
+        let t = t.clamp(0.0, segments as f32);
+        let segment = (t.floor() as usize).min(segments - 1);
+        let idx = segment as isize;
+
+        (
+            [
+                self.point(idx - 1),
+                self.point(idx),
+                self.point(idx + 1),
+                self.point(idx + 2),
+            ],
+            t - segment as f32,
+        )
+    }
+
+    /// A point on the spline, at `t` between 0.0 and [Self::segment_count].
+    ///
+    /// Each whole value of `t` falls on a control point.
+    ///
+    /// Returns [None] if there are no control points.
+    pub fn sample(&self, t: f32) -> Option<T> {
+        match self.points.len() {
+            0 => None,
+            1 => Some(self.points[0]),
+            _ => {
+                let ([p0, p1, p2, p3], t) = self.segment_at(t);
+                Some(catmull_rom(p0, p1, p2, p3, t))
+            }
+        }
+    }
+
+    /// A point on the spline, at `u` between 0.0 (the start) and 1.0 (the
+    /// end).
+    pub fn sample_normalized(&self, u: f32) -> Option<T> {
+        self.sample(u * self.segment_count() as f32)
+    }
+
+    /// The tangent of the spline, at `t` between 0.0 and
+    /// [Self::segment_count].
+    pub fn tangent(&self, t: f32) -> Option<T> {
+        if self.points.len() < 2 {
+            return None;
+        }
+
+        let ([p0, p1, p2, p3], t) = self.segment_at(t);
+        Some(catmull_rom_tangent(p0, p1, p2, p3, t))
+    }
+
+    /// Samples the whole spline as a polyline, with a number of points per
+    /// segment (at least 1).
+    pub fn polyline(&self, samples_per_segment: usize) -> Vec<T> {
+        let samples_per_segment = samples_per_segment.max(1);
+        let total = self.segment_count() * samples_per_segment;
+
+        (0..=total)
+            .filter_map(|idx| self.sample(idx as f32 / samples_per_segment as f32))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::math::{Vec2, Vec3};
+
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.distance(b) < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn bezier_endpoints() {
+        let [p0, p1, p2, p3] = [Vec3::ZERO, Vec3::Y, Vec3::new(1.0, 1.0, 0.0), Vec3::X];
+
+        assert_close(quadratic_bezier(p0, p1, p3, 0.0), p0);
+        assert_close(quadratic_bezier(p0, p1, p3, 1.0), p3);
+        assert_close(cubic_bezier(p0, p1, p2, p3, 0.0), p0);
+        assert_close(cubic_bezier(p0, p1, p2, p3, 1.0), p3);
+        assert_close(cubic_bezier(p0, p1, p2, p3, 0.5), Vec3::new(0.5, 0.75, 0.0));
+
+        // Tangents at the ends point towards the inner control points.
+        assert_close(cubic_bezier_tangent(p0, p1, p2, p3, 0.0), (p1 - p0) * 3.0);
+        assert_close(cubic_bezier_tangent(p0, p1, p2, p3, 1.0), (p3 - p2) * 3.0);
+    }
+
+    #[test]
+    fn bezier_straight_line() {
+        // Collinear, evenly spaced control points make a straight line,
+        // traversed at constant speed.
+        let line = |t| cubic_bezier(Vec2::ZERO, Vec2::X, Vec2::X * 2.0, Vec2::X * 3.0, t);
+
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert!(line(t).distance(Vec2::X * 3.0 * t) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let points = vec![
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(3.0, 1.0, 2.0),
+            Vec3::new(4.0, 0.0, 0.0),
+        ];
+
+        let open = CatmullRomSpline::new(points.clone());
+        assert_eq!(open.segment_count(), 3);
+
+        for (idx, point) in points.iter().enumerate() {
+            assert_close(open.sample(idx as f32).unwrap(), *point);
+        }
+
+        assert_close(open.sample_normalized(0.0).unwrap(), points[0]);
+        assert_close(open.sample_normalized(1.0).unwrap(), points[3]);
+
+        let closed = CatmullRomSpline::closed(points.clone());
+        assert_eq!(closed.segment_count(), 4);
+        assert_close(closed.sample(4.0).unwrap(), points[0]);
+
+        // The tangent at a control point follows its neighbours.
+        assert_close(open.tangent(1.0).unwrap(), (points[2] - points[0]) * 0.5);
+    }
+
+    #[test]
+    fn catmull_rom_polyline() {
+        let spline = CatmullRomSpline::new(vec![Vec2::ZERO, Vec2::X, Vec2::ONE]);
+        let polyline = spline.polyline(4);
+
+        assert_eq!(polyline.len(), 9);
+        assert_eq!(polyline[0], Vec2::ZERO);
+        assert!(polyline[4].distance(Vec2::X) < 1e-5);
+        assert!(polyline[8].distance(Vec2::ONE) < 1e-5);
+
+        assert!(CatmullRomSpline::<Vec2>::new(vec![]).sample(0.0).is_none());
+        assert_eq!(
+            CatmullRomSpline::new(vec![Vec2::ONE]).polyline(4),
+            vec![Vec2::ONE]
+        );
+    }
+}
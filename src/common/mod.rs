@@ -36,21 +36,36 @@
 
 use bevy::prelude::Plugin;
 
+pub mod ai; // NPC ship controller
+pub mod ballistics; // Projectile trajectory prediction
 pub mod construct; // Constructs (genrealized part holders)
+pub mod crew; // Construct crew, and manning parts
+pub mod damage; // Health, damage, and part destruction
+pub mod defs; // Data-driven definitions of ship makes, items and props
+pub mod diagnostics; // Allocation diagnostics
+pub mod error; // Crate-wide error type and error handling policy
+#[cfg(test)]
+pub mod fixtures; // Items and ships shared by unit tests
+pub mod fleet; // Player fleets, their flagship and escorts
+pub mod fx; // Visual effect cues emitted by the simulation, for the client to draw
+pub mod intermission; // Town buildings, and moving between them
 pub mod inventory; // Inventory items and related operations
 pub mod makeup; // Ship makeup and parts
+pub mod markers; // Player-placed buoys and map markers
 pub mod math; // Mathematical utility functions
 pub mod physics; // Object physics and collision detection
+pub mod player; // Player state tracking and ship control
+pub mod props; // Static props (decorative, buildings, etc) and their spawning
+pub mod save; // Campaign saves, and save schema versioning and migrations
 pub mod scene; // Scene management and initializatoin
+pub mod scratch; // Reusable scratch buffers
+pub mod seed; // World seeds and reproducible random streams
+pub mod sound; // Sound cues emitted by the simulation, for the client to play
+pub mod spawner; // NPC ship spawning
 pub mod state; // Ingame state handling
 pub mod terrain; // Terrain generation, caching, and lookup
 
-// pub mod defs;      // Definitions for ship parts, makes, NPC templates, etc
 // pub mod namegen;   // Localizable name generation for NPC ships
-// pub mod ai;        // NPC ship controller
-// pub mod player;    // Player state tracking
-// pub mod spawner;   // NPC ship spawning
-// pub mod props;     // Static props (decorative, buildings, etc) and their spawning
 // pub mod town;      // Economic mechanisms, and town state tracking
 // pub mod meta;      // Simulation meta-state, including game name, difficulty level, etc
 // pub mod event;     // Top-level events (player creation, login, death, mooring, etc.)
@@ -65,12 +80,35 @@ pub struct CommonPlugin;
 impl Plugin for CommonPlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.add_plugins((
-            physics::BasicPhysicsPlugin,
+            physics::BasicPhysicsPlugin::default(),
             terrain::collision::TerrainCollisionPlugin,
+            terrain::chunk::TerrainStreamingPlugin,
             state::BaseStatePlugin,
             scene::SceneManagementPlugin,
             physics::collision::CollisionPlugin,
             construct::ConstructPlugin,
+            makeup::parts::PartsPlugin,
+            diagnostics::AllocationDiagnosticsPlugin,
+            markers::MarkerPlugin,
+            props::PropPlugin,
+            spawner::SpawnerPlugin,
+            ai::AiPlugin,
+            player::PlayerPlugin,
+            error::ErrorReportingPlugin,
+        ));
+        app.add_plugins((
+            damage::DamagePlugin,
+            makeup::sinking::SinkingPlugin,
+            crew::CrewPlugin,
+            inventory::container::InventoryPlugin,
+            makeup::sync::MakeupSyncPlugin,
+            defs::DefsPlugin,
+            inventory::modifier::ModifierPlugin,
+            intermission::IntermissionPlugin,
+            fleet::FleetPlugin,
+            save::campaign::SavePlugin,
+            sound::SoundCuePlugin,
+            fx::FxCuePlugin,
         ));
     }
 }
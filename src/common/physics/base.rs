@@ -16,9 +16,16 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use bevy::prelude::*;
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::primitives::{Frustum, Sphere},
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+use super::sleep::Sleeping;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PhysPoint {
     /// The position of this physics point in space.
     pub pos: Vec3,
@@ -90,7 +97,7 @@ impl PhysPoint {
 /// A network of physics points.
 ///
 /// A component that must be in any physics-capable entity.
-#[derive(Component, Clone, Default)]
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PointNetwork {
     pub points: Vec<PhysPoint>,
 }
@@ -149,7 +156,10 @@ impl PointNetwork {
 }
 
 /// The system responsible for the inertia of physics points.
-pub fn point_base_physics(time: Res<Time>, mut query_points: Query<(&mut PointNetwork,)>) {
+pub fn point_base_physics(
+    time: Res<Time>,
+    mut query_points: Query<(&mut PointNetwork,), Without<Sleeping>>,
+) {
     let delta_secs = time.delta_secs();
 
     for (mut network,) in query_points.iter_mut() {
@@ -168,19 +178,131 @@ pub struct PointAttach {
     pub point_idx: usize,
 }
 
+/// Opts a [PointAttach] child out of culling, so that it is always snapped
+/// to its point, even when off-screen.
+///
+/// Use this for attachments which gameplay depends on, such as cannons
+/// or sensors, rather than purely visual ones.
+#[derive(Component)]
+pub struct AlwaysSnap;
+
+/// Culling of [PointAttach] children.
+///
+/// Children of a parent which is outside of every camera's frustum, or
+/// farther than [Self::max_distance] from every camera, are not snapped to
+/// their points, unless they have [AlwaysSnap]. They are snapped again once
+/// their parent is back in view.
+///
+/// Without any cameras (e.g. on a headless server), nothing is culled.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AttachCulling {
+    /// Whether culling is enabled at all.
+    pub enabled: bool,
+
+    /// Distance from the nearest camera beyond which children are culled.
+    ///
+    /// [None] means only the frustum is checked.
+    pub max_distance: Option<f32>,
+
+    /// Extra radius added around each parent's points before checking it
+    /// against frustums, so that children are not culled while still
+    /// partially in view.
+    pub margin: f32,
+}
+
+impl Default for AttachCulling {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_distance: Some(500.0),
+            margin: 2.0,
+        }
+    }
+}
+
+impl AttachCulling {
+    /// Whether a parent's points can be seen by any of the given cameras.
+    fn is_visible<'a>(
+        &self,
+        points: &PointNetwork,
+        mut cameras: impl Iterator<Item = (&'a GlobalTransform, &'a Frustum)>,
+    ) -> bool {
+        if points.points.is_empty() {
+            return false;
+        }
+
+        // Bounding sphere of the points.
+        let center =
+            points.points.iter().map(|point| point.pos).sum::<Vec3>() / points.points.len() as f32;
+        let radius = points
+            .points
+            .iter()
+            .map(|point| point.pos.distance(center))
+            .fold(0.0, f32::max)
+            + self.margin;
+
+        let sphere = Sphere {
+            center: center.into(),
+            radius,
+        };
+
+        cameras.any(|(camera_transform, frustum)| {
+            let in_range = self.max_distance.is_none_or(|max_distance| {
+                camera_transform.translation().distance(center) - radius <= max_distance
+            });
+
+            in_range && frustum.intersects_sphere(&sphere, true)
+        })
+    }
+}
+
 // Always runs after point_base_physics.
 pub fn point_attach_snap(
-    mut query_child: Query<(&ChildOf, &mut Transform, &PointAttach)>,
+    culling: Res<AttachCulling>,
+    mut query_child: Query<(&ChildOf, &mut Transform, &PointAttach, Has<AlwaysSnap>)>,
     query_parent: Query<(&PointNetwork, &GlobalTransform, &Transform), Without<PointAttach>>,
+    cameras: Query<(&GlobalTransform, &Frustum), With<Camera>>,
+    mut parent_visibility: Local<HashMap<Entity, bool>>,
 ) {
-    for (child_of, mut transform, attachment) in query_child.iter_mut() {
-        let (parent_points, parent_global_transform, parent_transform) =
-            query_parent.get(child_of.parent()).unwrap();
+    // [NOTE] Frustums are updated in PostUpdate, so this uses the ones from
+    // the previous frame. The margin covers for the difference.
+    let cull = culling.enabled && !cameras.is_empty();
+    parent_visibility.clear();
+
+    // [NOTE] A broken attachment is only warned about, so it does not keep
+    // every other attachment from snapping.
+    for (child_of, mut transform, attachment, always_snap) in query_child.iter_mut() {
+        let Ok((parent_points, parent_global_transform, parent_transform)) =
+            query_parent.get(child_of.parent())
+        else {
+            warn!(
+                "Point attachment's parent {} has no point network",
+                child_of.parent()
+            );
+            continue;
+        };
+
+        if cull && !always_snap {
+            let visible = *parent_visibility
+                .entry(child_of.parent())
+                .or_insert_with(|| culling.is_visible(parent_points, cameras.iter()));
+
+            if !visible {
+                continue;
+            }
+        }
 
-        assert!(attachment.point_idx < parent_points.points.len());
+        let Some(point) = parent_points.points.get(attachment.point_idx) else {
+            warn!(
+                "Point attachment index {} is out of bounds for parent {} with {} points",
+                attachment.point_idx,
+                child_of.parent(),
+                parent_points.points.len()
+            );
+            continue;
+        };
 
-        transform.translation =
-            parent_points.points[attachment.point_idx].pos - parent_global_transform.translation();
+        transform.translation = point.pos - parent_global_transform.translation();
         transform.rotate_around(Vec3::ZERO, parent_transform.rotation.inverse());
     }
 }
@@ -0,0 +1,219 @@
+//! # Collision broad phase
+//!
+//! Cheaply narrows down which pairs of objects may be colliding, so that the
+//! more expensive volume-volume checks (the 'narrow phase') only run on
+//! those.
+//!
+//! This uses sweep-and-prune: every object's [AABB] is projected onto the X
+//! axis and sorted, so that only objects whose projections overlap are ever
+//! compared against each other.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::scratch::ScratchBuffer;
+
+use super::{
+    base::PointNetwork,
+    determinism::PhysicsDeterminism,
+    projectile::FastProjectile,
+    volume::{AABB, VolumeCollection},
+};
+
+/// Candidate collision pairs found by the broad phase.
+///
+/// Rebuilt every physics tick. Every pair's AABBs intersect, but their
+/// volumes might not.
+#[derive(Resource, Default, Debug)]
+pub struct BroadPhasePairs {
+    /// The candidate pairs.
+    pub pairs: Vec<(Entity, Entity)>,
+
+    /// Scratch buffer of objects.
+    entries: ScratchBuffer<(Entity, AABB)>,
+}
+
+/// Sweep-and-prune broad phase.
+pub(super) fn broad_phase_system(
+    mut broad_phase: ResMut<BroadPhasePairs>,
+    determinism: Option<Res<PhysicsDeterminism>>,
+    query: Query<(Entity, &PointNetwork, &VolumeCollection)>,
+    projectiles: Query<&FastProjectile>,
+) {
+    let BroadPhasePairs { pairs, entries } = &mut *broad_phase;
+
+    pairs.clear();
+    let entries = entries.fill(
+        query
+            .iter()
+            .filter(|(_, _, volumes)| !volumes.volumes.is_empty())
+            .map(|(entity, points, volumes)| (entity, volumes.aabb(points))),
+    );
+
+    // Ties are broken by entity, so that pair order is deterministic.
+    entries.sort_unstable_by(|(entity_a, aabb_a), (entity_b, aabb_b)| {
+        aabb_a.spans[0]
+            .start
+            .total_cmp(&aabb_b.spans[0].start)
+            .then(entity_a.cmp(entity_b))
+    });
+
+    for (idx, (entity_a, aabb_a)) in entries.iter().enumerate() {
+        for (entity_b, aabb_b) in &entries[idx + 1..] {
+            // Sorted by start; no later entry can overlap on X either.
+            if aabb_b.spans[0].start > aabb_a.spans[0].end {
+                break;
+            }
+
+            // Projectiles never collide with what launched them.
+            let ignored = |projectile: Entity, other: Entity| {
+                projectiles
+                    .get(projectile)
+                    .is_ok_and(|projectile| !projectile.collides_with(other))
+            };
+            if ignored(*entity_a, *entity_b) || ignored(*entity_b, *entity_a) {
+                continue;
+            }
+
+            if aabb_a.check(aabb_b) {
+                pairs.push((*entity_a, *entity_b));
+            }
+        }
+    }
+
+    // The sweep order depends on positions; in determinism mode, make the
+    // order in which collisions are resolved depend on entities alone.
+    if determinism.is_some() {
+        pairs.sort_unstable();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::common::physics::{
+        base::PhysPoint,
+        volume::{BoxDef, PhysicsVolume, VolumeInfo, VolumeType},
+    };
+
+    /// Runs the broad phase on boxes, by their centers and half extents.
+    ///
+    /// Returns the entity of every box, and the pairs found, each ordered.
+    fn broad_phase(boxes: &[(Vec3, Vec3)]) -> (Vec<Entity>, Vec<(Entity, Entity)>) {
+        let mut world = World::new();
+        world.init_resource::<BroadPhasePairs>();
+
+        let entities = boxes
+            .iter()
+            .map(|&(center, half_extents)| {
+                world
+                    .spawn((
+                        PointNetwork::from([PhysPoint::new(center, Vec3::ZERO, 1.0)].into_iter()),
+                        VolumeCollection {
+                            volumes: vec![PhysicsVolume {
+                                point_idx: 0,
+                                volume_type: VolumeType::Box(BoxDef::new(half_extents)),
+                            }],
+                        },
+                    ))
+                    .id()
+            })
+            .collect();
+
+        world.run_system_once(broad_phase_system).unwrap();
+
+        let mut pairs: Vec<_> = world
+            .resource::<BroadPhasePairs>()
+            .pairs
+            .iter()
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        pairs.sort_unstable();
+
+        (entities, pairs)
+    }
+
+    #[test]
+    fn boxes_pair_only_when_overlapping_on_every_axis() {
+        let half = Vec3::ONE;
+
+        let (entities, pairs) = broad_phase(&[(Vec3::ZERO, half), (Vec3::splat(1.5), half)]);
+        assert_eq!(pairs, [(entities[0], entities[1])]);
+
+        // Overlapping on the other two axes, but apart on one.
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            let apart = Vec3::splat(1.5) + axis * 2.0;
+            let (_, pairs) = broad_phase(&[(Vec3::ZERO, half), (apart, half)]);
+            assert!(pairs.is_empty(), "paired boxes apart along {axis}");
+        }
+    }
+
+    #[test]
+    fn touching_boxes_are_not_paired() {
+        let half = Vec3::ONE;
+
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            let (_, pairs) = broad_phase(&[(Vec3::ZERO, half), (axis * 2.0, half)]);
+            assert!(pairs.is_empty(), "paired boxes touching along {axis}");
+
+            let (_, pairs) = broad_phase(&[(Vec3::ZERO, half), (axis * 1.99, half)]);
+            assert_eq!(pairs.len(), 1, "missed boxes overlapping along {axis}");
+        }
+    }
+
+    #[test]
+    fn pairs_match_brute_force() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let boxes: Vec<_> = (0..80)
+            .map(|_| {
+                let center = Vec3::new(
+                    rng.random_range(-20.0..20.0),
+                    rng.random_range(-5.0..5.0),
+                    rng.random_range(-20.0..20.0),
+                );
+                let half_extents = Vec3::new(
+                    rng.random_range(0.2..3.0),
+                    rng.random_range(0.2..3.0),
+                    rng.random_range(0.2..3.0),
+                );
+                (center, half_extents)
+            })
+            .collect();
+
+        let (entities, pairs) = broad_phase(&boxes);
+
+        let aabbs: Vec<_> = boxes
+            .iter()
+            .map(|&(center, half_extents)| BoxDef::new(half_extents).aabb().translate(center))
+            .collect();
+        let mut expected = vec![];
+        for a in 0..boxes.len() {
+            for b in a + 1..boxes.len() {
+                if aabbs[a].check(&aabbs[b]) {
+                    let (a, b) = (entities[a], entities[b]);
+                    expected.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+        expected.sort_unstable();
+
+        assert!(!expected.is_empty());
+        assert_eq!(pairs, expected);
+    }
+}
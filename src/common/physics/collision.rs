@@ -19,8 +19,13 @@
 
 use bevy::prelude::*;
 
+use crate::common::diagnostics::AllocationScope;
+
 use super::{
-    base::PointNetwork,
+    base::{PhysPoint, PointNetwork, point_base_physics},
+    broadphase::{BroadPhasePairs, broad_phase_system},
+    projectile::fast_projectile_sweep,
+    sleep::Sleeping,
     volume::{CollisionInfo, PhysicsVolume, VolumeCollection, VolumeCollision, VolumeInfo},
 };
 
@@ -50,7 +55,9 @@ pub struct FloorPlaneCollision {
 ///
 /// It guarantees that every physics point is above a certain Y intercept
 /// value - by default 0.0.
-fn floor_plane_collision_system(mut query: Query<(&mut PointNetwork, &FloorPlaneCollision)>) {
+fn floor_plane_collision_system(
+    mut query: Query<(&mut PointNetwork, &FloorPlaneCollision), Without<Sleeping>>,
+) {
     for (mut points, collision) in query.iter_mut() {
         for point in &mut points.points {
             if point.pos.y < collision.intercept_y {
@@ -72,6 +79,99 @@ fn floor_plane_collision_system(mut query: Query<(&mut PointNetwork, &FloorPlane
     }
 }
 
+/// How an object responds to object-object collisions.
+///
+/// Objects without this component use [CollisionResponse::Depth].
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub enum CollisionResponse {
+    /// Pushes colliding points apart by adding the collision depth to their
+    /// velocities.
+    ///
+    /// Cheap, but ignores point masses, so heavy objects are knocked around
+    /// as easily as light ones.
+    #[default]
+    Depth,
+
+    /// Impulse-based response.
+    ///
+    /// Cancels the relative velocity of the colliding points along the
+    /// collision normal, and splits the resulting impulse between them
+    /// according to their masses. Penetration is also corrected, likewise
+    /// weighted by mass.
+    Impulse {
+        /// How much of the approach velocity is bounced back, between 0.0
+        /// (fully inelastic) and 1.0 (fully elastic).
+        restitution: f32,
+    },
+}
+
+impl CollisionResponse {
+    /// Combines the collision responses of two colliding objects.
+    ///
+    /// Impulse response is used if either object asks for it. If both do,
+    /// their restitutions are averaged.
+    pub fn combine(self, other: CollisionResponse) -> CollisionResponse {
+        match (self, other) {
+            (Self::Depth, Self::Depth) => Self::Depth,
+            (Self::Impulse { restitution }, Self::Depth)
+            | (Self::Depth, Self::Impulse { restitution }) => Self::Impulse { restitution },
+            (Self::Impulse { restitution: r1 }, Self::Impulse { restitution: r2 }) => {
+                Self::Impulse {
+                    restitution: (r1 + r2) / 2.0,
+                }
+            }
+        }
+    }
+
+    /// Resolves a collision between two points.
+    ///
+    /// The normal must point from the first point towards the second.
+    fn resolve(&self, point1: &mut PhysPoint, point2: &mut PhysPoint, normal: Vec3, depth: f32) {
+        match *self {
+            Self::Depth => {
+                point1.vel -= normal * depth;
+                point2.vel += normal * depth;
+            }
+
+            Self::Impulse { restitution } => {
+                let inv_mass1 = if point1.mass > 0.0 {
+                    point1.mass.recip()
+                } else {
+                    0.0
+                };
+                let inv_mass2 = if point2.mass > 0.0 {
+                    point2.mass.recip()
+                } else {
+                    0.0
+                };
+                let inv_mass_sum = inv_mass1 + inv_mass2;
+
+                if inv_mass_sum <= 0.0 {
+                    return;
+                }
+
+                // Penetration correction, mass-weighted so that lighter
+                // points are the ones pushed out the most.
+                let correction = normal * depth.max(0.0) / inv_mass_sum;
+                point1.pos -= correction * inv_mass1;
+                point2.pos += correction * inv_mass2;
+
+                // Only resolve velocity if the points are approaching.
+                let approach_vel = (point2.vel - point1.vel).dot(normal);
+
+                if approach_vel >= 0.0 {
+                    return;
+                }
+
+                let impulse =
+                    normal * -(1.0 + restitution.clamp(0.0, 1.0)) * approach_vel / inv_mass_sum;
+                point1.vel -= impulse * inv_mass1;
+                point2.vel += impulse * inv_mass2;
+            }
+        }
+    }
+}
+
 /// A generic collision detection event interface.
 ///
 /// All collision detection event types must implement this trait's common
@@ -157,24 +257,47 @@ impl CollisionDetectionEvent for VolumeVolumeCollisionDetectionEvent {
 }
 
 /// Object-object collision via physics volumes.
+///
+/// Only checks the candidate pairs found by the broad phase; see
+/// [super::broadphase].
 fn volume_volume_collision_system(
     mut ev_collision: EventWriter<VolumeVolumeCollisionDetectionEvent>,
-    mut query: Query<(Entity, &mut PointNetwork, &VolumeCollection)>,
+    broad_phase: Res<BroadPhasePairs>,
+    mut query: Query<(
+        Entity,
+        &mut PointNetwork,
+        &VolumeCollection,
+        Option<&CollisionResponse>,
+    )>,
+    sleeping: Query<(), With<Sleeping>>,
 ) {
-    // [TODO] Replace global all-pair combination iteration with a spatially accelerated data structure.
-    let mut combinations = query.iter_combinations_mut();
+    let _scope = AllocationScope::new("volume_volume_collision");
 
     // [NOTE] For more info on the below comment on loop label, see note below
     // near its continue.
 
     // 'detect_loop:
-    while let Some([(e1, mut points1, volumes1), (e2, mut points2, volumes2)]) =
-        combinations.fetch_next()
-    {
-        if !volumes1.aabb(&points1).check(&volumes2.aabb(&points2)) {
+    for &(e1, e2) in &broad_phase.pairs {
+        // Two objects at rest against each other stay at rest.
+        if sleeping.contains(e1) && sleeping.contains(e2) {
             continue;
         }
 
+        let Ok(
+            [
+                (_, mut points1, volumes1, response1),
+                (_, mut points2, volumes2, response2),
+            ],
+        ) = query.get_many_mut([e1, e2])
+        else {
+            continue;
+        };
+
+        let response = response1
+            .copied()
+            .unwrap_or_default()
+            .combine(response2.copied().unwrap_or_default());
+
         for vol1 in &volumes1.volumes {
             let pos1 = points1.points[vol1.point_idx].pos;
 
@@ -190,11 +313,12 @@ fn volume_volume_collision_system(
                         - vol2.volume_type.sdf(collision.pos - offs_1_to_2))
                         / 2.0;
 
-                    // points1.points[vol1.point_idx].pos -= collision.normal * depth;
-                    points1.points[vol1.point_idx].vel -= collision.normal * depth;
-
-                    // points2.points[vol2.point_idx].pos += collision.normal * depth;
-                    points2.points[vol2.point_idx].vel += collision.normal * depth;
+                    response.resolve(
+                        &mut points1.points[vol1.point_idx],
+                        &mut points2.points[vol2.point_idx],
+                        collision.normal,
+                        depth,
+                    );
 
                     ev_collision.write(VolumeVolumeCollisionDetectionEvent {
                         entity_ref: e1,
@@ -226,8 +350,102 @@ impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             FixedUpdate,
-            (floor_plane_collision_system, volume_volume_collision_system),
+            (
+                floor_plane_collision_system,
+                (
+                    fast_projectile_sweep.after(point_base_physics),
+                    broad_phase_system,
+                    volume_volume_collision_system,
+                )
+                    .chain(),
+            ),
         );
+        app.init_resource::<BroadPhasePairs>();
         app.add_event::<VolumeVolumeCollisionDetectionEvent>();
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn approaching(mass1: f32, mass2: f32) -> (PhysPoint, PhysPoint) {
+        (
+            PhysPoint::new(Vec3::ZERO, Vec3::X * 2.0, mass1),
+            PhysPoint::new(Vec3::X, Vec3::NEG_X, mass2),
+        )
+    }
+
+    #[test]
+    fn impulse_response_wins_when_combined() {
+        let impulse = CollisionResponse::Impulse { restitution: 0.2 };
+        let bouncy = CollisionResponse::Impulse { restitution: 0.8 };
+
+        assert!(matches!(
+            CollisionResponse::Depth.combine(CollisionResponse::Depth),
+            CollisionResponse::Depth
+        ));
+        assert!(matches!(
+            CollisionResponse::Depth.combine(impulse),
+            CollisionResponse::Impulse { restitution: 0.2 }
+        ));
+        let CollisionResponse::Impulse { restitution } = impulse.combine(bouncy) else {
+            panic!("expected an impulse response");
+        };
+        assert!((restitution - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn impulses_conserve_momentum_and_weigh_by_mass() {
+        let response = CollisionResponse::Impulse { restitution: 0.5 };
+        let (mut light, mut heavy) = approaching(1.0, 9.0);
+        let momentum = light.vel * light.mass + heavy.vel * heavy.mass;
+
+        response.resolve(&mut light, &mut heavy, Vec3::X, 0.1);
+
+        let after = light.vel * light.mass + heavy.vel * heavy.mass;
+        assert!((after - momentum).length() < 1e-4, "{after} != {momentum}");
+
+        // They approached at 3, so they part at half that.
+        assert!(((heavy.vel - light.vel).x - 1.5).abs() < 1e-4);
+
+        // The light point takes most of the impulse and of the correction.
+        assert!(light.vel.x < 0.0 && heavy.vel.x > -1.0);
+        assert!((light.pos.x + 0.09).abs() < 1e-5, "{}", light.pos);
+        assert!((heavy.pos.x - 1.01).abs() < 1e-5, "{}", heavy.pos);
+    }
+
+    #[test]
+    fn elastic_impulses_swap_equal_velocities() {
+        let response = CollisionResponse::Impulse { restitution: 1.0 };
+        let (mut point1, mut point2) = approaching(2.0, 2.0);
+
+        response.resolve(&mut point1, &mut point2, Vec3::X, 0.0);
+
+        assert!((point1.vel - Vec3::NEG_X).length() < 1e-5, "{}", point1.vel);
+        assert!(
+            (point2.vel - Vec3::X * 2.0).length() < 1e-5,
+            "{}",
+            point2.vel
+        );
+    }
+
+    #[test]
+    fn impulses_leave_receding_and_massless_points_be() {
+        let response = CollisionResponse::Impulse { restitution: 1.0 };
+
+        // Already moving apart; only the overlap is corrected.
+        let (mut point1, mut point2) = approaching(1.0, 1.0);
+        point2.vel = Vec3::X * 3.0;
+        response.resolve(&mut point1, &mut point2, Vec3::X, 0.2);
+        assert_eq!((point1.vel, point2.vel), (Vec3::X * 2.0, Vec3::X * 3.0));
+        assert!((point1.pos.x + 0.1).abs() < 1e-5, "{}", point1.pos);
+        assert!((point2.pos.x - 1.1).abs() < 1e-5, "{}", point2.pos);
+
+        // Neither point can be moved.
+        let (mut point1, mut point2) = approaching(0.0, 0.0);
+        response.resolve(&mut point1, &mut point2, Vec3::X, 0.2);
+        assert_eq!((point1.vel, point2.vel), (Vec3::X * 2.0, Vec3::NEG_X));
+        assert_eq!((point1.pos, point2.pos), (Vec3::ZERO, Vec3::X));
+    }
+}
@@ -0,0 +1,193 @@
+//! # Deterministic physics
+//!
+//! Loot & Roam's distributive-authoritative networking model (see
+//! [crate::server]) has non-authoritative instances replay the simulation,
+//! predicting the authoritative one. For that to work, two instances given
+//! the same inputs must produce the exact same results.
+//!
+//! The determinism mode, enabled through
+//! [super::BasicPhysicsPlugin::deterministic], guarantees that by:
+//!
+//! * Using a fixed physics timestep, at a known tick rate;
+//! * Running physics systems one at a time, in a fixed order, rather than
+//!   in parallel;
+//! * Processing collisions in a stable order, sorted by entity;
+//! * Providing a seeded RNG, [PhysicsRng], for any randomness the
+//!   simulation needs.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::{ecs::schedule::ExecutorKind, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Configuration of the physics determinism mode.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsDeterminism {
+    /// Physics ticks per second.
+    pub tick_rate: f64,
+
+    /// Seed of the [PhysicsRng].
+    pub seed: u64,
+}
+
+impl PhysicsDeterminism {
+    /// Determinism mode at the default tick rate (64 Hz).
+    pub fn new(seed: u64) -> Self {
+        Self {
+            tick_rate: 64.0,
+            seed,
+        }
+    }
+}
+
+/// Seeded RNG for the simulation.
+///
+/// Any randomness which affects the simulation must be drawn from here, so
+/// that it is reproducible in determinism mode. Outside of it, the RNG is
+/// seeded from the OS, by whichever plugin first needs it.
+///
+/// Like the [WorldRng](crate::common::seed::WorldRng), it is a
+/// [ChaCha8Rng], whose output is the same across platforms and versions.
+#[derive(Resource, Debug, Clone)]
+pub struct PhysicsRng(pub ChaCha8Rng);
+
+impl PhysicsRng {
+    /// Creates a new RNG from a seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl Default for PhysicsRng {
+    fn default() -> Self {
+        Self(ChaCha8Rng::from_os_rng())
+    }
+}
+
+/// Sets up the determinism mode on an app.
+pub(super) fn setup_determinism(app: &mut App, config: PhysicsDeterminism) {
+    app.insert_resource(config);
+    app.insert_resource(PhysicsRng::from_seed(config.seed));
+    app.insert_resource(Time::<Fixed>::from_hz(config.tick_rate));
+
+    // Systems with conflicting access but no explicit ordering may run in
+    // either order on the multithreaded executor.
+    app.edit_schedule(FixedUpdate, |schedule| {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    });
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::physics::{
+        BasicPhysicsPlugin,
+        base::{PhysPoint, PointNetwork},
+        collision::{CollisionPlugin, FloorPlaneCollision},
+        determinism::PhysicsDeterminism,
+        forces::Gravity,
+        spring::{NormalSpring, SpringMode},
+        volume::{SphereDef, VolumeCloneSpawner, VolumeCollection, VolumeType},
+    };
+
+    fn make_world(config: PhysicsDeterminism) -> App {
+        let mut app = App::new();
+
+        app.add_plugins((
+            MinimalPlugins,
+            BasicPhysicsPlugin::deterministic(config),
+            CollisionPlugin,
+        ));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / config.tick_rate,
+        )));
+
+        // A few soft cubes, falling onto each other and the floor.
+        for (idx, offset) in [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.3, 2.6, 0.2),
+            Vec3::new(-0.2, 4.1, -0.4),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let points = PointNetwork::from(
+                (0..8)
+                    .map(|corner| {
+                        Vec3::new(
+                            (corner & 1) as f32,
+                            ((corner >> 1) & 1) as f32,
+                            ((corner >> 2) & 1) as f32,
+                        ) - 0.5
+                    })
+                    .map(|corner| PhysPoint::new(corner + offset, Vec3::ZERO, 1.0 + idx as f32)),
+            );
+            let springs = points
+                .make_fully_connected_springs(SpringMode::Normal(NormalSpring { stiffness: 40.0 }));
+            let volumes = VolumeCollection::at_every_point(
+                &points,
+                VolumeCloneSpawner::new(VolumeType::Sphere(SphereDef::new(0.3))),
+            );
+
+            app.world_mut().spawn((
+                points,
+                springs,
+                volumes,
+                Gravity::default(),
+                FloorPlaneCollision {
+                    intercept_y: 0.0,
+                    restitution: 0.3,
+                    friction: 0.2,
+                },
+            ));
+        }
+
+        app
+    }
+
+    fn positions(app: &mut App) -> Vec<Vec3> {
+        let mut query = app.world_mut().query::<(Entity, &PointNetwork)>();
+        let mut networks = query.iter(app.world()).collect::<Vec<_>>();
+        networks.sort_by_key(|(entity, _)| *entity);
+
+        networks
+            .into_iter()
+            .flat_map(|(_, network)| network.points.iter().map(|point| point.pos))
+            .collect()
+    }
+
+    #[test]
+    fn two_worlds_stay_identical() {
+        let config = PhysicsDeterminism::new(1234);
+        let mut world_1 = make_world(config);
+        let mut world_2 = make_world(config);
+        let initial_positions = positions(&mut world_1);
+
+        for _ in 0..300 {
+            world_1.update();
+            world_2.update();
+        }
+
+        let positions_1 = positions(&mut world_1);
+        let positions_2 = positions(&mut world_2);
+
+        // The simulation must have actually run.
+        assert_ne!(positions_1, initial_positions);
+        assert_eq!(positions_1, positions_2);
+    }
+}
@@ -1,6 +1,6 @@
 //! # Basic physics forces
 //!
-//! Gravity and air drag.
+//! Gravity, air drag, and wind.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -19,6 +19,7 @@ use bevy::prelude::*;
 
 use super::{
     base::PointNetwork,
+    sleep::Sleeping,
     volume::{VolumeCollection, VolumeInfo},
 };
 
@@ -50,7 +51,7 @@ impl Gravity {
 }
 
 /// The system responsible for gravity in the physics system.
-fn gravity(time: Res<Time>, mut query: Query<(&mut PointNetwork, &Gravity)>) {
+fn gravity(time: Res<Time>, mut query: Query<(&mut PointNetwork, &Gravity), Without<Sleeping>>) {
     for (mut points, gravity) in query.iter_mut() {
         for point in points.points.iter_mut() {
             point.vel += gravity.force * time.delta_secs();
@@ -82,7 +83,10 @@ impl Default for AirDrag {
 }
 
 /// The system responsible for air drag in the physics system.
-fn air_drag(time: Res<Time>, mut query: Query<(&mut PointNetwork, &VolumeCollection, &AirDrag)>) {
+fn air_drag(
+    time: Res<Time>,
+    mut query: Query<(&mut PointNetwork, &VolumeCollection, &AirDrag), Without<Sleeping>>,
+) {
     for (mut points, volumes, drag) in query.iter_mut() {
         for volume in &volumes.volumes {
             let point = &mut points.points[volume.point_idx];
@@ -93,10 +97,268 @@ fn air_drag(time: Res<Time>, mut query: Query<(&mut PointNetwork, &VolumeCollect
     }
 }
 
+/// This Bevy component damps the rotation of a physics-enabled object.
+///
+/// Without it, a spinning object keeps spinning forever while airborne.
+///
+/// Requires [PointNetwork].
+#[derive(Component, Clone)]
+pub struct RotationalDrag {
+    /// Fraction of the angular velocity lost per second, roughly.
+    ///
+    /// Damping is exponential, so values above 1.0 are valid, and simply
+    /// damp faster.
+    pub drag_factor: f32,
+}
+
+impl RotationalDrag {
+    pub fn new(drag_factor: f32) -> Self {
+        Self { drag_factor }
+    }
+}
+
+impl Default for RotationalDrag {
+    fn default() -> Self {
+        Self { drag_factor: 0.5 }
+    }
+}
+
+/// The system responsible for rotational drag in the physics system.
+///
+/// Applies a counter-torque against the angular velocity about the center of
+/// mass, distributed across every point according to its distance from it.
+fn rotational_drag(
+    time: Res<Time>,
+    mut query: Query<(&mut PointNetwork, &RotationalDrag), Without<Sleeping>>,
+) {
+    for (mut points, drag) in query.iter_mut() {
+        let angular_velocity = points.angular_velocity();
+
+        if angular_velocity == Vec3::ZERO {
+            continue;
+        }
+
+        let center_of_mass = points.center_of_mass();
+        let damping = 1.0 - (-drag.drag_factor * time.delta_secs()).exp();
+
+        for point in points.points.iter_mut() {
+            let rotational_vel = angular_velocity.cross(point.pos - center_of_mass);
+            point.vel -= rotational_vel * damping;
+        }
+    }
+}
+
+/// The global wind.
+///
+/// Blows horizontally, with gusts that travel downwind across the sea, so
+/// that nearby objects feel a gust at roughly the same time.
+///
+/// Besides pushing [WindAffected] objects around, this can be queried
+/// directly, e.g. by sails to generate thrust; see [Wind::sail_force].
+#[derive(Resource, Clone, Debug)]
+pub struct Wind {
+    /// The direction the wind blows towards.
+    ///
+    /// Only the horizontal (XZ) component is used.
+    pub direction: Vec3,
+
+    /// The mean wind speed.
+    pub strength: f32,
+
+    /// How much gusts vary the wind speed, as a fraction of [Wind::strength].
+    pub gustiness: f32,
+
+    /// Roughly how many gusts pass by per second.
+    pub gust_frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::X,
+            strength: 5.0,
+            gustiness: 0.3,
+            gust_frequency: 0.1,
+        }
+    }
+}
+
+impl Wind {
+    /// Normalized horizontal direction of the wind.
+    pub fn horizontal_direction(&self) -> Vec3 {
+        Vec3::new(self.direction.x, 0.0, self.direction.z).normalize_or_zero()
+    }
+
+    /// Gust multiplier of the wind speed at a given position and time.
+    ///
+    /// Always between `1 - gustiness` and `1 + gustiness`.
+    pub fn gust_at(&self, pos: Vec3, elapsed_secs: f32) -> f32 {
+        if self.strength <= 0.0 {
+            return 1.0;
+        }
+
+        // Gusts travel with the wind.
+        let phase = (elapsed_secs - pos.dot(self.horizontal_direction()) / self.strength)
+            * self.gust_frequency
+            * std::f32::consts::TAU;
+
+        // Incommensurate frequencies, so the pattern never visibly repeats.
+        let noise =
+            0.5 * phase.sin() + 0.3 * (phase * 2.31 + 1.7).sin() + 0.2 * (phase * 4.73 + 0.4).sin();
+
+        1.0 + self.gustiness * noise
+    }
+
+    /// Wind velocity at a given position and time.
+    pub fn velocity_at(&self, pos: Vec3, elapsed_secs: f32) -> Vec3 {
+        self.horizontal_direction() * self.strength * self.gust_at(pos, elapsed_secs)
+    }
+
+    /// Force of the wind on a flat sail.
+    ///
+    /// * `pos` - Where the sail is.
+    /// * `vessel_vel` - Velocity of the sail, which changes the apparent wind.
+    /// * `sail_normal` - Normal of the sail; its sign does not matter.
+    /// * `sail_area` - Area of the sail.
+    /// * `lift_factor` - Efficiency of the sail.
+    ///
+    /// The force is always along the sail's normal. Ship parts turn that into
+    /// thrust and heeling by applying it at the mast.
+    pub fn sail_force(
+        &self,
+        pos: Vec3,
+        elapsed_secs: f32,
+        vessel_vel: Vec3,
+        sail_normal: Vec3,
+        sail_area: f32,
+        lift_factor: f32,
+    ) -> Vec3 {
+        let apparent_wind = self.velocity_at(pos, elapsed_secs) - vessel_vel;
+        let sail_normal = sail_normal.normalize_or_zero();
+        let normal_speed = apparent_wind.dot(sail_normal);
+
+        sail_normal * normal_speed * normal_speed.abs() * sail_area * lift_factor
+    }
+}
+
+/// This Bevy component makes a physics-enabled object get pushed by the
+/// [Wind].
+///
+/// The force is proportional to the surface area of each volume, and is the
+/// counterpart to [AirDrag]: with the same factor on both, an object is
+/// dragged by the air relative to the wind, rather than to still air.
+///
+/// Requires [PointNetwork] and [VolumeCollection].
+#[derive(Component, Clone)]
+pub struct WindAffected {
+    pub wind_factor: f32,
+}
+
+impl WindAffected {
+    pub fn new(wind_factor: f32) -> Self {
+        Self { wind_factor }
+    }
+}
+
+impl Default for WindAffected {
+    fn default() -> Self {
+        Self { wind_factor: 0.1 }
+    }
+}
+
+// [TODO] Only count the area above water, once there is a notion of water
+// level outside of [super::water::WaterPhysics].
+
+/// The system responsible for wind forces in the physics system.
+fn wind_force(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut query: Query<(&mut PointNetwork, &VolumeCollection, &WindAffected), Without<Sleeping>>,
+) {
+    let elapsed_secs = time.elapsed_secs();
+
+    for (mut points, volumes, affected) in query.iter_mut() {
+        for volume in &volumes.volumes {
+            let point = &mut points.points[volume.point_idx];
+
+            let force = wind.velocity_at(point.pos, elapsed_secs)
+                * volume.volume_type.surface_area()
+                * affected.wind_factor;
+            point.apply_force_over_time(force, time.delta_secs());
+        }
+    }
+}
+
 pub struct BasicForcesPlugin;
 
 impl Plugin for BasicForcesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, (gravity, air_drag));
+        app.init_resource::<Wind>();
+        app.add_systems(
+            FixedUpdate,
+            (gravity, air_drag, rotational_drag, wind_force),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::common::physics::base::PhysPoint;
+
+    use super::*;
+
+    /// A triangle of points, spinning about its center of mass as it drifts.
+    fn spinning_triangle(angular_velocity: Vec3, linear_velocity: Vec3) -> PointNetwork {
+        let positions = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.5, 2.0),
+        ];
+        let center = positions.iter().sum::<Vec3>() / 3.0;
+
+        PointNetwork::from(positions.into_iter().map(|pos| {
+            let vel = angular_velocity.cross(pos - center) + linear_velocity;
+            PhysPoint::new(pos, vel, 1.0)
+        }))
+    }
+
+    #[test]
+    fn rotational_drag_damps_spin_but_not_drift() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+
+        let spin = Vec3::Y * 2.0;
+        let drift = Vec3::Z * 3.0;
+        let spinning = world
+            .spawn((spinning_triangle(spin, drift), RotationalDrag::new(0.5)))
+            .id();
+        let still = world
+            .spawn((
+                spinning_triangle(Vec3::ZERO, drift),
+                RotationalDrag::new(0.5),
+            ))
+            .id();
+
+        world.run_system_once(rotational_drag).unwrap();
+
+        // Half of the spin, roughly, is lost over a second.
+        let points = world.get::<PointNetwork>(spinning).unwrap();
+        let expected = spin * (-0.5_f32).exp();
+        assert!(
+            (points.angular_velocity() - expected).length() < 1e-4,
+            "{}",
+            points.angular_velocity()
+        );
+        assert!((points.linear_velocity() - drift).length() < 1e-4);
+
+        // Objects which don't spin are left alone.
+        let points = world.get::<PointNetwork>(still).unwrap();
+        assert!(points.points.iter().all(|point| point.vel == drift));
     }
 }
@@ -17,16 +17,28 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use base::{point_attach_snap, point_base_physics};
+use base::{AttachCulling, point_attach_snap, point_base_physics};
 use bevy::prelude::*;
+use determinism::PhysicsDeterminism;
 use forces::BasicForcesPlugin;
+use rigid::RigidBodyPlugin;
+use sleep::SleepPlugin;
+use snapshot::PhysicsSnapshotPlugin;
 use spring::SpringForcesPlugin;
+use stability::ShipStabilityPlugin;
 use water::WaterPhysicsPlugin;
 
 pub mod base; // Basic point network definitions and systems
+pub mod broadphase; // Collision broad phase
 pub mod collision; // Advanced collision handling for objects
+pub mod determinism; // Deterministic physics mode
 pub mod forces; // Basic forces
+pub mod projectile; // Swept collisions for fast projectiles
+pub mod rigid; // Shape matching rigid body constraints
+pub mod sleep; // Resting object deactivation
+pub mod snapshot; // Physics state capture and restore
 pub mod spring; // Spring based soft body implementation
+pub mod stability; // Ship self-righting
 pub mod torque; // User rotational forces
 pub mod volume; // Volumes, their intersection, and volume/surface forces
 pub mod water; // Water physics
@@ -36,12 +48,38 @@ pub mod water; // Water physics
 /// Adds systems for basic physics:
 ///
 /// * Point inertia (applying velocity to position) - see [PointNetwork].
+/// * Snapping [base::PointAttach] children to their points, culled when
+///   off-screen - see [AttachCulling].
 /// * [SpringNetwork]s.
 /// * [Gravity].
-pub struct BasicPhysicsPlugin;
+/// * [forces::Wind], pushing [forces::WindAffected] objects.
+///
+/// Physics ticks are counted, and the physics state can be captured and
+/// restored; see [snapshot].
+///
+/// Optionally, physics can be made deterministic; see [determinism].
+#[derive(Default)]
+pub struct BasicPhysicsPlugin {
+    /// Determinism mode configuration, if enabled.
+    pub determinism: Option<PhysicsDeterminism>,
+}
+
+impl BasicPhysicsPlugin {
+    /// Basic physics, in determinism mode.
+    pub fn deterministic(config: PhysicsDeterminism) -> Self {
+        Self {
+            determinism: Some(config),
+        }
+    }
+}
 
 impl Plugin for BasicPhysicsPlugin {
     fn build(&self, app: &mut App) {
+        if let Some(config) = self.determinism {
+            determinism::setup_determinism(app, config);
+        }
+
+        app.init_resource::<AttachCulling>();
         app.add_systems(
             FixedUpdate,
             (
@@ -49,21 +87,39 @@ impl Plugin for BasicPhysicsPlugin {
                 point_attach_snap.after(point_base_physics),
             ),
         );
-        app.add_plugins((SpringForcesPlugin, BasicForcesPlugin, WaterPhysicsPlugin));
+        app.add_plugins((
+            SpringForcesPlugin,
+            RigidBodyPlugin,
+            SleepPlugin,
+            PhysicsSnapshotPlugin,
+            BasicForcesPlugin,
+            WaterPhysicsPlugin,
+            ShipStabilityPlugin,
+        ));
     }
 }
 
 pub mod prelude {
     pub use super::BasicPhysicsPlugin;
-    pub use super::base::{PhysPoint, PointAttach, PointNetwork};
+    pub use super::base::{AlwaysSnap, AttachCulling, PhysPoint, PointAttach, PointNetwork};
+    pub use super::broadphase::BroadPhasePairs;
     pub use super::collision::{
-        CollisionPlugin, FloorPlaneCollision, VolumeVolumeCollisionDetectionEvent,
+        CollisionPlugin, CollisionResponse, FloorPlaneCollision,
+        VolumeVolumeCollisionDetectionEvent,
     };
-    pub use super::forces::{AirDrag, Gravity};
+    pub use super::determinism::{PhysicsDeterminism, PhysicsRng};
+    pub use super::forces::{AirDrag, Gravity, RotationalDrag, Wind, WindAffected};
+    pub use super::projectile::FastProjectile;
+    pub use super::rigid::RigidBodyMode;
+    pub use super::sleep::{SleepPolicy, Sleeping};
+    pub use super::snapshot::{PhysicsSnapshot, PhysicsSnapshotHistory, PhysicsTick};
     pub use super::spring::{NormalSpring, Spring, SpringMode, SpringNetwork};
+    pub use super::stability::ShipStability;
     pub use super::volume::{
-        AABB, CollisionInfo, PhysicsVolume, SphereDef, VolumeCloneSpawner, VolumeCollection,
-        VolumeCollision, VolumeInfo, VolumeType,
+        AABB, BoxDef, CapsuleDef, CollisionInfo, CylinderDef, PhysicsVolume, SphereDef, VolumeAxis,
+        VolumeCloneSpawner, VolumeCollection, VolumeCollision, VolumeInfo, VolumeType,
+    };
+    pub use super::water::{
+        WaterCurrentField, WaterPhysics, WaterSurface, WaterWake, Wave, WaveField,
     };
-    pub use super::water::WaterPhysics;
 }
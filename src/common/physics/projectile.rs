@@ -0,0 +1,204 @@
+//! # Fast projectiles
+//!
+//! Objects fast enough to travel further than their own size in a single
+//! physics tick, such as cannonballs, could fly right through whatever they
+//! hit, since collisions are only checked where objects are at every tick.
+//!
+//! A [FastProjectile]'s path since the previous tick is swept for obstacles
+//! before collisions are checked. If it hit anything along the way, it is
+//! moved back to where it first touched it, so that the collision is then
+//! detected and resolved as usual.
+//!
+//! Projectiles also never collide with their [FastProjectile::source], such
+//! as the ship which fired them.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    base::PointNetwork,
+    volume::{VolumeCollection, VolumeCollision},
+};
+
+/// Marks an object as a fast projectile, whose path is swept for collisions.
+///
+/// Requires [PointNetwork] and [VolumeCollection].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FastProjectile {
+    /// The object which launched this projectile, if any.
+    ///
+    /// The projectile does not collide with it.
+    pub source: Option<Entity>,
+
+    /// The projectile's center of mass on the previous tick.
+    last_pos: Option<Vec3>,
+}
+
+impl FastProjectile {
+    /// A projectile launched by an object.
+    pub fn from_source(source: Entity) -> Self {
+        Self {
+            source: Some(source),
+            last_pos: None,
+        }
+    }
+
+    /// Whether this projectile may collide with an object.
+    pub fn collides_with(&self, other: Entity) -> bool {
+        self.source != Some(other)
+    }
+}
+
+/// Sweeps the paths of [FastProjectile]s, moving them back to where they first
+/// hit an obstacle, if any.
+// Always runs after point_base_physics, and before the broad phase.
+pub fn fast_projectile_sweep(
+    mut projectiles: Query<(&mut FastProjectile, &mut PointNetwork, &VolumeCollection)>,
+    obstacles: Query<(Entity, &PointNetwork, &VolumeCollection), Without<FastProjectile>>,
+) {
+    for (mut projectile, mut points, volumes) in &mut projectiles {
+        if volumes.volumes.is_empty() {
+            continue;
+        }
+
+        let current_pos = points.center_of_mass();
+        let Some(last_pos) = projectile.last_pos.replace(current_pos) else {
+            continue;
+        };
+
+        // Sweep in steps of half the projectile's size.
+        let travel = current_pos - last_pos;
+        let aabb = volumes.aabb(&points);
+        let size = aabb
+            .spans
+            .iter()
+            .map(|span| span.end - span.start)
+            .fold(f32::INFINITY, f32::min);
+        let steps = (travel.length() / (size * 0.5).max(0.01)).ceil() as usize;
+
+        if steps <= 1 {
+            continue;
+        }
+
+        let path = aabb.clone().union(aabb.translate(-travel));
+        let mut first_hit: Option<usize> = None;
+
+        for (entity, obstacle_points, obstacle_volumes) in &obstacles {
+            if !projectile.collides_with(entity)
+                || obstacle_volumes.volumes.is_empty()
+                || !path.check(&obstacle_volumes.aabb(obstacle_points))
+            {
+                continue;
+            }
+
+            let hits_at = |step: usize| {
+                let rewind = travel * (1.0 - step as f32 / steps as f32);
+
+                volumes.iter_with_points(&points).any(|(volume, point)| {
+                    obstacle_volumes.iter_with_points(obstacle_points).any(
+                        |(obstacle_volume, obstacle_point)| {
+                            let offset = obstacle_point.pos - (point.pos - rewind);
+
+                            volume
+                                .volume_type
+                                .collides_with(&obstacle_volume.volume_type, offset)
+                        },
+                    )
+                })
+            };
+
+            let until = first_hit.unwrap_or(steps);
+            if let Some(step) = (0..until).find(|&step| hits_at(step)) {
+                first_hit = Some(step);
+            }
+        }
+
+        if let Some(step) = first_hit {
+            let rewind = travel * (1.0 - step as f32 / steps as f32);
+
+            for point in &mut points.points {
+                point.pos -= rewind;
+            }
+            projectile.last_pos = Some(current_pos - rewind);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::physics::{
+        base::{PhysPoint, PointNetwork},
+        volume::{BoxDef, PhysicsVolume, SphereDef, VolumeCollection, VolumeType},
+    };
+
+    use super::*;
+
+    fn volumes(volume_type: VolumeType) -> VolumeCollection {
+        VolumeCollection {
+            volumes: vec![PhysicsVolume {
+                point_idx: 0,
+                volume_type,
+            }],
+        }
+    }
+
+    #[test]
+    fn projectiles_stop_at_walls_but_not_their_source() {
+        let mut app = App::new();
+        app.add_systems(Update, fast_projectile_sweep);
+
+        // A thin wall at X = 5.
+        let wall = app
+            .world_mut()
+            .spawn((
+                PointNetwork::from([PhysPoint::new(Vec3::X * 5.0, Vec3::ZERO, 1.0)].into_iter()),
+                volumes(VolumeType::Box(BoxDef::new(Vec3::new(0.1, 5.0, 5.0)))),
+            ))
+            .id();
+
+        let mut launch = |source: Option<Entity>| {
+            let ball = app
+                .world_mut()
+                .spawn((
+                    FastProjectile {
+                        source,
+                        last_pos: None,
+                    },
+                    PointNetwork::from([PhysPoint::zero()].into_iter()),
+                    volumes(VolumeType::Sphere(SphereDef::new(0.2))),
+                ))
+                .id();
+            app.update();
+
+            // Right through the wall, within a single tick.
+            app.world_mut()
+                .get_mut::<PointNetwork>(ball)
+                .unwrap()
+                .points[0]
+                .pos = Vec3::X * 10.0;
+            app.update();
+
+            app.world().get::<PointNetwork>(ball).unwrap().points[0].pos
+        };
+
+        let stopped = launch(None);
+        assert!((4.0..5.0).contains(&stopped.x), "{stopped}");
+
+        let passed = launch(Some(wall));
+        assert_eq!(passed, Vec3::X * 10.0);
+    }
+}
@@ -0,0 +1,283 @@
+//! # Rigid body mode
+//!
+//! Shape matching constraints, which keep a point network close to its rest
+//! configuration, as described by Müller et al. in "Meshless Deformations
+//! Based on Shape Matching" (2005).
+//!
+//! Every tick, the best-fitting rigid transform of the rest shape onto the
+//! current points is found, and the points are pulled towards it. Unlike
+//! springs, this never drifts or wobbles, so it is well suited for ships and
+//! other objects that should feel rigid.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::diagnostics::AllocationScope;
+
+use super::{base::PointNetwork, sleep::Sleeping};
+
+/// Number of iterations used to extract the rotation of the point network.
+///
+/// The rotation from the previous tick is used as a starting guess, so few
+/// iterations are needed.
+const ROTATION_ITERATIONS: usize = 4;
+
+/// Keeps a [PointNetwork] in its rest configuration using shape matching.
+///
+/// Can be used instead of a [super::spring::SpringNetwork], or alongside one;
+/// the stiffness blends between leaving the points alone and keeping them
+/// fully rigid.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct RigidBodyMode {
+    /// How strongly points are pulled to their rest configuration every tick,
+    /// between 0.0 (not at all) and 1.0 (fully rigid).
+    pub stiffness: f32,
+
+    /// Rest position of each point, relative to the rest center of mass.
+    rest_shape: Vec<Vec3>,
+
+    /// The current rotation of the network, relative to its rest shape.
+    rotation: Quat,
+}
+
+impl RigidBodyMode {
+    /// Creates a rigid body mode, with the network's current configuration as
+    /// its rest shape.
+    pub fn from_network(network: &PointNetwork, stiffness: f32) -> Self {
+        let center_of_mass = network.center_of_mass();
+
+        Self {
+            stiffness,
+            rest_shape: network
+                .points
+                .iter()
+                .map(|point| point.pos - center_of_mass)
+                .collect(),
+            rotation: Quat::IDENTITY,
+        }
+    }
+
+    /// The current rotation of the network, relative to its rest shape.
+    pub fn rotation(&self) -> Quat {
+        self.rotation
+    }
+
+    /// Finds the rotation which best maps the rest shape onto the current
+    /// points, given their moment matrix.
+    ///
+    /// Uses the iterative method from Müller et al., "A Robust Method to
+    /// Extract the Rotational Part of Deformations" (2016).
+    fn extract_rotation(&mut self, moment: Mat3) {
+        for _ in 0..ROTATION_ITERATIONS {
+            let rotation = Mat3::from_quat(self.rotation);
+
+            let omega = (0..3)
+                .map(|i| rotation.col(i).cross(moment.col(i)))
+                .sum::<Vec3>()
+                / ((0..3)
+                    .map(|i| rotation.col(i).dot(moment.col(i)))
+                    .sum::<f32>()
+                    .abs()
+                    + 1.0e-9);
+
+            let angle = omega.length();
+
+            if angle < 1.0e-9 {
+                break;
+            }
+
+            self.rotation =
+                (Quat::from_axis_angle(omega / angle, angle) * self.rotation).normalize();
+        }
+    }
+}
+
+/// The system responsible for shape matching constraints.
+pub(super) fn rigid_body_constraints(
+    time: Res<Time>,
+    mut query: Query<(&mut PointNetwork, &mut RigidBodyMode), Without<Sleeping>>,
+) {
+    let _scope = AllocationScope::new("rigid_body_constraints");
+
+    let delta_secs = time.delta_secs();
+
+    if delta_secs <= 0.0 {
+        return;
+    }
+
+    for (mut points, mut rigid) in query.iter_mut() {
+        if rigid.rest_shape.len() != points.points.len() {
+            warn!("RigidBodyMode's rest shape does not match its PointNetwork; skipping");
+            continue;
+        }
+
+        let center_of_mass = points.center_of_mass();
+
+        let moment = points
+            .points
+            .iter()
+            .zip(&rigid.rest_shape)
+            .map(|(point, rest)| {
+                let relative_pos = (point.pos - center_of_mass) * point.mass;
+                Mat3::from_cols(
+                    relative_pos * rest.x,
+                    relative_pos * rest.y,
+                    relative_pos * rest.z,
+                )
+            })
+            .fold(Mat3::ZERO, |a, b| a + b);
+
+        rigid.extract_rotation(moment);
+
+        let stiffness = rigid.stiffness.clamp(0.0, 1.0);
+
+        for (point, rest) in points.points.iter_mut().zip(&rigid.rest_shape) {
+            let goal = center_of_mass + rigid.rotation * *rest;
+            let correction = (goal - point.pos) * stiffness;
+
+            // Position-based correction; velocity is corrected alongside, so
+            // that it stays consistent with the corrected motion.
+            point.pos += correction;
+            point.vel += correction / delta_secs;
+        }
+    }
+}
+
+/// Rigid body plugin.
+///
+/// Included in [super::BasicPhysicsPlugin].
+pub struct RigidBodyPlugin;
+
+impl Plugin for RigidBodyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            rigid_body_constraints
+                .after(super::base::point_base_physics)
+                .before(super::base::point_attach_snap),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::common::physics::base::PhysPoint;
+
+    use super::*;
+
+    fn rest_network() -> PointNetwork {
+        PointNetwork::from(
+            [
+                (Vec3::new(1.0, 0.0, 0.0), 1.0),
+                (Vec3::new(-1.0, 0.0, 0.0), 1.0),
+                (Vec3::new(0.0, 1.0, 0.0), 1.0),
+                (Vec3::new(0.0, 0.0, 1.5), 2.0),
+            ]
+            .into_iter()
+            .map(|(pos, mass)| PhysPoint::new(pos, Vec3::ZERO, mass)),
+        )
+    }
+
+    fn distances(network: &PointNetwork) -> Vec<f32> {
+        let points = &network.points;
+        (0..points.len())
+            .flat_map(|i| (i + 1..points.len()).map(move |j| points[i].pos.distance(points[j].pos)))
+            .collect()
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f64(1.0 / 64.0));
+        world.insert_resource(time);
+        world
+    }
+
+    #[test]
+    fn rotation_is_tracked_without_moving_points() {
+        let mut world = world();
+        let rest = rest_network();
+        let rotation = Quat::from_euler(EulerRot::XYZ, 0.4, 1.1, -0.3);
+
+        let mut moved = rest.clone();
+        for point in &mut moved.points {
+            point.pos = rotation * point.pos + Vec3::new(5.0, 2.0, -3.0);
+        }
+
+        // With no stiffness, the points are left alone, but the rotation is
+        // still found, refining it tick by tick.
+        let body = world
+            .spawn((moved.clone(), RigidBodyMode::from_network(&rest, 0.0)))
+            .id();
+        for _ in 0..10 {
+            world.run_system_once(rigid_body_constraints).unwrap();
+        }
+
+        let points = world.get::<PointNetwork>(body).unwrap();
+        assert!(
+            points
+                .points
+                .iter()
+                .zip(&moved.points)
+                .all(|(point, moved)| point.pos == moved.pos && point.vel == Vec3::ZERO)
+        );
+        let found = world.get::<RigidBodyMode>(body).unwrap().rotation();
+        assert!(found.angle_between(rotation) < 1e-3, "{found}");
+    }
+
+    #[test]
+    fn stiff_bodies_snap_back_to_their_rest_shape() {
+        let mut world = world();
+        let rest = rest_network();
+
+        let mut deformed = rest.clone();
+        deformed.points[2].pos += Vec3::new(0.3, -0.2, 0.1);
+        let center_of_mass = deformed.center_of_mass();
+
+        let body = world
+            .spawn((deformed, RigidBodyMode::from_network(&rest, 1.0)))
+            .id();
+        world.run_system_once(rigid_body_constraints).unwrap();
+
+        // Rigid again, and still in the same place.
+        let points = world.get::<PointNetwork>(body).unwrap();
+        for (distance, rest_distance) in distances(points).into_iter().zip(distances(&rest)) {
+            assert!((distance - rest_distance).abs() < 1e-3);
+        }
+        assert!((points.center_of_mass() - center_of_mass).length() < 1e-4);
+
+        // Rest shapes which don't fit the network are ignored.
+        let mismatched = world
+            .spawn((
+                rest.clone(),
+                RigidBodyMode::from_network(&rest_network(), 1.0),
+            ))
+            .id();
+        world
+            .get_mut::<PointNetwork>(mismatched)
+            .unwrap()
+            .points
+            .pop();
+        world.run_system_once(rigid_body_constraints).unwrap();
+        let points = world.get::<PointNetwork>(mismatched).unwrap();
+        assert_eq!(points.points.len(), 3);
+        assert!(points.points.iter().all(|point| point.vel == Vec3::ZERO));
+    }
+}
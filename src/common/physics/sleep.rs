@@ -0,0 +1,184 @@
+//! # Sleeping objects
+//!
+//! Objects at rest, such as loot crates bobbing in the water, would otherwise
+//! jitter forever, wasting CPU on forces and collisions that change nothing.
+//!
+//! Objects with a [SleepPolicy] are put to sleep once their kinetic energy
+//! stays below a threshold for long enough. Sleeping objects are marked
+//! [Sleeping], and skipped by force and collision systems. They wake up as
+//! soon as anything else changes their [PointNetwork], such as an awake
+//! object colliding with them, or gameplay code applying a force.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::base::PointNetwork;
+
+/// Allows an object to be put to sleep when at rest.
+///
+/// Requires [PointNetwork].
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct SleepPolicy {
+    /// Kinetic energy per unit of mass, below which the object is
+    /// considered at rest.
+    pub energy_threshold: f32,
+
+    /// How long the object must be at rest before it is put to sleep, in
+    /// seconds.
+    pub delay: f32,
+
+    /// How long the object has been at rest, in seconds.
+    rest_time: f32,
+}
+
+impl SleepPolicy {
+    pub fn new(energy_threshold: f32, delay: f32) -> Self {
+        Self {
+            energy_threshold,
+            delay,
+            rest_time: 0.0,
+        }
+    }
+}
+
+impl Default for SleepPolicy {
+    fn default() -> Self {
+        Self::new(0.01, 1.0)
+    }
+}
+
+/// Marks a sleeping object.
+///
+/// Inserted and removed automatically. Removing it manually wakes the object
+/// up.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Sleeping;
+
+impl PointNetwork {
+    /// Average kinetic energy per unit of mass.
+    pub fn kinetic_energy_per_mass(&self) -> f32 {
+        let total_mass: f32 = self.points.iter().map(|point| point.mass).sum();
+        if total_mass == 0.0 {
+            return 0.0;
+        }
+
+        self.points
+            .iter()
+            .map(|point| 0.5 * point.mass * point.vel.length_squared())
+            .sum::<f32>()
+            / total_mass
+    }
+}
+
+/// Wakes up sleeping objects whose point network was changed.
+///
+/// Physics systems skip sleeping objects, so any change to them comes from
+/// elsewhere, e.g. a collision with an awake object, or an applied force.
+pub(super) fn wake_changed(
+    mut commands: Commands,
+    query: Query<Entity, (With<Sleeping>, Changed<PointNetwork>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).remove::<Sleeping>();
+    }
+}
+
+/// Puts objects to sleep once they have been at rest for long enough.
+pub(super) fn fall_asleep(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PointNetwork, &mut SleepPolicy), Without<Sleeping>>,
+) {
+    for (entity, mut points, mut policy) in query.iter_mut() {
+        if points.kinetic_energy_per_mass() > policy.energy_threshold {
+            policy.rest_time = 0.0;
+            continue;
+        }
+
+        policy.rest_time += time.delta_secs();
+
+        if policy.rest_time < policy.delay {
+            continue;
+        }
+
+        // Bypassing change detection, or it would immediately wake up again.
+        for point in points.bypass_change_detection().points.iter_mut() {
+            point.vel = Vec3::ZERO;
+        }
+
+        policy.rest_time = 0.0;
+        commands.entity(entity).insert(Sleeping);
+    }
+}
+
+/// Puts resting objects to sleep; see [SleepPolicy].
+///
+/// Runs after the physics tick, so that any changes made to sleeping objects
+/// during it wake them up.
+///
+/// Included in [super::BasicPhysicsPlugin].
+pub struct SleepPlugin;
+
+impl Plugin for SleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedPostUpdate, (wake_changed, fall_asleep).chain());
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::physics::{
+        BasicPhysicsPlugin,
+        base::{PhysPoint, PointNetwork},
+        sleep::{SleepPolicy, Sleeping},
+    };
+
+    #[test]
+    fn sleeps_at_rest_and_wakes_on_change() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, BasicPhysicsPlugin::default()));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                PointNetwork::from([PhysPoint::zero()].into_iter()),
+                SleepPolicy::new(0.01, 0.5),
+            ))
+            .id();
+
+        for _ in 0..120 {
+            app.update();
+        }
+        assert!(app.world().entity(entity).contains::<Sleeping>());
+
+        app.world_mut()
+            .get_mut::<PointNetwork>(entity)
+            .unwrap()
+            .apply_instant_force(Vec3::X);
+
+        for _ in 0..8 {
+            app.update();
+        }
+        assert!(!app.world().entity(entity).contains::<Sleeping>());
+    }
+}
@@ -0,0 +1,350 @@
+//! # Physics snapshots
+//!
+//! Captures the full physics state of a world, so that it can be re-applied
+//! later; e.g. to roll back and replay the simulation when networking (see
+//! [super::determinism]), or to save the game.
+//!
+//! A [PhysicsSnapshot] holds the physics components of every body, as of a
+//! [PhysicsTick]. Snapshots are serializable with serde.
+//!
+//! To keep recent snapshots around for rollback, insert a
+//! [PhysicsSnapshotHistory] resource; a snapshot is then recorded after every
+//! physics tick.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    base::PointNetwork,
+    rigid::RigidBodyMode,
+    sleep::{SleepPolicy, Sleeping},
+    spring::SpringNetwork,
+    volume::VolumeCollection,
+    water::WaterPhysics,
+};
+
+/// Number of physics ticks run so far.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicsTick(pub u64);
+
+/// The physics state of a single body.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BodySnapshot {
+    /// The entity of the body.
+    pub entity: Entity,
+
+    pub points: PointNetwork,
+    pub springs: Option<SpringNetwork>,
+    pub rigid: Option<RigidBodyMode>,
+    pub volumes: Option<VolumeCollection>,
+    pub water: Option<WaterPhysics>,
+    pub sleep_policy: Option<SleepPolicy>,
+
+    /// Whether the body was [Sleeping].
+    pub sleeping: bool,
+}
+
+/// The physics state of every body in a world, as of a physics tick.
+///
+/// Every entity with a [PointNetwork] is a body.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PhysicsSnapshot {
+    /// The [PhysicsTick] this snapshot was captured at.
+    pub tick: u64,
+
+    /// Every body, sorted by entity.
+    pub bodies: Vec<BodySnapshot>,
+}
+
+impl PhysicsSnapshot {
+    /// Captures the physics state of a world.
+    pub fn capture(world: &mut World) -> Self {
+        let tick = world
+            .get_resource::<PhysicsTick>()
+            .copied()
+            .unwrap_or_default();
+
+        let mut query = world.query::<(
+            Entity,
+            &PointNetwork,
+            Option<&SpringNetwork>,
+            Option<&RigidBodyMode>,
+            Option<&VolumeCollection>,
+            Option<&WaterPhysics>,
+            Option<&SleepPolicy>,
+            Has<Sleeping>,
+        )>();
+
+        let mut bodies = query
+            .iter(world)
+            .map(
+                |(entity, points, springs, rigid, volumes, water, sleep_policy, sleeping)| {
+                    BodySnapshot {
+                        entity,
+                        points: points.clone(),
+                        springs: springs.cloned(),
+                        rigid: rigid.cloned(),
+                        volumes: volumes.cloned(),
+                        water: water.cloned(),
+                        sleep_policy: sleep_policy.cloned(),
+                        sleeping,
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        bodies.sort_by_key(|body| body.entity);
+
+        Self {
+            tick: tick.0,
+            bodies,
+        }
+    }
+
+    /// The state of a body in this snapshot, if it was captured.
+    pub fn body(&self, entity: Entity) -> Option<&BodySnapshot> {
+        self.bodies
+            .binary_search_by_key(&entity, |body| body.entity)
+            .ok()
+            .map(|idx| &self.bodies[idx])
+    }
+
+    /// Re-applies this snapshot onto a world, and rewinds its [PhysicsTick].
+    ///
+    /// Physics components of every captured body are replaced, or removed if
+    /// the body didn't have them when captured. Bodies whose entity no
+    /// longer exists are skipped; their number is returned.
+    ///
+    /// Bodies spawned after the snapshot was captured are left untouched.
+    pub fn restore(&self, world: &mut World) -> usize {
+        // [TODO] Map entities when restoring saved games, since entities
+        // of a previous session won't match the current ones.
+        let mut missing = 0;
+
+        for body in &self.bodies {
+            let Ok(mut entity) = world.get_entity_mut(body.entity) else {
+                missing += 1;
+                continue;
+            };
+
+            // Changing the points of a sleeping body would wake it up.
+            match entity.get_mut::<PointNetwork>() {
+                Some(mut points) if body.sleeping => {
+                    *points.bypass_change_detection() = body.points.clone();
+                }
+                _ => {
+                    entity.insert(body.points.clone());
+                }
+            }
+
+            restore_component(&mut entity, &body.springs);
+            restore_component(&mut entity, &body.rigid);
+            restore_component(&mut entity, &body.volumes);
+            restore_component(&mut entity, &body.water);
+            restore_component(&mut entity, &body.sleep_policy);
+
+            if body.sleeping {
+                entity.insert(Sleeping);
+            } else {
+                entity.remove::<Sleeping>();
+            }
+        }
+
+        world.insert_resource(PhysicsTick(self.tick));
+
+        if missing > 0 {
+            warn!(
+                "{missing} bodies of the physics snapshot at tick {} no longer exist",
+                self.tick
+            );
+        }
+
+        missing
+    }
+}
+
+/// Inserts a component if it was captured, or removes it otherwise.
+fn restore_component<T: Component + Clone>(entity: &mut EntityWorldMut, captured: &Option<T>) {
+    match captured {
+        Some(component) => {
+            entity.insert(component.clone());
+        }
+        None => {
+            entity.remove::<T>();
+        }
+    }
+}
+
+/// Recent physics snapshots, recorded after every physics tick.
+///
+/// Insert this resource to start recording.
+#[derive(Resource, Clone, Debug)]
+pub struct PhysicsSnapshotHistory {
+    /// How many snapshots are kept; older ones are dropped.
+    pub capacity: usize,
+
+    snapshots: VecDeque<PhysicsSnapshot>,
+}
+
+impl PhysicsSnapshotHistory {
+    /// An empty history, keeping up to `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The snapshot captured at a tick, if it is still kept.
+    pub fn at_tick(&self, tick: u64) -> Option<&PhysicsSnapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.tick == tick)
+    }
+
+    /// The most recent snapshot.
+    pub fn latest(&self) -> Option<&PhysicsSnapshot> {
+        self.snapshots.back()
+    }
+
+    /// Records a snapshot, dropping the oldest ones if over capacity.
+    ///
+    /// Snapshots from the same tick or later are replaced, e.g. after
+    /// rolling back.
+    pub fn push(&mut self, snapshot: PhysicsSnapshot) {
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|latest| latest.tick >= snapshot.tick)
+        {
+            self.snapshots.pop_back();
+        }
+
+        self.snapshots.push_back(snapshot);
+
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+}
+
+fn advance_tick(mut tick: ResMut<PhysicsTick>) {
+    tick.0 += 1;
+}
+
+fn record_snapshot_history(world: &mut World) {
+    let snapshot = PhysicsSnapshot::capture(world);
+    world
+        .resource_mut::<PhysicsSnapshotHistory>()
+        .push(snapshot);
+}
+
+/// Physics snapshot plugin.
+///
+/// Counts [PhysicsTick]s, and records the [PhysicsSnapshotHistory] if
+/// present.
+///
+/// Included in [super::BasicPhysicsPlugin].
+pub struct PhysicsSnapshotPlugin;
+
+impl Plugin for PhysicsSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsTick>();
+        app.add_systems(
+            FixedLast,
+            (
+                advance_tick,
+                record_snapshot_history.run_if(resource_exists::<PhysicsSnapshotHistory>),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::physics::{
+        BasicPhysicsPlugin,
+        base::{PhysPoint, PointNetwork},
+        determinism::PhysicsDeterminism,
+        forces::Gravity,
+        spring::{NormalSpring, SpringMode},
+    };
+
+    use super::*;
+
+    fn positions(app: &mut App) -> Vec<Vec3> {
+        PhysicsSnapshot::capture(app.world_mut())
+            .bodies
+            .into_iter()
+            .flat_map(|body| body.points.points.into_iter().map(|point| point.pos))
+            .collect()
+    }
+
+    #[test]
+    fn restore_replays_identically() {
+        let config = PhysicsDeterminism::new(42);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, BasicPhysicsPlugin::deterministic(config)));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / config.tick_rate,
+        )));
+        app.insert_resource(PhysicsSnapshotHistory::new(8));
+
+        let points = PointNetwork::from(
+            [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z]
+                .into_iter()
+                .map(|pos| PhysPoint::new(pos, Vec3::new(0.5, 2.0, 0.0), 1.0)),
+        );
+        let springs = points
+            .make_fully_connected_springs(SpringMode::Normal(NormalSpring { stiffness: 30.0 }));
+        app.world_mut().spawn((points, springs, Gravity::default()));
+
+        for _ in 0..20 {
+            app.update();
+        }
+
+        let snapshot = PhysicsSnapshot::capture(app.world_mut());
+        let tick = app.world().resource::<PhysicsTick>().0;
+        assert_eq!(snapshot.tick, tick);
+        assert!(tick > 0);
+
+        // The history keeps only the latest snapshots.
+        let history = app.world().resource::<PhysicsSnapshotHistory>();
+        assert_eq!(history.latest().unwrap().tick, tick);
+        assert!(history.at_tick(tick - 7).is_some());
+        assert!(history.at_tick(tick - 8).is_none());
+
+        for _ in 0..40 {
+            app.update();
+        }
+        let expected = positions(&mut app);
+
+        assert_eq!(snapshot.restore(app.world_mut()), 0);
+        assert_eq!(app.world().resource::<PhysicsTick>().0, tick);
+
+        for _ in 0..40 {
+            app.update();
+        }
+
+        assert_eq!(positions(&mut app), expected);
+    }
+}
@@ -19,11 +19,17 @@
 
 use bevy::prelude::*;
 use itertools::iproduct;
+use serde::{Deserialize, Serialize};
 
-use super::base::{PhysPoint, PointNetwork};
+use crate::common::diagnostics::AllocationScope;
+
+use super::{
+    base::{PhysPoint, PointNetwork},
+    sleep::Sleeping,
+};
 
 /// The parameters for a normal-mode spring.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct NormalSpring {
     /// The stiffness of the string.
     ///
@@ -36,7 +42,7 @@ pub struct NormalSpring {
 /// The spring mode.
 ///
 /// Determines how a spring connects two points.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum SpringMode {
     /// Instant mode - points snap to the exact target distance.
     Instant,
@@ -46,7 +52,7 @@ pub enum SpringMode {
 }
 
 /// A spring connecting two points.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Spring {
     /// The index of points A and B into the PointNetwork.
     pub points: (usize, usize),
@@ -62,7 +68,7 @@ pub struct Spring {
 ///
 /// A component that must be used to link points together, regardless of how
 /// spring-like their joints should actually be.
-#[derive(Component, Clone, Default)]
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SpringNetwork {
     /// The list of springs in this network.
     pub springs: Vec<Spring>,
@@ -114,7 +120,12 @@ impl PointNetwork {
 }
 
 /// The system responsible for computing the spring system and its forces on points.
-fn point_spring_forces(time: Res<Time>, mut query: Query<(&mut PointNetwork, &SpringNetwork)>) {
+fn point_spring_forces(
+    time: Res<Time>,
+    mut query: Query<(&mut PointNetwork, &SpringNetwork), Without<Sleeping>>,
+) {
+    let _scope = AllocationScope::new("point_spring_forces");
+
     let delta_secs = time.delta_secs();
 
     for (mut points, springs) in query.iter_mut() {
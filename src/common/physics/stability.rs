@@ -0,0 +1,373 @@
+//! # Ship stability
+//!
+//! Buoyancy on its own is applied point by point, so a hull with few volumes
+//! has a coarse center of buoyancy, and capsizes much more easily than a real
+//! ship would.
+//!
+//! [ShipStability] adds a righting moment, proportional to the metacentric
+//! height and the sine of the heel angle, standing in for the shift of the
+//! center of buoyancy that coarse volumes can't capture.
+//!
+//! The hull's orientation is tracked by a [RigidBodyMode]. Hulls without one
+//! are given one with no stiffness, which tracks their rotation without
+//! pulling their points about.
+//!
+//! The per-point buoyancy already turns a heeled hull by where the submerged
+//! volume centroid (the center of buoyancy) lies relative to the center of
+//! mass; [ShipStability::righting_factor] can add more of that same moment,
+//! but is off by default, so as not to count it twice.
+//!
+//! The moment is applied as torque through the point network.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use crate::common::diagnostics::AllocationScope;
+
+use super::{
+    base::PointNetwork,
+    forces::Gravity,
+    rigid::RigidBodyMode,
+    sleep::Sleeping,
+    volume::{VolumeCollection, VolumeInfo},
+    water::{WaterPhysics, WaveField},
+};
+
+/// Keeps a floating hull upright.
+///
+/// Requires [PointNetwork], [VolumeCollection], [WaterPhysics] and
+/// [Gravity]. The hull's orientation when this is added is taken to be
+/// upright.
+#[derive(Component, Clone, Debug)]
+pub struct ShipStability {
+    /// Scale of an extra righting moment from the center of buoyancy.
+    ///
+    /// Buoyancy already applies this moment once, point by point, so this
+    /// defaults to 0; raise it only to stiffen hulls beyond that.
+    pub righting_factor: f32,
+
+    /// Distance from the center of mass up to the metacenter, in world
+    /// units.
+    ///
+    /// Higher values make for a stiffer hull, which rights itself more
+    /// quickly.
+    pub metacentric_height: f32,
+
+    /// Damping of roll and pitch, so that the hull doesn't rock
+    /// indefinitely.
+    pub roll_damping: f32,
+}
+
+impl Default for ShipStability {
+    fn default() -> Self {
+        Self {
+            righting_factor: 0.0,
+            metacentric_height: 0.5,
+            roll_damping: 0.5,
+        }
+    }
+}
+
+/// The submerged part of a hull.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubmergedHull {
+    /// Submerged volume, in cubic world units.
+    pub volume: f32,
+
+    /// Centroid of the submerged volume.
+    pub center_of_buoyancy: Vec3,
+}
+
+impl SubmergedHull {
+    /// Measures the submerged part of a hull.
+    ///
+    /// Returns [None] if nothing is submerged.
+    pub fn measure(
+        points: &PointNetwork,
+        volumes: &VolumeCollection,
+        water: &WaterPhysics,
+        waves: &WaveField,
+        elapsed_secs: f32,
+    ) -> Option<Self> {
+        let (volume, weighted_pos) = volumes
+            .volumes
+            .iter()
+            .map(|volume| {
+                let pos = points.points[volume.point_idx].pos;
+                let water_level = water.water_level + waves.height_at(pos.xz(), elapsed_secs);
+                let submerged = volume.volume_type.volume_below(water_level - pos.y);
+                (submerged, pos * submerged)
+            })
+            .filter(|(submerged, _)| *submerged > 0.0)
+            .fold((0.0, Vec3::ZERO), |(total, sum), (submerged, pos)| {
+                (total + submerged, sum + pos)
+            });
+
+        (volume > 0.0).then(|| Self {
+            volume,
+            center_of_buoyancy: weighted_pos / volume,
+        })
+    }
+}
+
+impl ShipStability {
+    /// The righting moment on a hull, as a torque vector.
+    ///
+    /// * `center_of_mass` - The hull's center of mass.
+    /// * `submerged` - The submerged part of the hull.
+    /// * `buoyancy` - Total buoyancy force on the hull.
+    /// * `hull_up` - The hull's up direction, if known.
+    pub fn righting_moment(
+        &self,
+        center_of_mass: Vec3,
+        submerged: &SubmergedHull,
+        buoyancy: Vec3,
+        hull_up: Option<Vec3>,
+    ) -> Vec3 {
+        let mut moment =
+            (submerged.center_of_buoyancy - center_of_mass).cross(buoyancy) * self.righting_factor;
+
+        if let (Some(hull_up), Some(world_up)) = (hull_up, buoyancy.try_normalize()) {
+            // |up × world up| is the sine of the heel angle.
+            moment += hull_up.cross(world_up) * buoyancy.length() * self.metacentric_height;
+        }
+
+        moment
+    }
+}
+
+/// A floating hull, and what is needed to keep it upright.
+type StableHull = (
+    &'static mut PointNetwork,
+    &'static VolumeCollection,
+    &'static WaterPhysics,
+    &'static Gravity,
+    &'static ShipStability,
+    Option<&'static RigidBodyMode>,
+);
+
+/// Tracks the orientation of new stable hulls which aren't rigid bodies.
+fn track_hull_orientation(
+    mut commands: Commands,
+    hulls: Query<(Entity, &PointNetwork), (Added<ShipStability>, Without<RigidBodyMode>)>,
+) {
+    for (hull, points) in &hulls {
+        commands
+            .entity(hull)
+            .insert(RigidBodyMode::from_network(points, 0.0));
+    }
+}
+
+/// The system responsible for ship stability.
+fn ship_stability_system(
+    time: Res<Time>,
+    waves: Res<WaveField>,
+    mut query: Query<StableHull, Without<Sleeping>>,
+) {
+    let _scope = AllocationScope::new("ship_stability");
+
+    for (mut points, volumes, water, gravity, stability, rigid) in query.iter_mut() {
+        let Some(submerged) =
+            SubmergedHull::measure(&points, volumes, water, &waves, time.elapsed_secs())
+        else {
+            continue;
+        };
+
+        // Same as in water buoyancy; 1 m³ of water = 0.997 kg.
+        let buoyancy = -gravity.force * submerged.volume * 0.997 * water.buoyancy_factor;
+        let hull_up = rigid.map(|rigid| rigid.rotation() * Vec3::Y);
+
+        let mut torque =
+            stability.righting_moment(points.center_of_mass(), &submerged, buoyancy, hull_up);
+
+        // Damp rotation about horizontal axes, i.e. roll and pitch.
+        if let Some(world_up) = buoyancy.try_normalize() {
+            let angular_velocity = points.angular_velocity();
+            let rocking = angular_velocity - world_up * angular_velocity.dot(world_up);
+            torque -= rocking * stability.roll_damping * buoyancy.length();
+        }
+
+        if torque.length_squared() > f32::EPSILON {
+            points.apply_torque(torque, time.delta());
+        }
+    }
+}
+
+/// Ship stability plugin.
+///
+/// Included in [super::BasicPhysicsPlugin].
+pub struct ShipStabilityPlugin;
+
+impl Plugin for ShipStabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                track_hull_orientation.before(super::rigid::rigid_body_constraints),
+                ship_stability_system.after(super::water::water_buoyancy_system),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+    use crate::common::physics::{
+        BasicPhysicsPlugin,
+        base::{PhysPoint, PointNetwork},
+        volume::{SphereDef, VolumeCloneSpawner, VolumeCollection, VolumeType},
+        water::{WaterPhysics, WaveField},
+    };
+
+    use super::*;
+
+    /// A raft of floats, heeled by an angle about the Z axis, with a heavy
+    /// mast sticking up from it.
+    fn heeled_raft(angle: f32) -> (PointNetwork, VolumeCollection) {
+        let rotation = Quat::from_rotation_z(angle);
+
+        let points = PointNetwork::from(
+            [
+                (Vec3::new(-1.0, 0.0, 0.0), 1.0),
+                (Vec3::new(1.0, 0.0, 0.0), 1.0),
+                (Vec3::new(0.0, 0.0, -1.0), 1.0),
+                (Vec3::new(0.0, 0.0, 1.0), 1.0),
+                (Vec3::new(0.0, 2.0, 0.0), 2.0),
+            ]
+            .into_iter()
+            .map(|(pos, mass)| PhysPoint::new(rotation * pos, Vec3::ZERO, mass)),
+        );
+
+        let mut volumes = VolumeCollection::at_every_point(
+            &points,
+            VolumeCloneSpawner::new(VolumeType::Sphere(SphereDef::new(0.5))),
+        );
+        volumes.volumes.pop(); // The mast doesn't float.
+
+        (points, volumes)
+    }
+
+    #[test]
+    fn rights_heeled_hull() {
+        let stability = ShipStability::default();
+        let water = WaterPhysics::default();
+        let waves = WaveField::default();
+        let buoyancy = Vec3::Y * 10.0;
+
+        // Heeled towards -X, so the moment must turn it back towards +X,
+        // i.e. clockwise about Z.
+        let angle = 0.4;
+        let (points, volumes) = heeled_raft(angle);
+        let submerged = SubmergedHull::measure(&points, &volumes, &water, &waves, 0.0).unwrap();
+
+        assert!(submerged.center_of_buoyancy.x < points.center_of_mass().x);
+
+        let hull_up = Quat::from_rotation_z(angle) * Vec3::Y;
+        let moment =
+            stability.righting_moment(points.center_of_mass(), &submerged, buoyancy, Some(hull_up));
+        assert!(moment.z < 0.0, "{moment}");
+
+        // Buoyancy rights the hull from the center of buoyancy by itself, so
+        // that is only added to if asked for.
+        let moment = stability.righting_moment(points.center_of_mass(), &submerged, buoyancy, None);
+        assert!(moment.length() < 1e-5, "{moment}");
+
+        let stiffened = ShipStability {
+            righting_factor: 1.0,
+            ..default()
+        };
+        let moment = stiffened.righting_moment(points.center_of_mass(), &submerged, buoyancy, None);
+        assert!(moment.z < 0.0, "{moment}");
+
+        // Upright, nothing needs to be corrected.
+        let (points, volumes) = heeled_raft(0.0);
+        let submerged = SubmergedHull::measure(&points, &volumes, &water, &waves, 0.0).unwrap();
+        let moment =
+            stability.righting_moment(points.center_of_mass(), &submerged, buoyancy, Some(Vec3::Y));
+        assert!(moment.length() < 1e-5, "{moment}");
+    }
+
+    /// How far a raft floating for a while ends up heeled, with or without
+    /// stability.
+    fn heel_after_floating(stability: Option<ShipStability>) -> f32 {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, BasicPhysicsPlugin::default()));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+
+        // Rigid, with its upright shape at rest, and floats big enough to
+        // carry it.
+        let (upright, mut volumes) = heeled_raft(0.0);
+        for volume in &mut volumes.volumes {
+            volume.volume_type = VolumeType::Sphere(SphereDef::new(1.0));
+        }
+        let mut raft = app.world_mut().spawn((
+            heeled_raft(0.6).0,
+            RigidBodyMode::from_network(&upright, 1.0),
+            volumes,
+            WaterPhysics::default(),
+            Gravity::default(),
+        ));
+        if let Some(stability) = stability {
+            raft.insert(stability);
+        }
+        let raft = raft.id();
+
+        for _ in 0..64 * 10 {
+            app.update();
+        }
+
+        let up = app.world().get::<RigidBodyMode>(raft).unwrap().rotation() * Vec3::Y;
+        up.angle_between(Vec3::Y)
+    }
+
+    #[test]
+    fn heeled_ships_return_upright() {
+        let heel = heel_after_floating(Some(ShipStability::default()));
+        assert!(heel < 0.05, "{heel}");
+
+        // With its heavy mast, the raft capsizes on buoyancy alone.
+        let unstable = heel_after_floating(None);
+        assert!(heel < unstable, "{heel} vs {unstable}");
+    }
+
+    #[test]
+    fn hulls_without_rigid_bodies_are_tracked() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, BasicPhysicsPlugin::default()));
+
+        let (points, volumes) = heeled_raft(0.0);
+        let hull = app
+            .world_mut()
+            .spawn((
+                points.clone(),
+                volumes,
+                WaterPhysics::default(),
+                Gravity::default(),
+                ShipStability::default(),
+            ))
+            .id();
+        app.world_mut().run_schedule(FixedUpdate);
+
+        // Tracking only, so the points are left alone.
+        let rigid = app.world().get::<RigidBodyMode>(hull).unwrap();
+        assert_eq!(rigid.stiffness, 0.0);
+    }
+}
@@ -18,7 +18,10 @@
 
 use std::time::Duration;
 
-use bevy::{log::warn, math::Vec3};
+use bevy::{
+    log::warn,
+    math::{Mat3, Vec3},
+};
 
 use crate::prelude::PointNetwork;
 
@@ -38,6 +41,66 @@ impl PointNetwork {
             .sum()
     }
 
+    /// Average velocity of the points, weighted by mass.
+    ///
+    /// This is the velocity of the center of mass.
+    pub fn linear_velocity(&self) -> Vec3 {
+        let total_mass: f32 = self.points.iter().map(|point| point.mass).sum();
+        if total_mass == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        self.points
+            .iter()
+            .map(|point| point.vel * point.mass)
+            .sum::<Vec3>()
+            / total_mass
+    }
+
+    /// Angular velocity about the center of mass.
+    ///
+    /// Computed from the angular momentum and inertia tensor of the points,
+    /// so it is only meaningful for somewhat rigid networks. Returns zero if
+    /// the rotation is undefined, e.g. if there are fewer than two points.
+    pub fn angular_velocity(&self) -> Vec3 {
+        let center_of_mass = self.center_of_mass();
+        let linear_velocity = self.linear_velocity();
+
+        let mut momentum = Vec3::ZERO;
+        let mut inertia = Mat3::ZERO;
+
+        for point in &self.points {
+            let relative_pos = point.pos - center_of_mass;
+
+            momentum += relative_pos.cross(point.vel - linear_velocity) * point.mass;
+            inertia += (Mat3::from_diagonal(Vec3::splat(relative_pos.length_squared()))
+                - Mat3::from_cols(
+                    relative_pos * relative_pos.x,
+                    relative_pos * relative_pos.y,
+                    relative_pos * relative_pos.z,
+                ))
+                * point.mass;
+        }
+
+        if inertia.determinant().abs() > f32::EPSILON {
+            return inertia.inverse() * momentum;
+        }
+
+        // Degenerate (e.g. collinear points); fall back to the moment of
+        // inertia along the momentum's own axis.
+        let Some(axis) = momentum.try_normalize() else {
+            return Vec3::ZERO;
+        };
+
+        let moment_of_inertia = self.moment_of_inertia_along_axis(axis);
+
+        if moment_of_inertia > 0.0 {
+            momentum / moment_of_inertia
+        } else {
+            Vec3::ZERO
+        }
+    }
+
     /// Applies an instant rotational force (angular impulse).
     pub fn apply_angular_impulse(&mut self, angular_impulse: Vec3) {
         if angular_impulse == Vec3::ZERO {
@@ -73,4 +136,38 @@ impl PointNetwork {
     pub fn apply_torque(&mut self, torque: Vec3, delta_time: Duration) {
         self.apply_angular_impulse(torque * delta_time.as_secs_f32());
     }
+
+    /// Applies a continuous force at a world space position, spread over a
+    /// tick's duration.
+    ///
+    /// Besides pushing the whole network, a force applied away from the
+    /// center of mass turns it, like an off-center engine would.
+    pub fn apply_force_at(&mut self, at: Vec3, force: Vec3, delta_time: Duration) {
+        let torque = (at - self.center_of_mass()).cross(force);
+        self.apply_force_over_time(force, delta_time.as_secs_f32());
+        self.apply_torque_if_any(torque * delta_time.as_secs_f32());
+    }
+
+    /// Applies an instant force (impulse) at a world space position, such as
+    /// a cannon's recoil.
+    ///
+    /// Like [Self::apply_force_at], it also turns the network if applied
+    /// away from the center of mass.
+    pub fn apply_impulse_at(&mut self, at: Vec3, impulse: Vec3) {
+        let angular_impulse = (at - self.center_of_mass()).cross(impulse);
+        self.apply_instant_force(impulse);
+        self.apply_torque_if_any(angular_impulse);
+    }
+
+    /// Applies an angular impulse, unless the network cannot turn about its
+    /// axis, e.g. single points, or there is no impulse at all.
+    fn apply_torque_if_any(&mut self, angular_impulse: Vec3) {
+        let Some(axis) = angular_impulse.try_normalize() else {
+            return;
+        };
+
+        if self.moment_of_inertia_along_axis(axis) > f32::EPSILON {
+            self.apply_angular_impulse(angular_impulse);
+        }
+    }
 }
@@ -19,6 +19,7 @@ use std::ops::Range;
 use bevy::prelude::*;
 use enum_dispatch::enum_dispatch;
 use range_ext::intersect::Intersect;
+use serde::{Deserialize, Serialize};
 
 use super::base::{PhysPoint, PointNetwork};
 
@@ -32,7 +33,7 @@ pub struct AABB {
 }
 
 fn span_union(span_a: &Range<f32>, span_b: &Range<f32>) -> Range<f32> {
-    span_a.start.min(span_b.start)..span_a.end.min(span_b.end)
+    span_a.start.min(span_b.start)..span_a.end.max(span_b.end)
 }
 
 impl AABB {
@@ -153,10 +154,114 @@ pub trait VolumeInfo {
     fn surface_area(&self) -> f32;
 
     /// Returns the volume of this geometry's section under a given y-intercept.
-    fn volume_below(&self, y_intercept: f32) -> f32;
+    ///
+    /// Used for buoyancy. The default implementation is a numeric
+    /// approximation, [numeric_volume_below]; implementors should override it
+    /// with an analytic solution whenever one is practical.
+    fn volume_below(&self, y_intercept: f32) -> f32 {
+        numeric_volume_below(self, y_intercept)
+    }
 
     /// Returns the surface area of this geometry's section under a given y-intercept.
-    fn surface_area_below(&self, y_intercept: f32) -> f32;
+    ///
+    /// Used for water drag. The default implementation is a numeric
+    /// approximation, [numeric_surface_area_below]; implementors should
+    /// override it with an analytic solution whenever one is practical.
+    fn surface_area_below(&self, y_intercept: f32) -> f32 {
+        numeric_surface_area_below(self, y_intercept)
+    }
+}
+
+/// Number of samples, along each axis, used by the numeric volume
+/// approximations.
+const NUMERIC_SAMPLES: usize = 16;
+
+/// Returns the minimum corner of a volume's AABB, and the size of a cell of
+/// the sampling grid spanning it.
+fn volume_grid<V: VolumeInfo + ?Sized>(volume: &V) -> (Vec3, Vec3) {
+    let aabb = volume.aabb();
+    let min = Vec3::new(
+        aabb.spans[0].start,
+        aabb.spans[1].start,
+        aabb.spans[2].start,
+    );
+    let max = Vec3::new(aabb.spans[0].end, aabb.spans[1].end, aabb.spans[2].end);
+
+    (min, (max - min) / NUMERIC_SAMPLES as f32)
+}
+
+/// Samples a volume's SDF on a regular grid spanning its AABB.
+///
+/// Calls the callback with the position and SDF value at the center of each
+/// grid cell.
+fn sample_volume_grid<V: VolumeInfo + ?Sized>(volume: &V, mut callback: impl FnMut(Vec3, f32)) {
+    let (min, cell) = volume_grid(volume);
+
+    for x in 0..NUMERIC_SAMPLES {
+        for y in 0..NUMERIC_SAMPLES {
+            for z in 0..NUMERIC_SAMPLES {
+                let pos = min + cell * (Vec3::new(x as f32, y as f32, z as f32) + 0.5);
+                callback(pos, volume.sdf(pos));
+            }
+        }
+    }
+}
+
+/// Numerically approximates the volume of a geometry's section under a
+/// given y-intercept, by sampling its SDF.
+///
+/// This works with any [VolumeInfo], but is much slower and less precise
+/// than an analytic solution.
+pub fn numeric_volume_below<V: VolumeInfo + ?Sized>(volume: &V, y_intercept: f32) -> f32 {
+    let mut inside = 0;
+    let mut inside_below = 0;
+
+    sample_volume_grid(volume, |pos, sdf| {
+        if sdf < 0.0 {
+            inside += 1;
+
+            if pos.y < y_intercept {
+                inside_below += 1;
+            }
+        }
+    });
+
+    // The total is known exactly; only the fraction below is sampled.
+    if inside == 0 {
+        0.0
+    } else {
+        volume.volume() * inside_below as f32 / inside as f32
+    }
+}
+
+/// Numerically approximates the surface area of a geometry's section under a
+/// given y-intercept, by sampling its SDF.
+///
+/// This works with any [VolumeInfo], but is much slower and less precise
+/// than an analytic solution.
+pub fn numeric_surface_area_below<V: VolumeInfo + ?Sized>(volume: &V, y_intercept: f32) -> f32 {
+    let mut shell = 0;
+    let mut shell_below = 0;
+
+    // Count cells within half a cell of the surface.
+    let thickness = volume_grid(volume).1.max_element() / 2.0;
+
+    sample_volume_grid(volume, |pos, sdf| {
+        if sdf.abs() <= thickness {
+            shell += 1;
+
+            if pos.y < y_intercept {
+                shell_below += 1;
+            }
+        }
+    });
+
+    // The total is known exactly; only the fraction below is sampled.
+    if shell == 0 {
+        0.0
+    } else {
+        volume.surface_area() * shell_below as f32 / shell as f32
+    }
 }
 
 /// Basic information on a detected collision.
@@ -235,7 +340,7 @@ impl<V: VolumeInfo> VolumeCollision for V {
 }
 
 /// A Sphere-based volume.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct SphereDef {
     /// The radius of this sphere, centered at its origin.
     pub radius: f32,
@@ -287,7 +392,7 @@ impl VolumeInfo for SphereDef {
         } else if y_intercept >= self.radius {
             self.volume()
         } else {
-            let cap_height = -y_intercept + self.radius;
+            let cap_height = y_intercept + self.radius;
             std::f32::consts::FRAC_PI_3 * cap_height.powi(2) * (3.0 * self.radius - cap_height)
         }
     }
@@ -300,20 +405,475 @@ impl VolumeInfo for SphereDef {
         } else if y_intercept >= self.radius {
             self.surface_area()
         } else {
-            let cap_height = -y_intercept + self.radius;
+            let cap_height = y_intercept + self.radius;
             2.0 * std::f32::consts::PI * self.radius * cap_height
         }
     }
 }
 
+/// The axis along which an elongated volume, such as a [CylinderDef] or a
+/// [CapsuleDef], is oriented.
+///
+/// Volumes do not rotate with their physics point, so only the three
+/// coordinate axes are supported. To approximate a ship hull, use horizontal
+/// volumes (X or Z).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolumeAxis {
+    /// Along the X axis.
+    X,
+
+    /// Along the Y axis (upright).
+    #[default]
+    Y,
+
+    /// Along the Z axis.
+    Z,
+}
+
+impl VolumeAxis {
+    /// The unit vector of this axis.
+    pub fn unit(&self) -> Vec3 {
+        match self {
+            Self::X => Vec3::X,
+            Self::Y => Vec3::Y,
+            Self::Z => Vec3::Z,
+        }
+    }
+
+    /// Splits a point into its component along this axis, and its radial
+    /// offset from this axis.
+    fn split(&self, pos: Vec3) -> (f32, Vec3) {
+        let along = pos.dot(self.unit());
+        (along, pos - self.unit() * along)
+    }
+}
+
+/// Area and arc length of the segment of a circle lying below a horizontal
+/// line, given the height of that line above the bottom of the circle.
+fn circular_segment(radius: f32, height: f32) -> (f32, f32) {
+    let height = height.clamp(0.0, 2.0 * radius);
+    let half_angle = ((radius - height) / radius).clamp(-1.0, 1.0).acos();
+    let half_chord = (2.0 * radius * height - height * height).max(0.0).sqrt();
+
+    (
+        radius * radius * half_angle - (radius - height) * half_chord,
+        2.0 * radius * half_angle,
+    )
+}
+
+/// A cylinder-based volume.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CylinderDef {
+    /// The radius of this cylinder.
+    pub radius: f32,
+
+    /// Half of the length of this cylinder, measured along its axis.
+    pub half_length: f32,
+
+    /// The axis along which this cylinder is oriented.
+    pub axis: VolumeAxis,
+}
+
+impl CylinderDef {
+    /// Return a new CylinderDef.
+    ///
+    /// The origin is assumed to be (0,0,0), at the center of the cylinder.
+    pub fn new(radius: f32, half_length: f32, axis: VolumeAxis) -> Self {
+        Self {
+            radius,
+            half_length,
+            axis,
+        }
+    }
+}
+
+impl VolumeInfo for CylinderDef {
+    fn closest_point_to(&self, reference: Vec3) -> Vec3 {
+        let (along, radial) = self.axis.split(reference);
+
+        self.axis.unit() * along.clamp(-self.half_length, self.half_length)
+            + radial.clamp_length_max(self.radius)
+    }
+
+    fn sdf(&self, pos: Vec3) -> f32 {
+        let (along, radial) = self.axis.split(pos);
+        let dist = Vec2::new(
+            radial.length() - self.radius,
+            along.abs() - self.half_length,
+        );
+
+        dist.max_element().min(0.0) + dist.max(Vec2::ZERO).length()
+    }
+
+    fn normal(&self, pos: Vec3) -> Vec3 {
+        let (along, radial) = self.axis.split(pos);
+        let axial_normal = self.axis.unit() * along.signum();
+
+        if self.sdf(pos) > 0.0 {
+            return (pos - self.closest_point_to(pos)).normalize_or(axial_normal);
+        }
+
+        // Inside; use the normal of the nearest face.
+        if self.half_length - along.abs() < self.radius - radial.length() {
+            axial_normal
+        } else {
+            radial.normalize_or(Vec3::Y)
+        }
+    }
+
+    fn aabb(&self) -> AABB {
+        let extents =
+            Vec3::splat(self.radius) + self.axis.unit() * (self.half_length - self.radius);
+
+        AABB::new(
+            -extents.x..extents.x,
+            -extents.y..extents.y,
+            -extents.z..extents.z,
+        )
+    }
+
+    fn volume(&self) -> f32 {
+        std::f32::consts::PI * self.radius.powi(2) * 2.0 * self.half_length
+    }
+
+    fn surface_area(&self) -> f32 {
+        2.0 * std::f32::consts::PI * self.radius * (2.0 * self.half_length + self.radius)
+    }
+
+    fn volume_below(&self, y_intercept: f32) -> f32 {
+        if self.axis == VolumeAxis::Y {
+            let height = (y_intercept + self.half_length).clamp(0.0, 2.0 * self.half_length);
+            std::f32::consts::PI * self.radius.powi(2) * height
+        } else {
+            let (area, _) = circular_segment(self.radius, y_intercept + self.radius);
+            area * 2.0 * self.half_length
+        }
+    }
+
+    fn surface_area_below(&self, y_intercept: f32) -> f32 {
+        if self.axis == VolumeAxis::Y {
+            if y_intercept <= -self.half_length {
+                return 0.0;
+            }
+
+            let cap_area = std::f32::consts::PI * self.radius.powi(2);
+            let height = (y_intercept + self.half_length).min(2.0 * self.half_length);
+            let top_cap = if y_intercept >= self.half_length {
+                cap_area
+            } else {
+                0.0
+            };
+
+            cap_area + 2.0 * std::f32::consts::PI * self.radius * height + top_cap
+        } else {
+            let (area, arc) = circular_segment(self.radius, y_intercept + self.radius);
+            arc * 2.0 * self.half_length + 2.0 * area
+        }
+    }
+}
+
+/// A capsule-based volume.
+///
+/// A capsule is a cylinder capped with a hemisphere on either end; or,
+/// equivalently, every point within a given radius of a line segment.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CapsuleDef {
+    /// The radius of this capsule.
+    pub radius: f32,
+
+    /// Half of the length of this capsule's inner line segment, measured
+    /// along its axis.
+    ///
+    /// This does not include the hemispherical caps; the full length of the
+    /// capsule is `2 * (half_length + radius)`.
+    pub half_length: f32,
+
+    /// The axis along which this capsule is oriented.
+    pub axis: VolumeAxis,
+}
+
+impl CapsuleDef {
+    /// Return a new CapsuleDef.
+    ///
+    /// The origin is assumed to be (0,0,0), at the center of the capsule.
+    pub fn new(radius: f32, half_length: f32, axis: VolumeAxis) -> Self {
+        Self {
+            radius,
+            half_length,
+            axis,
+        }
+    }
+
+    /// Returns the closest point to pos on this capsule's inner line segment.
+    fn segment_point(&self, pos: Vec3) -> Vec3 {
+        let (along, _) = self.axis.split(pos);
+        self.axis.unit() * along.clamp(-self.half_length, self.half_length)
+    }
+
+    /// The sphere formed by both of this capsule's caps.
+    fn caps(&self) -> SphereDef {
+        SphereDef::new(self.radius)
+    }
+}
+
+impl VolumeInfo for CapsuleDef {
+    fn closest_point_to(&self, reference: Vec3) -> Vec3 {
+        let segment_point = self.segment_point(reference);
+        segment_point + (reference - segment_point).clamp_length_max(self.radius)
+    }
+
+    fn sdf(&self, pos: Vec3) -> f32 {
+        (pos - self.segment_point(pos)).length() - self.radius
+    }
+
+    fn normal(&self, pos: Vec3) -> Vec3 {
+        (pos - self.segment_point(pos)).normalize_or(Vec3::Y)
+    }
+
+    fn aabb(&self) -> AABB {
+        let extents = Vec3::splat(self.radius) + self.axis.unit() * self.half_length;
+
+        AABB::new(
+            -extents.x..extents.x,
+            -extents.y..extents.y,
+            -extents.z..extents.z,
+        )
+    }
+
+    fn volume(&self) -> f32 {
+        std::f32::consts::PI * self.radius.powi(2) * 2.0 * self.half_length + self.caps().volume()
+    }
+
+    fn surface_area(&self) -> f32 {
+        2.0 * std::f32::consts::PI * self.radius * 2.0 * self.half_length
+            + self.caps().surface_area()
+    }
+
+    fn volume_below(&self, y_intercept: f32) -> f32 {
+        let caps = self.caps();
+
+        if self.axis == VolumeAxis::Y {
+            // Bottom cap, cylinder, then top cap.
+            let half_caps = caps.volume() / 2.0;
+            let height = (y_intercept + self.half_length).clamp(0.0, 2.0 * self.half_length);
+
+            caps.volume_below(y_intercept + self.half_length)
+                .min(half_caps)
+                + std::f32::consts::PI * self.radius.powi(2) * height
+                + (caps.volume_below(y_intercept - self.half_length) - half_caps).max(0.0)
+        } else {
+            // Both caps are always at the same height, forming a sphere.
+            let (area, _) = circular_segment(self.radius, y_intercept + self.radius);
+            area * 2.0 * self.half_length + caps.volume_below(y_intercept)
+        }
+    }
+
+    fn surface_area_below(&self, y_intercept: f32) -> f32 {
+        let caps = self.caps();
+
+        if self.axis == VolumeAxis::Y {
+            let half_caps = caps.surface_area() / 2.0;
+            let height = (y_intercept + self.half_length).clamp(0.0, 2.0 * self.half_length);
+
+            caps.surface_area_below(y_intercept + self.half_length)
+                .min(half_caps)
+                + 2.0 * std::f32::consts::PI * self.radius * height
+                + (caps.surface_area_below(y_intercept - self.half_length) - half_caps).max(0.0)
+        } else {
+            let (_, arc) = circular_segment(self.radius, y_intercept + self.radius);
+            arc * 2.0 * self.half_length + caps.surface_area_below(y_intercept)
+        }
+    }
+}
+
+/// A box-based volume, optionally oriented.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BoxDef {
+    /// Half of the size of this box along each of its local axes.
+    pub half_extents: Vec3,
+
+    /// The orientation of this box.
+    ///
+    /// Volumes do not rotate with their physics point; this is a fixed
+    /// orientation relative to the world axes.
+    pub rotation: Quat,
+}
+
+impl BoxDef {
+    /// Return a new, axis-aligned BoxDef.
+    ///
+    /// The origin is assumed to be (0,0,0), at the center of the box.
+    pub fn new(half_extents: Vec3) -> Self {
+        Self {
+            half_extents,
+            rotation: Quat::IDENTITY,
+        }
+    }
+
+    /// Return a new, oriented BoxDef.
+    ///
+    /// The origin is assumed to be (0,0,0), at the center of the box.
+    pub fn oriented(half_extents: Vec3, rotation: Quat) -> Self {
+        Self {
+            half_extents,
+            rotation,
+        }
+    }
+
+    /// Transforms a point from world space into this box's local space.
+    fn local_point(&self, pos: Vec3) -> Vec3 {
+        self.rotation.inverse() * pos
+    }
+
+    /// How far above its center the end of each of this box's local axes is.
+    fn rises(&self) -> [f32; 3] {
+        (Mat3::from_quat(self.rotation).row(1) * self.half_extents).to_array()
+    }
+}
+
+impl VolumeInfo for BoxDef {
+    fn closest_point_to(&self, reference: Vec3) -> Vec3 {
+        self.rotation
+            * self
+                .local_point(reference)
+                .clamp(-self.half_extents, self.half_extents)
+    }
+
+    fn sdf(&self, pos: Vec3) -> f32 {
+        let dist = self.local_point(pos).abs() - self.half_extents;
+
+        dist.max(Vec3::ZERO).length() + dist.max_element().min(0.0)
+    }
+
+    fn normal(&self, pos: Vec3) -> Vec3 {
+        if self.sdf(pos) > 0.0 {
+            return (pos - self.closest_point_to(pos)).normalize_or(Vec3::Y);
+        }
+
+        // Inside; use the normal of the nearest face.
+        let local = self.local_point(pos);
+        let dist = local.abs() - self.half_extents;
+
+        let axis = if dist.x >= dist.y && dist.x >= dist.z {
+            Vec3::X * local.x.signum()
+        } else if dist.y >= dist.z {
+            Vec3::Y * local.y.signum()
+        } else {
+            Vec3::Z * local.z.signum()
+        };
+
+        self.rotation * axis
+    }
+
+    fn aabb(&self) -> AABB {
+        let rotation = Mat3::from_quat(self.rotation);
+        let extents = Vec3::new(
+            rotation.row(0).abs().dot(self.half_extents),
+            rotation.row(1).abs().dot(self.half_extents),
+            rotation.row(2).abs().dot(self.half_extents),
+        );
+
+        AABB::new(
+            -extents.x..extents.x,
+            -extents.y..extents.y,
+            -extents.z..extents.z,
+        )
+    }
+
+    fn volume(&self) -> f32 {
+        8.0 * self.half_extents.element_product()
+    }
+
+    fn surface_area(&self) -> f32 {
+        let he = self.half_extents;
+        8.0 * (he.x * he.y + he.y * he.z + he.z * he.x)
+    }
+
+    fn volume_below(&self, y_intercept: f32) -> f32 {
+        self.volume() * box_fraction_below(&self.rises(), y_intercept)
+    }
+
+    fn surface_area_below(&self, y_intercept: f32) -> f32 {
+        let he = self.half_extents.to_array();
+        let rises = self.rises();
+
+        // Each pair of opposite faces, spanning the other two axes.
+        (0..3)
+            .map(|axis| {
+                let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+                let face_area = 4.0 * he[a] * he[b];
+
+                [-rises[axis], rises[axis]]
+                    .into_iter()
+                    .map(|offset| {
+                        face_area * box_fraction_below(&[rises[a], rises[b]], y_intercept - offset)
+                    })
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+}
+
+/// The fraction of a box, or box face, centered at the origin, which is
+/// under a y-intercept.
+///
+/// `rises` is how far above the center the end of each of its axes is; the
+/// box spans each axis both ways. Any orientation works: heights within the
+/// box are a sum of uniformly distributed terms, one per axis, whose
+/// distribution function is known exactly.
+fn box_fraction_below(rises: &[f32], y_intercept: f32) -> f32 {
+    // Level axes have no effect on the fraction, but would make it unstable.
+    let largest = rises.iter().fold(0.0_f32, |max, rise| max.max(rise.abs()));
+
+    let mut sides = [0.0; 3];
+    let mut count = 0;
+    let mut height = y_intercept;
+    for rise in rises {
+        if rise.abs() > largest * 1e-3 {
+            sides[count] = 2.0 * rise.abs();
+            height += rise.abs();
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return if y_intercept > 0.0 { 1.0 } else { 0.0 };
+    }
+
+    let sides = &sides[..count];
+    let mut fraction = 0.0;
+    for subset in 0..(1_u32 << count) {
+        let excess = height
+            - sides
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| subset & (1 << idx) != 0)
+                .map(|(_, side)| side)
+                .sum::<f32>();
+        let term = excess.max(0.0).powi(count as i32);
+
+        if subset.count_ones() % 2 == 0 {
+            fraction += term;
+        } else {
+            fraction -= term;
+        }
+    }
+
+    let factorial = [1.0, 1.0, 2.0, 6.0][count];
+    (fraction / (factorial * sides.iter().product::<f32>())).clamp(0.0, 1.0)
+}
+
 /// A volume definition.
 ///
 /// All volume definitions are presumed to be at (0,0,0); see [VolumeInfo]
 /// for details on this.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[enum_dispatch(VolumeInfo)]
 pub enum VolumeType {
     Sphere(SphereDef),
+    Cylinder(CylinderDef),
+    Capsule(CapsuleDef),
+    Box(BoxDef),
 }
 
 impl Default for VolumeType {
@@ -323,20 +883,17 @@ impl Default for VolumeType {
 }
 
 /// A physics volume, attached to a physics point.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct PhysicsVolume {
     /// The physics point this volume should be attached to.
     pub point_idx: usize,
 
     /// The type of volume.
-    ///
-    /// Currently, only Spheres are implemented.
-    // [NOTE] The above line may have to be updated in the future :)
     pub volume_type: VolumeType,
 }
 
 /// ECS component with a list of physics-point-attached volumes.
-#[derive(Component, Clone, Default)]
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct VolumeCollection {
     /// The physics volumes on this collection.
     ///
@@ -461,3 +1018,112 @@ impl VolumeCollection {
             .map(|vol| (vol, &point_net.points[vol.point_idx]))
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    #[test]
+    fn aabb_union_contains_both() {
+        use super::AABB;
+        use bevy::prelude::*;
+
+        let a = AABB::new(0.0..1.0, 0.0..1.0, 0.0..1.0);
+        let b = AABB::new(2.0..5.0, -1.0..0.5, 0.5..3.0);
+        let union = a.clone().union(b.clone());
+
+        assert_eq!(union.spans, [0.0..5.0, -1.0..1.0, 0.0..3.0]);
+        assert!(union.check_point(Vec3::new(4.0, 0.0, 2.5)));
+        assert!(union.check(&a) && union.check(&b));
+    }
+
+    #[test]
+    fn numeric_below_matches_analytic() {
+        use super::{SphereDef, VolumeInfo, numeric_surface_area_below, numeric_volume_below};
+
+        let sphere = SphereDef::new(2.0);
+
+        for y_intercept in [-1.5, 0.0, 0.7] {
+            let volume = sphere.volume_below(y_intercept);
+            let area = sphere.surface_area_below(y_intercept);
+
+            assert!((numeric_volume_below(&sphere, y_intercept) - volume).abs() < volume * 0.1);
+            assert!((numeric_surface_area_below(&sphere, y_intercept) - area).abs() < area * 0.1);
+        }
+    }
+
+    #[test]
+    fn tilted_boxes_are_clipped_analytically() {
+        use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+        use super::{BoxDef, VolumeInfo, numeric_volume_below};
+        use bevy::prelude::*;
+
+        // A cube standing on an edge; its bottom is a triangular prism.
+        let cube = BoxDef::oriented(Vec3::ONE, Quat::from_rotation_z(FRAC_PI_4));
+        assert!((cube.volume_below(-SQRT_2 + 0.5) - 2.0 * 0.5 * 0.5).abs() < 1e-4);
+
+        // Any box is half under its center, and wholly under its top.
+        let tilted = BoxDef::oriented(
+            Vec3::new(2.0, 0.5, 1.0),
+            Quat::from_euler(EulerRot::XYZ, 0.3, 0.7, 0.2),
+        );
+        let top = tilted.aabb().spans[1].end;
+        assert!((tilted.volume_below(0.0) - tilted.volume() / 2.0).abs() < 1e-3);
+        assert!((tilted.surface_area_below(0.0) - tilted.surface_area() / 2.0).abs() < 1e-3);
+        assert!((tilted.volume_below(top) - tilted.volume()).abs() < 1e-3);
+        assert!((tilted.surface_area_below(top) - tilted.surface_area()).abs() < 1e-3);
+        assert!(tilted.volume_below(-top) < 1e-4);
+        assert!(tilted.surface_area_below(-top) < 1e-4);
+
+        let volume = tilted.volume_below(0.6);
+        assert!((numeric_volume_below(&tilted, 0.6) - volume).abs() < volume * 0.1);
+
+        // Upright boxes, as before.
+        let upright = BoxDef::new(Vec3::new(1.0, 2.0, 3.0));
+        assert!((upright.volume_below(1.0) - 2.0 * 3.0 * 6.0).abs() < 1e-4);
+        assert!((upright.surface_area_below(1.0) - (12.0 + 4.0 * (1.0 + 3.0) * 3.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cylinders_and_capsules_along_every_axis() {
+        use super::{CapsuleDef, CylinderDef, VolumeAxis, VolumeInfo, numeric_volume_below};
+        use bevy::prelude::*;
+
+        for axis in [VolumeAxis::X, VolumeAxis::Y, VolumeAxis::Z] {
+            let volumes: [&dyn VolumeInfo; 2] = [
+                &CylinderDef::new(1.0, 2.0, axis),
+                &CapsuleDef::new(1.0, 2.0, axis),
+            ];
+
+            for volume in volumes {
+                // Both are symmetric about their center, and fit their AABB.
+                let top = volume.aabb().spans[1].end;
+                assert!((volume.volume_below(0.0) - volume.volume() / 2.0).abs() < 1e-4);
+                assert!(
+                    (volume.surface_area_below(0.0) - volume.surface_area() / 2.0).abs() < 1e-4
+                );
+                assert!((volume.volume_below(top) - volume.volume()).abs() < 1e-4);
+                assert!((volume.surface_area_below(top) - volume.surface_area()).abs() < 1e-4);
+                assert!(volume.volume_below(-top) < 1e-4);
+                assert!(volume.surface_area_below(-top) < 1e-4);
+
+                let tip = axis.unit() * volume.aabb().spans[axis as usize].end;
+                assert!(volume.sdf(tip).abs() < 1e-5, "{axis:?}");
+                assert!(volume.sdf(Vec3::ZERO) < 0.0);
+                assert!((volume.normal(tip * 2.0) - axis.unit()).length() < 1e-5);
+
+                for y_intercept in [-0.5, 0.3] {
+                    let below = volume.volume_below(y_intercept);
+                    let numeric = numeric_volume_below(volume, y_intercept);
+                    assert!((numeric - below).abs() < below * 0.1, "{axis:?}");
+                }
+            }
+        }
+
+        // The caps make up a sphere.
+        let cylinder = CylinderDef::new(1.0, 2.0, VolumeAxis::X);
+        let capsule = CapsuleDef::new(1.0, 2.0, VolumeAxis::X);
+        let sphere = 4.0 / 3.0 * std::f32::consts::PI;
+        assert!((capsule.volume() - cylinder.volume() - sphere).abs() < 1e-4);
+        assert!((cylinder.volume() - 4.0 * std::f32::consts::PI).abs() < 1e-4);
+    }
+}
@@ -2,6 +2,17 @@
 //!
 //! Water-related forces, such as buoyancy and drag, arguably important in a
 //! naval combat game (don't quote me on that).
+//!
+//! Water may also flow, according to the [WaterCurrentField]; drag is
+//! relative to the current, so floating objects drift along with it.
+//!
+//! The water surface is not flat, either: the [WaveField] raises and lowers
+//! it, and is shared with the renderer so that what floats matches what is
+//! seen.
+//!
+//! Objects with a [WaterWake] also have how they move through the water
+//! recorded, from the velocities of their submerged points, so wakes can be
+//! drawn behind them.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -17,10 +28,14 @@
 // permitted by applicable law.  See the CNPL for details.
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{diagnostics::AllocationScope, terrain::buffer::TerrainBuffer};
 
 use super::{
     base::PointNetwork,
     forces::Gravity,
+    sleep::Sleeping,
     volume::{VolumeCollection, VolumeInfo},
 };
 
@@ -29,7 +44,7 @@ use super::{
 /// This includes both drag and buoyancy.
 ///
 /// Requires [PointNetwork] and [VolumeCollection].
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct WaterPhysics {
     /// Drag force factor.
     pub drag_factor: f32,
@@ -37,9 +52,10 @@ pub struct WaterPhysics {
     /// Buoyancy factor.
     pub buoyancy_factor: f32,
 
-    /// Y intercept of water level.
+    /// Y intercept of the calm water level.
     ///
-    /// All geometry below this point is considered submerged.
+    /// All geometry below this point, plus the [WaveField] height at it, is
+    /// considered submerged.
     pub water_level: f32,
 }
 
@@ -53,50 +69,409 @@ impl Default for WaterPhysics {
     }
 }
 
+/// How an object with [WaterPhysics] moves through the water.
+///
+/// Kept up to date by the water drag system, from the points of the object
+/// which are under water, weighted by how much of their volume's surface is.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct WaterWake {
+    /// Where the submerged points are on average, at the calm water level.
+    pub center: Vec3,
+
+    /// How fast the submerged points move on average, relative to the
+    /// current.
+    pub velocity: Vec3,
+
+    /// How far apart the submerged points are across the way the object
+    /// moves, i.e. how wide it is at the waterline.
+    pub width: f32,
+
+    /// How much of the object's surface is under water.
+    pub wetted_area: f32,
+}
+
+impl WaterWake {
+    /// Whether the object is in the water at all.
+    pub fn is_submerged(&self) -> bool {
+        self.wetted_area > 0.0
+    }
+}
+
+/// The currents of the sea.
+///
+/// A 2D vector field over the XZ plane, stored as a grid and sampled with
+/// bilinear interpolation. Positions outside the grid take the current of the
+/// nearest edge. The default field is empty, i.e. still water everywhere.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct WaterCurrentField {
+    /// World space XZ position of the first cell.
+    origin: Vec2,
+
+    /// Spacing between cells, in world space units.
+    cell_size: f32,
+
+    /// Number of cells along X.
+    width: usize,
+
+    /// Number of cells along Z.
+    height: usize,
+
+    /// Current velocity of every cell, row by row.
+    cells: Vec<Vec2>,
+}
+
+impl WaterCurrentField {
+    /// A field of still water, with cells starting at `origin`.
+    pub fn new(origin: Vec2, cell_size: f32, width: usize, height: usize) -> Self {
+        Self {
+            origin,
+            cell_size,
+            width,
+            height,
+            cells: vec![Vec2::ZERO; width * height],
+        }
+    }
+
+    /// A field with the same current everywhere.
+    pub fn uniform(current: Vec2) -> Self {
+        let mut field = Self::new(Vec2::ZERO, 1.0, 1, 1);
+        field.cells[0] = current;
+        field
+    }
+
+    /// Generates currents which flow around islands.
+    ///
+    /// `base_current` is the current in open sea. Near the shore, the part of
+    /// it which flows towards land is deflected along the coast; on land,
+    /// there is no current at all.
+    ///
+    /// * `terrain` - The terrain to flow around.
+    /// * `terrain_offset` - Translation of the terrain entity.
+    /// * `water_level` - Y intercept of the water level.
+    /// * `shore_depth` - Depth under which the deflection starts.
+    /// * `cell_size` - Spacing between cells of the resulting field.
+    pub fn from_terrain(
+        terrain: &TerrainBuffer,
+        terrain_offset: Vec3,
+        water_level: f32,
+        shore_depth: f32,
+        base_current: Vec2,
+        cell_size: f32,
+    ) -> Self {
+        let size = Vec2::new(terrain.get_real_width(), terrain.get_real_height());
+        let to_local = |at: Vec2| at - terrain_offset.xz();
+
+        Self::from_heightfield(
+            Rect::from_center_size(terrain_offset.xz(), size),
+            |at| {
+                let local = to_local(at);
+                terrain.get_height_at(local.x, local.y) + terrain_offset.y
+            },
+            |at| {
+                let local = to_local(at);
+                terrain.get_gradient_at(local.x, local.y)
+            },
+            water_level,
+            shore_depth,
+            base_current,
+            cell_size,
+        )
+    }
+
+    /// Generates currents which flow around islands, from any height field,
+    /// such as a [crate::common::terrain::chunk::TerrainChunkIndex].
+    ///
+    /// * `area` - The XZ area the field covers.
+    /// * `height_at` - Terrain height at a world space XZ position.
+    /// * `gradient_at` - Gradient of the terrain height at a world space XZ
+    ///   position.
+    ///
+    /// The other parameters are the same as in [Self::from_terrain].
+    pub fn from_heightfield(
+        area: Rect,
+        height_at: impl Fn(Vec2) -> f32,
+        gradient_at: impl Fn(Vec2) -> Vec2,
+        water_level: f32,
+        shore_depth: f32,
+        base_current: Vec2,
+        cell_size: f32,
+    ) -> Self {
+        let size = area.size();
+        let width = (size.x / cell_size).ceil() as usize + 1;
+        let height = (size.y / cell_size).ceil() as usize + 1;
+
+        let mut field = Self::new(area.min, cell_size, width, height);
+
+        for cell_y in 0..height {
+            for cell_x in 0..width {
+                let at = area.min + Vec2::new(cell_x as f32, cell_y as f32) * cell_size;
+                let depth = water_level - height_at(at);
+
+                if depth <= 0.0 {
+                    continue;
+                }
+
+                // The gradient points uphill, i.e. towards land.
+                let towards_land = gradient_at(at).normalize_or_zero();
+                let deflection = (1.0 - depth / shore_depth).clamp(0.0, 1.0);
+                let landward = base_current.dot(towards_land).max(0.0);
+
+                field.cells[cell_y * width + cell_x] =
+                    base_current - towards_land * landward * deflection;
+            }
+        }
+
+        field
+    }
+
+    /// Sets the current of a cell.
+    pub fn set(&mut self, cell_x: usize, cell_y: usize, current: Vec2) {
+        self.cells[cell_y * self.width + cell_x] = current;
+    }
+
+    /// Gets the current of a cell.
+    pub fn get(&self, cell_x: usize, cell_y: usize) -> Vec2 {
+        self.cells[cell_y.min(self.height - 1) * self.width + cell_x.min(self.width - 1)]
+    }
+
+    /// Samples the current at a world space XZ position.
+    pub fn sample(&self, at: Vec2) -> Vec2 {
+        if self.cells.is_empty() {
+            return Vec2::ZERO;
+        }
+
+        let mapped = ((at - self.origin) / self.cell_size).max(Vec2::ZERO);
+        let (cell_x, cell_y) = (mapped.x.floor() as usize, mapped.y.floor() as usize);
+        let frac = mapped.fract();
+
+        let north = self
+            .get(cell_x, cell_y)
+            .lerp(self.get(cell_x + 1, cell_y), frac.x);
+        let south = self
+            .get(cell_x, cell_y + 1)
+            .lerp(self.get(cell_x + 1, cell_y + 1), frac.x);
+
+        north.lerp(south, frac.y)
+    }
+
+    /// Samples the current at a world space position, as a 3D velocity.
+    pub fn velocity_at(&self, pos: Vec3) -> Vec3 {
+        let current = self.sample(pos.xz());
+        Vec3::new(current.x, 0.0, current.y)
+    }
+}
+
+/// A single sine wave.
+#[derive(Clone, Debug)]
+pub struct Wave {
+    /// Direction the wave travels towards, on the XZ plane.
+    pub direction: Vec2,
+
+    /// Height of the wave crests above the calm water level.
+    pub amplitude: f32,
+
+    /// Distance between two wave crests.
+    pub wavelength: f32,
+
+    /// How fast the wave crests travel.
+    pub speed: f32,
+
+    /// Phase offset, in radians.
+    pub phase: f32,
+}
+
+impl Wave {
+    pub fn new(direction: Vec2, amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        Self {
+            direction,
+            amplitude,
+            wavelength,
+            speed,
+            phase: 0.0,
+        }
+    }
+
+    /// The wave's angle at a position and time, and its gradient factor.
+    fn angle(&self, at: Vec2, elapsed_secs: f32) -> (f32, Vec2) {
+        let wavenumber = std::f32::consts::TAU / self.wavelength;
+        let direction = self.direction.normalize_or_zero();
+        let angle = wavenumber * (direction.dot(at) - self.speed * elapsed_secs) + self.phase;
+
+        (angle, direction * wavenumber)
+    }
+}
+
+/// The waves of the sea.
+///
+/// A sum of sine waves, offsetting the height of the water surface from the
+/// calm water level. The default field has no waves at all.
+///
+/// Both physics and rendering sample this, so they stay consistent.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct WaveField {
+    pub waves: Vec<Wave>,
+}
+
+impl WaveField {
+    /// A few waves roughly following the wind, for a given overall height.
+    pub fn choppy(wind_direction: Vec2, height: f32) -> Self {
+        let direction = wind_direction.normalize_or(Vec2::X);
+
+        Self {
+            waves: vec![
+                Wave::new(direction, height * 0.5, 40.0, 6.0),
+                Wave {
+                    phase: 1.3,
+                    ..Wave::new(
+                        Vec2::from_angle(0.4).rotate(direction),
+                        height * 0.3,
+                        23.0,
+                        4.5,
+                    )
+                },
+                Wave {
+                    phase: 4.1,
+                    ..Wave::new(
+                        Vec2::from_angle(-0.7).rotate(direction),
+                        height * 0.2,
+                        11.0,
+                        3.0,
+                    )
+                },
+            ],
+        }
+    }
+
+    /// Height of the water surface above the calm water level.
+    pub fn height_at(&self, at: Vec2, elapsed_secs: f32) -> f32 {
+        self.waves
+            .iter()
+            .map(|wave| wave.amplitude * wave.angle(at, elapsed_secs).0.sin())
+            .sum()
+    }
+
+    /// Gradient of the water surface height, over X and Z.
+    pub fn gradient_at(&self, at: Vec2, elapsed_secs: f32) -> Vec2 {
+        self.waves
+            .iter()
+            .map(|wave| {
+                let (angle, factor) = wave.angle(at, elapsed_secs);
+                factor * wave.amplitude * angle.cos()
+            })
+            .sum()
+    }
+
+    /// Normal of the water surface.
+    pub fn normal_at(&self, at: Vec2, elapsed_secs: f32) -> Vec3 {
+        let gradient = self.gradient_at(at, elapsed_secs);
+        Vec3::new(-gradient.x, 1.0, -gradient.y).normalize()
+    }
+}
+
+/// Marks a water surface mesh, to be displaced by the [WaveField] when
+/// rendered.
+///
+/// The mesh should be a subdivided plane on the XZ plane, and its entity
+/// should not be rotated.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct WaterSurface;
+
 /// The system responsible for water drag in the physics system.
+///
+/// Drag is relative to the [WaterCurrentField].
 fn water_drag_system(
     time: Res<Time>,
-    mut query: Query<(&mut PointNetwork, &VolumeCollection, &WaterPhysics)>,
+    currents: Res<WaterCurrentField>,
+    waves: Res<WaveField>,
+    mut query: Query<
+        (
+            &mut PointNetwork,
+            &VolumeCollection,
+            &WaterPhysics,
+            Option<&mut WaterWake>,
+        ),
+        Without<Sleeping>,
+    >,
 ) {
-    for (mut points, volumes, water_physics) in query.iter_mut() {
+    let _scope = AllocationScope::new("water_drag");
+
+    for (mut points, volumes, water_physics, wake) in query.iter_mut() {
+        // Width is measured across the way the object last moved.
+        let across = wake
+            .as_ref()
+            .and_then(|wake| Vec3::Y.cross(wake.velocity).try_normalize())
+            .unwrap_or(Vec3::X);
+        let mut wetted_area = 0.0;
+        let mut center = Vec3::ZERO;
+        let mut velocity = Vec3::ZERO;
+        let mut span = (f32::INFINITY, f32::NEG_INFINITY);
+
         for volume in &volumes.volumes {
             let point = &mut points.points[volume.point_idx];
 
             // [NOTE] Water level is fixed to the Y axis because of the
             // geometry API only requiring volume_below and surface_below.
+            let water_level =
+                water_physics.water_level + waves.height_at(point.pos.xz(), time.elapsed_secs());
             let water_area = volume
                 .volume_type
-                .surface_area_below(water_physics.water_level - point.pos.y);
+                .surface_area_below(water_level - point.pos.y);
 
             if water_area <= 0.0 {
                 continue;
             }
 
-            let drag = -point.vel * water_area * water_physics.drag_factor;
+            let relative_vel = point.vel - currents.velocity_at(point.pos);
+            let drag = -relative_vel * water_area * water_physics.drag_factor;
             point.apply_force_over_time(drag, time.delta_secs());
+
+            wetted_area += water_area;
+            center += point.pos * water_area;
+            velocity += relative_vel * water_area;
+            let offset = point.pos.dot(across);
+            span = (span.0.min(offset), span.1.max(offset));
+        }
+
+        if let Some(mut wake) = wake {
+            *wake = if wetted_area > 0.0 {
+                WaterWake {
+                    center: (center / wetted_area).with_y(water_physics.water_level),
+                    velocity: velocity / wetted_area,
+                    width: span.1 - span.0,
+                    wetted_area,
+                }
+            } else {
+                WaterWake::default()
+            };
         }
     }
 }
 
 /// The system responsible for buoyancy in the physics system.
-fn water_buoyancy_system(
+pub(super) fn water_buoyancy_system(
     time: Res<Time>,
-    mut query: Query<(
-        &mut PointNetwork,
-        &VolumeCollection,
-        &WaterPhysics,
-        &Gravity,
-    )>,
+    waves: Res<WaveField>,
+    mut query: Query<
+        (
+            &mut PointNetwork,
+            &VolumeCollection,
+            &WaterPhysics,
+            &Gravity,
+        ),
+        Without<Sleeping>,
+    >,
 ) {
+    let _scope = AllocationScope::new("water_buoyancy");
+
     for (mut points, volumes, water_physics, gravity) in query.iter_mut() {
         for volume in &volumes.volumes {
             let point = &mut points.points[volume.point_idx];
 
             // [NOTE] Water level is fixed to the Y axis because of the
             // geometry API only requiring volume_below and surface_below.
-            let water_vol = volume
-                .volume_type
-                .volume_below(water_physics.water_level - point.pos.y);
+            let water_level =
+                water_physics.water_level + waves.height_at(point.pos.xz(), time.elapsed_secs());
+            let water_vol = volume.volume_type.volume_below(water_level - point.pos.y);
 
             if water_vol <= 0.0 {
                 continue;
@@ -115,6 +490,8 @@ pub struct WaterPhysicsPlugin;
 
 impl Plugin for WaterPhysicsPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<WaterCurrentField>();
+        app.init_resource::<WaveField>();
         app.add_systems(FixedUpdate, (water_drag_system, water_buoyancy_system));
     }
 }
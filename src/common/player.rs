@@ -0,0 +1,151 @@
+//! # Player ship control
+//!
+//! The ship controlled by the local player has a [PlayerControlled]
+//! component, along with a [PlayerInput], which holds what the player is
+//! asking the ship to do. The client fills it in from the keyboard and mouse
+//! (see [crate::app::controls]), but it can be set by anything, e.g. by a
+//! server from a networked player's inputs.
+//!
+//! Every tick, the player's input is dispatched to the ship's parts as
+//! `"thrust"`, `"steer"` and `"fire_weapon"`
+//! [PartAction](super::construct::action::PartAction)s, the same way as NPC
+//! ships' (see [super::ai]).
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::construct::action::{FireWeaponCommand, SteerCommand, ThrustCommand, dispatch_action};
+
+/// Marks the ship controlled by the local player.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[require(PlayerInput)]
+pub struct PlayerControlled;
+
+/// What the player is asking their ship to do.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct PlayerInput {
+    /// Between -1.0 (full reverse) and 1.0 (full ahead); see
+    /// [ThrustCommand].
+    pub throttle: f32,
+
+    /// Between -1.0 (to starboard) and 1.0 (to port); see [SteerCommand].
+    pub rudder: f32,
+
+    /// Where the player is aiming, in world space, if anywhere.
+    pub aim_point: Option<Vec3>,
+
+    /// Whether the player is firing, at the aim point.
+    pub firing: bool,
+}
+
+fn dispatch_player_actions(
+    mut commands: Commands,
+    players: Query<(Entity, &PlayerInput), With<PlayerControlled>>,
+) {
+    for (entity, input) in &players {
+        dispatch_action(
+            &mut commands,
+            entity,
+            "thrust".into(),
+            vec![],
+            ThrustCommand {
+                throttle: input.throttle.clamp(-1.0, 1.0),
+            },
+        );
+        dispatch_action(
+            &mut commands,
+            entity,
+            "steer".into(),
+            vec![],
+            SteerCommand {
+                rudder: input.rudder.clamp(-1.0, 1.0),
+            },
+        );
+
+        if let Some(aim_point) = input.aim_point.filter(|_| input.firing) {
+            dispatch_action(
+                &mut commands,
+                entity,
+                "fire_weapon".into(),
+                vec![],
+                FireWeaponCommand {
+                    aim_point,
+                    ammo: vec![],
+                },
+            );
+        }
+    }
+}
+
+/// Player ship control plugin.
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, dispatch_player_actions);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::construct::action::{PartActionData, PartActionDispatchRequest};
+
+    use super::*;
+
+    #[test]
+    fn player_input_is_dispatched() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PlayerPlugin));
+        app.add_event::<PartActionDispatchRequest>();
+
+        let ship = app.world_mut().spawn(PlayerControlled).id();
+        app.update();
+
+        let tags = |app: &App| {
+            app.world()
+                .resource::<Events<PartActionDispatchRequest>>()
+                .iter_current_update_events()
+                .map(|request| {
+                    assert_eq!(request.construct_ref, ship);
+                    request.action.action_tag.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Weapons only fire when asked to, with somewhere to aim at.
+        assert_eq!(tags(&app), ["thrust", "steer"]);
+        app.world_mut()
+            .resource_mut::<Events<PartActionDispatchRequest>>()
+            .clear();
+
+        *app.world_mut().get_mut::<PlayerInput>(ship).unwrap() = PlayerInput {
+            throttle: 2.0,
+            rudder: 0.5,
+            aim_point: Some(Vec3::new(10.0, 0.0, 5.0)),
+            firing: true,
+        };
+        app.update();
+        assert_eq!(tags(&app), ["thrust", "steer", "fire_weapon"]);
+
+        let events = app.world().resource::<Events<PartActionDispatchRequest>>();
+        let thrust = events.iter_current_update_events().next().unwrap();
+        let PartActionData::Thrust(thrust) = &thrust.action.data else {
+            panic!("thrust action without thrust data: {:?}", thrust.action);
+        };
+        assert_eq!(thrust.throttle, 1.0);
+    }
+}
@@ -0,0 +1,570 @@
+//! # Island props
+//!
+//! Props are the static objects strewn about an island: trees, boulders,
+//! supply crates, and the defenses guarding it, such as watchtowers and
+//! cannon towers.
+//!
+//! Every kind of prop is described by a [PropDef], in the [PropCatalog]
+//! resource. When an overworld scene is set up, a [PropSpawner] picks where
+//! each kind of prop goes, following its [PlacementRules], and spawns them
+//! under the scene tree.
+//!
+//! How many defensive props an island gets is limited by its
+//! [OverworldSceneParams::prop_defense]; see [PropDefense::cost].
+//!
+//! [NOTE] Props are spawned with a [Prop] component, and a [PropDefense] if
+//! they are defensive; the renderer gives them a mesh, by [PropDef::mesh].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    f32::consts::TAU,
+    ops::{Range, RangeInclusive},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    physics::volume::{BoxDef, CylinderDef, SphereDef, VolumeAxis, VolumeType},
+    scene::{biome::IslandBiome, init::OverworldSceneParams},
+    terrain::biome::TerrainBiome,
+};
+
+/// An entry of a prop's loot table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LootEntry {
+    /// Name of the item dropped.
+    pub item: String,
+
+    /// Chance of the item being dropped, between 0.0 and 1.0.
+    pub chance: f32,
+
+    /// How many of the item are dropped, if any.
+    pub amount: RangeInclusive<u32>,
+}
+
+impl LootEntry {
+    pub fn new(item: impl Into<String>, chance: f32, amount: RangeInclusive<u32>) -> Self {
+        Self {
+            item: item.into(),
+            chance,
+            amount,
+        }
+    }
+}
+
+/// What a defensive prop does about intruders.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DefensiveBehavior {
+    /// Spots ships within range, alerting the island's defenses.
+    Lookout { sight_range: f32 },
+
+    /// Fires at ships within range.
+    Turret {
+        range: f32,
+        power: f32,
+        fire_interval: Duration,
+    },
+}
+
+/// The defenses of a prop.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PropDefense {
+    pub behavior: DefensiveBehavior,
+
+    /// How much of the island's
+    /// [OverworldSceneParams::prop_defense] each prop of this kind takes up.
+    pub cost: u8,
+}
+
+/// Where a kind of prop can be placed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlacementRules {
+    /// Terrain biomes the prop can be placed on; any if empty.
+    pub biomes: Vec<TerrainBiome>,
+
+    /// Island biomes the prop can be found in; any if empty.
+    pub island_biomes: Vec<IslandBiome>,
+
+    /// Steepest slope the prop can be placed on, as the length of the
+    /// terrain's height gradient.
+    pub max_slope: f32,
+
+    /// Heights above sea level the prop can be placed at.
+    pub heights: Range<f32>,
+
+    /// Least distance to other props of the same kind.
+    ///
+    /// Props of different kinds keep the lesser of both their spacings.
+    pub min_spacing: f32,
+
+    /// Greatest distance to a river or lagoon, if the prop must be near one.
+    pub max_waterway_distance: Option<f32>,
+
+    /// How many of the prop to place, at most.
+    pub count: u32,
+}
+
+impl Default for PlacementRules {
+    fn default() -> Self {
+        Self {
+            biomes: vec![],
+            island_biomes: vec![],
+            max_slope: 0.5,
+            heights: 0.5..60.0,
+            min_spacing: 6.0,
+            max_waterway_distance: None,
+            count: 10,
+        }
+    }
+}
+
+/// A kind of prop.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PropDef {
+    /// Name of the prop.
+    pub name: String,
+
+    /// Name of the prop's mesh, which the renderer looks up.
+    pub mesh: String,
+
+    /// The prop's collision volume, relative to its base.
+    pub volume: VolumeType,
+
+    /// What the prop drops when looted or destroyed.
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+
+    /// The prop's defenses, if it is defensive.
+    #[serde(default)]
+    pub defense: Option<PropDefense>,
+
+    /// Where the prop can be placed.
+    #[serde(default)]
+    pub placement: PlacementRules,
+}
+
+/// Every kind of prop which can be spawned.
+#[derive(Resource, Clone, Debug)]
+pub struct PropCatalog {
+    pub defs: Vec<PropDef>,
+}
+
+impl Default for PropCatalog {
+    fn default() -> Self {
+        Self {
+            defs: vec![
+                PropDef {
+                    name: "Palm tree".into(),
+                    mesh: "palm_tree".into(),
+                    volume: VolumeType::Cylinder(CylinderDef::new(0.4, 4.0, VolumeAxis::Y)),
+                    loot: vec![],
+                    defense: None,
+                    placement: PlacementRules {
+                        biomes: vec![TerrainBiome::Beach, TerrainBiome::Grassland],
+                        island_biomes: vec![IslandBiome::Tropical],
+                        heights: 0.5..15.0,
+                        count: 40,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Pine tree".into(),
+                    mesh: "pine_tree".into(),
+                    volume: VolumeType::Cylinder(CylinderDef::new(0.5, 5.0, VolumeAxis::Y)),
+                    loot: vec![],
+                    defense: None,
+                    placement: PlacementRules {
+                        biomes: vec![TerrainBiome::Forest, TerrainBiome::Grassland],
+                        island_biomes: vec![IslandBiome::RockyNorth],
+                        max_slope: 0.7,
+                        count: 40,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Boulder".into(),
+                    mesh: "boulder".into(),
+                    volume: VolumeType::Sphere(SphereDef::new(2.0)),
+                    loot: vec![],
+                    defense: None,
+                    placement: PlacementRules {
+                        biomes: vec![
+                            TerrainBiome::Rock,
+                            TerrainBiome::Grassland,
+                            TerrainBiome::Volcanic,
+                        ],
+                        max_slope: 1.5,
+                        heights: 0.0..80.0,
+                        min_spacing: 8.0,
+                        count: 20,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Reed bed".into(),
+                    mesh: "reeds".into(),
+                    volume: VolumeType::Sphere(SphereDef::new(1.0)),
+                    loot: vec![],
+                    defense: None,
+                    placement: PlacementRules {
+                        max_slope: 0.3,
+                        heights: 0.0..20.0,
+                        min_spacing: 4.0,
+                        max_waterway_distance: Some(4.0),
+                        count: 15,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Supply crate".into(),
+                    mesh: "crate".into(),
+                    volume: VolumeType::Box(BoxDef::new(Vec3::splat(0.6))),
+                    loot: vec![
+                        LootEntry::new("Food", 0.8, 1..=4),
+                        LootEntry::new("Coal", 0.4, 2..=10),
+                        LootEntry::new("Cannonball", 0.5, 5..=20),
+                    ],
+                    defense: None,
+                    placement: PlacementRules {
+                        biomes: vec![TerrainBiome::Beach, TerrainBiome::Grassland],
+                        max_slope: 0.3,
+                        heights: 0.2..8.0,
+                        count: 6,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Watchtower".into(),
+                    mesh: "watchtower".into(),
+                    volume: VolumeType::Box(BoxDef::new(Vec3::new(2.0, 6.0, 2.0))),
+                    loot: vec![LootEntry::new("Food", 0.5, 1..=2)],
+                    defense: Some(PropDefense {
+                        behavior: DefensiveBehavior::Lookout { sight_range: 150.0 },
+                        cost: 2,
+                    }),
+                    placement: PlacementRules {
+                        biomes: vec![
+                            TerrainBiome::Grassland,
+                            TerrainBiome::Forest,
+                            TerrainBiome::Rock,
+                        ],
+                        max_slope: 0.4,
+                        heights: 10.0..60.0,
+                        min_spacing: 40.0,
+                        count: 4,
+                        ..default()
+                    },
+                },
+                PropDef {
+                    name: "Cannon tower".into(),
+                    mesh: "cannon_tower".into(),
+                    volume: VolumeType::Cylinder(CylinderDef::new(2.5, 4.0, VolumeAxis::Y)),
+                    loot: vec![LootEntry::new("Cannonball", 0.9, 10..=30)],
+                    defense: Some(PropDefense {
+                        behavior: DefensiveBehavior::Turret {
+                            range: 90.0,
+                            power: 40.0,
+                            fire_interval: Duration::from_secs(4),
+                        },
+                        cost: 3,
+                    }),
+                    placement: PlacementRules {
+                        max_slope: 0.4,
+                        heights: 5.0..40.0,
+                        min_spacing: 30.0,
+                        count: 8,
+                        ..default()
+                    },
+                },
+            ],
+        }
+    }
+}
+
+/// A spot of terrain where a prop could be placed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacementSite {
+    /// World space position of the terrain's surface.
+    pub position: Vec3,
+
+    /// Length of the terrain's height gradient.
+    pub slope: f32,
+
+    /// The terrain biome.
+    pub biome: TerrainBiome,
+}
+
+/// Where to place a prop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropPlacement {
+    /// Index of the prop's [PropDef] in the [PropCatalog].
+    pub def: usize,
+
+    /// World space position of the prop's base.
+    pub position: Vec3,
+
+    /// Rotation of the prop around the Y axis, in radians.
+    pub yaw: f32,
+}
+
+/// A prop in the scene.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prop {
+    /// Index of the prop's [PropDef] in the [PropCatalog].
+    pub def: usize,
+}
+
+/// Places props on an island.
+#[derive(Clone, Debug)]
+pub struct PropSpawner {
+    /// The area to place props in, on the XZ plane.
+    pub area: Rect,
+
+    /// Y intercept of the water level.
+    pub water_level: f32,
+
+    /// The biome of the island.
+    pub island_biome: IslandBiome,
+
+    /// How much defense to place; see [PropDefense::cost].
+    pub defense: u8,
+
+    /// World space XZ points along the island's rivers and lagoons.
+    pub waterways: Vec<Vec2>,
+
+    /// How many spots to try, per prop to be placed.
+    pub attempts: u32,
+}
+
+impl PropSpawner {
+    /// Makes a prop spawner for an island generated with the given
+    /// parameters.
+    pub fn new(area: Rect, water_level: f32, params: &OverworldSceneParams) -> Self {
+        Self {
+            area,
+            water_level,
+            island_biome: params.biome,
+            defense: params.prop_defense,
+            waterways: vec![],
+            attempts: 20,
+        }
+    }
+
+    /// Sets the points along the island's rivers and lagoons.
+    pub fn with_waterways(mut self, waterways: impl IntoIterator<Item = Vec2>) -> Self {
+        self.waterways = waterways.into_iter().collect();
+        self
+    }
+
+    /// Whether a prop can be placed at a site, regardless of other props.
+    pub fn accepts(&self, rules: &PlacementRules, site: &PlacementSite) -> bool {
+        let height = site.position.y - self.water_level;
+
+        (rules.biomes.is_empty() || rules.biomes.contains(&site.biome))
+            && site.slope <= rules.max_slope
+            && rules.heights.contains(&height)
+            && rules.max_waterway_distance.is_none_or(|max| {
+                self.waterways
+                    .iter()
+                    .any(|point| point.distance(site.position.xz()) <= max)
+            })
+    }
+
+    /// Picks where to place props.
+    ///
+    /// * `catalog` - The kinds of props to place, in order.
+    /// * `site_at` - The site at a world space XZ position.
+    pub fn place<R: Rng + ?Sized>(
+        &self,
+        catalog: &PropCatalog,
+        mut site_at: impl FnMut(Vec2) -> PlacementSite,
+        rng: &mut R,
+    ) -> Vec<PropPlacement> {
+        let mut placements: Vec<PropPlacement> = vec![];
+        let mut defense = self.defense as u32;
+
+        for (def_idx, def) in catalog.defs.iter().enumerate() {
+            let rules = &def.placement;
+
+            if !rules.island_biomes.is_empty() && !rules.island_biomes.contains(&self.island_biome)
+            {
+                continue;
+            }
+
+            let count = match &def.defense {
+                Some(prop_defense) => rules.count.min(defense / (prop_defense.cost as u32).max(1)),
+                None => rules.count,
+            };
+
+            let mut placed = 0;
+
+            for _ in 0..count * self.attempts {
+                if placed == count {
+                    break;
+                }
+
+                let at = Vec2::new(
+                    rng.random_range(self.area.min.x..self.area.max.x),
+                    rng.random_range(self.area.min.y..self.area.max.y),
+                );
+                let site = site_at(at);
+
+                if !self.accepts(rules, &site)
+                    || placements.iter().any(|other| {
+                        let other_spacing = catalog.defs[other.def].placement.min_spacing;
+                        let spacing = if other.def == def_idx {
+                            rules.min_spacing
+                        } else {
+                            rules.min_spacing.min(other_spacing)
+                        };
+                        other.position.xz().distance(at) < spacing
+                    })
+                {
+                    continue;
+                }
+
+                placements.push(PropPlacement {
+                    def: def_idx,
+                    position: site.position,
+                    yaw: rng.random_range(0.0..TAU),
+                });
+                placed += 1;
+            }
+
+            if let Some(prop_defense) = &def.defense {
+                defense -= placed * prop_defense.cost as u32;
+            }
+        }
+
+        placements
+    }
+
+    /// Spawns placed props under the scene tree.
+    pub fn spawn(
+        placements: &[PropPlacement],
+        catalog: &PropCatalog,
+        scene_tree: Entity,
+        commands: &mut Commands,
+    ) {
+        for placement in placements {
+            let mut prop = commands.spawn((
+                Prop { def: placement.def },
+                Transform::from_translation(placement.position)
+                    .with_rotation(Quat::from_rotation_y(placement.yaw)),
+                Visibility::Visible,
+            ));
+
+            if let Some(defense) = &catalog.defs[placement.def].defense {
+                prop.insert(defense.clone());
+            }
+
+            let prop = prop.id();
+            commands.entity(scene_tree).add_child(prop);
+        }
+    }
+}
+
+/// Prop catalog plugin.
+pub struct PropPlugin;
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PropCatalog>();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    /// A round island with a peak 60 units above sea level, and a river
+    /// running down its east side.
+    fn site_at(at: Vec2) -> PlacementSite {
+        let height = 60.0 - at.length() * 0.6;
+
+        PlacementSite {
+            position: at.extend(height).xzy(),
+            slope: 0.3,
+            biome: if height < 4.0 {
+                TerrainBiome::Beach
+            } else {
+                TerrainBiome::Grassland
+            },
+        }
+    }
+
+    #[test]
+    fn props_follow_placement_rules() {
+        let catalog = PropCatalog::default();
+        let params = OverworldSceneParams {
+            prop_defense: 7,
+            ..default()
+        };
+        let spawner = PropSpawner::new(Rect::new(-120.0, -120.0, 120.0, 120.0), 0.0, &params)
+            .with_waterways((0..40).map(|x| Vec2::new(x as f32 * 2.5, 0.0)));
+
+        let placements = spawner.place(&catalog, site_at, &mut StdRng::seed_from_u64(7));
+        assert!(!placements.is_empty());
+
+        let defense = placements
+            .iter()
+            .filter_map(|placement| catalog.defs[placement.def].defense.as_ref())
+            .map(|defense| defense.cost as u32)
+            .sum::<u32>();
+        assert!(defense > 0 && defense <= 7, "{defense}");
+
+        for (idx, placement) in placements.iter().enumerate() {
+            let rules = &catalog.defs[placement.def].placement;
+            let site = site_at(placement.position.xz());
+
+            assert!(spawner.accepts(rules, &site));
+            assert!(rules.island_biomes.is_empty() || rules.island_biomes.contains(&params.biome));
+
+            for other in placements[..idx]
+                .iter()
+                .filter(|other| other.def == placement.def)
+            {
+                assert!(other.position.xz().distance(placement.position.xz()) >= rules.min_spacing);
+            }
+        }
+
+        // Reeds only grow along the river.
+        assert!(
+            placements
+                .iter()
+                .filter(|placement| catalog.defs[placement.def].name == "Reed bed")
+                .all(|placement| placement.position.x > -4.0 && placement.position.z.abs() <= 4.0)
+        );
+
+        // Undefended islands get no defensive props.
+        let undefended = PropSpawner {
+            defense: 0,
+            ..spawner
+        };
+        assert!(
+            undefended
+                .place(&catalog, site_at, &mut StdRng::seed_from_u64(7))
+                .iter()
+                .all(|placement| catalog.defs[placement.def].defense.is_none())
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! # Save migrations
+//!
+//! Saves are tagged with a schema version. Whenever the schema changes, the
+//! version is bumped, and a migration from the previous version is
+//! registered in [SaveMigrations]. Loading an older save then applies every
+//! migration between its version and the current one, in sequence.
+//!
+//! Migrations describe what they did in a [MigrationReport], including any
+//! data that could not be carried over, so that players can be told about it
+//! before their save is overwritten.
+//!
+//! Migrations are generic over the save document type. Campaign saves, and
+//! their on-disk format, are in [campaign].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{collections::BTreeMap, fmt};
+
+pub mod campaign; // Campaign state, and reading and writing save files
+
+/// A save schema version.
+pub type SaveVersion = u32;
+
+/// What happened while migrating a save.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The version of the save before migrating.
+    pub from: SaveVersion,
+
+    /// The version of the save after migrating.
+    pub to: SaveVersion,
+
+    /// Changes made to the save, in order.
+    pub changes: Vec<String>,
+
+    /// Data which could not be carried over, in order.
+    pub losses: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Records a change made to the save.
+    pub fn change(&mut self, description: impl Into<String>) {
+        self.changes.push(description.into());
+    }
+
+    /// Records data which could not be carried over.
+    pub fn loss(&mut self, description: impl Into<String>) {
+        self.losses.push(description.into());
+    }
+
+    /// Whether no data was lost.
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+/// Why a save could not be migrated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The save is from a newer version of the game.
+    TooNew {
+        version: SaveVersion,
+        current: SaveVersion,
+    },
+
+    /// No migration is registered from this version.
+    Unsupported { version: SaveVersion },
+
+    /// A migration failed.
+    Failed {
+        version: SaveVersion,
+        reason: String,
+    },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooNew { version, current } => write!(
+                f,
+                "save version {version} is newer than the current version {current}"
+            ),
+            Self::Unsupported { version } => {
+                write!(f, "saves of version {version} can no longer be migrated")
+            }
+            Self::Failed { version, reason } => {
+                write!(f, "migration from save version {version} failed: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Migrates a save document by one version.
+///
+/// Returns a reason if the document could not be migrated.
+pub type MigrationFn<D> = fn(&mut D, &mut MigrationReport) -> Result<(), String>;
+
+/// Every registered save migration.
+pub struct SaveMigrations<D> {
+    current: SaveVersion,
+    migrations: BTreeMap<SaveVersion, MigrationFn<D>>,
+}
+
+impl<D> SaveMigrations<D> {
+    /// A migration registry for saves whose current version is `current`.
+    pub fn new(current: SaveVersion) -> Self {
+        Self {
+            current,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// The current save version.
+    pub fn current(&self) -> SaveVersion {
+        self.current
+    }
+
+    /// Registers the migration from `from` to the next version.
+    ///
+    /// # Panics
+    ///
+    /// If `from` is not older than the current version, or a migration is
+    /// already registered from it.
+    pub fn register(&mut self, from: SaveVersion, migration: MigrationFn<D>) -> &mut Self {
+        assert!(
+            from < self.current,
+            "cannot migrate from save version {from}, which is not older than {}",
+            self.current
+        );
+        assert!(
+            self.migrations.insert(from, migration).is_none(),
+            "a migration from save version {from} is already registered"
+        );
+        self
+    }
+
+    /// The oldest save version which can still be migrated.
+    pub fn oldest_supported(&self) -> SaveVersion {
+        (0..self.current)
+            .rev()
+            .take_while(|version| self.migrations.contains_key(version))
+            .last()
+            .unwrap_or(self.current)
+    }
+
+    /// Migrates a save document of the given version up to the current one.
+    ///
+    /// On failure, the document may have been partially migrated, and
+    /// should be discarded.
+    pub fn migrate(
+        &self,
+        document: &mut D,
+        version: SaveVersion,
+    ) -> Result<MigrationReport, MigrationError> {
+        if version > self.current {
+            return Err(MigrationError::TooNew {
+                version,
+                current: self.current,
+            });
+        }
+
+        if version < self.oldest_supported() {
+            return Err(MigrationError::Unsupported { version });
+        }
+
+        let mut report = MigrationReport {
+            from: version,
+            to: version,
+            ..Default::default()
+        };
+
+        for from in version..self.current {
+            self.migrations[&from](document, &mut report).map_err(|reason| {
+                MigrationError::Failed {
+                    version: from,
+                    reason,
+                }
+            })?;
+            report.to = from + 1;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A toy save, as a list of key-value pairs.
+    type Document = Vec<(String, i64)>;
+
+    fn rename_gold(document: &mut Document, report: &mut MigrationReport) -> Result<(), String> {
+        for (key, _) in document.iter_mut().filter(|(key, _)| key == "gold") {
+            *key = "money".into();
+            report.change("renamed gold to money");
+        }
+        Ok(())
+    }
+
+    fn drop_negative(document: &mut Document, report: &mut MigrationReport) -> Result<(), String> {
+        document.retain(|(key, value)| {
+            if *value < 0 {
+                report.loss(format!("dropped {key}, which was negative"));
+            }
+            *value >= 0
+        });
+        Ok(())
+    }
+
+    fn reject_all(_: &mut Document, _: &mut MigrationReport) -> Result<(), String> {
+        Err("unreadable".into())
+    }
+
+    #[test]
+    fn migrates_in_sequence() {
+        let mut migrations = SaveMigrations::<Document>::new(3);
+        migrations
+            .register(2, drop_negative)
+            .register(1, rename_gold);
+
+        assert_eq!(migrations.oldest_supported(), 1);
+
+        let mut document = vec![("gold".into(), 10), ("debt".into(), -5)];
+        let report = migrations.migrate(&mut document, 1).unwrap();
+
+        assert_eq!(document, vec![("money".into(), 10)]);
+        assert_eq!((report.from, report.to), (1, 3));
+        assert_eq!(report.changes.len(), 1);
+        assert!(!report.is_lossless());
+
+        // Up to date saves are left untouched.
+        assert_eq!(migrations.migrate(&mut document, 3).unwrap().to, 3);
+
+        assert_eq!(
+            migrations.migrate(&mut document, 0),
+            Err(MigrationError::Unsupported { version: 0 })
+        );
+        assert!(matches!(
+            migrations.migrate(&mut document, 4),
+            Err(MigrationError::TooNew { .. })
+        ));
+
+        migrations.register(0, reject_all);
+        assert!(matches!(
+            migrations.migrate(&mut document, 0),
+            Err(MigrationError::Failed { version: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,644 @@
+//! # Campaign saves
+//!
+//! A [CampaignState] is everything a playthrough needs to be resumed: the
+//! world seed, the islands raided so far, the player's fleet (as
+//! [ConstructBlueprint]s) and funds, and where in town the player is.
+//! Campaigns are taken from the world with [capture_campaign], and put
+//! back with [apply_campaign], which always resumes at the intermission.
+//!
+//! Save files are a header line, `LNRSAVE <version>`, followed by the
+//! campaign state as a [SaveDocument] written in RON. Loading a save from an
+//! older version runs its document through the [CampaignMigrations] before
+//! reading the campaign state from it, so migrations can handle saves which
+//! no longer fit the current [CampaignState] at all.
+//!
+//! Saving and loading are requested with [SaveRequested] and [LoadRequested]
+//! events, and acknowledged with [CampaignSaved] and [CampaignLoaded], or
+//! [SaveFailed] and [LoadFailed]; the application moves to the game once a
+//! campaign is loaded. Saves can also be checked and migrated in bulk, without
+//! running the game, with [migrate_save_dir]; see `lnr-game --migrate-saves`.
+//!
+//! [NOTE] RON's own untyped value drops the names of enum variants when
+//! parsed, so it can't carry a campaign through; the document is instead a
+//! JSON-like tree, where variants are kept as strings and maps, which RON
+//! writes and reads back intact.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::ron, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{MigrationReport, SaveMigrations, SaveVersion};
+use crate::common::{
+    error::{Context, LnrError},
+    fleet::{Fleet, FleetShips, Funds, InFleet, SelectFlagship},
+    intermission::{
+        IntermissionBuilding, TownBuildings,
+        observatory::{RaidCount, VisitedIslands},
+    },
+    makeup::{
+        ShipMakeup,
+        blueprint::{ConstructBlueprint, spawn_from_blueprint, to_blueprint},
+    },
+    seed::{IslandId, WorldSeed},
+    state::GameState,
+};
+
+/// The current campaign save version.
+pub const CAMPAIGN_SAVE_VERSION: SaveVersion = 1;
+
+/// The first word of every save file.
+pub const SAVE_MAGIC: &str = "LNRSAVE";
+
+/// A campaign save's contents, before being read as a [CampaignState].
+///
+/// This is what [CampaignMigrations] work on.
+pub type SaveDocument = serde_json::Value;
+
+/// A save file which does not start with a valid `LNRSAVE <version>` line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BadSaveHeader;
+
+impl fmt::Display for BadSaveHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a save file: no valid {SAVE_MAGIC} header")
+    }
+}
+
+impl std::error::Error for BadSaveHeader {}
+
+/// Everything needed to resume a playthrough.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CampaignState {
+    /// The seed of the world.
+    pub world_seed: u64,
+
+    /// How many raids the player has been on.
+    pub raids: u32,
+
+    /// Every island the player has raided, in order.
+    pub visited_islands: Vec<IslandId>,
+
+    /// The money of the player's fleet.
+    pub funds: u64,
+
+    /// The ships of the player's fleet.
+    pub fleet: Vec<ConstructBlueprint>,
+
+    /// The index in [Self::fleet] of the flagship, if any.
+    pub flagship: Option<usize>,
+
+    /// Where in town the player is.
+    pub building: IntermissionBuilding,
+
+    /// The buildings the town has.
+    pub town: Vec<IntermissionBuilding>,
+}
+
+impl CampaignState {
+    /// Writes this campaign as a save file.
+    pub fn to_save(&self) -> Result<String, LnrError> {
+        let document = serde_json::to_value(self)
+            .map_err(LnrError::parse)
+            .context("writing a campaign")?;
+        let body = ron::ser::to_string_pretty(&document, ron::ser::PrettyConfig::default())
+            .context("writing a campaign")?;
+
+        Ok(format!("{SAVE_MAGIC} {CAMPAIGN_SAVE_VERSION}\n{body}"))
+    }
+
+    /// Reads a campaign from a save file, migrating it if it is older.
+    pub fn from_save(
+        bytes: &[u8],
+        migrations: &SaveMigrations<SaveDocument>,
+    ) -> Result<(Self, MigrationReport), LnrError> {
+        let split = bytes
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| LnrError::parse(BadSaveHeader))?;
+        let (header, body) = bytes.split_at(split);
+
+        let version = std::str::from_utf8(header)
+            .ok()
+            .and_then(|header| header.trim().strip_prefix(SAVE_MAGIC))
+            .and_then(|version| version.trim().parse::<SaveVersion>().ok())
+            .ok_or_else(|| LnrError::parse(BadSaveHeader))?;
+
+        let mut document: SaveDocument =
+            ron::de::from_bytes(&body[1..]).context("parsing a save")?;
+        let report = migrations
+            .migrate(&mut document, version)
+            .map_err(LnrError::parse)?;
+        let campaign = serde_json::from_value(document)
+            .map_err(LnrError::parse)
+            .context("reading a migrated save")?;
+
+        Ok((campaign, report))
+    }
+}
+
+/// The migrations campaign saves go through when loaded.
+#[derive(Resource, Deref, DerefMut)]
+pub struct CampaignMigrations(pub SaveMigrations<SaveDocument>);
+
+impl Default for CampaignMigrations {
+    fn default() -> Self {
+        Self(SaveMigrations::new(CAMPAIGN_SAVE_VERSION))
+    }
+}
+
+/// Checks a save file, and migrates it in place if it is older.
+///
+/// The save is valid if it can be read as a [CampaignState] after
+/// migrating, and every ship in its fleet could be built. Saves which would
+/// lose data by being migrated are left as they are; loading them in game
+/// tells the player what would be lost first.
+pub fn migrate_save_file(path: &Path, migrations: &CampaignMigrations) -> Result<MigrationReport> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let (campaign, report) = CampaignState::from_save(&bytes, migrations)
+        .with_context(|| format!("reading {}", path.display()))?;
+
+    for (idx, blueprint) in campaign.fleet.iter().enumerate() {
+        ShipMakeup::from_blueprint(blueprint.clone())
+            .map_err(|err| format!("{}: ship {idx}: {err}", path.display()))?;
+    }
+
+    if report.from < report.to && report.is_lossless() {
+        std::fs::write(path, campaign.to_save()?)
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+
+    Ok(report)
+}
+
+/// Checks, and migrates where needed, every save file in a directory, as
+/// with [migrate_save_file].
+///
+/// Fails only if the directory can't be read; the result of every save is
+/// returned, sorted by path.
+pub fn migrate_save_dir(
+    dir: &Path,
+    migrations: &CampaignMigrations,
+) -> Result<Vec<(PathBuf, Result<MigrationReport>)>> {
+    let mut paths = std::fs::read_dir(dir)
+        .with_context(|| format!("listing {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let result = migrate_save_file(&path, migrations);
+            (path, result)
+        })
+        .collect())
+}
+
+/// The state of the current campaign.
+///
+/// Takes the first [Fleet] as the player's.
+pub fn capture_campaign(world: &mut World) -> Result<CampaignState> {
+    let (flagship, ships) = world
+        .query::<(&Fleet, Option<&FleetShips>)>()
+        .iter(world)
+        .next()
+        .map(|(fleet, ships)| {
+            let ships: Vec<_> = ships
+                .map(|ships| ships.iter().copied().collect())
+                .unwrap_or_default();
+            (fleet.flagship(), ships)
+        })
+        .unwrap_or_default();
+
+    let fleet = ships
+        .iter()
+        .map(|&ship| to_blueprint(world, ship))
+        .collect::<Result<Vec<_>>>()?;
+    let building = world
+        .get_resource::<State<IntermissionBuilding>>()
+        .map(|state| *state.get())
+        .unwrap_or_default();
+
+    Ok(CampaignState {
+        world_seed: world.resource::<WorldSeed>().0,
+        raids: world.resource::<RaidCount>().0,
+        visited_islands: world.resource::<VisitedIslands>().0.clone(),
+        funds: world.resource::<Funds>().0,
+        flagship: flagship.and_then(|flagship| ships.iter().position(|&ship| ship == flagship)),
+        fleet,
+        building,
+        town: world.resource::<TownBuildings>().iter().collect(),
+    })
+}
+
+/// Resumes a campaign at the intermission.
+///
+/// Replaces the player's fleet, and any ships in it, with the campaign's.
+/// The campaign's ships are spawned first; if any can't be, the world is
+/// left as it was.
+pub fn apply_campaign(world: &mut World, campaign: CampaignState) -> Result {
+    let old_fleets: Vec<_> = world
+        .query_filtered::<Entity, With<Fleet>>()
+        .iter(world)
+        .collect();
+    let old_ships: Vec<_> = world
+        .query_filtered::<Entity, With<InFleet>>()
+        .iter(world)
+        .collect();
+
+    let mut ships = Vec::with_capacity(campaign.fleet.len());
+    for blueprint in campaign.fleet {
+        match spawn_from_blueprint(world, blueprint) {
+            Ok(ship) => ships.push(ship),
+            Err(err) => {
+                for ship in ships {
+                    world.despawn(ship);
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    for entity in old_ships.into_iter().chain(old_fleets) {
+        world.despawn(entity);
+    }
+
+    world.insert_resource(WorldSeed(campaign.world_seed));
+    world.insert_resource(RaidCount(campaign.raids));
+    world.insert_resource(VisitedIslands(campaign.visited_islands));
+    world.insert_resource(Funds(campaign.funds));
+    world.insert_resource(TownBuildings::new(campaign.town));
+
+    let fleet = world.spawn(Fleet::default()).id();
+    for &ship in &ships {
+        world.entity_mut(ship).insert(InFleet::new(fleet));
+    }
+    if let Some(&flagship) = campaign.flagship.and_then(|idx| ships.get(idx)) {
+        world.trigger_targets(SelectFlagship, flagship);
+    }
+
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Intermission);
+    if let Some(mut next_building) = world.get_resource_mut::<NextState<IntermissionBuilding>>() {
+        next_building.set(campaign.building);
+    }
+
+    Ok(())
+}
+
+/// Requests the current campaign to be saved to a file.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct SaveRequested {
+    pub path: PathBuf,
+}
+
+/// Requests a campaign to be loaded from a save file.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct LoadRequested {
+    pub path: PathBuf,
+}
+
+/// Sent once the campaign was saved.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignSaved {
+    pub path: PathBuf,
+}
+
+/// Sent once a campaign was loaded.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignLoaded {
+    pub path: PathBuf,
+
+    /// What migrating the save did, if it was older.
+    pub report: MigrationReport,
+}
+
+/// Sent when the campaign could not be saved.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct SaveFailed {
+    pub path: PathBuf,
+
+    /// Why, for the player.
+    pub reason: String,
+}
+
+/// Sent when a campaign could not be loaded.
+///
+/// The current campaign, if any, is left as it was.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct LoadFailed {
+    pub path: PathBuf,
+
+    /// Why, for the player.
+    pub reason: String,
+}
+
+fn save_campaign(world: &mut World, path: &Path) -> Result {
+    let save = capture_campaign(world)?.to_save()?;
+    std::fs::write(path, save).with_context(|| format!("saving to {}", path.display()))?;
+    Ok(())
+}
+
+fn load_campaign(world: &mut World, path: &Path) -> Result<MigrationReport> {
+    let (campaign, report) = std::fs::read(path)
+        .map_err(LnrError::from)
+        .and_then(|bytes| CampaignState::from_save(&bytes, world.resource::<CampaignMigrations>()))
+        .with_context(|| format!("loading {}", path.display()))?;
+
+    for loss in &report.losses {
+        warn!("Loading {}: {loss}", path.display());
+    }
+    apply_campaign(world, campaign)?;
+
+    Ok(report)
+}
+
+// [NOTE] Failures are reported per request, rather than failing the system,
+// so one bad save file neither drops the other requests nor panics debug
+// builds.
+
+fn handle_save_requests(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Events<SaveRequested>>()
+        .drain()
+        .collect();
+
+    for SaveRequested { path } in requests {
+        match save_campaign(world, &path) {
+            Ok(()) => {
+                info!("Saved campaign to {}", path.display());
+                world.send_event(CampaignSaved { path });
+            }
+            Err(err) => {
+                warn!("Could not save the campaign: {err}");
+                world.send_event(SaveFailed {
+                    path,
+                    reason: err.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn handle_load_requests(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Events<LoadRequested>>()
+        .drain()
+        .collect();
+
+    for LoadRequested { path } in requests {
+        match load_campaign(world, &path) {
+            Ok(report) => {
+                info!("Loaded campaign from {}", path.display());
+                world.send_event(CampaignLoaded { path, report });
+            }
+            Err(err) => {
+                warn!("Could not load a campaign: {err}");
+                world.send_event(LoadFailed {
+                    path,
+                    reason: err.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Campaign saving and loading.
+///
+/// Requires the [GameState] to be initialized, e.g. by the
+/// [BaseStatePlugin](crate::common::state::BaseStatePlugin), and the
+/// [FleetPlugin](crate::common::fleet::FleetPlugin) and
+/// [IntermissionPlugin](crate::common::intermission::IntermissionPlugin).
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CampaignMigrations>();
+        app.add_event::<SaveRequested>();
+        app.add_event::<LoadRequested>();
+        app.add_event::<CampaignSaved>();
+        app.add_event::<CampaignLoaded>();
+        app.add_event::<SaveFailed>();
+        app.add_event::<LoadFailed>();
+        app.add_systems(Update, (handle_save_requests, handle_load_requests));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+
+    use crate::common::{
+        fixtures::make,
+        fleet::{FleetPlugin, add_to_fleet, select_flagship, spawn_fleet},
+        intermission::IntermissionPlugin,
+        makeup::{Ship, ShipMake},
+    };
+
+    use super::*;
+
+    fn save_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.init_state::<GameState>();
+        app.add_plugins((IntermissionPlugin, FleetPlugin, SavePlugin));
+        app
+    }
+
+    fn ship(hull_mass: f32) -> ConstructBlueprint {
+        ConstructBlueprint::new(ShipMake {
+            hull_mass,
+            ..make(vec![])
+        })
+    }
+
+    /// Version 0 saves kept the funds as a string of coins, e.g. "300c".
+    fn coins_to_funds(
+        document: &mut SaveDocument,
+        report: &mut MigrationReport,
+    ) -> Result<(), String> {
+        let Some(funds) = document.get_mut("funds") else {
+            return Ok(());
+        };
+        let coins = funds
+            .as_str()
+            .and_then(|coins| coins.strip_suffix('c'))
+            .and_then(|coins| coins.parse::<u64>().ok())
+            .ok_or_else(|| format!("unreadable funds {funds}"))?;
+
+        *funds = coins.into();
+        report.change("converted funds from coins");
+        Ok(())
+    }
+
+    #[test]
+    fn old_saves_are_migrated_before_being_read() {
+        let save = "LNRSAVE 0\n{\"world_seed\": 42, \"funds\": \"300c\"}";
+
+        let mut migrations = CampaignMigrations::default();
+        assert!(CampaignState::from_save(save.as_bytes(), &migrations).is_err());
+
+        // The old funds would not read as the current ones.
+        migrations.register(0, coins_to_funds);
+        let (campaign, report) = CampaignState::from_save(save.as_bytes(), &migrations).unwrap();
+        assert_eq!(campaign.world_seed, 42);
+        assert_eq!(campaign.funds, 300);
+        assert_eq!((report.from, report.to), (0, 1));
+        assert_eq!(report.changes.len(), 1);
+    }
+
+    #[test]
+    fn campaigns_round_trip_through_saves() {
+        let mut app = save_app();
+        app.insert_resource(WorldSeed(42));
+        app.insert_resource(Funds(300));
+        app.insert_resource(VisitedIslands(vec![IslandId(7)]));
+
+        let fleet = spawn_fleet(&mut app.world_mut().commands());
+        let ships: Vec<_> = [10.0, 20.0]
+            .map(|mass| spawn_from_blueprint(app.world_mut(), ship(mass)).unwrap())
+            .into();
+        for &ship in &ships {
+            add_to_fleet(&mut app.world_mut().commands(), fleet, ship);
+        }
+        select_flagship(&mut app.world_mut().commands(), ships[1]);
+        app.world_mut().flush();
+
+        let save = capture_campaign(app.world_mut())
+            .unwrap()
+            .to_save()
+            .unwrap();
+        assert!(save.starts_with("LNRSAVE 1\n"));
+
+        let mut app = save_app();
+        let (campaign, report) = CampaignState::from_save(
+            save.as_bytes(),
+            app.world().resource::<CampaignMigrations>(),
+        )
+        .unwrap();
+        assert!(report.is_lossless());
+        apply_campaign(app.world_mut(), campaign).unwrap();
+        app.update();
+
+        assert_eq!(app.world().resource::<WorldSeed>().0, 42);
+        assert_eq!(app.world().resource::<Funds>().0, 300);
+        assert_eq!(app.world().resource::<VisitedIslands>().0, [IslandId(7)]);
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Intermission
+        );
+
+        let fleet = app
+            .world_mut()
+            .query::<(&Fleet, &FleetShips)>()
+            .single(app.world())
+            .map(|(fleet, ships)| (fleet.flagship(), ships.iter().copied().collect::<Vec<_>>()))
+            .unwrap();
+        assert_eq!(fleet.1.len(), 2);
+        assert_eq!(fleet.0, Some(fleet.1[1]));
+
+        // Saves from the future are refused.
+        let future = save.replacen("LNRSAVE 1", "LNRSAVE 2", 1);
+        assert!(
+            CampaignState::from_save(
+                future.as_bytes(),
+                app.world().resource::<CampaignMigrations>()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn failed_loads_keep_the_current_campaign() {
+        let mut app = save_app();
+        app.insert_resource(Funds(300));
+
+        let fleet = spawn_fleet(&mut app.world_mut().commands());
+        let current = spawn_from_blueprint(app.world_mut(), ship(10.0)).unwrap();
+        add_to_fleet(&mut app.world_mut().commands(), fleet, current);
+        app.world_mut().flush();
+
+        // The second ship installs an item it does not have.
+        let mut broken = ship(20.0);
+        broken.installed = vec![Some(0)];
+        let campaign = CampaignState {
+            funds: 5,
+            fleet: vec![ship(30.0), broken],
+            ..default()
+        };
+        assert!(apply_campaign(app.world_mut(), campaign).is_err());
+        assert_eq!(app.world().resource::<Funds>().0, 300);
+        assert_eq!(
+            app.world_mut().query::<&Ship>().iter(app.world()).count(),
+            1
+        );
+        assert!(app.world().get_entity(fleet).is_ok());
+
+        // Missing saves are reported, rather than failing the system.
+        let path = PathBuf::from("/nonexistent/campaign.lnrsave");
+        app.world_mut()
+            .send_event(LoadRequested { path: path.clone() });
+        app.update();
+
+        let failures: Vec<_> = app
+            .world_mut()
+            .resource_mut::<Events<LoadFailed>>()
+            .drain()
+            .collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, path);
+        assert!(app.world().get_entity(current).is_ok());
+    }
+
+    #[test]
+    fn save_dirs_are_migrated_in_bulk() {
+        let dir = std::env::temp_dir().join(format!("lnr-saves-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("old.lnrsave"),
+            "LNRSAVE 0\n{\"world_seed\": 42, \"funds\": \"300c\"}",
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.lnrsave"), "not a save").unwrap();
+
+        let mut migrations = CampaignMigrations::default();
+        migrations.register(0, coins_to_funds);
+        let results = migrate_save_dir(&dir, &migrations).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].0.ends_with("broken.lnrsave"));
+        assert!(results[0].1.is_err());
+        assert!(results[1].0.ends_with("old.lnrsave"));
+        assert_eq!(results[1].1.as_ref().unwrap().to, 1);
+
+        // The old save was rewritten at the current version.
+        let migrated = std::fs::read(dir.join("old.lnrsave")).unwrap();
+        assert!(migrated.starts_with(b"LNRSAVE 1\n"));
+        let (campaign, report) = CampaignState::from_save(&migrated, &migrations).unwrap();
+        assert_eq!(campaign.funds, 300);
+        assert_eq!(report.from, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+//! # Island biomes
+//!
+//! Every island has a biome, chosen when the island is generated, which
+//! themes the overworld scene: the colors of the terrain, water, sky and
+//! sunlight, as well as how likely each kind of weather is.
+//!
+//! Biomes are purely thematic; they do not affect terrain shape.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+// [TODO] Biome-specific ambient audio. The audio module (app::audio) only
+// plays sound effects and music by mood; it has no looping ambience yet, nor
+// any ambience tracks to pick per biome.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::common::terrain::biome::BiomeParams;
+
+/// The biome of an island.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IslandBiome {
+    /// Lush green islands in warm, clear waters.
+    #[default]
+    Tropical,
+
+    /// Cold, rocky islands under overcast skies.
+    RockyNorth,
+
+    /// Ashen islands with dark rock, under a hazy red sky.
+    Volcanic,
+}
+
+/// The weather over an island, picked when its overworld scene is set up,
+/// and kept for the whole raid.
+///
+/// Rougher weather blows stronger winds and raises higher waves. Drawing
+/// rain and fog is up to the client.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+    Fog,
+}
+
+impl Weather {
+    /// How much stronger than usual the wind blows.
+    pub fn wind_factor(&self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 1.4,
+            Self::Storm => 2.5,
+            Self::Fog => 0.4,
+        }
+    }
+
+    /// How much higher than usual for the biome the waves are.
+    pub fn wave_factor(&self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 1.2,
+            Self::Storm => 2.0,
+            Self::Fog => 0.7,
+        }
+    }
+}
+
+/// How likely each kind of weather is in a biome.
+///
+/// The chances are relative to each other; they need not add up to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherChances {
+    pub clear: f32,
+    pub rain: f32,
+    pub storm: f32,
+    pub fog: f32,
+}
+
+impl WeatherChances {
+    /// Picks the weather at random, by the chances of each kind.
+    ///
+    /// If no weather has any chance, the weather is clear.
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Weather {
+        let chances = [
+            (Weather::Clear, self.clear),
+            (Weather::Rain, self.rain),
+            (Weather::Storm, self.storm),
+            (Weather::Fog, self.fog),
+        ]
+        .map(|(weather, chance)| (weather, chance.max(0.0)));
+
+        let total: f32 = chances.iter().map(|(_, chance)| chance).sum();
+        if total <= 0.0 {
+            return Weather::Clear;
+        }
+
+        let mut roll = rng.random_range(0.0..total);
+        for (weather, chance) in chances {
+            if roll < chance {
+                return weather;
+            }
+            roll -= chance;
+        }
+
+        // Only reachable through rounding errors.
+        chances
+            .into_iter()
+            .rfind(|(_, chance)| *chance > 0.0)
+            .map_or(Weather::Clear, |(weather, _)| weather)
+    }
+}
+
+impl IslandBiome {
+    /// All biomes.
+    pub const ALL: [IslandBiome; 3] = [Self::Tropical, Self::RockyNorth, Self::Volcanic];
+
+    /// Picks a random biome, e.g. when generating islands in the
+    /// Observatory.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::ALL[rng.random_range(0..Self::ALL.len())]
+    }
+
+    /// How the island's terrain is split into
+    /// [crate::common::terrain::biome::TerrainBiome]s.
+    pub fn terrain_biome_params(&self) -> BiomeParams {
+        match self {
+            Self::Tropical => BiomeParams {
+                beach_height: 0.06,
+                ..default()
+            },
+            Self::RockyNorth => BiomeParams {
+                beach_height: 0.02,
+                rock_slope: 0.8,
+                rock_height: 0.35,
+                forest_threshold: 0.2,
+                ..default()
+            },
+            Self::Volcanic => BiomeParams {
+                volcanic_height: Some(0.35),
+                forest_threshold: 0.3,
+                ..default()
+            },
+        }
+    }
+
+    /// The color of the water surface.
+    pub fn water_color(&self) -> Color {
+        match self {
+            Self::Tropical => Color::srgba_u8(190, 190, 255, 90),
+            Self::RockyNorth => Color::srgba_u8(110, 130, 160, 140),
+            Self::Volcanic => Color::srgba_u8(120, 110, 130, 120),
+        }
+    }
+
+    /// Typical height of the waves around the island.
+    pub fn wave_height(&self) -> f32 {
+        match self {
+            Self::Tropical => 0.5,
+            Self::RockyNorth => 1.4,
+            Self::Volcanic => 0.9,
+        }
+    }
+
+    /// The color of the sky.
+    pub fn sky_color(&self) -> Color {
+        match self {
+            Self::Tropical => Color::srgb_u8(40, 160, 200),
+            Self::RockyNorth => Color::srgb_u8(150, 165, 180),
+            Self::Volcanic => Color::srgb_u8(150, 95, 80),
+        }
+    }
+
+    /// The color of the sunlight, which tints the whole scene.
+    pub fn light_color(&self) -> Color {
+        match self {
+            Self::Tropical => Color::srgb(1.0, 0.98, 0.92),
+            Self::RockyNorth => Color::srgb(0.85, 0.9, 1.0),
+            Self::Volcanic => Color::srgb(1.0, 0.75, 0.6),
+        }
+    }
+
+    /// How likely each kind of weather is.
+    pub fn weather_chances(&self) -> WeatherChances {
+        match self {
+            Self::Tropical => WeatherChances {
+                clear: 0.7,
+                rain: 0.2,
+                storm: 0.1,
+                fog: 0.0,
+            },
+            Self::RockyNorth => WeatherChances {
+                clear: 0.3,
+                rain: 0.3,
+                storm: 0.15,
+                fog: 0.25,
+            },
+            Self::Volcanic => WeatherChances {
+                clear: 0.5,
+                rain: 0.05,
+                storm: 0.1,
+                fog: 0.35,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn weather_is_picked_by_its_chances() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let chances = IslandBiome::Tropical.weather_chances();
+
+        let picks = (0..1000)
+            .map(|_| chances.pick(&mut rng))
+            .collect::<Vec<_>>();
+        let count = |weather| picks.iter().filter(|&&pick| pick == weather).count();
+
+        // Tropical islands are never foggy, and mostly clear.
+        assert_eq!(count(Weather::Fog), 0);
+        assert!(count(Weather::Clear) > count(Weather::Rain));
+        assert!(count(Weather::Rain) > count(Weather::Storm));
+        assert!(count(Weather::Storm) > 0);
+
+        let calm = WeatherChances {
+            clear: 0.0,
+            rain: 0.0,
+            storm: 0.0,
+            fog: 0.0,
+        };
+        assert_eq!(calm.pick(&mut rng), Weather::Clear);
+    }
+}
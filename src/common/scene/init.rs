@@ -30,11 +30,25 @@ use rand::Rng;
 use crate::{
     app::camera::DevCamera,
     common::{
+        ai::patrol::PatrolRoutes,
+        physics::{
+            forces::Wind,
+            water::{WaterCurrentField, WaterSurface, WaveField},
+        },
         prelude::{
-            CenterPoint, FractalNoise, ModulationParams, TerrainGeneratorBuilder, default_modulator,
+            BiomeLayer, CaveLayer, CenterPoint, DefaultTerrainGenerator, FractalNoise,
+            HydrologyParams, ModulationParams, NoiseVolume, TerrainGeneratorBuilder,
+            default_modulator,
         },
+        props::{PlacementSite, PropCatalog, PropSpawner},
+        scene::biome::{IslandBiome, Weather},
+        seed::{IslandId, WorldSeed},
+        spawner::ShipSpawner,
         state::{GameState, SceneSetupEvent},
-        terrain::buffer::TerrainBuffer,
+        terrain::{
+            chunk::{TerrainChunkIndex, TerrainChunkSource, TerrainStreamer},
+            navigation::NavGrid,
+        },
     },
 };
 
@@ -49,6 +63,30 @@ pub struct OverworldSceneParams {
     /// 32 is the default, 255 is the maximum.
     pub island_size: u8,
 
+    /// The biome of the island.
+    ///
+    /// Themes the scene's colors, props and weather.
+    #[builder(default)]
+    pub biome: IslandBiome,
+
+    /// The sea current around the island, in open sea.
+    ///
+    /// Near the shore, it is deflected to flow around the island; see
+    /// [WaterCurrentField::from_heightfield].
+    #[builder(default = Vec2::new(0.4, 0.1))]
+    pub sea_current: Vec2,
+
+    /// Rivers and lagoons to carve into the island, if any.
+    ///
+    /// Their waterways can be found through the terrain generator; see
+    /// [crate::common::terrain::generator::TerrainGenerator::hydrology].
+    #[builder(default = Some(HydrologyParams::default()))]
+    pub hydrology: Option<HydrologyParams>,
+
+    /// Whether to carve sea caves into the island.
+    #[builder(default = true)]
+    pub caves: bool,
+
     /// How well defended the island should be, inland.
     ///
     /// Controls the placement of defensive props.
@@ -95,6 +133,10 @@ impl Default for OverworldSceneParams {
     fn default() -> Self {
         Self {
             island_size: 32,
+            biome: IslandBiome::default(),
+            sea_current: Vec2::new(0.4, 0.1),
+            hydrology: Some(HydrologyParams::default()),
+            caves: true,
             prop_defense: 10,
             patrol_paths: 2,
             visit_frequency: 50,
@@ -157,21 +199,20 @@ impl OverworldSceneParams {
 #[derive(Resource, Default, Clone, Debug)]
 pub struct OverworldSceneInitializer {
     pub params: OverworldSceneParams,
+
+    /// The island to generate.
+    ///
+    /// If unset, the first island of the [WorldSeed] is generated.
+    pub island: Option<IslandId>,
 }
 
 #[derive(Component)]
 pub struct OverworldCamera;
 
 impl OverworldSceneInitializer {
-    fn setup_overworld_island(
-        &self,
-        scene_tree: Entity,
-        commands: &mut Commands,
-        meshes: &mut ResMut<Assets<Mesh>>,
-        materials: &mut ResMut<Assets<StandardMaterial>>,
-    ) {
-        // [TODO] use a Bevy resource to store a common RNG
-        let mut rng = rand::rng();
+    /// Makes the terrain generator of an island.
+    pub fn terrain_generator(&self, island: IslandId) -> DefaultTerrainGenerator {
+        let mut rng = island.rng("terrain");
 
         let num_seeds = self.params.terrain_num_seeds(&mut rng);
 
@@ -197,37 +238,163 @@ impl OverworldSceneInitializer {
             })
             .center_points(center_points)
             .resolution(10.0)
+            .biomes(BiomeLayer::new(
+                self.params.biome.terrain_biome_params(),
+                FractalNoise::random_octaves(10.0, 10.0, 2.try_into().unwrap(), &mut rng),
+            ))
             .build()
             .unwrap();
 
-        let terrain = TerrainBuffer::generate(terragen, 0.2, 3.0, 80.0);
+        match &self.params.hydrology {
+            Some(params) => terragen.with_hydrology(params, &mut rng),
+            None => terragen,
+        }
+    }
 
-        let terrain_entity = commands
-            .spawn((
-                terrain.as_bundle(meshes),
-                MeshMaterial3d(materials.add(Color::srgb_u8(80, 190, 45))),
-                Transform::from_xyz(0.0, -40.0, 0.0),
+    fn setup_overworld_island(
+        &self,
+        island: IslandId,
+        scene_tree: Entity,
+        commands: &mut Commands,
+        materials: &mut ResMut<Assets<StandardMaterial>>,
+        catalog: &PropCatalog,
+    ) {
+        let terragen = self.terrain_generator(island);
+
+        let terrain_offset = Vec3::new(0.0, -40.0, 0.0);
+        let source = TerrainChunkSource::new(terragen, 0.2, 3.0, 80.0, terrain_offset);
+
+        // Chunks are streamed in around the camera; see [TerrainStreamer].
+        let mut index = TerrainChunkIndex::new(source, 32);
+        // Colored by biome, through vertex colors; the client replaces this
+        // with its own terrain material.
+        index.material = Some(materials.add(StandardMaterial::default()));
+        index.caves = self.params.caves.then(|| {
+            CaveLayer::new(NoiseVolume::random(
+                2.try_into().unwrap(),
+                &mut island.rng("caves"),
             ))
-            .id();
-        commands.entity(scene_tree).add_child(terrain_entity);
+        });
+
+        commands.insert_resource(WaterCurrentField::from_heightfield(
+            index.source().bounds(),
+            |at| index.height_at(at),
+            |at| index.gradient_at(at),
+            -40.0,
+            6.0,
+            self.params.sea_current,
+            10.0,
+        ));
+
+        // So that ships know where they can sail.
+        let nav_grid = NavGrid::from_heightfield(
+            index.source().bounds(),
+            |at| index.height_at(at),
+            -40.0,
+            8.0,
+        );
+
+        commands.insert_resource(PatrolRoutes::generate(
+            &nav_grid,
+            index.source().bounds().center(),
+            self.params.patrol_paths,
+            6.0,
+            12.0,
+        ));
+        commands.insert_resource(nav_grid);
+
+        self.setup_overworld_props(island, scene_tree, commands, &index, catalog);
+
+        commands.insert_resource(ShipSpawner::new(
+            &self.params,
+            index.source().bounds(),
+            -40.0,
+            island.rng("ships"),
+        ));
+
+        commands.insert_resource(index);
+    }
+
+    fn setup_overworld_props(
+        &self,
+        island: IslandId,
+        scene_tree: Entity,
+        commands: &mut Commands,
+        index: &TerrainChunkIndex,
+        catalog: &PropCatalog,
+    ) {
+        let source = index.source();
+        let waterways = source
+            .generator()
+            .hydrology()
+            .into_iter()
+            .flat_map(|hydrology| hydrology.spawn_points(0.25))
+            .map(|at| source.to_world(at));
+
+        let spawner =
+            PropSpawner::new(source.bounds(), -40.0, &self.params).with_waterways(waterways);
+
+        let placements = spawner.place(
+            catalog,
+            |at| PlacementSite {
+                position: at.extend(index.height_at(at)).xzy(),
+                slope: index.gradient_at(at).length(),
+                biome: index.biome_at(at),
+            },
+            &mut island.rng("props"),
+        );
+
+        info!("Spawning {} props", placements.len());
+        PropSpawner::spawn(&placements, catalog, scene_tree, commands);
+    }
+
+    fn setup_overworld_weather(&self, island: IslandId, commands: &mut Commands) -> Weather {
+        let weather = self
+            .params
+            .biome
+            .weather_chances()
+            .pick(&mut island.rng("weather"));
+        info!("The weather is {weather:?}");
+
+        let calm = Wind::default();
+        commands.insert_resource(Wind {
+            strength: calm.strength * weather.wind_factor(),
+            ..calm
+        });
+        commands.insert_resource(weather);
+
+        weather
     }
 
     fn setup_overworld_water(
         &self,
+        weather: Weather,
         scene_tree: Entity,
         commands: &mut Commands,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<StandardMaterial>>,
     ) {
+        commands.insert_resource(WaveField::choppy(
+            self.params.sea_current,
+            self.params.biome.wave_height() * weather.wave_factor(),
+        ));
+
         let water_entity = commands
             .spawn((
-                Mesh3d(meshes.add(Circle::new(1000.0))),
+                Mesh3d(
+                    meshes.add(
+                        Plane3d::default()
+                            .mesh()
+                            .size(2000.0, 2000.0)
+                            .subdivisions(127),
+                    ),
+                ),
+                WaterSurface,
                 MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: Color::srgba_u8(190, 190, 255, 90),
+                    base_color: self.params.biome.water_color(),
                     ..Default::default()
                 })),
-                Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2))
-                    .with_translation(Vec3::new(0.0, -40.0, 0.0)),
+                Transform::from_xyz(0.0, -40.0, 0.0),
             ))
             .id();
         commands.entity(scene_tree).add_child(water_entity);
@@ -237,6 +404,7 @@ impl OverworldSceneInitializer {
         let light_entity = commands
             .spawn((
                 PointLight {
+                    color: self.params.biome.light_color(),
                     shadows_enabled: true,
                     intensity: 5000.0,
                     range: 2000.0,
@@ -256,6 +424,7 @@ impl OverworldSceneInitializer {
                     ..default()
                 },
                 OverworldCamera,
+                TerrainStreamer,
                 DevCamera {
                     move_speed: 80.0,
                     enabled: true,
@@ -271,19 +440,24 @@ impl OverworldSceneInitializer {
     /// Initializes an overworld scene.
     pub(crate) fn setup_overworld(
         &self,
+        island: IslandId,
         scene_tree: Entity,
         commands: &mut Commands,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<StandardMaterial>>,
+        catalog: &PropCatalog,
     ) {
         info!(
-            "Setting up Overworld scene for parameters: {:?}",
+            "Setting up Overworld scene for island {island} and parameters: {:?}",
             self.params
         );
-        self.setup_overworld_island(scene_tree, commands, meshes, materials);
-        self.setup_overworld_water(scene_tree, commands, meshes, materials);
+        self.setup_overworld_island(island, scene_tree, commands, materials, catalog);
+        let weather = self.setup_overworld_weather(island, commands);
+        self.setup_overworld_water(weather, scene_tree, commands, meshes, materials);
         self.setup_overworld_lighting(scene_tree, commands);
         self.setup_overworld_camera(scene_tree, commands);
+
+        commands.insert_resource(ClearColor(self.params.biome.sky_color()));
     }
 }
 
@@ -293,10 +467,20 @@ fn setup_overworld_scene(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     initializer: Res<OverworldSceneInitializer>,
+    seed: Res<WorldSeed>,
+    catalog: Res<PropCatalog>,
 ) {
     for ev in ev_scene_setup.read() {
         info!("Received SceneSetup event for the Overworld scene");
-        initializer.setup_overworld(ev.scene_tree, &mut commands, &mut meshes, &mut materials);
+        let island = initializer.island.unwrap_or_else(|| seed.island(0));
+        initializer.setup_overworld(
+            island,
+            ev.scene_tree,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            &catalog,
+        );
     }
 }
 
@@ -309,5 +493,6 @@ impl Plugin for OverworldSceneSetupPlugin {
             setup_overworld_scene.run_if(in_state(GameState::Overworld)),
         );
         app.init_resource::<OverworldSceneInitializer>();
+        app.init_resource::<WorldSeed>();
     }
 }
@@ -18,7 +18,8 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-pub mod init;
+pub mod biome; // Island biomes
+pub mod init; // Overworld scene initialization
 
 use bevy::prelude::Plugin;
 
@@ -33,4 +34,5 @@ impl Plugin for SceneManagementPlugin {
 
 pub mod prelude {
     pub use super::SceneManagementPlugin;
+    pub use super::biome::IslandBiome;
 }
@@ -0,0 +1,67 @@
+//! # Scratch buffers
+//!
+//! Hot loops often need a temporary list, e.g. the distances to every
+//! terrain center point, or the objects in the collision broad phase.
+//! Allocating a new Vec for each of them adds up quickly.
+//!
+//! A [ScratchBuffer] is cleared before every use instead, keeping its
+//! capacity, so after warming up, it no longer allocates. Keep one per
+//! system, as a [bevy::prelude::Local] or in a resource, or pass one down to
+//! functions which are called in a loop.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+/// A reusable buffer for temporary lists.
+#[derive(Debug, Clone)]
+pub struct ScratchBuffer<T> {
+    buffer: Vec<T>,
+}
+
+impl<T> Default for ScratchBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ScratchBuffer<T> {
+    /// Makes a new, empty scratch buffer.
+    ///
+    /// Does not allocate until first used.
+    pub fn new() -> Self {
+        Self { buffer: Vec::new() }
+    }
+
+    /// Clears the buffer, and returns it to be filled in again.
+    pub fn reuse(&mut self) -> &mut Vec<T> {
+        self.buffer.clear();
+        &mut self.buffer
+    }
+
+    /// Clears the buffer, and fills it in with the items of an iterator.
+    pub fn fill(&mut self, items: impl IntoIterator<Item = T>) -> &mut [T] {
+        let buffer = self.reuse();
+        buffer.extend(items);
+        buffer
+    }
+
+    /// The buffer's contents, as of the last use.
+    pub fn as_slice(&self) -> &[T] {
+        &self.buffer
+    }
+
+    /// How many items fit in the buffer without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
@@ -0,0 +1,145 @@
+//! # World seeds
+//!
+//! Everything random about world generation comes from a [WorldSeed], so that
+//! it can be reproduced, e.g. by networked peers, or by players sharing an
+//! island they liked.
+//!
+//! Each island of a world has its own [IslandId], derived from the world
+//! seed. An island is fully reproduced from its ID and its
+//! [crate::common::scene::init::OverworldSceneParams].
+//!
+//! Different parts of generation (terrain, props, etc.) draw from separate
+//! random streams, given by a purpose label, so that changing how many
+//! random numbers one of them uses does not change the others.
+//!
+//! [NOTE] The random streams are [ChaCha8Rng]s, whose output is stable
+//! across platforms and versions, unlike [rand::rngs::StdRng].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// The random number generator used for world generation.
+pub type WorldRng = ChaCha8Rng;
+
+/// The random stream of a seed for a given purpose.
+fn seeded_stream(seed: u64, purpose: &str) -> WorldRng {
+    // FNV-1a, which, unlike the standard library's hashers, is stable.
+    let stream = purpose
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+
+    let mut rng = WorldRng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+/// The seed of the world.
+///
+/// Defaults to a random seed. Insert it before the overworld is set up to
+/// reproduce a world.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorldSeed(pub u64);
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+impl WorldSeed {
+    /// A random stream for a given purpose.
+    pub fn rng(&self, purpose: &str) -> WorldRng {
+        seeded_stream(self.0, purpose)
+    }
+
+    /// The ID of an island of this world, by the order in which islands are
+    /// visited.
+    pub fn island(&self, index: u32) -> IslandId {
+        let mut rng = self.rng("islands");
+        rng.set_word_pos(index as u128 * 2);
+        IslandId(rand::Rng::random(&mut rng))
+    }
+}
+
+/// The ID of an island, which seeds its generation.
+///
+/// Written as 16 hexadecimal digits, for sharing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IslandId(pub u64);
+
+impl IslandId {
+    /// A random stream for a given purpose, such as `"terrain"` or
+    /// `"props"`.
+    pub fn rng(&self, purpose: &str) -> WorldRng {
+        seeded_stream(self.0, purpose)
+    }
+}
+
+impl fmt::Display for IslandId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+impl FromStr for IslandId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s.trim(), 16).map(Self)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::Rng;
+
+    use crate::common::scene::init::OverworldSceneInitializer;
+
+    use super::*;
+
+    #[test]
+    fn islands_are_reproducible() {
+        let seed = WorldSeed(0x10_07_AB_0A);
+        let island = seed.island(3);
+
+        assert_eq!(island, WorldSeed(0x10_07_AB_0A).island(3));
+        assert_ne!(island, seed.island(4));
+        assert_eq!(island.to_string().parse::<IslandId>(), Ok(island));
+
+        // Streams for different purposes are independent.
+        let terrain: u64 = island.rng("terrain").random();
+        assert_eq!(terrain, island.rng("terrain").random::<u64>());
+        assert_ne!(terrain, island.rng("props").random::<u64>());
+
+        let initializer = OverworldSceneInitializer::default();
+        let first = initializer.terrain_generator(island);
+        let second = initializer.terrain_generator(island);
+
+        for idx in 0..100 {
+            let at = Vec2::new((idx % 10) as f32, (idx / 10) as f32) * 9.0;
+            assert_eq!(
+                first.get_height_at(at).to_bits(),
+                second.get_height_at(at).to_bits()
+            );
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! # Sound cues
+//!
+//! The simulation tells the client what to play with [AudioEvent]s, without
+//! knowing anything about audio itself; headless instances just never read
+//! them.
+//!
+//! Most cues are emitted here, from other simulation events: cannons firing,
+//! mines detonating, projectiles splashing into the water, ships beginning to
+//! sink, and items being picked up. The client adds its own, such as clicks
+//! on UI elements.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{
+    construct::{
+        action::{PartActionOutcome, PartActionResult},
+        part::PartInstalledOn,
+    },
+    inventory::pickup::ItemCollectedEvent,
+    makeup::{
+        parts::{cannon::CannonPart, minelayer::MineDetonated},
+        sinking::Sinking,
+    },
+    physics::{base::PointNetwork, projectile::FastProjectile, water::WaveField},
+};
+
+/// What kind of sound to play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AudioKind {
+    CannonFire,
+    Explosion,
+
+    /// Something falling into the water.
+    Splash,
+
+    /// A hull groaning under strain.
+    Creak,
+
+    /// An item being picked up.
+    Pickup,
+
+    UiClick,
+}
+
+/// A sound to be played.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct AudioEvent {
+    pub kind: AudioKind,
+
+    /// Where the sound comes from, in world space; [None] for sounds that
+    /// are heard the same everywhere, such as UI sounds.
+    pub position: Option<Vec3>,
+
+    /// How loud the sound is, relative to its usual volume.
+    pub volume: f32,
+}
+
+impl AudioEvent {
+    /// A sound coming from somewhere in the world.
+    pub fn at(kind: AudioKind, position: Vec3) -> Self {
+        Self {
+            kind,
+            position: Some(position),
+            volume: 1.0,
+        }
+    }
+
+    /// A sound heard the same everywhere.
+    pub fn ui(kind: AudioKind) -> Self {
+        Self {
+            kind,
+            position: None,
+            volume: 1.0,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+}
+
+/// The height of the sea, without waves.
+const SEA_LEVEL: f32 = 0.0;
+
+fn cannon_fire_sounds(
+    mut outcomes: EventReader<PartActionOutcome>,
+    cannons: Query<&PartInstalledOn, With<CannonPart>>,
+    constructs: Query<&PointNetwork>,
+    mut sounds: EventWriter<AudioEvent>,
+) {
+    for outcome in outcomes.read() {
+        if outcome.result != PartActionResult::Performed {
+            continue;
+        }
+        let Ok(installed_on) = cannons.get(outcome.part) else {
+            continue;
+        };
+        if let Ok(network) = constructs.get(installed_on.get()) {
+            sounds.write(AudioEvent::at(
+                AudioKind::CannonFire,
+                network.center_of_mass(),
+            ));
+        }
+    }
+}
+
+fn explosion_sounds(
+    mut detonations: EventReader<MineDetonated>,
+    mut sounds: EventWriter<AudioEvent>,
+) {
+    sounds.write_batch(
+        detonations
+            .read()
+            .map(|detonation| AudioEvent::at(AudioKind::Explosion, detonation.at)),
+    );
+}
+
+/// Splashes projectiles which went below the water since the last tick.
+fn splash_sounds(
+    time: Res<Time>,
+    waves: Res<WaveField>,
+    projectiles: Query<&PointNetwork, With<FastProjectile>>,
+    mut sounds: EventWriter<AudioEvent>,
+) {
+    for network in &projectiles {
+        let at = network.center_of_mass();
+        let last_at = at - network.linear_velocity() * time.delta_secs();
+        let surface = SEA_LEVEL + waves.height_at(at.xz(), time.elapsed_secs());
+
+        if at.y < surface && last_at.y >= surface {
+            sounds.write(AudioEvent::at(AudioKind::Splash, at.with_y(surface)));
+        }
+    }
+}
+
+fn sinking_sounds(
+    ships: Query<&PointNetwork, Added<Sinking>>,
+    mut sounds: EventWriter<AudioEvent>,
+) {
+    for network in &ships {
+        sounds.write(AudioEvent::at(AudioKind::Creak, network.center_of_mass()));
+    }
+}
+
+fn pickup_sounds(
+    mut collected: EventReader<ItemCollectedEvent>,
+    collectors: Query<&PointNetwork>,
+    mut sounds: EventWriter<AudioEvent>,
+) {
+    for event in collected.read() {
+        if let Ok(network) = collectors.get(event.collector) {
+            sounds.write(AudioEvent::at(AudioKind::Pickup, network.center_of_mass()));
+        }
+    }
+}
+
+/// Emits sound cues from the simulation.
+///
+/// Already included in the [`CommonPlugin`](crate::common::CommonPlugin).
+pub struct SoundCuePlugin;
+
+impl Plugin for SoundCuePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AudioEvent>();
+        app.add_systems(FixedUpdate, splash_sounds);
+        app.add_systems(
+            Update,
+            (
+                cannon_fire_sounds,
+                explosion_sounds,
+                sinking_sounds,
+                pickup_sounds,
+            ),
+        );
+    }
+}
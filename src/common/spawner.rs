@@ -0,0 +1,427 @@
+//! # NPC ship spawning
+//!
+//! Islands are visited by NPC ships, both unarmed (fishers, merchants, etc.)
+//! and armed ones. When an overworld scene is set up, a [ShipSpawner] is
+//! made from its [OverworldSceneParams], which:
+//!
+//! * spawns the island's initial population, anywhere on navigable water
+//!   (see [NavGrid]);
+//! * spawns visiting ships over time, arriving from the edge of the play
+//!   area, at the rate given by [OverworldSceneParams::visit_interval].
+//!
+//! Ships which sail out of the play area are despawned.
+//!
+//! The makes of spawned ships are looked up in the
+//! [DefRegistry](super::defs::DefRegistry), by the ids in the spawner.
+//!
+//! Spawned ships are driven by an [AiController]; visitors head for a spot
+//! within the play area, and the others start out idle.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    ai::{AiController, AiState},
+    damage::Health,
+    defs::{DefId, DefRegistry, GUNBOAT, SLOOP},
+    error::LnrError,
+    makeup::{Ship, ShipMake, ShipMakeup, livery::ShipLivery},
+    physics::{
+        base::{PhysPoint, PointNetwork},
+        forces::Gravity,
+        stability::ShipStability,
+        volume::{CapsuleDef, PhysicsVolume, VolumeAxis, VolumeCollection, VolumeType},
+        water::WaterPhysics,
+    },
+    scene::init::OverworldSceneParams,
+    seed::WorldRng,
+    state::{GameState, SceneTree},
+    terrain::navigation::NavGrid,
+};
+
+/// How much health NPC ships spawn with.
+const SHIP_HEALTH: f32 = 100.0;
+
+/// An NPC ship.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NpcShip {
+    /// Whether the ship carries guns.
+    pub armed: bool,
+
+    /// Whether the ship patrols the island, rather than passing by.
+    ///
+    /// Only armed ships patrol.
+    pub patrolling: bool,
+
+    /// Whether the ship arrived during the raid, rather than being there
+    /// from the start.
+    pub visitor: bool,
+}
+
+/// Spawns NPC ships in an overworld scene.
+///
+/// Inserted when an overworld scene is set up; see the module documentation.
+#[derive(Resource, Clone, Debug)]
+pub struct ShipSpawner {
+    /// Center of the play area, on the XZ plane.
+    pub center: Vec2,
+
+    /// Visiting ships arrive this far from the center.
+    pub arrival_radius: f32,
+
+    /// Ships further than this from the center are despawned.
+    pub departure_radius: f32,
+
+    /// Y intercept of the water level.
+    pub water_level: f32,
+
+    /// Draft of spawned ships, i.e. the least depth they are spawned in.
+    pub draft: f32,
+
+    /// How many unarmed ships to spawn at first.
+    pub initial_unarmed: u8,
+
+    /// How many armed ships to spawn at first.
+    pub initial_armed: u8,
+
+    /// Chance of each armed ship patrolling the island, between 0.0 and 1.0.
+    pub patrol_chance: f32,
+
+    /// The make of unarmed ships.
+    pub unarmed_make: DefId<ShipMake>,
+
+    /// The make of armed ships.
+    pub armed_make: DefId<ShipMake>,
+
+    /// Counts down to the next visit, if ships visit at all.
+    visit_timer: Option<Timer>,
+
+    rng: WorldRng,
+}
+
+impl ShipSpawner {
+    /// Makes a ship spawner for an overworld scene.
+    ///
+    /// * `area` - The play area, on the XZ plane.
+    /// * `rng` - The random stream of the spawner, e.g. from
+    ///   [crate::common::seed::IslandId::rng].
+    pub fn new(params: &OverworldSceneParams, area: Rect, water_level: f32, rng: WorldRng) -> Self {
+        let arrival_radius = area.half_size().max_element();
+
+        Self {
+            center: area.center(),
+            arrival_radius,
+            departure_radius: arrival_radius * 1.25,
+            water_level,
+            draft: 1.5,
+            initial_unarmed: params.spawn_unarmed,
+            initial_armed: params.spawn_armed,
+            patrol_chance: params.patrol_chance_f32(),
+            unarmed_make: DefId::new(SLOOP),
+            armed_make: DefId::new(GUNBOAT),
+            visit_timer: params
+                .visit_interval()
+                .map(|interval| Timer::new(interval, TimerMode::Repeating)),
+            rng,
+        }
+    }
+
+    /// Advances the visit timer, returning how many ships arrived.
+    pub fn tick_visits(&mut self, delta: Duration) -> u32 {
+        self.visit_timer
+            .as_mut()
+            .map_or(0, |timer| timer.tick(delta).times_finished_this_tick())
+    }
+
+    /// Whether a ship at a world space XZ position has left the play area.
+    pub fn has_departed(&self, at: Vec2) -> bool {
+        at.distance(self.center) > self.departure_radius
+    }
+
+    /// Picks a random spot on navigable water within the play area, if one
+    /// is found.
+    fn random_spot(&mut self, nav_grid: &NavGrid) -> Option<Vec2> {
+        (0..50).find_map(|_| {
+            let angle = self.rng.random_range(0.0..TAU);
+            let distance = self.arrival_radius * self.rng.random::<f32>().sqrt();
+            let at = self.center + Vec2::from_angle(angle) * distance;
+
+            nav_grid.is_navigable_at(at, self.draft).then_some(at)
+        })
+    }
+
+    /// Picks a random spot on the edge of the play area.
+    fn arrival_spot(&mut self) -> Vec2 {
+        let angle = self.rng.random_range(0.0..TAU);
+        self.center + Vec2::from_angle(angle) * self.arrival_radius
+    }
+
+    /// Picks whether a visiting ship is armed, in proportion to the initial
+    /// population.
+    fn visitor_armed(&mut self) -> bool {
+        let total = self.initial_unarmed as u32 + self.initial_armed as u32;
+        total > 0 && self.rng.random_range(0..total) < self.initial_armed as u32
+    }
+
+    fn make_ship(&mut self, armed: bool, visitor: bool) -> NpcShip {
+        NpcShip {
+            armed,
+            patrolling: armed && self.rng.random::<f32>() < self.patrol_chance,
+            visitor,
+        }
+    }
+
+    /// Spawns an NPC ship at a world space XZ position, on the water.
+    ///
+    /// Fails if the ship's make is not defined.
+    fn spawn_ship(
+        &self,
+        npc: NpcShip,
+        mut controller: AiController,
+        at: Vec2,
+        scene_tree: Entity,
+        defs: &DefRegistry,
+        commands: &mut Commands,
+    ) -> Result<(), LnrError> {
+        controller.draft = self.draft;
+
+        let make_id = if npc.armed {
+            &self.armed_make
+        } else {
+            &self.unarmed_make
+        };
+        let make = defs.get(make_id).cloned().ok_or_else(|| {
+            LnrError::invalid_state(format!("Tried to spawn a ship of unknown make {make_id:?}"))
+        })?;
+        let makeup = ShipMakeup::new(make, ShipLivery::default());
+        let position = at.extend(self.water_level).xzy();
+
+        let points = PointNetwork::from(
+            [PhysPoint::new(
+                position,
+                Vec3::ZERO,
+                makeup.get_total_mass(),
+            )]
+            .into_iter(),
+        );
+
+        let ship = commands
+            .spawn((
+                Ship { makeup },
+                Health::new(SHIP_HEALTH),
+                npc,
+                controller,
+                points,
+                VolumeCollection {
+                    volumes: vec![PhysicsVolume {
+                        point_idx: 0,
+                        volume_type: VolumeType::Capsule(CapsuleDef::new(1.5, 3.0, VolumeAxis::Z)),
+                    }],
+                },
+                WaterPhysics {
+                    water_level: self.water_level,
+                    ..default()
+                },
+                Gravity::default(),
+                ShipStability::default(),
+                Transform::from_translation(position),
+                Visibility::Visible,
+            ))
+            .id();
+
+        commands.entity(scene_tree).add_child(ship);
+        Ok(())
+    }
+}
+
+fn spawn_initial_ships(
+    mut commands: Commands,
+    mut spawner: ResMut<ShipSpawner>,
+    nav_grid: Res<NavGrid>,
+    defs: Res<DefRegistry>,
+    scene_tree: Query<Entity, With<SceneTree>>,
+) -> Result {
+    let scene_tree = scene_tree.single()?;
+
+    let armed = (0..spawner.initial_armed).map(|_| true);
+    let unarmed = (0..spawner.initial_unarmed).map(|_| false);
+
+    for armed in armed.chain(unarmed).collect::<Vec<_>>() {
+        let Some(at) = spawner.random_spot(&nav_grid) else {
+            warn!("Found no navigable water to spawn an NPC ship in");
+            break;
+        };
+
+        let npc = spawner.make_ship(armed, false);
+        spawner.spawn_ship(
+            npc,
+            AiController::default(),
+            at,
+            scene_tree,
+            &defs,
+            &mut commands,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn spawn_visiting_ships(
+    mut commands: Commands,
+    mut spawner: ResMut<ShipSpawner>,
+    time: Res<Time>,
+    nav_grid: Option<Res<NavGrid>>,
+    defs: Res<DefRegistry>,
+    scene_tree: Query<Entity, With<SceneTree>>,
+) -> Result {
+    let visits = spawner.tick_visits(time.delta());
+    if visits == 0 {
+        return Ok(());
+    }
+
+    let scene_tree = scene_tree.single()?;
+
+    for _ in 0..visits {
+        let at = spawner.arrival_spot();
+        let armed = spawner.visitor_armed();
+        let npc = spawner.make_ship(armed, true);
+
+        // Visitors head somewhere within the play area.
+        let destination = nav_grid
+            .as_deref()
+            .and_then(|nav_grid| spawner.random_spot(nav_grid))
+            .unwrap_or(spawner.center);
+        let controller = AiController::new(AiState::Travel { destination });
+
+        spawner.spawn_ship(npc, controller, at, scene_tree, &defs, &mut commands)?;
+    }
+
+    Ok(())
+}
+
+fn despawn_departed_ships(
+    mut commands: Commands,
+    spawner: Res<ShipSpawner>,
+    ships: Query<(Entity, &PointNetwork), With<NpcShip>>,
+) {
+    for (entity, points) in &ships {
+        if spawner.has_departed(points.center_of_mass().xz()) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// NPC ship spawning plugin.
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DefRegistry>();
+        app.add_systems(
+            Update,
+            (
+                spawn_initial_ships.run_if(resource_added::<ShipSpawner>),
+                spawn_visiting_ships,
+                despawn_departed_ships,
+            )
+                .chain()
+                .run_if(in_state(GameState::Overworld).and(resource_exists::<ShipSpawner>)),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn ships_spawn_on_water_and_leave() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, SpawnerPlugin));
+        app.init_state::<GameState>();
+        app.insert_state(GameState::Overworld);
+        app.world_mut().spawn(SceneTree);
+
+        // A round island of radius 40, in a play area of radius 100.
+        let area = Rect::new(-100.0, -100.0, 100.0, 100.0);
+        app.insert_resource(NavGrid::from_heightfield(
+            area,
+            |at| if at.length() < 40.0 { 5.0 } else { -10.0 },
+            0.0,
+            4.0,
+        ));
+
+        let params = OverworldSceneParams {
+            spawn_unarmed: 6,
+            spawn_armed: 3,
+            visit_frequency: 0,
+            ..default()
+        };
+        app.insert_resource(ShipSpawner::new(
+            &params,
+            area,
+            0.0,
+            WorldRng::seed_from_u64(3),
+        ));
+        app.update();
+
+        let mut query = app.world_mut().query::<(&Transform, &NpcShip)>();
+        let ships = query.iter(app.world()).collect::<Vec<_>>();
+
+        assert_eq!(ships.len(), 9);
+        assert_eq!(ships.iter().filter(|(_, npc)| npc.armed).count(), 3);
+        assert!(ships.iter().all(|(_, npc)| !npc.visitor));
+        assert!(ships.iter().all(|(transform, _)| {
+            let at = transform.translation.xz();
+            at.length() > 40.0 && at.length() <= 100.0
+        }));
+
+        // The initial population is only spawned once.
+        app.update();
+        let mut query = app.world_mut().query::<&NpcShip>();
+        assert_eq!(query.iter(app.world()).count(), 9);
+
+        // Ships leaving the play area are despawned, going by where their
+        // physics points are.
+        let mut query = app.world_mut().query::<&mut PointNetwork>();
+        for mut points in query.iter_mut(app.world_mut()) {
+            for point in &mut points.points {
+                point.pos.x += 500.0;
+            }
+        }
+        app.update();
+        let mut query = app.world_mut().query::<&NpcShip>();
+        assert_eq!(query.iter(app.world()).count(), 0);
+
+        // Visits come at the rate given by the scene parameters.
+        let mut spawner = ShipSpawner::new(
+            &OverworldSceneParams {
+                visit_frequency: 16,
+                ..default()
+            },
+            area,
+            0.0,
+            WorldRng::seed_from_u64(3),
+        );
+        assert_eq!(spawner.tick_visits(Duration::from_secs(30)), 0);
+        assert_eq!(spawner.tick_visits(Duration::from_secs(150)), 3);
+    }
+}
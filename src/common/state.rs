@@ -21,6 +21,12 @@
 
 use bevy::{input::mouse::MouseMotion, prelude::*, window::PrimaryWindow};
 
+use super::{
+    ai::patrol::PatrolRoutes,
+    spawner::ShipSpawner,
+    terrain::{chunk::TerrainChunkIndex, navigation::NavGrid},
+};
+
 /// The current superstate of the game.
 ///
 /// A game typically cycles between:
@@ -98,16 +104,23 @@ fn setup_intermission(mut commands: Commands, mut ev_scene_setup: EventWriter<Sc
     ev_scene_setup.write(SceneSetupEvent::new(tree));
 }
 
-fn cleanup_start(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) {
-    commands.entity(q_tree.single().unwrap().0).despawn();
+fn cleanup_start(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) -> Result {
+    commands.entity(q_tree.single()?.0).despawn();
+    Ok(())
 }
 
-fn cleanup_overworld(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) {
-    commands.entity(q_tree.single().unwrap().0).despawn();
+fn cleanup_overworld(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) -> Result {
+    commands.entity(q_tree.single()?.0).despawn();
+    commands.remove_resource::<TerrainChunkIndex>();
+    commands.remove_resource::<NavGrid>();
+    commands.remove_resource::<ShipSpawner>();
+    commands.remove_resource::<PatrolRoutes>();
+    Ok(())
 }
 
-fn cleanup_intermission(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) {
-    commands.entity(q_tree.single().unwrap().0).despawn();
+fn cleanup_intermission(mut commands: Commands, q_tree: Query<(Entity, &SceneTree)>) -> Result {
+    commands.entity(q_tree.single()?.0).despawn();
+    Ok(())
 }
 
 fn input_handler_start(
@@ -0,0 +1,212 @@
+//! # Terrain biomes
+//!
+//! The biome layer is the last stage of the terrain generator. It assigns a
+//! [TerrainBiome] to every spot of terrain, from its height, its slope, and a
+//! secondary noise channel which breaks up otherwise uniform areas, such as
+//! forests within grassland.
+//!
+//! Biomes are included in terrain meshes as vertex colors, and can be looked
+//! up anywhere, e.g. by prop spawners, using
+//! [super::generator::TerrainGenerator::get_biome_at].
+//!
+//! Not to be confused with [crate::common::scene::biome::IslandBiome], which
+//! themes an island as a whole.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::noise::FractalNoise;
+
+/// The biome of a spot of terrain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TerrainBiome {
+    /// Sand along the shore, and the seabed.
+    Beach,
+
+    /// Open grass.
+    #[default]
+    Grassland,
+
+    /// Wooded areas.
+    Forest,
+
+    /// Bare rock, on steep slopes and mountain tops.
+    Rock,
+
+    /// Ash and cooled lava, high up on volcanic islands.
+    Volcanic,
+}
+
+impl TerrainBiome {
+    /// All terrain biomes.
+    pub const ALL: [TerrainBiome; 5] = [
+        Self::Beach,
+        Self::Grassland,
+        Self::Forest,
+        Self::Rock,
+        Self::Volcanic,
+    ];
+
+    /// The color of terrain of this biome, used as its vertex color.
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Beach => Color::srgb_u8(215, 200, 140),
+            Self::Grassland => Color::srgb_u8(80, 190, 45),
+            Self::Forest => Color::srgb_u8(35, 110, 40),
+            Self::Rock => Color::srgb_u8(120, 125, 115),
+            Self::Volcanic => Color::srgb_u8(60, 50, 48),
+        }
+    }
+}
+
+/// The parameters used when assigning biomes.
+///
+/// Heights are in terrain generator units, where 0.0 is sea level and 1.0
+/// the highest terrain can be. Slopes are in those same units, per noise
+/// tile of the terrain generator.
+#[derive(Clone, Debug)]
+pub struct BiomeParams {
+    /// Terrain below this height is beach.
+    pub beach_height: f32,
+
+    /// Terrain steeper than this is rock.
+    pub rock_slope: f32,
+
+    /// Terrain above this height is rock.
+    pub rock_height: f32,
+
+    /// Terrain above this height is volcanic, if set.
+    ///
+    /// Takes precedence over [Self::rock_height].
+    pub volcanic_height: Option<f32>,
+
+    /// Terrain where the secondary noise channel is above this value is
+    /// forest, rather than grassland.
+    pub forest_threshold: f32,
+}
+
+impl Default for BiomeParams {
+    fn default() -> Self {
+        Self {
+            beach_height: 0.04,
+            rock_slope: 1.2,
+            rock_height: 0.5,
+            volcanic_height: None,
+            forest_threshold: 0.05,
+        }
+    }
+}
+
+/// The biome assignment stage of the terrain generator.
+#[derive(Clone, Default)]
+pub struct BiomeLayer {
+    /// The biome assignment parameters.
+    pub params: BiomeParams,
+
+    /// The secondary noise channel.
+    ///
+    /// Sampled at the same coordinates as the terrain noise. Without it,
+    /// there are no forests.
+    pub noise: Option<FractalNoise>,
+}
+
+impl BiomeLayer {
+    /// A biome layer with a secondary noise channel.
+    pub fn new(params: BiomeParams, noise: FractalNoise) -> Self {
+        Self {
+            params,
+            noise: Some(noise),
+        }
+    }
+
+    /// Assigns a biome.
+    ///
+    /// * `noise_at` - Where to sample the secondary noise channel, in noise
+    ///   tiles.
+    /// * `height` - Height of the terrain.
+    /// * `slope` - Length of the height gradient.
+    pub fn classify(&self, noise_at: Vec2, height: f32, slope: f32) -> TerrainBiome {
+        let params = &self.params;
+
+        if height < params.beach_height {
+            return TerrainBiome::Beach;
+        }
+
+        if params.volcanic_height.is_some_and(|min| height > min) {
+            return TerrainBiome::Volcanic;
+        }
+
+        if slope > params.rock_slope || height > params.rock_height {
+            return TerrainBiome::Rock;
+        }
+
+        let secondary = self.noise.as_ref().map_or(f32::NEG_INFINITY, |noise| {
+            let max = Vec2::new(noise.get_width(), noise.get_height()) - 0.001;
+            let noise_at = noise_at.clamp(Vec2::ZERO, max.max(Vec2::ZERO));
+            noise.get_influence_at(noise_at.x, noise_at.y)
+        });
+
+        if secondary > params.forest_threshold {
+            TerrainBiome::Forest
+        } else {
+            TerrainBiome::Grassland
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn classifies_by_height_and_slope() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let layer = BiomeLayer::new(
+            BiomeParams::default(),
+            FractalNoise::random_octaves(4.0, 4.0, 2.try_into().unwrap(), &mut rng),
+        );
+
+        assert_eq!(layer.classify(Vec2::ONE, -0.5, 0.0), TerrainBiome::Beach);
+        assert_eq!(layer.classify(Vec2::ONE, 0.3, 10.0), TerrainBiome::Rock);
+        assert_eq!(layer.classify(Vec2::ONE, 0.9, 0.0), TerrainBiome::Rock);
+
+        // The secondary noise channel yields both forest and grassland.
+        let lowlands = (0..400)
+            .map(|idx| Vec2::new((idx % 20) as f32, (idx / 20) as f32) * 0.2)
+            .map(|at| layer.classify(at, 0.3, 0.0))
+            .collect::<Vec<_>>();
+        assert!(lowlands.contains(&TerrainBiome::Forest));
+        assert!(lowlands.contains(&TerrainBiome::Grassland));
+
+        let volcanic = BiomeLayer {
+            params: BiomeParams {
+                volcanic_height: Some(0.5),
+                ..default()
+            },
+            noise: None,
+        };
+        assert_eq!(
+            volcanic.classify(Vec2::ONE, 0.9, 0.0),
+            TerrainBiome::Volcanic
+        );
+        assert_eq!(
+            volcanic.classify(Vec2::ONE, 0.3, 0.0),
+            TerrainBiome::Grassland
+        );
+    }
+}
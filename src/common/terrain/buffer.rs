@@ -1,6 +1,9 @@
 //! # Terrain buffer.
 //!
 //! A terrain heightmap can be meshed.
+//!
+//! It may also have caves carved into it (see [super::cave]), which are
+//! meshed separately, using [TerrainBuffer::cave_mesh].
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -15,9 +18,11 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use std::ops::Range;
+use std::{ops::Range, sync::Arc};
+
+use crate::common::{prelude::*, scratch::ScratchBuffer};
 
-use crate::common::prelude::*;
+use super::{density::DensityField, erosion::erode};
 use bevy::{
     asset::RenderAssetUsages,
     prelude::*,
@@ -45,6 +50,12 @@ pub struct TerrainBuffer {
 
     /// The range of values that this buffer holds.
     height_range: Range<f32>,
+
+    /// The biome of every sample, laid out like [Self::values], if known.
+    biomes: Option<Vec<TerrainBiome>>,
+
+    /// Space carved out below the surface, if any.
+    caves: Option<DensityField>,
 }
 
 /// A request to compute the gradient at a given X and Y position of the
@@ -58,6 +69,160 @@ pub struct GradientLookupRequest {
 }
 
 impl TerrainBuffer {
+    /// Makes a terrain buffer from a heightmap, with its rows laid out one
+    /// after another.
+    ///
+    /// `resolution` is the spacing, in world space units, between vertices.
+    pub fn from_heights(width: usize, height: usize, resolution: f32, values: Vec<f32>) -> Self {
+        assert_eq!(values.len(), width * height);
+
+        let height_range = values
+            .iter()
+            .copied()
+            .fold(f32::INFINITY..f32::NEG_INFINITY, |range, value| {
+                range.start.min(value)..range.end.max(value)
+            });
+
+        Self {
+            resolution,
+            width,
+            height,
+            values,
+            height_range,
+            biomes: None,
+            caves: None,
+        }
+    }
+
+    /// Adds the biome of every sample, laid out like the heightmap.
+    ///
+    /// Meshes made from this buffer will be colored by biome.
+    pub fn with_biomes(mut self, biomes: Vec<TerrainBiome>) -> Self {
+        assert_eq!(biomes.len(), self.values.len());
+        self.biomes = Some(biomes);
+        self
+    }
+
+    /// Adds caves, given the space carved out below the surface.
+    ///
+    /// The density field must be laid out over this terrain; see
+    /// [DensityField::over_terrain].
+    pub fn with_caves(mut self, caves: DensityField) -> Self {
+        assert_eq!(caves.spacing(), self.resolution);
+        assert_eq!(
+            caves.size().xz(),
+            UVec2::new(self.width as u32, self.height as u32)
+        );
+        self.caves = Some(caves);
+        self
+    }
+
+    /// The space carved out below the surface, if any.
+    ///
+    /// Densities are positive within caves.
+    pub fn caves(&self) -> Option<&DensityField> {
+        self.caves.as_ref()
+    }
+
+    /// Whether caves come near the column of a quad.
+    fn is_cave_column(&self, quad_x: usize, quad_y: usize) -> bool {
+        let Some(caves) = &self.caves else {
+            return false;
+        };
+
+        (0..caves.size().y).any(|layer| {
+            [(0, 0), (1, 0), (0, 1), (1, 1)].iter().any(|&(x, y)| {
+                let sample = UVec3::new((quad_x + x) as u32, layer, (quad_y + y) as u32);
+                caves.get_value_at(sample) > -caves.spacing()
+            })
+        })
+    }
+
+    /// Heights of the corners of a quad.
+    fn quad_heights(&self, quad_x: usize, quad_y: usize) -> [f32; 4] {
+        [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| self.get_value_at(quad_x + x, quad_y + y))
+    }
+
+    /// Whether a quad of the heightmap is left out of [Self::to_mesh], for
+    /// its surface is part of [Self::cave_mesh] instead.
+    fn is_cave_quad(&self, quad_x: usize, quad_y: usize) -> bool {
+        let Some(caves) = &self.caves else {
+            return false;
+        };
+
+        let bottom = caves.origin().y;
+        let top = caves.sample_position(caves.size() - 1).y;
+
+        self.is_cave_column(quad_x, quad_y)
+            && self
+                .quad_heights(quad_x, quad_y)
+                .iter()
+                .all(|height| (bottom..top).contains(height))
+    }
+
+    /// The mesh of the walls of the caves, and of the surface right around
+    /// their openings, if there are any caves.
+    pub fn cave_mesh(&self) -> Option<Mesh> {
+        let caves = self.caves.as_ref()?;
+
+        // Open wherever above ground or carved.
+        let open = DensityField::from_fn(caves.origin(), caves.spacing(), caves.size(), |pos| {
+            (pos.y - self.get_height_at(pos.x, pos.z)).max(caves.density_at(pos))
+        });
+
+        let mut mesh = open.to_mesh_where(|cell| {
+            let (quad_x, quad_y) = (cell.x as usize, cell.z as usize);
+            let cell_top = open.sample_position(cell + UVec3::Y).y;
+
+            // Elsewhere, the surface is already part of the heightmap mesh.
+            self.is_cave_column(quad_x, quad_y)
+                && (self.is_cave_quad(quad_x, quad_y)
+                    || self
+                        .quad_heights(quad_x, quad_y)
+                        .iter()
+                        .all(|&height| height >= cell_top))
+        })?;
+
+        let color = TerrainBiome::Rock.color().to_linear().to_f32_array();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![color; mesh.count_vertices()]);
+
+        Some(mesh)
+    }
+
+    /// The biome of a sample, if known.
+    pub fn get_biome_at_value(&self, value_x: usize, value_y: usize) -> Option<TerrainBiome> {
+        let biomes = self.biomes.as_ref()?;
+        Some(
+            biomes[value_y.min(self.get_vertex_height() - 1) * self.get_vertex_width()
+                + value_x.min(self.get_vertex_width() - 1)],
+        )
+    }
+
+    /// The biome nearest to a point along the terrain, if known.
+    pub fn get_biome_at(&self, pos_x: f32, pos_y: f32) -> Option<TerrainBiome> {
+        let mapped_x = (pos_x + self.get_real_width() * 0.5) / self.resolution;
+        let mapped_y = (pos_y + self.get_real_height() * 0.5) / self.resolution;
+
+        self.get_biome_at_value(
+            mapped_x.round().max(0.0) as usize,
+            mapped_y.round().max(0.0) as usize,
+        )
+    }
+
+    /// The spacing, in world space units, between vertices.
+    pub fn get_resolution(&self) -> f32 {
+        self.resolution
+    }
+
+    /// Position of a vertex, in the terrain's local space.
+    pub fn get_vertex_position(&self, value_x: usize, value_y: usize) -> Vec3 {
+        Vec3::new(
+            value_x as f32 * self.resolution - self.get_real_width() / 2.0,
+            self.get_value_at(value_x, value_y),
+            value_y as f32 * self.resolution - self.get_real_height() / 2.0,
+        )
+    }
+
     pub fn get_vertex_width(&self) -> usize {
         self.width
     }
@@ -169,6 +334,9 @@ impl TerrainBuffer {
     }
 
     /// Create a new TerrainBuffer by using a TerrainGenerator to initialize.
+    ///
+    /// If the generator has an erosion stage, the heightmap is eroded before
+    /// biomes are assigned; see [super::erosion].
     pub fn generate<TMA, DC>(
         generator: TerrainGenerator<TMA, DC>,
         resolution: f32,
@@ -185,14 +353,35 @@ impl TerrainBuffer {
         debug_assert!(width > 1);
         debug_assert!(height > 1);
 
-        let values = (0_usize..width * height)
+        let mut scratch = ScratchBuffer::new();
+        let mut values = (0_usize..width * height)
             .map(|idx| {
                 let x = idx % width;
                 let y = idx / width;
                 let x = x as f32 * resolution;
                 let y = y as f32 * resolution;
 
-                generator.get_height_at(Vec2::new(x, y)) * vert_scale
+                generator.get_height_at_with(Vec2::new(x, y), &mut scratch)
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(params) = generator.erosion() {
+            erode(&mut values, width, height, resolution, params);
+        }
+
+        // Gradients are taken from the neighbouring samples.
+        let sample = |x: usize, y: usize| values[y.min(height - 1) * width + x.min(width - 1)];
+        let biomes = (0_usize..width * height)
+            .map(|idx| {
+                let x = idx % width;
+                let y = idx / width;
+                let gradient = Vec2::new(
+                    sample(x + 1, y) - sample(x.saturating_sub(1), y),
+                    sample(x, y + 1) - sample(x, y.saturating_sub(1)),
+                ) / (resolution * 2.0);
+                let at = Vec2::new(x as f32, y as f32) * resolution;
+
+                generator.classify_biome(at, sample(x, y), gradient)
             })
             .collect::<Vec<_>>();
 
@@ -200,8 +389,10 @@ impl TerrainBuffer {
             width,
             height,
             resolution: scale,
-            values,
+            values: values.into_iter().map(|value| value * vert_scale).collect(),
             height_range: -vert_scale..vert_scale,
+            biomes: Some(biomes),
+            caves: None,
         }
     }
 
@@ -214,56 +405,83 @@ impl TerrainBuffer {
         debug_assert!(self.width > 1);
         debug_assert!(self.height > 1);
 
-        let quad_width = self.get_vertex_width() - 1;
         let center_x = self.get_real_width() / 2.0;
         let center_y = self.get_real_height() / 2.0;
 
-        Mesh::new(
+        let positions = self
+            .mesh_vertex_values()
+            .map(|(value_x, value_y)| {
+                // horizontal
+                let vert_x = value_x as f32 * self.resolution - center_x;
+                let vert_z = value_y as f32 * self.resolution - center_y;
+                // vertical
+                let vert_y = self.get_value_at(value_x, value_y);
+
+                [vert_x, vert_y, vert_z]
+            })
+            .collect::<Vec<_>>();
+        let num_vertices = positions.len() as u32;
+
+        let mut mesh = Mesh::new(
             PrimitiveTopology::TriangleList,
             RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
         )
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_POSITION,
-            (0..self.get_num_tris() * 3)
-                .map(|vertex_idx| {
-                    let tri_idx = vertex_idx / 3;
-                    let vert_in_tri = vertex_idx % 3;
-                    let quad_idx = tri_idx / 2;
-
-                    // vertex quad, not perlin quad
-                    let quad_x = quad_idx % quad_width;
-                    let quad_y = quad_idx / quad_width;
-
-                    use QuadCorner::*;
-
-                    let which_corner = match tri_idx % 2 {
-                        0 => {
-                            // even triangles: NW, NE, SW
-                            [NE, NW, SW][vert_in_tri]
-                        }
-                        1 => {
-                            // odd triangles: NE, SW, SE
-                            [NE, SW, SE][vert_in_tri]
-                        }
-                        _ => unreachable!(),
-                    };
-
-                    // horizontal
-                    let value_x = quad_x + which_corner.x();
-                    let value_y = quad_y + which_corner.y();
-                    let vert_x = value_x as f32 * self.resolution - center_x;
-                    let vert_z = value_y as f32 * self.resolution - center_y;
-                    // vertical
-                    let vert_y = self.get_value_at(value_x, value_y);
-
-                    [vert_x, vert_y, vert_z]
-                })
-                .collect::<Vec<_>>(),
-        )
-        .with_inserted_indices(Indices::U32(
-            (0_u32..(self.get_num_tris() * 3) as u32).collect::<Vec<_>>(),
-        ))
-        .with_computed_normals()
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32((0..num_vertices).collect::<Vec<_>>()))
+        .with_computed_normals();
+
+        if self.biomes.is_some() {
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_COLOR,
+                self.mesh_vertex_values()
+                    .map(|(value_x, value_y)| {
+                        let biome = self.get_biome_at_value(value_x, value_y).unwrap();
+                        biome.color().to_linear().to_f32_array()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        mesh
+    }
+
+    /// The sample of every vertex of the mesh made by [Self::to_mesh], in
+    /// order.
+    ///
+    /// Quads which are part of [Self::cave_mesh] are left out.
+    fn mesh_vertex_values(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let quad_width = self.get_vertex_width() - 1;
+
+        (0..self.get_num_tris() * 3)
+            .filter(move |vertex_idx| {
+                let quad_idx = vertex_idx / 6;
+                !self.is_cave_quad(quad_idx % quad_width, quad_idx / quad_width)
+            })
+            .map(move |vertex_idx| {
+                let tri_idx = vertex_idx / 3;
+                let vert_in_tri = vertex_idx % 3;
+                let quad_idx = tri_idx / 2;
+
+                // vertex quad, not perlin quad
+                let quad_x = quad_idx % quad_width;
+                let quad_y = quad_idx / quad_width;
+
+                use QuadCorner::*;
+
+                let which_corner = match tri_idx % 2 {
+                    0 => {
+                        // even triangles: NW, NE, SW
+                        [NE, NW, SW][vert_in_tri]
+                    }
+                    1 => {
+                        // odd triangles: NE, SW, SE
+                        [NE, SW, SE][vert_in_tri]
+                    }
+                    _ => unreachable!(),
+                };
+
+                (quad_x + which_corner.x(), quad_y + which_corner.y())
+            })
     }
 
     /// Create an entity bundle from this Terrain.
@@ -278,15 +496,20 @@ impl TerrainBuffer {
 /// It msut hold a [TerrainBuffer].
 ///
 /// Only a single Terrain entity will be loaded by the terrain renderer.
+///
+/// The buffer is shared, so that chunked terrain (see
+/// [super::chunk::TerrainChunkIndex]) can query it too.
 #[derive(Component)]
 pub struct TerrainMarker {
     /// The buffer of this terrain.
-    pub buffer: TerrainBuffer,
+    pub buffer: Arc<TerrainBuffer>,
 }
 
 impl TerrainMarker {
     /// Construct a new TerrainMarker and initialize it with a [TerrainBuffer].
     pub fn new(buffer: TerrainBuffer) -> Self {
-        Self { buffer }
+        Self {
+            buffer: Arc::new(buffer),
+        }
     }
 }
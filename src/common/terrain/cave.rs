@@ -0,0 +1,135 @@
+//! # Terrain caves
+//!
+//! An optional volumetric layer of terrain, which carves sea caves out of the
+//! ground below the heightmap surface, using 3D noise. Caves are only carved
+//! within a band of heights around sea level, and well below the surface, so
+//! that they open sideways into cliffs rather than up into the sky.
+//!
+//! The carved space is stored as a [DensityField] alongside the
+//! [TerrainBuffer] (see [TerrainBuffer::with_caves]), which is meshed
+//! separately from the heightmap, and taken into account by the terrain's
+//! signed distance field, and thus by terrain collision.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+
+use super::{buffer::TerrainBuffer, density::DensityField, noise::NoiseVolume};
+
+/// The cave carving layer.
+///
+/// Heights and sizes are in the terrain's local space, where sea level is at
+/// a height of zero.
+#[derive(Clone, Debug)]
+pub struct CaveLayer {
+    /// The noise which caves are carved from.
+    pub noise: NoiseVolume,
+
+    /// Size of each noise tile; caves are roughly this large.
+    pub size: f32,
+
+    /// Space is carved where the noise is above this value.
+    ///
+    /// The higher, the fewer and narrower caves are.
+    pub threshold: f32,
+
+    /// Caves are never carved below this height.
+    pub floor: f32,
+
+    /// Caves are never carved above this height.
+    pub ceiling: f32,
+
+    /// Caves are never carved closer than this below the surface.
+    pub min_cover: f32,
+}
+
+impl CaveLayer {
+    /// A cave layer with default parameters, carved from the given noise.
+    pub fn new(noise: NoiseVolume) -> Self {
+        Self {
+            noise,
+            size: 30.0,
+            threshold: 0.25,
+            floor: -12.0,
+            ceiling: 20.0,
+            min_cover: 6.0,
+        }
+    }
+
+    /// How far into the open carved space a point is; negative if it is not
+    /// carved.
+    ///
+    /// * `pos` - The point, in the terrain's local space.
+    /// * `offset` - Added to points before sampling the noise, e.g. the
+    ///   translation of the terrain, so that neighbouring chunks match.
+    /// * `surface` - The height of the terrain's surface above the point.
+    pub fn carved_density(&self, pos: Vec3, offset: Vec3, surface: f32) -> f32 {
+        let noise = self.noise.get_influence_at((pos + offset) / self.size);
+
+        ((noise - self.threshold) * self.size * 0.5)
+            .min(pos.y - self.floor)
+            .min(self.ceiling - pos.y)
+            .min(surface - self.min_cover - pos.y)
+    }
+
+    /// Carves caves into a terrain, returning the carved space.
+    ///
+    /// See [Self::carved_density] for `offset`.
+    pub fn carve(&self, buffer: &TerrainBuffer, offset: Vec3) -> DensityField {
+        DensityField::over_terrain(buffer, self.floor..self.ceiling, |pos| {
+            self.carved_density(pos, offset, buffer.get_height_at(pos.x, pos.z))
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::terrain::{buffer::TerrainBuffer, density::DensityField};
+
+    /// A flat terrain 20 units above sea level, with a spherical cave of
+    /// radius 4 carved at sea level.
+    fn hollow_ground() -> TerrainBuffer {
+        let size = 16;
+        let buffer = TerrainBuffer::from_heights(size, size, 1.0, vec![20.0; size * size]);
+        let caves = DensityField::over_terrain(&buffer, -8.0..8.0, |pos| 4.0 - pos.length());
+
+        buffer.with_caves(caves)
+    }
+
+    #[test]
+    fn caves_are_open_space() {
+        let terrain = hollow_ground();
+        let sdf = terrain.sdf_field();
+
+        // Within the cave, points are in the open, despite being below the
+        // heightmap.
+        assert!(sdf.sdf(Vec3::ZERO) > 3.0);
+        assert!(sdf.sdf(Vec3::new(0.0, -6.0, 0.0)) < 0.0);
+        assert!(sdf.sdf(Vec3::new(0.0, 10.0, 0.0)) < 0.0);
+
+        // Near the cave's wall, objects are pushed back into the cave.
+        let normal = sdf.normal(Vec3::new(3.9, 0.0, 0.0));
+        assert!(normal.x < -0.5, "{normal}");
+
+        // The cave's walls are meshed separately; the heightmap surface
+        // above it is left untouched.
+        assert!(terrain.cave_mesh().is_some());
+        assert_eq!(
+            terrain.to_mesh().count_vertices(),
+            terrain.get_num_tris() * 3
+        );
+    }
+}
@@ -0,0 +1,624 @@
+//! # Chunked terrain
+//!
+//! Larger islands are too big to generate and mesh as a single
+//! [TerrainBuffer]. Instead, the terrain is split into square chunks, each
+//! with its own buffer, mesh and entity, which are generated lazily around
+//! [TerrainStreamer]s (usually players) and unloaded once far from all of
+//! them.
+//!
+//! The [TerrainChunkIndex] resource keeps track of loaded chunks, and answers
+//! height queries anywhere on the terrain, across chunk boundaries. Where no
+//! chunk is loaded, it samples the terrain generator directly, so queries
+//! never depend on what happens to be loaded.
+//!
+//! Chunks share their border vertices with their neighbours, so there are no
+//! seams between them.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::sync::Arc;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::common::{
+    diagnostics::AllocationScope, math::lerp, scratch::ScratchBuffer, state::SceneTree,
+};
+
+use super::{
+    biome::TerrainBiome,
+    buffer::{TerrainBuffer, TerrainMarker},
+    cave::CaveLayer,
+    generator::DefaultTerrainGenerator,
+};
+
+/// Where terrain heights come from, and how they are laid out in the world.
+#[derive(Clone)]
+pub struct TerrainChunkSource {
+    /// The terrain generator.
+    generator: DefaultTerrainGenerator,
+
+    /// Spacing between vertices, in generator coordinates.
+    sample_spacing: f32,
+
+    /// Spacing between vertices, in world space units.
+    scale: f32,
+
+    /// Scale of the generated heights.
+    vert_scale: f32,
+
+    /// World space position of the first vertex, at a height of zero.
+    origin: Vec3,
+
+    /// Number of vertices along X and Z.
+    vertices: UVec2,
+}
+
+impl TerrainChunkSource {
+    /// Lays out a terrain generator in the world.
+    ///
+    /// The parameters are the same as in [TerrainBuffer::generate]; `center`
+    /// is where the middle of the terrain goes, as would be the translation
+    /// of a single terrain entity.
+    pub fn new(
+        generator: DefaultTerrainGenerator,
+        sample_spacing: f32,
+        scale: f32,
+        vert_scale: f32,
+        center: Vec3,
+    ) -> Self {
+        let vertices = UVec2::new(
+            (generator.get_width() / sample_spacing).floor() as u32,
+            (generator.get_height() / sample_spacing).floor() as u32,
+        );
+
+        Self {
+            generator,
+            sample_spacing,
+            scale,
+            vert_scale,
+            origin: center - Vec3::new(vertices.x as f32, 0.0, vertices.y as f32) * scale * 0.5,
+            vertices,
+        }
+    }
+
+    /// The terrain generator.
+    pub fn generator(&self) -> &DefaultTerrainGenerator {
+        &self.generator
+    }
+
+    /// World space XZ position of a point in generator coordinates, such as
+    /// those of [super::hydrology::Waterway]s.
+    pub fn to_world(&self, at: Vec2) -> Vec2 {
+        self.origin.xz() + at / self.sample_spacing * self.scale
+    }
+
+    /// The world space heights of the lowest and highest terrain can be.
+    pub fn height_range(&self) -> (f32, f32) {
+        (self.origin.y, self.origin.y + self.vert_scale)
+    }
+
+    /// The area covered by the terrain, on the XZ plane.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_corners(
+            self.origin.xz(),
+            self.origin.xz() + (self.vertices - 1).as_vec2() * self.scale,
+        )
+    }
+
+    /// Height of a vertex, relative to the origin.
+    ///
+    /// Vertices outside the terrain take the height of the nearest edge.
+    fn vertex_height(&self, vertex: IVec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let vertex = vertex.clamp(IVec2::ZERO, self.vertices.as_ivec2() - 1);
+
+        self.generator
+            .get_height_at_with(vertex.as_vec2() * self.sample_spacing, scratch)
+            * self.vert_scale
+    }
+
+    /// Biome of a vertex, given its height and those of its neighbours, as
+    /// returned by [Self::vertex_height].
+    fn classify_vertex(
+        &self,
+        vertex: IVec2,
+        height: f32,
+        west: f32,
+        east: f32,
+        north: f32,
+        south: f32,
+    ) -> TerrainBiome {
+        let vertex = vertex.clamp(IVec2::ZERO, self.vertices.as_ivec2() - 1);
+        let gradient =
+            Vec2::new(east - west, south - north) / (self.vert_scale * self.sample_spacing * 2.0);
+
+        self.generator.classify_biome(
+            vertex.as_vec2() * self.sample_spacing,
+            height / self.vert_scale,
+            gradient,
+        )
+    }
+
+    /// Biome of the vertex nearest to a world space XZ position.
+    ///
+    /// Matches the biomes of chunk meshes.
+    pub fn biome_at(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> TerrainBiome {
+        let vertex = ((at - self.origin.xz()) / self.scale).round().as_ivec2();
+        let mut height = |offset: IVec2| self.vertex_height(vertex + offset, scratch);
+
+        let center = height(IVec2::ZERO);
+        let west = height(IVec2::NEG_X);
+        let east = height(IVec2::X);
+        let north = height(IVec2::NEG_Y);
+        let south = height(IVec2::Y);
+
+        self.classify_vertex(vertex, center, west, east, north, south)
+    }
+
+    /// Samples the height of the terrain at a world space XZ position,
+    /// interpolating between vertices.
+    pub fn height_at(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let mapped = (at - self.origin.xz()) / self.scale;
+        let base = mapped.floor();
+        let frac = mapped - base;
+        let base = base.as_ivec2();
+
+        let nw = self.vertex_height(base, scratch);
+        let ne = self.vertex_height(base + IVec2::X, scratch);
+        let sw = self.vertex_height(base + IVec2::Y, scratch);
+        let se = self.vertex_height(base + IVec2::ONE, scratch);
+
+        lerp(lerp(nw, ne, frac.x), lerp(sw, se, frac.x), frac.y) + self.origin.y
+    }
+}
+
+/// A loaded chunk.
+struct LoadedChunk {
+    entity: Entity,
+    buffer: Arc<TerrainBuffer>,
+}
+
+/// Marks a chunk entity.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TerrainChunk {
+    /// The chunk's coordinates, in chunks.
+    pub coord: IVec2,
+}
+
+/// Index of the terrain's chunks.
+///
+/// Insert this resource to enable chunked terrain; see the module
+/// documentation.
+#[derive(Resource)]
+pub struct TerrainChunkIndex {
+    source: TerrainChunkSource,
+
+    /// Number of quads along each side of a chunk.
+    chunk_quads: u32,
+
+    chunks: HashMap<IVec2, LoadedChunk>,
+
+    /// Material given to chunk meshes.
+    pub material: Option<Handle<StandardMaterial>>,
+
+    /// Caves to carve into chunks, if any.
+    pub caves: Option<CaveLayer>,
+}
+
+impl TerrainChunkIndex {
+    /// Makes a new chunk index, with no chunks loaded yet.
+    ///
+    /// `chunk_quads` is the number of quads along each side of a chunk.
+    pub fn new(source: TerrainChunkSource, chunk_quads: u32) -> Self {
+        assert!(chunk_quads > 0);
+
+        Self {
+            source,
+            chunk_quads,
+            chunks: HashMap::default(),
+            material: None,
+            caves: None,
+        }
+    }
+
+    /// Where terrain heights come from.
+    pub fn source(&self) -> &TerrainChunkSource {
+        &self.source
+    }
+
+    /// Length of each side of a chunk, in world space units.
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_quads as f32 * self.source.scale
+    }
+
+    /// Number of chunks along X and Z.
+    pub fn chunk_count(&self) -> IVec2 {
+        ((self.source.vertices - 1).as_ivec2() + self.chunk_quads as i32 - 1)
+            / self.chunk_quads as i32
+    }
+
+    /// The area covered by the whole terrain, on the XZ plane.
+    pub fn bounds(&self) -> Rect {
+        let min = self.source.origin.xz();
+        Rect::from_corners(
+            min,
+            min + (self.source.vertices - 1).as_vec2() * self.source.scale,
+        )
+    }
+
+    /// The chunk containing a world space XZ position.
+    pub fn chunk_at(&self, at: Vec2) -> IVec2 {
+        ((at - self.source.origin.xz()) / self.chunk_size())
+            .floor()
+            .as_ivec2()
+    }
+
+    /// Whether a chunk is within the terrain.
+    pub fn contains_chunk(&self, coord: IVec2) -> bool {
+        coord.cmpge(IVec2::ZERO).all() && coord.cmplt(self.chunk_count()).all()
+    }
+
+    /// The area covered by a chunk, on the XZ plane.
+    pub fn chunk_bounds(&self, coord: IVec2) -> Rect {
+        let min = self.source.origin.xz() + coord.as_vec2() * self.chunk_size();
+        Rect::from_corners(min, min + Vec2::splat(self.chunk_size()))
+    }
+
+    /// Translation of a chunk's entity.
+    ///
+    /// [TerrainBuffer]s are laid out around their middle, so this is offset
+    /// from the chunk's first vertex by half the buffer's size.
+    fn chunk_translation(&self, coord: IVec2) -> Vec3 {
+        let first_vertex = self.chunk_bounds(coord).min;
+        let half_buffer = (self.chunk_quads + 1) as f32 * self.source.scale * 0.5;
+
+        Vec3::new(
+            first_vertex.x + half_buffer,
+            self.source.origin.y,
+            first_vertex.y + half_buffer,
+        )
+    }
+
+    /// Generates the buffer of a chunk.
+    pub fn generate_chunk(&self, coord: IVec2, scratch: &mut ScratchBuffer<f32>) -> TerrainBuffer {
+        let side = self.chunk_quads as usize + 1;
+        let first_vertex = coord * self.chunk_quads as i32;
+
+        // Padded by a vertex on every side, to find the slope at the edges.
+        let padded_side = side + 2;
+        let padded = (0..padded_side * padded_side)
+            .map(|idx| {
+                let offset = IVec2::new((idx % padded_side) as i32, (idx / padded_side) as i32);
+                self.source
+                    .vertex_height(first_vertex + offset - IVec2::ONE, scratch)
+            })
+            .collect::<Vec<_>>();
+        let padded_at = |x: usize, y: usize| padded[y * padded_side + x];
+
+        let (values, biomes) = (0..side * side)
+            .map(|idx| {
+                let (x, y) = (idx % side + 1, idx / side + 1);
+                let vertex = first_vertex + IVec2::new(x as i32, y as i32) - IVec2::ONE;
+                let height = padded_at(x, y);
+
+                let biome = self.source.classify_vertex(
+                    vertex,
+                    height,
+                    padded_at(x - 1, y),
+                    padded_at(x + 1, y),
+                    padded_at(x, y - 1),
+                    padded_at(x, y + 1),
+                );
+
+                (height, biome)
+            })
+            .unzip();
+
+        let buffer =
+            TerrainBuffer::from_heights(side, side, self.source.scale, values).with_biomes(biomes);
+
+        // Caves are carved in world space, so that they carry on across
+        // chunks.
+        match &self.caves {
+            Some(caves) => {
+                let carved = caves.carve(&buffer, self.chunk_translation(coord));
+                buffer.with_caves(carved)
+            }
+            None => buffer,
+        }
+    }
+
+    /// Registers a loaded chunk.
+    pub fn insert_chunk(&mut self, coord: IVec2, entity: Entity, buffer: Arc<TerrainBuffer>) {
+        self.chunks.insert(coord, LoadedChunk { entity, buffer });
+    }
+
+    /// Unregisters a chunk, returning its entity if it was loaded.
+    pub fn remove_chunk(&mut self, coord: IVec2) -> Option<Entity> {
+        self.chunks.remove(&coord).map(|chunk| chunk.entity)
+    }
+
+    /// Whether a chunk is loaded.
+    pub fn is_loaded(&self, coord: IVec2) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    /// Coordinates of every loaded chunk.
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// Height of the terrain at a world space XZ position.
+    pub fn height_at(&self, at: Vec2) -> f32 {
+        self.height_at_with(at, &mut ScratchBuffer::new())
+    }
+
+    /// Height of the terrain at a world space XZ position, reusing a scratch
+    /// buffer if the terrain generator needs to be sampled.
+    pub fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let coord = self.chunk_at(at);
+
+        match self.chunks.get(&coord) {
+            Some(chunk) => {
+                let local = at - self.chunk_translation(coord).xz();
+                chunk.buffer.get_height_at(local.x, local.y) + self.source.origin.y
+            }
+            None => self.source.height_at(at, scratch),
+        }
+    }
+
+    /// Biome of the terrain at a world space XZ position.
+    pub fn biome_at(&self, at: Vec2) -> TerrainBiome {
+        self.source.biome_at(at, &mut ScratchBuffer::new())
+    }
+
+    /// Gradient of the terrain height at a world space XZ position.
+    pub fn gradient_at(&self, at: Vec2) -> Vec2 {
+        let mut scratch = ScratchBuffer::new();
+        let epsilon = self.source.scale * 0.5;
+
+        Vec2::new(
+            self.height_at_with(at + Vec2::X * epsilon, &mut scratch)
+                - self.height_at_with(at - Vec2::X * epsilon, &mut scratch),
+            self.height_at_with(at + Vec2::Y * epsilon, &mut scratch)
+                - self.height_at_with(at - Vec2::Y * epsilon, &mut scratch),
+        ) / (epsilon * 2.0)
+    }
+}
+
+/// Loads terrain chunks around this entity.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TerrainStreamer;
+
+/// Terrain chunk streaming settings.
+#[derive(Resource, Clone, Debug)]
+pub struct TerrainStreaming {
+    /// Chunks closer than this to any streamer are loaded.
+    pub load_radius: f32,
+
+    /// Chunks farther than this from every streamer are unloaded.
+    ///
+    /// Should be larger than [Self::load_radius], so that chunks near the
+    /// edge don't load and unload over and over.
+    pub unload_radius: f32,
+
+    /// Maximum number of chunks generated per frame, to avoid hitches.
+    pub max_loads_per_frame: usize,
+}
+
+impl Default for TerrainStreaming {
+    fn default() -> Self {
+        Self {
+            load_radius: 600.0,
+            unload_radius: 800.0,
+            max_loads_per_frame: 2,
+        }
+    }
+}
+
+fn stream_terrain_chunks(
+    mut commands: Commands,
+    mut index: ResMut<TerrainChunkIndex>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut scratch: Local<ScratchBuffer<f32>>,
+    settings: Res<TerrainStreaming>,
+    streamers: Query<&GlobalTransform, With<TerrainStreamer>>,
+    scene_tree: Query<Entity, With<SceneTree>>,
+) {
+    let _scope = AllocationScope::new("stream_terrain_chunks");
+
+    let streamers = streamers
+        .iter()
+        .map(|transform| transform.translation().xz())
+        .collect::<Vec<_>>();
+
+    let distance = |bounds: Rect| {
+        streamers
+            .iter()
+            .map(|pos| pos.distance(pos.clamp(bounds.min, bounds.max)))
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    // Unload far chunks.
+    let far = index
+        .loaded_chunks()
+        .filter(|coord| distance(index.chunk_bounds(*coord)) > settings.unload_radius)
+        .collect::<Vec<_>>();
+
+    for coord in far {
+        if let Some(entity) = index.remove_chunk(coord) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Ok(scene_tree) = scene_tree.single() else {
+        return;
+    };
+
+    // Load the nearest missing chunks.
+    let radius = (settings.load_radius / index.chunk_size()).ceil() as i32;
+    let mut missing = streamers
+        .iter()
+        .flat_map(|pos| {
+            let center = index.chunk_at(*pos);
+            (-radius..=radius)
+                .flat_map(move |z| (-radius..=radius).map(move |x| center + IVec2::new(x, z)))
+        })
+        .filter(|coord| index.contains_chunk(*coord) && !index.is_loaded(*coord))
+        .map(|coord| (distance(index.chunk_bounds(coord)), coord))
+        .filter(|(distance, _)| *distance <= settings.load_radius)
+        .collect::<Vec<_>>();
+
+    missing.sort_by(|a, b| {
+        a.0.total_cmp(&b.0)
+            .then(a.1.to_array().cmp(&b.1.to_array()))
+    });
+    missing.dedup_by_key(|(_, coord)| *coord);
+
+    for (_, coord) in missing.into_iter().take(settings.max_loads_per_frame) {
+        let buffer = Arc::new(index.generate_chunk(coord, &mut scratch));
+        let cave_mesh = buffer.cave_mesh().map(|mesh| meshes.add(mesh));
+
+        let mut chunk = commands.spawn((
+            TerrainChunk { coord },
+            Mesh3d(meshes.add(buffer.to_mesh())),
+            TerrainMarker {
+                buffer: buffer.clone(),
+            },
+            Transform::from_translation(index.chunk_translation(coord)),
+        ));
+
+        if let Some(material) = &index.material {
+            chunk.insert(MeshMaterial3d(material.clone()));
+        }
+
+        if let Some(cave_mesh) = cave_mesh {
+            let material = index.material.clone();
+            chunk.with_children(|parent| {
+                let mut caves = parent.spawn(Mesh3d(cave_mesh));
+                if let Some(material) = material {
+                    caves.insert(MeshMaterial3d(material));
+                }
+            });
+        }
+
+        let entity = chunk.id();
+        commands.entity(scene_tree).add_child(entity);
+        index.insert_chunk(coord, entity, buffer);
+    }
+}
+
+/// Terrain chunk streaming plugin.
+///
+/// Does nothing unless there is a [TerrainChunkIndex].
+pub struct TerrainStreamingPlugin;
+
+impl Plugin for TerrainStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainStreaming>();
+        app.add_systems(
+            Update,
+            stream_terrain_chunks.run_if(resource_exists::<TerrainChunkIndex>),
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::common::{
+        scratch::ScratchBuffer,
+        terrain::{
+            generator::{
+                CenterPoint, ModulationParams, TerrainGeneratorBuilder, default_modulator,
+            },
+            noise::FractalNoise,
+        },
+    };
+
+    use super::*;
+
+    fn index() -> TerrainChunkIndex {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let generator = TerrainGeneratorBuilder::default()
+            .noise(FractalNoise::random_octaves(
+                4.0,
+                4.0,
+                2.try_into().unwrap(),
+                &mut rng,
+            ))
+            .modulator(default_modulator())
+            .modulation_params(ModulationParams::default())
+            .center_points(vec![CenterPoint::new(Vec2::splat(200.0), 1.0)])
+            .build()
+            .unwrap();
+
+        let source =
+            TerrainChunkSource::new(generator, 10.0, 3.0, 80.0, Vec3::new(0.0, -40.0, 0.0));
+
+        TerrainChunkIndex::new(source, 8)
+    }
+
+    #[test]
+    fn seamless_across_chunks() {
+        let mut index = index();
+        let mut scratch = ScratchBuffer::new();
+        assert!(index.contains_chunk(IVec2::ONE));
+
+        let samples = (0..50)
+            .map(|step| index.chunk_bounds(IVec2::ZERO).min + Vec2::new(step as f32, 13.0) * 0.93)
+            .collect::<Vec<_>>();
+
+        // Sampled straight from the generator first.
+        let unloaded = samples
+            .iter()
+            .map(|at| index.height_at(*at))
+            .collect::<Vec<_>>();
+
+        for coord in [IVec2::new(0, 0), IVec2::new(1, 0)] {
+            let buffer = Arc::new(index.generate_chunk(coord, &mut scratch));
+            index.insert_chunk(coord, Entity::PLACEHOLDER, buffer);
+        }
+
+        // Samples span both chunks, and cross the boundary between them.
+        assert!(
+            samples
+                .iter()
+                .any(|at| index.chunk_at(*at) == IVec2::new(1, 0))
+        );
+
+        for (at, expected) in samples.iter().zip(unloaded) {
+            let height = index.height_at(*at);
+            assert!(
+                (height - expected).abs() < 1e-3,
+                "{at}: {height} != {expected}"
+            );
+        }
+
+        // Chunk meshes and lookups agree on biomes.
+        for at in &samples {
+            let coord = index.chunk_at(*at);
+            let local = *at - index.chunk_translation(coord).xz();
+            let meshed = index.chunks[&coord].buffer.get_biome_at(local.x, local.y);
+            assert_eq!(meshed, Some(index.biome_at(*at)));
+        }
+
+        assert_eq!(index.remove_chunk(IVec2::ZERO), Some(Entity::PLACEHOLDER));
+        assert!(!index.is_loaded(IVec2::ZERO));
+    }
+}
@@ -16,7 +16,8 @@
 use bevy::prelude::*;
 
 use crate::common::{
-    physics::collision::CollisionDetectionEvent,
+    diagnostics::AllocationScope,
+    physics::{collision::CollisionDetectionEvent, sleep::Sleeping},
     prelude::{AABB, CollisionInfo, PhysicsVolume, PointNetwork, VolumeCollection},
 };
 
@@ -79,12 +80,17 @@ impl CollisionDetectionEvent for TerrainVolumeCollisionDetectionEvent {
     }
 }
 
+/// Objects which can collide with terrain; sleeping objects are skipped.
+type AwakeNonTerrain = (Without<TerrainMarker>, Without<Sleeping>);
+
 /// Terrain-object collision via physics volumes.
 fn terrain_volume_collision_system(
     mut ev_collision: EventWriter<TerrainVolumeCollisionDetectionEvent>,
-    mut query: Query<(Entity, &mut PointNetwork, &VolumeCollection), Without<TerrainMarker>>,
+    mut query: Query<(Entity, &mut PointNetwork, &VolumeCollection), AwakeNonTerrain>,
     terrain_query: Query<(Entity, &TerrainMarker, &Transform)>,
 ) {
+    let _scope = AllocationScope::new("terrain_volume_collision");
+
     for (e1, mut points1, volumes1) in query.iter_mut() {
         // [NOTE] For more info on the below comment on loop label, see note below
         // near its continue.
@@ -93,6 +99,7 @@ fn terrain_volume_collision_system(
         for (e2, terramark, terratransf) in terrain_query.iter() {
             let terrabuf = &terramark.buffer;
             let terrabox = terrain_aabb(terrabuf);
+            let sdf = terrabuf.sdf_field();
 
             if !volumes1.aabb(&points1).check(&terrabox) {
                 continue;
@@ -109,25 +116,24 @@ fn terrain_volume_collision_system(
                     continue;
                 }
 
-                // Terrain height check
-                let terra_height = terrabuf.get_height_at(pos_mapped.x, pos_mapped.z);
+                // Terrain distance check; unlike the heightmap alone, this
+                // also finds cliff faces to the side of the point.
+                let distance = sdf.sdf(pos_mapped);
 
-                if pos_mapped.y > terra_height {
+                if distance > 0.0 {
                     continue;
                 }
 
                 // Depth is how far into the ground the point is.
-                let depth = terra_height - pos_mapped.y;
+                let depth = -distance;
 
-                // Normal is based on the gradient, which is brute forced by
-                // interpolating terrain values at offset positions in a
-                // weighted manner.
-                // [TODO] Analytical Perlin noise differentiation
-                let normal = terrabuf.get_normal_at(pos_mapped.x, pos_mapped.z);
+                // Objects are pushed out along the SDF normal, i.e. towards the
+                // nearest terrain surface, rather than always upwards.
+                let normal = sdf.normal(pos_mapped);
                 let normal_global = terratransf.transform_point(normal) - terratransf.translation;
 
                 let collision = CollisionInfo {
-                    pos: terratransf.transform_point(pos_mapped + Vec3::Z * (depth / 2.0)),
+                    pos: terratransf.transform_point(pos_mapped + normal * (depth / 2.0)),
                     normal: normal_global,
                 };
 
@@ -0,0 +1,327 @@
+//! # Terrain density fields
+//!
+//! A heightmap cannot describe overhangs, such as cave ceilings. Where those
+//! are needed, terrain is described by a [DensityField] instead: a 3D grid of
+//! density samples, negative inside solid ground and positive in the open,
+//! roughly proportional to the distance to the surface.
+//!
+//! Density fields are meshed with marching cubes. Each cube is split into six
+//! tetrahedra around its main diagonal (i.e. marching tetrahedra), which
+//! avoids the ambiguous cases of the classic lookup tables, and always yields
+//! watertight meshes.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::ops::Range;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::common::math::lerp;
+
+use super::buffer::TerrainBuffer;
+
+/// The corners of a grid cell, as offsets from its first sample.
+///
+/// The index of each corner is `x + 2 * y + 4 * z`.
+const CELL_CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(0, 1, 1),
+    UVec3::new(1, 1, 1),
+];
+
+/// The six tetrahedra of a grid cell, by their corners in [CELL_CORNERS].
+///
+/// All of them share the diagonal from the first corner to the last, so the
+/// faces of neighbouring cells match up.
+const CELL_TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 3, 2, 7],
+    [0, 2, 6, 7],
+    [0, 6, 4, 7],
+    [0, 4, 5, 7],
+    [0, 5, 1, 7],
+];
+
+/// A 3D grid of density samples.
+#[derive(Clone, Debug)]
+pub struct DensityField {
+    /// Position of the first sample.
+    origin: Vec3,
+
+    /// Spacing between samples, along every axis.
+    spacing: f32,
+
+    /// Number of samples along every axis.
+    size: UVec3,
+
+    /// The samples, X first, then Z, then Y.
+    values: Vec<f32>,
+}
+
+impl DensityField {
+    /// Makes a density field by sampling a function at every point of a
+    /// grid.
+    pub fn from_fn(
+        origin: Vec3,
+        spacing: f32,
+        size: UVec3,
+        mut density_at: impl FnMut(Vec3) -> f32,
+    ) -> Self {
+        assert!(size.cmpge(UVec3::splat(2)).all());
+
+        let values = (0..size.y)
+            .flat_map(|y| (0..size.z).flat_map(move |z| (0..size.x).map(move |x| (x, y, z))))
+            .map(|(x, y, z)| density_at(origin + UVec3::new(x, y, z).as_vec3() * spacing))
+            .collect();
+
+        Self {
+            origin,
+            spacing,
+            size,
+            values,
+        }
+    }
+
+    /// Makes a density field laid out over a terrain, in its local space.
+    ///
+    /// Samples line up with the terrain's vertices horizontally, and cover
+    /// the given range of heights vertically, at the same spacing.
+    pub fn over_terrain(
+        buffer: &TerrainBuffer,
+        heights: Range<f32>,
+        density_at: impl FnMut(Vec3) -> f32,
+    ) -> Self {
+        let spacing = buffer.get_resolution();
+        let layers = ((heights.end - heights.start) / spacing).ceil().max(1.0) as u32 + 1;
+        let origin = buffer.get_vertex_position(0, 0).with_y(heights.start);
+
+        Self::from_fn(
+            origin,
+            spacing,
+            UVec3::new(
+                buffer.get_vertex_width() as u32,
+                layers,
+                buffer.get_vertex_height() as u32,
+            ),
+            density_at,
+        )
+    }
+
+    /// Position of the first sample.
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    /// Spacing between samples.
+    pub fn spacing(&self) -> f32 {
+        self.spacing
+    }
+
+    /// Number of samples along every axis.
+    pub fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    /// Position of a sample.
+    pub fn sample_position(&self, sample: UVec3) -> Vec3 {
+        self.origin + sample.as_vec3() * self.spacing
+    }
+
+    /// The density of a sample.
+    ///
+    /// Samples outside the grid take the value of the nearest one.
+    pub fn get_value_at(&self, sample: UVec3) -> f32 {
+        let sample = sample.min(self.size - 1);
+        self.values[((sample.y * self.size.z + sample.z) * self.size.x + sample.x) as usize]
+    }
+
+    /// Whether a point is within the grid.
+    pub fn contains(&self, pos: Vec3) -> bool {
+        let max = self.sample_position(self.size - 1);
+        pos.cmpge(self.origin).all() && pos.cmple(max).all()
+    }
+
+    /// The density at a point, interpolating between samples.
+    ///
+    /// Points outside the grid take the density of the nearest point within
+    /// it.
+    pub fn density_at(&self, pos: Vec3) -> f32 {
+        let mapped =
+            ((pos - self.origin) / self.spacing).clamp(Vec3::ZERO, (self.size - 1).as_vec3());
+        let base = mapped.floor().as_uvec3().min(self.size - 2);
+        let frac = mapped - base.as_vec3();
+
+        let corner = |idx: usize| self.get_value_at(base + CELL_CORNERS[idx]);
+        let bottom = lerp(
+            lerp(corner(0), corner(1), frac.x),
+            lerp(corner(4), corner(5), frac.x),
+            frac.z,
+        );
+        let top = lerp(
+            lerp(corner(2), corner(3), frac.x),
+            lerp(corner(6), corner(7), frac.x),
+            frac.z,
+        );
+
+        lerp(bottom, top, frac.y)
+    }
+
+    /// Triangles of the surface within a cell, given by its first sample.
+    ///
+    /// Triangles face away from solid ground.
+    fn cell_triangles(&self, cell: UVec3, triangles: &mut Vec<[Vec3; 3]>) {
+        let positions = CELL_CORNERS.map(|corner| self.sample_position(cell + corner));
+        let densities = CELL_CORNERS.map(|corner| self.get_value_at(cell + corner));
+
+        for tetrahedron in CELL_TETRAHEDRA {
+            polygonize_tetrahedron(
+                tetrahedron.map(|idx| positions[idx]),
+                tetrahedron.map(|idx| densities[idx]),
+                triangles,
+            );
+        }
+    }
+
+    /// Meshes the surface within the cells for which `include` is true, given
+    /// their first sample.
+    ///
+    /// Returns [None] if there is no surface there.
+    pub fn to_mesh_where(&self, mut include: impl FnMut(UVec3) -> bool) -> Option<Mesh> {
+        let mut triangles = Vec::new();
+
+        for y in 0..self.size.y - 1 {
+            for z in 0..self.size.z - 1 {
+                for x in 0..self.size.x - 1 {
+                    let cell = UVec3::new(x, y, z);
+                    if include(cell) {
+                        self.cell_triangles(cell, &mut triangles);
+                    }
+                }
+            }
+        }
+
+        if triangles.is_empty() {
+            return None;
+        }
+
+        let positions = triangles.into_iter().flatten().collect::<Vec<_>>();
+        let num_vertices = positions.len() as u32;
+
+        Some(
+            Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_indices(Indices::U32((0..num_vertices).collect()))
+            .with_computed_normals(),
+        )
+    }
+
+    /// Meshes the whole surface.
+    pub fn to_mesh(&self) -> Option<Mesh> {
+        self.to_mesh_where(|_| true)
+    }
+}
+
+/// Adds the triangles of the surface within a tetrahedron.
+fn polygonize_tetrahedron(
+    positions: [Vec3; 4],
+    densities: [f32; 4],
+    triangles: &mut Vec<[Vec3; 3]>,
+) {
+    let (solid, open): (Vec<usize>, Vec<usize>) = (0..4).partition(|&idx| densities[idx] < 0.0);
+
+    // Where the surface crosses an edge.
+    let crossing = |from: usize, to: usize| {
+        let alpha = densities[from] / (densities[from] - densities[to]);
+        positions[from].lerp(positions[to], alpha)
+    };
+
+    let new_triangles = match (solid.as_slice(), open.as_slice()) {
+        (&[inside], &[a, b, c]) | (&[a, b, c], &[inside]) => vec![[
+            crossing(inside, a),
+            crossing(inside, b),
+            crossing(inside, c),
+        ]],
+        (&[a, b], &[c, d]) => {
+            let quad = [
+                crossing(a, c),
+                crossing(a, d),
+                crossing(b, d),
+                crossing(b, c),
+            ];
+            vec![[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]]
+        }
+        _ => return,
+    };
+
+    let centroid = |corners: &[usize]| {
+        corners.iter().map(|&idx| positions[idx]).sum::<Vec3>() / corners.len() as f32
+    };
+    let outwards = centroid(&open) - centroid(&solid);
+
+    triangles.extend(new_triangles.into_iter().map(|[a, b, c]| {
+        if (b - a).cross(c - a).dot(outwards) < 0.0 {
+            [a, c, b]
+        } else {
+            [a, b, c]
+        }
+    }));
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::{prelude::*, render::mesh::VertexAttributeValues};
+
+    use super::*;
+
+    #[test]
+    fn meshes_a_sphere() {
+        let field = DensityField::from_fn(Vec3::splat(-6.0), 0.5, UVec3::splat(25), |pos| {
+            pos.length() - 4.0
+        });
+
+        assert!((field.density_at(Vec3::new(0.25, 0.0, 0.0)) + 3.75).abs() < 0.1);
+        assert!(field.contains(Vec3::ZERO));
+        assert!(!field.contains(Vec3::splat(7.0)));
+
+        let mesh = field.to_mesh().unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("sphere mesh has no positions");
+        };
+
+        for triangle in positions.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|idx| Vec3::from(triangle[idx]));
+            assert!((a.length() - 4.0).abs() < 0.1);
+
+            // Triangles face outwards.
+            assert!((b - a).cross(c - a).dot(a + b + c) >= 0.0);
+        }
+
+        // Nothing to mesh where there is no surface.
+        assert!(field.to_mesh_where(|cell| cell.x < 2).is_none());
+    }
+}
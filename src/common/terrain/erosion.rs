@@ -0,0 +1,332 @@
+//! # Terrain erosion
+//!
+//! Raw fractal noise makes for blobby islands. Erosion is an optional stage
+//! of [super::buffer::TerrainBuffer::generate], which weathers the generated
+//! heightmap in two passes:
+//!
+//! * Thermal erosion crumbles slopes steeper than a talus angle, moving
+//!   material down to their foot.
+//! * Hydraulic erosion simulates rain droplets running downhill, picking up
+//!   sediment on the way down and dropping it where they slow down, carving
+//!   valleys and ridges. Droplets which reach the sea drop all of their
+//!   sediment, building up beaches.
+//!
+//! [NOTE] Erosion needs the whole heightmap at once, so it does not apply to
+//! chunked terrain, whose chunks are generated independently; see
+//! [super::chunk].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+
+use crate::common::{math::lerp, seed::WorldRng};
+
+/// The parameters of terrain erosion.
+///
+/// Heights are in terrain generator units, and slopes in those units per
+/// terrain generator coordinate; see [super::generator::TerrainGenerator].
+#[derive(Clone, Debug)]
+pub struct ErosionParams {
+    /// Seeds where droplets fall.
+    pub seed: u64,
+
+    /// How many times to crumble steep slopes.
+    pub thermal_iterations: u32,
+
+    /// Slopes steeper than this crumble.
+    pub talus_slope: f32,
+
+    /// How much of the excess height of steep slopes crumbles every
+    /// iteration, between 0.0 and 1.0.
+    pub thermal_rate: f32,
+
+    /// How many droplets fall, per heightmap sample.
+    pub droplets_per_sample: f32,
+
+    /// How many steps each droplet runs for, at most.
+    pub droplet_lifetime: u32,
+
+    /// How much droplets keep their direction, rather than following the
+    /// slope, between 0.0 and 1.0.
+    pub inertia: f32,
+
+    /// How much sediment droplets can carry, relative to their speed, water
+    /// and how steeply they are running down.
+    pub capacity: f32,
+
+    /// The least sediment droplets can carry, so that they keep eroding
+    /// even on flat ground.
+    pub min_capacity: f32,
+
+    /// How much of their excess sediment droplets drop every step.
+    pub deposition: f32,
+
+    /// How much of their spare capacity droplets fill up every step.
+    pub erosion: f32,
+
+    /// How much of its water a droplet loses every step.
+    pub evaporation: f32,
+
+    /// How quickly droplets speed up when running down.
+    pub gravity: f32,
+}
+
+impl Default for ErosionParams {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            thermal_iterations: 20,
+            talus_slope: 0.15,
+            thermal_rate: 0.5,
+            droplets_per_sample: 0.5,
+            droplet_lifetime: 30,
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.0001,
+            deposition: 0.3,
+            erosion: 0.3,
+            evaporation: 0.02,
+            gravity: 4.0,
+        }
+    }
+}
+
+/// A heightmap being eroded.
+struct Heightmap<'a> {
+    values: &'a mut [f32],
+    width: usize,
+    height: usize,
+}
+
+impl Heightmap<'_> {
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// The height and gradient at a point, in samples, interpolating
+    /// between samples.
+    ///
+    /// The point must be within the heightmap.
+    fn sample(&self, at: Vec2) -> (f32, Vec2) {
+        let (x, y) = (at.x as usize, at.y as usize);
+        let frac = at - Vec2::new(x as f32, y as f32);
+
+        let nw = self.values[self.index(x, y)];
+        let ne = self.values[self.index(x + 1, y)];
+        let sw = self.values[self.index(x, y + 1)];
+        let se = self.values[self.index(x + 1, y + 1)];
+
+        let height = lerp(lerp(nw, ne, frac.x), lerp(sw, se, frac.x), frac.y);
+        let gradient = Vec2::new(
+            lerp(ne - nw, se - sw, frac.y),
+            lerp(sw - nw, se - ne, frac.x),
+        );
+
+        (height, gradient)
+    }
+
+    /// Adds to the height around a point, spread over its four nearest
+    /// samples.
+    fn add(&mut self, at: Vec2, amount: f32) {
+        let (x, y) = (at.x as usize, at.y as usize);
+        let frac = at - Vec2::new(x as f32, y as f32);
+
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - frac.x) * (1.0 - frac.y)),
+            (1, 0, frac.x * (1.0 - frac.y)),
+            (0, 1, (1.0 - frac.x) * frac.y),
+            (1, 1, frac.x * frac.y),
+        ] {
+            let idx = self.index(x + dx, y + dy);
+            self.values[idx] += amount * weight;
+        }
+    }
+
+    /// Whether a point, in samples, is far enough within the heightmap to
+    /// be sampled.
+    fn contains(&self, at: Vec2) -> bool {
+        at.x >= 0.0
+            && at.y >= 0.0
+            && at.x < (self.width - 1) as f32
+            && at.y < (self.height - 1) as f32
+    }
+
+    fn thermal(&mut self, params: &ErosionParams, spacing: f32) {
+        let talus = params.talus_slope * spacing;
+        let mut deltas = vec![0.0; self.values.len()];
+
+        for _ in 0..params.thermal_iterations {
+            deltas.fill(0.0);
+
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let idx = self.index(x, y);
+                    let here = self.values[idx];
+
+                    let neighbours = [
+                        (x > 0).then(|| idx - 1),
+                        (x + 1 < self.width).then(|| idx + 1),
+                        (y > 0).then(|| idx - self.width),
+                        (y + 1 < self.height).then(|| idx + self.width),
+                    ];
+
+                    let excess = |neighbour: usize| here - self.values[neighbour] - talus;
+                    let total_excess = neighbours
+                        .iter()
+                        .flatten()
+                        .map(|&neighbour| excess(neighbour).max(0.0))
+                        .sum::<f32>();
+                    let steepest = neighbours
+                        .iter()
+                        .flatten()
+                        .map(|&neighbour| excess(neighbour))
+                        .fold(0.0_f32, f32::max);
+
+                    if total_excess <= 0.0 {
+                        continue;
+                    }
+
+                    // Half the steepest excess at most, so that material
+                    // never piles up higher than where it came from.
+                    let moved = steepest * 0.5 * params.thermal_rate;
+                    deltas[idx] -= moved;
+
+                    for &neighbour in neighbours.iter().flatten() {
+                        deltas[neighbour] += moved * excess(neighbour).max(0.0) / total_excess;
+                    }
+                }
+            }
+
+            for (value, delta) in self.values.iter_mut().zip(&deltas) {
+                *value += delta;
+            }
+        }
+    }
+
+    fn hydraulic(&mut self, params: &ErosionParams) {
+        let mut rng = WorldRng::seed_from_u64(params.seed);
+        let droplets = (self.values.len() as f32 * params.droplets_per_sample) as usize;
+        let max = Vec2::new((self.width - 1) as f32, (self.height - 1) as f32);
+
+        for _ in 0..droplets {
+            let mut at = Vec2::new(rng.random_range(0.0..max.x), rng.random_range(0.0..max.y));
+            let mut direction = Vec2::ZERO;
+            let mut speed = 1.0;
+            let mut water = 1.0;
+            let mut sediment = 0.0;
+
+            for _ in 0..params.droplet_lifetime {
+                let (height, gradient) = self.sample(at);
+
+                // Droplets which reach the sea drop everything.
+                if height < 0.0 {
+                    self.add(at, sediment);
+                    break;
+                }
+
+                direction = (-gradient).lerp(direction, params.inertia);
+                let Some(step) = direction.try_normalize() else {
+                    self.add(at, sediment);
+                    break;
+                };
+                direction = step;
+
+                let next = at + direction;
+                if !self.contains(next) {
+                    break;
+                }
+
+                let delta = self.sample(next).0 - height;
+                let capacity = (-delta * speed * water * params.capacity).max(params.min_capacity);
+
+                if sediment > capacity || delta > 0.0 {
+                    // Fill up pits, or drop excess sediment.
+                    let dropped = if delta > 0.0 {
+                        delta.min(sediment)
+                    } else {
+                        (sediment - capacity) * params.deposition
+                    };
+                    sediment -= dropped;
+                    self.add(at, dropped);
+                } else {
+                    // Never dig deeper than the next step is.
+                    let eroded = ((capacity - sediment) * params.erosion).min(-delta);
+                    sediment += eroded;
+                    self.add(at, -eroded);
+                }
+
+                speed = (speed * speed - delta * params.gravity).max(0.0).sqrt();
+                water *= 1.0 - params.evaporation;
+                at = next;
+            }
+        }
+    }
+}
+
+/// Erodes a heightmap, with its rows laid out one after another.
+///
+/// `spacing` is the distance between samples, in terrain generator
+/// coordinates.
+pub fn erode(
+    values: &mut [f32],
+    width: usize,
+    height: usize,
+    spacing: f32,
+    params: &ErosionParams,
+) {
+    assert_eq!(values.len(), width * height);
+    assert!(width > 1 && height > 1);
+
+    let mut heightmap = Heightmap {
+        values,
+        width,
+        height,
+    };
+
+    heightmap.thermal(params, spacing);
+    heightmap.hydraulic(params);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn smooths_steep_slopes() {
+        let size = 32;
+        let spacing = 0.25;
+
+        // A cone, with a sharp spike on one side.
+        let mut values = (0..size * size)
+            .map(|idx| {
+                let at = Vec2::new((idx % size) as f32, (idx / size) as f32) - 16.0;
+                let spike = if at == Vec2::new(6.0, 0.0) { 0.5 } else { 0.0 };
+                0.6 - at.length() * 0.05 + spike
+            })
+            .collect::<Vec<_>>();
+        let total = values.iter().sum::<f32>();
+
+        erode(&mut values, size, size, spacing, &ErosionParams::default());
+
+        let spike = values[16 * size + 22];
+        let beside = values[16 * size + 23];
+        assert!(spike - beside < 0.2, "{spike} vs {beside}");
+
+        // Material is moved around, not made or destroyed, save for what
+        // droplets carry off the edges.
+        let eroded = values.iter().sum::<f32>();
+        assert!((eroded - total).abs() < total.abs() * 0.05);
+    }
+}
@@ -20,12 +20,21 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
-use bevy::math::Vec2;
+use bevy::math::{Rect, Vec2};
 use derive_builder::Builder;
+use rand::Rng;
 
-use crate::common::math::smootherstep;
+use crate::common::{math::smootherstep, scratch::ScratchBuffer};
 
-use super::noise::FractalNoise;
+use super::{
+    biome::{BiomeLayer, TerrainBiome},
+    erosion::ErosionParams,
+    hydrology::{Hydrology, HydrologyParams},
+    noise::FractalNoise,
+    primitive::{
+        CarveNode, ClampNode, ConformNode, IslandShapePrimitive, NoisePrimitive, TerrainNode,
+    },
+};
 
 /// Some of the parameters used when modulating terrain height.
 ///
@@ -137,7 +146,7 @@ pub trait DistanceCollector: Clone {
     /// ## Safety
     ///
     /// May panic if distances is empty.
-    fn collect_distances(&self, distances: Vec<f32>) -> f32;
+    fn collect_distances(&self, distances: &[f32]) -> f32;
 }
 
 /// A simple distance collector; simply pick the smallest!
@@ -145,8 +154,8 @@ pub trait DistanceCollector: Clone {
 pub struct MinDistance;
 
 impl DistanceCollector for MinDistance {
-    fn collect_distances(&self, distances: Vec<f32>) -> f32 {
-        distances.into_iter().reduce(f32::min).unwrap()
+    fn collect_distances(&self, distances: &[f32]) -> f32 {
+        distances.iter().copied().reduce(f32::min).unwrap()
     }
 }
 
@@ -169,11 +178,11 @@ impl Default for SmoothminDistance {
 }
 
 impl DistanceCollector for SmoothminDistance {
-    fn collect_distances(&self, distances: Vec<f32>) -> f32 {
+    fn collect_distances(&self, distances: &[f32]) -> f32 {
         0.0_f32.max(
             (-distances
-                .into_iter()
-                .map(|v| (-self.roughness as f64 * v as f64).exp())
+                .iter()
+                .map(|&v| (-self.roughness as f64 * v as f64).exp())
                 .sum::<f64>()
                 .ln()
                 / self.roughness as f64) as f32,
@@ -223,6 +232,12 @@ impl CenterPoint {
     pub fn new(pos: Vec2, scale: f32) -> Self {
         Self { pos, scale }
     }
+
+    /// The distance from this center point to the given coordinates, divided
+    /// by its scale.
+    pub fn distance(&self, at: Vec2) -> f32 {
+        (self.pos - at).length() / self.scale
+    }
 }
 
 impl<TMA, DC> TerrainModulator<TMA, DC>
@@ -232,17 +247,18 @@ where
 {
     /// Modulate terrain using the passed parameters, center points, input
     /// coordinates, and the current height.
+    ///
+    /// The distances to every center point are collected into `scratch`,
+    /// which should be reused across calls.
     pub fn push_terrain(
         &self,
         params: &ModulationParams,
         center_points: &[CenterPoint],
         at: Vec2,
         curr_height: f32,
+        scratch: &mut ScratchBuffer<f32>,
     ) -> f32 {
-        let distances = center_points
-            .iter()
-            .map(|point| (point.pos - at).length() / point.scale)
-            .collect::<Vec<_>>();
+        let distances = scratch.fill(center_points.iter().map(|point| point.distance(at)));
         let distance = self.distance_collector.collect_distances(distances);
 
         self.algorithm.push_terrain(params, distance, curr_height)
@@ -280,6 +296,19 @@ where
     // [NOTE] Change the below default value to change the size of terrain noise tiles!
     #[builder(default = 200.0)]
     resolution: f32,
+
+    /// The biome assignment stage.
+    #[builder(default)]
+    biomes: BiomeLayer,
+
+    /// The hydrology pass, if any; see [Self::with_hydrology].
+    #[builder(default)]
+    hydrology: Option<Hydrology>,
+
+    /// The erosion stage of [super::buffer::TerrainBuffer::generate], if
+    /// any.
+    #[builder(default)]
+    erosion: Option<ErosionParams>,
 }
 
 impl<'fn_interp, TMA, DC> TerrainGenerator<'fn_interp, TMA, DC>
@@ -288,13 +317,107 @@ where
     DC: DistanceCollector,
 {
     /// Get the height of terrain generated at these coordinates.
+    ///
+    /// When sampling many heights, prefer [Self::get_height_at_with].
     pub fn get_height_at(&self, at: Vec2) -> f32 {
+        self.get_height_at_with(at, &mut ScratchBuffer::new())
+    }
+
+    /// Get the height of terrain generated at these coordinates, reusing a
+    /// scratch buffer.
+    pub fn get_height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
         let height = self
             .noise
             .get_influence_at(at.x / self.resolution, at.y / self.resolution);
 
-        self.modulator
-            .push_terrain(&self.modulation_params, &self.center_points, at, height)
+        let height = self.modulator.push_terrain(
+            &self.modulation_params,
+            &self.center_points,
+            at,
+            height,
+            scratch,
+        );
+
+        match &self.hydrology {
+            Some(hydrology) => hydrology.carve(at, height),
+            None => height,
+        }
+    }
+
+    /// Traces rivers and lagoons over the terrain, and carves them into it.
+    ///
+    /// Replaces any previous hydrology pass.
+    pub fn with_hydrology<R: Rng>(mut self, params: &HydrologyParams, rng: &mut R) -> Self {
+        self.hydrology = None;
+
+        let epsilon = self.resolution / 64.0;
+        let bounds = Rect::new(
+            0.0,
+            0.0,
+            self.get_width() - epsilon,
+            self.get_height() - epsilon,
+        );
+        let mut scratch = ScratchBuffer::new();
+
+        // Tracing samples around points, which may fall off the edges.
+        let hydrology = Hydrology::trace(
+            params,
+            bounds,
+            |at| self.get_height_at_with(at.clamp(bounds.min, bounds.max), &mut scratch),
+            rng,
+        );
+
+        self.hydrology = Some(hydrology);
+        self
+    }
+
+    /// The erosion stage of [super::buffer::TerrainBuffer::generate], if
+    /// any.
+    pub fn erosion(&self) -> Option<&ErosionParams> {
+        self.erosion.as_ref()
+    }
+
+    /// The hydrology pass, if any.
+    ///
+    /// Its waterways are in the same coordinates as [Self::get_height_at].
+    pub fn hydrology(&self) -> Option<&Hydrology> {
+        self.hydrology.as_ref()
+    }
+
+    /// Get the biome of terrain generated at these coordinates.
+    pub fn get_biome_at(&self, at: Vec2) -> TerrainBiome {
+        self.get_biome_at_with(at, &mut ScratchBuffer::new())
+    }
+
+    /// Get the biome of terrain generated at these coordinates, reusing a
+    /// scratch buffer.
+    ///
+    /// Samples the terrain height around the coordinates to find its slope.
+    /// If the heights are already known, e.g. when generating a
+    /// [super::buffer::TerrainBuffer], prefer [Self::classify_biome].
+    pub fn get_biome_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> TerrainBiome {
+        let epsilon = self.resolution / 64.0;
+        let max = Vec2::new(self.get_width(), self.get_height()) - epsilon;
+        let mut sample =
+            |offset: Vec2| self.get_height_at_with((at + offset).clamp(Vec2::ZERO, max), scratch);
+
+        let height = sample(Vec2::ZERO);
+        let gradient = Vec2::new(
+            sample(Vec2::X * epsilon) - sample(Vec2::NEG_X * epsilon),
+            sample(Vec2::Y * epsilon) - sample(Vec2::NEG_Y * epsilon),
+        ) / (epsilon * 2.0);
+
+        self.classify_biome(at, height, gradient)
+    }
+
+    /// Assigns a biome to terrain at these coordinates, given its height and
+    /// the gradient of its height, as output by this generator.
+    pub fn classify_biome(&self, at: Vec2, height: f32, gradient: Vec2) -> TerrainBiome {
+        self.biomes.classify(
+            at / self.resolution,
+            height,
+            gradient.length() * self.resolution,
+        )
     }
 
     /// Get the bounding width of this terrain generator.
@@ -308,6 +431,47 @@ where
     }
 }
 
+impl TerrainGenerator<'_, DefaultTerrainModulatorAlgorithm, SmoothminDistance> {
+    /// Expresses this terrain generator as a tree of terrain primitives.
+    ///
+    /// The tree yields the same heights as the generator; the biome layer is
+    /// not included.
+    pub fn to_primitive_tree(&self) -> TerrainNode {
+        let params = &self.modulation_params;
+
+        let noise = NoisePrimitive {
+            noise: self.noise.clone(),
+            resolution: self.resolution,
+        };
+
+        let shape = IslandShapePrimitive {
+            center_points: self.center_points.clone(),
+            min_shore_distance: params.min_shore_distance,
+            max_shore_distance: params.max_shore_distance,
+            roughness: Some(self.modulator.distance_collector.roughness),
+        };
+
+        let tree = TerrainNode::from(ClampNode {
+            node: Box::new(TerrainNode::from(ConformNode {
+                node: Box::new(noise.into()),
+                shape: Box::new(shape.into()),
+                amount: params.islandification,
+                interpolator: *params.interpolator,
+            })),
+            min: -1.0,
+            max: f32::INFINITY,
+        });
+
+        match &self.hydrology {
+            Some(hydrology) => TerrainNode::from(CarveNode {
+                node: Box::new(tree),
+                hydrology: hydrology.clone(),
+            }),
+            None => tree,
+        }
+    }
+}
+
 pub type DefaultTerrainGenerator =
     TerrainGenerator<'static, DefaultTerrainModulatorAlgorithm, SmoothminDistance>;
 
@@ -0,0 +1,397 @@
+//! # Terrain hydrology
+//!
+//! An optional pass of the terrain generator, which carves rivers from high
+//! ground down to the sea, and hollows out lagoons inside the island, each
+//! with an outlet to the sea.
+//!
+//! Waterways are traced once, over the heights of the generator without
+//! them, and then carved down below sea level wherever the terrain is
+//! sampled. The traced [Waterway]s are kept, so that props and ships can be
+//! spawned in navigable inland waters; see [Hydrology::spawn_points].
+//!
+//! Everything here is in terrain generator units; see
+//! [super::generator::TerrainGenerator].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::common::math::{closest::closest_point_on_polyline_2d, lerp, smootherstep};
+
+/// The parameters of the hydrology pass.
+#[derive(Clone, Debug)]
+pub struct HydrologyParams {
+    /// How many rivers to carve.
+    ///
+    /// Fewer may be carved if there is not enough high ground.
+    pub rivers: u8,
+
+    /// How many lagoons to hollow out.
+    pub lagoons: u8,
+
+    /// Half the width of river beds.
+    pub river_width: f32,
+
+    /// Radius of lagoon beds.
+    pub lagoon_radius: f32,
+
+    /// Width of the slopes from waterway beds up to the surrounding terrain.
+    pub bank_width: f32,
+
+    /// Height of waterway beds, below sea level.
+    pub depth: f32,
+
+    /// Rivers only start above this height.
+    pub min_source_height: f32,
+
+    /// Length of each step when tracing a river downhill.
+    pub step: f32,
+
+    /// Maximum number of steps of a river.
+    pub max_steps: usize,
+}
+
+impl Default for HydrologyParams {
+    fn default() -> Self {
+        Self {
+            rivers: 2,
+            lagoons: 1,
+            river_width: 1.0,
+            lagoon_radius: 3.0,
+            bank_width: 1.0,
+            depth: 0.1,
+            min_source_height: 0.3,
+            step: 0.25,
+            max_steps: 1000,
+        }
+    }
+}
+
+/// What kind of waterway a [Waterway] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaterwayKind {
+    /// A river, flowing along its path.
+    River,
+
+    /// A lagoon, around the only point of its path.
+    Lagoon,
+}
+
+/// A carved waterway.
+#[derive(Clone, Debug)]
+pub struct Waterway {
+    /// What kind of waterway this is.
+    pub kind: WaterwayKind,
+
+    /// The center line of the waterway. Rivers flow from the first point to
+    /// the last.
+    pub path: Vec<Vec2>,
+
+    /// Distance from the center line within which the bed is fully carved.
+    pub width: f32,
+
+    /// Area which may be affected by carving this waterway.
+    bounds: Rect,
+}
+
+impl Waterway {
+    fn new(kind: WaterwayKind, path: Vec<Vec2>, width: f32, bank_width: f32) -> Self {
+        let bounds = path
+            .iter()
+            .fold(
+                Rect::from_center_size(path[0], Vec2::ZERO),
+                |bounds, &at| bounds.union_point(at),
+            )
+            .inflate(width + bank_width);
+
+        Self {
+            kind,
+            path,
+            width,
+            bounds,
+        }
+    }
+
+    /// Distance from a point to the center line of this waterway.
+    pub fn distance(&self, at: Vec2) -> f32 {
+        closest_point_on_polyline_2d(&self.path, at)
+            .map_or(f32::INFINITY, |closest| closest.distance_squared.sqrt())
+    }
+
+    /// Whether a point is within the bed of this waterway.
+    pub fn contains(&self, at: Vec2) -> bool {
+        self.bounds.contains(at) && self.distance(at) <= self.width
+    }
+}
+
+/// The waterways carved into a terrain.
+#[derive(Clone, Debug, Default)]
+pub struct Hydrology {
+    waterways: Vec<Waterway>,
+
+    /// See [HydrologyParams::bank_width].
+    bank_width: f32,
+
+    /// See [HydrologyParams::depth].
+    depth: f32,
+}
+
+impl Hydrology {
+    /// Traces waterways over a terrain.
+    ///
+    /// * `bounds` - The area within which to trace.
+    /// * `height_at` - Height of the terrain before carving.
+    pub fn trace<R: Rng>(
+        params: &HydrologyParams,
+        bounds: Rect,
+        mut height_at: impl FnMut(Vec2) -> f32,
+        rng: &mut R,
+    ) -> Self {
+        let mut hydrology = Self {
+            waterways: Vec::new(),
+            bank_width: params.bank_width,
+            depth: params.depth,
+        };
+
+        // Sample candidate spots, which are reused for both river sources
+        // and lagoons.
+        let mut candidates = (0..256)
+            .map(|_| {
+                let at = Vec2::new(
+                    rng.random_range(bounds.min.x..bounds.max.x),
+                    rng.random_range(bounds.min.y..bounds.max.y),
+                );
+                (at, height_at(at))
+            })
+            .filter(|&(_, height)| height > 0.0)
+            .collect::<Vec<_>>();
+
+        // Rivers start from the highest spots, spread apart.
+        candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        let spacing = (params.river_width + params.bank_width) * 4.0;
+        let mut sources: Vec<Vec2> = Vec::new();
+
+        for &(at, height) in &candidates {
+            if sources.len() >= params.rivers as usize || height < params.min_source_height {
+                break;
+            }
+            if sources.iter().all(|source| source.distance(at) > spacing) {
+                sources.push(at);
+            }
+        }
+
+        for source in sources {
+            hydrology.trace_river(params, bounds, source, &mut height_at);
+        }
+
+        // Lagoons go in lowlands, away from the sea.
+        let lowlands = candidates
+            .iter()
+            .filter(|&&(at, height)| {
+                height < params.min_source_height
+                    && [Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y]
+                        .iter()
+                        .all(|&dir| height_at(at + dir * params.lagoon_radius * 2.0) > 0.0)
+            })
+            .map(|&(at, _)| at)
+            .collect::<Vec<_>>();
+
+        for at in lowlands.into_iter().take(params.lagoons as usize) {
+            hydrology.add_lagoon(params, at);
+
+            // Lagoons drain to the sea, so that they can be sailed into.
+            let dir = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+            let outlet = at + dir * params.lagoon_radius;
+            hydrology.trace_river(params, bounds, outlet, &mut height_at);
+        }
+
+        hydrology
+    }
+
+    fn add_lagoon(&mut self, params: &HydrologyParams, at: Vec2) {
+        self.waterways.push(Waterway::new(
+            WaterwayKind::Lagoon,
+            vec![at],
+            params.lagoon_radius,
+            params.bank_width,
+        ));
+    }
+
+    /// Traces a river downhill from a source, until it reaches the sea.
+    ///
+    /// Rivers which get stuck in a basin end in a lagoon.
+    fn trace_river(
+        &mut self,
+        params: &HydrologyParams,
+        bounds: Rect,
+        source: Vec2,
+        height_at: &mut impl FnMut(Vec2) -> f32,
+    ) {
+        let step = params.step;
+        let mut path = vec![source];
+        let mut at = source;
+        let mut heading = Vec2::ZERO;
+        let mut reached_sea = false;
+
+        for _ in 0..params.max_steps {
+            if height_at(at) < 0.0 {
+                reached_sea = true;
+                break;
+            }
+
+            let gradient = Vec2::new(
+                height_at(at + Vec2::X * step) - height_at(at - Vec2::X * step),
+                height_at(at + Vec2::Y * step) - height_at(at - Vec2::Y * step),
+            );
+
+            // Some momentum keeps rivers from zigzagging down valleys, and
+            // carries them across small dips.
+            heading = (heading * 0.5 - gradient.normalize_or_zero())
+                .try_normalize()
+                .unwrap_or(heading);
+            if heading == Vec2::ZERO {
+                break;
+            }
+
+            let next = (at + heading * step).clamp(bounds.min, bounds.max);
+            if next == at {
+                break;
+            }
+            at = next;
+
+            // Only keep a point every so often, to keep carving cheap.
+            if path.last().unwrap().distance(at) >= params.river_width {
+                path.push(at);
+            }
+
+            // Rivers which keep going around in circles are stuck.
+            if path.len() > 8 && path[path.len() - 8].distance(at) < params.river_width * 2.0 {
+                break;
+            }
+        }
+
+        if *path.last().unwrap() != at {
+            path.push(at);
+        }
+
+        if !reached_sea {
+            self.add_lagoon(params, at);
+        }
+
+        self.waterways.push(Waterway::new(
+            WaterwayKind::River,
+            path,
+            params.river_width,
+            params.bank_width,
+        ));
+    }
+
+    /// Every carved waterway.
+    pub fn waterways(&self) -> &[Waterway] {
+        &self.waterways
+    }
+
+    /// The waterway whose bed a point is within, if any.
+    pub fn waterway_at(&self, at: Vec2) -> Option<&Waterway> {
+        self.waterways.iter().find(|waterway| waterway.contains(at))
+    }
+
+    /// Carves waterways into the height of the terrain at a point.
+    pub fn carve(&self, at: Vec2, height: f32) -> f32 {
+        let distance = self
+            .waterways
+            .iter()
+            .filter(|waterway| waterway.bounds.contains(at))
+            .map(|waterway| waterway.distance(at) - waterway.width)
+            .fold(f32::INFINITY, f32::min);
+
+        if distance >= self.bank_width {
+            return height;
+        }
+
+        let alpha = (distance / self.bank_width.max(f32::EPSILON)).max(0.0);
+        height.min(lerp(-self.depth, height, smootherstep(0.0, 1.0, alpha)))
+    }
+
+    /// Points along the center lines of every waterway, spaced roughly
+    /// `spacing` apart, where props or ships can be spawned.
+    pub fn spawn_points(&self, spacing: f32) -> impl Iterator<Item = Vec2> + '_ {
+        self.waterways.iter().flat_map(move |waterway| {
+            let mut last: Option<Vec2> = None;
+
+            waterway.path.iter().copied().filter(move |&at| {
+                let keep = last.is_none_or(|last| last.distance(at) >= spacing);
+                if keep {
+                    last = Some(at);
+                }
+                keep
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    /// A round island, sloping down from a peak at the origin.
+    fn island(at: Vec2) -> f32 {
+        0.8 - at.length() / 40.0
+    }
+
+    #[test]
+    fn rivers_reach_the_sea() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let params = HydrologyParams {
+            rivers: 1,
+            lagoons: 1,
+            ..default()
+        };
+        let bounds = Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(40.0));
+        let hydrology = Hydrology::trace(&params, bounds, island, &mut rng);
+
+        let rivers = hydrology
+            .waterways()
+            .iter()
+            .filter(|waterway| waterway.kind == WaterwayKind::River)
+            .collect::<Vec<_>>();
+        let lagoon = hydrology
+            .waterways()
+            .iter()
+            .find(|waterway| waterway.kind == WaterwayKind::Lagoon)
+            .expect("a lagoon should be hollowed out");
+
+        // One river, and the lagoon's outlet.
+        assert_eq!(rivers.len(), 2);
+
+        for river in rivers {
+            let mouth = *river.path.last().unwrap();
+            assert!(island(mouth) < 0.0, "river ends on land at {mouth}");
+
+            // Rivers are carved below sea level along their whole length.
+            for &at in &river.path {
+                assert!(hydrology.carve(at, island(at)) < 0.0);
+                assert!(hydrology.waterway_at(at).is_some());
+            }
+        }
+
+        let center = lagoon.path[0];
+        assert!(island(center) > 0.0);
+        assert_eq!(hydrology.carve(center, island(center)), -params.depth);
+        assert!(hydrology.spawn_points(2.0).count() > 2);
+    }
+}
@@ -17,13 +17,30 @@
 // Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
 // permitted by applicable law.  See the CNPL for details.
 
+pub mod biome;
 pub mod buffer;
+pub mod cave;
+pub mod chunk;
 pub mod collision;
+pub mod density;
+pub mod erosion;
 pub mod generator;
+pub mod hydrology;
+pub mod navigation;
 pub mod noise;
+pub mod primitive;
+pub mod sdf;
 
 pub mod prelude {
+    pub use super::biome::{BiomeLayer, BiomeParams, TerrainBiome};
+    pub use super::cave::CaveLayer;
+    pub use super::chunk::{
+        TerrainChunk, TerrainChunkIndex, TerrainChunkSource, TerrainStreamer, TerrainStreaming,
+        TerrainStreamingPlugin,
+    };
     pub use super::collision::TerrainCollisionPlugin;
+    pub use super::density::DensityField;
+    pub use super::erosion::ErosionParams;
     pub use super::generator::{
         BaseModulationParams, BaseModulationParamsBuilder, BaseModulationParamsBuilderError,
         CenterPoint, DefaultTerrainGenerator, DefaultTerrainGeneratorBuilder,
@@ -32,5 +49,13 @@ pub mod prelude {
         TerrainGeneratorBuilderError, TerrainModulator, TerrainModulatorAlgorithm,
         default_modulator,
     };
-    pub use super::noise::{FractalNoise, NoiseLattice};
+    pub use super::hydrology::{Hydrology, HydrologyParams, Waterway, WaterwayKind};
+    pub use super::navigation::{NavCell, NavGrid};
+    pub use super::noise::{FractalNoise, NoiseLattice, NoiseVolume};
+    pub use super::primitive::{
+        CarveNode, ClampNode, ConePrimitive, ConformNode, ConstantPrimitive, IslandShapePrimitive,
+        MaskNode, NoisePrimitive, PlateauPrimitive, SmoothUnionNode, TerrainNode, TerrainPrimitive,
+        UnionNode,
+    };
+    pub use super::sdf::TerrainSdf;
 }
@@ -0,0 +1,442 @@
+//! # Navigation grid
+//!
+//! Surface vessels need to know where they can sail. The [NavGrid] resource
+//! is a 2D grid over the XZ plane, storing the water depth of every cell, and
+//! whether it is obstructed by something other than terrain, such as a prop.
+//!
+//! Paths are found with A*, for a given draft, i.e. the least depth a ship
+//! needs not to run aground; see [NavGrid::find_path].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use bevy::prelude::*;
+
+use super::buffer::TerrainBuffer;
+
+/// A cell of the [NavGrid].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NavCell {
+    /// Water depth at the cell's center; zero or less on land.
+    pub depth: f32,
+
+    /// Whether something other than terrain is in the way.
+    pub obstructed: bool,
+}
+
+/// The navigation grid; see the module documentation.
+///
+/// Positions outside the grid are open sea.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct NavGrid {
+    /// World space XZ position of the center of the first cell.
+    origin: Vec2,
+
+    /// Spacing between cells, in world space units.
+    cell_size: f32,
+
+    /// Number of cells along X and Z.
+    size: UVec2,
+
+    /// Every cell, row by row.
+    cells: Vec<NavCell>,
+}
+
+/// An entry of the A* open set.
+#[derive(PartialEq)]
+struct OpenCell {
+    /// Estimated length of the path through this cell.
+    estimate: f32,
+    cell: UVec2,
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.estimate
+            .total_cmp(&other.estimate)
+            .then_with(|| self.cell.to_array().cmp(&other.cell.to_array()))
+    }
+}
+
+impl NavGrid {
+    /// Generates a navigation grid from a terrain.
+    ///
+    /// * `terrain` - The terrain.
+    /// * `terrain_offset` - Translation of the terrain entity.
+    /// * `water_level` - Y intercept of the water level.
+    /// * `cell_size` - Spacing between cells.
+    pub fn from_terrain(
+        terrain: &TerrainBuffer,
+        terrain_offset: Vec3,
+        water_level: f32,
+        cell_size: f32,
+    ) -> Self {
+        let size = Vec2::new(terrain.get_real_width(), terrain.get_real_height());
+
+        Self::from_heightfield(
+            Rect::from_center_size(terrain_offset.xz(), size),
+            |at| {
+                let local = at - terrain_offset.xz();
+                terrain.get_height_at(local.x, local.y) + terrain_offset.y
+            },
+            water_level,
+            cell_size,
+        )
+    }
+
+    /// Generates a navigation grid from any height field, such as a
+    /// [super::chunk::TerrainChunkIndex].
+    ///
+    /// * `area` - The XZ area the grid covers.
+    /// * `height_at` - Terrain height at a world space XZ position.
+    ///
+    /// The other parameters are the same as in [Self::from_terrain].
+    pub fn from_heightfield(
+        area: Rect,
+        height_at: impl Fn(Vec2) -> f32,
+        water_level: f32,
+        cell_size: f32,
+    ) -> Self {
+        let size = (area.size() / cell_size).ceil().as_uvec2().max(UVec2::ONE);
+        let origin = area.min + Vec2::splat(cell_size * 0.5);
+
+        let cells = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| UVec2::new(x, y)))
+            .map(|cell| NavCell {
+                depth: water_level - height_at(origin + cell.as_vec2() * cell_size),
+                obstructed: false,
+            })
+            .collect();
+
+        Self {
+            origin,
+            cell_size,
+            size,
+            cells,
+        }
+    }
+
+    /// Spacing between cells.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Number of cells along X and Z.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The XZ area the grid covers.
+    pub fn bounds(&self) -> Rect {
+        let half_cell = Vec2::splat(self.cell_size * 0.5);
+
+        Rect::from_corners(
+            self.origin - half_cell,
+            self.origin + self.size.as_vec2() * self.cell_size - half_cell,
+        )
+    }
+
+    /// The cell containing a world space XZ position, if within the grid.
+    pub fn cell_at(&self, at: Vec2) -> Option<UVec2> {
+        let mapped = ((at - self.origin) / self.cell_size).round();
+
+        (mapped.cmpge(Vec2::ZERO).all() && mapped.cmplt(self.size.as_vec2()).all())
+            .then(|| mapped.as_uvec2())
+    }
+
+    /// World space XZ position of the center of a cell.
+    pub fn cell_center(&self, cell: UVec2) -> Vec2 {
+        self.origin + cell.as_vec2() * self.cell_size
+    }
+
+    /// A cell of the grid.
+    ///
+    /// # Panics
+    ///
+    /// If the cell is outside the grid.
+    pub fn cell(&self, cell: UVec2) -> &NavCell {
+        &self.cells[(cell.y * self.size.x + cell.x) as usize]
+    }
+
+    /// Water depth at a world space XZ position.
+    ///
+    /// Infinite outside the grid.
+    pub fn depth_at(&self, at: Vec2) -> f32 {
+        self.cell_at(at)
+            .map_or(f32::INFINITY, |cell| self.cell(cell).depth)
+    }
+
+    /// Marks a cell as obstructed, or not.
+    pub fn set_obstructed(&mut self, cell: UVec2, obstructed: bool) {
+        self.cells[(cell.y * self.size.x + cell.x) as usize].obstructed = obstructed;
+    }
+
+    /// Marks every cell whose center is within a circle as obstructed.
+    pub fn obstruct_circle(&mut self, center: Vec2, radius: f32) {
+        let min = ((center - radius - self.origin) / self.cell_size)
+            .floor()
+            .max(Vec2::ZERO)
+            .as_uvec2();
+        let max = ((center + radius - self.origin) / self.cell_size)
+            .ceil()
+            .max(Vec2::ZERO)
+            .as_uvec2()
+            .min(self.size - 1);
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = UVec2::new(x, y);
+                if self.cell_center(cell).distance(center) <= radius {
+                    self.set_obstructed(cell, true);
+                }
+            }
+        }
+    }
+
+    /// Whether a ship with the given draft can be in a cell.
+    pub fn is_navigable(&self, cell: UVec2, draft: f32) -> bool {
+        let cell = self.cell(cell);
+        !cell.obstructed && cell.depth >= draft
+    }
+
+    /// Whether a ship with the given draft can be at a world space XZ
+    /// position.
+    pub fn is_navigable_at(&self, at: Vec2, draft: f32) -> bool {
+        self.cell_at(at)
+            .is_none_or(|cell| self.is_navigable(cell, draft))
+    }
+
+    /// Whether a ship with the given draft can sail straight between two
+    /// world space XZ positions.
+    pub fn is_clear(&self, from: Vec2, to: Vec2, draft: f32) -> bool {
+        let steps = (from.distance(to) / (self.cell_size * 0.5)).ceil() as usize;
+
+        (0..=steps).all(|step| {
+            let alpha = if steps == 0 {
+                0.0
+            } else {
+                step as f32 / steps as f32
+            };
+            self.is_navigable_at(from.lerp(to, alpha), draft)
+        })
+    }
+
+    /// Navigable neighbours of a cell, and the distance to them.
+    ///
+    /// Diagonal moves may not cut corners.
+    fn neighbours(&self, cell: UVec2, draft: f32) -> impl Iterator<Item = (UVec2, f32)> + '_ {
+        let open = move |offset: IVec2| {
+            let neighbour = cell.as_ivec2() + offset;
+            (neighbour.cmpge(IVec2::ZERO).all() && neighbour.cmplt(self.size.as_ivec2()).all())
+                .then(|| neighbour.as_uvec2())
+                .filter(|&neighbour| self.is_navigable(neighbour, draft))
+        };
+
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y)))
+            .filter(|offset| *offset != IVec2::ZERO)
+            .filter_map(move |offset| {
+                let neighbour = open(offset)?;
+
+                if offset.x != 0 && offset.y != 0 {
+                    open(IVec2::new(offset.x, 0))?;
+                    open(IVec2::new(0, offset.y))?;
+                }
+
+                Some((neighbour, offset.as_vec2().length()))
+            })
+    }
+
+    /// Finds a path for a ship with the given draft, between two world space
+    /// XZ positions.
+    ///
+    /// The path starts at `from` and ends at `to`, with straight legs in
+    /// between; it is [None] if there is no such path, including if either
+    /// end is not navigable. Positions outside the grid are linked to the
+    /// nearest edge cell.
+    pub fn find_path(&self, from: Vec2, to: Vec2, draft: f32) -> Option<Vec<Vec2>> {
+        if !self.is_navigable_at(from, draft) || !self.is_navigable_at(to, draft) {
+            return None;
+        }
+
+        if self.is_clear(from, to, draft) {
+            return Some(vec![from, to]);
+        }
+
+        let nearest_cell = |at: Vec2| {
+            ((at - self.origin) / self.cell_size)
+                .round()
+                .clamp(Vec2::ZERO, (self.size - 1).as_vec2())
+                .as_uvec2()
+        };
+        let start = nearest_cell(from);
+        let goal = nearest_cell(to);
+
+        if !self.is_navigable(start, draft) || !self.is_navigable(goal, draft) {
+            return None;
+        }
+
+        // Octile distance, in cells.
+        let heuristic = |cell: UVec2| {
+            let delta = cell.as_vec2() - goal.as_vec2();
+            let delta = delta.abs();
+            delta.max_element() + (std::f32::consts::SQRT_2 - 1.0) * delta.min_element()
+        };
+
+        let index = |cell: UVec2| (cell.y * self.size.x + cell.x) as usize;
+        let mut costs = vec![f32::INFINITY; self.cells.len()];
+        let mut came_from = vec![None; self.cells.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[index(start)] = 0.0;
+        open.push(Reverse(OpenCell {
+            estimate: heuristic(start),
+            cell: start,
+        }));
+
+        while let Some(Reverse(OpenCell { cell, estimate })) = open.pop() {
+            if cell == goal {
+                break;
+            }
+
+            let cost = costs[index(cell)];
+            if estimate > cost + heuristic(cell) {
+                // Already reached more cheaply.
+                continue;
+            }
+
+            for (neighbour, distance) in self.neighbours(cell, draft) {
+                let new_cost = cost + distance;
+                if new_cost < costs[index(neighbour)] {
+                    costs[index(neighbour)] = new_cost;
+                    came_from[index(neighbour)] = Some(cell);
+                    open.push(Reverse(OpenCell {
+                        estimate: new_cost + heuristic(neighbour),
+                        cell: neighbour,
+                    }));
+                }
+            }
+        }
+
+        if goal != start && came_from[index(goal)].is_none() {
+            return None;
+        }
+
+        let mut cells = vec![goal];
+        while let Some(previous) = came_from[index(*cells.last().unwrap())] {
+            cells.push(previous);
+        }
+
+        let mut waypoints = vec![to];
+        waypoints.extend(cells.into_iter().map(|cell| self.cell_center(cell)));
+        waypoints.push(from);
+        waypoints.reverse();
+
+        Some(self.smooth_path(waypoints, draft))
+    }
+
+    /// Removes waypoints which can be skipped by sailing straight.
+    fn smooth_path(&self, waypoints: Vec<Vec2>, draft: f32) -> Vec<Vec2> {
+        let mut smoothed = vec![waypoints[0]];
+        let mut current = 0;
+
+        while current < waypoints.len() - 1 {
+            // The farthest waypoint in sight; the next one always is.
+            let next = (current + 1..waypoints.len())
+                .rev()
+                .find(|&next| self.is_clear(waypoints[current], waypoints[next], draft))
+                .unwrap_or(current + 1);
+
+            smoothed.push(waypoints[next]);
+            current = next;
+        }
+
+        smoothed
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+
+    /// A channel between two bays, cut through a wall of land at x = 0 which
+    /// is shallow (depth 1) but for a deep gap (depth 5) at z = 40.
+    fn strait() -> NavGrid {
+        NavGrid::from_heightfield(
+            Rect::new(-50.0, -50.0, 50.0, 50.0),
+            |at| {
+                if at.x.abs() > 6.0 {
+                    -10.0
+                } else if (at.y - 40.0).abs() < 6.0 {
+                    -5.0
+                } else if (at.y + 40.0).abs() < 6.0 {
+                    -1.0
+                } else {
+                    5.0
+                }
+            },
+            0.0,
+            2.0,
+        )
+    }
+
+    #[test]
+    fn paths_account_for_draft() {
+        let grid = strait();
+        let from = Vec2::new(-30.0, 0.0);
+        let to = Vec2::new(30.0, 0.0);
+
+        assert!(!grid.is_clear(from, to, 0.5));
+
+        // Shallow boats take the nearest gap; deep ships need the deep one.
+        let shallow = grid.find_path(from, to, 0.5).unwrap();
+        let deep = grid.find_path(from, to, 3.0).unwrap();
+
+        assert_eq!((shallow[0], *shallow.last().unwrap()), (from, to));
+        assert_eq!((deep[0], *deep.last().unwrap()), (from, to));
+
+        for path in [&shallow, &deep] {
+            for leg in path.windows(2) {
+                assert!(grid.is_clear(leg[0], leg[1], 0.5));
+            }
+        }
+        for leg in deep.windows(2) {
+            assert!(grid.is_clear(leg[0], leg[1], 3.0));
+        }
+
+        assert!(deep.iter().any(|at| at.y > 30.0));
+        assert!(grid.find_path(from, to, 6.0).is_none());
+
+        // Land is never navigable.
+        assert!(grid.find_path(from, Vec2::new(0.0, 0.0), 0.5).is_none());
+
+        let mut blocked = strait();
+        blocked.obstruct_circle(Vec2::new(0.0, 40.0), 8.0);
+        assert!(blocked.find_path(from, to, 3.0).is_none());
+    }
+}
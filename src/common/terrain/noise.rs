@@ -7,6 +7,11 @@
 //! unit-length 'gradient vector'. Stacking noise lattices with varying
 //! resolutions and strengths, we can get fractal noise, here implemented
 //! as [FractalNoise] which enforces power-of-two 'octaves'.
+//!
+//! For volumetric terrain, such as caves, there is also [NoiseVolume], a
+//! three-dimensional fractal noise which, rather than storing a lattice,
+//! hashes the gradient vector of every lattice point from a seed; it is thus
+//! unbounded.
 
 // Written by:
 // * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
@@ -23,6 +28,7 @@
 
 use std::{fmt::Debug, num::NonZeroU16};
 
+use bevy::math::{IVec3, Vec3};
 use rand::Rng;
 
 use crate::common::math::smootherstep;
@@ -398,6 +404,99 @@ impl FractalNoise {
     }
 }
 
+/// Unbounded three-dimensional fractal Perlin noise.
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseVolume {
+    seed: u64,
+    num_octaves: NonZeroU16,
+}
+
+/// Gradient vectors of [NoiseVolume] lattice points, which are picked from
+/// the edges of a cube, as in improved Perlin noise.
+const VOLUME_GRADIENTS: [Vec3; 12] = [
+    Vec3::new(1.0, 1.0, 0.0),
+    Vec3::new(-1.0, 1.0, 0.0),
+    Vec3::new(1.0, -1.0, 0.0),
+    Vec3::new(-1.0, -1.0, 0.0),
+    Vec3::new(1.0, 0.0, 1.0),
+    Vec3::new(-1.0, 0.0, 1.0),
+    Vec3::new(1.0, 0.0, -1.0),
+    Vec3::new(-1.0, 0.0, -1.0),
+    Vec3::new(0.0, 1.0, 1.0),
+    Vec3::new(0.0, -1.0, 1.0),
+    Vec3::new(0.0, 1.0, -1.0),
+    Vec3::new(0.0, -1.0, -1.0),
+];
+
+impl NoiseVolume {
+    /// Creates a volumetric noise generator from a seed.
+    pub fn new(seed: u64, num_octaves: NonZeroU16) -> Self {
+        Self { seed, num_octaves }
+    }
+
+    /// Creates a volumetric noise generator with a random seed.
+    pub fn random(num_octaves: NonZeroU16, rng: &mut impl Rng) -> Self {
+        Self::new(rng.random(), num_octaves)
+    }
+
+    /// The gradient vector of a lattice point of an octave.
+    fn gradient(&self, octave: u16, point: IVec3) -> Vec3 {
+        // SplitMix64 finalizer over the seed and coordinates.
+        let mut hash = self.seed
+            ^ (point.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (point.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (point.z as u64).wrapping_mul(0x1656_67B1_9E37_79F9)
+            ^ (octave as u64).wrapping_mul(0x27D4_EB2F_1656_67C5);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        VOLUME_GRADIENTS[(hash % VOLUME_GRADIENTS.len() as u64) as usize]
+    }
+
+    /// Get the noise value of a single octave.
+    fn get_octave_influence_at(&self, octave: u16, pos: Vec3) -> f32 {
+        let base = pos.floor();
+        let inner = pos - base;
+        let base = base.as_ivec3();
+
+        let corner = |x: i32, y: i32, z: i32| {
+            let offset = IVec3::new(x, y, z);
+            self.gradient(octave, base + offset)
+                .dot(inner - offset.as_vec3())
+        };
+
+        let near = smootherstep(
+            smootherstep(corner(0, 0, 0), corner(1, 0, 0), inner.x),
+            smootherstep(corner(0, 1, 0), corner(1, 1, 0), inner.x),
+            inner.y,
+        );
+        let far = smootherstep(
+            smootherstep(corner(0, 0, 1), corner(1, 0, 1), inner.x),
+            smootherstep(corner(0, 1, 1), corner(1, 1, 1), inner.x),
+            inner.y,
+        );
+
+        smootherstep(near, far, inner.z)
+    }
+
+    /// Get the noise value at a point.
+    ///
+    /// Values are roughly between -1.0 and 1.0.
+    pub fn get_influence_at(&self, pos: Vec3) -> f32 {
+        let (sum, norm_denom) =
+            (0..u16::from(self.num_octaves)).fold((0.0, 0.0), |(sum, norm_denom), octave| {
+                let scale = 2.0_f32.powi(octave.into());
+                (
+                    sum + self.get_octave_influence_at(octave, pos * scale) / scale,
+                    norm_denom + 1.0 / scale,
+                )
+            });
+
+        sum / norm_denom
+    }
+}
+
 pub mod tests {
     #[test]
     fn quad_lookup() {
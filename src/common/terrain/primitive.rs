@@ -0,0 +1,410 @@
+//! # Terrain primitives
+//!
+//! Terrain is described as a tree of [TerrainNode]s. Leaves are primitive
+//! shapes, such as noise, cones and plateaus; inner nodes combine their
+//! children, e.g. taking their union, or conforming one to the shape of
+//! another.
+//!
+//! Trees evaluate to a height at any XZ position, and to a signed distance
+//! at any point in space. The default terrain generator is one such tree;
+//! see [super::generator::DefaultTerrainGenerator::to_primitive_tree].
+//!
+//! Heights are in terrain generator units, where 0.0 is sea level; see
+//! [super::generator::TerrainGenerator].
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use bevy::prelude::*;
+use enum_dispatch::enum_dispatch;
+
+use crate::common::{
+    math::{lerp, smootherstep},
+    scratch::ScratchBuffer,
+};
+
+use super::{generator::CenterPoint, hydrology::Hydrology, noise::FractalNoise};
+
+/// Something which evaluates to terrain.
+#[enum_dispatch]
+pub trait TerrainPrimitive {
+    /// The height of the terrain at an XZ position, reusing a scratch buffer.
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32;
+
+    /// The height of the terrain at an XZ position.
+    ///
+    /// When sampling many heights, prefer [Self::height_at_with].
+    fn height_at(&self, at: Vec2) -> f32 {
+        self.height_at_with(at, &mut ScratchBuffer::new())
+    }
+
+    /// The signed distance from a point to the terrain; negative if below
+    /// it.
+    ///
+    /// By default, this is the vertical distance to the surface, which is
+    /// never shorter than the true distance; it is therefore safe to use
+    /// for sphere tracing.
+    fn sdf_with(&self, pos: Vec3, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        pos.y - self.height_at_with(pos.xz(), scratch)
+    }
+}
+
+/// A node of a terrain primitive tree.
+#[derive(Clone)]
+#[enum_dispatch(TerrainPrimitive)]
+pub enum TerrainNode {
+    Constant(ConstantPrimitive),
+    Noise(NoisePrimitive),
+    Cone(ConePrimitive),
+    Plateau(PlateauPrimitive),
+    IslandShape(IslandShapePrimitive),
+    Union(UnionNode),
+    SmoothUnion(SmoothUnionNode),
+    Mask(MaskNode),
+    Conform(ConformNode),
+    Clamp(ClampNode),
+    Carve(CarveNode),
+}
+
+/// The same height everywhere.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantPrimitive {
+    pub height: f32,
+}
+
+impl TerrainPrimitive for ConstantPrimitive {
+    fn height_at_with(&self, _at: Vec2, _scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.height
+    }
+}
+
+/// Fractal Perlin noise.
+#[derive(Clone)]
+pub struct NoisePrimitive {
+    /// The noise generator.
+    pub noise: FractalNoise,
+
+    /// The size of each noise tile.
+    pub resolution: f32,
+}
+
+impl TerrainPrimitive for NoisePrimitive {
+    fn height_at_with(&self, at: Vec2, _scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.noise
+            .get_influence_at(at.x / self.resolution, at.y / self.resolution)
+    }
+}
+
+/// A cone, sloping down linearly from its peak.
+///
+/// Keeps sloping down past its radius, below zero.
+#[derive(Clone, Copy, Debug)]
+pub struct ConePrimitive {
+    /// Where the peak is.
+    pub center: Vec2,
+
+    /// Distance from the peak at which the cone's height reaches zero.
+    pub radius: f32,
+
+    /// Height of the peak.
+    pub height: f32,
+}
+
+impl TerrainPrimitive for ConePrimitive {
+    fn height_at_with(&self, at: Vec2, _scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.height * (1.0 - at.distance(self.center) / self.radius)
+    }
+}
+
+/// A flat-topped hill, with smooth slopes down to zero.
+#[derive(Clone, Copy, Debug)]
+pub struct PlateauPrimitive {
+    /// The center of the plateau.
+    pub center: Vec2,
+
+    /// Radius of the flat top.
+    pub radius: f32,
+
+    /// Width of the slopes around the top.
+    pub falloff: f32,
+
+    /// Height of the top.
+    pub height: f32,
+}
+
+impl TerrainPrimitive for PlateauPrimitive {
+    fn height_at_with(&self, at: Vec2, _scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let past_top = (at.distance(self.center) - self.radius).max(0.0);
+        let alpha = (past_top / self.falloff.max(f32::EPSILON)).min(1.0);
+        smootherstep(self.height, 0.0, alpha)
+    }
+}
+
+/// The shape of an island around center points.
+///
+/// 1.0 within the minimum shore distance of the nearest center point,
+/// sloping down to 0.0 at the maximum shore distance, and below past it.
+#[derive(Clone, Debug)]
+pub struct IslandShapePrimitive {
+    /// The center points of the island.
+    pub center_points: Vec<CenterPoint>,
+
+    /// See [super::generator::ModulationParams::min_shore_distance].
+    pub min_shore_distance: f32,
+
+    /// See [super::generator::ModulationParams::max_shore_distance].
+    pub max_shore_distance: f32,
+
+    /// If set, distances to center points are combined with a smooth
+    /// minimum of this roughness; see
+    /// [super::generator::SmoothminDistance].
+    pub roughness: Option<f32>,
+}
+
+impl TerrainPrimitive for IslandShapePrimitive {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let distances = scratch.fill(self.center_points.iter().map(|point| point.distance(at)));
+
+        let distance = match self.roughness {
+            Some(roughness) => 0.0_f32.max(
+                (-distances
+                    .iter()
+                    .map(|&v| (-roughness as f64 * v as f64).exp())
+                    .sum::<f64>()
+                    .ln()
+                    / roughness as f64) as f32,
+            ),
+            None => distances.iter().copied().fold(f32::INFINITY, f32::min),
+        };
+
+        if distance < self.min_shore_distance {
+            1.0
+        } else {
+            1.0 - (distance - self.min_shore_distance)
+                / (self.max_shore_distance - self.min_shore_distance)
+        }
+    }
+}
+
+/// The highest of its children.
+#[derive(Clone, Default)]
+pub struct UnionNode {
+    pub children: Vec<TerrainNode>,
+}
+
+impl TerrainPrimitive for UnionNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.children
+            .iter()
+            .map(|child| child.height_at_with(at, scratch))
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+}
+
+/// The highest of its children, blended smoothly where they meet.
+#[derive(Clone, Default)]
+pub struct SmoothUnionNode {
+    pub children: Vec<TerrainNode>,
+
+    /// Height difference below which children are blended.
+    pub smoothness: f32,
+}
+
+impl TerrainPrimitive for SmoothUnionNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let smoothness = self.smoothness.max(f32::EPSILON);
+
+        self.children
+            .iter()
+            .map(|child| child.height_at_with(at, scratch))
+            .reduce(|a, b| {
+                // Polynomial smooth maximum.
+                let h = (0.5 + 0.5 * (a - b) / smoothness).clamp(0.0, 1.0);
+                lerp(b, a, h) + smoothness * h * (1.0 - h)
+            })
+            .unwrap_or(f32::NEG_INFINITY)
+    }
+}
+
+/// Keeps a node only where a mask is positive.
+///
+/// The height is interpolated from `floor` up to the masked node, by the
+/// mask's height clamped between 0.0 and 1.0.
+#[derive(Clone)]
+pub struct MaskNode {
+    pub node: Box<TerrainNode>,
+    pub mask: Box<TerrainNode>,
+
+    /// The height where the mask is zero.
+    pub floor: f32,
+}
+
+impl TerrainPrimitive for MaskNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let mask = self.mask.height_at_with(at, scratch).clamp(0.0, 1.0);
+
+        if mask <= 0.0 {
+            return self.floor;
+        }
+
+        lerp(self.floor, self.node.height_at_with(at, scratch), mask)
+    }
+}
+
+/// Conforms a node to the shape of another.
+#[derive(Clone)]
+pub struct ConformNode {
+    pub node: Box<TerrainNode>,
+    pub shape: Box<TerrainNode>,
+
+    /// How much to conform; 0.0 leaves the node as is, 1.0 replaces it with
+    /// the shape, and higher values overshoot.
+    pub amount: f32,
+
+    /// The interpolator used to conform.
+    pub interpolator: fn(f32, f32, f32) -> f32,
+}
+
+impl TerrainPrimitive for ConformNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        let height = self.node.height_at_with(at, scratch);
+        let shape = self.shape.height_at_with(at, scratch);
+        (self.interpolator)(height, shape, self.amount)
+    }
+}
+
+/// Clamps the height of a node.
+#[derive(Clone)]
+pub struct ClampNode {
+    pub node: Box<TerrainNode>,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl TerrainPrimitive for ClampNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.node
+            .height_at_with(at, scratch)
+            .clamp(self.min, self.max)
+    }
+}
+
+/// Carves waterways into a node; see [Hydrology].
+#[derive(Clone)]
+pub struct CarveNode {
+    pub node: Box<TerrainNode>,
+    pub hydrology: Hydrology,
+}
+
+impl TerrainPrimitive for CarveNode {
+    fn height_at_with(&self, at: Vec2, scratch: &mut ScratchBuffer<f32>) -> f32 {
+        self.hydrology
+            .carve(at, self.node.height_at_with(at, scratch))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::common::terrain::{
+        generator::{CenterPoint, ModulationParams, TerrainGeneratorBuilder, default_modulator},
+        hydrology::HydrologyParams,
+    };
+
+    use super::*;
+
+    #[test]
+    fn combinators() {
+        let cone = TerrainNode::from(ConePrimitive {
+            center: Vec2::ZERO,
+            radius: 10.0,
+            height: 1.0,
+        });
+        let plateau = TerrainNode::from(PlateauPrimitive {
+            center: Vec2::X * 20.0,
+            radius: 4.0,
+            falloff: 4.0,
+            height: 0.5,
+        });
+
+        assert_eq!(cone.height_at(Vec2::Y * 5.0), 0.5);
+        assert_eq!(plateau.height_at(Vec2::X * 22.0), 0.5);
+        assert_eq!(plateau.height_at(Vec2::X * 30.0), 0.0);
+
+        let union = TerrainNode::from(UnionNode {
+            children: vec![cone.clone(), plateau.clone()],
+        });
+        assert_eq!(union.height_at(Vec2::ZERO), 1.0);
+        assert_eq!(union.height_at(Vec2::X * 20.0), 0.5);
+
+        // Smooth unions are never lower than plain ones.
+        let smooth = TerrainNode::from(SmoothUnionNode {
+            children: vec![cone.clone(), plateau.clone()],
+            smoothness: 0.3,
+        });
+        for x in 0..30 {
+            let at = Vec2::X * x as f32;
+            assert!(smooth.height_at(at) >= union.height_at(at) - 1e-6);
+        }
+
+        let masked = TerrainNode::from(MaskNode {
+            node: Box::new(TerrainNode::from(ConstantPrimitive { height: 1.0 })),
+            mask: Box::new(cone),
+            floor: -1.0,
+        });
+        assert_eq!(masked.height_at(Vec2::ZERO), 1.0);
+        assert_eq!(masked.height_at(Vec2::X * 15.0), -1.0);
+
+        let mut scratch = ScratchBuffer::new();
+        assert_eq!(masked.sdf_with(Vec3::new(0.0, 3.0, 0.0), &mut scratch), 2.0);
+    }
+
+    #[test]
+    fn default_generator_as_tree() {
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let generator = TerrainGeneratorBuilder::default()
+            .noise(FractalNoise::random_octaves(
+                4.0,
+                4.0,
+                3.try_into().unwrap(),
+                &mut rng,
+            ))
+            .modulator(default_modulator())
+            .modulation_params(ModulationParams {
+                min_shore_distance: 40.0,
+                max_shore_distance: 140.0,
+                ..Default::default()
+            })
+            .center_points(vec![
+                CenterPoint::new(Vec2::splat(300.0), 1.0),
+                CenterPoint::new(Vec2::new(450.0, 350.0), 0.7),
+            ])
+            .build()
+            .unwrap()
+            .with_hydrology(&HydrologyParams::default(), &mut rng);
+
+        let tree = generator.to_primitive_tree();
+
+        for idx in 0..400 {
+            let at = Vec2::new((idx % 20) as f32, (idx / 20) as f32) * 39.0;
+            let expected = generator.get_height_at(at);
+            let height = tree.height_at(at);
+            assert!(
+                (height - expected).abs() < 1e-5,
+                "{at}: {height} != {expected}"
+            );
+        }
+    }
+}
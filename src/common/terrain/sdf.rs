@@ -0,0 +1,188 @@
+//! # Terrain signed distance field
+//!
+//! The heightmap alone only tells how far a point is above or below the
+//! ground *vertically*. Near cliffs, that is far from the actual distance to
+//! the terrain, and pushing objects out vertically launches them up the
+//! cliff face.
+//!
+//! [TerrainSdf] samples the true distance to the terrain surface from a
+//! [TerrainBuffer], by measuring the distance to the nearby triangles of the
+//! terrain mesh. Its gradient, [TerrainSdf::normal],
+//! points away from the nearest surface, be it ground or cliff face.
+//!
+//! Distances are only exact within a band around the query point (see
+//! [TerrainSdf::with_band]); farther from the surface, they may be an
+//! overestimate, which is fine for collision. Whether a point is underground
+//! is decided by the heightmap.
+//!
+//! If the terrain has caves (see [super::cave]), points within them are in
+//! the open, even though they are below the heightmap.
+
+// Written by:
+// * Gustavo Ramos Rehermann <rehermann6046@gmail.com>
+//
+// (c)2025 GameCircular. Under the Cooperative Non-Violent Public License.
+//
+// Loot & Roam is non-violent software: you can use, redistribute,
+// and/or modify it under the terms of the CNPLv6+ as found
+// in the LICENSE file in the source code root directory or
+// at <https://git.pixie.town/thufie/CNPL>.
+//
+// Loot & Roam comes with ABSOLUTELY NO WARRANTY, to the extent
+// permitted by applicable law.  See the CNPL for details.
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::common::math::closest::closest_point_on_triangle;
+
+use super::buffer::TerrainBuffer;
+
+/// A signed distance field of a terrain, in its local space.
+///
+/// Negative distances are underground.
+pub struct TerrainSdf<'a> {
+    buffer: &'a TerrainBuffer,
+
+    /// Distance around the query point within which the surface is checked.
+    band: f32,
+}
+
+impl<'a> TerrainSdf<'a> {
+    /// The SDF of a terrain, with a band of twice its vertex spacing.
+    pub fn new(buffer: &'a TerrainBuffer) -> Self {
+        Self {
+            buffer,
+            band: buffer.get_resolution() * 2.0,
+        }
+    }
+
+    /// Sets the distance around query points within which the surface is
+    /// checked.
+    ///
+    /// Larger bands are more accurate farther from the surface, but the cost
+    /// of each query grows with the square of the band.
+    pub fn with_band(self, band: f32) -> Self {
+        Self { band, ..self }
+    }
+
+    /// Range of quads within the band, along one axis, by the index of
+    /// their first vertex.
+    fn quad_range(&self, pos: f32, real_size: f32, vertices: usize) -> Range<usize> {
+        let resolution = self.buffer.get_resolution();
+        let mapped = (pos + real_size / 2.0) / resolution;
+        let band = self.band / resolution;
+
+        let start = (mapped - band).floor().max(0.0) as usize;
+        let end = ((mapped + band).ceil().max(0.0) as usize).min(vertices - 1);
+
+        start..end
+    }
+
+    /// Signed distance from a point to the terrain surface.
+    pub fn sdf(&self, pos: Vec3) -> f32 {
+        let buffer = self.buffer;
+        let vertical = pos.y - buffer.get_height_at(pos.x, pos.z);
+
+        let range_x = self.quad_range(pos.x, buffer.get_real_width(), buffer.get_vertex_width());
+        let range_z = self.quad_range(pos.z, buffer.get_real_height(), buffer.get_vertex_height());
+
+        let mut distance_squared = vertical * vertical;
+
+        // Check the triangles of every quad within the band, split the same
+        // way as in the terrain mesh.
+        for value_y in range_z {
+            for value_x in range_x.clone() {
+                let nw = buffer.get_vertex_position(value_x, value_y);
+                let ne = buffer.get_vertex_position(value_x + 1, value_y);
+                let sw = buffer.get_vertex_position(value_x, value_y + 1);
+                let se = buffer.get_vertex_position(value_x + 1, value_y + 1);
+
+                for [a, b, c] in [[ne, nw, sw], [ne, sw, se]] {
+                    let closest = closest_point_on_triangle(a, b, c, pos);
+                    distance_squared = distance_squared.min(closest.distance_squared(pos));
+                }
+            }
+        }
+
+        let distance = distance_squared.sqrt() * vertical.signum();
+
+        match buffer.caves() {
+            Some(caves) if caves.contains(pos) => distance.max(caves.density_at(pos)),
+            _ => distance,
+        }
+    }
+
+    /// Direction away from the nearest terrain surface, at a point.
+    ///
+    /// Computed from the gradient of the [Self::sdf]; falls back to the
+    /// heightmap normal where the gradient vanishes.
+    pub fn normal(&self, pos: Vec3) -> Vec3 {
+        let epsilon = self.buffer.get_resolution() * 0.25;
+
+        let gradient = Vec3::new(
+            self.sdf(pos + Vec3::X * epsilon) - self.sdf(pos - Vec3::X * epsilon),
+            self.sdf(pos + Vec3::Y * epsilon) - self.sdf(pos - Vec3::Y * epsilon),
+            self.sdf(pos + Vec3::Z * epsilon) - self.sdf(pos - Vec3::Z * epsilon),
+        );
+
+        gradient
+            .try_normalize()
+            .unwrap_or_else(|| self.buffer.get_normal_at(pos.x, pos.z))
+    }
+}
+
+impl TerrainBuffer {
+    /// The signed distance field of this terrain.
+    pub fn sdf_field(&self) -> TerrainSdf<'_> {
+        TerrainSdf::new(self)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use bevy::prelude::*;
+
+    use crate::common::terrain::buffer::TerrainBuffer;
+
+    /// A terrain with a cliff: low ground on the west half, and a plateau
+    /// 10 units higher on the east half.
+    fn cliff() -> TerrainBuffer {
+        let size = 16;
+        let values = (0..size * size)
+            .map(|idx| if idx % size >= size / 2 { 10.0 } else { 0.0 })
+            .collect();
+
+        TerrainBuffer::from_heights(size, size, 1.0, values)
+    }
+
+    #[test]
+    fn flat_ground() {
+        let terrain = cliff();
+        let sdf = terrain.sdf_field();
+
+        // Far from the cliff, the distance is vertical.
+        let pos = Vec3::new(-5.0, 1.5, 0.0);
+        assert!((sdf.sdf(pos) - 1.5).abs() < 1e-4);
+        assert!(sdf.sdf(pos - Vec3::Y * 2.0) < 0.0);
+        assert!(sdf.normal(pos).distance(Vec3::Y) < 1e-3);
+    }
+
+    #[test]
+    fn cliff_face() {
+        let terrain = cliff();
+        let sdf = terrain.sdf_field();
+
+        // The cliff face slopes up from x = -1.0 (the last low vertex) to
+        // x = 0.0 (the first high one). Halfway up, right next to it, the
+        // cliff is much closer than the ground below.
+        let pos = Vec3::new(-1.5, 5.0, 0.0);
+        let distance = sdf.sdf(pos);
+        assert!(distance > 0.9 && distance < 1.1, "{distance}");
+
+        // The normal points away from the cliff, not up.
+        let normal = sdf.normal(pos);
+        assert!(normal.x < -0.5, "{normal}");
+    }
+}